@@ -5,7 +5,6 @@ use plm::config::PluginSource;
 use plm::traits::{Plugin, PluginMetadata, PluginError, InstallOptions, VersionInfo, PluginStatus};
 use async_trait::async_trait;
 use std::collections::HashMap;
-use std::sync::Arc;
 use std::error::Error;
 
 /// 自定义工具插件
@@ -27,7 +26,7 @@ impl CustomToolPlugin {
             supported_platforms: vec!["linux".to_string(), "macos".to_string(), "windows".to_string()],
             tags: vec!["development".to_string(), "custom".to_string()],
             dependencies: vec![],
-            min_plm_version: Some("0.1.0".to_string()),
+            min_plm_version: Some("0.0.1".to_string()),
         };
 
         Self {
@@ -181,8 +180,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // 4. 注册自定义插件
     println!("\n🔌 注册自定义插件...");
-    let custom_plugin = Arc::new(CustomToolPlugin::new());
-    manager.register_plugin_for_test("custom-tool".to_string(), custom_plugin).await?;
+    let custom_plugin = Box::new(CustomToolPlugin::new());
+    manager.register_plugin("custom-tool".to_string(), custom_plugin).await?;
 
     // 5. 初始化
     println!("\n🔧 初始化插件管理器...");
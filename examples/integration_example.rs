@@ -34,7 +34,7 @@ impl CustomToolPlugin {
             ],
             tags: vec!["development".to_string(), "custom".to_string()],
             dependencies: vec![],
-            min_plm_version: Some("0.1.0".to_string()),
+            min_plm_version: Some("0.0.1".to_string()),
         };
 
         Self {
@@ -0,0 +1,76 @@
+//! PLM 插件过程宏
+//!
+//! 插件作者通过 `plm::prelude::plm_plugin` 使用，不需要直接依赖本 crate。
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Item};
+
+/// 为插件生成入口函数
+///
+/// 标注在返回插件实例的无参函数上，或标注在实现了 `Default` 的插件类型上：
+///
+/// ```ignore
+/// use plm::prelude::*;
+///
+/// #[plm_plugin]
+/// fn create() -> MyPlugin {
+///     MyPlugin::new()
+/// }
+/// ```
+///
+/// 生成 `pub fn plm_plugin_create() -> Box<dyn Plugin>`，宿主把插件 crate 作为依赖编译进来后
+/// 调用它创建实例并注册。`Plugin` 没有稳定的 ABI，不支持以动态库加载（见 `plm::sdk`）。
+#[proc_macro_attribute]
+pub fn plm_plugin(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "#[plm_plugin] does not take arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let item = parse_macro_input!(input as Item);
+    let constructor = match &item {
+        Item::Fn(function) if function.sig.inputs.is_empty() => {
+            let name = &function.sig.ident;
+            quote! { #name() }
+        }
+        Item::Fn(function) => {
+            return syn::Error::new_spanned(
+                &function.sig.inputs,
+                "#[plm_plugin] functions must not take arguments",
+            )
+            .to_compile_error()
+            .into();
+        }
+        Item::Struct(item) if item.generics.params.is_empty() => {
+            let name = &item.ident;
+            quote! { <#name as ::core::default::Default>::default() }
+        }
+        Item::Enum(item) if item.generics.params.is_empty() => {
+            let name = &item.ident;
+            quote! { <#name as ::core::default::Default>::default() }
+        }
+        other => {
+            return syn::Error::new_spanned(
+                other,
+                "#[plm_plugin] expects a constructor function or a non-generic plugin type",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    quote! {
+        #item
+
+        /// 创建插件实例，由 `#[plm_plugin]` 生成
+        pub fn plm_plugin_create() -> ::std::boxed::Box<dyn ::plm::traits::Plugin> {
+            ::std::boxed::Box::new(#constructor)
+        }
+    }
+    .into()
+}
@@ -0,0 +1,119 @@
+//! PLM 审计日志
+//!
+//! 以 JSON Lines 格式追加记录需要事后排查的事件（目前是 CLI 崩溃），
+//! 默认位于 `~/.plm/audit.log`，可用 `PLM_AUDIT_LOG` 指定路径。
+//! 写入是同步的，可以在 panic hook 中使用；`plm bug-report` 会附带最近的记录。
+
+use crate::traits::PluginError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 指定审计日志路径的环境变量
+pub const AUDIT_LOG_ENV: &str = "PLM_AUDIT_LOG";
+
+/// 审计事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+    /// CLI 发生 panic
+    Crash,
+}
+
+/// 一条审计记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Utc>,
+    pub kind: AuditKind,
+    /// 执行的命令行
+    pub command: String,
+    pub message: String,
+    /// 源码位置（`file:line:column`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    pub version: String,
+}
+
+impl AuditRecord {
+    /// 创建崩溃记录，命令行取自当前进程参数
+    pub fn crash(message: &str, location: Option<String>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            kind: AuditKind::Crash,
+            command: std::env::args().collect::<Vec<_>>().join(" "),
+            message: message.to_string(),
+            location,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// 审计日志路径
+pub fn audit_log_path() -> Option<PathBuf> {
+    match std::env::var_os(AUDIT_LOG_ENV) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => dirs::home_dir().map(|home| home.join(".plm").join("audit.log")),
+    }
+}
+
+/// 追加一条记录
+pub fn append(path: &Path, record: &AuditRecord) -> Result<(), PluginError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| PluginError::IoError(format!("创建审计日志目录失败: {}", e)))?;
+    }
+    let line = serde_json::to_string(record)
+        .map_err(|e| PluginError::IoError(format!("序列化审计记录失败: {}", e)))?;
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| PluginError::IoError(format!("写入审计日志失败: {}", e)))
+}
+
+/// 读取最近的 `limit` 条记录，跳过无法解析的行，文件不存在时返回空列表
+pub fn read_recent(path: &Path, limit: usize) -> Vec<AuditRecord> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let records: Vec<AuditRecord> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let skip = records.len().saturating_sub(limit);
+    records.into_iter().skip(skip).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_read_recent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("audit.log");
+        assert!(read_recent(&path, 10).is_empty());
+
+        for message in ["first", "second", "third"] {
+            append(
+                &path,
+                &AuditRecord::crash(message, Some("src/main.rs:1:1".to_string())),
+            )
+            .unwrap();
+        }
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "not json"))
+            .unwrap();
+
+        let recent = read_recent(&path, 2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].message, "second");
+        assert_eq!(recent[1].message, "third");
+        assert_eq!(recent[1].kind, AuditKind::Crash);
+        assert_eq!(recent[1].location.as_deref(), Some("src/main.rs:1:1"));
+    }
+}
@@ -0,0 +1,324 @@
+//! 插件源认证
+//!
+//! 插件源的 `auth` 指定访问私有注册表、制品仓库（GitLab、Artifactory、Nexus 等）的方式：
+//!
+//! ```json
+//! "sources": [
+//!   { "type": "registry", "url": "https://plm.example.com", "auth": { "type": "bearer", "token": "env:PLM_TOKEN" } },
+//!   { "type": "static", "url": "https://artifactory.example.com/plm", "auth": { "type": "basic", "username": "ci", "password": "keychain:artifactory" } },
+//!   { "type": "static", "url": "https://gitlab.example.com/api/v4/projects/42/packages/generic/plm", "auth": { "type": "header", "name": "PRIVATE-TOKEN", "value": "${GITLAB_TOKEN}" } },
+//!   { "type": "registry", "url": "git+ssh://git@git.example.com/plm-index.git", "auth": { "type": "ssh", "key": "~/.ssh/plm_deploy" } },
+//!   { "type": "static", "url": "https://files.example.com/plm", "auth": { "type": "netrc" } }
+//! ]
+//! ```
+//!
+//! 令牌、密码和请求头的值可以写成 `keychain:<name>`、`env:<VAR>` 等引用（见 `crate::providers`）。
+//! 插件源的凭据按以下顺序确定，先找到的生效：
+//!
+//! 1. 插件源的 `auth`
+//! 2. 插件源的 `token`，作为 Bearer 令牌
+//! 3. netrc 文件中插件源主机的条目（`NETRC` 环境变量指定的文件，默认 `~/.netrc`，Windows 上为 `~/_netrc`）
+//!
+//! 凭据只发送到插件源地址及其镜像地址下的请求。Git 仓库注册表通过环境变量把凭据交给 git：
+//! SSH 密钥设置 `GIT_SSH_COMMAND`，其他方式设置 HTTPS 请求的 `http.extraHeader`。
+
+use crate::config::{resolve_path, PluginSource};
+use crate::providers::{Secret, SettingResolver};
+use crate::traits::PluginError;
+use base64::Engine;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 插件源配置中的认证方式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SourceAuth {
+    /// `Authorization: Bearer <token>`
+    Bearer { token: String },
+    /// HTTP Basic 认证
+    Basic { username: String, password: String },
+    /// 自定义请求头，例如 GitLab 的 `PRIVATE-TOKEN`
+    Header { name: String, value: String },
+    /// Git 仓库注册表使用的 SSH 私钥，支持 `~` 和环境变量
+    Ssh { key: String },
+    /// 从 netrc 文件读取插件源主机的用户名和密码，`path` 默认为 `~/.netrc`
+    Netrc {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
+}
+
+/// 解析得到的凭据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credentials {
+    Bearer(Secret),
+    Basic { username: String, password: Secret },
+    Header { name: String, value: Secret },
+    SshKey(PathBuf),
+}
+
+impl Credentials {
+    /// 携带凭据的 HTTP 请求头，SSH 密钥返回 None
+    pub fn header(&self) -> Option<(String, String)> {
+        match self {
+            Credentials::Bearer(token) => Some((
+                "Authorization".to_string(),
+                format!("Bearer {}", token.expose()),
+            )),
+            Credentials::Basic { username, password } => {
+                let encoded = base64::engine::general_purpose::STANDARD.encode(format!(
+                    "{}:{}",
+                    username,
+                    password.expose()
+                ));
+                Some(("Authorization".to_string(), format!("Basic {}", encoded)))
+            }
+            Credentials::Header { name, value } => Some((name.clone(), value.expose().to_string())),
+            Credentials::SshKey(_) => None,
+        }
+    }
+
+    /// 为 HTTP 请求加上凭据
+    pub fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.header() {
+            Some((name, value)) => request.header(name, value),
+            None => request,
+        }
+    }
+
+    /// 传给 git 的环境变量
+    pub fn git_env(&self) -> Vec<(String, String)> {
+        if let Credentials::SshKey(key) = self {
+            return vec![(
+                "GIT_SSH_COMMAND".to_string(),
+                format!("ssh -i '{}' -o IdentitiesOnly=yes", key.display()),
+            )];
+        }
+        match self.header() {
+            Some((name, value)) => vec![
+                ("GIT_CONFIG_COUNT".to_string(), "1".to_string()),
+                (
+                    "GIT_CONFIG_KEY_0".to_string(),
+                    "http.extraHeader".to_string(),
+                ),
+                (
+                    "GIT_CONFIG_VALUE_0".to_string(),
+                    format!("{}: {}", name, value),
+                ),
+            ],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// 按模块文档中的顺序确定插件源的凭据
+pub async fn resolve(source: &PluginSource) -> Result<Option<Credentials>, PluginError> {
+    if let Some(auth) = &source.auth {
+        return resolve_auth(auth, &source.url).await.map(Some);
+    }
+    if let Some(token) = source.resolve_token().await? {
+        return Ok(Some(Credentials::Bearer(token)));
+    }
+    let Some(host) = host_of(&source.url) else {
+        return Ok(None);
+    };
+    Ok(read_netrc(None)
+        .await
+        .and_then(|content| netrc_credentials(&content, &host))
+        .map(|(username, password)| Credentials::Basic {
+            username,
+            password: Secret::from(password),
+        }))
+}
+
+async fn resolve_auth(auth: &SourceAuth, url: &str) -> Result<Credentials, PluginError> {
+    Ok(match auth {
+        SourceAuth::Bearer { token } => Credentials::Bearer(secret(token).await?),
+        SourceAuth::Basic { username, password } => Credentials::Basic {
+            username: username.clone(),
+            password: secret(password).await?,
+        },
+        SourceAuth::Header { name, value } => Credentials::Header {
+            name: name.clone(),
+            value: secret(value).await?,
+        },
+        SourceAuth::Ssh { key } => Credentials::SshKey(resolve_path(key, None)),
+        SourceAuth::Netrc { path } => {
+            let host = host_of(url).ok_or_else(|| {
+                PluginError::ConfigError(format!("无法从插件源地址 {} 确定 netrc 主机", url))
+            })?;
+            let content = read_netrc(path.as_deref()).await.ok_or_else(|| {
+                PluginError::ConfigError(format!(
+                    "插件源 {} 使用 netrc 认证，但无法读取 netrc 文件",
+                    url
+                ))
+            })?;
+            let (username, password) = netrc_credentials(&content, &host).ok_or_else(|| {
+                PluginError::ConfigError(format!("netrc 文件中没有主机 {} 的条目", host))
+            })?;
+            Credentials::Basic {
+                username,
+                password: Secret::from(password),
+            }
+        }
+    })
+}
+
+/// 值为提供者引用时读取实际值，否则原样使用
+async fn secret(value: &str) -> Result<Secret, PluginError> {
+    Ok(SettingResolver::new()
+        .resolve(&serde_json::Value::String(value.to_string()))
+        .await?
+        .unwrap_or_else(|| Secret::from(value.to_string())))
+}
+
+/// HTTP(S) 地址的主机名，`git+` 前缀的地址去掉前缀后解析
+fn host_of(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url.strip_prefix("git+").unwrap_or(url)).ok()?;
+    matches!(parsed.scheme(), "http" | "https")
+        .then(|| parsed.host_str().map(str::to_string))
+        .flatten()
+}
+
+async fn read_netrc(path: Option<&str>) -> Option<String> {
+    let path = match path {
+        Some(path) => resolve_path(path, None),
+        None => match std::env::var("NETRC") {
+            Ok(path) if !path.is_empty() => PathBuf::from(path),
+            _ => dirs::home_dir()?.join(if cfg!(windows) { "_netrc" } else { ".netrc" }),
+        },
+    };
+    tokio::fs::read_to_string(path).await.ok()
+}
+
+/// 在 netrc 内容中查找主机的用户名和密码，没有匹配的 `machine` 时使用 `default` 条目
+pub fn netrc_credentials(content: &str, host: &str) -> Option<(String, String)> {
+    let mut tokens = content.split_whitespace();
+    let mut fallback = None;
+    // 当前条目：是否匹配主机、是否为 default、用户名、密码
+    let mut entry: Option<(bool, bool, String, String)> = None;
+    let mut finish = |entry: Option<(bool, bool, String, String)>| -> Option<(String, String)> {
+        let (matched, is_default, login, password) = entry?;
+        if matched {
+            return Some((login, password));
+        }
+        if is_default && fallback.is_none() {
+            fallback = Some((login, password));
+        }
+        None
+    };
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" => {
+                if let Some(found) = finish(entry.take()) {
+                    return Some(found);
+                }
+                let machine = tokens.next().unwrap_or_default();
+                entry = Some((machine == host, false, String::new(), String::new()));
+            }
+            "default" => {
+                if let Some(found) = finish(entry.take()) {
+                    return Some(found);
+                }
+                entry = Some((false, true, String::new(), String::new()));
+            }
+            "login" => {
+                let login = tokens.next().unwrap_or_default().to_string();
+                if let Some(entry) = entry.as_mut() {
+                    entry.2 = login;
+                }
+            }
+            "password" => {
+                let password = tokens.next().unwrap_or_default().to_string();
+                if let Some(entry) = entry.as_mut() {
+                    entry.3 = password;
+                }
+            }
+            "account" => {
+                tokens.next();
+            }
+            // 宏定义之后的内容不再解析
+            "macdef" => break,
+            _ => {}
+        }
+    }
+    finish(entry.take()).or(fallback)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_netrc_credentials() {
+        let content = "machine other.example.com login a password b\n\
+                       machine files.example.com\n  login ci\n  password s3cret\n\
+                       default login anonymous password guest\n";
+        assert_eq!(
+            netrc_credentials(content, "files.example.com"),
+            Some(("ci".to_string(), "s3cret".to_string()))
+        );
+        assert_eq!(
+            netrc_credentials(content, "unknown.example.com"),
+            Some(("anonymous".to_string(), "guest".to_string()))
+        );
+        assert_eq!(netrc_credentials("machine a login b password c", "x"), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolution_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let netrc = dir.path().join("netrc");
+        std::fs::write(&netrc, "machine files.example.com login ci password s3cret").unwrap();
+
+        let mut source = PluginSource::static_registry("https://files.example.com/plm");
+        source.token = Some("token".to_string());
+        assert_eq!(
+            resolve(&source).await.unwrap(),
+            Some(Credentials::Bearer(Secret::from("token".to_string())))
+        );
+
+        source.auth = Some(SourceAuth::Netrc {
+            path: Some(netrc.to_string_lossy().into_owned()),
+        });
+        let credentials = resolve(&source).await.unwrap().unwrap();
+        assert_eq!(
+            credentials.header(),
+            Some((
+                "Authorization".to_string(),
+                format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD.encode("ci:s3cret")
+                )
+            ))
+        );
+
+        source.auth = Some(SourceAuth::Header {
+            name: "PRIVATE-TOKEN".to_string(),
+            value: "glpat".to_string(),
+        });
+        let credentials = resolve(&source).await.unwrap().unwrap();
+        assert_eq!(
+            credentials.git_env()[2].1,
+            "PRIVATE-TOKEN: glpat".to_string()
+        );
+
+        source.auth = Some(SourceAuth::Ssh {
+            key: "/keys/deploy".to_string(),
+        });
+        let credentials = resolve(&source).await.unwrap().unwrap();
+        assert_eq!(credentials.header(), None);
+        assert_eq!(credentials.git_env()[0].0, "GIT_SSH_COMMAND");
+
+        let raw = serde_json::json!({ "type": "basic", "username": "ci", "password": "env:PW" });
+        assert_eq!(
+            serde_json::from_value::<SourceAuth>(raw).unwrap(),
+            SourceAuth::Basic {
+                username: "ci".to_string(),
+                password: "env:PW".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! PLM 配置文件备份
+//!
+//! 每次保存时把现有文件轮换为 `<file>.bak.1..N`（`.bak.1` 最新），
+//! 并先写入临时文件再重命名，避免写入中途崩溃留下损坏的文件。
+
+use crate::temp::TempFileGuard;
+use crate::traits::PluginError;
+use std::path::{Path, PathBuf};
+
+/// 默认保留的备份数量
+pub const DEFAULT_BACKUP_COUNT: usize = 3;
+
+/// 原始配置中 `global_settings.config_backups` 指定的备份数量，至少保留 1 份
+pub fn backup_count(raw: &serde_json::Value) -> usize {
+    raw.pointer("/global_settings/config_backups")
+        .and_then(serde_json::Value::as_u64)
+        .map_or(DEFAULT_BACKUP_COUNT, |n| n as usize)
+        .max(1)
+}
+
+/// 第 `index` 个备份的路径（从 1 开始）
+pub fn backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".bak.{}", index));
+    PathBuf::from(name)
+}
+
+/// 列出已存在的备份，按从新到旧排列
+pub async fn list_backups(path: &Path) -> Vec<PathBuf> {
+    let mut backups = Vec::new();
+    let mut index = 1;
+    loop {
+        let candidate = backup_path(path, index);
+        if tokio::fs::metadata(&candidate).await.is_err() {
+            break;
+        }
+        backups.push(candidate);
+        index += 1;
+    }
+    backups
+}
+
+/// 轮换备份：`.bak.N-1` -> `.bak.N`，……，当前文件 -> `.bak.1`
+///
+/// `keep` 为 0 时不做备份
+pub async fn rotate_backups(path: &Path, keep: usize) -> Result<(), PluginError> {
+    if keep == 0 || tokio::fs::metadata(path).await.is_err() {
+        return Ok(());
+    }
+
+    for index in (1..keep).rev() {
+        let from = backup_path(path, index);
+        if tokio::fs::metadata(&from).await.is_ok() {
+            tokio::fs::rename(&from, backup_path(path, index + 1))
+                .await
+                .map_err(|e| {
+                    PluginError::IoError(format!("轮换备份 {} 失败: {}", from.display(), e))
+                })?;
+        }
+    }
+
+    tokio::fs::copy(path, backup_path(path, 1))
+        .await
+        .map_err(|e| PluginError::IoError(format!("备份 {} 失败: {}", path.display(), e)))?;
+    Ok(())
+}
+
+/// 备份现有文件后写入新内容
+pub async fn write_with_backups(
+    path: &Path,
+    content: &str,
+    keep: usize,
+) -> Result<(), PluginError> {
+    rotate_backups(path, keep).await?;
+
+    let tmp = TempFileGuard::sibling(path, "tmp");
+    tokio::fs::write(tmp.path(), content)
+        .await
+        .map_err(|e| PluginError::IoError(format!("写入 {} 失败: {}", tmp.path().display(), e)))?;
+    tmp.persist(path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rotation_keeps_n_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plm.json");
+
+        for version in 1..=4 {
+            write_with_backups(&path, &format!("v{}", version), 2)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "v4");
+        assert_eq!(
+            std::fs::read_to_string(backup_path(&path, 1)).unwrap(),
+            "v3"
+        );
+        assert_eq!(
+            std::fs::read_to_string(backup_path(&path, 2)).unwrap(),
+            "v2"
+        );
+        assert_eq!(list_backups(&path).await.len(), 2);
+    }
+
+    #[test]
+    fn test_backup_count() {
+        assert_eq!(backup_count(&serde_json::json!({})), DEFAULT_BACKUP_COUNT);
+        let raw = serde_json::json!({ "global_settings": { "config_backups": 0 } });
+        assert_eq!(backup_count(&raw), 1);
+        let raw = serde_json::json!({ "global_settings": { "config_backups": 5 } });
+        assert_eq!(backup_count(&raw), 5);
+    }
+}
@@ -0,0 +1,25 @@
+//! `cargo plm`：在 Cargo 工作区中运行 plm，默认使用工作区根目录下的 plm.json
+
+use plm::cargo_subcommand;
+use std::ffi::OsString;
+use std::process::Command;
+
+fn main() {
+    let args = cargo_subcommand::strip_subcommand_name(std::env::args_os().skip(1).collect());
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo"));
+    let config = std::env::current_dir()
+        .ok()
+        .and_then(|dir| cargo_subcommand::workspace_config(&cargo, &dir));
+
+    let program = cargo_subcommand::plm_program();
+    let status = Command::new(&program)
+        .args(cargo_subcommand::forward_args(args, config.as_deref()))
+        .status();
+    match status {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            eprintln!("无法运行 {}: {}", program.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! PLM 构建脚本辅助
+//!
+//! 在 `build.rs` 中按 `plm.json` 固定的版本安装构建所需的工具（如 protoc、wasm-opt），
+//! 并通过 cargo 指令导出工具路径：
+//!
+//! ```no_run
+//! use plm::config::{GlobalSettings, PluginSource};
+//! use plm::PluginConfig;
+//!
+//! // build.rs
+//! fn main() -> Result<(), plm::PluginError> {
+//!     let protoc = plm::github::plugin(
+//!         &PluginConfig::new("protoc"),
+//!         &PluginSource::github("protocolbuffers/protobuf"),
+//!         &GlobalSettings::default(),
+//!     )?;
+//!     plm::build::BuildTools::new()
+//!         .plugin("protoc", protoc)
+//!         .run()?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! `plm.json` 已为工具配置插件源（`source` 或 `backend`）时使用配置创建的插件，
+//! 传入的插件只在配置没有提供实现时使用。安装期间与 plm 命令一样持有进程锁（见 `lock` 模块），
+//! 并行构建的多个 crate 依次安装。
+//!
+//! 每个工具的可执行文件路径以 `cargo:rustc-env=PLM_<TOOL>_BIN=<path>` 导出，
+//! 编译时可通过 `env!("PLM_PROTOC_BIN")` 读取；同时设置到构建脚本自身的环境变量中，
+//! 供之后的构建步骤（如 prost-build）使用。`plm.json` 变化时 cargo 会重新运行构建脚本。
+
+use crate::config::ProjectConfig;
+use crate::core::PluginManager;
+use crate::id::IntoPluginId;
+use crate::lock::ProcessLock;
+use crate::traits::{InstallOptions, Plugin, PluginError};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 构建脚本中需要的工具
+pub struct BuildTools {
+    config_path: PathBuf,
+    plugins: Vec<(String, Box<dyn Plugin>)>,
+}
+
+impl Default for BuildTools {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BuildTools {
+    /// 使用 crate 根目录（`CARGO_MANIFEST_DIR`）下的 `plm.json`
+    pub fn new() -> Self {
+        let root = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        Self {
+            config_path: root.join("plm.json"),
+            plugins: Vec::new(),
+        }
+    }
+
+    /// 指定配置文件路径
+    pub fn config(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = path.into();
+        self
+    }
+
+    /// 添加需要的工具及其插件实现，版本取自配置中同名插件的固定版本；
+    /// 配置已为该工具创建插件时不使用 `plugin`
+    pub fn plugin(mut self, name: &str, plugin: impl Plugin + 'static) -> Self {
+        self.plugins.push((name.to_string(), Box::new(plugin)));
+        self
+    }
+
+    /// 安装所有工具，返回工具名到可执行文件路径的映射
+    pub async fn ensure(self) -> Result<BTreeMap<String, PathBuf>, PluginError> {
+        let config_path = self.config_path.to_string_lossy().into_owned();
+        let config = ProjectConfig::load_from_file(&config_path).await?;
+
+        let mut tools = Vec::new();
+        for (name, _) in &self.plugins {
+            let version = config
+                .get_plugin(name)
+                .and_then(|p| p.get_version())
+                .ok_or_else(|| {
+                    PluginError::ConfigError(format!(
+                        "Build tool '{}' has no pinned version in {}",
+                        name, config_path
+                    ))
+                })?;
+            tools.push((name.clone(), version.to_string()));
+        }
+
+        let settings = config.global_settings.clone();
+        let mut manager = PluginManager::from_project_config(config).await?;
+        for (name, plugin) in self.plugins {
+            if manager.get_plugin(name.as_str()).await.is_err() {
+                manager.register_plugin(name.as_str(), plugin).await?;
+            }
+        }
+
+        let timeout = match settings.lock_timeout {
+            0 => Duration::MAX,
+            seconds => Duration::from_secs(seconds),
+        };
+        let _lock = ProcessLock::acquire(&settings.lock_path(), Some(timeout)).await?;
+        let options = InstallOptions::new().quiet();
+        let mut paths = BTreeMap::new();
+        for (name, version) in tools {
+            let id = name.as_str().into_plugin_id()?;
+            let installed = manager
+                .install_plugin(&id, Some(&version), &options)
+                .await?;
+            let binary = find_binary(&installed.path, id.name()).ok_or_else(|| {
+                PluginError::NotFound(format!(
+                    "{} executable in {}",
+                    name,
+                    installed.path.display()
+                ))
+            })?;
+            paths.insert(name, binary);
+        }
+        Ok(paths)
+    }
+
+    /// 在构建脚本中同步执行 `ensure` 并输出 cargo 指令
+    pub fn run(self) -> Result<BTreeMap<String, PathBuf>, PluginError> {
+        let config_path = self.config_path.clone();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| PluginError::IoError(format!("创建运行时失败: {}", e)))?;
+        let paths = runtime.block_on(self.ensure())?;
+
+        println!("cargo:rerun-if-changed={}", config_path.display());
+        for (name, path) in &paths {
+            let var = env_var_name(name);
+            println!("cargo:rerun-if-env-changed={}", var);
+            println!("cargo:rustc-env={}={}", var, path.display());
+            std::env::set_var(&var, path);
+        }
+        Ok(paths)
+    }
+}
+
+/// 工具路径的环境变量名，如 `wasm-opt` -> `PLM_WASM_OPT_BIN`
+pub fn env_var_name(tool: &str) -> String {
+    let name: String = tool
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("PLM_{}_BIN", name)
+}
+
+/// 在安装目录中查找工具的可执行文件：`<dir>/bin/<tool>`、`<dir>/<tool>`，
+/// 安装路径本身是文件时直接使用
+pub fn find_binary(install_path: &Path, tool: &str) -> Option<PathBuf> {
+    if install_path.is_file() {
+        return Some(install_path.to_path_buf());
+    }
+    let file_name = format!("{}{}", tool, std::env::consts::EXE_SUFFIX);
+    [
+        install_path.join("bin").join(&file_name),
+        install_path.join(&file_name),
+    ]
+    .into_iter()
+    .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_var_name() {
+        assert_eq!(env_var_name("protoc"), "PLM_PROTOC_BIN");
+        assert_eq!(env_var_name("wasm-opt"), "PLM_WASM_OPT_BIN");
+    }
+
+    #[test]
+    fn test_find_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(find_binary(dir.path(), "protoc"), None);
+
+        let file_name = format!("protoc{}", std::env::consts::EXE_SUFFIX);
+        let bin = dir.path().join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(bin.join(&file_name), "").unwrap();
+        assert_eq!(
+            find_binary(dir.path(), "protoc"),
+            Some(bin.join(&file_name))
+        );
+        assert_eq!(
+            find_binary(&bin.join(&file_name), "protoc"),
+            Some(bin.join(&file_name))
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! PLM 缓存管理
+//!
+//! 记录 `cache_dir` 中保存的文件、大小和最近访问时间，并按全局设置中的
+//! 大小上限（`cache_max_size_mb`）和保留天数（`cache_max_age_days`）淘汰文件。
+//!
+//! 最近访问时间记录在缓存目录下的索引文件中（文件系统的 atime 常被禁用），
+//! 没有记录的文件以修改时间为准。
+
+use crate::config::GlobalSettings;
+use crate::traits::PluginError;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 访问记录索引文件名
+const INDEX_FILE: &str = ".plm-cache-index.json";
+
+/// 缓存淘汰策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// 缓存总大小上限（字节）
+    pub max_size: Option<u64>,
+    /// 文件最长保留时间
+    pub max_age: Option<Duration>,
+}
+
+impl CachePolicy {
+    /// 根据全局设置创建淘汰策略
+    pub fn from_settings(settings: &GlobalSettings) -> Self {
+        Self {
+            max_size: settings.cache_max_size_mb.map(|mb| mb * 1024 * 1024),
+            max_age: settings
+                .cache_max_age_days
+                .map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+        }
+    }
+}
+
+/// 缓存中的单个文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    /// 相对缓存目录的路径
+    pub path: PathBuf,
+    pub size: u64,
+    pub last_access: DateTime<Utc>,
+}
+
+/// 缓存概况
+#[derive(Debug, Clone, Default)]
+pub struct CacheInfo {
+    /// 按最近访问时间排序（最久未访问的在前）
+    pub entries: Vec<CacheEntry>,
+    pub total_size: u64,
+}
+
+/// 清理结果
+#[derive(Debug, Clone, Default)]
+pub struct CleanReport {
+    pub removed: Vec<CacheEntry>,
+    pub freed_bytes: u64,
+}
+
+/// 缓存目录
+#[derive(Debug, Clone)]
+pub struct Cache {
+    root: PathBuf,
+}
+
+impl Cache {
+    /// 创建缓存管理器
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// 根据全局设置创建缓存管理器
+    pub fn from_settings(settings: &GlobalSettings) -> Self {
+        Self::new(settings.cache_path())
+    }
+
+    /// 缓存目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 记录缓存文件被访问
+    pub async fn record_access(&self, path: &Path) -> Result<(), PluginError> {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let mut index = self.load_index().await;
+        index.insert(relative.to_string_lossy().into_owned(), Utc::now());
+        self.save_index(&index).await
+    }
+
+    /// 扫描缓存目录
+    pub async fn info(&self) -> Result<CacheInfo, PluginError> {
+        let index = self.load_index().await;
+        let mut entries = Vec::new();
+        let mut pending = vec![self.root.clone()];
+
+        while let Some(dir) = pending.pop() {
+            let mut read_dir = match tokio::fs::read_dir(&dir).await {
+                Ok(read_dir) => read_dir,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    return Err(PluginError::IoError(format!(
+                        "读取缓存目录 {} 失败: {}",
+                        dir.display(),
+                        e
+                    )))
+                }
+            };
+
+            while let Some(entry) = read_dir
+                .next_entry()
+                .await
+                .map_err(|e| PluginError::IoError(format!("读取缓存目录失败: {}", e)))?
+            {
+                let path = entry.path();
+                let meta = entry.metadata().await.map_err(|e| {
+                    PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e))
+                })?;
+                if meta.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+
+                let relative = path.strip_prefix(&self.root).unwrap_or(&path).to_path_buf();
+                if relative == Path::new(INDEX_FILE) {
+                    continue;
+                }
+
+                let last_access = index
+                    .get(relative.to_string_lossy().as_ref())
+                    .copied()
+                    .or_else(|| meta.modified().ok().map(DateTime::<Utc>::from))
+                    .unwrap_or_else(Utc::now);
+                entries.push(CacheEntry {
+                    path: relative,
+                    size: meta.len(),
+                    last_access,
+                });
+            }
+        }
+
+        entries.sort_by_key(|e| e.last_access);
+        let total_size = entries.iter().map(|e| e.size).sum();
+        Ok(CacheInfo {
+            entries,
+            total_size,
+        })
+    }
+
+    /// 按策略淘汰缓存：先删除超过保留时间的文件，再按最久未访问的顺序删除直到低于大小上限
+    pub async fn clean(&self, policy: &CachePolicy) -> Result<CleanReport, PluginError> {
+        let info = self.info().await?;
+        let now = Utc::now();
+        let mut remaining = info.total_size;
+        let mut to_remove = Vec::new();
+
+        for entry in info.entries {
+            let expired = policy.max_age.is_some_and(|max_age| {
+                (now - entry.last_access)
+                    .to_std()
+                    .is_ok_and(|age| age > max_age)
+            });
+            let over_size = policy.max_size.is_some_and(|max| remaining > max);
+            if expired || over_size {
+                remaining -= entry.size;
+                to_remove.push(entry);
+            }
+        }
+
+        self.remove_entries(to_remove).await
+    }
+
+    /// 清空缓存
+    pub async fn clear(&self) -> Result<CleanReport, PluginError> {
+        let info = self.info().await?;
+        self.remove_entries(info.entries).await
+    }
+
+    async fn remove_entries(&self, entries: Vec<CacheEntry>) -> Result<CleanReport, PluginError> {
+        let mut report = CleanReport::default();
+        if entries.is_empty() {
+            return Ok(report);
+        }
+
+        let mut index = self.load_index().await;
+        for entry in entries {
+            let path = self.root.join(&entry.path);
+            tokio::fs::remove_file(&path).await.map_err(|e| {
+                PluginError::IoError(format!("删除 {} 失败: {}", path.display(), e))
+            })?;
+            index.remove(entry.path.to_string_lossy().as_ref());
+            report.freed_bytes += entry.size;
+            report.removed.push(entry);
+        }
+
+        self.save_index(&index).await?;
+        Ok(report)
+    }
+
+    /// 读取访问记录，索引损坏或不存在时视为空
+    async fn load_index(&self) -> HashMap<String, DateTime<Utc>> {
+        tokio::fs::read_to_string(self.root.join(INDEX_FILE))
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    async fn save_index(&self, index: &HashMap<String, DateTime<Utc>>) -> Result<(), PluginError> {
+        tokio::fs::create_dir_all(&self.root)
+            .await
+            .map_err(|e| PluginError::IoError(format!("创建缓存目录失败: {}", e)))?;
+        let content = serde_json::to_string_pretty(index)
+            .map_err(|e| PluginError::IoError(format!("序列化缓存索引失败: {}", e)))?;
+        tokio::fs::write(self.root.join(INDEX_FILE), content)
+            .await
+            .map_err(|e| PluginError::IoError(format!("写入缓存索引失败: {}", e)))
+    }
+}
+
+/// 以易读的单位格式化字节数
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_clean_by_size_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path());
+        tokio::fs::create_dir_all(dir.path().join("downloads"))
+            .await
+            .unwrap();
+
+        for name in ["old.tar.gz", "new.tar.gz"] {
+            let path = dir.path().join("downloads").join(name);
+            tokio::fs::write(&path, vec![0u8; 100]).await.unwrap();
+        }
+        cache
+            .record_access(&dir.path().join("downloads/old.tar.gz"))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        cache
+            .record_access(&dir.path().join("downloads/new.tar.gz"))
+            .await
+            .unwrap();
+
+        let info = cache.info().await.unwrap();
+        assert_eq!(info.entries.len(), 2);
+        assert_eq!(info.total_size, 200);
+
+        let policy = CachePolicy {
+            max_size: Some(150),
+            max_age: None,
+        };
+        let report = cache.clean(&policy).await.unwrap();
+        assert_eq!(report.freed_bytes, 100);
+        assert_eq!(report.removed[0].path, Path::new("downloads/old.tar.gz"));
+        assert!(dir.path().join("downloads/new.tar.gz").exists());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MB");
+    }
+}
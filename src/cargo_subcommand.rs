@@ -0,0 +1,129 @@
+//! `cargo plm` 子命令
+//!
+//! `cargo-plm` 可执行文件安装到 PATH 后，cargo 会把 `cargo plm <args>` 转交给它。
+//! 它只做转发：找到当前 Cargo 工作区的根目录，未指定 `--config` 时使用根目录下的
+//! `plm.json`，然后以相同的参数运行 `plm`，在工作区的任意子目录中都使用同一份配置。
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 工作区根目录下的默认配置文件名
+pub const CONFIG_FILE: &str = "plm.json";
+
+/// 去掉 cargo 调用子命令时插入的子命令名：`cargo plm sync` 会以 `cargo-plm plm sync` 运行
+pub fn strip_subcommand_name(mut args: Vec<OsString>) -> Vec<OsString> {
+    if args.first().is_some_and(|arg| arg == "plm") {
+        args.remove(0);
+    }
+    args
+}
+
+/// 参数中是否已经指定了配置文件（`-c`、`--config`）
+pub fn has_config_arg(args: &[OsString]) -> bool {
+    args.iter()
+        .map(|arg| arg.to_string_lossy())
+        .take_while(|arg| arg != "--")
+        .any(|arg| arg == "--config" || arg.starts_with("--config=") || arg.starts_with("-c"))
+}
+
+/// 转发给 `plm` 的参数：没有指定配置文件且找到了工作区配置时在前面加上 `--config`
+pub fn forward_args(args: Vec<OsString>, workspace_config: Option<&Path>) -> Vec<OsString> {
+    match workspace_config {
+        Some(config) if !has_config_arg(&args) => {
+            let mut forwarded = vec![OsString::from("--config"), config.as_os_str().to_owned()];
+            forwarded.extend(args);
+            forwarded
+        }
+        _ => args,
+    }
+}
+
+/// 用 `cargo locate-project --workspace` 查找 `dir` 所在 Cargo 工作区的根目录
+///
+/// `cargo` 为 cargo 可执行文件，cargo 运行子命令时通过 `CARGO` 环境变量提供。
+/// 不在工作区中或 cargo 无法运行时返回 None
+pub fn workspace_root(cargo: &OsStr, dir: &Path) -> Option<PathBuf> {
+    let output = Command::new(cargo)
+        .current_dir(dir)
+        .args(["locate-project", "--workspace", "--message-format", "plain"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let manifest = String::from_utf8(output.stdout).ok()?;
+    Path::new(manifest.trim()).parent().map(Path::to_path_buf)
+}
+
+/// 工作区根目录下存在的配置文件
+pub fn workspace_config(cargo: &OsStr, dir: &Path) -> Option<PathBuf> {
+    workspace_root(cargo, dir)
+        .map(|root| root.join(CONFIG_FILE))
+        .filter(|config| config.is_file())
+}
+
+/// 要运行的 `plm`：优先使用与 `cargo-plm` 安装在同一目录中的版本，否则从 PATH 中查找
+pub fn plm_program() -> PathBuf {
+    let file_name = format!("plm{}", std::env::consts::EXE_SUFFIX);
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(&file_name)))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<OsString> {
+        values.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_forward_args() {
+        let config = Path::new("/work/plm.json");
+        assert_eq!(
+            forward_args(strip_subcommand_name(args(&["plm", "sync"])), Some(config)),
+            args(&["--config", "/work/plm.json", "sync"])
+        );
+        // 用户指定的配置文件优先
+        for explicit in [
+            &["-c", "other.json", "sync"][..],
+            &["--config=other.json", "sync"],
+            &["sync", "--config", "other.json"],
+        ] {
+            assert_eq!(forward_args(args(explicit), Some(config)), args(explicit));
+        }
+        // `--` 之后是交给工具的参数
+        assert_eq!(
+            forward_args(args(&["exec", "node", "--", "-c"]), Some(config)),
+            args(&["--config", "/work/plm.json", "exec", "node", "--", "-c"])
+        );
+        assert_eq!(forward_args(args(&["sync"]), None), args(&["sync"]));
+        // 直接运行 `cargo-plm` 时没有子命令名
+        assert_eq!(strip_subcommand_name(args(&["sync"])), args(&["sync"]));
+    }
+
+    #[test]
+    fn test_workspace_config() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = []\nresolver = \"2\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("crates/app")).unwrap();
+        std::fs::write(dir.path().join(CONFIG_FILE), "{}").unwrap();
+
+        let cargo = std::env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo"));
+        let config = workspace_config(&cargo, &dir.path().join("crates/app")).unwrap();
+        assert_eq!(
+            config.canonicalize().unwrap(),
+            dir.path().join(CONFIG_FILE).canonicalize().unwrap()
+        );
+
+        // 工作区根目录下没有配置文件时不指定
+        std::fs::remove_file(dir.path().join(CONFIG_FILE)).unwrap();
+        assert!(workspace_config(&cargo, dir.path()).is_none());
+    }
+}
@@ -0,0 +1,196 @@
+//! PLM 工具链版本检查
+//!
+//! 对比项目中固定的插件版本与开发者当前环境中实际生效的工具版本，
+//! 供 pre-commit 钩子和 CI 使用。
+
+use crate::config::{PluginConfig, ProjectConfig};
+use crate::permissions;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 版本容差策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionTolerance {
+    /// 版本必须完全一致
+    #[default]
+    Exact,
+    /// 允许补丁版本不同
+    Patch,
+    /// 允许次版本不同（主版本必须一致）
+    Minor,
+}
+
+impl VersionTolerance {
+    /// 判断实际版本是否满足固定版本
+    pub fn matches(&self, pinned: &str, actual: &str) -> bool {
+        let pinned = parse_components(pinned);
+        let actual = parse_components(actual);
+        if pinned.is_empty() || actual.is_empty() {
+            return false;
+        }
+
+        let significant = match self {
+            VersionTolerance::Exact => 3,
+            VersionTolerance::Patch => 2,
+            VersionTolerance::Minor => 1,
+        };
+
+        // 固定版本只写了部分字段（如 "18"）时，只比较写出的部分
+        (0..significant.min(pinned.len())).all(|i| pinned[i] == actual.get(i).copied().unwrap_or(0))
+    }
+}
+
+/// 单个工具的检查状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// 版本符合要求
+    Ok,
+    /// 版本不符合要求
+    Mismatch,
+    /// 未找到工具
+    Missing,
+}
+
+/// 单个工具的检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCheck {
+    pub plugin: String,
+    pub pinned: String,
+    pub actual: Option<String>,
+    pub status: CheckStatus,
+}
+
+/// 工具链检查报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckReport {
+    pub tolerance: VersionTolerance,
+    pub tools: Vec<ToolCheck>,
+}
+
+impl CheckReport {
+    /// 是否所有工具都符合要求
+    pub fn is_ok(&self) -> bool {
+        self.tools.iter().all(|t| t.status == CheckStatus::Ok)
+    }
+
+    /// 获取不符合要求的工具
+    pub fn failures(&self) -> Vec<&ToolCheck> {
+        self.tools
+            .iter()
+            .filter(|t| t.status != CheckStatus::Ok)
+            .collect()
+    }
+}
+
+/// 检查项目中所有启用且固定了版本的插件
+pub async fn check_project(config: &ProjectConfig) -> CheckReport {
+    let tolerance = config.global_settings.version_tolerance;
+
+    let mut names: Vec<&String> = config.plugins.keys().collect();
+    names.sort();
+
+    let mut tools = Vec::new();
+    for name in names {
+        let plugin = &config.plugins[name];
+        let pinned = match (plugin.enabled, plugin.get_version()) {
+            (true, Some(version)) => version.to_string(),
+            _ => continue,
+        };
+
+        let actual = detect_active_version(plugin).await;
+        let status = match &actual {
+            None => CheckStatus::Missing,
+            Some(actual) if tolerance.matches(&pinned, actual) => CheckStatus::Ok,
+            Some(_) => CheckStatus::Mismatch,
+        };
+
+        tools.push(ToolCheck {
+            plugin: name.clone(),
+            pinned,
+            actual,
+            status,
+        });
+    }
+
+    CheckReport { tolerance, tools }
+}
+
+/// 探测当前 PATH 中生效的工具版本
+///
+/// 可通过插件设置 `binary` 指定可执行文件名，`version_args` 指定查询版本的参数
+/// （默认 `--version`）。
+pub async fn detect_active_version(plugin: &PluginConfig) -> Option<String> {
+    let binary = plugin
+        .get_setting("binary")
+        .and_then(|v| v.as_str())
+        .unwrap_or(plugin.name.as_str());
+    let args: Vec<&str> = plugin
+        .get_setting("version_args")
+        .and_then(|v| v.as_str())
+        .unwrap_or("--version")
+        .split_whitespace()
+        .collect();
+
+    permissions::check(|guard| guard.check_subprocess(binary)).ok()?;
+    let path = which::which(binary).ok()?;
+    let output = permissions::command(path).args(&args).output().await.ok()?;
+
+    // 部分工具（如 java）把版本输出到 stderr
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    extract_version(&text)
+}
+
+/// 从命令输出中提取第一个版本号
+pub fn extract_version(text: &str) -> Option<String> {
+    let re = Regex::new(r"\d+\.\d+(?:\.\d+)?").ok()?;
+    re.find(text).map(|m| m.as_str().to_string())
+}
+
+/// 把版本字符串拆分为数字字段，忽略前缀 `v` 和预发布后缀
+fn parse_components(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tolerance_matching() {
+        assert!(VersionTolerance::Exact.matches("18.17.0", "v18.17.0"));
+        assert!(!VersionTolerance::Exact.matches("18.17.0", "18.17.1"));
+        assert!(VersionTolerance::Patch.matches("18.17.0", "18.17.1"));
+        assert!(!VersionTolerance::Patch.matches("18.17.0", "18.18.0"));
+        assert!(VersionTolerance::Minor.matches("18.17.0", "18.20.3"));
+        assert!(!VersionTolerance::Minor.matches("18.17.0", "20.0.0"));
+        assert!(VersionTolerance::Exact.matches("18", "18.20.3"));
+    }
+
+    #[test]
+    fn test_extract_version() {
+        assert_eq!(
+            extract_version("go version go1.21.5 linux/amd64"),
+            Some("1.21.5".to_string())
+        );
+        assert_eq!(
+            extract_version("openjdk version \"17.0.2\" 2022-01-18"),
+            Some("17.0.2".to_string())
+        );
+        assert_eq!(extract_version("no version here"), None);
+    }
+}
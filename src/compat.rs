@@ -0,0 +1,74 @@
+//! 插件与 PLM 的版本兼容性检查
+//!
+//! 插件在 `PluginMetadata::min_plm_version` 中声明需要的最低 PLM 版本。注册插件时与当前
+//! PLM 版本比较，版本过低时拒绝加载并提示升级。设置 `global_settings.ignore_compat`、
+//! 环境变量 `PLM_IGNORE_COMPAT=1` 或传入 `--ignore-compat` 时只记录警告。
+
+use crate::traits::{PluginError, PluginMetadata};
+use crate::version::Version;
+
+/// 跳过兼容性检查的环境变量
+pub const IGNORE_COMPAT_ENV: &str = "PLM_IGNORE_COMPAT";
+
+/// 环境变量是否要求跳过兼容性检查
+pub fn ignore_compat_from_env() -> bool {
+    std::env::var(IGNORE_COMPAT_ENV).is_ok_and(|value| !matches!(value.as_str(), "" | "0"))
+}
+
+/// 检查插件能否在当前 PLM 版本中运行
+pub fn check_compatible(metadata: &PluginMetadata) -> Result<(), PluginError> {
+    check_compatible_with(metadata, crate::self_update::current_version())
+}
+
+/// 检查插件能否在指定的 PLM 版本中运行
+pub fn check_compatible_with(
+    metadata: &PluginMetadata,
+    plm_version: &str,
+) -> Result<(), PluginError> {
+    let Some(required) = &metadata.min_plm_version else {
+        return Ok(());
+    };
+    let minimum = Version::parse(required).map_err(|e| {
+        PluginError::ValidationError(format!(
+            "插件 {} 的 min_plm_version 无效: {}",
+            metadata.name, e
+        ))
+    })?;
+    if Version::parse(plm_version)? >= minimum {
+        return Ok(());
+    }
+    Err(PluginError::ValidationError(format!(
+        "插件 {} {} 需要 PLM {} 或更高版本，当前为 {}；请运行 `plm self-update` 升级 PLM，\
+         或使用 --ignore-compat 跳过检查",
+        metadata.name, metadata.version, required, plm_version
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_plm_version() {
+        let mut metadata = PluginMetadata {
+            name: "tool".to_string(),
+            version: "1.0.0".to_string(),
+            ..PluginMetadata::default()
+        };
+        assert!(check_compatible_with(&metadata, "0.1.0").is_ok());
+
+        metadata.min_plm_version = Some("0.2.0".to_string());
+        assert!(check_compatible_with(&metadata, "0.2.0").is_ok());
+        assert!(check_compatible_with(&metadata, "1.0.0").is_ok());
+        match check_compatible_with(&metadata, "0.1.5") {
+            Err(PluginError::ValidationError(message)) => {
+                assert!(message.contains("PLM 0.2.0"));
+                assert!(message.contains("plm self-update"));
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+
+        metadata.min_plm_version = Some("not-a-version".to_string());
+        assert!(check_compatible_with(&metadata, "1.0.0").is_err());
+    }
+}
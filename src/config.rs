@@ -1,27 +1,59 @@
 //! PLM 配置管理模块
 
+use crate::auth::{Credentials, SourceAuth};
+use crate::check::VersionTolerance;
+use crate::layers::{self, ConfigOrigin};
+use crate::permissions::PluginPermissions;
+use crate::providers::{Secret, SettingResolver};
+use crate::shims::LazyInstall;
+use crate::signature::{KeylessPolicy, TrustedKey};
+use crate::timings::{self, Phase};
 use crate::traits::PluginError;
+use crate::vendor::VendorMode;
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 /// 项目配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProjectConfig {
+    /// 配置格式版本，旧版本的配置在加载时自动迁移
+    #[serde(default = "legacy_config_version")]
+    pub config_version: u32,
     pub project: ProjectInfo,
     pub global_settings: GlobalSettings,
     pub plugins: HashMap<String, PluginConfig>,
     pub sources: Vec<PluginSource>,
+    #[serde(default)]
+    pub git_hooks: GitHooksConfig,
 
     // 兼容性字段
     pub project_name: String,
     pub project_root: String,
     pub version: String,
     pub settings: GlobalSettings,
+
+    /// 加载时展开过 `${VAR}` 的字符串（JSON 指针 -> 原始值），保存时还原
+    #[serde(skip)]
+    env_placeholders: HashMap<String, EnvPlaceholder>,
+
+    /// 各叶子值的来源（JSON 指针 -> 来源及该层的原始值），保存时据此跳过继承的值
+    #[serde(skip)]
+    origins: BTreeMap<String, (ConfigOrigin, serde_json::Value)>,
+}
+
+/// 展开前后的字符串
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EnvPlaceholder {
+    raw: String,
+    expanded: String,
 }
 
 /// 项目信息
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProjectInfo {
     pub name: String,
     pub version: String,
@@ -32,7 +64,7 @@ pub struct ProjectInfo {
 }
 
 /// 全局设置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GlobalSettings {
     pub cache_dir: String,
     pub registry_url: String,
@@ -45,21 +77,174 @@ pub struct GlobalSettings {
     pub plugin_dir: String,
     pub log_level: String,
     pub download_timeout: u64,
+    /// `plm check` 的版本容差策略
+    #[serde(default)]
+    pub version_tolerance: VersionTolerance,
+    /// 拒绝安装未签名或签名无效的制品
+    #[serde(default)]
+    pub require_signatures: bool,
+    /// 受信任的签名公钥
+    #[serde(default)]
+    pub trusted_keys: Vec<TrustedKey>,
+    /// HTTP/HTTPS 代理地址，未设置时使用 `HTTP_PROXY`/`HTTPS_PROXY` 环境变量
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// 不走代理的主机列表（逗号分隔），格式同 `NO_PROXY`
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+    /// 缓存目录大小上限（MB），`plm cache clean` 时按最近访问时间淘汰
+    #[serde(default)]
+    pub cache_max_size_mb: Option<u64>,
+    /// 缓存文件最长保留天数
+    #[serde(default)]
+    pub cache_max_age_days: Option<u64>,
+    /// 保存配置时保留的备份数量（`plm.json.bak.1..N`），0 表示不备份
+    #[serde(default = "default_config_backups")]
+    pub config_backups: usize,
+    /// 注册表中不存在的插件的缓存时间（秒），0 表示不缓存
+    #[serde(default = "default_negative_cache_ttl")]
+    pub negative_cache_ttl: u64,
+    /// 有插件初始化失败时继续运行：失败的插件记为错误状态，其他插件照常使用
+    #[serde(default)]
+    pub continue_on_init_error: bool,
+    /// 加载声明了更高 `min_plm_version` 的插件，只记录警告（也可设置 `PLM_IGNORE_COMPAT=1`）
+    #[serde(default)]
+    pub ignore_compat: bool,
+    /// 关闭单个插件的超时时间（秒），超时的插件不再等待，0 表示不限制
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout: u64,
+    /// `--wait` 时等待其他 plm 进程释放锁的最长时间（秒），0 表示一直等待
+    #[serde(default = "default_lock_timeout")]
+    pub lock_timeout: u64,
+    /// 安装后为插件声明的 GUI 工具创建启动器（`.desktop` 文件、开始菜单快捷方式），卸载时删除
+    #[serde(default = "default_launcher_integration")]
+    pub launcher_integration: bool,
+    /// 启动器目录，默认为当前平台的应用菜单目录
+    #[serde(default)]
+    pub launcher_dir: Option<String>,
+    /// 插件包装脚本的目录，相对路径相对于项目根目录，加入 PATH 后生效
+    #[serde(default = "default_shims_dir")]
+    pub shims_dir: String,
+    /// `plm sync --lazy` 生成的脚本首次运行时安装插件的方式：`prompt` 询问确认，`auto` 直接安装
+    #[serde(default)]
+    pub lazy_install: LazyInstall,
+    /// 下载 `ipfs://` 制品使用的 IPFS 网关（实验性，需要 `p2p` 特性），默认为本机节点
+    #[serde(default)]
+    pub ipfs_gateway: Option<String>,
+    /// `--vendor-dir` 指定的 `plm vendor` 目录，设置后只从该目录解析制品，不访问网络
+    #[serde(skip)]
+    pub vendor_dir: Option<PathBuf>,
+    /// `plm vendor` 运行时设置：下载的制品同时保存到该目录并记入索引
+    #[serde(skip)]
+    pub vendor_record_dir: Option<PathBuf>,
+    /// 解析相对路径的基准目录，加载项目配置时设为 `project_root`
+    #[serde(skip)]
+    pub base_dir: Option<PathBuf>,
+}
+
+fn default_negative_cache_ttl() -> u64 {
+    300
+}
+
+fn default_shutdown_timeout() -> u64 {
+    10
+}
+
+fn default_lock_timeout() -> u64 {
+    600
+}
+
+fn default_shims_dir() -> String {
+    ".plm/shims".to_string()
+}
+
+fn default_launcher_integration() -> bool {
+    true
+}
+
+fn default_plugin_enabled() -> bool {
+    true
+}
+
+fn legacy_config_version() -> u32 {
+    1
+}
+
+fn default_config_backups() -> usize {
+    crate::backup::DEFAULT_BACKUP_COUNT
+}
+
+/// Git 钩子配置，值为钩子中执行的命令，设为 null 表示不安装该钩子
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct GitHooksConfig {
+    pub pre_commit: Option<String>,
+    pub post_checkout: Option<String>,
 }
 
 /// 插件配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PluginConfig {
     pub name: String,
+    /// 旧格式的插件条目没有该字段，列出即启用
+    #[serde(default = "default_plugin_enabled")]
     pub enabled: bool,
     pub version: Option<String>,
     pub source: Option<PluginSource>,
+    #[serde(default)]
     pub settings: HashMap<String, serde_json::Value>,
+    #[serde(default)]
     pub auto_update: bool,
+    /// 安装/卸载前后执行的 shell 命令
+    #[serde(default)]
+    pub hooks: LifecycleHooks,
+    /// 包装脚本，键为命令名：安装后在 `shims_dir` 中生成，调用工具时注入默认参数和环境变量
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub wrappers: BTreeMap<String, WrapperConfig>,
+    /// 插件权限，未设置时不受限制，见 `crate::permissions`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<PluginPermissions>,
+}
+
+/// 把插件设置反序列化为插件自己的设置类型，供 `Plugin::configure` 使用
+///
+/// 错误信息以字段路径开头，如 `retries: invalid type: string "3", expected u32`
+pub fn settings_from_value<T: DeserializeOwned>(
+    value: serde_json::Value,
+) -> Result<T, PluginError> {
+    serde_path_to_error::deserialize(value).map_err(|e| {
+        let path = e.path().to_string();
+        PluginError::ConfigError(format!("{}: {}", path, e.into_inner()))
+    })
+}
+
+/// 包装脚本配置，如 `"kubectl": { "args": ["--context", "dev"] }`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct WrapperConfig {
+    /// 被包装的可执行文件，相对于安装位置；未设置时查找 `bin/<命令名>` 和 `<命令名>`
+    pub target: Option<String>,
+    /// 放在用户参数之前的默认参数
+    pub args: Vec<String>,
+    /// 运行前设置的环境变量
+    pub env: BTreeMap<String, String>,
+}
+
+/// 插件生命周期钩子，全局设置 `enable_hooks` 为 false 时不执行
+///
+/// 命令执行时可使用环境变量 `PLM_PLUGIN`、`PLM_VERSION`、`PLM_HOOK`、`PLM_PROJECT_ROOT`，
+/// 以及安装完成后的 `PLM_INSTALL_PATH`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct LifecycleHooks {
+    pub pre_install: Option<String>,
+    pub post_install: Option<String>,
+    pub pre_uninstall: Option<String>,
+    pub post_uninstall: Option<String>,
 }
 
 /// 插件源类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum PluginSourceType {
     Builtin,
@@ -67,17 +252,37 @@ pub enum PluginSourceType {
     Git,
     Http,
     Registry,
+    /// 静态文件注册表：只包含 `index.json` 和制品的 HTTPS 或 `file://` 目录
+    Static,
+    /// OCI 注册表：`oci://<registry>/<repository>`，标签即版本，见 `crate::oci`
+    Oci,
+    /// GitHub Releases：仓库地址，按插件设置中的附件模式选择制品，见 `crate::releases`
+    Github,
+    /// GitLab 发布或通用软件包：项目地址，支持自托管实例，见 `crate::gitlab`
+    Gitlab,
+    /// crates.io：crate 名称，优先安装预编译的二进制文件，见 `crate::crates`
+    Crates,
 }
 
 /// 插件源配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PluginSource {
     #[serde(rename = "type")]
     pub source_type: PluginSourceType,
     pub url: String,
     pub branch: Option<String>,
     pub tag: Option<String>,
+    /// 访问令牌，可写为 `keychain:<name>`、`env:<VAR>` 等引用，避免明文保存；作为 Bearer 令牌使用
     pub token: Option<String>,
+    /// 认证方式，设置后优先于 `token`，见 `crate::auth`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<SourceAuth>,
+    /// Sigstore/cosign 无密钥签名校验策略
+    #[serde(default)]
+    pub keyless: Option<KeylessPolicy>,
+    /// 镜像地址，主地址不可用时按顺序尝试
+    #[serde(default)]
+    pub mirrors: Vec<String>,
 }
 
 impl Default for GlobalSettings {
@@ -94,6 +299,98 @@ impl Default for GlobalSettings {
             plugin_dir: "~/.plm/plugins".to_string(),
             log_level: "info".to_string(),
             download_timeout: 300,
+            version_tolerance: VersionTolerance::default(),
+            require_signatures: false,
+            trusted_keys: Vec::new(),
+            proxy: None,
+            no_proxy: None,
+            cache_max_size_mb: None,
+            cache_max_age_days: None,
+            config_backups: default_config_backups(),
+            negative_cache_ttl: default_negative_cache_ttl(),
+            continue_on_init_error: false,
+            ignore_compat: false,
+            shutdown_timeout: default_shutdown_timeout(),
+            lock_timeout: default_lock_timeout(),
+            launcher_integration: default_launcher_integration(),
+            launcher_dir: None,
+            shims_dir: default_shims_dir(),
+            lazy_install: LazyInstall::default(),
+            ipfs_gateway: None,
+            vendor_dir: None,
+            vendor_record_dir: None,
+            base_dir: None,
+        }
+    }
+}
+
+impl GlobalSettings {
+    /// 设置解析相对路径的基准目录
+    pub fn with_base_dir(mut self, base_dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = Some(base_dir.into());
+        self
+    }
+
+    /// 解析后的缓存目录，见 `resolve_path`
+    pub fn cache_path(&self) -> PathBuf {
+        resolve_path(&self.cache_dir, self.base_dir.as_deref())
+    }
+
+    /// 进程锁文件路径，位于缓存目录下
+    pub fn lock_path(&self) -> PathBuf {
+        self.cache_path().join(crate::lock::LOCK_FILE)
+    }
+
+    /// 解析后的插件目录，见 `resolve_path`
+    pub fn plugin_path(&self) -> PathBuf {
+        resolve_path(&self.plugin_dir, self.base_dir.as_deref())
+    }
+
+    /// 启动器目录，未设置 `launcher_dir` 时见 `launcher::default_launcher_dir`
+    pub fn launcher_path(&self) -> Option<PathBuf> {
+        match &self.launcher_dir {
+            Some(dir) => Some(resolve_path(dir, self.base_dir.as_deref())),
+            None => crate::launcher::default_launcher_dir(),
+        }
+    }
+
+    /// 解析后的包装脚本目录，见 `resolve_path`
+    pub fn shims_path(&self) -> PathBuf {
+        resolve_path(&self.shims_dir, self.base_dir.as_deref())
+    }
+
+    /// 下载器使用 vendor 目录的方式
+    pub fn vendor_mode(&self) -> Option<VendorMode> {
+        match &self.vendor_record_dir {
+            Some(dir) => Some(VendorMode::Record(dir.clone())),
+            None => self.vendor_dir.clone().map(VendorMode::Resolve),
+        }
+    }
+
+    /// 传递给子进程（如 git、插件安装脚本）的代理环境变量
+    ///
+    /// 只包含配置文件中显式设置的代理；环境中已有的代理变量会被子进程直接继承
+    pub fn proxy_env_vars(&self) -> HashMap<String, String> {
+        let mut vars = HashMap::new();
+        if let Some(proxy) = &self.proxy {
+            for key in ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"] {
+                vars.insert(key.to_string(), proxy.clone());
+            }
+        }
+        if let Some(no_proxy) = &self.no_proxy {
+            for key in ["NO_PROXY", "no_proxy"] {
+                vars.insert(key.to_string(), no_proxy.clone());
+            }
+        }
+        vars
+    }
+}
+
+impl Default for GitHooksConfig {
+    fn default() -> Self {
+        Self {
+            pre_commit: Some("plm check".to_string()),
+            post_checkout: Some("plm sync --quiet".to_string()),
         }
     }
 }
@@ -102,8 +399,9 @@ impl ProjectConfig {
     /// 为项目创建默认配置
     pub fn default_for_project(name: &str, root_path: &str) -> Self {
         let now = Utc::now();
-        let settings = GlobalSettings::default();
+        let settings = GlobalSettings::default().with_base_dir(resolve_path(root_path, None));
         Self {
+            config_version: crate::migrate::CURRENT_CONFIG_VERSION,
             project: ProjectInfo {
                 name: name.to_string(),
                 version: "1.0.0".to_string(),
@@ -120,49 +418,209 @@ impl ProjectConfig {
                 branch: None,
                 tag: None,
                 token: None,
+                auth: None,
+                keyless: None,
+                mirrors: Vec::new(),
             }],
+            git_hooks: GitHooksConfig::default(),
             // 兼容性字段
             project_name: name.to_string(),
             project_root: root_path.to_string(),
             version: "1.0.0".to_string(),
             settings,
+            env_placeholders: HashMap::new(),
+            origins: BTreeMap::new(),
         }
     }
 
     /// 从文件加载配置
     pub async fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let content = tokio::fs::read_to_string(path).await?;
-        let config: ProjectConfig = serde_json::from_str(&content)?;
-        Ok(config)
+        Ok(Self::from_json_str(&content)?)
     }
 
     /// 从文件加载配置（兼容性方法）
+    ///
+    /// 项目配置合并在内置默认值和用户配置 `~/.plm/config.json` 之上，项目中的值优先。
+    /// 字符串值中的 `${VAR}` 会被替换为环境变量的值，`${VAR:-default}` 在变量未设置时
+    /// 使用默认值，`$${VAR}` 表示字面量 `${VAR}`。
+    ///
+    /// 旧格式的配置会迁移到当前版本并写回文件，原文件保留为 `.bak.1`
     pub async fn load_from_file(path: &str) -> Result<Self, PluginError> {
+        timings::measure(Phase::ConfigLoad, Self::load_and_migrate(path)).await
+    }
+
+    async fn load_and_migrate(path: &str) -> Result<Self, PluginError> {
         let content = tokio::fs::read_to_string(path)
             .await
             .map_err(|e| PluginError::ConfigError(format!("Failed to read config file: {}", e)))?;
+        let mut raw = parse_raw(&content)?;
+
+        if !crate::migrate::needs_migration(&raw) {
+            return Self::from_raw(raw);
+        }
+
+        let applied = crate::migrate::migrate(&mut raw)?;
+        // 迁移结果能被正确解析后才写回文件
+        let config = Self::from_raw(raw.clone())?;
+        let keep = crate::backup::backup_count(&raw);
+        let migrated = serde_json::to_string_pretty(&raw)
+            .map_err(|e| PluginError::ConfigError(format!("Failed to serialize config: {}", e)))?;
+        crate::backup::write_with_backups(Path::new(path), &migrated, keep)
+            .await
+            .map_err(|e| PluginError::ConfigError(format!("Failed to write config file: {}", e)))?;
+        log::info!(
+            "Migrated {} to config version {}: {}",
+            path,
+            config.config_version,
+            applied.join("; ")
+        );
+
+        Ok(config)
+    }
+
+    /// 解析 JSON 配置，在内存中迁移旧格式并展开环境变量占位符
+    pub fn from_json_str(content: &str) -> Result<Self, PluginError> {
+        let mut raw = parse_raw(content)?;
+        crate::migrate::migrate(&mut raw)?;
+        Self::from_raw(raw)
+    }
+
+    /// 把项目配置合并到默认值和用户配置之上
+    fn from_raw(project: serde_json::Value) -> Result<Self, PluginError> {
+        Self::from_layers(project, layers::read_user_config()?)
+    }
+
+    fn from_layers(
+        project: serde_json::Value,
+        user: Option<serde_json::Value>,
+    ) -> Result<Self, PluginError> {
+        let mut stack = vec![(ConfigOrigin::Default, layers::defaults())];
+        if let Some(user) = user {
+            stack.push((ConfigOrigin::User, user));
+        }
+        stack.push((ConfigOrigin::Project, project));
+        let layered = layers::merge_layers(stack);
 
-        let config: Self = serde_json::from_str(&content)
+        let mut value = layered.value;
+        let mut env_placeholders = HashMap::new();
+        interpolate_env(&mut value, "", &mut env_placeholders)?;
+
+        let mut config: Self = serde_json::from_value(value)
             .map_err(|e| PluginError::ConfigError(format!("Failed to parse config: {}", e)))?;
+        config.env_placeholders = env_placeholders;
+        config.origins = layered.origins;
+        config.set_path_base();
+        Ok(config)
+    }
+
+    /// 全局设置中的相对路径相对于项目根目录解析
+    fn set_path_base(&mut self) {
+        let root = resolve_path(&self.project_root, None);
+        self.global_settings.base_dir = Some(root.clone());
+        self.settings.base_dir = Some(root);
+    }
+
+    /// 配置值的来源，路径为 JSON 指针（如 `/global_settings/cache_dir`）
+    ///
+    /// 未从文件加载的配置没有来源记录
+    pub fn origin_of(&self, pointer: &str) -> Option<ConfigOrigin> {
+        self.origins.get(pointer).map(|(origin, _)| *origin)
+    }
 
+    /// 加载时各叶子值的路径、来源和该层中的原始值（未展开环境变量），按路径排序
+    pub fn origins(&self) -> impl Iterator<Item = (&str, ConfigOrigin, &serde_json::Value)> {
+        self.origins
+            .iter()
+            .map(|(pointer, (origin, raw))| (pointer.as_str(), *origin, raw))
+    }
+
+    /// 序列化为 JSON 文本
+    ///
+    /// 加载时展开过的值若未被修改，会还原为原来的占位符，避免把密钥写回配置文件；
+    /// 从默认值或用户配置继承且未被修改的值不会写出
+    pub fn to_json_string(&self) -> Result<String, PluginError> {
+        let mut value = serde_json::to_value(self)
+            .map_err(|e| PluginError::ConfigError(format!("Failed to serialize config: {}", e)))?;
+
+        for (pointer, placeholder) in &self.env_placeholders {
+            if let Some(slot) = value.pointer_mut(pointer) {
+                if slot.as_str() == Some(placeholder.expanded.as_str()) {
+                    *slot = serde_json::Value::String(placeholder.raw.clone());
+                }
+            }
+        }
+
+        for (pointer, (origin, inherited)) in &self.origins {
+            if *origin != ConfigOrigin::Project && value.pointer(pointer) == Some(inherited) {
+                layers::remove_pointer(&mut value, pointer);
+            }
+        }
+
+        serde_json::to_string_pretty(&value)
+            .map_err(|e| PluginError::ConfigError(format!("Failed to serialize config: {}", e)))
+    }
+
+    /// 配置文件的 JSON Schema，可供编辑器补全和校验使用
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(ProjectConfig)).unwrap_or_default()
+    }
+
+    /// 按配置结构校验 JSON 文本，不展开环境变量占位符
+    ///
+    /// 结构错误会给出字段路径和行列号，例如
+    /// `global_settings.download_timeout: invalid type: string "30", expected u64 at line 14 column 32`。
+    /// 文件中缺少的字段可以由默认值和用户配置补全，合并后仍缺少时才报告；
+    /// 结构正确时再执行 `validate` 中的检查
+    pub fn validate_json(content: &str) -> Result<Self, PluginError> {
+        let mut raw = parse_raw(content)?;
+
+        // 直接解析原文以获得行列号，缺少字段的错误留到合并后再判断
+        if !crate::migrate::needs_migration(&raw) {
+            let mut deserializer = serde_json::Deserializer::from_str(content);
+            let strict: Result<Self, _> = serde_path_to_error::deserialize(&mut deserializer);
+            if let Err(e) = strict {
+                let path = e.path().to_string();
+                let inner = e.into_inner();
+                if !inner.to_string().starts_with("missing field") {
+                    return Err(PluginError::ConfigError(format!("{}: {}", path, inner)));
+                }
+            }
+        }
+
+        crate::migrate::migrate(&mut raw)?;
+        let mut stack = vec![(ConfigOrigin::Default, layers::defaults())];
+        if let Some(user) = layers::read_user_config()? {
+            stack.push((ConfigOrigin::User, user));
+        }
+        stack.push((ConfigOrigin::Project, raw));
+        let merged = layers::merge_layers(stack).value;
+
+        let config: Self = serde_path_to_error::deserialize(merged).map_err(|e| {
+            let path = e.path().to_string();
+            PluginError::ConfigError(format!("{}: {}", path, e.into_inner()))
+        })?;
+        config.validate()?;
         Ok(config)
     }
 
     /// 保存配置到文件
     pub async fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let content = serde_json::to_string_pretty(self)?;
-        tokio::fs::write(path, content).await?;
+        self.save_to_file(path).await?;
         Ok(())
     }
 
     /// 保存配置到文件（兼容性方法）
     pub async fn save_to_file(&self, path: &str) -> Result<(), PluginError> {
-        let content = serde_json::to_string_pretty(self)
-            .map_err(|e| PluginError::ConfigError(format!("Failed to serialize config: {}", e)))?;
+        let content = self.to_json_string()?;
 
-        tokio::fs::write(path, content)
-            .await
-            .map_err(|e| PluginError::ConfigError(format!("Failed to write config file: {}", e)))?;
+        crate::backup::write_with_backups(
+            Path::new(path),
+            &content,
+            self.global_settings.config_backups,
+        )
+        .await
+        .map_err(|e| PluginError::ConfigError(format!("Failed to write config file: {}", e)))?;
 
         Ok(())
     }
@@ -282,6 +740,9 @@ impl PluginConfig {
             source: None,
             settings: HashMap::new(),
             auto_update: false,
+            hooks: LifecycleHooks::default(),
+            wrappers: BTreeMap::new(),
+            permissions: None,
         }
     }
 
@@ -305,9 +766,71 @@ impl PluginConfig {
         self.settings.insert(key.to_string(), value);
     }
 
-    /// 获取配置项
+    /// 获取配置项，当前平台的设置块（如 `settings.linux`）优先于基础设置
     pub fn get_setting(&self, key: &str) -> Option<&serde_json::Value> {
-        self.settings.get(key)
+        self.get_setting_for(key, current_platform())
+    }
+
+    /// 获取指定平台下的配置项
+    pub fn get_setting_for(&self, key: &str, platform: &str) -> Option<&serde_json::Value> {
+        self.settings
+            .get(platform)
+            .and_then(|block| block.get(key))
+            .or_else(|| self.settings.get(key))
+    }
+
+    /// 设置平台专属配置项
+    pub fn set_platform_setting(&mut self, platform: &str, key: &str, value: serde_json::Value) {
+        let block = self
+            .settings
+            .entry(platform.to_string())
+            .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+        if !block.is_object() {
+            *block = serde_json::Value::Object(serde_json::Map::new());
+        }
+        if let Some(map) = block.as_object_mut() {
+            map.insert(key.to_string(), value);
+        }
+    }
+
+    /// 获取当前平台生效的全部设置（平台设置块合并到基础设置之上）
+    pub fn effective_settings(&self) -> HashMap<String, serde_json::Value> {
+        self.effective_settings_for(current_platform())
+    }
+
+    /// 获取指定平台生效的全部设置
+    pub fn effective_settings_for(&self, platform: &str) -> HashMap<String, serde_json::Value> {
+        let mut merged: HashMap<String, serde_json::Value> = self
+            .settings
+            .iter()
+            .filter(|(key, value)| !(is_platform_key(key) && value.is_object()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        if let Some(serde_json::Value::Object(block)) = self.settings.get(platform) {
+            for (key, value) in block {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+
+        merged
+    }
+
+    /// 当前平台生效的全部设置，作为 JSON 对象
+    pub fn settings_value(&self) -> serde_json::Value {
+        serde_json::Value::Object(self.effective_settings().into_iter().collect())
+    }
+
+    /// 把当前平台生效的设置反序列化为插件自己的设置类型
+    ///
+    /// 错误信息带有字段路径，例如 `plugins.node.settings.retries: invalid type: string "3", expected u32`
+    pub fn settings_as<T: DeserializeOwned>(&self) -> Result<T, PluginError> {
+        settings_from_value(self.settings_value()).map_err(|e| match e {
+            PluginError::ConfigError(message) => {
+                PluginError::ConfigError(format!("plugins.{}.settings.{}", self.name, message))
+            }
+            e => e,
+        })
     }
 
     /// 移除配置项
@@ -326,7 +849,92 @@ impl PluginConfig {
     }
 }
 
+/// 可在插件设置中作为平台设置块键名的平台
+pub const PLATFORM_KEYS: &[&str] = &["linux", "macos", "windows"];
+
+/// 当前运行平台名称（与 `PLATFORM_KEYS` 中的键一致）
+pub fn current_platform() -> &'static str {
+    std::env::consts::OS
+}
+
+/// 解析配置中的路径
+///
+/// 依次展开 `$VAR`/`${VAR}` 环境变量（未设置的变量保持原样）和开头的 `~`，
+/// 结果仍是相对路径且提供了 `base` 时，相对于 `base` 解析
+pub fn resolve_path(path: &str, base: Option<&Path>) -> PathBuf {
+    let expanded = expand_path_vars(path);
+    let resolved = if expanded == "~" {
+        dirs::home_dir().unwrap_or_else(|| PathBuf::from(&expanded))
+    } else {
+        expand_home(&expanded)
+    };
+    match base {
+        Some(base) if resolved.is_relative() => base.join(resolved),
+        _ => resolved,
+    }
+}
+
+/// 展开路径中的 `$VAR` 和 `${VAR}`，未设置的变量保持原样
+fn expand_path_vars(path: &str) -> String {
+    let mut output = String::with_capacity(path.len());
+    let mut rest = path;
+
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, consumed) = match after.strip_prefix('{') {
+            Some(body) => match body.find('}') {
+                Some(end) => (&body[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+
+        match std::env::var(name).ok().filter(|_| !name.is_empty()) {
+            Some(value) => output.push_str(&value),
+            None => output.push_str(&rest[start..start + 1 + consumed]),
+        }
+        rest = &after[consumed..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// 展开路径开头的 `~/` 为用户主目录
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+fn is_platform_key(key: &str) -> bool {
+    PLATFORM_KEYS.contains(&key)
+}
+
 impl PluginSource {
+    /// 创建内置插件源，`name` 为内置插件名（如 `rustup`）
+    pub fn builtin(name: &str) -> Self {
+        PluginSource {
+            source_type: PluginSourceType::Builtin,
+            url: name.to_string(),
+            branch: None,
+            tag: None,
+            token: None,
+            auth: None,
+            keyless: None,
+            mirrors: Vec::new(),
+        }
+    }
+
     /// 创建本地插件源
     pub fn local(path: &str) -> Self {
         PluginSource {
@@ -335,6 +943,9 @@ impl PluginSource {
             branch: None,
             tag: None,
             token: None,
+            auth: None,
+            keyless: None,
+            mirrors: Vec::new(),
         }
     }
 
@@ -346,6 +957,51 @@ impl PluginSource {
             branch: None,
             tag: None,
             token: None,
+            auth: None,
+            keyless: None,
+            mirrors: Vec::new(),
+        }
+    }
+
+    /// 创建静态文件注册表插件源
+    pub fn static_registry(url: &str) -> Self {
+        PluginSource {
+            source_type: PluginSourceType::Static,
+            url: url.to_string(),
+            branch: None,
+            tag: None,
+            token: None,
+            auth: None,
+            keyless: None,
+            mirrors: Vec::new(),
+        }
+    }
+
+    /// 创建 OCI 仓库插件源
+    pub fn oci(url: &str) -> Self {
+        PluginSource {
+            source_type: PluginSourceType::Oci,
+            url: url.to_string(),
+            branch: None,
+            tag: None,
+            token: None,
+            auth: None,
+            keyless: None,
+            mirrors: Vec::new(),
+        }
+    }
+
+    /// 创建 GitHub Releases 插件源
+    pub fn github(url: &str) -> Self {
+        PluginSource {
+            source_type: PluginSourceType::Github,
+            url: url.to_string(),
+            branch: None,
+            tag: None,
+            token: None,
+            auth: None,
+            keyless: None,
+            mirrors: Vec::new(),
         }
     }
 
@@ -357,6 +1013,9 @@ impl PluginSource {
             branch: branch.map(|s| s.to_string()),
             tag: None,
             token: None,
+            auth: None,
+            keyless: None,
+            mirrors: Vec::new(),
         }
     }
 
@@ -368,6 +1027,9 @@ impl PluginSource {
             branch: None,
             tag: None,
             token: None,
+            auth: None,
+            keyless: None,
+            mirrors: Vec::new(),
         }
     }
 
@@ -379,9 +1041,71 @@ impl PluginSource {
             branch: None,
             tag: None,
             token: None,
+            auth: None,
+            keyless: None,
+            mirrors: Vec::new(),
         }
     }
 
+    /// 设置无密钥签名校验策略
+    pub fn with_keyless(mut self, policy: KeylessPolicy) -> Self {
+        self.keyless = Some(policy);
+        self
+    }
+
+    /// 添加镜像地址
+    pub fn with_mirror(mut self, url: &str) -> Self {
+        self.mirrors.push(url.to_string());
+        self
+    }
+
+    /// 设置访问令牌为钥匙串引用，令牌本身需通过 `secrets::store_secret` 保存
+    pub fn with_keychain_token(mut self, name: &str) -> Self {
+        self.token = Some(crate::secrets::keychain_reference(name));
+        self
+    }
+
+    /// 设置认证方式
+    pub fn with_auth(mut self, auth: SourceAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// 按 `auth`、`token`、netrc 的顺序确定访问插件源的凭据
+    pub async fn resolve_auth(&self) -> Result<Option<Credentials>, PluginError> {
+        crate::auth::resolve(self).await
+    }
+
+    /// 解析访问令牌，令牌为提供者引用（如 `keychain:`）时读取实际值
+    pub async fn resolve_token(&self) -> Result<Option<Secret>, PluginError> {
+        let Some(token) = &self.token else {
+            return Ok(None);
+        };
+        let resolved = SettingResolver::new()
+            .resolve(&serde_json::Value::String(token.clone()))
+            .await?;
+        Ok(Some(
+            resolved.unwrap_or_else(|| Secret::from(token.clone())),
+        ))
+    }
+
+    /// 按尝试顺序列出制品的候选下载地址
+    ///
+    /// 第一个始终是原地址；原地址以源地址开头时，依次追加替换为各镜像地址后的结果
+    pub fn candidate_urls(&self, artifact_url: &str) -> Vec<String> {
+        let mut urls = vec![artifact_url.to_string()];
+        let base = self.url.trim_end_matches('/');
+        if let Some(rest) = artifact_url.strip_prefix(base).filter(|_| !base.is_empty()) {
+            for mirror in &self.mirrors {
+                let candidate = format!("{}{}", mirror.trim_end_matches('/'), rest);
+                if !urls.contains(&candidate) {
+                    urls.push(candidate);
+                }
+            }
+        }
+        urls
+    }
+
     /// 获取源的 URL
     pub fn get_url(&self) -> &str {
         &self.url
@@ -392,6 +1116,11 @@ impl PluginSource {
         match self.source_type {
             PluginSourceType::Local => "local",
             PluginSourceType::Registry => "registry",
+            PluginSourceType::Static => "static",
+            PluginSourceType::Oci => "oci",
+            PluginSourceType::Github => "github",
+            PluginSourceType::Gitlab => "gitlab",
+            PluginSourceType::Crates => "crates",
             PluginSourceType::Git => "git",
             PluginSourceType::Http => "http",
             PluginSourceType::Builtin => "builtin",
@@ -399,10 +1128,131 @@ impl PluginSource {
     }
 }
 
+fn parse_raw(content: &str) -> Result<serde_json::Value, PluginError> {
+    serde_json::from_str(content)
+        .map_err(|e| PluginError::ConfigError(format!("Failed to parse config: {}", e)))
+}
+
+/// 递归展开 JSON 中字符串值的环境变量占位符，记录被修改的值
+fn interpolate_env(
+    value: &mut serde_json::Value,
+    pointer: &str,
+    placeholders: &mut HashMap<String, EnvPlaceholder>,
+) -> Result<(), PluginError> {
+    match value {
+        serde_json::Value::String(raw) if raw.contains("${") => {
+            let expanded = expand_env_vars(raw).map_err(|e| {
+                PluginError::ConfigError(format!("Failed to expand '{}': {}", pointer, e))
+            })?;
+            if expanded != *raw {
+                let raw = std::mem::replace(raw, expanded.clone());
+                placeholders.insert(pointer.to_string(), EnvPlaceholder { raw, expanded });
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                interpolate_env(item, &format!("{}/{}", pointer, index), placeholders)?;
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                // JSON 指针中 `~` 和 `/` 需要转义
+                let key = key.replace('~', "~0").replace('/', "~1");
+                interpolate_env(item, &format!("{}/{}", pointer, key), placeholders)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// 展开字符串中的 `${VAR}`、`${VAR:-default}`，`$${` 转义为字面量 `${`
+fn expand_env_vars(input: &str) -> Result<String, String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        if let Some(escaped) = after.strip_prefix("${") {
+            output.push_str("${");
+            rest = escaped;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = body
+                .find('}')
+                .ok_or_else(|| "unterminated '${' placeholder".to_string())?;
+            let (name, default) = match body[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&body[..end], None),
+            };
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("invalid environment variable name '{}'", name));
+            }
+
+            match (std::env::var(name), default) {
+                (Ok(value), _) => output.push_str(&value),
+                (Err(_), Some(default)) => output.push_str(default),
+                (Err(_), None) => {
+                    return Err(format!("environment variable '{}' is not set", name))
+                }
+            }
+            rest = &body[end + 1..];
+        } else {
+            output.push('$');
+            rest = after;
+        }
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resolve_settings_paths() {
+        std::env::set_var("PLM_TEST_CACHE_ROOT", "/var/cache");
+        assert_eq!(
+            resolve_path("$PLM_TEST_CACHE_ROOT/plm", None),
+            PathBuf::from("/var/cache/plm")
+        );
+        assert_eq!(
+            resolve_path("${PLM_TEST_CACHE_ROOT}/plm", None),
+            PathBuf::from("/var/cache/plm")
+        );
+        assert_eq!(
+            resolve_path("$PLM_TEST_UNSET_DIR/plm", None),
+            PathBuf::from("$PLM_TEST_UNSET_DIR/plm")
+        );
+
+        let mut config = ProjectConfig::default_for_project("paths", "/work/app");
+        config.global_settings.cache_dir = ".plm/cache".to_string();
+        config.global_settings.plugin_dir = "/opt/plm/plugins".to_string();
+        assert_eq!(
+            config.global_settings.cache_path(),
+            PathBuf::from("/work/app/.plm/cache")
+        );
+        assert_eq!(
+            config.global_settings.plugin_path(),
+            PathBuf::from("/opt/plm/plugins")
+        );
+
+        let loaded = ProjectConfig::from_json_str(&config.to_json_string().unwrap()).unwrap();
+        assert_eq!(
+            loaded.global_settings.cache_path(),
+            PathBuf::from("/work/app/.plm/cache")
+        );
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(
+                GlobalSettings::default().cache_path(),
+                home.join(".plm/cache")
+            );
+        }
+    }
+
     #[test]
     fn test_project_config_creation() {
         let config = ProjectConfig::default_for_project("test-project", "/tmp");
@@ -457,4 +1307,168 @@ mod tests {
         );
         assert_eq!(plugin.get_setting("nonexistent"), None);
     }
+
+    #[test]
+    fn test_mirror_candidate_urls() {
+        let source = PluginSource::http("https://nodejs.org/dist/")
+            .with_mirror("https://npmmirror.com/mirrors/node")
+            .with_mirror("https://nodejs.org/dist");
+
+        assert_eq!(
+            source.candidate_urls("https://nodejs.org/dist/v18.17.0/node.tar.gz"),
+            vec![
+                "https://nodejs.org/dist/v18.17.0/node.tar.gz".to_string(),
+                "https://npmmirror.com/mirrors/node/v18.17.0/node.tar.gz".to_string(),
+            ]
+        );
+        assert_eq!(
+            source.candidate_urls("https://example.com/node.tar.gz"),
+            vec!["https://example.com/node.tar.gz".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_proxy_env_vars() {
+        let mut settings = GlobalSettings::default();
+        assert!(settings.proxy_env_vars().is_empty());
+
+        settings.proxy = Some("http://proxy.corp:3128".to_string());
+        settings.no_proxy = Some("localhost,.corp".to_string());
+        let vars = settings.proxy_env_vars();
+        assert_eq!(vars["HTTPS_PROXY"], "http://proxy.corp:3128");
+        assert_eq!(vars["no_proxy"], "localhost,.corp");
+    }
+
+    #[test]
+    fn test_platform_settings() {
+        let mut plugin = PluginConfig::new("test-plugin");
+        plugin.set_setting("binary", serde_json::json!("tool"));
+        plugin.set_setting("timeout", serde_json::json!(30));
+        plugin.set_platform_setting("windows", "binary", serde_json::json!("tool.exe"));
+
+        assert_eq!(
+            plugin.get_setting_for("binary", "windows"),
+            Some(&serde_json::json!("tool.exe"))
+        );
+        assert_eq!(
+            plugin.get_setting_for("binary", "linux"),
+            Some(&serde_json::json!("tool"))
+        );
+
+        let windows = plugin.effective_settings_for("windows");
+        assert_eq!(windows.get("binary"), Some(&serde_json::json!("tool.exe")));
+        assert_eq!(windows.get("timeout"), Some(&serde_json::json!(30)));
+        assert!(!windows.contains_key("windows"));
+    }
+
+    #[test]
+    fn test_typed_settings() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct ToolSettings {
+            binary: String,
+            #[serde(default)]
+            retries: u32,
+        }
+
+        let mut plugin = PluginConfig::new("tool");
+        plugin.set_setting("binary", serde_json::json!("tool"));
+        plugin.set_platform_setting(
+            current_platform(),
+            "binary",
+            serde_json::json!("tool-native"),
+        );
+        assert_eq!(
+            plugin.settings_as::<ToolSettings>().unwrap(),
+            ToolSettings {
+                binary: "tool-native".to_string(),
+                retries: 0,
+            }
+        );
+
+        plugin.set_setting("retries", serde_json::json!("3"));
+        match plugin.settings_as::<ToolSettings>() {
+            Err(PluginError::ConfigError(message)) => {
+                assert!(message.starts_with("plugins.tool.settings.retries: "));
+            }
+            other => panic!("expected config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_env_interpolation_roundtrip() {
+        std::env::set_var("PLM_TEST_TOKEN", "secret-token");
+        std::env::remove_var("PLM_TEST_UNSET");
+
+        let mut config = ProjectConfig::default_for_project("env", "/tmp");
+        config.sources[0].token = Some("${PLM_TEST_TOKEN}".to_string());
+        config.global_settings.cache_dir = "${PLM_TEST_UNSET:-/var/cache}/plm".to_string();
+        config.project_root = "$${LITERAL} $HOME".to_string();
+        let json = serde_json::to_string(&config).unwrap();
+
+        let loaded = ProjectConfig::from_json_str(&json).unwrap();
+        assert_eq!(loaded.sources[0].token.as_deref(), Some("secret-token"));
+        assert_eq!(loaded.global_settings.cache_dir, "/var/cache/plm");
+        assert_eq!(loaded.project_root, "${LITERAL} $HOME");
+
+        // 保存时不会写出展开后的密钥
+        let saved = loaded.to_json_string().unwrap();
+        assert!(!saved.contains("secret-token"));
+        assert!(saved.contains("${PLM_TEST_TOKEN}"));
+
+        config.sources[0].token = Some("${PLM_TEST_UNSET}".to_string());
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(matches!(
+            ProjectConfig::from_json_str(&json),
+            Err(PluginError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_json_reports_field_path() {
+        let config = ProjectConfig::default_for_project("schema", "/tmp");
+        let mut value = serde_json::to_value(&config).unwrap();
+        assert!(ProjectConfig::validate_json(&value.to_string()).is_ok());
+
+        value["global_settings"]["download_timeout"] = serde_json::json!("30");
+        let content = serde_json::to_string_pretty(&value).unwrap();
+        match ProjectConfig::validate_json(&content) {
+            Err(PluginError::ConfigError(message)) => {
+                assert!(message.starts_with("global_settings.download_timeout: "));
+                assert!(message.contains("line "));
+            }
+            other => panic!("expected config error, got {:?}", other),
+        }
+
+        let schema = ProjectConfig::json_schema();
+        assert!(schema["properties"]["global_settings"].is_object());
+    }
+
+    #[test]
+    fn test_layered_config_skips_inherited_values_on_save() {
+        let config = ProjectConfig::default_for_project("layered", "/tmp");
+        let mut project = serde_json::to_value(&config).unwrap();
+        // 项目只声明与默认值不同的全局设置
+        project["global_settings"] = serde_json::json!({ "parallel_downloads": 8 });
+        let user = serde_json::json!({ "global_settings": { "cache_dir": "/data/plm" } });
+
+        let mut loaded = ProjectConfig::from_layers(project, Some(user)).unwrap();
+        assert_eq!(loaded.global_settings.cache_dir, "/data/plm");
+        assert_eq!(loaded.global_settings.parallel_downloads, 8);
+        assert_eq!(
+            loaded.origin_of("/global_settings/cache_dir"),
+            Some(ConfigOrigin::User)
+        );
+        assert_eq!(
+            loaded.origin_of("/global_settings/download_timeout"),
+            Some(ConfigOrigin::Default)
+        );
+
+        loaded.global_settings.download_timeout = 60;
+        let saved: serde_json::Value =
+            serde_json::from_str(&loaded.to_json_string().unwrap()).unwrap();
+        assert_eq!(
+            saved["global_settings"],
+            serde_json::json!({ "parallel_downloads": 8, "download_timeout": 60 })
+        );
+    }
 }
@@ -1,120 +1,1864 @@
 //! PLM 核心插件管理器实现
 
-use crate::config::{PluginConfig, ProjectConfig};
-use crate::traits::{InstallOptions, Plugin, PluginError, ValidationSummary};
-use std::collections::HashMap;
+use crate::check::{self, CheckReport};
+use crate::compat;
+use crate::config::{PluginConfig, PluginSourceType, ProjectConfig, WrapperConfig};
+use crate::crates::CratePlugin;
+use crate::delegate::{self, DelegatePlugin};
+use crate::download;
+use crate::events::{EventBus, ListenerId, PlmEvent};
+use crate::github;
+use crate::gitlab;
+use crate::hooks::{self, HookContext, HookEvent};
+use crate::id::{IntoPluginId, PluginId};
+use crate::launcher;
+use crate::managed::ManagedPlugin;
+use crate::metadata_cache::{self, MetadataCache};
+#[cfg(feature = "metrics")]
+use crate::metrics::{self, MetricsSnapshot};
+use crate::node::{self, NodePlugin};
+use crate::oci::OciPlugin;
+use crate::permissions::PermissionGuard;
+use crate::project_files;
+use crate::providers::{ResolvedValue, SettingResolver};
+use crate::reload::ConfigDiff;
+use crate::rustup::{self, RustupPlugin};
+use crate::shims::{self, LazyInstall};
+use crate::state::{self, Orphan, StateStore};
+use crate::static_registry::StaticPlugin;
+use crate::system::{self, SystemPackagePlugin};
+use crate::target::TargetPlatform;
+use crate::terraform::{self, TerraformPlugin};
+use crate::timings::{self, Phase};
+use crate::traits::{
+    Capability, ChangeKind, InitReport, InstallOptions, InstallReport, InstallResult,
+    LifecycleState, PlannedChange, Plugin, PluginError, PluginFactory, PluginLifecycle,
+    PluginLoader, PluginMetadata, ReloadReport, ShutdownReport, SkipReason, UpdateResult,
+    UpgradeSummary, ValidationSummary, VersionEntry, VersionInfo,
+};
+use crate::version::{DependencySpec, Version, VersionReq};
+use futures_util::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::fs;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+/// 同时初始化的插件数量上限
+const MAX_CONCURRENT_INITIALIZE: usize = 8;
 
 /// PLM 插件管理器
 ///
 /// 负责管理插件的生命周期，包括注册、初始化、安装、卸载等操作
 pub struct PluginManager {
-    plugins: HashMap<String, Arc<dyn Plugin>>,
+    plugins: HashMap<PluginId, Arc<ManagedPlugin>>,
+    /// 管理器记录的插件生命周期状态，不依赖插件自己报告的 `status()`
+    lifecycle: HashMap<PluginId, PluginLifecycle>,
     config: ProjectConfig,
+    config_tx: watch::Sender<ProjectConfig>,
+    resolver: SettingResolver,
+    /// 配置自上次保存后是否被修改
+    dirty: Arc<AtomicBool>,
+    auto_save: Option<AutoSave>,
+    events: EventBus,
+    /// 通过 `register_factory` 或构建器添加的插件工厂，热加载时用于创建新配置的插件
+    factories: Vec<Arc<dyn PluginFactory>>,
+    /// 串行化运行状态的读取-修改-保存，避免并发安装互相覆盖记录
+    state_lock: tokio::sync::Mutex<()>,
+}
+
+/// 自动保存状态
+struct AutoSave {
+    path: PathBuf,
+    task: JoinHandle<()>,
 }
 
 impl PluginManager {
     /// 创建新的插件管理器实例
     pub async fn new() -> Result<Self, PluginError> {
         let config = ProjectConfig::default_for_project("default", ".");
-        Ok(Self {
-            plugins: HashMap::new(),
-            config,
-        })
+        Self::from_project_config(config).await
+    }
+
+    /// 创建插件管理器构建器，用于在构建时注册插件工厂、加载器和插件
+    pub fn builder() -> PluginManagerBuilder {
+        PluginManagerBuilder::new()
     }
 
     /// 从项目配置创建插件管理器
+    ///
+    /// 设置了 `"backend": "system"` 或 `"backend": "delegate"` 的已启用插件
+    /// 会直接注册为系统包管理器插件或版本管理器委托插件，`builtin` 类型插件源的插件
+    /// 注册为对应的内置插件，`static` 类型插件源的插件从对应的静态文件注册表安装。
+    /// 无法按配置创建的插件只记录警告，其生命周期状态记为错误（见 `plugin_state`）
     pub async fn from_project_config(config: ProjectConfig) -> Result<Self, PluginError> {
+        let project_root = Path::new(config.get_project_root());
+        let mut plugins: HashMap<PluginId, Arc<ManagedPlugin>> = HashMap::new();
+        let mut failed: Vec<(PluginId, String)> = Vec::new();
+        for plugin_config in config.plugins.values().filter(|p| p.enabled) {
+            let id = plugin_config.name.as_str().into_plugin_id()?;
+            match backend_plugin(plugin_config, &config, project_root) {
+                Ok(Some(plugin)) => {
+                    plugins.insert(id, Arc::new(ManagedPlugin::new(plugin)));
+                }
+                Ok(None) => {}
+                // 插件配置有误（如未编译进来或拼错的内置插件）时不影响其他插件，
+                // 该插件记为错误状态，`plm lint` 会报告配置问题
+                Err(e) => {
+                    log::warn!("无法创建插件 {}: {}", id, e);
+                    failed.push((id, e.to_string()));
+                }
+            }
+        }
+
+        let mut lifecycle: HashMap<PluginId, PluginLifecycle> = plugins
+            .keys()
+            .map(|id| (id.clone(), PluginLifecycle::registered()))
+            .collect();
+        for (id, error) in failed {
+            let mut record = PluginLifecycle::registered();
+            record.transition(LifecycleState::Initializing);
+            record.fail(error);
+            lifecycle.insert(id, record);
+        }
+        let (config_tx, _) = watch::channel(config.clone());
+        let events = EventBus::new();
+        #[cfg(feature = "metrics")]
+        events.add_listener(metrics::record_event);
         Ok(Self {
-            plugins: HashMap::new(),
+            plugins,
+            lifecycle,
             config,
+            config_tx,
+            resolver: SettingResolver::new(),
+            dirty: Arc::new(AtomicBool::new(false)),
+            auto_save: None,
+            events,
+            factories: Vec::new(),
+            state_lock: tokio::sync::Mutex::new(()),
         })
     }
 
     /// 初始化插件管理器
+    ///
+    /// 见 `initialize_all`。有插件初始化失败时返回汇总的错误；全局设置
+    /// `continue_on_init_error` 开启时只记录警告并返回 Ok，失败的插件保持错误状态，
+    /// 其他插件照常使用
     pub async fn initialize(&mut self) -> Result<(), PluginError> {
-        // 初始化所有已注册的插件
-        for (name, plugin) in &mut self.plugins {
-            if let Err(e) = Arc::get_mut(plugin)
-                .ok_or_else(|| {
-                    PluginError::PluginError(format!("无法获取插件 {} 的可变引用", name))
-                })?
-                .initialize()
-                .await
-            {
-                return Err(PluginError::PluginError(format!(
-                    "插件 {} 初始化失败: {}",
-                    name, e
+        let report = self.initialize_all().await?;
+        if report.is_success() {
+            return Ok(());
+        }
+        let message = report.error_message();
+        if self.config.global_settings.continue_on_init_error {
+            log::warn!("{}", message);
+            Ok(())
+        } else {
+            Err(PluginError::PluginError(message))
+        }
+    }
+
+    /// 初始化所有插件，返回每个插件的结果
+    ///
+    /// 先用已添加的插件工厂（见 `register_factory`）创建配置中尚未注册的已启用插件，
+    /// 创建失败的插件记为失败。
+    /// 按依赖关系分层初始化插件：同一层的插件互不依赖，按标识顺序并发初始化
+    /// （最多 `MAX_CONCURRENT_INITIALIZE` 个），上一层全部完成后才开始下一层。
+    /// 单个插件失败不会中断其他插件，失败的插件记为错误状态（见 `plugin_state`），
+    /// 依赖它的插件会被跳过。只有插件之间存在循环依赖时返回错误
+    pub async fn initialize_all(&mut self) -> Result<InitReport, PluginError> {
+        timings::measure(Phase::PluginLoad, self.initialize_layers()).await
+    }
+
+    async fn initialize_layers(&mut self) -> Result<InitReport, PluginError> {
+        let mut report = InitReport::default();
+        for (name, result) in self.instantiate_from_factories().await {
+            if let Err(e) = result {
+                report.failed.push((name, e.to_string()));
+            }
+        }
+
+        let layers = self.initialization_layers()?;
+        let mut failed: HashSet<PluginId> = HashSet::new();
+
+        for layer in layers {
+            let mut ready = HashSet::new();
+            for id in layer {
+                let dependencies = self.registered_dependencies(&id);
+                match dependencies.iter().find(|dep| failed.contains(*dep)) {
+                    Some(dep) => {
+                        let error = format!("依赖的插件 {} 初始化失败", dep);
+                        report.skipped.push((id.to_string(), error.clone()));
+                        self.set_lifecycle_error(&id, error);
+                        failed.insert(id);
+                    }
+                    None => {
+                        self.set_lifecycle(&id, LifecycleState::Initializing);
+                        ready.insert(id);
+                    }
+                }
+            }
+
+            let mut plugins: Vec<(PluginId, Arc<ManagedPlugin>)> = self
+                .plugins
+                .iter()
+                .filter(|(id, _)| ready.contains(*id))
+                .map(|(id, plugin)| (id.clone(), plugin.clone()))
+                .collect();
+            plugins.sort_by(|a, b| a.0.cmp(&b.0));
+
+            let mut results: Vec<(PluginId, Result<(), PluginError>)> = stream::iter(plugins)
+                .map(|(id, plugin)| async move {
+                    let result = plugin.initialize_exclusive().await;
+                    (id, result)
+                })
+                .buffer_unordered(MAX_CONCURRENT_INITIALIZE)
+                .collect()
+                .await;
+            results.sort_by(|a, b| a.0.cmp(&b.0));
+
+            for (id, result) in results {
+                match result {
+                    Ok(()) => {
+                        self.set_lifecycle(&id, LifecycleState::Active);
+                        report.initialized.push(id.to_string());
+                    }
+                    Err(e) => {
+                        report.failed.push((id.to_string(), e.to_string()));
+                        self.set_lifecycle_error(&id, e.to_string());
+                        failed.insert(id);
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// 按依赖关系把插件分层，每层只依赖之前各层的插件
+    fn initialization_layers(&self) -> Result<Vec<Vec<PluginId>>, PluginError> {
+        let mut remaining: HashMap<PluginId, HashSet<PluginId>> = self
+            .plugins
+            .keys()
+            .map(|id| (id.clone(), self.registered_dependencies(id)))
+            .collect();
+        let mut layers = Vec::new();
+
+        while !remaining.is_empty() {
+            let mut layer: Vec<PluginId> = remaining
+                .iter()
+                .filter(|(_, dependencies)| dependencies.is_empty())
+                .map(|(id, _)| id.clone())
+                .collect();
+            if layer.is_empty() {
+                let mut cycle: Vec<String> = remaining.keys().map(|id| id.to_string()).collect();
+                cycle.sort();
+                return Err(PluginError::ValidationError(format!(
+                    "插件之间存在循环依赖: {}",
+                    cycle.join(", ")
                 )));
             }
+
+            layer.sort();
+            for id in &layer {
+                remaining.remove(id);
+            }
+            for dependencies in remaining.values_mut() {
+                for id in &layer {
+                    dependencies.remove(id);
+                }
+            }
+            layers.push(layer);
         }
-        Ok(())
+
+        Ok(layers)
+    }
+
+    /// 插件依赖中同样由本管理器注册的插件，其他依赖（如系统库）不影响初始化顺序
+    fn registered_dependencies(&self, id: &PluginId) -> HashSet<PluginId> {
+        self.plugins[id]
+            .metadata()
+            .dependencies
+            .iter()
+            .filter_map(|dependency| DependencySpec::parse(dependency).ok())
+            .flat_map(|spec| {
+                self.plugins
+                    .keys()
+                    .filter(move |other| other.name() == spec.name && *other != id)
+            })
+            .cloned()
+            .collect()
     }
 
     /// 关闭插件管理器
-    pub async fn shutdown(&mut self) -> Result<(), PluginError> {
-        // 关闭所有插件
-        for (name, plugin) in &mut self.plugins {
-            if let Err(e) = Arc::get_mut(plugin)
-                .ok_or_else(|| {
-                    PluginError::PluginError(format!("无法获取插件 {} 的可变引用", name))
-                })?
-                .shutdown()
+    ///
+    /// 并发关闭所有插件，每个插件最多等待 `shutdown_timeout` 秒。单个插件关闭失败或超时
+    /// 不影响其他插件，结果汇总在返回的 `ShutdownReport` 中
+    pub async fn shutdown(&mut self) -> Result<ShutdownReport, PluginError> {
+        // 停止后台保存并写入尚未保存的修改
+        if let Some(auto_save) = self.auto_save.take() {
+            auto_save.task.abort();
+            if self.is_dirty() {
+                write_config(&auto_save.path, &self.config).await?;
+                self.dirty.store(false, Ordering::SeqCst);
+            }
+        }
+
+        let mut plugins: Vec<(PluginId, Arc<ManagedPlugin>)> = self.plugins.drain().collect();
+        plugins.sort_by(|a, b| a.0.cmp(&b.0));
+        for (id, _) in &plugins {
+            if let Some(lifecycle) = self.lifecycle.get_mut(id) {
+                lifecycle.transition(LifecycleState::ShuttingDown);
+            }
+        }
+
+        let timeout = self.shutdown_timeout();
+        let results =
+            futures_util::future::join_all(plugins.into_iter().map(|(id, plugin)| async move {
+                let result = stop_plugin(plugin, timeout).await;
+                (id, result)
+            }))
+            .await;
+
+        let mut report = ShutdownReport::default();
+        for (id, result) in results {
+            match result {
+                Ok(()) => report.stopped.push(id.to_string()),
+                Err(StopFailure::Error(e)) => report.failed.push((id.to_string(), e)),
+                Err(StopFailure::TimedOut) => report.timed_out.push(id.to_string()),
+            }
+            self.events.emit(PlmEvent::PluginShutdown {
+                plugin: id.to_string(),
+            });
+        }
+        self.lifecycle.clear();
+        Ok(report)
+    }
+
+    /// 关闭单个插件的超时时间，`shutdown_timeout` 为 0 时不限制
+    fn shutdown_timeout(&self) -> Option<Duration> {
+        let seconds = self.config.global_settings.shutdown_timeout;
+        (seconds > 0).then(|| Duration::from_secs(seconds))
+    }
+
+    /// 应用新的项目配置，不重启宿主应用
+    ///
+    /// 比较当前配置和新配置：被移除或禁用的插件被关闭并注销；新启用的内置或后端插件被创建并初始化；
+    /// 配置有变化（或全局设置有变化）的内置或后端插件按新配置重新创建并初始化，
+    /// 其他已注册插件（如宿主应用注册的插件）通过 `set_config` 接收新的设置。
+    /// 新配置校验失败时不做任何修改。替换后的配置不标记为未保存，它来自配置文件本身。
+    pub async fn reload(&mut self, config: ProjectConfig) -> Result<ReloadReport, PluginError> {
+        config.validate()?;
+        let diff = ConfigDiff::between(&self.config, &config);
+        let project_root = PathBuf::from(config.get_project_root());
+        let mut report = ReloadReport::default();
+
+        for name in &diff.removed {
+            let ids = self.registered_ids(name);
+            for id in &ids {
+                if let Some(plugin) = self.plugins.remove(id) {
+                    self.shutdown_plugin(id, plugin).await;
+                }
+                self.lifecycle.remove(id);
+            }
+            if !ids.is_empty() {
+                report.removed.push(name.clone());
+            }
+        }
+
+        let mut changed = diff.changed.clone();
+        if diff.global_settings_changed {
+            changed.extend(diff.unchanged.iter().cloned());
+            changed.sort();
+        }
+        for name in &changed {
+            let plugin_config = &config.plugins[name];
+            let ids = self.registered_ids(name);
+            let result = match self
+                .configured_plugin(plugin_config, &config, &project_root)
+                .await
+            {
+                Ok(Some(plugin)) => {
+                    for id in &ids {
+                        if let Some(old) = self.plugins.remove(id) {
+                            self.shutdown_plugin(id, old).await;
+                        }
+                        self.lifecycle.remove(id);
+                    }
+                    self.start_plugin(name, plugin).await
+                }
+                Ok(None) if ids.is_empty() => continue,
+                Ok(None) => self.reconfigure_plugins(&ids, plugin_config).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) if ids.is_empty() => report.added.push(name.clone()),
+                Ok(()) => report.reloaded.push(name.clone()),
+                Err(e) => report.failed.push((name.clone(), e.to_string())),
+            }
+        }
+
+        for name in &diff.added {
+            let result = match self
+                .configured_plugin(&config.plugins[name], &config, &project_root)
                 .await
             {
-                eprintln!("警告: 插件 {} 关闭失败: {}", name, e);
+                Ok(Some(plugin)) => self.start_plugin(name, plugin).await,
+                Ok(None) => continue,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) => report.added.push(name.clone()),
+                Err(e) => report.failed.push((name.clone(), e.to_string())),
             }
         }
-        self.plugins.clear();
+
+        self.config = config;
+        self.refresh_permissions();
+        self.resolver.clear_cache();
+        self.config_tx.send_replace(self.config.clone());
+        self.events.emit(PlmEvent::ConfigReloaded {
+            added: report.added.len(),
+            removed: report.removed.len(),
+            reloaded: report.reloaded.len(),
+        });
+        Ok(report)
+    }
+
+    /// 按配置创建插件：先尝试内置后端（见 `backend_plugin`），再尝试已添加的插件工厂
+    async fn configured_plugin(
+        &self,
+        plugin_config: &PluginConfig,
+        project: &ProjectConfig,
+        project_root: &Path,
+    ) -> Result<Option<Box<dyn Plugin>>, PluginError> {
+        if let Some(plugin) = backend_plugin(plugin_config, project, project_root)? {
+            return Ok(Some(plugin));
+        }
+        let Some(factory) = self
+            .factories
+            .iter()
+            .find(|factory| factory_supports(factory.as_ref(), plugin_config))
+        else {
+            return Ok(None);
+        };
+        factory.validate_config(plugin_config)?;
+        Ok(Some(factory.create_plugin(plugin_config).await?))
+    }
+
+    /// 已注册的同名插件（包括各个变体）
+    fn registered_ids(&self, name: &str) -> Vec<PluginId> {
+        let mut ids: Vec<PluginId> = self
+            .plugins
+            .keys()
+            .filter(|id| id.name() == name)
+            .cloned()
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// 注册并初始化按配置创建的插件，初始化失败时插件保持注册并记为错误状态
+    async fn start_plugin(
+        &mut self,
+        name: &str,
+        plugin: Box<dyn Plugin>,
+    ) -> Result<(), PluginError> {
+        let id = name.into_plugin_id()?;
+        self.check_compat(plugin.as_ref())?;
+        let plugin = Arc::new(ManagedPlugin::new(plugin));
+        self.events.emit(PlmEvent::PluginRegistered {
+            plugin: id.to_string(),
+        });
+        self.lifecycle
+            .insert(id.clone(), PluginLifecycle::registered());
+        self.set_lifecycle(&id, LifecycleState::Initializing);
+        let result = plugin.initialize_exclusive().await;
+        match &result {
+            Ok(()) => self.set_lifecycle(&id, LifecycleState::Active),
+            Err(e) => self.set_lifecycle_error(&id, e.to_string()),
+        }
+        self.plugins.insert(id, plugin);
+        result
+    }
+
+    /// 把新的插件设置传给不由配置创建的已注册插件
+    async fn reconfigure_plugins(
+        &self,
+        ids: &[PluginId],
+        plugin_config: &PluginConfig,
+    ) -> Result<(), PluginError> {
+        for id in ids {
+            apply_settings(id, self.plugins[id].as_ref(), plugin_config).await?;
+        }
         Ok(())
     }
 
-    /// 注册插件（用于测试）
+    /// 关闭单个已注销的插件，关闭失败只记录警告
+    async fn shutdown_plugin(&mut self, id: &PluginId, plugin: Arc<ManagedPlugin>) {
+        if let Some(lifecycle) = self.lifecycle.get_mut(id) {
+            lifecycle.transition(LifecycleState::ShuttingDown);
+        }
+        match stop_plugin(plugin, self.shutdown_timeout()).await {
+            Ok(()) => {}
+            Err(StopFailure::Error(e)) => log::warn!("插件 {} 关闭失败: {}", id, e),
+            Err(StopFailure::TimedOut) => log::warn!("插件 {} 关闭超时", id),
+        }
+        self.events.emit(PlmEvent::PluginShutdown {
+            plugin: id.to_string(),
+        });
+    }
+
+    /// 管理器记录的插件生命周期状态，插件未注册时返回 None
+    pub fn plugin_state(&self, id: impl IntoPluginId) -> Option<PluginLifecycle> {
+        let id = id.into_plugin_id().ok()?;
+        self.lifecycle.get(&id).cloned()
+    }
+
+    fn set_lifecycle(&mut self, id: &PluginId, state: LifecycleState) {
+        if let Some(lifecycle) = self.lifecycle.get_mut(id) {
+            if !lifecycle.transition(state) {
+                log::warn!("插件 {} 不能从 {} 变为 {}", id, lifecycle.state, state);
+            }
+        }
+    }
+
+    fn set_lifecycle_error(&mut self, id: &PluginId, error: String) {
+        if let Some(lifecycle) = self.lifecycle.get_mut(id) {
+            // 依赖失败的插件没有进入 Initializing，直接记为错误
+            if lifecycle.state == LifecycleState::Registered {
+                lifecycle.transition(LifecycleState::Initializing);
+            }
+            lifecycle.fail(error);
+        }
+    }
+
+    /// 注册插件实例
+    ///
+    /// 同一标识已注册插件时返回错误。配置中有同名插件时与之关联：把配置中的插件设置
+    /// 传给插件（见 `Plugin::set_config`），插件在配置中已禁用时记录警告。
+    /// 注册后的插件尚未初始化，需要再调用 `initialize`
+    pub async fn register_plugin(
+        &mut self,
+        id: impl IntoPluginId,
+        plugin: Box<dyn Plugin>,
+    ) -> Result<(), PluginError> {
+        let id = id.into_plugin_id()?;
+        if self.plugins.contains_key(&id) {
+            return Err(PluginError::ValidationError(format!("插件 {} 已注册", id)));
+        }
+        self.check_compat(plugin.as_ref())?;
+        if let Some(plugin_config) = self.config.get_plugin(id.name()) {
+            if !plugin_config.enabled {
+                log::warn!("插件 {} 在配置中已禁用", id);
+            }
+            if !plugin_config.settings.is_empty() {
+                apply_settings(&id, plugin.as_ref(), plugin_config).await?;
+            }
+        }
+
+        self.events.emit(PlmEvent::PluginRegistered {
+            plugin: id.to_string(),
+        });
+        self.insert_plugin(id, plugin);
+        Ok(())
+    }
+
+    /// 添加插件工厂，并为配置中 `backend` 设置在其 `supported_types` 中、尚未注册的已启用插件
+    /// 创建并注册插件，返回注册的插件名称
+    ///
+    /// 工厂由管理器保留，`reload` 时用于创建新配置的插件
+    pub async fn register_factory(
+        &mut self,
+        factory: impl PluginFactory + 'static,
+    ) -> Result<Vec<String>, PluginError> {
+        self.factories.push(Arc::new(factory));
+        let mut registered = Vec::new();
+        for (name, result) in self.instantiate_from_factories().await {
+            result?;
+            registered.push(name);
+        }
+        Ok(registered)
+    }
+
+    /// 用已添加的插件工厂创建并注册配置中尚未注册的已启用插件
+    ///
+    /// 每个插件使用第一个 `supported_types` 包含其 `backend` 设置的工厂，按插件名称顺序创建，
+    /// 返回每个插件的名称和结果；单个插件创建失败不影响其他插件
+    async fn instantiate_from_factories(&mut self) -> Vec<(String, Result<(), PluginError>)> {
+        let mut plugin_configs: Vec<PluginConfig> = self
+            .config
+            .plugins
+            .values()
+            .filter(|p| p.enabled)
+            .cloned()
+            .collect();
+        plugin_configs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut results = Vec::new();
+        for plugin_config in &plugin_configs {
+            let Some(factory) = self
+                .factories
+                .iter()
+                .find(|factory| factory_supports(factory.as_ref(), plugin_config))
+                .cloned()
+            else {
+                continue;
+            };
+            let id = match plugin_config.name.as_str().into_plugin_id() {
+                Ok(id) if self.plugins.contains_key(&id) => continue,
+                Ok(id) => id,
+                Err(e) => {
+                    results.push((plugin_config.name.clone(), Err(e)));
+                    continue;
+                }
+            };
+            let result = async {
+                factory.validate_config(plugin_config)?;
+                let plugin = factory.create_plugin(plugin_config).await?;
+                self.register_plugin(id, plugin).await
+            }
+            .await;
+            results.push((plugin_config.name.clone(), result));
+        }
+        results
+    }
+
+    /// 注册插件，已有同名插件时替换，不与配置关联
+    #[doc(hidden)]
+    #[deprecated(note = "use `register_plugin`")]
     pub async fn register_plugin_for_test(
         &mut self,
-        name: String,
-        plugin: Arc<dyn Plugin>,
+        id: impl IntoPluginId,
+        plugin: Box<dyn Plugin>,
     ) -> Result<(), PluginError> {
-        self.plugins.insert(name, plugin);
+        let id = id.into_plugin_id()?;
+        self.events.emit(PlmEvent::PluginRegistered {
+            plugin: id.to_string(),
+        });
+        self.insert_plugin(id, plugin);
         Ok(())
     }
 
+    /// 移除尚未初始化的插件，构建器用其他插件替换配置创建的插件时使用
+    fn remove_unstarted(&mut self, id: &PluginId) {
+        self.plugins.remove(id);
+        self.lifecycle.remove(id);
+    }
+
+    /// 注册插件，已有同名插件时替换
+    fn insert_plugin(&mut self, id: PluginId, plugin: Box<dyn Plugin>) {
+        self.lifecycle
+            .insert(id.clone(), PluginLifecycle::registered());
+        let managed = ManagedPlugin::new(plugin);
+        managed.set_permissions(self.permission_guard(&id));
+        self.plugins.insert(id, Arc::new(managed));
+    }
+
+    /// 检查插件声明的最低 PLM 版本，设置了 `ignore_compat` 时只记录警告
+    fn check_compat(&self, plugin: &dyn Plugin) -> Result<(), PluginError> {
+        match compat::check_compatible(&plugin.metadata()) {
+            Err(e)
+                if self.config.global_settings.ignore_compat
+                    || compat::ignore_compat_from_env() =>
+            {
+                log::warn!("{}", e);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// 按当前配置更新已注册插件的权限
+    fn refresh_permissions(&self) {
+        for (id, plugin) in &self.plugins {
+            plugin.set_permissions(self.permission_guard(id));
+        }
+    }
+
+    /// 配置中为插件声明的权限
+    fn permission_guard(&self, id: &PluginId) -> Option<PermissionGuard> {
+        let plugin_config = self.config.get_plugin(id.name())?;
+        PermissionGuard::for_plugin(plugin_config, &self.config.global_settings)
+    }
+
+    /// 注册生命周期事件监听器
+    pub fn on_event(&self, listener: impl Fn(&PlmEvent) + Send + Sync + 'static) -> ListenerId {
+        self.events.add_listener(listener)
+    }
+
+    /// 订阅生命周期事件流，适合多个异步消费者（TUI、日志、指标等）同时观察
+    pub fn subscribe(&self) -> broadcast::Receiver<PlmEvent> {
+        self.events.subscribe()
+    }
+
+    /// 移除生命周期事件监听器
+    pub fn remove_event_listener(&self, id: ListenerId) -> bool {
+        self.events.remove_listener(id)
+    }
+
+    /// 当前的 Prometheus 指标，见 `metrics` 模块；指标是进程级的，包含同一进程中所有管理器的安装
+    #[cfg(feature = "metrics")]
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        metrics::snapshot()
+    }
+
     /// 获取插件
-    pub async fn get_plugin(&self, name: &str) -> Result<Arc<dyn Plugin>, PluginError> {
+    pub async fn get_plugin(&self, id: impl IntoPluginId) -> Result<Arc<dyn Plugin>, PluginError> {
+        let id = id.into_plugin_id()?;
         self.plugins
-            .get(name)
-            .cloned()
-            .ok_or_else(|| PluginError::NotFound(name.to_string()))
+            .get(&id)
+            .map(|plugin| plugin.clone() as Arc<dyn Plugin>)
+            .ok_or_else(|| PluginError::NotFound(id.to_string()))
     }
 
-    /// 列出所有插件名称
-    pub async fn list_plugins(&self) -> Vec<String> {
+    /// 列出所有插件标识
+    pub async fn plugin_ids(&self) -> Vec<PluginId> {
         self.plugins.keys().cloned().collect()
     }
 
+    /// 列出所有插件名称（插件标识的字符串形式）
+    pub async fn list_plugins(&self) -> Vec<String> {
+        self.plugins.keys().map(PluginId::to_string).collect()
+    }
+
+    /// 获取插件元数据
+    ///
+    /// 插件已注册时直接读取，否则使用元数据缓存中与当前配置匹配的记录
+    pub async fn plugin_metadata(
+        &self,
+        id: impl IntoPluginId,
+    ) -> Result<PluginMetadata, PluginError> {
+        let id = id.into_plugin_id()?;
+        if let Some(plugin) = self.plugins.get(&id) {
+            return Ok(plugin.metadata());
+        }
+
+        let cache = MetadataCache::load(&self.metadata_cache_path()).await;
+        self.cached_metadata(&cache, &id)
+            .ok_or_else(|| PluginError::NotFound(id.to_string()))
+    }
+
+    /// 列出已注册和已配置插件的元数据，按标识排序
+    ///
+    /// 只读取一次元数据缓存；既未注册也没有有效缓存的插件元数据为 `None`
+    pub async fn list_plugin_metadata(&self) -> Vec<(String, Option<PluginMetadata>)> {
+        let cache = MetadataCache::load(&self.metadata_cache_path()).await;
+        let mut ids: Vec<String> = self.plugins.keys().map(PluginId::to_string).collect();
+        for name in self.config.plugins.keys() {
+            if !ids.contains(name) {
+                ids.push(name.clone());
+            }
+        }
+        ids.sort();
+
+        ids.into_iter()
+            .map(|name| {
+                let metadata = name.as_str().into_plugin_id().ok().and_then(|id| {
+                    match self.plugins.get(&id) {
+                        Some(plugin) => Some(plugin.metadata()),
+                        None => self.cached_metadata(&cache, &id),
+                    }
+                });
+                (name, metadata)
+            })
+            .collect()
+    }
+
+    /// 把已注册插件的元数据写入缓存，并清除已不存在的插件记录
+    ///
+    /// 返回写入的记录数量
+    pub async fn refresh_metadata_cache(&self) -> Result<usize, PluginError> {
+        let path = self.metadata_cache_path();
+        let mut cache = MetadataCache::load(&path).await;
+
+        for (id, plugin) in &self.plugins {
+            let key = id.to_string();
+            let hash = metadata_cache::source_hash(self.config.plugins.get(&key));
+            cache.insert(&key, hash, plugin.metadata());
+        }
+
+        let mut known: Vec<String> = self.plugins.keys().map(PluginId::to_string).collect();
+        known.extend(self.config.plugins.keys().cloned());
+        cache.retain(&known);
+
+        cache.save(&path).await?;
+        Ok(self.plugins.len())
+    }
+
+    fn cached_metadata(&self, cache: &MetadataCache, id: &PluginId) -> Option<PluginMetadata> {
+        let key = id.to_string();
+        let hash = metadata_cache::source_hash(self.config.plugins.get(&key));
+        cache.get(&key, &hash).cloned()
+    }
+
+    /// 读取插件运行状态
+    pub async fn state(&self) -> Result<StateStore, PluginError> {
+        StateStore::load(&self.state_path()).await
+    }
+
+    /// 更新并保存运行状态；失败只记录警告，状态文件损坏时不覆盖
+    async fn record_state(&self, update: impl FnOnce(&mut StateStore)) {
+        let _guard = self.state_lock.lock().await;
+        let path = self.state_path();
+        let result = match StateStore::load(&path).await {
+            Ok(mut store) => {
+                update(&mut store);
+                store.save(&path).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            log::warn!("{}", e);
+        }
+    }
+
+    /// 删除配置中不存在或已禁用的插件仍记录的已安装版本，返回删除（`dry_run` 时为将要删除）的版本
+    ///
+    /// 单个版本删除失败不影响其他版本，所有错误汇总后返回
+    pub async fn prune_orphans(&self, dry_run: bool) -> Result<Vec<Orphan>, PluginError> {
+        let orphans = self.state().await?.orphans(&self.config);
+        if dry_run {
+            return Ok(orphans);
+        }
+
+        let mut removed = Vec::new();
+        let mut errors = Vec::new();
+        for orphan in orphans {
+            match self.remove_recorded(&orphan.plugin, &orphan.version).await {
+                Ok(()) => removed.push(orphan),
+                Err(e) => errors.push(format!("{} {}: {}", orphan.plugin, orphan.version, e)),
+            }
+        }
+        if errors.is_empty() {
+            Ok(removed)
+        } else {
+            Err(PluginError::PluginError(errors.join("; ")))
+        }
+    }
+
+    /// 删除运行状态中记录的已安装版本并移除记录，用于没有插件实现可以卸载的版本
+    ///
+    /// 只删除位于 `plugin_dir` 下的安装位置，其他位置（如系统包管理器安装的版本）只移除记录
+    async fn remove_recorded(&self, name: &str, version: &str) -> Result<(), PluginError> {
+        let state = self.state().await?;
+        let installed = state
+            .installed_version(name, version)
+            .ok_or_else(|| PluginError::NotFound(format!("{} {}", name, version)))?;
+
+        self.remove_generated_files(name, version).await;
+        if let Some(path) = &installed.path {
+            let plugin_dir = self.config.global_settings.plugin_path();
+            if path.starts_with(&plugin_dir) && path != &plugin_dir {
+                let removed = if path.is_dir() {
+                    tokio::fs::remove_dir_all(path).await
+                } else {
+                    tokio::fs::remove_file(path).await
+                };
+                match removed {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => {
+                        return Err(PluginError::IoError(format!(
+                            "删除 {} 失败: {}",
+                            path.display(),
+                            e
+                        )))
+                    }
+                }
+            } else {
+                log::warn!(
+                    "{} {} 安装在插件目录之外（{}），只移除记录",
+                    name,
+                    version,
+                    path.display()
+                );
+            }
+        }
+
+        self.record_state(|store| store.record_uninstall(name, version))
+            .await;
+        Ok(())
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.config
+            .global_settings
+            .plugin_path()
+            .join(state::STATE_FILE)
+    }
+
+    fn metadata_cache_path(&self) -> PathBuf {
+        self.config
+            .global_settings
+            .cache_path()
+            .join(metadata_cache::METADATA_CACHE_FILE)
+    }
+
     /// 安装插件
     pub async fn install_plugin(
+        &self,
+        id: impl IntoPluginId,
+        version: Option<&str>,
+        options: &InstallOptions,
+    ) -> Result<InstallResult, PluginError> {
+        let id = id.into_plugin_id()?;
+        let plugin = self.get_plugin(&id).await?;
+        let resolve_started = Instant::now();
+        let conflict = self.find_conflicts(Some(&id)).into_iter().next();
+        timings::record(Phase::Resolve, resolve_started.elapsed());
+        if let Some(conflict) = conflict {
+            return Err(conflict);
+        }
+        if options.dry_run {
+            let plan = self.plan_install(&id, version, options).await?;
+            return Ok(InstallResult {
+                plugin: plan.plugin,
+                version: plan.version,
+                path: PathBuf::new(),
+                duration: Duration::ZERO,
+                bytes_downloaded: 0,
+                was_cached: plan.kind == ChangeKind::AlreadyInstalled,
+                warnings: Vec::new(),
+            });
+        }
+        let version = resolve_version(plugin.as_ref(), version).await;
+        let version = version.as_str();
+        if let Some(target) = options.cross_target() {
+            return self
+                .install_for_target(&id, plugin.as_ref(), version, target, options)
+                .await;
+        }
+
+        self.events.emit(PlmEvent::InstallStarted {
+            plugin: id.to_string(),
+            version: version.to_string(),
+        });
+        let mut result = self
+            .run_install(&id, plugin.as_ref(), version, options)
+            .await;
+        if let Ok(installed) = &mut result {
+            let path = Some(installed.path.as_path()).filter(|path| !path.as_os_str().is_empty());
+            self.record_state(|store| store.record_install(id.name(), &installed.version, path))
+                .await;
+            // 插件已经安装完成，启动器和包装脚本生成失败只作为警告报告
+            if let Err(e) = self
+                .install_launchers(&id, plugin.as_ref(), installed)
+                .await
+            {
+                installed.warnings.push(e.to_string());
+            }
+            let warnings = self.write_wrappers(&id, installed).await;
+            installed.warnings.extend(warnings);
+        }
+        self.events.emit(match &result {
+            Ok(installed) => PlmEvent::InstallCompleted {
+                plugin: installed.plugin.clone(),
+                version: installed.version.clone(),
+                path: installed.to_string(),
+                duration: installed.duration,
+            },
+            Err(e) => PlmEvent::InstallFailed {
+                plugin: id.to_string(),
+                version: version.to_string(),
+                error: e.to_string(),
+            },
+        });
+        result
+    }
+
+    /// 为其他平台安装插件，见 `target` 模块
+    ///
+    /// 安装到 `options.install_dir` 下以插件名命名的目录，结果单独记入运行状态；
+    /// 不改变当前使用的版本，也不生成启动器和包装脚本
+    async fn install_for_target(
+        &self,
+        id: &PluginId,
+        plugin: &dyn Plugin,
+        version: &str,
+        target: &TargetPlatform,
+        options: &InstallOptions,
+    ) -> Result<InstallResult, PluginError> {
+        require_capability(id, plugin, Capability::CrossInstall)?;
+        let Some(dest) = &options.install_dir else {
+            return Err(PluginError::ValidationError(format!(
+                "为 {} 安装 {} 时需要指定安装目录",
+                target, id
+            )));
+        };
+        let mut options = options.clone();
+        options.install_dir = Some(
+            Path::new(dest)
+                .join(id.name())
+                .to_string_lossy()
+                .into_owned(),
+        );
+
+        self.events.emit(PlmEvent::InstallStarted {
+            plugin: id.to_string(),
+            version: version.to_string(),
+        });
+        let result = self.run_install(id, plugin, version, &options).await;
+        match &result {
+            Ok(installed) => {
+                let path =
+                    Some(installed.path.as_path()).filter(|path| !path.as_os_str().is_empty());
+                self.record_state(|store| {
+                    store.record_target_install(id.name(), target, &installed.version, path)
+                })
+                .await;
+                self.events.emit(PlmEvent::InstallCompleted {
+                    plugin: installed.plugin.clone(),
+                    version: installed.version.clone(),
+                    path: installed.to_string(),
+                    duration: installed.duration,
+                });
+            }
+            Err(e) => self.events.emit(PlmEvent::InstallFailed {
+                plugin: id.to_string(),
+                version: version.to_string(),
+                error: e.to_string(),
+            }),
+        }
+        result
+    }
+
+    /// 并发安装多个插件
+    ///
+    /// 互不依赖的插件同时安装（最多 `parallel_downloads` 个）；依赖同一批次中其他插件的插件
+    /// 等依赖安装完成后再安装，依赖安装失败时不再安装。单个插件失败不影响其他插件，
+    /// 结果按请求顺序汇总到 `InstallReport`
+    pub async fn install_many(
+        &self,
+        plugins: &[(&str, Option<&str>)],
+        options: &InstallOptions,
+    ) -> InstallReport {
+        let mut outcomes: Vec<Option<Result<InstallResult, String>>> = vec![None; plugins.len()];
+        let mut pending: Vec<(usize, PluginId, Option<&str>)> = Vec::new();
+        for (index, &(name, version)) in plugins.iter().enumerate() {
+            match name.into_plugin_id() {
+                Ok(id) => pending.push((index, id, version)),
+                Err(e) => outcomes[index] = Some(Err(e.to_string())),
+            }
+        }
+
+        let batch: HashSet<PluginId> = pending.iter().map(|(_, id, _)| id.clone()).collect();
+        let dependencies: HashMap<PluginId, HashSet<PluginId>> = pending
+            .iter()
+            .map(|(_, id, _)| {
+                let deps = if self.plugins.contains_key(id) {
+                    self.registered_dependencies(id)
+                        .into_iter()
+                        .filter(|dep| batch.contains(dep))
+                        .collect()
+                } else {
+                    HashSet::new()
+                };
+                (id.clone(), deps)
+            })
+            .collect();
+
+        let limit = self.config.global_settings.parallel_downloads.max(1) as usize;
+        let mut done: HashSet<PluginId> = HashSet::new();
+        let mut failed: HashSet<PluginId> = HashSet::new();
+        while !pending.is_empty() {
+            let (ready, waiting): (Vec<_>, Vec<_>) = pending
+                .into_iter()
+                .partition(|(_, id, _)| dependencies[id].iter().all(|dep| done.contains(dep)));
+            pending = waiting;
+            if ready.is_empty() {
+                for (index, id, _) in pending.drain(..) {
+                    outcomes[index] = Some(Err(format!("插件 {} 与同批次的插件存在循环依赖", id)));
+                }
+                break;
+            }
+
+            let mut layer = Vec::new();
+            for (index, id, version) in ready {
+                match dependencies[&id].iter().find(|dep| failed.contains(*dep)) {
+                    Some(dep) => {
+                        outcomes[index] = Some(Err(format!(
+                            "插件 {} 未安装: 依赖的插件 {} 安装失败",
+                            id, dep
+                        )));
+                        failed.insert(id.clone());
+                        done.insert(id);
+                    }
+                    None => layer.push((index, id, version)),
+                }
+            }
+
+            let results: Vec<(usize, PluginId, Result<InstallResult, PluginError>)> =
+                stream::iter(layer)
+                    .map(|(index, id, version)| async move {
+                        let result = self.install_plugin(&id, version, options).await;
+                        (index, id, result)
+                    })
+                    .buffer_unordered(limit)
+                    .collect()
+                    .await;
+            for (index, id, result) in results {
+                if result.is_err() {
+                    failed.insert(id.clone());
+                }
+                done.insert(id);
+                outcomes[index] = Some(result.map_err(|e| e.to_string()));
+            }
+        }
+
+        let mut report = InstallReport::default();
+        for ((name, _), outcome) in plugins.iter().zip(outcomes) {
+            match outcome {
+                Some(Ok(result)) => report.installed.push(result),
+                Some(Err(error)) => report.failed.push((name.to_string(), error)),
+                None => {}
+            }
+        }
+        report
+    }
+
+    /// 执行安装：生命周期钩子、代理环境变量和插件安装
+    async fn run_install(
+        &self,
+        id: &PluginId,
+        plugin: &dyn Plugin,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<InstallResult, PluginError> {
+        let started = Instant::now();
+
+        let mut warnings = Vec::new();
+        if let Some(pinned) = self
+            .config
+            .get_plugin(id.name())
+            .and_then(|p| p.get_version())
+        {
+            if version != "latest" && !same_version(pinned, version) {
+                warnings.push(format!(
+                    "安装 {} {}，但项目固定的版本为 {}",
+                    id, version, pinned
+                ));
+            }
+        }
+        let was_cached = !options.force
+            && options.cross_target().is_none()
+            && plugin.is_installed(version).await.unwrap_or(false);
+
+        // 配置中的代理传递给插件启动的子进程（git、下载脚本等），显式设置的变量优先
+        let mut options = options.clone();
+        for (key, value) in self.config.global_settings.proxy_env_vars() {
+            options.env_vars.entry(key).or_insert(value);
+        }
+
+        let plugin_name = id.to_string();
+        let mut context = HookContext {
+            plugin: &plugin_name,
+            version,
+            project_root: self.config.get_project_root(),
+            install_path: None,
+        };
+        self.run_lifecycle_hook(id.name(), HookEvent::PreInstall, &context)
+            .await?;
+
+        let (path, bytes_downloaded) =
+            download::count_downloaded(plugin.install(version, &options)).await;
+        let path = path?;
+
+        // 插件已经安装完成，post_install 失败只作为警告报告
+        context.install_path = Some(Path::new(&path));
+        if let Err(e) = self
+            .run_lifecycle_hook(id.name(), HookEvent::PostInstall, &context)
+            .await
+        {
+            warnings.push(e.to_string());
+        }
+
+        Ok(InstallResult {
+            plugin: id.to_string(),
+            version: version.to_string(),
+            path: path.into(),
+            duration: started.elapsed(),
+            bytes_downloaded,
+            was_cached,
+            warnings,
+        })
+    }
+
+    /// 为插件声明的启动器生成文件并记入运行状态，`launcher_integration` 关闭时跳过
+    async fn install_launchers(
+        &self,
+        id: &PluginId,
+        plugin: &dyn Plugin,
+        installed: &InstallResult,
+    ) -> Result<(), PluginError> {
+        let settings = &self.config.global_settings;
+        if !settings.launcher_integration || installed.path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let entries = plugin
+            .launcher_entries(&installed.version, &installed.path)
+            .await?;
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let Some(dir) = settings.launcher_path() else {
+            log::warn!(
+                "当前平台没有默认的启动器目录，跳过 {} 的启动器（可通过 launcher_dir 指定）",
+                id
+            );
+            return Ok(());
+        };
+
+        let launchers =
+            launcher::create_launchers(&dir, id.name(), &installed.path, &entries).await?;
+        self.record_state(|store| store.record_launchers(id.name(), &installed.version, launchers))
+            .await;
+        Ok(())
+    }
+
+    /// 按插件配置中的 `wrappers` 生成包装脚本并记入运行状态，返回无法生成的脚本的警告
+    async fn write_wrappers(&self, id: &PluginId, installed: &InstallResult) -> Vec<String> {
+        let Some(wrappers) = self
+            .config
+            .get_plugin(id.name())
+            .map(|config| &config.wrappers)
+            .filter(|wrappers| !wrappers.is_empty())
+        else {
+            return Vec::new();
+        };
+
+        let dir = self.config.global_settings.shims_path();
+        let mut written = Vec::new();
+        let mut warnings = Vec::new();
+        for (name, wrapper) in wrappers {
+            let Some(target) = shims::find_target(&installed.path, name, wrapper) else {
+                warnings.push(format!(
+                    "在 {} 中找不到包装脚本 {} 的可执行文件",
+                    installed.path.display(),
+                    name
+                ));
+                continue;
+            };
+            match shims::write_wrapper(&dir, id.name(), name, &target, wrapper).await {
+                Ok(path) => written.push(path),
+                Err(e) => warnings.push(e.to_string()),
+            }
+        }
+        self.record_state(|store| store.record_shims(id.name(), &installed.version, written))
+            .await;
+        warnings
+    }
+
+    /// 删除为已安装版本生成的启动器和包装脚本，失败只记录警告
+    async fn remove_generated_files(&self, name: &str, version: &str) {
+        let state = match self.state().await {
+            Ok(state) => state,
+            Err(e) => {
+                log::warn!("{}", e);
+                return;
+            }
+        };
+        let Some(installed) = state.installed_version(name, version) else {
+            return;
+        };
+        if let Err(e) = launcher::remove_launchers(&installed.launchers).await {
+            log::warn!("{}", e);
+        }
+        if let Err(e) = shims::remove_shims(&installed.shims).await {
+            log::warn!("{}", e);
+        }
+    }
+
+    /// 按配置同步插件：安装所有启用的插件中尚未安装的版本
+    ///
+    /// 配置中是固定版本时安装该版本；是范围约束时安装满足约束的最新稳定版本；
+    /// 未设置版本时使用插件从项目文件中读取的约束（见 `Plugin::project_constraint`），
+    /// 都没有时跳过。返回本次安装的 `name@version` 列表，`options.dry_run` 时只返回将要安装的列表
+    ///
+    /// 插件通过 `install_many` 并发安装，单个插件失败不影响其他插件，全部完成后汇总返回错误
+    ///
+    /// `options.platform` 为其他平台时把插件安装到 `options.install_dir` 下，见 `target` 模块
+    pub async fn sync_plugins(&self, options: &InstallOptions) -> Result<Vec<String>, PluginError> {
+        let pending = self
+            .pending_installs(options.quiet, options.cross_target())
+            .await?;
+        let requests: Vec<(&str, Option<&str>)> = pending
+            .iter()
+            .map(|(name, _, version)| (name.as_str(), Some(version.as_str())))
+            .collect();
+        let report = self.install_many(&requests, options).await;
+        if !report.is_success() {
+            let errors: Vec<String> = report
+                .failed
+                .iter()
+                .map(|(name, error)| format!("{}: {}", name, error))
+                .collect();
+            return Err(PluginError::PluginError(errors.join("; ")));
+        }
+        Ok(pending
+            .into_iter()
+            .map(|(name, _, version)| format!("{}@{}", name, version))
+            .collect())
+    }
+
+    /// 为尚未安装的插件生成按需安装脚本，代替 `sync_plugins` 的下载，见 `shims` 模块
+    ///
+    /// `program` 为 plm 可执行文件，`config_path` 为脚本运行时使用的配置文件。
+    /// 返回推迟安装的 `name@version` 列表
+    pub async fn sync_plugins_lazy(
+        &self,
+        program: &Path,
+        config_path: &Path,
+        quiet: bool,
+    ) -> Result<Vec<String>, PluginError> {
+        let dir = self.config.global_settings.shims_path();
+        let mut deferred = Vec::new();
+        for (name, _, version) in self.pending_installs(quiet, None).await? {
+            for tool in shims::tool_names(&self.config.plugins[&name]) {
+                shims::write_lazy_shim(&dir, &name, &tool, program, config_path).await?;
+            }
+            deferred.push(format!("{}@{}", name, version));
+        }
+        Ok(deferred)
+    }
+
+    /// 运行按需安装脚本前的准备：插件尚未安装时按 `lazy_install` 策略安装，
+    /// 返回要执行的可执行文件和包装配置
+    ///
+    /// `prompt` 策略下调用 `confirm` 询问是否安装，参数为 `name@version`，返回 false 时不安装
+    pub async fn prepare_lazy_shim(
         &self,
         name: &str,
+        tool: &str,
+        confirm: impl FnOnce(&str) -> bool,
+    ) -> Result<(PathBuf, WrapperConfig), PluginError> {
+        let plugin_config = self
+            .config
+            .get_plugin(name)
+            .filter(|config| config.enabled)
+            .ok_or_else(|| PluginError::NotFound(format!("已启用的插件 {}", name)))?;
+        let id = name.into_plugin_id()?;
+        let plugin = self.get_plugin(&id).await?;
+        let version = self
+            .configured_version(&id, plugin_config, plugin.as_ref())
+            .await?
+            .ok_or_else(|| PluginError::ValidationError(format!("插件 {} 没有配置版本", name)))?;
+        let wrapper = plugin_config
+            .wrappers
+            .get(tool)
+            .cloned()
+            .unwrap_or_default();
+
+        let install_path = if plugin.is_installed(&version).await? {
+            self.state()
+                .await?
+                .installed_version(name, &version)
+                .and_then(|installed| installed.path.clone())
+        } else {
+            let spec = format!("{}@{}", name, version);
+            if self.config.global_settings.lazy_install == LazyInstall::Prompt && !confirm(&spec) {
+                return Err(PluginError::PluginError(format!(
+                    "{} 尚未安装，运行 plm sync 安装",
+                    spec
+                )));
+            }
+            let installed = self
+                .install_plugin(&id, Some(&version), &InstallOptions::new().yes())
+                .await?;
+            for warning in &installed.warnings {
+                log::warn!("{}", warning);
+            }
+            Some(installed.path).filter(|path| !path.as_os_str().is_empty())
+        };
+
+        let target = install_path
+            .and_then(|path| shims::find_target(&path, tool, &wrapper))
+            .ok_or_else(|| {
+                PluginError::NotFound(format!("插件 {} {} 中的可执行文件 {}", name, version, tool))
+            })?;
+        Ok((target, wrapper))
+    }
+
+    /// 启用且已注册实现、按配置需要安装但尚未安装的插件及版本，按名称排序
+    ///
+    /// 指定 `target` 时按运行状态中为该平台安装的记录判断是否已安装
+    async fn pending_installs(
+        &self,
+        quiet: bool,
+        target: Option<&TargetPlatform>,
+    ) -> Result<Vec<(String, PluginId, String)>, PluginError> {
+        let state = match target {
+            Some(_) => Some(self.state().await?),
+            None => None,
+        };
+        let mut names: Vec<&String> = self.config.plugins.keys().collect();
+        names.sort();
+
+        let mut pending = Vec::new();
+        for name in names {
+            let plugin_config = &self.config.plugins[name];
+            if !plugin_config.enabled {
+                continue;
+            }
+
+            let id = name.into_plugin_id()?;
+            let Some(plugin) = self.plugins.get(&id) else {
+                if plugin_config.get_version().is_some() && !quiet {
+                    log::warn!("插件 {} 没有已注册的实现，跳过同步", name);
+                }
+                continue;
+            };
+
+            let Some(version) = self
+                .configured_version(&id, plugin_config, plugin.as_ref())
+                .await?
+            else {
+                continue;
+            };
+            let installed = match (target, &state) {
+                (Some(target), Some(state)) => state
+                    .target_version(name, target, &version)
+                    .and_then(|installed| installed.path.as_deref())
+                    .is_some_and(Path::exists),
+                _ => plugin.is_installed(&version).await?,
+            };
+            if installed {
+                continue;
+            }
+            pending.push((name.clone(), id, version));
+        }
+
+        Ok(pending)
+    }
+
+    /// 插件配置对应的版本，见 `sync_plugins`；既没有配置版本也没有项目约束时返回 None
+    async fn configured_version(
+        &self,
+        id: &PluginId,
+        plugin_config: &PluginConfig,
+        plugin: &dyn Plugin,
+    ) -> Result<Option<String>, PluginError> {
+        let project_root = Path::new(self.config.get_project_root());
+        let version = match plugin_config.get_version() {
+            Some(version) if Version::parse(version).is_ok() => version.to_string(),
+            Some(constraint) => resolve_constraint(id, plugin, constraint).await?,
+            None => match plugin.project_constraint(project_root).await? {
+                Some(constraint) => resolve_constraint(id, plugin, &constraint).await?,
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(version))
+    }
+
+    /// 更新插件
+    ///
+    /// 配置中的版本是范围约束（如 `^18`、`>=1.2, <2`）时，更新到满足约束的最新稳定版本，
+    /// 配置保持不变；是固定版本或未设置时更新到最新版本，并把新版本写入配置
+    pub async fn update_plugin(
+        &mut self,
+        id: impl IntoPluginId,
+    ) -> Result<UpdateResult, PluginError> {
+        let id = id.into_plugin_id()?;
+        let plugin = self.get_plugin(&id).await?;
+        require_capability(&id, plugin.as_ref(), Capability::Update)?;
+        let previous = self
+            .config
+            .get_plugin(id.name())
+            .and_then(|p| p.get_version())
+            .map(str::to_string);
+        let constraint = previous.as_deref().filter(|v| Version::parse(v).is_err());
+
+        let version = match constraint {
+            Some(constraint) => {
+                let target = resolve_constraint(&id, plugin.as_ref(), constraint).await?;
+                plugin.update(Some(&target)).await?
+            }
+            None => {
+                let version = plugin.update(None).await?;
+                let name = id.name().to_string();
+                let updated = version.clone();
+                self.with_config_mut(move |config| match config.get_plugin_mut(&name) {
+                    Some(plugin_config) => plugin_config.set_version(&updated),
+                    None => {
+                        let mut plugin_config = PluginConfig::new(&name);
+                        plugin_config.set_version(&updated);
+                        config.add_plugin(plugin_config);
+                    }
+                })
+                .await?;
+                version
+            }
+        };
+        self.record_state(|store| store.record_update(id.name(), &version))
+            .await;
+
+        Ok(UpdateResult {
+            plugin: id.to_string(),
+            constraint: constraint.map(str::to_string),
+            previous,
+            version,
+        })
+    }
+
+    /// 更新所有启用且已注册实现的插件，单个插件失败不影响其他插件
+    pub async fn update_all_plugins(&mut self) -> Vec<(String, Result<UpdateResult, PluginError>)> {
+        let mut results = Vec::new();
+        for (name, id) in self.updatable_plugins() {
+            let result = self.update_plugin(&id).await;
+            results.push((name, result));
+        }
+        results
+    }
+
+    /// 启用且已注册实现的插件，按名称排序
+    fn updatable_plugins(&self) -> Vec<(String, PluginId)> {
+        let mut names: Vec<String> = self
+            .config
+            .plugins
+            .values()
+            .filter(|p| p.enabled)
+            .map(|p| p.name.clone())
+            .collect();
+        names.sort();
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let id = name.as_str().into_plugin_id().ok()?;
+                self.plugins.contains_key(&id).then_some((name, id))
+            })
+            .collect()
+    }
+
+    /// 预演安装：解析版本和下载的制品，不做任何修改
+    pub async fn plan_install(
+        &self,
+        id: impl IntoPluginId,
         version: Option<&str>,
         options: &InstallOptions,
-    ) -> Result<String, PluginError> {
-        let plugin = self.get_plugin(name).await?;
-        let version = version.unwrap_or("latest");
-        plugin.install(version, options).await
+    ) -> Result<PlannedChange, PluginError> {
+        let id = id.into_plugin_id()?;
+        let plugin = self.get_plugin(&id).await?;
+        let artifact = match version.unwrap_or("latest") {
+            "latest" => Some(plugin.get_latest_version().await?),
+            version => find_artifact(plugin.as_ref(), version).await,
+        };
+        let version = match &artifact {
+            Some(info) => info.version.clone(),
+            None => version.unwrap_or("latest").to_string(),
+        };
+
+        let installed = !options.force && plugin.is_installed(&version).await.unwrap_or(false);
+        Ok(PlannedChange {
+            plugin: id.to_string(),
+            kind: if installed {
+                ChangeKind::AlreadyInstalled
+            } else {
+                ChangeKind::Install
+            },
+            previous: None,
+            download_url: artifact
+                .as_ref()
+                .filter(|_| !installed)
+                .map(|info| info.download_url.clone()),
+            size: artifact.filter(|_| !installed).and_then(|info| info.size),
+            version,
+        })
+    }
+
+    /// 预演卸载：确认版本已安装，不做任何修改
+    pub async fn plan_uninstall(
+        &self,
+        id: impl IntoPluginId,
+        version: &str,
+    ) -> Result<PlannedChange, PluginError> {
+        let id = id.into_plugin_id()?;
+        let plugin = self.get_plugin(&id).await?;
+        if !plugin.is_installed(version).await? {
+            return Err(PluginError::NotFound(format!("{} {}", id, version)));
+        }
+        Ok(PlannedChange {
+            plugin: id.to_string(),
+            kind: ChangeKind::Uninstall,
+            version: version.to_string(),
+            previous: None,
+            download_url: None,
+            size: None,
+        })
+    }
+
+    /// 预演更新：按 `update_plugin` 的规则解析目标版本，不安装也不修改配置
+    pub async fn plan_update(&self, id: impl IntoPluginId) -> Result<PlannedChange, PluginError> {
+        let id = id.into_plugin_id()?;
+        let plugin = self.get_plugin(&id).await?;
+        let previous = self
+            .config
+            .get_plugin(id.name())
+            .and_then(|p| p.get_version())
+            .map(str::to_string);
+
+        let artifact = match previous.as_deref().filter(|v| Version::parse(v).is_err()) {
+            Some(constraint) => {
+                let target = resolve_constraint(&id, plugin.as_ref(), constraint).await?;
+                find_artifact(plugin.as_ref(), &target)
+                    .await
+                    .unwrap_or_else(|| VersionInfo::new(&target, "", ""))
+            }
+            None => plugin.get_latest_version().await?,
+        };
+
+        // 与 `UpdateResult::changed` 一致：未设置版本时会写入配置，范围约束只看是否已安装
+        let installed = plugin
+            .is_installed(&artifact.version)
+            .await
+            .unwrap_or(false);
+        let unchanged = match previous.as_deref() {
+            Some(previous) if Version::parse(previous).is_ok() => {
+                same_version(previous, &artifact.version)
+            }
+            Some(_) => true,
+            None => false,
+        };
+        let kind = if installed && unchanged {
+            ChangeKind::UpToDate
+        } else {
+            ChangeKind::Update
+        };
+        let download = !installed && !artifact.download_url.is_empty();
+        Ok(PlannedChange {
+            plugin: id.to_string(),
+            kind,
+            previous,
+            download_url: download.then(|| artifact.download_url.clone()),
+            size: artifact.size.filter(|_| download),
+            version: artifact.version,
+        })
+    }
+
+    /// 预演 `update_all_plugins`
+    pub async fn plan_update_all(&self) -> Vec<(String, Result<PlannedChange, PluginError>)> {
+        let mut plans = Vec::new();
+        for (name, id) in self.updatable_plugins() {
+            let plan = self.plan_update(&id).await;
+            plans.push((name, plan));
+        }
+        plans
+    }
+
+    /// 批量升级所有启用的插件
+    ///
+    /// 跳过 `auto_update` 为 false、固定了具体版本、未注册实现或不支持更新的插件；
+    /// 其余插件按 `update_plugin` 的规则升级（范围约束内取最新版本），单个插件失败不影响其他插件
+    pub async fn upgrade_all_plugins(&mut self) -> UpgradeSummary {
+        let mut candidates: Vec<(String, bool, Option<String>)> = self
+            .config
+            .plugins
+            .values()
+            .filter(|p| p.enabled)
+            .map(|p| (p.name.clone(), p.auto_update, p.version.clone()))
+            .collect();
+        candidates.sort();
+
+        let mut summary = UpgradeSummary::default();
+        for (name, auto_update, version) in candidates {
+            let plugin = name
+                .as_str()
+                .into_plugin_id()
+                .ok()
+                .and_then(|id| self.plugins.get(&id).cloned());
+            let skip = if !auto_update {
+                Some(SkipReason::AutoUpdateDisabled)
+            } else if let Some(version) = version.filter(|v| Version::parse(v).is_ok()) {
+                Some(SkipReason::Pinned(version))
+            } else if plugin.is_none() {
+                Some(SkipReason::NotRegistered)
+            } else if plugin.is_some_and(|p| !p.supports(Capability::Update)) {
+                Some(SkipReason::Unsupported)
+            } else {
+                None
+            };
+            if let Some(reason) = skip {
+                summary.skipped.push((name, reason));
+                continue;
+            }
+
+            // 范围约束的 previous 是约束本身，需要与升级前已安装的版本比较
+            let installed = match self.get_plugin(name.as_str()).await {
+                Ok(plugin) => plugin.list_installed().await.unwrap_or_default(),
+                Err(_) => Vec::new(),
+            };
+            match self.update_plugin(name.as_str()).await {
+                Ok(result) => {
+                    let changed = match &result.constraint {
+                        Some(_) => !installed.iter().any(|v| same_version(v, &result.version)),
+                        None => result.changed(),
+                    };
+                    if changed {
+                        summary.upgraded.push(result);
+                    } else {
+                        summary.unchanged.push(result);
+                    }
+                }
+                Err(e) => summary.failed.push((name, e.to_string())),
+            }
+        }
+        summary
+    }
+
+    /// 合并远程可用版本和本地已安装版本，按版本号从新到旧排列
+    ///
+    /// 同一版本有多个平台的制品时优先使用当前平台的版本信息
+    pub async fn version_matrix(
+        &self,
+        id: impl IntoPluginId,
+    ) -> Result<Vec<VersionEntry>, PluginError> {
+        let id = id.into_plugin_id()?;
+        let plugin = self.get_plugin(&id).await?;
+        let platform = crate::config::current_platform();
+        let started = Instant::now();
+
+        let available = plugin.list_versions().await?;
+        let installed = plugin.list_installed().await?;
+        let plugin_config = self
+            .config
+            .get_plugin(id.name())
+            .cloned()
+            .unwrap_or_else(|| PluginConfig::new(id.name()));
+        let active = check::detect_active_version(&plugin_config).await;
+
+        let mut entries: Vec<VersionEntry> = Vec::new();
+        for info in available {
+            let compatible = info.supports_platform(platform);
+            match entries.iter_mut().find(|e| e.version == info.version) {
+                Some(entry) if compatible && !entry.platform_compatible => {
+                    entry.platform_compatible = true;
+                    entry.info = Some(info);
+                }
+                Some(_) => {}
+                None => entries.push(VersionEntry {
+                    version: info.version.clone(),
+                    installed: false,
+                    active: false,
+                    prerelease: info.prerelease,
+                    yanked: info.yanked,
+                    platform_compatible: compatible,
+                    info: Some(info),
+                }),
+            }
+        }
+        for version in &installed {
+            if !entries.iter().any(|e| same_version(&e.version, version)) {
+                entries.push(VersionEntry {
+                    version: version.clone(),
+                    installed: false,
+                    active: false,
+                    prerelease: false,
+                    yanked: false,
+                    platform_compatible: true,
+                    info: None,
+                });
+            }
+        }
+
+        for entry in &mut entries {
+            entry.installed = installed.iter().any(|v| same_version(v, &entry.version));
+            entry.active = active
+                .as_deref()
+                .is_some_and(|v| same_version(v, &entry.version));
+        }
+
+        entries.sort_by(
+            |a, b| match (Version::parse(&a.version), Version::parse(&b.version)) {
+                (Ok(a), Ok(b)) => b.cmp(&a),
+                (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+                (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+                (Err(_), Err(_)) => b.version.cmp(&a.version),
+            },
+        );
+        timings::record(Phase::Resolve, started.elapsed());
+        Ok(entries)
     }
 
     /// 卸载插件
-    pub async fn uninstall_plugin(&self, name: &str, version: &str) -> Result<(), PluginError> {
-        let plugin = self.get_plugin(name).await?;
-        plugin.uninstall(version).await
+    ///
+    /// 插件没有注册实现（如已从配置中移除）时，按运行状态中记录的安装位置删除
+    pub async fn uninstall_plugin(
+        &self,
+        id: impl IntoPluginId,
+        version: &str,
+    ) -> Result<(), PluginError> {
+        let id = id.into_plugin_id()?;
+        let plugin = match self.get_plugin(&id).await {
+            Ok(plugin) => plugin,
+            Err(e @ PluginError::NotFound(_)) => {
+                if self
+                    .state()
+                    .await?
+                    .installed_version(id.name(), version)
+                    .is_none()
+                {
+                    return Err(e);
+                }
+                return self.remove_recorded(id.name(), version).await;
+            }
+            Err(e) => return Err(e),
+        };
+        require_capability(&id, plugin.as_ref(), Capability::Uninstall)?;
+
+        let plugin_name = id.to_string();
+        let context = HookContext {
+            plugin: &plugin_name,
+            version,
+            project_root: self.config.get_project_root(),
+            install_path: None,
+        };
+        self.run_lifecycle_hook(id.name(), HookEvent::PreUninstall, &context)
+            .await?;
+
+        plugin.uninstall(version).await?;
+        self.remove_generated_files(id.name(), version).await;
+        self.record_state(|store| store.record_uninstall(id.name(), version))
+            .await;
+
+        if let Err(e) = self
+            .run_lifecycle_hook(id.name(), HookEvent::PostUninstall, &context)
+            .await
+        {
+            log::warn!("{}", e);
+        }
+        Ok(())
+    }
+
+    /// 执行插件配置中的生命周期钩子，`enable_hooks` 关闭时跳过
+    async fn run_lifecycle_hook(
+        &self,
+        name: &str,
+        event: HookEvent,
+        context: &HookContext<'_>,
+    ) -> Result<(), PluginError> {
+        if !self.config.global_settings.enable_hooks {
+            return Ok(());
+        }
+        match self.config.get_plugin(name) {
+            Some(plugin_config) => {
+                timings::measure(
+                    Phase::Hooks,
+                    hooks::run_hook(&plugin_config.hooks, event, context),
+                )
+                .await
+            }
+            None => Ok(()),
+        }
     }
 
     /// 发现插件
     pub async fn discover_plugins(&self) -> Result<usize, PluginError> {
         // 简化的发现逻辑 - 返回当前已注册的插件数量
-        Ok(self.plugins.len())
+        let count = self.plugins.len();
+        self.events.emit(PlmEvent::DiscoveryFinished { count });
+        Ok(count)
     }
 
     /// 验证所有插件
@@ -125,30 +1869,134 @@ impl PluginManager {
             errors: Vec::new(),
         };
 
-        for (name, plugin) in &self.plugins {
+        for (id, plugin) in &self.plugins {
             // 简化的验证逻辑 - 检查插件元数据
             let metadata = plugin.metadata();
-            if !metadata.name.is_empty() && !metadata.version.is_empty() {
-                summary.valid_plugins += 1;
-            } else {
+            if metadata.name.is_empty() || metadata.version.is_empty() {
+                summary.invalid_plugins += 1;
+                summary.errors.push(format!("插件 {} 元数据不完整", id));
+            } else if metadata.name != id.name() {
                 summary.invalid_plugins += 1;
-                summary.errors.push(format!("插件 {} 元数据不完整", name));
+                summary.errors.push(format!(
+                    "插件 {} 的元数据名称 {} 与标识不一致",
+                    id, metadata.name
+                ));
+            } else {
+                summary.valid_plugins += 1;
             }
         }
 
+        if let Some(conflict) = self.find_conflicts(None).into_iter().next() {
+            return Err(conflict);
+        }
+
         Ok(summary)
     }
 
+    /// 检测插件之间的依赖版本冲突
+    ///
+    /// 指定 `involving` 时只返回与该插件相关的冲突
+    pub fn find_conflicts(&self, involving: Option<&PluginId>) -> Vec<PluginError> {
+        // 依赖名 -> [(声明该依赖的插件, 依赖约束)]
+        let mut requirements: HashMap<String, Vec<(&PluginId, DependencySpec)>> = HashMap::new();
+        let mut ids: Vec<&PluginId> = self.plugins.keys().collect();
+        ids.sort();
+
+        for id in ids {
+            for dependency in self.plugins[id].metadata().dependencies {
+                // 无法解析的依赖声明由元数据校验负责报告
+                if let Ok(spec) = DependencySpec::parse(&dependency) {
+                    requirements
+                        .entry(spec.name.clone())
+                        .or_default()
+                        .push((id, spec));
+                }
+            }
+        }
+
+        let mut dependencies: Vec<&String> = requirements.keys().collect();
+        dependencies.sort();
+
+        let mut conflicts = Vec::new();
+        for dependency in dependencies {
+            let specs = &requirements[dependency];
+            for (i, (plugin_a, spec_a)) in specs.iter().enumerate() {
+                for (plugin_b, spec_b) in &specs[i + 1..] {
+                    if spec_a.req.intersects(&spec_b.req) {
+                        continue;
+                    }
+                    if involving.is_some_and(|id| id != *plugin_a && id != *plugin_b) {
+                        continue;
+                    }
+                    conflicts.push(PluginError::ConflictError {
+                        dependency: dependency.clone(),
+                        constraints: vec![
+                            format!("{} requires {}", plugin_a, spec_a),
+                            format!("{} requires {}", plugin_b, spec_b),
+                        ],
+                    });
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// 检查当前环境中的工具版本是否符合项目固定版本
+    pub async fn check_toolchain(&self) -> CheckReport {
+        check::check_project(&self.config).await
+    }
+
     /// 保存配置到文件
     pub async fn save_config(&self, path: &str) -> Result<(), PluginError> {
-        let config_json = serde_json::to_string_pretty(&self.config)
-            .map_err(|e| PluginError::ConfigError(format!("序列化配置失败: {}", e)))?;
+        write_config(Path::new(path), &self.config).await?;
+        self.dirty.store(false, Ordering::SeqCst);
+        Ok(())
+    }
 
-        fs::write(path, config_json)
-            .await
-            .map_err(|e| PluginError::ConfigError(format!("写入配置文件失败: {}", e)))?;
+    /// 启用自动保存：配置修改后经过 `debounce` 无新修改时写入 `path`，
+    /// `shutdown` 时写入所有尚未保存的修改
+    pub fn enable_auto_save(&mut self, path: impl Into<PathBuf>, debounce: Duration) {
+        self.disable_auto_save();
 
-        Ok(())
+        let path = path.into();
+        let mut rx = self.config_tx.subscribe();
+        let dirty = Arc::clone(&self.dirty);
+        let task_path = path.clone();
+        let task = tokio::spawn(async move {
+            while rx.changed().await.is_ok() {
+                // 防抖：等待修改停止后再写入
+                loop {
+                    tokio::select! {
+                        _ = tokio::time::sleep(debounce) => break,
+                        changed = rx.changed() => {
+                            if changed.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+
+                let config = rx.borrow_and_update().clone();
+                match write_config(&task_path, &config).await {
+                    // 写入期间又有新修改时保持未保存状态
+                    Ok(()) if !rx.has_changed().unwrap_or(false) => {
+                        dirty.store(false, Ordering::SeqCst)
+                    }
+                    Ok(()) => {}
+                    Err(e) => log::warn!("自动保存配置失败: {}", e),
+                }
+            }
+        });
+
+        self.auto_save = Some(AutoSave { path, task });
+    }
+
+    /// 停止自动保存，尚未写入的修改保留在内存中
+    pub fn disable_auto_save(&mut self) {
+        if let Some(auto_save) = self.auto_save.take() {
+            auto_save.task.abort();
+        }
     }
 
     /// 获取项目配置
@@ -156,29 +2004,396 @@ impl PluginManager {
         &self.config
     }
 
+    /// 订阅配置变更
+    ///
+    /// 每次通过管理器修改配置后，接收端都会收到最新的完整配置
+    pub fn watch_config(&self) -> watch::Receiver<ProjectConfig> {
+        self.config_tx.subscribe()
+    }
+
     /// 更新项目配置
     pub fn update_config(&mut self, config: ProjectConfig) {
         self.config = config;
+        self.notify_config_changed();
     }
 
     /// 添加插件配置
     pub fn add_plugin_config(&mut self, plugin_config: PluginConfig) {
         self.config.add_plugin(plugin_config);
+        self.notify_config_changed();
     }
 
     /// 移除插件配置
     pub fn remove_plugin_config(&mut self, name: &str) {
         self.config.remove_plugin(name);
+        self.notify_config_changed();
+    }
+
+    /// 在作用域内修改配置
+    ///
+    /// 闭包在配置副本上执行，返回后校验配置：校验失败时丢弃修改并返回错误，
+    /// 成功时替换配置、标记为未保存并通知订阅者
+    pub async fn with_config_mut<R>(
+        &mut self,
+        f: impl FnOnce(&mut ProjectConfig) -> R,
+    ) -> Result<R, PluginError> {
+        let mut config = self.config.clone();
+        let result = f(&mut config);
+        config.validate()?;
+
+        self.config = config;
+        self.notify_config_changed();
+        Ok(result)
+    }
+
+    /// 配置自上次保存后是否被修改
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::SeqCst)
+    }
+
+    /// 标记配置已修改，并向配置订阅者广播当前配置
+    fn notify_config_changed(&self) {
+        self.refresh_permissions();
+        self.dirty.store(true, Ordering::SeqCst);
+        self.config_tx.send_replace(self.config.clone());
     }
 
     /// 获取插件配置
     pub fn get_plugin_config(&self, name: &str) -> Option<&PluginConfig> {
         self.config.get_plugin(name)
     }
+
+    /// 解析插件设置中的值提供者引用（`env:`、`file:`、`exec:`）
+    ///
+    /// 普通值原样返回；提供者引用在首次访问时解析并缓存
+    pub async fn resolve_setting(
+        &self,
+        plugin_name: &str,
+        key: &str,
+    ) -> Result<Option<ResolvedValue>, PluginError> {
+        let plugin_config = self
+            .config
+            .get_plugin(plugin_name)
+            .ok_or_else(|| PluginError::NotFound(plugin_name.to_string()))?;
+        let Some(value) = plugin_config.get_setting(key) else {
+            return Ok(None);
+        };
+
+        Ok(Some(match self.resolver.resolve(value).await? {
+            Some(secret) => ResolvedValue::Secret(secret),
+            None => ResolvedValue::Plain(value.clone()),
+        }))
+    }
+}
+
+/// 插件管理器构建器
+///
+/// 供嵌入 PLM 的程序一次性组装插件管理器，不需要在构建后再修改管理器：
+///
+/// ```ignore
+/// let manager = PluginManager::builder()
+///     .with_config(config)
+///     .with_loader(MyLoader::new())
+///     .with_factory(SystemPackageFactory)
+///     .register(MyPlugin::default())
+///     .build()
+///     .await?;
+/// ```
+///
+/// 配置中每个已启用的插件按以下顺序创建：插件设置 `backend` 匹配的工厂、
+/// 支持其插件源类型的加载器（按添加顺序取第一个），最后是内置的后端插件。
+/// 通过 `register` 注册的插件以插件名称为标识，覆盖配置中的同名插件。
+/// 所有插件都按 `PluginManager::register_plugin` 注册：检查兼容性、关联配置中的插件设置，
+/// 重复注册同名插件时返回错误；工厂创建插件前先调用 `validate_config`。
+/// 构建后的管理器尚未初始化，需要再调用 `initialize`。
+#[derive(Default)]
+pub struct PluginManagerBuilder {
+    config: Option<ProjectConfig>,
+    factories: Vec<Arc<dyn PluginFactory>>,
+    loaders: Vec<Box<dyn PluginLoader>>,
+    plugins: Vec<Box<dyn Plugin>>,
+}
+
+impl PluginManagerBuilder {
+    /// 创建构建器，未设置配置时使用默认项目配置
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置项目配置
+    pub fn with_config(mut self, config: ProjectConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// 添加插件工厂，用于创建 `backend` 设置在其 `supported_types` 中的插件
+    pub fn with_factory(mut self, factory: impl PluginFactory + 'static) -> Self {
+        self.factories.push(Arc::new(factory));
+        self
+    }
+
+    /// 添加插件加载器，用于加载插件源类型受其支持的插件
+    pub fn with_loader(mut self, loader: impl PluginLoader + 'static) -> Self {
+        self.loaders.push(Box::new(loader));
+        self
+    }
+
+    /// 注册插件实例，标识取自插件元数据中的名称
+    pub fn register(mut self, plugin: impl Plugin + 'static) -> Self {
+        self.plugins.push(Box::new(plugin));
+        self
+    }
+
+    /// 创建插件管理器
+    pub async fn build(self) -> Result<PluginManager, PluginError> {
+        let config = self
+            .config
+            .unwrap_or_else(|| ProjectConfig::default_for_project("default", "."));
+        let plugin_configs: Vec<PluginConfig> = config
+            .plugins
+            .values()
+            .filter(|p| p.enabled)
+            .cloned()
+            .collect();
+        let mut manager = PluginManager::from_project_config(config).await?;
+
+        for plugin_config in &plugin_configs {
+            let factory = self
+                .factories
+                .iter()
+                .find(|factory| factory_supports(factory.as_ref(), plugin_config));
+            let plugin = if let Some(factory) = factory {
+                factory.validate_config(plugin_config)?;
+                factory.create_plugin(plugin_config).await?
+            } else if let Some((loader, source)) =
+                plugin_config.source.as_ref().and_then(|source| {
+                    self.loaders
+                        .iter()
+                        .find(|loader| loader.supports_source(&source.source_type))
+                        .map(|loader| (loader, source))
+                })
+            {
+                loader.validate_source(source).await?;
+                loader.load_plugin(source).await?
+            } else {
+                continue;
+            };
+            let id = plugin_config.name.as_str().into_plugin_id()?;
+            manager.remove_unstarted(&id);
+            manager.register_plugin(id, plugin).await?;
+        }
+
+        let mut registered = HashSet::new();
+        for plugin in self.plugins {
+            let id = plugin.metadata().name.as_str().into_plugin_id()?;
+            // 替换配置中的同名插件；重复注册同名插件时由 register_plugin 报错
+            if registered.insert(id.clone()) {
+                manager.remove_unstarted(&id);
+            }
+            manager.register_plugin(id, plugin).await?;
+        }
+        manager.factories = self.factories;
+        Ok(manager)
+    }
+}
+
+/// 插件设置 `backend` 是否在工厂的 `supported_types` 中
+fn factory_supports(factory: &dyn PluginFactory, plugin_config: &PluginConfig) -> bool {
+    plugin_config
+        .get_setting_for("backend", crate::config::current_platform())
+        .and_then(|v| v.as_str())
+        .is_some_and(|backend| factory.supported_types().iter().any(|t| t == backend))
+}
+
+/// 插件设置转换为 `Plugin::set_config` 使用的字符串形式
+fn string_settings(plugin_config: &PluginConfig) -> HashMap<String, String> {
+    plugin_config
+        .effective_settings()
+        .into_iter()
+        .map(|(key, value)| match value {
+            serde_json::Value::String(value) => (key, value),
+            value => (key, value.to_string()),
+        })
+        .collect()
+}
+
+/// 把插件设置传给插件（先 `configure`，再 `set_config`），插件不支持配置时只记录调试日志
+async fn apply_settings(
+    id: &PluginId,
+    plugin: &dyn Plugin,
+    plugin_config: &PluginConfig,
+) -> Result<(), PluginError> {
+    plugin.configure(plugin_config.settings_value()).await?;
+    match plugin.set_config(string_settings(plugin_config)).await {
+        Err(PluginError::Unsupported(_)) => {
+            log::debug!("插件 {} 不支持配置，忽略插件设置", id);
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+/// 插件没有声明某项能力时返回 `PluginError::Unsupported`
+fn require_capability(
+    id: &PluginId,
+    plugin: &dyn Plugin,
+    capability: Capability,
+) -> Result<(), PluginError> {
+    if plugin.supports(capability) {
+        Ok(())
+    } else {
+        Err(PluginError::Unsupported(format!(
+            "插件 {} 不支持 {}",
+            id, capability
+        )))
+    }
+}
+
+/// 在插件的可用版本中选择满足约束的最新稳定版本
+async fn resolve_constraint(
+    id: &PluginId,
+    plugin: &dyn Plugin,
+    constraint: &str,
+) -> Result<String, PluginError> {
+    let req = VersionReq::parse(constraint)?;
+    let started = Instant::now();
+    let versions = plugin.list_versions().await?;
+    let selected = project_files::select_version(versions, &req, crate::config::current_platform());
+    timings::record(Phase::Resolve, started.elapsed());
+    selected.ok_or_else(|| PluginError::NotFound(format!("{} 中满足 {} 的版本", id, constraint)))
+}
+
+/// 把 `latest` 解析为插件当前的最新版本，其他版本原样返回
+///
+/// 状态文件和事件中记录的应是实际安装的版本；插件无法查询最新版本时仍按 `latest` 交给插件安装
+async fn resolve_version(plugin: &dyn Plugin, version: Option<&str>) -> String {
+    match version.unwrap_or("latest") {
+        "latest" => match plugin.get_latest_version().await {
+            Ok(info) => info.version,
+            Err(e) => {
+                log::debug!("无法解析最新版本，按 latest 安装: {}", e);
+                "latest".to_string()
+            }
+        },
+        version => version.to_string(),
+    }
+}
+
+/// 在插件的可用版本中查找当前平台的指定版本，查询失败或不存在时返回 None
+async fn find_artifact(plugin: &dyn Plugin, version: &str) -> Option<VersionInfo> {
+    let platform = crate::config::current_platform();
+    plugin
+        .list_versions()
+        .await
+        .ok()?
+        .into_iter()
+        .find(|info| same_version(&info.version, version) && info.supports_platform(platform))
+}
+
+/// 插件没有正常关闭的原因
+enum StopFailure {
+    Error(String),
+    TimedOut,
+}
+
+/// 关闭单个插件，超过 `timeout` 后放弃等待
+async fn stop_plugin(
+    plugin: Arc<ManagedPlugin>,
+    timeout: Option<Duration>,
+) -> Result<(), StopFailure> {
+    let result = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, plugin.shutdown_exclusive())
+            .await
+            .map_err(|_| StopFailure::TimedOut)?,
+        None => plugin.shutdown_exclusive().await,
+    };
+    result.map_err(|e| StopFailure::Error(e.to_string()))
+}
+
+/// 按插件源类型（内置、静态注册表、OCI、GitHub、GitLab、crates.io）或插件设置 `backend`
+/// 创建插件，都不适用时返回 None
+fn backend_plugin(
+    config: &PluginConfig,
+    project: &ProjectConfig,
+    project_root: &Path,
+) -> Result<Option<Box<dyn Plugin>>, PluginError> {
+    let Some(source) = &config.source else {
+        return package_backend(config);
+    };
+    let settings = &project.global_settings;
+    let plugin: Box<dyn Plugin> = match source.source_type {
+        PluginSourceType::Builtin => match source.url.as_str() {
+            rustup::BUILTIN_RUSTUP => Box::new(RustupPlugin::from_config(config, project_root)),
+            node::BUILTIN_NODE => Box::new(NodePlugin::from_config(config, project_root)),
+            terraform::BUILTIN_TERRAFORM | terraform::BUILTIN_OPENTOFU | "tofu" => {
+                Box::new(TerraformPlugin::from_config(config, &source.url, settings)?)
+            }
+            other => {
+                return Err(PluginError::ConfigError(format!(
+                    "Unknown builtin plugin '{}' for {}",
+                    other, config.name
+                )))
+            }
+        },
+        PluginSourceType::Static => Box::new(StaticPlugin::from_config(config, source, settings)),
+        PluginSourceType::Oci => Box::new(OciPlugin::from_config(config, source, settings)),
+        PluginSourceType::Github => Box::new(github::plugin(config, source, settings)?),
+        PluginSourceType::Gitlab => Box::new(gitlab::plugin(config, source, settings)?),
+        PluginSourceType::Crates => Box::new(CratePlugin::from_config(config, source, settings)?),
+        PluginSourceType::Local
+        | PluginSourceType::Git
+        | PluginSourceType::Http
+        | PluginSourceType::Registry => return package_backend(config),
+    };
+    Ok(Some(plugin))
+}
+
+/// `builtin` 类型插件源支持的内置插件名称
+pub(crate) const BUILTIN_PLUGINS: &[&str] = &[
+    rustup::BUILTIN_RUSTUP,
+    node::BUILTIN_NODE,
+    terraform::BUILTIN_TERRAFORM,
+    terraform::BUILTIN_OPENTOFU,
+    "tofu",
+];
+
+/// 按插件设置 `backend` 创建系统包管理器或委托的版本管理器插件，未设置时返回 None
+fn package_backend(config: &PluginConfig) -> Result<Option<Box<dyn Plugin>>, PluginError> {
+    if system::is_system_backend(config) {
+        Ok(Some(Box::new(SystemPackagePlugin::from_config(config)?)))
+    } else if delegate::is_delegate_backend(config) {
+        Ok(Some(Box::new(DelegatePlugin::from_config(config)?)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// 比较两个版本字符串，忽略 `v` 前缀
+pub(crate) fn same_version(a: &str, b: &str) -> bool {
+    a.trim().trim_start_matches('v') == b.trim().trim_start_matches('v')
+}
+
+/// 将配置写入文件，并按 `config_backups` 轮换备份
+async fn write_config(path: &Path, config: &ProjectConfig) -> Result<(), PluginError> {
+    let config_json = config.to_json_string()?;
+
+    crate::backup::write_with_backups(path, &config_json, config.global_settings.config_backups)
+        .await
+        .map_err(|e| PluginError::ConfigError(format!("写入配置文件失败: {}", e)))
 }
 
 impl Drop for PluginManager {
     fn drop(&mut self) {
+        if let Some(auto_save) = self.auto_save.take() {
+            auto_save.task.abort();
+            if self.is_dirty() {
+                eprintln!(
+                    "警告: PluginManager 被销毁时配置有未保存的修改，请在销毁前调用 shutdown（{}）",
+                    auto_save.path.display()
+                );
+            }
+        }
+
         // 在析构时尝试清理资源
         if !self.plugins.is_empty() {
             eprintln!(
@@ -0,0 +1,990 @@
+//! PLM crates.io 插件源
+//!
+//! 管理以 crate 发布的 Rust 命令行工具，版本列表取自 crates.io：
+//!
+//! ```json
+//! "ripgrep": {
+//!   "version": "14.1.0",
+//!   "source": { "type": "crates", "url": "ripgrep" },
+//!   "settings": { "bins": ["rg"] }
+//! }
+//! ```
+//!
+//! 插件源地址为 crate 名称或 `https://crates.io/crates/<name>`，为空时使用插件名称。
+//! 安装时按 cargo-binstall 的方式优先使用预编译的二进制文件，依次尝试：
+//!
+//! 1. crate 的 `Cargo.toml` 中 `[package.metadata.binstall]` 声明的 `pkg-url`（支持 `overrides.<target>`）
+//! 2. crate 仓库（GitHub）中与版本对应的发布里目标平台的附件
+//! 3. cargo-quickinstall 预编译的二进制文件
+//!
+//! 都不可用时执行 `cargo install --locked --root <安装目录>` 从源码构建；跨平台安装只使用预编译文件。
+//! 启用 `verify_checksums` 时只使用能从 GitHub 取得 SHA-256 的预编译文件，否则从源码构建。
+//! 安装后可执行文件统一放在 `<安装目录>/bin` 下。
+//!
+//! 插件设置：`bins` 为要安装的可执行文件（默认取 crate 声明的全部可执行文件），
+//! `features` 为从源码构建时启用的特性，`from_source` 为 true 时不使用预编译文件，
+//! `locked` 为 false 时构建不使用 crate 自带的 `Cargo.lock`。
+
+use crate::config::{GlobalSettings, PluginConfig, PluginSource};
+use crate::core::same_version;
+use crate::download::{build_client, Downloader};
+use crate::github::{self, GithubReleases};
+use crate::permissions;
+use crate::releases::ReleaseSettings;
+use crate::system::run_command;
+use crate::target::TargetPlatform;
+use crate::traits::{
+    ArchiveFormat, Capability, InstallOptions, Plugin, PluginError, PluginMetadata, PluginStatus,
+    VersionInfo,
+};
+use crate::version::Version;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// crates.io API 地址
+const CRATES_API: &str = "https://crates.io/api/v1/crates";
+
+/// crate 文件的下载地址
+const CRATES_STATIC: &str = "https://static.crates.io/crates";
+
+/// cargo-quickinstall 的发布地址
+const QUICKINSTALL_RELEASES: &str =
+    "https://github.com/cargo-bins/cargo-quickinstall/releases/download";
+
+/// crates.io 要求请求带有可识别的 User-Agent
+const USER_AGENT: &str = concat!("plm/", env!("CARGO_PKG_VERSION"));
+
+/// 插件设置
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CrateSettings {
+    /// 要安装的可执行文件，为空时取 crate 声明的全部可执行文件
+    pub bins: Vec<String>,
+    /// 从源码构建时启用的特性
+    pub features: Vec<String>,
+    /// 不使用预编译文件
+    pub from_source: bool,
+    /// 构建时使用 crate 自带的 `Cargo.lock`
+    pub locked: bool,
+}
+
+impl Default for CrateSettings {
+    fn default() -> Self {
+        Self {
+            bins: Vec::new(),
+            features: Vec::new(),
+            from_source: false,
+            locked: true,
+        }
+    }
+}
+
+/// crate 的 `Cargo.toml` 中与安装相关的内容
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CrateManifest {
+    /// 声明的可执行文件
+    pub bins: Vec<String>,
+    /// `[package.metadata.binstall]`
+    pub binstall: Option<BinstallMetadata>,
+}
+
+/// cargo-binstall 元数据
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BinstallMetadata {
+    #[serde(default)]
+    pub pkg_url: Option<String>,
+    #[serde(default)]
+    pub pkg_fmt: Option<String>,
+    /// 各目标平台的覆盖设置
+    #[serde(default)]
+    pub overrides: HashMap<String, BinstallMetadata>,
+}
+
+impl CrateManifest {
+    /// 解析 `Cargo.toml`，没有 `[[bin]]` 时可执行文件与包同名
+    pub fn parse(content: &str) -> Result<Self, PluginError> {
+        let value: toml::Value = toml::from_str(content)
+            .map_err(|e| PluginError::ValidationError(format!("无效的 Cargo.toml: {}", e)))?;
+        let package = value.get("package");
+        let mut bins: Vec<String> = value
+            .get("bin")
+            .and_then(|bins| bins.as_array())
+            .map(|bins| {
+                bins.iter()
+                    .filter_map(|bin| bin.get("name")?.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if bins.is_empty() {
+            if let Some(name) = package.and_then(|p| p.get("name")).and_then(|n| n.as_str()) {
+                bins.push(name.to_string());
+            }
+        }
+        let binstall = package
+            .and_then(|p| p.get("metadata"))
+            .and_then(|m| m.get("binstall"))
+            .cloned()
+            .map(|binstall| {
+                binstall.try_into().map_err(|e| {
+                    PluginError::ValidationError(format!("无效的 binstall 元数据: {}", e))
+                })
+            })
+            .transpose()?;
+        Ok(Self { bins, binstall })
+    }
+}
+
+impl BinstallMetadata {
+    /// 目标平台生效的 `pkg-url` 和 `pkg-fmt`
+    fn for_target(&self, triple: &str) -> (Option<&str>, &str) {
+        let target = self.overrides.get(triple);
+        let pkg_url = target
+            .and_then(|t| t.pkg_url.as_deref())
+            .or(self.pkg_url.as_deref());
+        let pkg_fmt = target
+            .and_then(|t| t.pkg_fmt.as_deref())
+            .or(self.pkg_fmt.as_deref())
+            .unwrap_or("tgz");
+        (pkg_url, pkg_fmt)
+    }
+}
+
+/// binstall 的 `pkg-fmt` 对应的压缩格式和文件后缀，不支持的格式返回 None
+fn package_format(pkg_fmt: &str) -> Option<(ArchiveFormat, &'static [&'static str])> {
+    match pkg_fmt {
+        "tgz" | "tar.gz" => Some((ArchiveFormat::TarGz, &[".tgz", ".tar.gz"])),
+        "zip" => Some((ArchiveFormat::Zip, &[".zip"])),
+        "bin" => Some((ArchiveFormat::Raw, &[""])),
+        _ => None,
+    }
+}
+
+/// 替换 binstall 模板中的 `{ name }` 等变量（大括号内可以有空格）
+pub fn render_template(template: &str, vars: &HashMap<&str, String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            output.push_str(&rest[start..]);
+            return output;
+        };
+        let key = rest[start + 1..start + end].trim();
+        match vars.get(key) {
+            Some(value) => output.push_str(value),
+            None => output.push_str(&rest[start..start + end + 1]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// 目标平台的 Rust 目标三元组，按优先顺序
+pub fn target_triples(target: &TargetPlatform) -> Vec<String> {
+    let arch = match target.arch.as_str() {
+        "x86" => "i686",
+        "arm" => "armv7",
+        arch => arch,
+    };
+    match target.os.as_str() {
+        "linux" if arch == "armv7" => vec![
+            "armv7-unknown-linux-gnueabihf".to_string(),
+            "armv7-unknown-linux-musleabihf".to_string(),
+        ],
+        "linux" => vec![
+            format!("{}-unknown-linux-gnu", arch),
+            format!("{}-unknown-linux-musl", arch),
+        ],
+        "macos" => vec![format!("{}-apple-darwin", arch)],
+        "windows" => vec![format!("{}-pc-windows-msvc", arch)],
+        os => vec![format!("{}-{}", arch, os)],
+    }
+}
+
+/// crates.io 上的 crate 信息
+#[derive(Debug, Clone, Deserialize)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    krate: CrateInfo,
+    versions: Vec<CrateVersion>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CrateInfo {
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    homepage: Option<String>,
+    #[serde(default)]
+    repository: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CrateVersion {
+    num: String,
+    #[serde(default)]
+    yanked: bool,
+    #[serde(default)]
+    created_at: Option<String>,
+}
+
+/// 预编译文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Prebuilt {
+    url: String,
+    format: ArchiveFormat,
+    checksum: Option<String>,
+}
+
+/// 从 crates.io 安装的 Rust 工具
+pub struct CratePlugin {
+    name: String,
+    /// crate 名称
+    krate: String,
+    options: CrateSettings,
+    /// 各版本的安装目录 `<install_root>/<version>`
+    install_root: PathBuf,
+    settings: GlobalSettings,
+    status: PluginStatus,
+    config: RwLock<HashMap<String, String>>,
+    /// 首次查询后缓存的 crate 信息
+    info: RwLock<Option<CrateResponse>>,
+}
+
+impl CratePlugin {
+    /// 创建插件，安装到 `plugin_dir/<name>` 下
+    pub fn from_config(
+        config: &PluginConfig,
+        source: &PluginSource,
+        settings: &GlobalSettings,
+    ) -> Result<Self, PluginError> {
+        let url = source.url.trim().trim_end_matches('/');
+        let krate = url
+            .strip_prefix("https://crates.io/crates/")
+            .unwrap_or(url)
+            .split('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&config.name);
+        if !krate
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(PluginError::ConfigError(format!(
+                "无效的 crate 名称 '{}'",
+                krate
+            )));
+        }
+        Ok(Self {
+            name: config.name.clone(),
+            krate: krate.to_string(),
+            options: config.settings_as()?,
+            install_root: settings.plugin_path().join(&config.name),
+            settings: settings.clone(),
+            status: PluginStatus::Inactive,
+            config: RwLock::new(HashMap::new()),
+            info: RwLock::new(None),
+        })
+    }
+
+    /// 安装版本的目录
+    pub fn version_dir(&self, version: &str) -> PathBuf {
+        self.install_root.join(version.trim_start_matches('v'))
+    }
+
+    fn client(&self) -> Result<reqwest::Client, PluginError> {
+        build_client(
+            Duration::from_secs(self.settings.download_timeout),
+            self.settings.proxy.as_deref(),
+            self.settings.no_proxy.as_deref(),
+        )
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response, PluginError> {
+        permissions::check(|guard| guard.check_network(url))?;
+        let response = self
+            .client()?
+            .get(url)
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .send()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?;
+        match response.status() {
+            status if status.is_success() => Ok(response),
+            reqwest::StatusCode::NOT_FOUND => Err(PluginError::NotFound(url.to_string())),
+            status => Err(PluginError::NetworkError(format!(
+                "请求 {} 失败: {}",
+                url, status
+            ))),
+        }
+    }
+
+    /// 地址是否可以下载
+    async fn exists(&self, url: &str) -> bool {
+        if permissions::check(|guard| guard.check_network(url)).is_err() {
+            return false;
+        }
+        let Ok(client) = self.client() else {
+            return false;
+        };
+        client
+            .head(url)
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .send()
+            .await
+            .is_ok_and(|response| response.status().is_success())
+    }
+
+    async fn crate_info(&self) -> Result<CrateResponse, PluginError> {
+        if let Some(info) = self.info.read().ok().and_then(|i| i.clone()) {
+            return Ok(info);
+        }
+        let url = format!("{}/{}", CRATES_API, self.krate);
+        let info: CrateResponse = self
+            .get(&url)
+            .await
+            .map_err(|e| match e {
+                PluginError::NotFound(_) => {
+                    PluginError::NotFound(format!("crates.io 上没有 {}", self.krate))
+                }
+                e => e,
+            })?
+            .json()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("解析 {} 的响应失败: {}", url, e)))?;
+        if let Ok(mut cached) = self.info.write() {
+            *cached = Some(info.clone());
+        }
+        Ok(info)
+    }
+
+    /// 从 crate 文件中读取 `Cargo.toml`，失败时返回默认值
+    async fn manifest(&self, version: &str) -> CrateManifest {
+        let url = format!(
+            "{}/{}/{}-{}.crate",
+            CRATES_STATIC, self.krate, self.krate, version
+        );
+        let prefix = format!("{}-{}", self.krate, version);
+        let result = async {
+            let bytes = self
+                .get(&url)
+                .await?
+                .bytes()
+                .await
+                .map_err(|e| PluginError::NetworkError(format!("下载 {} 失败: {}", url, e)))?;
+            let content =
+                tokio::task::spawn_blocking(move || cargo_toml_from_crate(&bytes, &prefix))
+                    .await
+                    .map_err(|e| PluginError::IoError(format!("读取 crate 文件失败: {}", e)))??;
+            CrateManifest::parse(&content)
+        };
+        match result.await {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                log::debug!("读取 {} {} 的 Cargo.toml 失败: {}", self.krate, version, e);
+                CrateManifest {
+                    bins: vec![self.krate.clone()],
+                    binstall: None,
+                }
+            }
+        }
+    }
+
+    /// 要安装的可执行文件
+    fn bins(&self, manifest: &CrateManifest) -> Vec<String> {
+        if self.options.bins.is_empty() {
+            manifest.bins.clone()
+        } else {
+            self.options.bins.clone()
+        }
+    }
+
+    /// 按模块文档中的顺序查找目标平台的预编译文件
+    async fn find_prebuilt(
+        &self,
+        version: &str,
+        target: &TargetPlatform,
+        manifest: &CrateManifest,
+        repository: Option<&str>,
+    ) -> Option<Prebuilt> {
+        let triples = target_triples(target);
+        let windows = target.os == "windows";
+
+        if let Some(binstall) = &manifest.binstall {
+            for triple in &triples {
+                let (Some(pkg_url), pkg_fmt) = binstall.for_target(triple) else {
+                    continue;
+                };
+                let Some((format, suffixes)) = package_format(pkg_fmt) else {
+                    log::debug!("不支持的 pkg-fmt {}，跳过 binstall 元数据", pkg_fmt);
+                    continue;
+                };
+                for suffix in suffixes {
+                    let vars = HashMap::from([
+                        ("name", self.krate.clone()),
+                        ("version", version.to_string()),
+                        ("repo", repository.unwrap_or_default().to_string()),
+                        ("target", triple.clone()),
+                        ("archive-suffix", suffix.to_string()),
+                        ("archive-format", suffix.trim_start_matches('.').to_string()),
+                        ("binary-ext", if windows { ".exe" } else { "" }.to_string()),
+                    ]);
+                    let url = render_template(pkg_url, &vars);
+                    if self.exists(&url).await {
+                        return Some(Prebuilt {
+                            url,
+                            format,
+                            checksum: None,
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(repository) = repository.filter(|r| r.contains("github.com")) {
+            if let Some(prebuilt) = self.github_release_asset(repository, version, target).await {
+                return Some(prebuilt);
+            }
+        }
+
+        for triple in &triples {
+            let url = format!(
+                "{}/{}-{}/{}-{}-{}.tar.gz",
+                QUICKINSTALL_RELEASES, self.krate, version, self.krate, version, triple
+            );
+            if self.exists(&url).await {
+                return Some(Prebuilt {
+                    url,
+                    format: ArchiveFormat::TarGz,
+                    checksum: None,
+                });
+            }
+        }
+        None
+    }
+
+    /// 仓库中与版本对应的 GitHub 发布里目标平台的附件
+    async fn github_release_asset(
+        &self,
+        repository: &str,
+        version: &str,
+        target: &TargetPlatform,
+    ) -> Option<Prebuilt> {
+        let host = GithubReleases::new(&PluginSource::github(repository), &self.settings).ok()?;
+        let tags = [
+            format!("v{}", version),
+            version.to_string(),
+            format!("{}-v{}", self.krate, version),
+            format!("{}-{}", self.krate, version),
+        ];
+        for tag in tags {
+            let release = match host.release(&tag).await {
+                Ok(Some(release)) => release,
+                Ok(None) => continue,
+                Err(e) => {
+                    log::debug!("查询 {} 的发布 {} 失败: {}", repository, tag, e);
+                    return None;
+                }
+            };
+            let (asset, _) = ReleaseSettings::default().select(&release, target)?;
+            return Some(Prebuilt {
+                url: asset.download_url.clone(),
+                format: ArchiveFormat::from_file_name(&asset.name),
+                checksum: asset.checksum.clone(),
+            });
+        }
+        None
+    }
+
+    /// 下载并解压预编译文件，把可执行文件移到 `bin` 下
+    async fn install_prebuilt(
+        &self,
+        prebuilt: &Prebuilt,
+        version: &str,
+        bins: &[String],
+        target: &TargetPlatform,
+        dest: &Path,
+    ) -> Result<(), PluginError> {
+        let mut info = VersionInfo::new(version, &target.to_string(), &prebuilt.url);
+        info.archive_format = Some(prebuilt.format);
+        info.checksum = prebuilt.checksum.clone();
+        Downloader::from_settings(&self.settings)?
+            .fetch_and_extract(&info, None, dest)
+            .await?;
+        collect_bins(dest, bins, target.os == "windows").await
+    }
+
+    /// 执行 `cargo install` 从源码构建
+    async fn install_from_source(
+        &self,
+        version: &str,
+        bins: &[String],
+        dest: &Path,
+        options: &InstallOptions,
+    ) -> Result<(), PluginError> {
+        let mut cmd = vec![
+            "cargo".to_string(),
+            "install".to_string(),
+            self.krate.clone(),
+            "--version".to_string(),
+            format!("={}", version),
+            "--root".to_string(),
+            dest.to_string_lossy().into_owned(),
+            "--no-track".to_string(),
+        ];
+        if self.options.locked {
+            cmd.push("--locked".to_string());
+        }
+        if !self.options.features.is_empty() {
+            cmd.push("--features".to_string());
+            cmd.push(self.options.features.join(","));
+        }
+        for bin in &self.options.bins {
+            cmd.push("--bin".to_string());
+            cmd.push(bin.clone());
+        }
+        if options.force {
+            cmd.push("--force".to_string());
+        }
+        log::info!(
+            "{} {} 没有可用的预编译文件，从源码构建",
+            self.krate,
+            version
+        );
+        if let Err(e) = run_command(&cmd, Some(options)).await {
+            let _ = tokio::fs::remove_dir_all(dest).await;
+            return Err(e);
+        }
+        collect_bins(dest, bins, cfg!(windows)).await
+    }
+
+    fn bin_paths(&self, dir: &Path, bins: &[String], windows: bool) -> Vec<PathBuf> {
+        bins.iter()
+            .map(|bin| dir.join("bin").join(bin_file_name(bin, windows)))
+            .collect()
+    }
+}
+
+fn bin_file_name(bin: &str, windows: bool) -> String {
+    if windows {
+        format!("{}.exe", bin)
+    } else {
+        bin.to_string()
+    }
+}
+
+/// 从 crate 文件（tar.gz）中读取 `<name>-<version>/Cargo.toml`
+fn cargo_toml_from_crate(bytes: &[u8], prefix: &str) -> Result<String, PluginError> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+    let expected = Path::new(prefix).join("Cargo.toml");
+    let entries = archive
+        .entries()
+        .map_err(|e| PluginError::ValidationError(format!("无效的 crate 文件: {}", e)))?;
+    for entry in entries {
+        let mut entry =
+            entry.map_err(|e| PluginError::ValidationError(format!("无效的 crate 文件: {}", e)))?;
+        if entry.path().is_ok_and(|path| path == expected) {
+            let mut content = String::new();
+            entry.read_to_string(&mut content).map_err(|e| {
+                PluginError::ValidationError(format!("读取 Cargo.toml 失败: {}", e))
+            })?;
+            return Ok(content);
+        }
+    }
+    Err(PluginError::NotFound(format!(
+        "crate 文件中没有 {}",
+        expected.display()
+    )))
+}
+
+/// 在解压目录中查找各可执行文件，移到 `<dir>/bin` 下并设置可执行权限
+async fn collect_bins(dir: &Path, bins: &[String], windows: bool) -> Result<(), PluginError> {
+    let bin_dir = dir.join("bin");
+    tokio::fs::create_dir_all(&bin_dir)
+        .await
+        .map_err(|e| PluginError::IoError(format!("创建 {} 失败: {}", bin_dir.display(), e)))?;
+    let files = list_files(dir).await?;
+    for bin in bins {
+        let name = bin_file_name(bin, windows);
+        let target = bin_dir.join(&name);
+        if !target.is_file() {
+            // 单文件的预编译文件以下载的文件名保存，只安装一个可执行文件时使用它
+            let found = files
+                .iter()
+                .find(|path| path.file_name().is_some_and(|n| n == name.as_str()))
+                .or_else(|| (bins.len() == 1 && files.len() == 1).then(|| &files[0]))
+                .ok_or_else(|| {
+                    PluginError::InstallationError(format!("预编译文件中没有可执行文件 {}", name))
+                })?;
+            tokio::fs::rename(found, &target).await.map_err(|e| {
+                PluginError::IoError(format!("移动 {} 失败: {}", found.display(), e))
+            })?;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755))
+                .await
+                .map_err(|e| {
+                    PluginError::IoError(format!("设置 {} 的权限失败: {}", target.display(), e))
+                })?;
+        }
+    }
+    Ok(())
+}
+
+/// 目录下的全部文件
+async fn list_files(dir: &Path) -> Result<Vec<PathBuf>, PluginError> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&current)
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", current.display(), e)))?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", current.display(), e)))?
+        {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[async_trait]
+impl Plugin for CratePlugin {
+    fn metadata(&self) -> PluginMetadata {
+        let info = self.info.read().ok().and_then(|i| i.clone());
+        let latest = info.as_ref().and_then(|info| {
+            info.versions
+                .iter()
+                .find(|v| !v.yanked && !v.num.contains('-'))
+                .map(|v| v.num.clone())
+        });
+        PluginMetadata {
+            name: self.name.clone(),
+            version: latest.unwrap_or_else(|| PluginMetadata::default().version),
+            description: info
+                .as_ref()
+                .and_then(|info| info.krate.description.clone())
+                .unwrap_or_else(|| format!("Installed from crates.io ({})", self.krate)),
+            homepage: info.as_ref().and_then(|info| info.krate.homepage.clone()),
+            repository: info.as_ref().and_then(|info| info.krate.repository.clone()),
+            tags: vec!["crates".to_string(), "rust".to_string()],
+            ..PluginMetadata::default()
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Active;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Inactive;
+        Ok(())
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        let version = match version {
+            "latest" => self.get_latest_version().await?.version,
+            version => {
+                let info = self.crate_info().await?;
+                info.versions
+                    .iter()
+                    .find(|v| same_version(&v.num, version))
+                    .map(|v| v.num.clone())
+                    .ok_or_else(|| {
+                        PluginError::NotFound(format!("{} {}（crates.io）", self.krate, version))
+                    })?
+            }
+        };
+        let cross_target = options.cross_target().cloned();
+        let target = cross_target.clone().unwrap_or_else(TargetPlatform::host);
+        let dest = match &options.install_dir {
+            Some(dir) => Path::new(dir).join(&version),
+            None => self.version_dir(&version),
+        };
+        if !options.force && dest.join("bin").is_dir() {
+            return Ok(dest.to_string_lossy().into_owned());
+        }
+        if options.force {
+            let _ = tokio::fs::remove_dir_all(&dest).await;
+        }
+
+        let info = self.crate_info().await?;
+        let manifest = self.manifest(&version).await;
+        let bins = self.bins(&manifest);
+        let prebuilt = match self.options.from_source {
+            true => None,
+            false => {
+                self.find_prebuilt(
+                    &version,
+                    &target,
+                    &manifest,
+                    info.krate.repository.as_deref(),
+                )
+                .await
+            }
+        };
+        // 启用校验时不使用没有校验和的预编译文件
+        let prebuilt = match prebuilt {
+            Some(mut prebuilt) if prebuilt.checksum.is_none() && self.settings.verify_checksums => {
+                prebuilt.checksum =
+                    github::release_asset_checksum(&prebuilt.url, &self.settings).await;
+                if prebuilt.checksum.is_none() {
+                    log::info!(
+                        "{} 的预编译文件 {} 没有校验和，启用 verify_checksums 时不使用",
+                        self.krate,
+                        prebuilt.url
+                    );
+                }
+                prebuilt.checksum.is_some().then_some(prebuilt)
+            }
+            prebuilt => prebuilt,
+        };
+
+        match (prebuilt, cross_target) {
+            (Some(prebuilt), _) => {
+                self.install_prebuilt(&prebuilt, &version, &bins, &target, &dest)
+                    .await?
+            }
+            (None, Some(target)) => {
+                return Err(PluginError::NotFound(format!(
+                    "{} {} 没有 {} 的预编译文件，跨平台安装不支持从源码构建",
+                    self.krate, version, target
+                )))
+            }
+            (None, None) => {
+                self.install_from_source(&version, &bins, &dest, options)
+                    .await?
+            }
+        }
+        Ok(dest.to_string_lossy().into_owned())
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        let dest = self.version_dir(version);
+        if !dest.exists() {
+            return Err(PluginError::NotFound(format!("{} {}", self.name, version)));
+        }
+        tokio::fs::remove_dir_all(&dest)
+            .await
+            .map_err(|e| PluginError::IoError(format!("删除 {} 失败: {}", dest.display(), e)))
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        let info = self.crate_info().await?;
+        let mut versions: Vec<(Version, VersionInfo)> = info
+            .versions
+            .iter()
+            .filter(|v| !v.yanked)
+            .filter_map(|v| {
+                let parsed = Version::parse(&v.num).ok()?;
+                let mut version = VersionInfo::new(
+                    &v.num,
+                    "any",
+                    &format!("{}/{}/{}/download", CRATES_API, self.krate, v.num),
+                );
+                version.prerelease = v.num.contains('-');
+                version.release_date = v.created_at.clone();
+                Some((parsed, version))
+            })
+            .collect();
+        versions.sort_by(|(a, _), (b, _)| b.cmp(a));
+        Ok(versions.into_iter().map(|(_, info)| info).collect())
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        let mut entries = match tokio::fs::read_dir(&self.install_root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(PluginError::IoError(format!("读取安装目录失败: {}", e))),
+        };
+        let mut versions = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取安装目录失败: {}", e)))?
+        {
+            let version = entry.file_name().to_string_lossy().into_owned();
+            if self.is_installed(&version).await? {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+        Ok(versions)
+    }
+
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        let dir = self.version_dir(version);
+        if self.options.bins.is_empty() {
+            return Ok(dir.join("bin").is_dir());
+        }
+        Ok(self
+            .bin_paths(&dir, &self.options.bins, cfg!(windows))
+            .iter()
+            .all(|path| path.is_file()))
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        self.list_versions()
+            .await?
+            .into_iter()
+            .find(|info| !info.prerelease)
+            .ok_or_else(|| PluginError::NotFound(format!("{} releases", self.krate)))
+    }
+
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        let version = match version {
+            Some(version) => version.trim_start_matches('v').to_string(),
+            None => self.get_latest_version().await?.version,
+        };
+        self.install(&version, &InstallOptions::new()).await?;
+        Ok(version)
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        Ok(self.config.read().map(|c| c.clone()).unwrap_or_default())
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+        Ok(())
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        if let Ok(mut config) = self.config.write() {
+            config.insert(key.to_string(), value.to_string());
+        }
+        Ok(())
+    }
+
+    fn get_help(&self) -> String {
+        format!(
+            "{}: installs the Rust crate {} from crates.io, preferring prebuilt binaries",
+            self.name, self.krate
+        )
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::ListVersions,
+            Capability::Config,
+            Capability::CrossInstall,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = CrateManifest::parse(
+            r#"
+            [package]
+            name = "ripgrep"
+            version = "14.1.0"
+
+            [package.metadata.binstall]
+            pkg-url = "{ repo }/releases/download/{ version }/{ name }-{ version }-{ target }{ archive-suffix }"
+
+            [package.metadata.binstall.overrides.x86_64-pc-windows-msvc]
+            pkg-fmt = "zip"
+
+            [[bin]]
+            name = "rg"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest.bins, vec!["rg"]);
+        let binstall = manifest.binstall.unwrap();
+        assert_eq!(binstall.for_target("x86_64-unknown-linux-gnu").1, "tgz");
+        let (pkg_url, pkg_fmt) = binstall.for_target("x86_64-pc-windows-msvc");
+        assert_eq!(pkg_fmt, "zip");
+
+        let vars = HashMap::from([
+            ("repo", "https://github.com/BurntSushi/ripgrep".to_string()),
+            ("name", "ripgrep".to_string()),
+            ("version", "14.1.0".to_string()),
+            ("target", "x86_64-pc-windows-msvc".to_string()),
+            ("archive-suffix", ".zip".to_string()),
+        ]);
+        assert_eq!(
+            render_template(pkg_url.unwrap(), &vars),
+            "https://github.com/BurntSushi/ripgrep/releases/download/14.1.0/ripgrep-14.1.0-x86_64-pc-windows-msvc.zip"
+        );
+        assert_eq!(
+            render_template("{unknown}-{name}", &vars),
+            "{unknown}-ripgrep"
+        );
+
+        let plain = CrateManifest::parse("[package]\nname = \"tool\"\n").unwrap();
+        assert_eq!(plain.bins, vec!["tool"]);
+        assert!(plain.binstall.is_none());
+    }
+
+    #[test]
+    fn test_target_triples() {
+        let triples = |target: &str| target_triples(&target.parse().unwrap());
+        assert_eq!(
+            triples("linux-x64"),
+            vec!["x86_64-unknown-linux-gnu", "x86_64-unknown-linux-musl"]
+        );
+        assert_eq!(triples("darwin-arm64"), vec!["aarch64-apple-darwin"]);
+        assert_eq!(triples("windows-x86"), vec!["i686-pc-windows-msvc"]);
+    }
+
+    #[tokio::test]
+    async fn test_collect_bins() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("ripgrep-14.1.0-x86_64-unknown-linux-musl");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(nested.join("rg"), "#!/bin/sh\n").unwrap();
+        std::fs::write(nested.join("README.md"), "docs").unwrap();
+        collect_bins(dir.path(), &["rg".to_string()], false)
+            .await
+            .unwrap();
+        assert!(dir.path().join("bin/rg").is_file());
+        assert!(collect_bins(dir.path(), &["missing".to_string()], false)
+            .await
+            .is_err());
+
+        // 单文件的预编译文件按可执行文件名保存
+        let raw = tempfile::tempdir().unwrap();
+        std::fs::write(raw.path().join("tool-x86_64-unknown-linux-gnu"), "bin").unwrap();
+        collect_bins(raw.path(), &["tool".to_string()], false)
+            .await
+            .unwrap();
+        assert!(raw.path().join("bin/tool").is_file());
+    }
+}
@@ -0,0 +1,742 @@
+//! PLM 声明式插件
+//!
+//! 大多数以二进制发布的工具只需要描述“去哪里查版本、去哪里下载、压缩包里有哪些可执行文件”。
+//! [`declarative_plugin!`](crate::declarative_plugin) 根据这些描述生成完整的 `Plugin` 实现：
+//!
+//! ```ignore
+//! use plm::prelude::*;
+//!
+//! declarative_plugin! {
+//!     pub struct Ripgrep {
+//!         name: "ripgrep",
+//!         description: "Recursively search directories for a regex pattern",
+//!         versions: "https://api.github.com/repos/BurntSushi/ripgrep/releases",
+//!         download: "https://github.com/BurntSushi/ripgrep/releases/download/{version}/ripgrep-{version}-{arch}-{os}.{ext}",
+//!         binaries: ["ripgrep-{version}-{arch}-{os}/rg{exe}"],
+//!         checksum: "https://github.com/BurntSushi/ripgrep/releases/download/{version}/ripgrep-{version}-{arch}-{os}.{ext}.sha256",
+//!         os: { "linux" => "unknown-linux-musl", "macos" => "apple-darwin", "windows" => "pc-windows-msvc" },
+//!     }
+//! }
+//!
+//! let plugin = Ripgrep::new(&settings);
+//! ```
+//!
+//! URL 和可执行文件路径中可以使用以下占位符：
+//!
+//! - `{version}`：版本号（不带 `v` 前缀）
+//! - `{os}`：`linux`、`macos` 或 `windows`，可用 `os` 映射改名
+//! - `{arch}`：`x86_64` 或 `aarch64`，可用 `arch` 映射改名
+//! - `{ext}`：Windows 上为 `zip`，其他平台为 `tar.gz`
+//! - `{exe}`：Windows 上为 `.exe`，其他平台为空
+//!
+//! 版本列表地址可以返回 GitHub Releases API 的响应、版本号字符串数组，
+//! 或带 `versions` 字段的对象（字段为以版本号为键的对象，或带 `id`/`version` 字段的数组）。
+//! 校验和地址可以返回 `SHA256SUMS` 格式的清单或只包含哈希的文件。
+
+use crate::config::{current_platform, GlobalSettings};
+use crate::download::{build_client, Downloader};
+use crate::permissions;
+use crate::target::TargetPlatform;
+use crate::traits::{
+    Capability, InstallOptions, Plugin, PluginError, PluginMetadata, PluginStatus, VersionInfo,
+};
+use crate::version::Version;
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// 声明式插件的描述
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReleaseSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub homepage: Option<&'static str>,
+    /// 版本列表地址
+    pub versions_url: &'static str,
+    /// 制品下载地址模板
+    pub download_url: &'static str,
+    /// 校验和地址模板
+    pub checksum_url: Option<&'static str>,
+    /// 制品中可执行文件的相对路径模板，全部存在时视为已安装
+    pub binaries: &'static [&'static str],
+    /// `{os}` 的替换名，如 `("macos", "darwin")`
+    pub os_names: &'static [(&'static str, &'static str)],
+    /// `{arch}` 的替换名，如 `("x86_64", "amd64")`
+    pub arch_names: &'static [(&'static str, &'static str)],
+}
+
+impl ReleaseSpec {
+    /// 按当前平台替换模板中的占位符
+    pub fn render(&self, template: &str, version: &str) -> String {
+        self.render_for(
+            template,
+            version,
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+        )
+    }
+
+    /// 按指定的操作系统和架构替换模板中的占位符
+    pub fn render_for(&self, template: &str, version: &str, os: &str, arch: &str) -> String {
+        let rename = |names: &[(&str, &'static str)], value: &str| -> String {
+            names
+                .iter()
+                .find(|(from, _)| *from == value)
+                .map_or(value, |(_, to)| *to)
+                .to_string()
+        };
+        let windows = os == "windows";
+        template
+            .replace("{version}", version.trim_start_matches('v'))
+            .replace("{os}", &rename(self.os_names, os))
+            .replace("{arch}", &rename(self.arch_names, arch))
+            .replace("{ext}", if windows { "zip" } else { "tar.gz" })
+            .replace("{exe}", if windows { ".exe" } else { "" })
+    }
+}
+
+/// 从版本列表响应中提取 `(版本号, 是否预发布)`
+pub fn parse_version_list(value: &serde_json::Value) -> Vec<(String, bool)> {
+    let entry = |item: &serde_json::Value| -> Option<(String, bool)> {
+        let version = match item {
+            serde_json::Value::String(version) => version.as_str(),
+            serde_json::Value::Object(release) => {
+                if release.get("draft").and_then(|v| v.as_bool()) == Some(true) {
+                    return None;
+                }
+                ["tag_name", "version", "id"]
+                    .iter()
+                    .find_map(|key| release.get(*key).and_then(|v| v.as_str()))?
+            }
+            _ => return None,
+        };
+        let version = version.trim_start_matches('v').to_string();
+        let prerelease =
+            item.get("prerelease").and_then(|v| v.as_bool()) == Some(true) || version.contains('-');
+        Some((version, prerelease))
+    };
+
+    match value {
+        serde_json::Value::Array(items) => items.iter().filter_map(entry).collect(),
+        serde_json::Value::Object(object) => match object.get("versions") {
+            Some(serde_json::Value::Object(versions)) => versions
+                .keys()
+                .map(|version| {
+                    let version = version.trim_start_matches('v').to_string();
+                    let prerelease = version.contains('-');
+                    (version, prerelease)
+                })
+                .collect(),
+            Some(versions) => parse_version_list(versions),
+            None => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// 从校验和文件中找到制品的哈希：`SHA256SUMS` 清单按文件名匹配，单个哈希直接返回
+pub fn parse_checksum(content: &str, artifact: &str) -> Option<String> {
+    let mut lines = content.lines().map(str::trim).filter(|l| !l.is_empty());
+    let first = lines.clone().next()?;
+    let mut parts = first.split_whitespace();
+    let hash = parts.next()?;
+    if parts.next().is_none() {
+        return Some(hash.to_string());
+    }
+    lines.find_map(|line| {
+        let (hash, file) = line.split_once(char::is_whitespace)?;
+        let file = file.trim().trim_start_matches('*');
+        (file == artifact || file.rsplit('/').next() == Some(artifact)).then(|| hash.to_string())
+    })
+}
+
+/// 根据 [`ReleaseSpec`] 下载和管理二进制发布的插件
+pub struct ReleasePlugin {
+    spec: ReleaseSpec,
+    /// 各版本的安装目录 `<install_root>/<version>`
+    install_root: PathBuf,
+    settings: GlobalSettings,
+    status: PluginStatus,
+    config: RwLock<HashMap<String, String>>,
+}
+
+impl ReleasePlugin {
+    /// 创建插件，安装到 `plugin_dir/<name>` 下
+    pub fn new(spec: ReleaseSpec, settings: &GlobalSettings) -> Self {
+        Self {
+            install_root: settings.plugin_path().join(spec.name),
+            spec,
+            settings: settings.clone(),
+            status: PluginStatus::Inactive,
+            config: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 插件描述
+    pub fn spec(&self) -> &ReleaseSpec {
+        &self.spec
+    }
+
+    /// 安装版本的目录
+    pub fn version_dir(&self, version: &str) -> PathBuf {
+        self.install_root.join(version.trim_start_matches('v'))
+    }
+
+    fn binary_paths(&self, version: &str) -> Vec<PathBuf> {
+        self.binary_paths_in(&self.version_dir(version), version, &TargetPlatform::host())
+    }
+
+    /// 目标平台的制品解压到 `dir` 后可执行文件的路径
+    fn binary_paths_in(&self, dir: &Path, version: &str, target: &TargetPlatform) -> Vec<PathBuf> {
+        self.spec
+            .binaries
+            .iter()
+            .map(|binary| {
+                dir.join(
+                    self.spec
+                        .render_for(binary, version, &target.os, &target.arch),
+                )
+            })
+            .collect()
+    }
+
+    async fn get_text(&self, url: &str) -> Result<String, PluginError> {
+        permissions::check(|guard| guard.check_network(url))?;
+        build_client(
+            Duration::from_secs(self.settings.download_timeout),
+            self.settings.proxy.as_deref(),
+            self.settings.no_proxy.as_deref(),
+        )?
+        .get(url)
+        .header(reqwest::header::USER_AGENT, "plm")
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?
+        .text()
+        .await
+        .map_err(|e| PluginError::NetworkError(format!("读取 {} 的响应失败: {}", url, e)))
+    }
+
+    /// 当前平台的制品信息，声明了校验和地址时附带校验和
+    pub async fn artifact(&self, version: &str) -> Result<VersionInfo, PluginError> {
+        self.artifact_for(version, &TargetPlatform::host()).await
+    }
+
+    /// 指定平台的制品信息，声明了校验和地址时附带校验和
+    pub async fn artifact_for(
+        &self,
+        version: &str,
+        target: &TargetPlatform,
+    ) -> Result<VersionInfo, PluginError> {
+        let render = |template: &str| {
+            self.spec
+                .render_for(template, version, &target.os, &target.arch)
+        };
+        let download_url = render(self.spec.download_url);
+        let platform = if target.is_host() {
+            current_platform().to_string()
+        } else {
+            target.to_string()
+        };
+        let mut info = VersionInfo::new(version.trim_start_matches('v'), &platform, &download_url);
+        info.entry_points = self
+            .spec
+            .binaries
+            .iter()
+            .map(|binary| render(binary))
+            .collect();
+        // 从 vendor 目录安装时不访问网络，校验和取自 vendor 索引
+        let checksum_url = self
+            .spec
+            .checksum_url
+            .filter(|_| self.settings.vendor_dir.is_none());
+        if let Some(checksum_url) = checksum_url {
+            let artifact = download_url.rsplit('/').next().unwrap_or_default();
+            match self.get_text(&render(checksum_url)).await {
+                Ok(content) => info.checksum = parse_checksum(&content, artifact),
+                // 未启用 verify_checksums 时缺少校验和不影响安装
+                Err(e) => log::debug!("获取 {} 的校验和失败: {}", artifact, e),
+            }
+        }
+        Ok(info)
+    }
+}
+
+#[async_trait]
+impl Plugin for ReleasePlugin {
+    fn metadata(&self) -> PluginMetadata {
+        PluginMetadata {
+            name: self.spec.name.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            description: self.spec.description.to_string(),
+            homepage: self.spec.homepage.map(str::to_string),
+            tags: vec!["declarative".to_string()],
+            ..PluginMetadata::default()
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Active;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Inactive;
+        Ok(())
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        let version = match version {
+            "latest" => self.get_latest_version().await?.version,
+            version => version.trim_start_matches('v').to_string(),
+        };
+        let target = options
+            .cross_target()
+            .cloned()
+            .unwrap_or_else(TargetPlatform::host);
+        let dest = match &options.install_dir {
+            Some(dir) => Path::new(dir).join(&version),
+            None => self.version_dir(&version),
+        };
+        let binaries = self.binary_paths_in(&dest, &version, &target);
+        if !options.force && !binaries.is_empty() && binaries.iter().all(|path| path.is_file()) {
+            return Ok(dest.to_string_lossy().into_owned());
+        }
+
+        let info = self.artifact_for(&version, &target).await?;
+        if options.force {
+            let _ = tokio::fs::remove_dir_all(&dest).await;
+        }
+        Downloader::from_settings(&self.settings)?
+            .fetch_and_extract(&info, None, &dest)
+            .await?;
+
+        if let Some(missing) = binaries.into_iter().find(|p| !p.is_file()) {
+            return Err(PluginError::InstallationError(format!(
+                "{} {} 的制品中没有 {}",
+                self.spec.name,
+                version,
+                missing.display()
+            )));
+        }
+        Ok(dest.to_string_lossy().into_owned())
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        let dest = self.version_dir(version);
+        if !dest.exists() {
+            return Err(PluginError::NotFound(format!(
+                "{} {}",
+                self.spec.name, version
+            )));
+        }
+        tokio::fs::remove_dir_all(&dest)
+            .await
+            .map_err(|e| PluginError::IoError(format!("删除 {} 失败: {}", dest.display(), e)))
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        let url = self.spec.versions_url;
+        let value: serde_json::Value = serde_json::from_str(&self.get_text(url).await?)
+            .map_err(|e| PluginError::NetworkError(format!("解析 {} 的响应失败: {}", url, e)))?;
+
+        let mut versions: Vec<(Version, VersionInfo)> = parse_version_list(&value)
+            .into_iter()
+            .filter_map(|(version, prerelease)| {
+                let parsed = Version::parse(&version).ok()?;
+                let mut info = VersionInfo::new(
+                    &version,
+                    current_platform(),
+                    &self.spec.render(self.spec.download_url, &version),
+                );
+                info.prerelease = prerelease;
+                Some((parsed, info))
+            })
+            .collect();
+        // 最新的在前，同一版本号的正式版排在预发布版之前
+        versions.sort_by(|(a, a_info), (b, b_info)| {
+            b.cmp(a).then(a_info.prerelease.cmp(&b_info.prerelease))
+        });
+        Ok(versions.into_iter().map(|(_, info)| info).collect())
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        let mut entries = match tokio::fs::read_dir(&self.install_root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(PluginError::IoError(format!("读取安装目录失败: {}", e))),
+        };
+        let mut versions = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取安装目录失败: {}", e)))?
+        {
+            let version = entry.file_name().to_string_lossy().into_owned();
+            if self.is_installed(&version).await? {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+        Ok(versions)
+    }
+
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        let binaries = self.binary_paths(version);
+        Ok(!binaries.is_empty() && binaries.iter().all(|path| path.is_file()))
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        self.list_versions()
+            .await?
+            .into_iter()
+            .find(|info| !info.prerelease)
+            .ok_or_else(|| PluginError::NotFound(format!("{} releases", self.spec.name)))
+    }
+
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        let version = match version {
+            Some(version) => version.trim_start_matches('v').to_string(),
+            None => self.get_latest_version().await?.version,
+        };
+        self.install(&version, &InstallOptions::new()).await?;
+        Ok(version)
+    }
+
+    async fn switch_version(&self, version: &str) -> Result<(), PluginError> {
+        if self.is_installed(version).await? {
+            Ok(())
+        } else {
+            Err(PluginError::NotFound(format!(
+                "{} {}",
+                self.spec.name, version
+            )))
+        }
+    }
+
+    async fn verify_installation(&self, version: &str) -> Result<bool, PluginError> {
+        self.is_installed(version).await
+    }
+
+    async fn cleanup(&self) -> Result<(), PluginError> {
+        Ok(())
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        Ok(self.config.read().map(|c| c.clone()).unwrap_or_default())
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+        Ok(())
+    }
+
+    async fn get_config_value(&self, key: &str) -> Result<Option<String>, PluginError> {
+        Ok(self.config.read().ok().and_then(|c| c.get(key).cloned()))
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        if let Ok(mut config) = self.config.write() {
+            config.insert(key.to_string(), value.to_string());
+        }
+        Ok(())
+    }
+
+    async fn execute_command(&self, command: &str, args: &[&str]) -> Result<String, PluginError> {
+        Err(PluginError::PluginError(format!(
+            "{} does not support command '{}' ({} arguments)",
+            self.spec.name,
+            command,
+            args.len()
+        )))
+    }
+
+    fn get_help(&self) -> String {
+        format!(
+            "{}: installs release binaries from {}",
+            self.spec.description, self.spec.download_url
+        )
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::ListVersions,
+            Capability::Config,
+            Capability::Vendor,
+            Capability::CrossInstall,
+        ])
+    }
+}
+
+/// 根据发布描述生成插件类型和完整的 `Plugin` 实现
+///
+/// `name`、`description`、`versions`、`download` 和 `binaries` 必填；
+/// `checksum`、`homepage`、`os` 和 `arch` 可选，按此顺序书写。
+/// 生成的类型提供 `SPEC` 常量和 `new(&GlobalSettings)` 构造函数，详见 [`crate::declarative`]。
+#[macro_export]
+macro_rules! declarative_plugin {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $ty:ident {
+            name: $name:expr,
+            description: $description:expr,
+            versions: $versions:expr,
+            download: $download:expr,
+            binaries: [$($binary:expr),+ $(,)?]
+            $(, checksum: $checksum:expr)?
+            $(, homepage: $homepage:expr)?
+            $(, os: { $($os_from:literal => $os_to:literal),* $(,)? })?
+            $(, arch: { $($arch_from:literal => $arch_to:literal),* $(,)? })?
+            $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $ty($crate::declarative::ReleasePlugin);
+
+        impl $ty {
+            /// 插件的发布描述
+            pub const SPEC: $crate::declarative::ReleaseSpec = $crate::declarative::ReleaseSpec {
+                name: $name,
+                description: $description,
+                homepage: $crate::__declarative_option!($($homepage)?),
+                versions_url: $versions,
+                download_url: $download,
+                checksum_url: $crate::__declarative_option!($($checksum)?),
+                binaries: &[$($binary),+],
+                os_names: &[$($(($os_from, $os_to)),*)?],
+                arch_names: &[$($(($arch_from, $arch_to)),*)?],
+            };
+
+            /// 创建插件，安装到 `plugin_dir/<name>` 下
+            pub fn new(settings: &$crate::config::GlobalSettings) -> Self {
+                Self($crate::declarative::ReleasePlugin::new(Self::SPEC, settings))
+            }
+        }
+
+        #[$crate::prelude::async_trait]
+        impl $crate::traits::Plugin for $ty {
+            fn metadata(&self) -> $crate::traits::PluginMetadata {
+                self.0.metadata()
+            }
+
+            fn status(&self) -> $crate::traits::PluginStatus {
+                self.0.status()
+            }
+
+            async fn initialize(&mut self) -> ::std::result::Result<(), $crate::traits::PluginError> {
+                self.0.initialize().await
+            }
+
+            async fn shutdown(&mut self) -> ::std::result::Result<(), $crate::traits::PluginError> {
+                self.0.shutdown().await
+            }
+
+            async fn install(
+                &self,
+                version: &str,
+                options: &$crate::traits::InstallOptions,
+            ) -> ::std::result::Result<::std::string::String, $crate::traits::PluginError> {
+                self.0.install(version, options).await
+            }
+
+            async fn uninstall(&self, version: &str) -> ::std::result::Result<(), $crate::traits::PluginError> {
+                self.0.uninstall(version).await
+            }
+
+            async fn list_versions(
+                &self,
+            ) -> ::std::result::Result<::std::vec::Vec<$crate::traits::VersionInfo>, $crate::traits::PluginError> {
+                self.0.list_versions().await
+            }
+
+            async fn list_installed(
+                &self,
+            ) -> ::std::result::Result<::std::vec::Vec<::std::string::String>, $crate::traits::PluginError> {
+                self.0.list_installed().await
+            }
+
+            async fn is_installed(&self, version: &str) -> ::std::result::Result<bool, $crate::traits::PluginError> {
+                self.0.is_installed(version).await
+            }
+
+            async fn get_latest_version(
+                &self,
+            ) -> ::std::result::Result<$crate::traits::VersionInfo, $crate::traits::PluginError> {
+                self.0.get_latest_version().await
+            }
+
+            async fn update(
+                &self,
+                version: ::std::option::Option<&str>,
+            ) -> ::std::result::Result<::std::string::String, $crate::traits::PluginError> {
+                self.0.update(version).await
+            }
+
+            async fn switch_version(&self, version: &str) -> ::std::result::Result<(), $crate::traits::PluginError> {
+                self.0.switch_version(version).await
+            }
+
+            async fn verify_installation(
+                &self,
+                version: &str,
+            ) -> ::std::result::Result<bool, $crate::traits::PluginError> {
+                self.0.verify_installation(version).await
+            }
+
+            async fn cleanup(&self) -> ::std::result::Result<(), $crate::traits::PluginError> {
+                self.0.cleanup().await
+            }
+
+            async fn get_config(
+                &self,
+            ) -> ::std::result::Result<
+                ::std::collections::HashMap<::std::string::String, ::std::string::String>,
+                $crate::traits::PluginError,
+            > {
+                self.0.get_config().await
+            }
+
+            async fn set_config(
+                &self,
+                config: ::std::collections::HashMap<::std::string::String, ::std::string::String>,
+            ) -> ::std::result::Result<(), $crate::traits::PluginError> {
+                self.0.set_config(config).await
+            }
+
+            async fn get_config_value(
+                &self,
+                key: &str,
+            ) -> ::std::result::Result<::std::option::Option<::std::string::String>, $crate::traits::PluginError> {
+                self.0.get_config_value(key).await
+            }
+
+            async fn set_config_value(
+                &self,
+                key: &str,
+                value: &str,
+            ) -> ::std::result::Result<(), $crate::traits::PluginError> {
+                self.0.set_config_value(key, value).await
+            }
+
+            async fn execute_command(
+                &self,
+                command: &str,
+                args: &[&str],
+            ) -> ::std::result::Result<::std::string::String, $crate::traits::PluginError> {
+                self.0.execute_command(command, args).await
+            }
+
+            fn get_help(&self) -> ::std::string::String {
+                self.0.get_help()
+            }
+
+            fn capabilities(
+                &self,
+            ) -> ::std::collections::HashSet<$crate::traits::Capability> {
+                self.0.capabilities()
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declarative_option {
+    () => {
+        ::std::option::Option::None
+    };
+    ($value:expr) => {
+        ::std::option::Option::Some($value)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::declarative_plugin! {
+        struct Ripgrep {
+            name: "ripgrep",
+            description: "Recursively search directories for a regex pattern",
+            versions: "https://api.github.com/repos/BurntSushi/ripgrep/releases",
+            download: "https://github.com/BurntSushi/ripgrep/releases/download/{version}/ripgrep-{version}-{arch}-{os}.{ext}",
+            binaries: ["ripgrep-{version}-{arch}-{os}/rg{exe}"],
+            checksum: "https://github.com/BurntSushi/ripgrep/releases/download/{version}/ripgrep-{version}-{arch}-{os}.{ext}.sha256",
+            os: { "linux" => "unknown-linux-musl", "macos" => "apple-darwin" },
+        }
+    }
+
+    #[test]
+    fn test_render_templates() {
+        let spec = Ripgrep::SPEC;
+        assert_eq!(
+            spec.render_for(spec.download_url, "v14.1.0", "linux", "x86_64"),
+            "https://github.com/BurntSushi/ripgrep/releases/download/14.1.0/ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz"
+        );
+        assert_eq!(
+            spec.render_for(spec.binaries[0], "14.1.0", "windows", "x86_64"),
+            "ripgrep-14.1.0-x86_64-windows/rg.exe"
+        );
+        assert_eq!(spec.homepage, None);
+        assert!(spec.checksum_url.is_some());
+    }
+
+    #[test]
+    fn test_generated_plugin() {
+        let plugin = Ripgrep::new(&GlobalSettings::default());
+        assert_eq!(plugin.metadata().name, "ripgrep");
+        assert!(plugin.supports_feature("install"));
+        assert_eq!(plugin.status(), PluginStatus::Inactive);
+    }
+
+    #[test]
+    fn test_parse_version_list() {
+        let github = serde_json::json!([
+            { "tag_name": "v14.1.0", "prerelease": false },
+            { "tag_name": "15.0.0-rc1", "prerelease": true },
+            { "tag_name": "14.2.0", "draft": true },
+        ]);
+        assert_eq!(
+            parse_version_list(&github),
+            [
+                ("14.1.0".to_string(), false),
+                ("15.0.0-rc1".to_string(), true)
+            ]
+        );
+        assert_eq!(
+            parse_version_list(&serde_json::json!(["1.0.0", "v1.1.0"])),
+            [("1.0.0".to_string(), false), ("1.1.0".to_string(), false)]
+        );
+        assert_eq!(
+            parse_version_list(&serde_json::json!({ "versions": [{ "id": "2.0.0" }] })),
+            [("2.0.0".to_string(), false)]
+        );
+    }
+
+    #[test]
+    fn test_parse_checksum() {
+        let sums = "abc123  tool-1.0.0-linux.tar.gz\ndef456 *tool-1.0.0-windows.zip\n";
+        assert_eq!(
+            parse_checksum(sums, "tool-1.0.0-windows.zip").as_deref(),
+            Some("def456")
+        );
+        assert_eq!(parse_checksum(sums, "missing.zip"), None);
+        assert_eq!(parse_checksum("abc123\n", "any").as_deref(), Some("abc123"));
+    }
+}
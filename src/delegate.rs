@@ -0,0 +1,592 @@
+//! PLM 版本管理器委托后端
+//!
+//! 已经在使用 nvm、pyenv 或 rustup 的机器上，可以让 PLM 插件委托给现有的版本管理器，
+//! 而不是由 PLM 重新下载一份运行时：已安装的版本、安装、卸载和切换都通过原来的工具完成，
+//! 团队可以先在 `plm.json` 中声明版本，之后再逐个迁移到 PLM 自己管理的插件。
+//!
+//! ```json
+//! {
+//!   "name": "node",
+//!   "version": "20.11.1",
+//!   "settings": { "backend": "delegate", "manager": "nvm" }
+//! }
+//! ```
+//!
+//! 未设置 `manager` 时按插件名选择：`node` -> nvm，`python` -> pyenv，`rust` -> rustup。
+//! nvm 是 shell 函数，通过 `bash` 加载 `$NVM_DIR/nvm.sh`（默认 `~/.nvm`）后执行。
+
+use crate::config::{current_platform, PluginConfig};
+use crate::system::{run_command, stream_command};
+use crate::traits::{
+    Capability, ExecOutcome, InstallOptions, Plugin, PluginError, PluginMetadata, PluginStatus,
+    VersionInfo,
+};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// 插件设置中选择委托后端的值
+pub const DELEGATE_BACKEND: &str = "delegate";
+
+/// 插件配置是否委托给现有的版本管理器
+pub fn is_delegate_backend(config: &PluginConfig) -> bool {
+    config
+        .get_setting_for("backend", current_platform())
+        .and_then(|v| v.as_str())
+        == Some(DELEGATE_BACKEND)
+}
+
+/// 支持委托的版本管理器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionManager {
+    Nvm,
+    Pyenv,
+    Rustup,
+}
+
+impl FromStr for VersionManager {
+    type Err = PluginError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "nvm" => Ok(VersionManager::Nvm),
+            "pyenv" => Ok(VersionManager::Pyenv),
+            "rustup" => Ok(VersionManager::Rustup),
+            other => Err(PluginError::ConfigError(format!(
+                "Unknown version manager '{}' (expected nvm, pyenv or rustup)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for VersionManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VersionManager::Nvm => "nvm",
+            VersionManager::Pyenv => "pyenv",
+            VersionManager::Rustup => "rustup",
+        })
+    }
+}
+
+impl VersionManager {
+    /// 按插件名选择默认的版本管理器
+    pub fn for_tool(name: &str) -> Option<Self> {
+        match name {
+            "node" | "nodejs" => Some(VersionManager::Nvm),
+            "python" => Some(VersionManager::Pyenv),
+            "rust" => Some(VersionManager::Rustup),
+            _ => None,
+        }
+    }
+
+    /// 版本管理器是否已安装在本机
+    pub fn is_available(&self) -> bool {
+        match self {
+            VersionManager::Nvm => nvm_dir().join("nvm.sh").is_file(),
+            VersionManager::Pyenv => which::which("pyenv").is_ok(),
+            VersionManager::Rustup => which::which("rustup").is_ok(),
+        }
+    }
+
+    /// 构造执行版本管理器子命令的完整命令行
+    pub fn command(&self, args: &[&str]) -> Vec<String> {
+        match self {
+            VersionManager::Nvm => {
+                let script = format!(
+                    ". \"$NVM_DIR/nvm.sh\" && nvm {}",
+                    args.iter()
+                        .map(|arg| shell_quote(arg))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                );
+                vec!["bash".to_string(), "-c".to_string(), script]
+            }
+            VersionManager::Pyenv | VersionManager::Rustup => std::iter::once(self.to_string())
+                .chain(args.iter().map(|arg| arg.to_string()))
+                .collect(),
+        }
+    }
+
+    /// 构造在版本管理器当前激活的版本下执行任意程序的命令行
+    ///
+    /// nvm 加载 `nvm.sh` 后执行（使用默认别名的 node），pyenv 通过 `pyenv exec`，
+    /// rustup 的代理命令本身会遵循目录覆盖和 `rust-toolchain.toml`，直接执行。
+    pub fn exec_command(&self, args: &[&str]) -> Vec<String> {
+        match self {
+            VersionManager::Nvm => {
+                let script = format!(
+                    ". \"$NVM_DIR/nvm.sh\" && exec {}",
+                    args.iter()
+                        .map(|arg| shell_quote(arg))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                );
+                vec!["bash".to_string(), "-c".to_string(), script]
+            }
+            VersionManager::Pyenv => ["pyenv", "exec"]
+                .into_iter()
+                .chain(args.iter().copied())
+                .map(str::to_string)
+                .collect(),
+            VersionManager::Rustup => args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    /// 安装指定版本的子命令参数
+    pub fn install_args<'a>(&self, version: &'a str) -> Vec<&'a str> {
+        match self {
+            VersionManager::Nvm => vec!["install", version],
+            VersionManager::Pyenv => vec!["install", "--skip-existing", version],
+            VersionManager::Rustup => vec!["toolchain", "install", version],
+        }
+    }
+
+    /// 卸载指定版本的子命令参数
+    pub fn uninstall_args<'a>(&self, version: &'a str) -> Vec<&'a str> {
+        match self {
+            VersionManager::Nvm => vec!["uninstall", version],
+            VersionManager::Pyenv => vec!["uninstall", "--force", version],
+            VersionManager::Rustup => vec!["toolchain", "uninstall", version],
+        }
+    }
+
+    /// 设为默认版本的子命令参数
+    pub fn switch_args<'a>(&self, version: &'a str) -> Vec<&'a str> {
+        match self {
+            VersionManager::Nvm => vec!["alias", "default", version],
+            VersionManager::Pyenv => vec!["global", version],
+            VersionManager::Rustup => vec!["default", version],
+        }
+    }
+
+    /// 查询版本安装位置的子命令参数
+    pub fn prefix_args<'a>(&self, version: &'a str) -> Vec<&'a str> {
+        match self {
+            VersionManager::Nvm => vec!["which", version],
+            VersionManager::Pyenv => vec!["prefix", version],
+            VersionManager::Rustup => vec!["run", version, "rustc", "--print", "sysroot"],
+        }
+    }
+
+    /// 列出已安装版本的子命令参数
+    pub fn installed_args(&self) -> &'static [&'static str] {
+        match self {
+            VersionManager::Nvm => &["ls", "--no-colors"],
+            VersionManager::Pyenv => &["versions", "--bare"],
+            VersionManager::Rustup => &["toolchain", "list"],
+        }
+    }
+
+    /// 列出可安装版本的子命令参数，rustup 没有版本列表，返回 None
+    pub fn available_args(&self) -> Option<&'static [&'static str]> {
+        match self {
+            VersionManager::Nvm => Some(&["ls-remote", "--no-colors"]),
+            VersionManager::Pyenv => Some(&["install", "--list"]),
+            VersionManager::Rustup => None,
+        }
+    }
+
+    /// 解析已安装版本列表
+    pub fn parse_installed(&self, output: &str) -> Vec<String> {
+        match self {
+            VersionManager::Nvm => output.lines().filter_map(nvm_version).collect(),
+            VersionManager::Pyenv => output
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+            // `stable-x86_64-unknown-linux-gnu (default)`
+            VersionManager::Rustup => output
+                .lines()
+                .filter_map(|line| line.split_whitespace().next())
+                .filter(|name| *name != "no")
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// 解析可安装版本列表，最新的在前
+    pub fn parse_available(&self, output: &str) -> Vec<String> {
+        let mut versions: Vec<String> = match self {
+            VersionManager::Nvm => output.lines().filter_map(nvm_version).collect(),
+            VersionManager::Pyenv => output
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.ends_with(':'))
+                .map(str::to_string)
+                .collect(),
+            VersionManager::Rustup => ["stable", "beta", "nightly"].map(String::from).to_vec(),
+        };
+        // nvm 和 pyenv 按从旧到新输出
+        if *self != VersionManager::Rustup {
+            versions.reverse();
+        }
+        versions
+    }
+
+    /// 已安装的版本名是否对应请求的版本
+    ///
+    /// rustup 的工具链名包含目标平台，`stable` 匹配 `stable-x86_64-unknown-linux-gnu`
+    pub fn matches_installed(&self, installed: &str, version: &str) -> bool {
+        let version = version.trim_start_matches('v');
+        installed == version
+            || (*self == VersionManager::Rustup
+                && installed
+                    .strip_prefix(version)
+                    .is_some_and(|rest| rest.starts_with('-')))
+    }
+}
+
+/// nvm 的安装目录，`NVM_DIR` 未设置时为 `~/.nvm`
+fn nvm_dir() -> PathBuf {
+    std::env::var_os("NVM_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".nvm")))
+        .unwrap_or_else(|| PathBuf::from(".nvm"))
+}
+
+/// 从 nvm 输出的一行中提取版本号，如 `->     v18.17.0 *` -> `18.17.0`
+fn nvm_version(line: &str) -> Option<String> {
+    let token = line
+        .trim_start()
+        .trim_start_matches("->")
+        .split_whitespace()
+        .next()?;
+    let version = token.strip_prefix('v')?;
+    version
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| version.to_string())
+}
+
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// 委托给现有版本管理器的插件
+pub struct DelegatePlugin {
+    name: String,
+    manager: VersionManager,
+    status: PluginStatus,
+    config: RwLock<HashMap<String, String>>,
+}
+
+impl DelegatePlugin {
+    /// 从插件配置创建
+    pub fn from_config(config: &PluginConfig) -> Result<Self, PluginError> {
+        let settings = config.effective_settings_for(current_platform());
+        let manager = match settings.get("manager").and_then(|v| v.as_str()) {
+            Some(manager) => manager.parse()?,
+            None => VersionManager::for_tool(&config.name).ok_or_else(|| {
+                PluginError::ConfigError(format!(
+                    "Plugin '{}' uses the delegate backend but sets no `manager`",
+                    config.name
+                ))
+            })?,
+        };
+        let values = settings
+            .iter()
+            .filter_map(|(key, value)| value.as_str().map(|v| (key.clone(), v.to_string())))
+            .collect();
+        Ok(Self::new(&config.name, manager).with_settings(values))
+    }
+
+    /// 创建委托给指定版本管理器的插件
+    pub fn new(name: &str, manager: VersionManager) -> Self {
+        Self {
+            name: name.to_string(),
+            manager,
+            status: PluginStatus::Inactive,
+            config: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn with_settings(self, settings: HashMap<String, String>) -> Self {
+        if let Ok(mut config) = self.config.write() {
+            *config = settings;
+        }
+        self
+    }
+
+    /// 委托的版本管理器
+    pub fn manager(&self) -> VersionManager {
+        self.manager
+    }
+
+    async fn run(
+        &self,
+        args: &[&str],
+        options: Option<&InstallOptions>,
+    ) -> Result<String, PluginError> {
+        let options = self.options(options);
+        run_command(&self.manager.command(args), Some(&options)).await
+    }
+
+    /// 版本管理器需要的环境变量
+    fn options(&self, options: Option<&InstallOptions>) -> InstallOptions {
+        let mut options = options.cloned().unwrap_or_default();
+        if self.manager == VersionManager::Nvm {
+            options
+                .env_vars
+                .entry("NVM_DIR".to_string())
+                .or_insert_with(|| nvm_dir().to_string_lossy().into_owned());
+        }
+        options
+    }
+
+    /// 解析 `latest` 为版本管理器中的最新版本
+    async fn resolve(&self, version: &str) -> Result<String, PluginError> {
+        if version == "latest" {
+            Ok(self.get_latest_version().await?.version)
+        } else {
+            Ok(version.trim_start_matches('v').to_string())
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for DelegatePlugin {
+    fn metadata(&self) -> PluginMetadata {
+        PluginMetadata {
+            name: self.name.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            description: format!("{} versions managed by {}", self.name, self.manager),
+            tags: vec![DELEGATE_BACKEND.to_string(), self.manager.to_string()],
+            ..PluginMetadata::default()
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        if !self.manager.is_available() {
+            return Err(PluginError::NotFound(format!(
+                "{} (required by the delegate backend of {})",
+                self.manager, self.name
+            )));
+        }
+        self.status = PluginStatus::Active;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Inactive;
+        Ok(())
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        let version = self.resolve(version).await?;
+        if options.force || !self.is_installed(&version).await? {
+            self.run(&self.manager.install_args(&version), Some(options))
+                .await?;
+        }
+        let prefix = self.run(&self.manager.prefix_args(&version), None).await?;
+        Ok(prefix.trim().to_string())
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        if !self.is_installed(version).await? {
+            return Err(PluginError::NotFound(format!(
+                "{} {} in {}",
+                self.name, version, self.manager
+            )));
+        }
+        self.run(&self.manager.uninstall_args(version), None)
+            .await
+            .map(|_| ())
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        let output = match self.manager.available_args() {
+            Some(args) => self.run(args, None).await?,
+            None => String::new(),
+        };
+        Ok(self
+            .manager
+            .parse_available(&output)
+            .iter()
+            .map(|version| VersionInfo::new(version, current_platform(), ""))
+            .collect())
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        let output = self.run(self.manager.installed_args(), None).await?;
+        Ok(self.manager.parse_installed(&output))
+    }
+
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        Ok(self
+            .list_installed()
+            .await?
+            .iter()
+            .any(|installed| self.manager.matches_installed(installed, version)))
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        self.list_versions()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                PluginError::NotFound(format!("{} versions in {}", self.name, self.manager))
+            })
+    }
+
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        let version = self.resolve(version.unwrap_or("latest")).await?;
+        self.install(&version, &InstallOptions::new()).await?;
+        Ok(version)
+    }
+
+    async fn switch_version(&self, version: &str) -> Result<(), PluginError> {
+        self.run(&self.manager.switch_args(version), None)
+            .await
+            .map(|_| ())
+    }
+
+    async fn verify_installation(&self, version: &str) -> Result<bool, PluginError> {
+        if !self.is_installed(version).await? {
+            return Ok(false);
+        }
+        Ok(self
+            .run(&self.manager.prefix_args(version), None)
+            .await
+            .is_ok())
+    }
+
+    async fn cleanup(&self) -> Result<(), PluginError> {
+        // 缓存由版本管理器自行管理
+        Ok(())
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        Ok(self.config.read().map(|c| c.clone()).unwrap_or_default())
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+        Ok(())
+    }
+
+    async fn get_config_value(&self, key: &str) -> Result<Option<String>, PluginError> {
+        Ok(self.config.read().ok().and_then(|c| c.get(key).cloned()))
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        if let Ok(mut config) = self.config.write() {
+            config.insert(key.to_string(), value.to_string());
+        }
+        Ok(())
+    }
+
+    async fn execute_command(&self, command: &str, args: &[&str]) -> Result<String, PluginError> {
+        let mut full = vec![command];
+        full.extend_from_slice(args);
+        self.run(&full, None).await
+    }
+
+    async fn exec_command(&self, command: &str, args: &[&str]) -> Result<ExecOutcome, PluginError> {
+        let mut full = vec![command];
+        full.extend_from_slice(args);
+        let options = self.options(None);
+        stream_command(&self.manager.exec_command(&full), Some(&options))
+            .await
+            .map(ExecOutcome::Exited)
+    }
+
+    fn get_help(&self) -> String {
+        format!(
+            "{} versions are managed by {}; PLM delegates install, uninstall and switching to it",
+            self.name, self.manager
+        )
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::SwitchVersion,
+            Capability::ListVersions,
+            Capability::Exec,
+            Capability::Config,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_installed_versions() {
+        let nvm = "->     v18.17.0 *\n       v20.11.1 *\ndefault -> 18 (-> v18.17.0 *)\niojs -> N/A (default)\n";
+        assert_eq!(
+            VersionManager::Nvm.parse_installed(nvm),
+            ["18.17.0", "20.11.1"]
+        );
+        assert_eq!(
+            VersionManager::Pyenv.parse_installed("3.11.7\n3.12.1\n"),
+            ["3.11.7", "3.12.1"]
+        );
+        let rustup = "stable-x86_64-unknown-linux-gnu (default)\nnightly-2024-01-01-x86_64-unknown-linux-gnu\n";
+        let installed = VersionManager::Rustup.parse_installed(rustup);
+        assert_eq!(installed.len(), 2);
+        assert!(VersionManager::Rustup.matches_installed(&installed[0], "stable"));
+        assert!(!VersionManager::Rustup.matches_installed(&installed[0], "beta"));
+        assert!(VersionManager::Nvm.matches_installed("18.17.0", "v18.17.0"));
+    }
+
+    #[test]
+    fn test_parse_available_newest_first() {
+        let pyenv = "Available versions:\n  3.11.7\n  3.12.1\n";
+        assert_eq!(
+            VersionManager::Pyenv.parse_available(pyenv),
+            ["3.12.1", "3.11.7"]
+        );
+        let nvm = "        v20.10.0\n->      v20.11.1   (Latest LTS: Iron)\n";
+        assert_eq!(
+            VersionManager::Nvm.parse_available(nvm),
+            ["20.11.1", "20.10.0"]
+        );
+    }
+
+    #[test]
+    fn test_commands_and_config() {
+        let cmd = VersionManager::Nvm.command(&["install", "20.11.1"]);
+        assert_eq!(cmd[0], "bash");
+        assert!(cmd[2].ends_with("nvm 'install' '20.11.1'"));
+        assert_eq!(
+            VersionManager::Pyenv.command(&["versions", "--bare"]),
+            ["pyenv", "versions", "--bare"]
+        );
+        let exec = VersionManager::Nvm.exec_command(&["npm", "ping"]);
+        assert!(exec[2].ends_with("&& exec 'npm' 'ping'"));
+        assert_eq!(
+            VersionManager::Pyenv.exec_command(&["pip", "list"]),
+            ["pyenv", "exec", "pip", "list"]
+        );
+
+        let mut config = PluginConfig::new("python");
+        config.set_setting("backend", serde_json::json!("delegate"));
+        assert!(is_delegate_backend(&config));
+        let plugin = DelegatePlugin::from_config(&config).unwrap();
+        assert_eq!(plugin.manager(), VersionManager::Pyenv);
+
+        let unknown = PluginConfig::new("deno");
+        assert!(DelegatePlugin::from_config(&unknown).is_err());
+    }
+}
@@ -0,0 +1,1160 @@
+//! PLM 下载子系统
+//!
+//! 下载文件到 `cache_dir`，网络中断时保留 `.part` 部分文件，
+//! 下次下载同一文件时通过 HTTP Range 请求从断点继续。部分文件旁的 `.part.etag` 记录远端文件的
+//! ETag（或 Last-Modified），续传时作为 `If-Range` 发送，远端文件变化时从头下载。其他错误或任务被取消时
+//! 部分文件由 `TempFileGuard` 删除，不会在缓存目录中留下无用的临时文件。
+//!
+//! 不需要校验的 tar.gz 制品可以直接从网络流解压（见 `fetch_and_extract`），
+//! 压缩包不落盘。
+//!
+//! 启用 `object-store` 特性后也可以从 `s3://`、`gs://` 地址下载，见 `object_store`；
+//! 启用实验性的 `p2p` 特性后先尝试版本的内容寻址地址（IPFS、magnet），见 `p2p`。
+
+use crate::auth::Credentials;
+use crate::cache::Cache;
+use crate::config::{GlobalSettings, PluginSource};
+use crate::extract;
+#[cfg(feature = "object-store")]
+use crate::object_store;
+#[cfg(feature = "p2p")]
+use crate::p2p;
+use crate::permissions;
+use crate::signature::{KeylessPolicy, SignatureKind, TrustedKey};
+use crate::temp::TempFileGuard;
+use crate::timings::{self, Phase};
+use crate::traits::{ArchiveFormat, PluginError, VersionInfo};
+use crate::vendor::{self, VendorMode};
+use futures_util::StreamExt;
+use reqwest::header::{HeaderMap, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use std::cell::Cell;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// 部分文件后缀
+const PARTIAL_SUFFIX: &str = "part";
+
+/// 部分文件对应的远端版本标识（ETag 或 Last-Modified）的文件后缀
+const VALIDATOR_SUFFIX: &str = "etag";
+
+/// 流式解压时在下载和解压线程之间缓冲的数据块数量
+const STREAM_BUFFER_CHUNKS: usize = 32;
+
+tokio::task_local! {
+    static DOWNLOADED: Cell<u64>;
+}
+
+/// 执行 `future` 并统计其间通过下载器实际传输的字节数（不含缓存命中）
+///
+/// 只统计同一任务中的下载，插件另外 spawn 的任务中的下载不计入
+pub async fn count_downloaded<F: Future>(future: F) -> (F::Output, u64) {
+    DOWNLOADED
+        .scope(Cell::new(0), async {
+            let output = future.await;
+            (output, DOWNLOADED.with(Cell::get))
+        })
+        .await
+}
+
+/// 把传输的字节数计入当前任务的统计，没有在 `count_downloaded` 中执行时忽略
+fn add_downloaded(bytes: u64) {
+    let _ = DOWNLOADED.try_with(|total| total.set(total.get() + bytes));
+}
+
+/// 下载结果
+#[derive(Debug, Clone)]
+pub struct DownloadOutcome {
+    /// 下载完成的文件路径
+    pub path: PathBuf,
+    /// 本次实际传输的字节数
+    pub bytes_downloaded: u64,
+    /// 从多少字节处续传（0 表示从头下载）
+    pub resumed_from: u64,
+    /// 文件是否已存在于缓存中
+    pub was_cached: bool,
+}
+
+/// 下载并解压的结果
+#[derive(Debug, Clone)]
+pub struct ExtractOutcome {
+    /// 解压后的目录
+    pub path: PathBuf,
+    /// 本次实际传输的字节数
+    pub bytes_downloaded: u64,
+    /// 是否直接从网络流解压（压缩包未写入磁盘）
+    pub streamed: bool,
+}
+
+/// 支持断点续传的下载器
+pub struct Downloader {
+    client: reqwest::Client,
+    download_dir: PathBuf,
+    cache: Cache,
+    verify_checksums: bool,
+    require_signatures: bool,
+    trusted_keys: Vec<TrustedKey>,
+    vendor: Option<VendorMode>,
+    /// 插件源的地址前缀（含镜像）和访问它们使用的凭据
+    credentials: Option<(Vec<String>, Credentials)>,
+    /// 请求制品时的 `Accept` 头
+    accept: Option<String>,
+    #[cfg(feature = "p2p")]
+    ipfs_gateway: Option<String>,
+}
+
+impl Downloader {
+    /// 创建下载器，文件保存在 `cache_dir/downloads` 下
+    ///
+    /// 代理取自 `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` 环境变量
+    pub fn new(cache_dir: impl AsRef<Path>, timeout: Duration) -> Result<Self, PluginError> {
+        Ok(Self::with_client(
+            cache_dir,
+            build_client(timeout, None, None)?,
+        ))
+    }
+
+    fn with_client(cache_dir: impl AsRef<Path>, client: reqwest::Client) -> Self {
+        Self {
+            client,
+            download_dir: cache_dir.as_ref().join("downloads"),
+            cache: Cache::new(cache_dir.as_ref()),
+            verify_checksums: true,
+            require_signatures: false,
+            trusted_keys: Vec::new(),
+            vendor: None,
+            credentials: None,
+            accept: None,
+            #[cfg(feature = "p2p")]
+            ipfs_gateway: None,
+        }
+    }
+
+    /// 设置是否校验下载文件的校验和
+    pub fn with_checksum_verification(mut self, verify: bool) -> Self {
+        self.verify_checksums = verify;
+        self
+    }
+
+    /// 设置签名策略：受信任公钥，以及是否拒绝未签名的制品
+    pub fn with_signature_policy(mut self, trusted_keys: Vec<TrustedKey>, require: bool) -> Self {
+        self.trusted_keys = trusted_keys;
+        self.require_signatures = require;
+        self
+    }
+
+    /// 设置 vendor 目录：只从中解析制品，或把下载的制品同时保存到其中
+    pub fn with_vendor(mut self, vendor: Option<VendorMode>) -> Self {
+        self.vendor = vendor;
+        self
+    }
+
+    /// 下载插件源地址或其镜像地址下的制品时带上凭据，其他地址的请求不受影响
+    pub fn with_source_credentials(
+        self,
+        source: &PluginSource,
+        credentials: Option<Credentials>,
+    ) -> Self {
+        let prefixes = std::iter::once(&source.url)
+            .chain(&source.mirrors)
+            .map(|url| url.to_string())
+            .collect();
+        self.with_scoped_credentials(prefixes, credentials)
+    }
+
+    /// 下载指定地址前缀下的制品时带上凭据
+    pub fn with_scoped_credentials(
+        mut self,
+        prefixes: Vec<String>,
+        credentials: Option<Credentials>,
+    ) -> Self {
+        self.credentials = credentials.map(|credentials| {
+            let prefixes = prefixes
+                .into_iter()
+                .map(|url| url.trim_end_matches('/').to_string())
+                .collect();
+            (prefixes, credentials)
+        });
+        self
+    }
+
+    /// 设置请求制品时的 `Accept` 头，如 GitHub API 附件地址需要 `application/octet-stream`
+    pub fn with_accept(mut self, accept: &str) -> Self {
+        self.accept = Some(accept.to_string());
+        self
+    }
+
+    /// 设置下载 `ipfs://` 制品使用的 IPFS 网关
+    #[cfg(feature = "p2p")]
+    pub fn with_ipfs_gateway(mut self, gateway: Option<String>) -> Self {
+        self.ipfs_gateway = gateway;
+        self
+    }
+
+    /// 根据全局设置创建下载器
+    pub fn from_settings(settings: &GlobalSettings) -> Result<Self, PluginError> {
+        let cache_dir = settings.cache_path();
+        let client = build_client(
+            Duration::from_secs(settings.download_timeout),
+            settings.proxy.as_deref(),
+            settings.no_proxy.as_deref(),
+        )?;
+        let downloader = Self::with_client(cache_dir, client)
+            .with_checksum_verification(settings.verify_checksums)
+            .with_signature_policy(settings.trusted_keys.clone(), settings.require_signatures)
+            .with_vendor(settings.vendor_mode());
+        #[cfg(feature = "p2p")]
+        let downloader = downloader.with_ipfs_gateway(settings.ipfs_gateway.clone());
+        Ok(downloader)
+    }
+
+    /// 下载文件目录
+    pub fn download_dir(&self) -> &Path {
+        &self.download_dir
+    }
+
+    /// 下载版本对应的制品，启用校验时在返回前校验 SHA-256
+    ///
+    /// 校验失败时删除已下载的文件，避免后续安装使用损坏或被篡改的制品
+    pub async fn fetch(&self, info: &VersionInfo) -> Result<DownloadOutcome, PluginError> {
+        self.fetch_from(info, None).await
+    }
+
+    /// 下载来自指定插件源的制品，插件源上的 keyless 策略用于校验 Sigstore 签名
+    pub async fn fetch_from(
+        &self,
+        info: &VersionInfo,
+        source: Option<&PluginSource>,
+    ) -> Result<DownloadOutcome, PluginError> {
+        if let Some(VendorMode::Resolve(dir)) = &self.vendor {
+            return self.fetch_vendored(dir, info, source).await;
+        }
+
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+        let outcome =
+            timings::measure(Phase::Download, self.download_with_failover(info, source)).await?;
+        #[cfg(feature = "metrics")]
+        if !outcome.was_cached {
+            crate::metrics::record_download(outcome.bytes_downloaded, started.elapsed());
+        }
+        let keyless = source.and_then(|s| s.keyless.as_ref());
+
+        if let Err(e) = self.verify_artifact(info, &outcome.path, keyless).await {
+            remove_file_if_exists(&outcome.path).await?;
+            return Err(e);
+        }
+
+        if let Some(VendorMode::Record(dir)) = &self.vendor {
+            vendor::record_artifact(dir, info, &outcome.path).await?;
+        }
+        Ok(outcome)
+    }
+
+    /// 从 vendor 目录获取制品，不访问网络
+    ///
+    /// 版本没有提供校验和时（离线时通常无法获取）以 vendor 索引中的 SHA-256 为准
+    async fn fetch_vendored(
+        &self,
+        dir: &Path,
+        info: &VersionInfo,
+        source: Option<&PluginSource>,
+    ) -> Result<DownloadOutcome, PluginError> {
+        let (path, artifact) = vendor::resolve_artifact(dir, info).await?;
+        let mut info = info.clone();
+        info.checksum.get_or_insert(artifact.sha256);
+        let keyless = source.and_then(|s| s.keyless.as_ref());
+        self.verify_artifact(&info, &path, keyless).await?;
+
+        Ok(DownloadOutcome {
+            path,
+            bytes_downloaded: 0,
+            resumed_from: 0,
+            was_cached: true,
+        })
+    }
+
+    /// 依次尝试版本的各个下载地址及插件源的镜像地址，网络错误时切换到下一个
+    async fn download_with_failover(
+        &self,
+        info: &VersionInfo,
+        source: Option<&PluginSource>,
+    ) -> Result<DownloadOutcome, PluginError> {
+        let url = &info.download_url;
+        // 所有镜像共用主地址对应的缓存文件名，切换镜像时可以继续使用已下载的部分
+        let file_name = cache_file_name(url)?;
+        let mut last_error = None;
+
+        for candidate in candidate_urls(info, source) {
+            match self
+                .download_as(&candidate, &file_name, info.checksum.as_deref())
+                .await
+            {
+                Ok(outcome) => {
+                    log::debug!("Downloaded {} from {}", file_name, candidate);
+                    return Ok(outcome);
+                }
+                Err(PluginError::NetworkError(e)) => {
+                    log::debug!("Mirror {} failed: {}", candidate, e);
+                    last_error = Some(PluginError::NetworkError(e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| PluginError::NetworkError(format!("没有可用的下载地址: {}", url))))
+    }
+
+    /// 下载制品并解压到 `dest`
+    ///
+    /// 不需要校验和或签名校验的 tar.gz 制品直接从网络流解压，压缩包不写入磁盘；
+    /// 需要校验、格式需要随机访问（zip）或压缩包已在缓存中时，先下载到缓存并校验，再从磁盘解压
+    pub async fn fetch_and_extract(
+        &self,
+        info: &VersionInfo,
+        source: Option<&PluginSource>,
+        dest: &Path,
+    ) -> Result<ExtractOutcome, PluginError> {
+        permissions::check(|guard| guard.check_path(dest))?;
+        let keyless = source.and_then(|s| s.keyless.as_ref());
+        let format = info.archive_format();
+        let cached_archive = self.download_dir.join(cache_file_name(&info.download_url)?);
+
+        // 使用 vendor 目录或内容寻址地址时制品必须落盘
+        let can_stream = format == ArchiveFormat::TarGz
+            && self.vendor.is_none()
+            && local_file_url(&info.download_url).is_none()
+            && (!cfg!(feature = "p2p") || info.content_uris.is_empty())
+            && !self.requires_verification(info, keyless)
+            && tokio::fs::metadata(&cached_archive).await.is_err();
+        if can_stream {
+            let mut last_error = None;
+            for candidate in candidate_urls(info, source) {
+                #[cfg(feature = "metrics")]
+                let started = std::time::Instant::now();
+                // 流式解压时下载和解压交替进行，整体计入下载阶段
+                let streamed = timings::measure(
+                    Phase::Download,
+                    self.stream_extract(&candidate, info.size, dest),
+                );
+                match streamed.await {
+                    Ok(bytes_downloaded) => {
+                        add_downloaded(bytes_downloaded);
+                        #[cfg(feature = "metrics")]
+                        crate::metrics::record_download(bytes_downloaded, started.elapsed());
+                        return Ok(ExtractOutcome {
+                            path: dest.to_path_buf(),
+                            bytes_downloaded,
+                            streamed: true,
+                        });
+                    }
+                    Err(PluginError::NetworkError(e)) => {
+                        log::debug!("Streaming from {} failed: {}", candidate, e);
+                        last_error = Some(PluginError::NetworkError(e));
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            return Err(last_error.unwrap_or_else(|| {
+                PluginError::NetworkError(format!("没有可用的下载地址: {}", info.download_url))
+            }));
+        }
+
+        let outcome = self.fetch_from(info, source).await?;
+        extract::extract_file(&outcome.path, format, dest).await?;
+        Ok(ExtractOutcome {
+            path: dest.to_path_buf(),
+            bytes_downloaded: outcome.bytes_downloaded,
+            streamed: false,
+        })
+    }
+
+    /// 是否需要在安装前对完整的制品做校验和或签名校验
+    fn requires_verification(&self, info: &VersionInfo, keyless: Option<&KeylessPolicy>) -> bool {
+        let has_signatures = info.all_signatures().next().is_some();
+        self.verify_checksums
+            || self.require_signatures
+            || (has_signatures && (!self.trusted_keys.is_empty() || keyless.is_some()))
+    }
+
+    /// 边下载边解压 tar.gz，返回传输的字节数
+    async fn stream_extract(
+        &self,
+        url: &str,
+        expected_size: Option<u64>,
+        dest: &Path,
+    ) -> Result<u64, PluginError> {
+        let response = self
+            .get(url)
+            .await?
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| PluginError::NetworkError(format!("下载 {} 失败: {}", url, e)))?;
+
+        let staging = TempFileGuard::new(extract::staging_path(dest));
+        staging.clear().await?;
+        let (tx, rx) = tokio::sync::mpsc::channel(STREAM_BUFFER_CHUNKS);
+        let unpack_into = staging.path().to_path_buf();
+        let unpack = tokio::task::spawn_blocking(move || {
+            extract::unpack_tar_gz(extract::ChannelReader::new(rx), &unpack_into)
+        });
+
+        let mut bytes_downloaded = 0u64;
+        let mut download_error = None;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(chunk) => {
+                    bytes_downloaded += chunk.len() as u64;
+                    // 解压线程已退出（解压出错），停止下载
+                    if tx.send(Ok(chunk)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(std::io::Error::other(e.to_string()))).await;
+                    download_error = Some(PluginError::NetworkError(format!(
+                        "下载 {} 中断: {}",
+                        url, e
+                    )));
+                    break;
+                }
+            }
+        }
+        drop(tx);
+
+        let unpacked = unpack
+            .await
+            .map_err(|e| PluginError::IoError(format!("解压任务失败: {}", e)))?;
+        let result = match (download_error, unpacked) {
+            (Some(e), _) | (None, Err(e)) => Err(e),
+            (None, Ok(())) => match expected_size {
+                Some(expected) if expected != bytes_downloaded => {
+                    Err(PluginError::ValidationError(format!(
+                        "{} 大小不符: 期望 {} 字节，实际 {} 字节",
+                        url, expected, bytes_downloaded
+                    )))
+                }
+                _ => Ok(bytes_downloaded),
+            },
+        };
+        extract::finish_staging(staging, dest, result).await
+    }
+
+    /// 按校验和与签名策略校验已下载的制品
+    async fn verify_artifact(
+        &self,
+        info: &VersionInfo,
+        path: &Path,
+        keyless: Option<&KeylessPolicy>,
+    ) -> Result<(), PluginError> {
+        if self.verify_checksums {
+            let expected = info.checksum.as_deref().ok_or_else(|| {
+                PluginError::ValidationError(format!(
+                    "版本 {} 没有提供校验和，无法在 verify_checksums 启用时安装",
+                    info.version
+                ))
+            })?;
+            verify_checksum(path, expected).await?;
+        }
+
+        if let Some(expected) = info.size {
+            let actual = tokio::fs::metadata(path)
+                .await
+                .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e)))?
+                .len();
+            if actual != expected {
+                return Err(PluginError::ValidationError(format!(
+                    "{} 大小不符: 期望 {} 字节，实际 {} 字节",
+                    path.display(),
+                    expected,
+                    actual
+                )));
+            }
+        }
+
+        let signatures: Vec<&str> = info.all_signatures().collect();
+        if signatures.is_empty() {
+            return if self.require_signatures {
+                Err(PluginError::SignatureError(format!(
+                    "版本 {} 没有签名，require_signatures 已启用",
+                    info.version
+                )))
+            } else {
+                Ok(())
+            };
+        }
+        if !self.require_signatures && self.trusted_keys.is_empty() && keyless.is_none() {
+            return Ok(());
+        }
+
+        // 任一签名校验通过即可，全部失败时返回最后一个错误
+        let mut last_error = None;
+        for signature in signatures {
+            match self.verify_signature(path, signature, keyless).await {
+                Ok(()) => return Ok(()),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| PluginError::SignatureError("签名校验失败".to_string())))
+    }
+
+    /// 校验单个签名引用
+    async fn verify_signature(
+        &self,
+        path: &Path,
+        signature: &str,
+        keyless: Option<&KeylessPolicy>,
+    ) -> Result<(), PluginError> {
+        let signature = self.load_signature(signature).await?;
+        match (crate::signature::detect_kind(&signature), keyless) {
+            (Some(SignatureKind::Sigstore), Some(policy)) => {
+                crate::signature::verify_keyless(path, &signature, policy).await
+            }
+            _ => crate::signature::verify_file(path, &signature, &self.trusted_keys).await,
+        }
+    }
+
+    /// 获取签名内容：http(s) 地址会被下载，否则视为内联签名
+    async fn load_signature(&self, signature: &str) -> Result<String, PluginError> {
+        if !(signature.starts_with("https://") || signature.starts_with("http://")) {
+            return Ok(signature.to_string());
+        }
+
+        let response = self
+            .get(signature)
+            .await?
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                PluginError::NetworkError(format!("下载签名 {} 失败: {}", signature, e))
+            })?;
+        response
+            .text()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("下载签名 {} 失败: {}", signature, e)))
+    }
+
+    /// 下载 URL 到缓存目录，文件名见 `cache_file_name`
+    pub async fn download(&self, url: &str) -> Result<DownloadOutcome, PluginError> {
+        let file_name = cache_file_name(url)?;
+        self.download_as(url, &file_name, None).await
+    }
+
+    /// 下载 URL 到缓存目录中的指定文件名
+    ///
+    /// 缓存中已有该文件时直接使用；提供了 `checksum` 时先校验，不符的缓存文件被删除后重新下载
+    pub async fn download_as(
+        &self,
+        url: &str,
+        file_name: &str,
+        checksum: Option<&str>,
+    ) -> Result<DownloadOutcome, PluginError> {
+        let target = self.download_dir.join(file_name);
+        if tokio::fs::metadata(&target).await.is_ok() {
+            match checksum {
+                Some(expected) if verify_checksum(&target, expected).await.is_err() => {
+                    log::debug!("缓存中的 {} 校验和不符，重新下载", target.display());
+                    remove_file_if_exists(&target).await?;
+                }
+                _ => {
+                    self.record_access(&target).await;
+                    return Ok(DownloadOutcome {
+                        path: target,
+                        bytes_downloaded: 0,
+                        resumed_from: 0,
+                        was_cached: true,
+                    });
+                }
+            }
+        }
+
+        tokio::fs::create_dir_all(&self.download_dir)
+            .await
+            .map_err(|e| PluginError::IoError(format!("创建下载目录失败: {}", e)))?;
+
+        let partial = TempFileGuard::new(partial_path(&target));
+        let validator = validator_path(partial.path());
+        let (bytes_downloaded, resumed_from) =
+            match self.download_partial(url, partial.path()).await {
+                Ok(progress) => progress,
+                Err(e @ PluginError::NetworkError(_)) => {
+                    // 网络错误时保留部分文件，下次从断点继续
+                    partial.keep();
+                    return Err(e);
+                }
+                Err(e) => {
+                    remove_file_if_exists(&validator).await?;
+                    return Err(e);
+                }
+            };
+
+        add_downloaded(bytes_downloaded);
+        partial.persist(&target).await?;
+        remove_file_if_exists(&validator).await?;
+        self.record_access(&target).await;
+
+        Ok(DownloadOutcome {
+            path: target,
+            bytes_downloaded,
+            resumed_from,
+            was_cached: false,
+        })
+    }
+
+    /// 下载到部分文件，已有部分文件时从断点继续，返回 (本次下载字节数, 续传起点)
+    async fn download_partial(&self, url: &str, partial: &Path) -> Result<(u64, u64), PluginError> {
+        // file:// 地址（如共享盘上的静态注册表）直接复制，失败时和网络错误一样切换到下一个地址
+        if let Some(path) = local_file_url(url) {
+            let copied = tokio::fs::copy(&path, partial).await.map_err(|e| {
+                PluginError::NetworkError(format!("复制 {} 失败: {}", path.display(), e))
+            })?;
+            return Ok((copied, 0));
+        }
+
+        #[cfg(feature = "p2p")]
+        if p2p::is_magnet(url) {
+            // BitTorrent 自己处理分块和续传，这里总是得到完整文件
+            return Ok((p2p::fetch_magnet(url, partial).await?, 0));
+        }
+
+        let validator_path = validator_path(partial);
+        let mut resumed_from = partial_len(partial).await;
+        // 没有记录远端版本时无法确认部分文件仍然有效，从头下载
+        let validator = match resumed_from {
+            0 => None,
+            _ => read_validator(&validator_path).await,
+        };
+        if validator.is_none() {
+            resumed_from = 0;
+        }
+
+        let mut request = self.get(url).await?;
+        if let Some(validator) = &validator {
+            request = request
+                .header(RANGE, format!("bytes={}-", resumed_from))
+                .header(IF_RANGE, validator.as_str());
+        }
+
+        let mut response = request
+            .send()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("下载 {} 失败: {}", url, e)))?;
+
+        // 只有 206 且 Content-Range 从断点开始时才续写；远端文件已变化时服务器按 If-Range
+        // 返回 200 完整内容，其他情况（416、起点不符的 206）重新请求完整文件
+        if resumed_from > 0 && !resumes_at(&response, resumed_from) {
+            resumed_from = 0;
+            if response.status() != StatusCode::OK {
+                response =
+                    self.get(url).await?.send().await.map_err(|e| {
+                        PluginError::NetworkError(format!("下载 {} 失败: {}", url, e))
+                    })?;
+            }
+        }
+
+        if !response.status().is_success() {
+            return Err(PluginError::NetworkError(format!(
+                "下载 {} 失败: HTTP {}",
+                url,
+                response.status()
+            )));
+        }
+
+        let append = resumed_from > 0;
+        if !append {
+            save_validator(&validator_path, response.headers()).await?;
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(partial)
+            .await
+            .map_err(|e| PluginError::IoError(format!("打开部分文件失败: {}", e)))?;
+
+        let mut bytes_downloaded = 0u64;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| {
+                PluginError::NetworkError(format!("下载 {} 中断: {}（已保留部分文件）", url, e))
+            })?;
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| PluginError::IoError(format!("写入部分文件失败: {}", e)))?;
+            bytes_downloaded += chunk.len() as u64;
+        }
+
+        file.flush()
+            .await
+            .map_err(|e| PluginError::IoError(format!("写入部分文件失败: {}", e)))?;
+        Ok((bytes_downloaded, resumed_from))
+    }
+
+    /// 创建下载请求，`s3://`、`gs://` 地址转换为签名后的 HTTPS 请求（需要 `object-store` 特性）
+    async fn get(&self, url: &str) -> Result<reqwest::RequestBuilder, PluginError> {
+        permissions::check(|guard| guard.check_network(url))?;
+        #[cfg(feature = "object-store")]
+        if object_store::is_object_store_url(url) {
+            let signed = object_store::sign_get(url).await?;
+            let mut request = self.client.get(&signed.url);
+            for (name, value) in signed.headers {
+                request = request.header(name, value);
+            }
+            return Ok(request);
+        }
+        #[cfg(feature = "p2p")]
+        if let Some(gateway_url) = p2p::gateway_url(url, self.ipfs_gateway.as_deref()) {
+            return Ok(self.client.get(gateway_url));
+        }
+        #[cfg(not(feature = "object-store"))]
+        if url.starts_with("s3://") || url.starts_with("gs://") {
+            return Err(PluginError::ConfigError(format!(
+                "下载 {} 需要启用 object-store 特性",
+                url
+            )));
+        }
+        let mut request = self.client.get(url);
+        if let Some(accept) = &self.accept {
+            request = request.header(reqwest::header::ACCEPT, accept);
+        }
+        match &self.credentials {
+            Some((prefixes, credentials))
+                if prefixes.iter().any(|prefix| is_under(url, prefix)) =>
+            {
+                Ok(credentials.apply(request))
+            }
+            _ => Ok(request),
+        }
+    }
+
+    /// 更新缓存访问记录，失败不影响下载结果
+    async fn record_access(&self, path: &Path) {
+        if let Err(e) = self.cache.record_access(path).await {
+            log::debug!(
+                "Failed to record cache access for {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// `file://` 地址对应的本地路径，其他地址返回 None
+fn local_file_url(url: &str) -> Option<PathBuf> {
+    url::Url::parse(url)
+        .ok()
+        .filter(|parsed| parsed.scheme() == "file")?
+        .to_file_path()
+        .ok()
+}
+
+/// 版本的所有下载地址及插件源镜像地址，按尝试顺序去重
+///
+/// 启用 `p2p` 特性时内容寻址地址排在最前
+fn candidate_urls(info: &VersionInfo, source: Option<&PluginSource>) -> Vec<String> {
+    let mut candidates: Vec<String> = Vec::new();
+    if cfg!(feature = "p2p") {
+        candidates.extend(info.content_uris.iter().cloned());
+    }
+    for artifact_url in info.urls() {
+        let expanded = match source {
+            Some(source) => source.candidate_urls(artifact_url),
+            None => vec![artifact_url.to_string()],
+        };
+        for candidate in expanded {
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+    }
+    candidates
+}
+
+/// 创建 HTTP 客户端，显式代理优先于环境变量中的代理
+pub(crate) fn build_client(
+    timeout: Duration,
+    proxy: Option<&str>,
+    no_proxy: Option<&str>,
+) -> Result<reqwest::Client, PluginError> {
+    let mut builder = reqwest::Client::builder().timeout(timeout);
+    if let Some(proxy) = proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|e| PluginError::ConfigError(format!("代理地址 {} 无效: {}", proxy, e)))?
+            .no_proxy(no_proxy.and_then(reqwest::NoProxy::from_string));
+        builder = builder.proxy(proxy);
+    }
+    builder
+        .build()
+        .map_err(|e| PluginError::NetworkError(format!("创建 HTTP 客户端失败: {}", e)))
+}
+
+/// `url` 是否为 `prefix` 本身或其下的地址
+fn is_under(url: &str, prefix: &str) -> bool {
+    url.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?', '#']))
+}
+
+/// 计算文件的 SHA-256（小写十六进制）
+pub async fn sha256_file(path: &Path) -> Result<String, PluginError> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| PluginError::IoError(format!("打开文件 {} 失败: {}", path.display(), e)))?;
+
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).await.map_err(|e| {
+            PluginError::IoError(format!("读取文件 {} 失败: {}", path.display(), e))
+        })?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 校验文件的 SHA-256，期望值可带 `sha256:` 前缀
+pub async fn verify_checksum(path: &Path, expected: &str) -> Result<(), PluginError> {
+    let expected = expected
+        .trim()
+        .trim_start_matches("sha256:")
+        .to_ascii_lowercase();
+    let actual = sha256_file(path).await?;
+
+    if actual != expected {
+        return Err(PluginError::ChecksumMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+/// 从 URL 中提取文件名
+pub fn file_name_from_url(url: &str) -> Result<String, PluginError> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| PluginError::ValidationError(format!("无效的下载地址 {}: {}", url, e)))?;
+
+    parsed
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .ok_or_else(|| PluginError::ValidationError(format!("无法从 {} 推断文件名", url)))
+}
+
+/// 制品在下载缓存中的文件名：URL 的 SHA-256 前缀加上 URL 的最后一段
+///
+/// 不同插件或版本发布的同名制品（如 `tool-linux-x64.tar.gz`）在缓存中互不覆盖
+pub fn cache_file_name(url: &str) -> Result<String, PluginError> {
+    let digest = format!("{:x}", Sha256::digest(url.as_bytes()));
+    Ok(format!("{}-{}", &digest[..16], file_name_from_url(url)?))
+}
+
+fn partial_path(target: &Path) -> PathBuf {
+    let mut name = target.as_os_str().to_os_string();
+    name.push(".");
+    name.push(PARTIAL_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn validator_path(partial: &Path) -> PathBuf {
+    let mut name = partial.as_os_str().to_os_string();
+    name.push(".");
+    name.push(VALIDATOR_SUFFIX);
+    PathBuf::from(name)
+}
+
+async fn read_validator(path: &Path) -> Option<String> {
+    tokio::fs::read_to_string(path)
+        .await
+        .ok()
+        .map(|validator| validator.trim().to_string())
+        .filter(|validator| !validator.is_empty())
+}
+
+/// 记录响应的强 ETag，没有时记录 Last-Modified；弱 ETag 不能用于 `If-Range`
+async fn save_validator(path: &Path, headers: &HeaderMap) -> Result<(), PluginError> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let validator = header(ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(LAST_MODIFIED));
+    match validator {
+        Some(validator) => tokio::fs::write(path, validator)
+            .await
+            .map_err(|e| PluginError::IoError(format!("写入 {} 失败: {}", path.display(), e))),
+        None => remove_file_if_exists(path).await,
+    }
+}
+
+/// 响应是否从 `offset` 处续传：状态为 206 且 Content-Range 的起点等于 `offset`
+fn resumes_at(response: &reqwest::Response, offset: u64) -> bool {
+    response.status() == StatusCode::PARTIAL_CONTENT
+        && response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(content_range_start)
+            == Some(offset)
+}
+
+/// 解析 `Content-Range: bytes <start>-<end>/<total>` 中的起点
+fn content_range_start(value: &str) -> Option<u64> {
+    value
+        .trim()
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+async fn partial_len(path: &Path) -> u64 {
+    tokio::fs::metadata(path)
+        .await
+        .map(|meta| meta.len())
+        .unwrap_or(0)
+}
+
+async fn remove_file_if_exists(path: &Path) -> Result<(), PluginError> {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(PluginError::IoError(format!("删除部分文件失败: {}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name_from_url() {
+        assert_eq!(
+            file_name_from_url("https://nodejs.org/dist/v18.17.0/node-v18.17.0-linux-x64.tar.gz")
+                .unwrap(),
+            "node-v18.17.0-linux-x64.tar.gz"
+        );
+        assert!(file_name_from_url("https://example.com/").is_err());
+        assert!(file_name_from_url("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("artifact.txt");
+        tokio::fs::write(&path, "hello").await.unwrap();
+
+        let hello = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert!(verify_checksum(&path, hello).await.is_ok());
+        assert!(
+            verify_checksum(&path, &format!("sha256:{}", hello.to_uppercase()))
+                .await
+                .is_ok()
+        );
+
+        match verify_checksum(&path, "deadbeef").await {
+            Err(PluginError::ChecksumMismatch { expected, actual }) => {
+                assert_eq!(expected, "deadbeef");
+                assert_eq!(actual, hello);
+            }
+            other => panic!("expected checksum mismatch, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_is_keyed_by_url() {
+        let a = cache_file_name("https://a.example.com/1.0/tool-linux-x64.tar.gz").unwrap();
+        let b = cache_file_name("https://b.example.com/2.0/tool-linux-x64.tar.gz").unwrap();
+        assert_ne!(a, b);
+        assert!(a.ends_with("-tool-linux-x64.tar.gz"));
+
+        // 校验和不符的缓存文件不会被当作命中
+        let dir = tempfile::tempdir().unwrap();
+        let downloader = Downloader::new(dir.path(), Duration::from_secs(1)).unwrap();
+        let url = "http://127.0.0.1:9/tool-linux-x64.tar.gz";
+        let cached = dir
+            .path()
+            .join("downloads")
+            .join(cache_file_name(url).unwrap());
+        tokio::fs::create_dir_all(cached.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(&cached, "stale").await.unwrap();
+        let hello = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert!(downloader
+            .download_as(url, &cache_file_name(url).unwrap(), Some(hello))
+            .await
+            .is_err());
+        assert!(!cached.exists());
+
+        tokio::fs::write(&cached, "hello").await.unwrap();
+        let outcome = downloader
+            .download_as(url, &cache_file_name(url).unwrap(), Some(hello))
+            .await
+            .unwrap();
+        assert!(outcome.was_cached);
+    }
+
+    #[tokio::test]
+    async fn test_count_downloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("tool.tar.gz");
+        tokio::fs::write(&source, "hello").await.unwrap();
+        let url = format!("file://{}", source.display());
+        let downloader = Downloader::new(dir.path(), Duration::from_secs(1)).unwrap();
+
+        // 第二次下载命中缓存，不计入传输的字节数
+        let (outcome, bytes) = count_downloaded(async {
+            downloader.download(&url).await?;
+            downloader.download(&url).await
+        })
+        .await;
+        assert!(outcome.unwrap().was_cached);
+        assert_eq!(bytes, 5);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_vendor_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let vendor_dir = dir.path().join("vendor");
+        let artifact = dir.path().join("tool");
+        tokio::fs::write(&artifact, "binary").await.unwrap();
+        // 地址不可访问，只能从 vendor 目录获取
+        let info = VersionInfo::new("2.0.0", "linux-x64", "http://127.0.0.1:9/tool");
+        crate::vendor::record_artifact(&vendor_dir, &info, &artifact)
+            .await
+            .unwrap();
+
+        let downloader = Downloader::new(dir.path().join("cache"), Duration::from_secs(1))
+            .unwrap()
+            .with_vendor(Some(VendorMode::Resolve(vendor_dir.clone())));
+        let outcome = downloader.fetch(&info).await.unwrap();
+        assert_eq!(
+            outcome.path,
+            vendor_dir.join("artifacts").join("2.0.0-tool")
+        );
+        assert!(outcome.was_cached);
+
+        let mut pinned = info.clone();
+        pinned.checksum = Some("deadbeef".to_string());
+        assert!(matches!(
+            downloader.fetch(&pinned).await,
+            Err(PluginError::ChecksumMismatch { .. })
+        ));
+        // 校验失败时不删除 vendor 目录中的制品
+        assert!(outcome.path.is_file());
+    }
+
+    #[test]
+    fn test_partial_path() {
+        assert_eq!(
+            partial_path(Path::new("/cache/downloads/go.tar.gz")),
+            PathBuf::from("/cache/downloads/go.tar.gz.part")
+        );
+        assert_eq!(
+            validator_path(Path::new("/cache/downloads/go.tar.gz.part")),
+            PathBuf::from("/cache/downloads/go.tar.gz.part.etag")
+        );
+    }
+
+    #[test]
+    fn test_content_range_start() {
+        assert_eq!(content_range_start("bytes 5-9/10"), Some(5));
+        assert_eq!(content_range_start("bytes 0-9/*"), Some(0));
+        assert_eq!(content_range_start("bytes */10"), None);
+        assert_eq!(content_range_start("items 5-9/10"), None);
+    }
+
+    /// 本地 HTTP 服务器，当前内容为 `HELLOworld`，ETag 为 `"v2"`：
+    /// `If-Range` 一致时返回从第 5 字节开始的 206，为 `"bogus"` 时返回起点错误的 206，
+    /// 其他情况返回 200 完整内容
+    async fn serve_artifact() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let (status, range, body) = if request.contains("if-range: \"v2\"") {
+                    ("206 Partial Content", Some("bytes 5-9/10"), "world")
+                } else if request.contains("if-range: \"bogus\"") {
+                    ("206 Partial Content", Some("bytes 0-9/10"), "HELLOworld")
+                } else {
+                    ("200 OK", None, "HELLOworld")
+                };
+                let mut response = format!(
+                    "HTTP/1.1 {}\r\nETag: \"v2\"\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    status,
+                    body.len()
+                );
+                if let Some(range) = range {
+                    response.push_str(&format!("Content-Range: {}\r\n", range));
+                }
+                response.push_str("\r\n");
+                response.push_str(body);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/tool.tar.gz", addr)
+    }
+
+    #[tokio::test]
+    async fn test_resume_checks_validator_and_content_range() {
+        let url = serve_artifact().await;
+        let dir = tempfile::tempdir().unwrap();
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let downloader = Downloader::with_client(dir.path(), client);
+        let target = dir.path().join("downloads").join("tool.tar.gz");
+        let partial = partial_path(&target);
+        let validator = validator_path(&partial);
+        tokio::fs::create_dir_all(target.parent().unwrap())
+            .await
+            .unwrap();
+
+        // (部分文件内容, 记录的 ETag, 预期的续传起点)
+        let cases = [
+            // ETag 一致：从断点续传
+            ("HELLO", Some("\"v2\""), 5),
+            // 远端文件已变化：服务器返回 200，旧的部分内容被丢弃
+            ("hello", Some("\"v1\""), 0),
+            // 没有记录 ETag：不发送 Range，从头下载
+            ("hello", None, 0),
+            // 206 的起点不是断点：重新请求完整文件
+            ("hello", Some("\"bogus\""), 0),
+        ];
+        for (content, etag, resumed_from) in cases {
+            remove_file_if_exists(&target).await.unwrap();
+            tokio::fs::write(&partial, content).await.unwrap();
+            match etag {
+                Some(etag) => tokio::fs::write(&validator, etag).await.unwrap(),
+                None => remove_file_if_exists(&validator).await.unwrap(),
+            }
+
+            let outcome = downloader
+                .download_as(&url, "tool.tar.gz", None)
+                .await
+                .unwrap();
+            assert_eq!(outcome.resumed_from, resumed_from, "{:?}", etag);
+            assert_eq!(std::fs::read_to_string(&target).unwrap(), "HELLOworld");
+            assert!(!partial.exists());
+            assert!(!validator.exists());
+        }
+    }
+}
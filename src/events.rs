@@ -0,0 +1,165 @@
+//! PLM 生命周期事件
+//!
+//! `PluginManager` 在插件注册、安装、关闭和发现时发出事件，
+//! 宿主应用（GUI、服务等）可以注册监听器观察这些事件，
+//! 也可以通过 `subscribe` 获得异步事件流，多个消费者互不影响。
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// 事件流缓冲区大小，消费者落后超过该数量时会丢失最早的事件
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// 生命周期事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlmEvent {
+    /// 插件已注册
+    PluginRegistered { plugin: String },
+    /// 开始安装
+    InstallStarted { plugin: String, version: String },
+    /// 安装完成
+    InstallCompleted {
+        plugin: String,
+        version: String,
+        path: String,
+        duration: Duration,
+    },
+    /// 安装失败
+    InstallFailed {
+        plugin: String,
+        version: String,
+        error: String,
+    },
+    /// 插件已关闭
+    PluginShutdown { plugin: String },
+    /// 插件发现完成
+    DiscoveryFinished { count: usize },
+    /// 已应用新的项目配置
+    ConfigReloaded {
+        added: usize,
+        removed: usize,
+        reloaded: usize,
+    },
+}
+
+/// 监听器标识，用于移除监听器
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(u64);
+
+type Listener = Arc<dyn Fn(&PlmEvent) + Send + Sync>;
+
+/// 事件总线
+pub struct EventBus {
+    listeners: Mutex<Vec<(ListenerId, Listener)>>,
+    next_id: AtomicU64,
+    sender: broadcast::Sender<PlmEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            listeners: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(0),
+            sender,
+        }
+    }
+}
+
+impl EventBus {
+    /// 创建事件总线
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 订阅事件流，只会收到订阅之后发出的事件
+    ///
+    /// 接收端落后过多时 `recv` 返回 `RecvError::Lagged`，之后可以继续接收
+    pub fn subscribe(&self) -> broadcast::Receiver<PlmEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 注册监听器
+    pub fn add_listener(&self, listener: impl Fn(&PlmEvent) + Send + Sync + 'static) -> ListenerId {
+        let id = ListenerId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((id, Arc::new(listener)));
+        id
+    }
+
+    /// 移除监听器，返回是否找到该监听器
+    pub fn remove_listener(&self, id: ListenerId) -> bool {
+        let mut listeners = self.listeners.lock().unwrap_or_else(|e| e.into_inner());
+        let before = listeners.len();
+        listeners.retain(|(listener_id, _)| *listener_id != id);
+        listeners.len() != before
+    }
+
+    /// 向所有监听器发送事件
+    ///
+    /// 调用监听器时不持有锁，监听器中可以安全地注册或移除监听器
+    pub fn emit(&self, event: PlmEvent) {
+        let listeners: Vec<Listener> = self
+            .listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(_, listener)| Arc::clone(listener))
+            .collect();
+        for listener in listeners {
+            listener(&event);
+        }
+        // 没有订阅者时发送失败，忽略即可
+        let _ = self.sender.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_emit_remove() {
+        let bus = EventBus::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let sink = Arc::clone(&received);
+        let id = bus.add_listener(move |event| sink.lock().unwrap().push(event.clone()));
+
+        bus.emit(PlmEvent::DiscoveryFinished { count: 2 });
+        assert!(bus.remove_listener(id));
+        bus.emit(PlmEvent::DiscoveryFinished { count: 3 });
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![PlmEvent::DiscoveryFinished { count: 2 }]
+        );
+        assert!(!bus.remove_listener(id));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_multiple_consumers() {
+        let bus = EventBus::new();
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+
+        bus.emit(PlmEvent::PluginRegistered {
+            plugin: "node".to_string(),
+        });
+
+        for rx in [&mut first, &mut second] {
+            assert_eq!(
+                rx.recv().await.unwrap(),
+                PlmEvent::PluginRegistered {
+                    plugin: "node".to_string()
+                }
+            );
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! PLM 配置导出
+//!
+//! 把 `plm.json` 中启用的插件及版本转换为其他工具的环境描述，
+//! 让使用 devcontainer 或 Nix 的团队以同一份配置为准：
+//!
+//! - `devcontainer`：`devcontainer.json`，常见工具映射为官方 feature，
+//!   其余插件在容器创建后通过 `plm sync` 安装
+//! - `nix`：`shell.nix`，插件映射为 nixpkgs 中对应版本的包
+//! - `dockerfile`：Dockerfile 片段，构建时挂载 `plm.json` 并执行 `plm sync`，
+//!   把固定版本的工具链安装到镜像中
+//!
+//! 版本为范围约束（如 `^18`）时只取其中的主/次版本号。
+
+use crate::config::ProjectConfig;
+use crate::traits::PluginError;
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::str::FromStr;
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// PLM 配置文件本身
+    #[default]
+    Json,
+    /// devcontainer.json
+    Devcontainer,
+    /// Nix shell 表达式
+    Nix,
+    /// Dockerfile 片段
+    Dockerfile,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "devcontainer" => Ok(ExportFormat::Devcontainer),
+            "nix" => Ok(ExportFormat::Nix),
+            "dockerfile" => Ok(ExportFormat::Dockerfile),
+            other => Err(format!(
+                "unknown export format '{}' (expected json, devcontainer, nix or dockerfile)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Devcontainer => "devcontainer",
+            ExportFormat::Nix => "nix",
+            ExportFormat::Dockerfile => "dockerfile",
+        })
+    }
+}
+
+/// 按格式导出配置
+pub fn export(config: &ProjectConfig, format: ExportFormat) -> Result<String, PluginError> {
+    match format {
+        ExportFormat::Json => config.to_json_string(),
+        ExportFormat::Devcontainer => serde_json::to_string_pretty(&to_devcontainer(config))
+            .map_err(|e| PluginError::ConfigError(format!("序列化 devcontainer 配置失败: {}", e))),
+        ExportFormat::Nix => Ok(to_nix(config)),
+        ExportFormat::Dockerfile => Ok(to_dockerfile(config)),
+    }
+}
+
+/// 生成 devcontainer.json
+pub fn to_devcontainer(config: &ProjectConfig) -> Value {
+    let mut features = Map::new();
+    let mut unmapped = Map::new();
+
+    for (name, version) in enabled_plugins(config) {
+        match devcontainer_feature(&name) {
+            Some(feature) => {
+                let version = version
+                    .as_deref()
+                    .map(plain_version)
+                    .unwrap_or_else(|| "latest".to_string());
+                features.insert(feature.to_string(), json!({ "version": version }));
+            }
+            None => {
+                unmapped.insert(name, version.map_or(Value::Null, Value::String));
+            }
+        }
+    }
+
+    let mut root = Map::new();
+    root.insert(
+        "name".to_string(),
+        Value::String(config.project_name.clone()),
+    );
+    root.insert("features".to_string(), Value::Object(features));
+    if !unmapped.is_empty() {
+        // 没有对应 feature 的插件由 PLM 在容器中安装
+        root.insert(
+            "postCreateCommand".to_string(),
+            Value::String("plm sync".to_string()),
+        );
+        root.insert(
+            "customizations".to_string(),
+            json!({ "plm": { "plugins": unmapped } }),
+        );
+    }
+    Value::Object(root)
+}
+
+/// 生成 shell.nix
+pub fn to_nix(config: &ProjectConfig) -> String {
+    let mut packages = Vec::new();
+    for (name, version) in enabled_plugins(config) {
+        let attrs = nix_packages(&name, version.as_deref());
+        let comment = match &version {
+            Some(version) => format!("{} {}", name, version),
+            None => name.clone(),
+        };
+        for attr in attrs {
+            packages.push(format!("    pkgs.{}  # {}", attr, comment));
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str("# Generated by `plm export --format nix`\n");
+    out.push_str("{ pkgs ? import <nixpkgs> {} }:\n\n");
+    out.push_str("pkgs.mkShell {\n");
+    out.push_str(&format!("  name = {};\n", nix_string(&config.project_name)));
+    out.push_str("  packages = [\n");
+    for line in packages {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.push_str("  ];\n");
+    out.push_str("}\n");
+    out
+}
+
+/// 生成 Dockerfile 片段
+///
+/// 输出只依赖配置内容（插件按名称排序），同一份配置总是生成相同的指令；
+/// 未固定到具体版本的插件会以注释提示，因为它们的安装结果可能随时间变化
+pub fn to_dockerfile(config: &ProjectConfig) -> String {
+    let plugins = enabled_plugins(config);
+
+    let mut out = String::new();
+    out.push_str("# Generated by `plm export --format dockerfile`; requires plm on PATH\n");
+    if !plugins.is_empty() {
+        let toolchain: Vec<String> = plugins
+            .iter()
+            .map(|(name, version)| format!("{} {}", name, version.as_deref().unwrap_or("latest")))
+            .collect();
+        out.push_str(&format!("# Toolchain: {}\n", toolchain.join(", ")));
+    }
+    for (name, version) in &plugins {
+        let pinned = version
+            .as_deref()
+            .is_some_and(|v| crate::version::Version::parse(v).is_ok());
+        if !pinned {
+            out.push_str(&format!(
+                "# WARNING: {} is not pinned to an exact version; the build is not reproducible\n",
+                name
+            ));
+        }
+    }
+
+    let mut mounts = vec!["--mount=type=bind,source=plm.json,target=/tmp/plm/plm.json".to_string()];
+    if let Some(cache_dir) = container_path(&config.global_settings.cache_dir) {
+        mounts.push(format!("--mount=type=cache,target={}", cache_dir));
+    }
+    out.push_str("RUN ");
+    for mount in mounts {
+        out.push_str(&mount);
+        out.push_str(" \\\n    ");
+    }
+    out.push_str("plm --config /tmp/plm/plm.json sync --quiet\n");
+    out
+}
+
+/// 容器中 root 用户看到的路径，`~` 展开为 `/root`，相对路径返回 None
+fn container_path(path: &str) -> Option<String> {
+    if let Some(rest) = path.strip_prefix("~/") {
+        Some(format!("/root/{}", rest))
+    } else if path.starts_with('/') {
+        Some(path.to_string())
+    } else {
+        None
+    }
+}
+
+/// 启用的插件及版本，按名称排序
+fn enabled_plugins(config: &ProjectConfig) -> Vec<(String, Option<String>)> {
+    let mut plugins: Vec<(String, Option<String>)> = config
+        .plugins
+        .values()
+        .filter(|p| p.enabled)
+        .map(|p| (p.name.clone(), p.version.clone()))
+        .collect();
+    plugins.sort();
+    plugins
+}
+
+fn devcontainer_feature(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "node" | "nodejs" => "ghcr.io/devcontainers/features/node:1",
+        "python" => "ghcr.io/devcontainers/features/python:1",
+        "go" | "golang" => "ghcr.io/devcontainers/features/go:1",
+        "rust" => "ghcr.io/devcontainers/features/rust:1",
+        "java" => "ghcr.io/devcontainers/features/java:1",
+        "ruby" => "ghcr.io/devcontainers/features/ruby:1",
+        "dotnet" => "ghcr.io/devcontainers/features/dotnet:2",
+        "php" => "ghcr.io/devcontainers/features/php:1",
+        "terraform" => "ghcr.io/devcontainers/features/terraform:1",
+        _ => return None,
+    })
+}
+
+fn nix_packages(name: &str, version: Option<&str>) -> Vec<String> {
+    let (major, minor) = version.map(version_parts).unwrap_or((None, None));
+    let versioned = |prefix: &str, fallback: &str| match (major, minor) {
+        (Some(major), Some(minor)) => format!("{}{}_{}", prefix, major, minor),
+        _ => fallback.to_string(),
+    };
+    match name {
+        "node" | "nodejs" => {
+            vec![major.map_or("nodejs".to_string(), |m| format!("nodejs_{}", m))]
+        }
+        "python" => vec![match (major, minor) {
+            (Some(major), Some(minor)) => format!("python{}{}", major, minor),
+            _ => "python3".to_string(),
+        }],
+        "go" | "golang" => vec![versioned("go_", "go")],
+        "ruby" => vec![versioned("ruby_", "ruby")],
+        "java" => vec![major.map_or("jdk".to_string(), |m| format!("jdk{}", m))],
+        "rust" => vec!["rustc".to_string(), "cargo".to_string()],
+        other => vec![other.to_string()],
+    }
+}
+
+/// 去掉约束运算符，如 `^18.2` -> `18.2`
+fn plain_version(version: &str) -> String {
+    version
+        .split(',')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .trim_start_matches(['^', '~', '=', '>', '<', 'v'])
+        .trim()
+        .to_string()
+}
+
+fn version_parts(version: &str) -> (Option<u64>, Option<u64>) {
+    let plain = plain_version(version);
+    let mut parts = plain.split('.').map(|p| p.parse::<u64>().ok());
+    (parts.next().flatten(), parts.next().flatten())
+}
+
+fn nix_string(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace("${", "\\${")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PluginConfig;
+
+    fn sample_config() -> ProjectConfig {
+        let mut config = ProjectConfig::default_for_project("demo", "/tmp/demo");
+        for (name, version) in [("node", "^20.1"), ("go", "1.21.3"), ("protoc", "25.1")] {
+            let mut plugin = PluginConfig::new(name);
+            plugin.set_version(version);
+            plugin.enabled = true;
+            config.add_plugin(plugin);
+        }
+        config
+    }
+
+    #[test]
+    fn test_devcontainer_export() {
+        let value = to_devcontainer(&sample_config());
+        assert_eq!(value["name"], "demo");
+        assert_eq!(
+            value["features"]["ghcr.io/devcontainers/features/node:1"]["version"],
+            "20.1"
+        );
+        assert_eq!(
+            value["features"]["ghcr.io/devcontainers/features/go:1"]["version"],
+            "1.21.3"
+        );
+        assert_eq!(value["customizations"]["plm"]["plugins"]["protoc"], "25.1");
+        assert_eq!(value["postCreateCommand"], "plm sync");
+    }
+
+    #[test]
+    fn test_nix_export() {
+        let nix = to_nix(&sample_config());
+        assert!(nix.contains("name = \"demo\";"));
+        assert!(nix.contains("pkgs.go_1_21  # go 1.21.3"));
+        assert!(nix.contains("pkgs.nodejs_20  # node ^20.1"));
+        assert!(nix.contains("pkgs.protoc  # protoc 25.1"));
+        assert_eq!("nix".parse::<ExportFormat>(), Ok(ExportFormat::Nix));
+        assert!("yaml".parse::<ExportFormat>().is_err());
+    }
+
+    #[test]
+    fn test_dockerfile_export() {
+        let config = sample_config();
+        let dockerfile = to_dockerfile(&config);
+        assert_eq!(dockerfile, to_dockerfile(&config));
+        assert!(dockerfile.contains("# Toolchain: go 1.21.3, node ^20.1, protoc 25.1"));
+        assert!(dockerfile.contains("WARNING: node is not pinned"));
+        assert!(!dockerfile.contains("WARNING: go"));
+        assert!(dockerfile.contains("--mount=type=cache,target=/root/.plm/cache"));
+        assert!(dockerfile.ends_with("plm --config /tmp/plm/plm.json sync --quiet\n"));
+    }
+}
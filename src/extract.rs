@@ -0,0 +1,239 @@
+//! PLM 制品解压
+//!
+//! 解压先写入 `<dest>.partial` 暂存目录，完成后再重命名为目标目录，
+//! 解压或校验失败、任务被取消时暂存目录由 `TempFileGuard` 删除，不会留下不完整的安装目录。
+
+use crate::temp::TempFileGuard;
+use crate::timings::{self, Phase};
+use crate::traits::{ArchiveFormat, PluginError};
+use bytes::Bytes;
+use flate2::read::GzDecoder;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+/// 把异步下载的数据块转换为同步 `Read`，供解压线程使用
+///
+/// 数据块直接从网络缓冲区传递，不经过磁盘
+pub struct ChannelReader {
+    rx: mpsc::Receiver<io::Result<Bytes>>,
+    current: Bytes,
+}
+
+impl ChannelReader {
+    pub fn new(rx: mpsc::Receiver<io::Result<Bytes>>) -> Self {
+        Self {
+            rx,
+            current: Bytes::new(),
+        }
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.rx.blocking_recv() {
+                Some(Ok(chunk)) => self.current = chunk,
+                Some(Err(e)) => return Err(e),
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.current.len());
+        buf[..n].copy_from_slice(&self.current[..n]);
+        self.current = self.current.slice(n..);
+        Ok(n)
+    }
+}
+
+/// 解压 tar.gz 数据流，条目路径不能越出 `dest`
+pub fn unpack_tar_gz(reader: impl Read, dest: &Path) -> Result<(), PluginError> {
+    let mut archive = tar::Archive::new(GzDecoder::new(reader));
+    archive.set_preserve_permissions(true);
+    archive
+        .unpack(dest)
+        .map_err(|e| PluginError::InstallationError(format!("解压 tar.gz 失败: {}", e)))
+}
+
+/// 解压 zip 文件，条目路径不能越出 `dest`
+pub fn unpack_zip(archive: &Path, dest: &Path) -> Result<(), PluginError> {
+    let file = std::fs::File::open(archive)
+        .map_err(|e| PluginError::IoError(format!("打开 {} 失败: {}", archive.display(), e)))?;
+    let mut zip = zip::ZipArchive::new(file)
+        .map_err(|e| PluginError::InstallationError(format!("读取 zip 失败: {}", e)))?;
+
+    for index in 0..zip.len() {
+        let mut entry = zip
+            .by_index(index)
+            .map_err(|e| PluginError::InstallationError(format!("读取 zip 条目失败: {}", e)))?;
+        let relative = entry
+            .enclosed_name()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| {
+                PluginError::InstallationError(format!("zip 条目路径不安全: {}", entry.name()))
+            })?;
+        let target = dest.join(relative);
+
+        if entry.is_dir() {
+            std::fs::create_dir_all(&target)
+                .map_err(|e| PluginError::IoError(format!("创建目录失败: {}", e)))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| PluginError::IoError(format!("创建目录失败: {}", e)))?;
+        }
+        let mut out = std::fs::File::create(&target)
+            .map_err(|e| PluginError::IoError(format!("写入 {} 失败: {}", target.display(), e)))?;
+        io::copy(&mut entry, &mut out)
+            .map_err(|e| PluginError::IoError(format!("写入 {} 失败: {}", target.display(), e)))?;
+
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&target, std::fs::Permissions::from_mode(mode))
+                .map_err(|e| PluginError::IoError(format!("设置权限失败: {}", e)))?;
+        }
+    }
+    Ok(())
+}
+
+/// 解压磁盘上的制品到 `dest`，`Raw` 格式的文件直接复制到 `dest` 下
+pub async fn extract_file(
+    archive: &Path,
+    format: ArchiveFormat,
+    dest: &Path,
+) -> Result<(), PluginError> {
+    timings::measure(Phase::Extract, extract_staged(archive, format, dest)).await
+}
+
+async fn extract_staged(
+    archive: &Path,
+    format: ArchiveFormat,
+    dest: &Path,
+) -> Result<(), PluginError> {
+    let staging = TempFileGuard::new(staging_path(dest));
+    staging.clear().await?;
+
+    let archive = archive.to_path_buf();
+    let unpack_into = staging.path().to_path_buf();
+    let result = tokio::task::spawn_blocking(move || -> Result<(), PluginError> {
+        std::fs::create_dir_all(&unpack_into)
+            .map_err(|e| PluginError::IoError(format!("创建目录失败: {}", e)))?;
+        match format {
+            ArchiveFormat::TarGz => {
+                let file = std::fs::File::open(&archive).map_err(|e| {
+                    PluginError::IoError(format!("打开 {} 失败: {}", archive.display(), e))
+                })?;
+                unpack_tar_gz(io::BufReader::new(file), &unpack_into)
+            }
+            ArchiveFormat::Zip => unpack_zip(&archive, &unpack_into),
+            ArchiveFormat::Raw => {
+                let name = archive.file_name().ok_or_else(|| {
+                    PluginError::ValidationError(format!("无效的文件路径: {}", archive.display()))
+                })?;
+                std::fs::copy(&archive, unpack_into.join(name))
+                    .map(|_| ())
+                    .map_err(|e| PluginError::IoError(format!("复制文件失败: {}", e)))
+            }
+        }
+    })
+    .await
+    .map_err(|e| PluginError::IoError(format!("解压任务失败: {}", e)))?;
+
+    finish_staging(staging, dest, result).await
+}
+
+/// 暂存目录路径
+pub(crate) fn staging_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_os_string();
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+/// 解压成功时用暂存目录替换目标目录，失败时丢弃守卫删除暂存目录
+pub(crate) async fn finish_staging<T>(
+    staging: TempFileGuard,
+    dest: &Path,
+    result: Result<T, PluginError>,
+) -> Result<T, PluginError> {
+    let value = result?;
+    remove_dir_if_exists(dest).await?;
+    staging.persist(dest).await?;
+    Ok(value)
+}
+
+async fn remove_dir_if_exists(path: &Path) -> Result<(), PluginError> {
+    match tokio::fs::remove_dir_all(path).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(PluginError::IoError(format!(
+            "删除 {} 失败: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder =
+            tar::Builder::new(GzEncoder::new(Vec::new(), flate2::Compression::fast()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *content).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unpack_tar_gz_from_channel() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = tar_gz(&[("tool/bin/tool", &b"#!/bin/sh\n"[..])]);
+
+        let (tx, rx) = mpsc::channel(4);
+        let dest = dir.path().join("out");
+        let unpack_into = dest.clone();
+        let unpack = tokio::task::spawn_blocking(move || {
+            unpack_tar_gz(ChannelReader::new(rx), &unpack_into)
+        });
+        // 分成多个小块发送，模拟网络数据流；读到归档结尾后解压线程可能不再接收末尾的填充
+        for chunk in archive.chunks(7) {
+            if tx.send(Ok(Bytes::copy_from_slice(chunk))).await.is_err() {
+                break;
+            }
+        }
+        drop(tx);
+
+        unpack.await.unwrap().unwrap();
+        assert_eq!(
+            std::fs::read(dest.join("tool/bin/tool")).unwrap(),
+            b"#!/bin/sh\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_zip_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("tool.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+        zip.start_file("bin/tool.exe", zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(b"MZ").unwrap();
+        zip.finish().unwrap();
+
+        let dest = dir.path().join("installed");
+        extract_file(&archive, ArchiveFormat::Zip, &dest)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(dest.join("bin/tool.exe")).unwrap(), b"MZ");
+        assert!(!staging_path(&dest).exists());
+    }
+}
@@ -0,0 +1,106 @@
+//! PLM Git 钩子安装
+//!
+//! 在项目所在的 git 仓库中安装 pre-commit / post-checkout 钩子，
+//! 使提交前自动检查工具链、切换分支后自动同步工具链。
+
+use crate::config::GitHooksConfig;
+use crate::traits::PluginError;
+use std::path::{Path, PathBuf};
+
+/// PLM 生成的钩子脚本中的标记行，用于识别可安全覆盖的钩子
+const HOOK_MARKER: &str = "# Installed by plm (plm hooks install-git)";
+
+/// 安装 git 钩子，返回写入的钩子文件路径
+///
+/// 已存在且不是由 PLM 生成的钩子不会被覆盖，除非指定 `force`
+pub async fn install_git_hooks(
+    project_root: &str,
+    hooks: &GitHooksConfig,
+    force: bool,
+) -> Result<Vec<PathBuf>, PluginError> {
+    let git_dir = find_git_dir(Path::new(project_root)).await?;
+    let hooks_dir = git_dir.join("hooks");
+    tokio::fs::create_dir_all(&hooks_dir)
+        .await
+        .map_err(|e| PluginError::IoError(format!("创建钩子目录失败: {}", e)))?;
+
+    let mut installed = Vec::new();
+    for (hook_name, command) in [
+        ("pre-commit", &hooks.pre_commit),
+        ("post-checkout", &hooks.post_checkout),
+    ] {
+        let Some(command) = command else {
+            continue;
+        };
+
+        let path = hooks_dir.join(hook_name);
+        if !force {
+            if let Ok(existing) = tokio::fs::read_to_string(&path).await {
+                if !existing.contains(HOOK_MARKER) {
+                    return Err(PluginError::ValidationError(format!(
+                        "钩子 {} 已存在且不是由 PLM 生成，使用 --force 覆盖",
+                        path.display()
+                    )));
+                }
+            }
+        }
+
+        tokio::fs::write(&path, hook_script(command))
+            .await
+            .map_err(|e| PluginError::IoError(format!("写入钩子 {} 失败: {}", hook_name, e)))?;
+        make_executable(&path).await?;
+        installed.push(path);
+    }
+
+    Ok(installed)
+}
+
+/// 生成钩子脚本内容
+fn hook_script(command: &str) -> String {
+    format!("#!/bin/sh\n{}\n{}\n", HOOK_MARKER, command)
+}
+
+/// 从项目根目录向上查找 git 目录，支持 worktree 中的 `.git` 文件
+async fn find_git_dir(start: &Path) -> Result<PathBuf, PluginError> {
+    let start = tokio::fs::canonicalize(start)
+        .await
+        .map_err(|e| PluginError::IoError(format!("无法解析项目路径: {}", e)))?;
+
+    for dir in start.ancestors() {
+        let candidate = dir.join(".git");
+        let Ok(meta) = tokio::fs::metadata(&candidate).await else {
+            continue;
+        };
+
+        if meta.is_dir() {
+            return Ok(candidate);
+        }
+
+        // worktree / submodule: `.git` 文件内容为 `gitdir: <path>`
+        let content = tokio::fs::read_to_string(&candidate)
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取 .git 文件失败: {}", e)))?;
+        if let Some(git_dir) = content.trim().strip_prefix("gitdir:") {
+            return Ok(dir.join(git_dir.trim()));
+        }
+    }
+
+    Err(PluginError::NotFound(format!(
+        "{} 不在 git 仓库中",
+        start.display()
+    )))
+}
+
+#[cfg(unix)]
+async fn make_executable(path: &Path) -> Result<(), PluginError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .await
+        .map_err(|e| PluginError::PermissionDenied(format!("设置钩子可执行权限失败: {}", e)))
+}
+
+#[cfg(not(unix))]
+async fn make_executable(_path: &Path) -> Result<(), PluginError> {
+    Ok(())
+}
@@ -0,0 +1,443 @@
+//! PLM Git 仓库注册表
+//!
+//! 注册表地址以 `git+` 开头时（如 `git+https://github.com/acme/plm-index.git`，
+//! 可以用 `#<branch>` 指定分支），注册表客户端把仓库浅克隆到 `cache_dir/git-registries` 下，
+//! 每个客户端在第一次查询前更新一次检出，之后的查询都读取本地文件。
+//! 团队可以通过 pull request 维护插件目录，并保留完整的变更历史。
+//! 更新失败（如离线）时继续使用已有的检出。
+//!
+//! 仓库布局与 crates.io-index 相同，按插件名称（小写）的长度分目录：
+//!
+//! ```text
+//! 1/a
+//! 2/ab
+//! 3/a/abc
+//! te/rr/terraform
+//! ```
+//!
+//! 每个文件每行一个 JSON 对象：版本行的格式与注册表接口返回的版本信息相同，
+//! 新版本追加到文件末尾；可选的 `{"metadata": {...}}` 行提供插件的描述、作者、标签等信息：
+//!
+//! ```text
+//! {"metadata": {"description": "Infrastructure as code", "tags": ["iac"]}}
+//! {"version": "1.5.0", "platform": "linux-x64", "download_url": "https://...", "prerelease": false}
+//! {"version": "1.6.0", "platform": "linux-x64", "download_url": "https://...", "prerelease": false}
+//! ```
+
+use crate::auth::Credentials;
+use crate::permissions;
+use crate::registry::RegistryEntry;
+use crate::static_registry::{IndexedPlugin, StaticIndex};
+use crate::traits::{PluginError, PluginMetadata, VersionInfo};
+use crate::version::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Git 注册表地址前缀
+pub const GIT_PREFIX: &str = "git+";
+
+/// `cache_dir` 下存放注册表检出的目录名
+pub const CHECKOUTS_DIR: &str = "git-registries";
+
+/// 注册表地址是否指向 Git 仓库
+pub fn is_git_url(url: &str) -> bool {
+    url.starts_with(GIT_PREFIX)
+}
+
+/// 插件在索引仓库中的相对路径（crates.io-index 布局）
+pub fn index_path(name: &str) -> PathBuf {
+    let name = name.to_lowercase();
+    let chars: Vec<char> = name.chars().collect();
+    let prefix: String = match chars.len() {
+        0..=2 => chars.len().to_string(),
+        3 => format!("3/{}", chars[0]),
+        _ => format!(
+            "{}/{}",
+            chars[..2].iter().collect::<String>(),
+            chars[2..4].iter().collect::<String>()
+        ),
+    };
+    Path::new(&prefix).join(name)
+}
+
+/// 索引文件中的插件信息行，所有字段均可省略
+#[derive(Debug, Default, Deserialize)]
+struct IndexMetadata {
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    homepage: Option<String>,
+    #[serde(default)]
+    repository: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+    #[serde(default)]
+    min_plm_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IndexLine {
+    Metadata { metadata: IndexMetadata },
+    Version(Box<VersionInfo>),
+}
+
+/// 解析插件的索引文件，版本按从新到旧排列
+pub fn parse_index_file(name: &str, content: &str) -> Result<IndexedPlugin, PluginError> {
+    let mut metadata = IndexMetadata::default();
+    let mut versions = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line).map_err(|e| {
+            PluginError::ConfigError(format!("索引 {} 第 {} 行无效: {}", name, number + 1, e))
+        })? {
+            IndexLine::Metadata { metadata: line } => metadata = line,
+            IndexLine::Version(info) => versions.push(*info),
+        }
+    }
+    // 稳定排序，同版本的各平台制品保持文件中的顺序
+    versions.sort_by(|a, b| {
+        Version::parse(&b.version)
+            .ok()
+            .cmp(&Version::parse(&a.version).ok())
+    });
+
+    let mut supported_platforms: Vec<String> = Vec::new();
+    for info in &versions {
+        if !supported_platforms.contains(&info.platform) {
+            supported_platforms.push(info.platform.clone());
+        }
+    }
+    let version = versions
+        .iter()
+        .find(|info| !info.prerelease && !info.yanked)
+        .or_else(|| versions.first())
+        .map(|info| info.version.clone())
+        .unwrap_or_default();
+
+    Ok(IndexedPlugin {
+        metadata: PluginMetadata {
+            name: name.to_string(),
+            version,
+            description: metadata.description,
+            author: metadata.author,
+            homepage: metadata.homepage,
+            repository: metadata.repository,
+            supported_platforms,
+            tags: metadata.tags,
+            dependencies: metadata.dependencies,
+            min_plm_version: metadata.min_plm_version,
+        },
+        versions,
+    })
+}
+
+/// Git 仓库注册表的本地检出
+#[derive(Debug, Clone)]
+pub struct GitIndex {
+    /// 去掉 `git+` 前缀和分支后的仓库地址
+    repo: String,
+    branch: Option<String>,
+    checkout: PathBuf,
+    /// 传给 git 的凭据环境变量
+    env: Vec<(String, String)>,
+}
+
+impl GitIndex {
+    /// `url` 为 `git+<仓库地址>[#<分支>]`，检出位于 `cache_dir/git-registries` 下
+    pub fn new(url: &str, cache_dir: &Path) -> Self {
+        let spec = url.strip_prefix(GIT_PREFIX).unwrap_or(url);
+        let (repo, branch) = match spec.split_once('#') {
+            Some((repo, branch)) if !branch.is_empty() => (repo, Some(branch.to_string())),
+            Some((repo, _)) => (repo, None),
+            None => (spec, None),
+        };
+
+        // 目录名带上仓库名便于辨认，哈希区分同名仓库和不同分支
+        let label: String = repo
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches(".git")
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+            .collect();
+        let hash = format!("{:x}", Sha256::digest(spec.as_bytes()));
+        let dir = if label.is_empty() {
+            hash[..16].to_string()
+        } else {
+            format!("{}-{}", label, &hash[..16])
+        };
+
+        Self {
+            repo: repo.to_string(),
+            branch,
+            checkout: cache_dir.join(CHECKOUTS_DIR).join(dir),
+            env: Vec::new(),
+        }
+    }
+
+    /// 克隆和更新时使用插件源的凭据（SSH 密钥或 HTTPS 请求头）
+    pub fn with_credentials(mut self, credentials: Option<&Credentials>) -> Self {
+        self.env = credentials.map(Credentials::git_env).unwrap_or_default();
+        self
+    }
+
+    /// 本地检出目录
+    pub fn checkout(&self) -> &Path {
+        &self.checkout
+    }
+
+    /// 克隆仓库或更新已有的检出
+    ///
+    /// 已有检出时更新失败只记录警告，继续使用旧的检出
+    pub async fn update(&self) -> Result<(), PluginError> {
+        permissions::check(|guard| guard.check_network(&self.repo))?;
+        if self.checkout.join(".git").exists() {
+            let reference = self.branch.as_deref().unwrap_or("HEAD");
+            let updated = async {
+                self.git_in_checkout(&["fetch", "--depth", "1", "origin", reference])
+                    .await?;
+                self.git_in_checkout(&["reset", "--hard", "FETCH_HEAD"])
+                    .await
+            }
+            .await;
+            if let Err(e) = updated {
+                log::warn!("更新注册表 {} 失败，使用已有的检出: {}", self.repo, e);
+            }
+            return Ok(());
+        }
+
+        if let Some(parent) = self.checkout.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error("创建", parent, e))?;
+        }
+        // 清理上次中断的克隆
+        if self.checkout.exists() {
+            tokio::fs::remove_dir_all(&self.checkout)
+                .await
+                .map_err(|e| io_error("删除", &self.checkout, e))?;
+        }
+        let checkout = self.checkout.to_string_lossy().into_owned();
+        let mut args = vec!["clone", "--depth", "1"];
+        if let Some(branch) = &self.branch {
+            args.extend(["--branch", branch.as_str()]);
+        }
+        args.extend([self.repo.as_str(), checkout.as_str()]);
+        let cloned = git(&args, &self.env).await;
+        if cloned.is_err() && self.checkout.exists() {
+            let _ = tokio::fs::remove_dir_all(&self.checkout).await;
+        }
+        cloned
+    }
+
+    /// 读取插件的索引文件，插件不存在时返回 `NotFound`
+    pub async fn plugin(&self, name: &str) -> Result<IndexedPlugin, PluginError> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(PluginError::NotFound(name.to_string()));
+        }
+        let path = self.checkout.join(index_path(name));
+        let content = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(PluginError::NotFound(name.to_string()))
+            }
+            Err(e) => return Err(io_error("读取", &path, e)),
+        };
+        parse_index_file(name, &content)
+    }
+
+    /// 仓库中的所有插件名称，按名称排序
+    ///
+    /// 只统计路径符合索引布局的文件，仓库中的 README 等其他文件会被忽略
+    pub async fn names(&self) -> Result<Vec<String>, PluginError> {
+        let mut names = Vec::new();
+        let mut pending = vec![self.checkout.clone()];
+        while let Some(dir) = pending.pop() {
+            let mut entries = tokio::fs::read_dir(&dir)
+                .await
+                .map_err(|e| io_error("读取", &dir, e))?;
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| io_error("读取", &dir, e))?
+            {
+                let path = entry.path();
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                if file_name.starts_with('.') {
+                    continue;
+                }
+                let file_type = entry
+                    .file_type()
+                    .await
+                    .map_err(|e| io_error("读取", &path, e))?;
+                if file_type.is_dir() {
+                    pending.push(path);
+                } else if path.strip_prefix(&self.checkout).ok() == Some(&index_path(&file_name)) {
+                    names.push(file_name);
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// 名称、描述或标签包含关键字（不区分大小写）的插件
+    pub async fn search(&self, query: &str) -> Result<Vec<RegistryEntry>, PluginError> {
+        let mut index = StaticIndex::default();
+        for name in self.names().await? {
+            let plugin = self.plugin(&name).await?;
+            index.plugins.insert(name, plugin);
+        }
+        Ok(index.search(query))
+    }
+
+    async fn git_in_checkout(&self, args: &[&str]) -> Result<(), PluginError> {
+        let checkout = self.checkout.to_string_lossy().into_owned();
+        let mut full = vec!["-C", checkout.as_str()];
+        full.extend_from_slice(args);
+        git(&full, &self.env).await
+    }
+}
+
+fn io_error(action: &str, path: &Path, e: std::io::Error) -> PluginError {
+    PluginError::IoError(format!("{} {} 失败: {}", action, path.display(), e))
+}
+
+/// 执行 git 命令，失败时返回 git 的错误输出
+async fn git(args: &[&str], env: &[(String, String)]) -> Result<(), PluginError> {
+    permissions::check(|guard| guard.check_subprocess("git"))?;
+    let program = which::which("git")
+        .map_err(|_| PluginError::NotFound("git (Git 仓库注册表需要安装 git)".to_string()))?;
+    log::debug!("执行: git {}", args.join(" "));
+    let output = permissions::command(program)
+        .args(args)
+        // 不等待凭据输入，私有仓库需在插件源的 auth 中指定凭据，或提前配置凭据助手或 SSH 密钥
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .envs(env.iter().map(|(name, value)| (name, value)))
+        .output()
+        .await
+        .map_err(|e| PluginError::NetworkError(format!("无法执行 git: {}", e)))?;
+    if !output.status.success() {
+        return Err(PluginError::NetworkError(format!(
+            "git {} 失败: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_path() {
+        assert_eq!(index_path("a"), Path::new("1/a"));
+        assert_eq!(index_path("go"), Path::new("2/go"));
+        assert_eq!(index_path("npm"), Path::new("3/n/npm"));
+        assert_eq!(index_path("Terraform"), Path::new("te/rr/terraform"));
+    }
+
+    #[test]
+    fn test_parse_index_file() {
+        let content = r#"
+{"metadata": {"description": "Infrastructure as code", "tags": ["iac"]}}
+{"version": "1.5.0", "platform": "linux-x64", "download_url": "https://example.com/1.5.0", "prerelease": false}
+{"version": "1.7.0-beta1", "platform": "linux-x64", "download_url": "https://example.com/1.7.0", "prerelease": true}
+{"version": "1.6.0", "platform": "linux-x64", "download_url": "https://example.com/1.6.0", "prerelease": false}
+{"version": "1.6.0", "platform": "darwin-arm64", "download_url": "https://example.com/1.6.0-mac", "prerelease": false}
+"#;
+        let plugin = parse_index_file("terraform", content).unwrap();
+        assert_eq!(plugin.metadata.name, "terraform");
+        assert_eq!(plugin.metadata.version, "1.6.0");
+        assert_eq!(plugin.metadata.description, "Infrastructure as code");
+        assert_eq!(
+            plugin.metadata.supported_platforms,
+            vec!["linux-x64", "darwin-arm64"]
+        );
+        let versions: Vec<&str> = plugin.versions.iter().map(|v| v.version.as_str()).collect();
+        assert_eq!(versions, vec!["1.7.0-beta1", "1.6.0", "1.6.0", "1.5.0"]);
+        assert_eq!(plugin.versions[2].platform, "darwin-arm64");
+        assert_eq!(plugin.entry().latest.unwrap().version, "1.6.0");
+
+        assert!(parse_index_file("bad", "{not json}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_git_index_clone_and_update() {
+        if which::which("git").is_err() {
+            return;
+        }
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("plm-index");
+        let run = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=plm", "-c", "user.email=plm@example.com"])
+                .arg("-C")
+                .arg(&repo)
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        let write = |name: &str, content: &str| {
+            let path = repo.join(index_path(name));
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+
+        std::fs::create_dir_all(&repo).unwrap();
+        run(&["init", "-q"]);
+        write(
+            "tool",
+            "{\"metadata\": {\"description\": \"Internal tool\"}}\n\
+             {\"version\": \"1.0.0\", \"platform\": \"linux-x64\", \"download_url\": \"https://example.com/tool\", \"prerelease\": false}\n",
+        );
+        std::fs::write(repo.join("README.md"), "plugins").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "-q", "-m", "add tool"]);
+
+        let url = format!("git+file://{}", repo.display());
+        let index = GitIndex::new(&url, &temp.path().join("cache"));
+        assert!(index
+            .checkout()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("plm-index-"));
+        index.update().await.unwrap();
+
+        assert_eq!(index.names().await.unwrap(), vec!["tool"]);
+        let entry = index.plugin("tool").await.unwrap().entry();
+        assert_eq!(entry.latest.unwrap().version, "1.0.0");
+        assert_eq!(index.search("internal").await.unwrap().len(), 1);
+        assert!(matches!(
+            index.plugin("missing").await,
+            Err(PluginError::NotFound(_))
+        ));
+
+        // 新版本通过提交发布，更新检出后可见
+        write(
+            "tool",
+            "{\"version\": \"1.0.0\", \"platform\": \"linux-x64\", \"download_url\": \"https://example.com/tool\", \"prerelease\": false}\n\
+             {\"version\": \"1.1.0\", \"platform\": \"linux-x64\", \"download_url\": \"https://example.com/tool\", \"prerelease\": false}\n",
+        );
+        run(&["commit", "-q", "-am", "release tool 1.1.0"]);
+        index.update().await.unwrap();
+        let plugin = index.plugin("tool").await.unwrap();
+        assert_eq!(plugin.metadata.version, "1.1.0");
+    }
+}
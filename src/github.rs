@@ -0,0 +1,419 @@
+//! PLM GitHub Releases 插件源
+//!
+//! 插件源地址为仓库地址 `https://github.com/<owner>/<repo>`（也可简写为 `<owner>/<repo>`），
+//! GitHub Enterprise Server 的仓库地址使用实例的 `/api/v3` 接口。附件的选择方式见 `crate::releases`。
+//!
+//! 插件源的 `token`/`auth` 用于访问 API（提高请求频率限制）和私有仓库：
+//!
+//! ```json
+//! "source": { "type": "github", "url": "https://github.com/acme/tool", "token": "env:GITHUB_TOKEN" }
+//! ```
+//!
+//! 配置了凭据时通过 API 的附件地址下载，以便访问私有仓库的附件。
+
+use crate::auth::Credentials;
+use crate::config::{GlobalSettings, PluginConfig, PluginSource};
+use crate::download::{build_client, Downloader};
+use crate::permissions;
+use crate::releases::{tag_version, Release, ReleaseAsset, ReleaseHost, ReleasesPlugin};
+use crate::traits::PluginError;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// github.com 的 API 地址
+const GITHUB_API: &str = "https://api.github.com";
+
+/// 每页的发布数量（API 允许的最大值）
+const PER_PAGE: usize = 100;
+
+/// 最多读取的页数，避免为发布很多的仓库发出大量请求
+const MAX_PAGES: usize = 10;
+
+/// GitHub 仓库
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GithubRepo {
+    /// API 地址，如 `https://api.github.com`
+    pub api_url: String,
+    pub owner: String,
+    pub repo: String,
+}
+
+impl GithubRepo {
+    /// 解析插件源地址
+    pub fn parse(url: &str) -> Result<Self, PluginError> {
+        let invalid = || {
+            PluginError::ConfigError(format!(
+                "无效的 GitHub 仓库地址 '{}'，应为 https://github.com/<owner>/<repo>",
+                url
+            ))
+        };
+        let trimmed = url.trim().trim_end_matches('/').trim_end_matches(".git");
+        let (api_url, path) = match url::Url::parse(trimmed) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {
+                let host = parsed.host_str().ok_or_else(invalid)?;
+                let api_url = if host == "github.com" || host == "www.github.com" {
+                    GITHUB_API.to_string()
+                } else {
+                    let port = parsed.port().map(|p| format!(":{}", p)).unwrap_or_default();
+                    format!("{}://{}{}/api/v3", parsed.scheme(), host, port)
+                };
+                (api_url, parsed.path().trim_matches('/').to_string())
+            }
+            Ok(_) => return Err(invalid()),
+            // `<owner>/<repo>` 简写
+            Err(_) => (GITHUB_API.to_string(), trimmed.to_string()),
+        };
+        match path.split('/').collect::<Vec<_>>().as_slice() {
+            [owner, repo] if !owner.is_empty() && !repo.is_empty() => Ok(Self {
+                api_url,
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+            }),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// 仓库的 API 地址
+    pub fn repo_url(&self) -> String {
+        format!("{}/repos/{}/{}", self.api_url, self.owner, self.repo)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiRelease {
+    tag_name: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<ApiAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiAsset {
+    name: String,
+    /// API 附件地址，以 `application/octet-stream` 请求时返回内容
+    url: String,
+    browser_download_url: String,
+    #[serde(default)]
+    size: Option<u64>,
+    /// `sha256:<hex>`，较早上传的附件没有该字段
+    #[serde(default)]
+    digest: Option<String>,
+}
+
+/// 把 API 响应转换为发布列表，跳过草稿和标签不是版本号的发布
+fn parse_releases(releases: Vec<ApiRelease>, use_api_urls: bool) -> Vec<Release> {
+    releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        .filter_map(|release| {
+            let version = tag_version(&release.tag_name)?;
+            Some(Release {
+                prerelease: release.prerelease || version.contains('-'),
+                version,
+                tag: release.tag_name,
+                published_at: release.published_at,
+                assets: release
+                    .assets
+                    .into_iter()
+                    .map(|asset| ReleaseAsset {
+                        download_url: if use_api_urls {
+                            asset.url
+                        } else {
+                            asset.browser_download_url
+                        },
+                        checksum: asset
+                            .digest
+                            .and_then(|digest| digest.strip_prefix("sha256:").map(str::to_string)),
+                        size: asset.size,
+                        name: asset.name,
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// GitHub Releases 接口
+pub struct GithubReleases {
+    repo: GithubRepo,
+    source: PluginSource,
+    settings: GlobalSettings,
+    credentials: tokio::sync::OnceCell<Option<Credentials>>,
+}
+
+impl GithubReleases {
+    pub fn new(source: &PluginSource, settings: &GlobalSettings) -> Result<Self, PluginError> {
+        Ok(Self {
+            repo: GithubRepo::parse(&source.url)?,
+            source: source.clone(),
+            settings: settings.clone(),
+            credentials: tokio::sync::OnceCell::new(),
+        })
+    }
+
+    async fn credentials(&self) -> Result<Option<&Credentials>, PluginError> {
+        Ok(self
+            .credentials
+            .get_or_try_init(|| self.source.resolve_auth())
+            .await?
+            .as_ref())
+    }
+
+    async fn get(&self, url: &str, accept: &str) -> Result<reqwest::Response, PluginError> {
+        permissions::check(|guard| guard.check_network(url))?;
+        let request = build_client(
+            Duration::from_secs(self.settings.download_timeout),
+            self.settings.proxy.as_deref(),
+            self.settings.no_proxy.as_deref(),
+        )?
+        .get(url)
+        .header(reqwest::header::USER_AGENT, "plm")
+        .header(reqwest::header::ACCEPT, accept)
+        .header("X-GitHub-Api-Version", "2022-11-28");
+        let request = match self.credentials().await? {
+            Some(credentials) => credentials.apply(request),
+            None => request,
+        };
+        let response = request
+            .send()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?;
+        match response.status() {
+            status if status.is_success() => Ok(response),
+            reqwest::StatusCode::NOT_FOUND => Err(PluginError::NotFound(format!(
+                "{}（私有仓库需要在插件源上配置 token）",
+                url
+            ))),
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                Err(PluginError::PermissionDenied(format!(
+                    "GitHub 拒绝了 {} 的请求（{}），请检查插件源的 token 或请求频率限制",
+                    url,
+                    response.status()
+                )))
+            }
+            status => Err(PluginError::NetworkError(format!(
+                "请求 {} 失败: {}",
+                url, status
+            ))),
+        }
+    }
+}
+
+impl GithubReleases {
+    /// 标签对应的发布，不存在或标签不是版本号时返回 None
+    pub async fn release(&self, tag: &str) -> Result<Option<Release>, PluginError> {
+        let url = format!("{}/releases/tags/{}", self.repo.repo_url(), tag);
+        let release: ApiRelease = match self.get(&url, "application/vnd.github+json").await {
+            Ok(response) => response.json().await.map_err(|e| {
+                PluginError::NetworkError(format!("解析 {} 的响应失败: {}", url, e))
+            })?,
+            Err(PluginError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let use_api_urls = self.credentials().await?.is_some();
+        Ok(parse_releases(vec![release], use_api_urls).pop())
+    }
+}
+
+/// `https://github.com/<owner>/<repo>/releases/download/<tag>/<file>` 形式的附件地址，
+/// 返回 GitHub 为该附件提供的 SHA-256；地址形式不符或附件没有摘要时返回 None
+pub async fn release_asset_checksum(
+    download_url: &str,
+    settings: &GlobalSettings,
+) -> Option<String> {
+    let parsed = url::Url::parse(download_url).ok()?;
+    if parsed.host_str() != Some("github.com") {
+        return None;
+    }
+    let segments: Vec<&str> = parsed.path_segments()?.collect();
+    let [owner, repo, "releases", "download", tag, file] = segments.as_slice() else {
+        return None;
+    };
+    let source = PluginSource::github(&format!("https://github.com/{}/{}", owner, repo));
+    let host = GithubReleases::new(&source, settings).ok()?;
+    match host.release(tag).await {
+        Ok(release) => {
+            release?
+                .assets
+                .into_iter()
+                .find(|asset| asset.name == *file)?
+                .checksum
+        }
+        Err(e) => {
+            log::debug!("查询 {} 的摘要失败: {}", download_url, e);
+            None
+        }
+    }
+}
+
+#[async_trait]
+impl ReleaseHost for GithubReleases {
+    fn kind(&self) -> &'static str {
+        "GitHub"
+    }
+
+    async fn releases(&self) -> Result<Vec<Release>, PluginError> {
+        let use_api_urls = self.credentials().await?.is_some();
+        let mut releases = Vec::new();
+        for page in 1..=MAX_PAGES {
+            let url = format!(
+                "{}/releases?per_page={}&page={}",
+                self.repo.repo_url(),
+                PER_PAGE,
+                page
+            );
+            let batch: Vec<ApiRelease> = self
+                .get(&url, "application/vnd.github+json")
+                .await?
+                .json()
+                .await
+                .map_err(|e| {
+                    PluginError::NetworkError(format!("解析 {} 的响应失败: {}", url, e))
+                })?;
+            let last = batch.len() < PER_PAGE;
+            releases.extend(parse_releases(batch, use_api_urls));
+            if last {
+                break;
+            }
+        }
+        Ok(releases)
+    }
+
+    async fn asset_text(&self, asset: &ReleaseAsset) -> Result<String, PluginError> {
+        self.get(&asset.download_url, "application/octet-stream")
+            .await?
+            .text()
+            .await
+            .map_err(|e| {
+                PluginError::NetworkError(format!("读取 {} 失败: {}", asset.download_url, e))
+            })
+    }
+
+    async fn prepare_download(&self, downloader: Downloader) -> Result<Downloader, PluginError> {
+        // 凭据只用于 API 附件地址，重定向到存储服务时不携带
+        Ok(downloader
+            .with_scoped_credentials(
+                vec![format!("{}/releases/assets", self.repo.repo_url())],
+                self.credentials().await?.cloned(),
+            )
+            .with_accept("application/octet-stream"))
+    }
+}
+
+/// 创建从 GitHub Releases 安装的插件
+pub fn plugin(
+    config: &PluginConfig,
+    source: &PluginSource,
+    settings: &GlobalSettings,
+) -> Result<ReleasesPlugin, PluginError> {
+    ReleasesPlugin::new(
+        config,
+        source,
+        Box::new(GithubReleases::new(source, settings)?),
+        settings,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::releases::ReleaseSettings;
+    use crate::target::TargetPlatform;
+
+    #[test]
+    fn test_parse_repo() {
+        let repo = GithubRepo::parse("https://github.com/BurntSushi/ripgrep").unwrap();
+        assert_eq!(
+            repo.repo_url(),
+            "https://api.github.com/repos/BurntSushi/ripgrep"
+        );
+        assert_eq!(GithubRepo::parse("BurntSushi/ripgrep.git").unwrap(), repo);
+        assert_eq!(
+            GithubRepo::parse("https://ghe.example.com/tools/cli/")
+                .unwrap()
+                .repo_url(),
+            "https://ghe.example.com/api/v3/repos/tools/cli"
+        );
+        assert!(GithubRepo::parse("https://github.com/BurntSushi").is_err());
+        assert!(GithubRepo::parse("ssh://git@github.com/a/b").is_err());
+    }
+
+    #[test]
+    fn test_select_release_asset() {
+        let response = serde_json::json!([
+            { "tag_name": "v2.0.0-rc.1", "prerelease": true, "assets": [] },
+            { "tag_name": "nightly", "assets": [] },
+            { "tag_name": "v1.0.0", "draft": true, "assets": [] },
+            {
+                "tag_name": "14.1.0",
+                "published_at": "2024-01-08T00:00:00Z",
+                "assets": [
+                    { "name": "ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz",
+                      "url": "https://api.github.com/repos/o/r/releases/assets/1",
+                      "browser_download_url": "https://github.com/o/r/releases/download/14.1.0/ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz",
+                      "digest": "sha256:abc" },
+                    { "name": "ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz.sha256",
+                      "url": "https://api.github.com/repos/o/r/releases/assets/2",
+                      "browser_download_url": "https://github.com/o/r/releases/download/14.1.0/ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz.sha256" },
+                    { "name": "ripgrep-14.1.0-aarch64-apple-darwin.tar.gz",
+                      "url": "https://api.github.com/repos/o/r/releases/assets/3",
+                      "browser_download_url": "https://github.com/o/r/releases/download/14.1.0/ripgrep-14.1.0-aarch64-apple-darwin.tar.gz" },
+                    { "name": "ripgrep-14.1.0-x86_64-pc-windows-msvc.zip",
+                      "url": "https://api.github.com/repos/o/r/releases/assets/4",
+                      "browser_download_url": "https://github.com/o/r/releases/download/14.1.0/ripgrep-14.1.0-x86_64-pc-windows-msvc.zip" }
+                ]
+            }
+        ]);
+        let releases = parse_releases(serde_json::from_value(response).unwrap(), false);
+        assert_eq!(releases.len(), 2);
+        assert!(releases[0].prerelease);
+        let release = &releases[1];
+        assert_eq!(release.version, "14.1.0");
+        assert_eq!(release.assets[0].checksum.as_deref(), Some("abc"));
+
+        let linux: TargetPlatform = "linux-x64".parse().unwrap();
+        let mac: TargetPlatform = "macos-arm64".parse().unwrap();
+        let windows: TargetPlatform = "windows-x64".parse().unwrap();
+
+        let settings: ReleaseSettings = serde_json::from_value(serde_json::json!({
+            "asset": "ripgrep-{version}-{arch}-{os}.{ext}",
+            "entry_points": ["ripgrep-{version}-{arch}-{os}/rg{exe}"]
+        }))
+        .unwrap();
+        let (asset, matched) = settings.select(release, &linux).unwrap();
+        assert_eq!(
+            asset.name,
+            "ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz"
+        );
+        assert_eq!(
+            settings.entry_points_for(release, &matched, &linux),
+            vec!["ripgrep-14.1.0-x86_64-unknown-linux-musl/rg"]
+        );
+        let (asset, matched) = settings.select(release, &windows).unwrap();
+        assert!(asset.name.ends_with("pc-windows-msvc.zip"));
+        assert_eq!(
+            settings.entry_points_for(release, &matched, &windows),
+            vec!["ripgrep-14.1.0-x86_64-pc-windows-msvc/rg.exe"]
+        );
+        let arm_linux: TargetPlatform = "linux-arm64".parse().unwrap();
+        assert!(settings.select(release, &arm_linux).is_none());
+
+        // 未设置模式时按文件名识别平台，跳过校验和附件
+        let detected = ReleaseSettings::default();
+        assert_eq!(
+            detected.select(release, &linux).unwrap().0.name,
+            "ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz"
+        );
+        assert_eq!(
+            detected.select(release, &mac).unwrap().0.name,
+            "ripgrep-14.1.0-aarch64-apple-darwin.tar.gz"
+        );
+        assert!(detected.select(release, &arm_linux).is_none());
+    }
+}
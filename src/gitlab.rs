@@ -0,0 +1,492 @@
+//! PLM GitLab 插件源
+//!
+//! 插件源地址为项目地址，支持 gitlab.com 和自托管实例，子组路径不限层级；
+//! 实例部署在子路径下时，可以直接写成项目的 API 地址 `<实例>/api/v4/projects/<id 或编码后的路径>`：
+//!
+//! ```json
+//! "source": { "type": "gitlab", "url": "https://gitlab.example.com/tools/cli", "token": "env:GITLAB_TOKEN" }
+//! ```
+//!
+//! 默认从项目的发布（Releases API）的附件链接中选择制品；插件设置 `package` 指定通用软件包
+//! （generic package registry）的名称时，软件包的各版本即插件版本，版本中的文件即附件。
+//! 附件的选择方式见 `crate::releases`，通用软件包文件的 SHA-256 由 GitLab 提供。
+//!
+//! 插件源的 `token` 作为 Bearer 令牌发送，也可以用 `auth` 指定请求头
+//! （如 `{ "type": "header", "name": "PRIVATE-TOKEN", "value": "..." }`，CI 中为 `JOB-TOKEN`）。
+//! 凭据只发送到该 GitLab 实例。
+
+use crate::auth::Credentials;
+use crate::config::{GlobalSettings, PluginConfig, PluginSource};
+use crate::download::{build_client, Downloader};
+use crate::permissions;
+use crate::releases::{tag_version, Release, ReleaseAsset, ReleaseHost, ReleasesPlugin};
+use crate::traits::PluginError;
+use crate::version::Version;
+use async_trait::async_trait;
+use futures_util::stream::{self, StreamExt};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// gitlab.com 的地址
+const GITLAB_COM: &str = "https://gitlab.com";
+
+/// 每页的条目数量（API 允许的最大值）
+const PER_PAGE: usize = 100;
+
+/// 最多读取的页数
+const MAX_PAGES: usize = 10;
+
+/// 同时查询文件列表的软件包版本数
+const MAX_CONCURRENT_REQUESTS: usize = 8;
+
+/// GitLab 项目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitlabProject {
+    /// 实例的 API 地址，如 `https://gitlab.example.com/api/v4`
+    pub api_url: String,
+    /// 项目 ID 或编码后的项目路径，如 `tools%2Fcli`
+    pub id: String,
+}
+
+impl GitlabProject {
+    /// 解析插件源地址
+    pub fn parse(url: &str) -> Result<Self, PluginError> {
+        let invalid = || {
+            PluginError::ConfigError(format!(
+                "无效的 GitLab 项目地址 '{}'，应为 https://<实例>/<组>/<项目>",
+                url
+            ))
+        };
+        let trimmed = url.trim().trim_end_matches('/').trim_end_matches(".git");
+        if let Some((api_url, id)) = trimmed.split_once("/projects/") {
+            if api_url.ends_with("/api/v4") && !id.is_empty() && !id.contains('/') {
+                return Ok(Self {
+                    api_url: api_url.to_string(),
+                    id: id.to_string(),
+                });
+            }
+        }
+        let (base, path) = match url::Url::parse(trimmed) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {
+                let host = parsed.host_str().ok_or_else(invalid)?;
+                let port = parsed.port().map(|p| format!(":{}", p)).unwrap_or_default();
+                // `/-/` 之后是项目内的页面，如 `/-/releases`
+                let path = parsed.path().split("/-/").next().unwrap_or_default();
+                (
+                    format!("{}://{}{}", parsed.scheme(), host, port),
+                    path.trim_matches('/').to_string(),
+                )
+            }
+            Ok(_) => return Err(invalid()),
+            // `<组>/<项目>` 简写
+            Err(_) => (GITLAB_COM.to_string(), trimmed.to_string()),
+        };
+        if path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .count()
+            < 2
+        {
+            return Err(invalid());
+        }
+        Ok(Self {
+            api_url: format!("{}/api/v4", base),
+            id: url::form_urlencoded::byte_serialize(path.as_bytes()).collect(),
+        })
+    }
+
+    /// 项目的 API 地址
+    pub fn project_url(&self) -> String {
+        format!("{}/projects/{}", self.api_url, self.id)
+    }
+
+    /// 实例地址，凭据只发送到该地址下
+    pub fn instance_url(&self) -> &str {
+        self.api_url.trim_end_matches("/api/v4")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiRelease {
+    tag_name: String,
+    #[serde(default)]
+    upcoming_release: bool,
+    #[serde(default)]
+    released_at: Option<String>,
+    #[serde(default)]
+    assets: ApiAssets,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ApiAssets {
+    #[serde(default)]
+    links: Vec<ApiLink>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiLink {
+    name: String,
+    url: String,
+    #[serde(default)]
+    direct_asset_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiPackage {
+    id: u64,
+    name: String,
+    version: String,
+    #[serde(default)]
+    created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiPackageFile {
+    file_name: String,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
+    file_sha256: Option<String>,
+}
+
+/// 把发布转换为发布列表，跳过即将发布的和标签不是版本号的发布
+///
+/// 附件名取链接地址中的文件名，地址不以文件名结尾时使用链接名称
+fn parse_releases(releases: Vec<ApiRelease>) -> Vec<Release> {
+    releases
+        .into_iter()
+        .filter(|release| !release.upcoming_release)
+        .filter_map(|release| {
+            let version = tag_version(&release.tag_name)?;
+            Some(Release {
+                prerelease: version.contains('-'),
+                version,
+                tag: release.tag_name,
+                published_at: release.released_at,
+                assets: release
+                    .assets
+                    .links
+                    .into_iter()
+                    .map(|link| {
+                        let download_url = link.direct_asset_url.unwrap_or(link.url);
+                        let name = url::Url::parse(&download_url)
+                            .ok()
+                            .and_then(|url| {
+                                url.path_segments()?
+                                    .next_back()
+                                    .filter(|name| name.contains('.') || name.contains('-'))
+                                    .map(str::to_string)
+                            })
+                            .unwrap_or(link.name);
+                        ReleaseAsset {
+                            name,
+                            download_url,
+                            size: None,
+                            checksum: None,
+                        }
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// 通用软件包的一个版本转换为发布
+fn package_release(
+    project: &GitlabProject,
+    name: &str,
+    package: ApiPackage,
+    files: Vec<ApiPackageFile>,
+) -> Release {
+    let version = package.version.trim_start_matches('v').to_string();
+    Release {
+        prerelease: version.contains('-'),
+        assets: files
+            .into_iter()
+            .map(|file| ReleaseAsset {
+                download_url: format!(
+                    "{}/packages/generic/{}/{}/{}",
+                    project.project_url(),
+                    name,
+                    package.version,
+                    file.file_name
+                ),
+                name: file.file_name,
+                size: file.size,
+                checksum: file.file_sha256,
+            })
+            .collect(),
+        tag: package.version,
+        version,
+        published_at: package.created_at,
+    }
+}
+
+/// GitLab 发布和通用软件包接口
+pub struct GitlabReleases {
+    project: GitlabProject,
+    /// 通用软件包名称，未设置时使用发布
+    package: Option<String>,
+    source: PluginSource,
+    settings: GlobalSettings,
+    credentials: tokio::sync::OnceCell<Option<Credentials>>,
+}
+
+impl GitlabReleases {
+    pub fn new(
+        source: &PluginSource,
+        package: Option<String>,
+        settings: &GlobalSettings,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            project: GitlabProject::parse(&source.url)?,
+            package,
+            source: source.clone(),
+            settings: settings.clone(),
+            credentials: tokio::sync::OnceCell::new(),
+        })
+    }
+
+    async fn credentials(&self) -> Result<Option<&Credentials>, PluginError> {
+        Ok(self
+            .credentials
+            .get_or_try_init(|| self.source.resolve_auth())
+            .await?
+            .as_ref())
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response, PluginError> {
+        permissions::check(|guard| guard.check_network(url))?;
+        let request = build_client(
+            Duration::from_secs(self.settings.download_timeout),
+            self.settings.proxy.as_deref(),
+            self.settings.no_proxy.as_deref(),
+        )?
+        .get(url);
+        let request = match self.credentials().await? {
+            Some(credentials) => credentials.apply(request),
+            None => request,
+        };
+        let response = request
+            .send()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?;
+        match response.status() {
+            status if status.is_success() => Ok(response),
+            reqwest::StatusCode::NOT_FOUND => Err(PluginError::NotFound(format!(
+                "{}（私有项目需要在插件源上配置 token）",
+                url
+            ))),
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                Err(PluginError::PermissionDenied(format!(
+                    "GitLab 拒绝了 {} 的请求（{}），请检查插件源的 token",
+                    url,
+                    response.status()
+                )))
+            }
+            status => Err(PluginError::NetworkError(format!(
+                "请求 {} 失败: {}",
+                url, status
+            ))),
+        }
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, PluginError> {
+        self.get(url)
+            .await?
+            .json()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("解析 {} 的响应失败: {}", url, e)))
+    }
+
+    /// 分页读取列表接口
+    async fn get_pages<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<Vec<T>, PluginError> {
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+        for page in 1..=MAX_PAGES {
+            let batch: Vec<T> = self
+                .get_json(&format!(
+                    "{}{}per_page={}&page={}",
+                    url, separator, PER_PAGE, page
+                ))
+                .await?;
+            let last = batch.len() < PER_PAGE;
+            items.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    async fn package_releases(&self, name: &str) -> Result<Vec<Release>, PluginError> {
+        let packages: Vec<ApiPackage> = self
+            .get_pages(&format!(
+                "{}/packages?package_type=generic&package_name={}",
+                self.project.project_url(),
+                url::form_urlencoded::byte_serialize(name.as_bytes()).collect::<String>()
+            ))
+            .await?;
+        // `package_name` 是模糊匹配，只保留同名且版本号有效的软件包
+        let packages = packages.into_iter().filter(|package| {
+            package.name == name && Version::parse(package.version.trim_start_matches('v')).is_ok()
+        });
+        let results: Vec<Result<Release, PluginError>> = stream::iter(packages)
+            .map(|package| async move {
+                let files: Vec<ApiPackageFile> = self
+                    .get_pages(&format!(
+                        "{}/packages/{}/package_files",
+                        self.project.project_url(),
+                        package.id
+                    ))
+                    .await?;
+                Ok(package_release(&self.project, name, package, files))
+            })
+            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+        results.into_iter().collect()
+    }
+}
+
+#[async_trait]
+impl ReleaseHost for GitlabReleases {
+    fn kind(&self) -> &'static str {
+        "GitLab"
+    }
+
+    async fn releases(&self) -> Result<Vec<Release>, PluginError> {
+        match &self.package {
+            Some(name) => self.package_releases(name).await,
+            None => {
+                let releases: Vec<ApiRelease> = self
+                    .get_pages(&format!("{}/releases", self.project.project_url()))
+                    .await?;
+                Ok(parse_releases(releases))
+            }
+        }
+    }
+
+    async fn asset_text(&self, asset: &ReleaseAsset) -> Result<String, PluginError> {
+        self.get(&asset.download_url)
+            .await?
+            .text()
+            .await
+            .map_err(|e| {
+                PluginError::NetworkError(format!("读取 {} 失败: {}", asset.download_url, e))
+            })
+    }
+
+    async fn prepare_download(&self, downloader: Downloader) -> Result<Downloader, PluginError> {
+        Ok(downloader.with_scoped_credentials(
+            vec![self.project.instance_url().to_string()],
+            self.credentials().await?.cloned(),
+        ))
+    }
+}
+
+/// 创建从 GitLab 发布或通用软件包安装的插件
+pub fn plugin(
+    config: &PluginConfig,
+    source: &PluginSource,
+    settings: &GlobalSettings,
+) -> Result<ReleasesPlugin, PluginError> {
+    let package = config
+        .effective_settings()
+        .get("package")
+        .and_then(|value| value.as_str())
+        .map(str::to_string);
+    ReleasesPlugin::new(
+        config,
+        source,
+        Box::new(GitlabReleases::new(source, package, settings)?),
+        settings,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::releases::ReleaseSettings;
+    use crate::target::TargetPlatform;
+
+    #[test]
+    fn test_parse_project() {
+        let project = GitlabProject::parse("https://gitlab.example.com/tools/sub/cli").unwrap();
+        assert_eq!(
+            project.project_url(),
+            "https://gitlab.example.com/api/v4/projects/tools%2Fsub%2Fcli"
+        );
+        assert_eq!(project.instance_url(), "https://gitlab.example.com");
+        assert_eq!(
+            GitlabProject::parse("https://gitlab.example.com/tools/cli/-/releases")
+                .unwrap()
+                .id,
+            "tools%2Fcli"
+        );
+        assert_eq!(
+            GitlabProject::parse("tools/cli").unwrap().api_url,
+            "https://gitlab.com/api/v4"
+        );
+        let custom = GitlabProject::parse("https://example.com/gitlab/api/v4/projects/42").unwrap();
+        assert_eq!(custom.instance_url(), "https://example.com/gitlab");
+        assert_eq!(custom.id, "42");
+        assert!(GitlabProject::parse("https://gitlab.example.com/tools").is_err());
+    }
+
+    #[test]
+    fn test_releases_and_packages() {
+        let response = serde_json::json!([
+            { "tag_name": "v1.3.0", "upcoming_release": true },
+            {
+                "tag_name": "v1.2.0",
+                "released_at": "2024-05-01T00:00:00Z",
+                "assets": {
+                    "links": [
+                        { "name": "Linux amd64",
+                          "url": "https://gitlab.example.com/tools/cli/-/package_files/7/download",
+                          "direct_asset_url": "https://gitlab.example.com/tools/cli/-/releases/v1.2.0/downloads/cli-linux-amd64.tar.gz" },
+                        { "name": "cli-darwin-arm64.tar.gz",
+                          "url": "https://gitlab.example.com/tools/cli/-/package_files/8/download" }
+                    ]
+                }
+            }
+        ]);
+        let releases = parse_releases(serde_json::from_value(response).unwrap());
+        assert_eq!(releases.len(), 1);
+        let names: Vec<&str> = releases[0]
+            .assets
+            .iter()
+            .map(|asset| asset.name.as_str())
+            .collect();
+        assert_eq!(names, ["cli-linux-amd64.tar.gz", "cli-darwin-arm64.tar.gz"]);
+
+        let linux: TargetPlatform = "linux-x64".parse().unwrap();
+        let (asset, _) = ReleaseSettings::default()
+            .select(&releases[0], &linux)
+            .unwrap();
+        assert!(asset
+            .download_url
+            .ends_with("/downloads/cli-linux-amd64.tar.gz"));
+
+        let project = GitlabProject::parse("https://gitlab.example.com/tools/cli").unwrap();
+        let package: ApiPackage = serde_json::from_value(
+            serde_json::json!({ "id": 3, "name": "cli", "version": "1.2.0" }),
+        )
+        .unwrap();
+        let files: Vec<ApiPackageFile> = serde_json::from_value(serde_json::json!([
+            { "file_name": "cli-linux-amd64.tar.gz", "size": 10, "file_sha256": "abc" }
+        ]))
+        .unwrap();
+        let release = package_release(&project, "cli", package, files);
+        assert_eq!(release.version, "1.2.0");
+        assert_eq!(
+            release.assets[0].download_url,
+            "https://gitlab.example.com/api/v4/projects/tools%2Fcli/packages/generic/cli/1.2.0/cli-linux-amd64.tar.gz"
+        );
+        assert_eq!(release.assets[0].checksum.as_deref(), Some("abc"));
+    }
+}
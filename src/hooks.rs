@@ -0,0 +1,130 @@
+//! PLM 插件生命周期钩子
+//!
+//! 执行 `PluginConfig::hooks` 中声明的 shell 命令。命令在项目根目录下运行，
+//! 通过环境变量获得插件名、版本和安装路径。
+
+use crate::config::LifecycleHooks;
+use crate::providers::shell_command;
+use crate::traits::PluginError;
+use std::fmt;
+use std::path::Path;
+
+/// 生命周期事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HookEvent {
+    PreInstall,
+    PostInstall,
+    PreUninstall,
+    PostUninstall,
+}
+
+impl HookEvent {
+    /// 获取该事件配置的命令
+    pub fn command(self, hooks: &LifecycleHooks) -> Option<&str> {
+        match self {
+            HookEvent::PreInstall => hooks.pre_install.as_deref(),
+            HookEvent::PostInstall => hooks.post_install.as_deref(),
+            HookEvent::PreUninstall => hooks.pre_uninstall.as_deref(),
+            HookEvent::PostUninstall => hooks.post_uninstall.as_deref(),
+        }
+    }
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HookEvent::PreInstall => "pre_install",
+            HookEvent::PostInstall => "post_install",
+            HookEvent::PreUninstall => "pre_uninstall",
+            HookEvent::PostUninstall => "post_uninstall",
+        })
+    }
+}
+
+/// 钩子执行上下文
+#[derive(Debug, Clone)]
+pub struct HookContext<'a> {
+    pub plugin: &'a str,
+    pub version: &'a str,
+    pub project_root: &'a str,
+    /// 仅在安装完成后可用
+    pub install_path: Option<&'a Path>,
+}
+
+/// 执行事件对应的钩子，未配置时直接返回
+///
+/// 命令以非零状态退出时返回错误，错误信息包含命令的标准错误输出
+pub async fn run_hook(
+    hooks: &LifecycleHooks,
+    event: HookEvent,
+    context: &HookContext<'_>,
+) -> Result<(), PluginError> {
+    let Some(command) = event.command(hooks) else {
+        return Ok(());
+    };
+
+    let mut cmd = shell_command(command);
+    cmd.current_dir(context.project_root)
+        .env("PLM_HOOK", event.to_string())
+        .env("PLM_PLUGIN", context.plugin)
+        .env("PLM_VERSION", context.version)
+        .env("PLM_PROJECT_ROOT", context.project_root);
+    if let Some(path) = context.install_path {
+        cmd.env("PLM_INSTALL_PATH", path);
+    }
+
+    let output = cmd.output().await.map_err(|e| {
+        PluginError::PluginError(format!(
+            "{} hook for {} failed to start: {}",
+            event, context.plugin, e
+        ))
+    })?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(PluginError::PluginError(format!(
+            "{} hook for {} exited with {}: {}",
+            event,
+            context.plugin,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_hook_with_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let hooks = LifecycleHooks {
+            post_install: Some(
+                "echo \"$PLM_HOOK $PLM_PLUGIN $PLM_VERSION $PLM_INSTALL_PATH\" > hook.log"
+                    .to_string(),
+            ),
+            pre_uninstall: Some("exit 3".to_string()),
+            ..LifecycleHooks::default()
+        };
+        let root = dir.path().to_str().unwrap();
+        let context = HookContext {
+            plugin: "node",
+            version: "18.17.0",
+            project_root: root,
+            install_path: Some(Path::new("/opt/node")),
+        };
+
+        run_hook(&hooks, HookEvent::PreInstall, &context)
+            .await
+            .unwrap();
+        run_hook(&hooks, HookEvent::PostInstall, &context)
+            .await
+            .unwrap();
+        let log = std::fs::read_to_string(dir.path().join("hook.log")).unwrap();
+        assert_eq!(log.trim(), "post_install node 18.17.0 /opt/node");
+
+        let result = run_hook(&hooks, HookEvent::PreUninstall, &context).await;
+        assert!(matches!(result, Err(PluginError::PluginError(_))));
+    }
+}
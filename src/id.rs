@@ -0,0 +1,209 @@
+//! PLM 插件标识
+//!
+//! 插件标识的格式为 `[scope/]name[:variant]`，例如 `node`、`lab-jy/node`、`python:conda`。
+//! 各段只允许小写字母、数字、`-`、`_` 和 `.`，且必须以字母或数字开头，
+//! 避免配置中的键与插件元数据名称因大小写或空白不同而无法对应。
+
+use crate::traits::PluginError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 插件标识
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PluginId {
+    scope: Option<String>,
+    name: String,
+    variant: Option<String>,
+}
+
+impl PluginId {
+    /// 创建不带作用域和变体的插件标识
+    pub fn new(name: &str) -> Result<Self, PluginError> {
+        validate_segment("name", name)?;
+        Ok(Self {
+            scope: None,
+            name: name.to_string(),
+            variant: None,
+        })
+    }
+
+    /// 解析插件标识
+    pub fn parse(id: &str) -> Result<Self, PluginError> {
+        let (rest, variant) = match id.split_once(':') {
+            Some((rest, variant)) => (rest, Some(variant)),
+            None => (id, None),
+        };
+        let (scope, name) = match rest.split_once('/') {
+            Some((scope, name)) => (Some(scope), name),
+            None => (None, rest),
+        };
+
+        if let Some(scope) = scope {
+            validate_segment("scope", scope)?;
+        }
+        validate_segment("name", name)?;
+        if let Some(variant) = variant {
+            validate_segment("variant", variant)?;
+        }
+
+        Ok(Self {
+            scope: scope.map(str::to_string),
+            name: name.to_string(),
+            variant: variant.map(str::to_string),
+        })
+    }
+
+    /// 设置作用域
+    pub fn with_scope(mut self, scope: &str) -> Result<Self, PluginError> {
+        validate_segment("scope", scope)?;
+        self.scope = Some(scope.to_string());
+        Ok(self)
+    }
+
+    /// 设置变体
+    pub fn with_variant(mut self, variant: &str) -> Result<Self, PluginError> {
+        validate_segment("variant", variant)?;
+        self.variant = Some(variant.to_string());
+        Ok(self)
+    }
+
+    /// 作用域
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+
+    /// 插件名称，应与插件元数据中的 `name` 一致
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 变体
+    pub fn variant(&self) -> Option<&str> {
+        self.variant.as_deref()
+    }
+}
+
+fn validate_segment(part: &str, value: &str) -> Result<(), PluginError> {
+    let valid = value
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(PluginError::ValidationError(format!(
+            "Invalid plugin {} '{}': expected lowercase letters, digits, '-', '_' or '.'",
+            part, value
+        )))
+    }
+}
+
+impl fmt::Display for PluginId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(scope) = &self.scope {
+            write!(f, "{}/", scope)?;
+        }
+        f.write_str(&self.name)?;
+        if let Some(variant) = &self.variant {
+            write!(f, ":{}", variant)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PluginId {
+    type Err = PluginError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for PluginId {
+    type Error = PluginError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl From<PluginId> for String {
+    fn from(id: PluginId) -> Self {
+        id.to_string()
+    }
+}
+
+/// 可转换为插件标识的类型，管理器 API 同时接受字符串和 `PluginId`
+pub trait IntoPluginId {
+    fn into_plugin_id(self) -> Result<PluginId, PluginError>;
+}
+
+impl IntoPluginId for PluginId {
+    fn into_plugin_id(self) -> Result<PluginId, PluginError> {
+        Ok(self)
+    }
+}
+
+impl IntoPluginId for &PluginId {
+    fn into_plugin_id(self) -> Result<PluginId, PluginError> {
+        Ok(self.clone())
+    }
+}
+
+impl IntoPluginId for &str {
+    fn into_plugin_id(self) -> Result<PluginId, PluginError> {
+        PluginId::parse(self)
+    }
+}
+
+impl IntoPluginId for String {
+    fn into_plugin_id(self) -> Result<PluginId, PluginError> {
+        PluginId::parse(&self)
+    }
+}
+
+impl IntoPluginId for &String {
+    fn into_plugin_id(self) -> Result<PluginId, PluginError> {
+        PluginId::parse(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let id = PluginId::parse("lab-jy/python:conda").unwrap();
+        assert_eq!(id.scope(), Some("lab-jy"));
+        assert_eq!(id.name(), "python");
+        assert_eq!(id.variant(), Some("conda"));
+        assert_eq!(id.to_string(), "lab-jy/python:conda");
+
+        let id: PluginId = "node".parse().unwrap();
+        assert_eq!(id, PluginId::new("node").unwrap());
+        assert_eq!(id.scope(), None);
+    }
+
+    #[test]
+    fn test_rejects_invalid_ids() {
+        for invalid in ["", "Node", " node", "a/b/c", "node:", "-node", "py thon"] {
+            assert!(PluginId::parse(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_serde_as_string() {
+        let id = PluginId::parse("tools/go").unwrap();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"tools/go\"");
+        assert_eq!(serde_json::from_str::<PluginId>(&json).unwrap(), id);
+        assert!(serde_json::from_str::<PluginId>("\"Bad Id\"").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! PLM 只读状态查询
+//!
+//! 同步读取配置和安装状态，不构建 `PluginManager`，也不需要异步运行时，
+//! 适合命令行提示符、编辑器插件等对启动速度敏感的工具。
+
+use crate::config::ProjectConfig;
+use crate::traits::PluginError;
+use crate::version::Version;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// 读取插件目录中已安装的版本
+///
+/// 插件目录的布局为 `<plugin_dir>/<插件名>/<版本>/`，返回插件名到版本列表的映射，
+/// 版本按从新到旧排列。目录不存在时返回空映射。
+pub fn read_installed_versions(
+    plugin_dir: impl AsRef<Path>,
+) -> Result<BTreeMap<String, Vec<String>>, PluginError> {
+    let plugin_dir = plugin_dir.as_ref();
+    let mut installed = BTreeMap::new();
+
+    let plugins = match std::fs::read_dir(plugin_dir) {
+        Ok(plugins) => plugins,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(installed),
+        Err(e) => {
+            return Err(PluginError::IoError(format!(
+                "读取插件目录 {} 失败: {}",
+                plugin_dir.display(),
+                e
+            )))
+        }
+    };
+
+    for plugin in plugins {
+        let plugin =
+            plugin.map_err(|e| PluginError::IoError(format!("读取插件目录失败: {}", e)))?;
+        if !plugin.path().is_dir() {
+            continue;
+        }
+
+        let mut versions = Vec::new();
+        let entries = std::fs::read_dir(plugin.path()).map_err(|e| {
+            PluginError::IoError(format!("读取 {} 失败: {}", plugin.path().display(), e))
+        })?;
+        for entry in entries {
+            let entry =
+                entry.map_err(|e| PluginError::IoError(format!("读取插件目录失败: {}", e)))?;
+            if entry.path().is_dir() {
+                versions.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        if versions.is_empty() {
+            continue;
+        }
+
+        versions.sort_by(|a, b| compare_versions(b, a));
+        installed.insert(plugin.file_name().to_string_lossy().into_owned(), versions);
+    }
+
+    Ok(installed)
+}
+
+/// 读取项目配置文件（`plm.json`），其中记录了各插件固定的版本
+///
+/// 与 `ProjectConfig::load_from_file` 一样会展开 `${VAR}` 环境变量占位符
+pub fn read_lockfile(path: impl AsRef<Path>) -> Result<ProjectConfig, PluginError> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        PluginError::ConfigError(format!(
+            "Failed to read config file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    ProjectConfig::from_json_str(&content)
+}
+
+/// 按语义化版本比较，无法解析的版本按字符串比较并排在可解析版本之前
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    match (Version::parse(a), Version::parse(b)) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Greater,
+        (Err(_), Ok(_)) => Ordering::Less,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_installed_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        for version in ["18.2.0", "20.1.0", "18.10.0"] {
+            std::fs::create_dir_all(dir.path().join("node").join(version)).unwrap();
+        }
+        std::fs::create_dir_all(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("README"), "").unwrap();
+
+        let installed = read_installed_versions(dir.path()).unwrap();
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed["node"], vec!["20.1.0", "18.10.0", "18.2.0"]);
+
+        let missing = read_installed_versions(dir.path().join("missing")).unwrap();
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn test_read_lockfile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plm.json");
+        let config = ProjectConfig::default_for_project("demo", "/tmp/demo");
+        std::fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+
+        assert_eq!(read_lockfile(&path).unwrap().project_name, "demo");
+        assert!(matches!(
+            read_lockfile(dir.path().join("missing.json")),
+            Err(PluginError::ConfigError(_))
+        ));
+    }
+}
@@ -0,0 +1,281 @@
+//! PLM 启动器集成
+//!
+//! 插件可以通过 `Plugin::launcher_entries` 为 GUI 工具声明启动器，安装完成后由 PLM 生成，
+//! 使不依赖 PATH 的桌面环境也能找到这些工具：
+//!
+//! - Linux 等 freedesktop 平台：`~/.local/share/applications/plm-<plugin>-<id>.desktop`
+//! - Windows：开始菜单 `Programs/PLM` 下的 `.url` 快捷方式（不支持启动参数）
+//!
+//! 生成的文件都带有 `X-PLM-Plugin` 标记，记录在运行状态中，卸载对应版本时删除；
+//! 没有标记的同名文件不会被覆盖或删除。macOS 没有默认目录，需要通过 `launcher_dir` 指定。
+
+use crate::traits::{LauncherEntry, PluginError};
+use std::path::{Path, PathBuf};
+
+/// PLM 生成的启动器中的标记键，值为插件名
+const LAUNCHER_MARKER: &str = "X-PLM-Plugin";
+
+/// 当前平台的默认启动器目录，不支持的平台返回 None
+pub fn default_launcher_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        dirs::data_dir().map(|dir| dir.join("Microsoft/Windows/Start Menu/Programs/PLM"))
+    } else if cfg!(target_os = "macos") {
+        None
+    } else {
+        dirs::data_dir().map(|dir| dir.join("applications"))
+    }
+}
+
+/// 在 `dir` 下为插件的一个已安装版本生成启动器，返回写入的文件路径
+///
+/// 相对路径的 `exec` 和 `icon` 相对于 `install_path` 解析
+pub async fn create_launchers(
+    dir: &Path,
+    plugin: &str,
+    install_path: &Path,
+    entries: &[LauncherEntry],
+) -> Result<Vec<PathBuf>, PluginError> {
+    if entries.is_empty() {
+        return Ok(Vec::new());
+    }
+    tokio::fs::create_dir_all(dir).await.map_err(|e| {
+        PluginError::IoError(format!("创建启动器目录 {} 失败: {}", dir.display(), e))
+    })?;
+
+    let mut created = Vec::new();
+    for entry in entries {
+        let path = dir.join(launcher_file_name(plugin, &entry.id));
+        if let Ok(existing) = tokio::fs::read_to_string(&path).await {
+            if !existing.contains(LAUNCHER_MARKER) {
+                return Err(PluginError::ValidationError(format!(
+                    "启动器 {} 已存在且不是由 PLM 生成",
+                    path.display()
+                )));
+            }
+        }
+
+        let content = if cfg!(windows) {
+            if !entry.args.is_empty() {
+                log::warn!("快捷方式不支持启动参数，忽略 {} 的参数", entry.id);
+            }
+            render_shortcut(plugin, install_path, entry)
+        } else {
+            render_desktop_entry(plugin, install_path, entry)
+        };
+        tokio::fs::write(&path, content).await.map_err(|e| {
+            PluginError::IoError(format!("写入启动器 {} 失败: {}", path.display(), e))
+        })?;
+        created.push(path);
+    }
+    Ok(created)
+}
+
+/// 删除之前生成的启动器，已不存在或不是由 PLM 生成的文件跳过
+pub async fn remove_launchers(paths: &[PathBuf]) -> Result<(), PluginError> {
+    for path in paths {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) if content.contains(LAUNCHER_MARKER) => {}
+            Ok(_) => {
+                log::warn!("{} 不是由 PLM 生成，不删除", path.display());
+                continue;
+            }
+            Err(_) => continue,
+        }
+        match tokio::fs::remove_file(path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(PluginError::IoError(format!(
+                    "删除启动器 {} 失败: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 启动器文件名：`plm-<plugin>-<id>`，文件名中不安全的字符替换为 `_`
+fn launcher_file_name(plugin: &str, id: &str) -> String {
+    let sanitize = |value: &str| -> String {
+        value
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    };
+    let extension = if cfg!(windows) { "url" } else { "desktop" };
+    format!("plm-{}-{}.{}", sanitize(plugin), sanitize(id), extension)
+}
+
+/// 生成 freedesktop `.desktop` 文件内容
+fn render_desktop_entry(plugin: &str, install_path: &Path, entry: &LauncherEntry) -> String {
+    let exec = std::iter::once(install_path.join(&entry.exec).to_string_lossy().into_owned())
+        .chain(entry.args.iter().cloned())
+        .map(|arg| quote_exec_arg(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut lines = vec![
+        "[Desktop Entry]".to_string(),
+        "Type=Application".to_string(),
+        format!("Name={}", single_line(&entry.name)),
+        format!("Exec={}", exec),
+        format!("Terminal={}", entry.terminal),
+    ];
+    if let Some(comment) = &entry.comment {
+        lines.push(format!("Comment={}", single_line(comment)));
+    }
+    if let Some(icon) = &entry.icon {
+        lines.push(format!(
+            "Icon={}",
+            single_line(&install_path.join(icon).to_string_lossy())
+        ));
+    }
+    if !entry.categories.is_empty() {
+        lines.push(format!("Categories={};", entry.categories.join(";")));
+    }
+    lines.push(format!("{}={}", LAUNCHER_MARKER, single_line(plugin)));
+    lines.join("\n") + "\n"
+}
+
+/// 生成 Windows `.url` 快捷方式内容
+fn render_shortcut(plugin: &str, install_path: &Path, entry: &LauncherEntry) -> String {
+    let target = install_path.join(&entry.exec);
+    let mut lines = vec![
+        "[InternetShortcut]".to_string(),
+        format!("URL={}", file_url(&target)),
+    ];
+    if let Some(icon) = &entry.icon {
+        lines.push(format!("IconFile={}", install_path.join(icon).display()));
+        lines.push("IconIndex=0".to_string());
+    }
+    lines.push(format!("{}={}", LAUNCHER_MARKER, single_line(plugin)));
+    lines.join("\r\n") + "\r\n"
+}
+
+/// 按 freedesktop 规范给 Exec 参数加引号
+fn quote_exec_arg(arg: &str) -> String {
+    let needs_quotes = arg.is_empty()
+        || arg.chars().any(|c| {
+            c.is_whitespace()
+                || matches!(
+                    c,
+                    '"' | '\'' | '\\' | '>' | '<' | '~' | '|' | '&' | ';' | '$' | '*' | '?' | '#'
+                        | '(' | ')' | '`'
+                )
+        });
+    let arg = single_line(arg).replace('%', "%%");
+    if !needs_quotes {
+        return arg;
+    }
+    let mut quoted = String::from('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 键值文件中的值不能跨行
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+fn file_url(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        format!("file:///{}", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_entry() -> LauncherEntry {
+        let mut entry = LauncherEntry::new("studio", "Tool Studio", "bin/studio")
+            .with_icon("share/studio.png")
+            .with_comment("Edit\nthings")
+            .with_category("Development")
+            .with_category("IDE");
+        entry.args = vec!["--profile".to_string(), "my work".to_string()];
+        entry
+    }
+
+    #[test]
+    fn test_render_desktop_entry() {
+        let content = render_desktop_entry("tool", Path::new("/opt/tool/1.0.0"), &sample_entry());
+        assert!(content.starts_with("[Desktop Entry]\nType=Application\n"));
+        assert!(content.contains("Name=Tool Studio\n"));
+        assert!(content.contains("Exec=/opt/tool/1.0.0/bin/studio --profile \"my work\"\n"));
+        assert!(content.contains("Comment=Edit things\n"));
+        assert!(content.contains("Icon=/opt/tool/1.0.0/share/studio.png\n"));
+        assert!(content.contains("Categories=Development;IDE;\n"));
+        assert!(content.ends_with("X-PLM-Plugin=tool\n"));
+
+        assert_eq!(quote_exec_arg("100%"), "100%%");
+        assert_eq!(quote_exec_arg("a\"b"), "\"a\\\"b\"");
+    }
+
+    #[test]
+    fn test_render_shortcut() {
+        let content = render_shortcut("tool", Path::new("C:\\Tools\\tool"), &sample_entry());
+        assert!(content.starts_with("[InternetShortcut]\r\n"));
+        assert!(content.contains("URL=file:///C:/Tools/tool"));
+        assert!(content.contains("IconIndex=0\r\n"));
+        assert!(content.ends_with("X-PLM-Plugin=tool\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_create_and_remove_launchers() {
+        let dir = tempfile::tempdir().unwrap();
+        let launchers = dir.path().join("applications");
+        let entries = [sample_entry(), LauncherEntry::new("cli tool", "CLI", "bin/cli")];
+
+        let created = create_launchers(&launchers, "tool", Path::new("/opt/tool"), &entries)
+            .await
+            .unwrap();
+        assert_eq!(created.len(), 2);
+        assert!(created[1]
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("plm-tool-cli_tool."));
+        // 重新安装时覆盖自己生成的文件
+        create_launchers(&launchers, "tool", Path::new("/opt/tool"), &entries)
+            .await
+            .unwrap();
+
+        // 不是由 PLM 生成的文件既不覆盖也不删除
+        let foreign = launchers.join(launcher_file_name("other", "app"));
+        std::fs::write(&foreign, "[Desktop Entry]\n").unwrap();
+        assert!(create_launchers(
+            &launchers,
+            "other",
+            Path::new("/opt/other"),
+            &[LauncherEntry::new("app", "App", "app")]
+        )
+        .await
+        .is_err());
+
+        let mut paths = created.clone();
+        paths.push(foreign.clone());
+        remove_launchers(&paths).await.unwrap();
+        assert!(created.iter().all(|path| !path.exists()));
+        assert!(foreign.exists());
+        // 已删除的启动器再次删除不报错
+        remove_launchers(&created).await.unwrap();
+    }
+}
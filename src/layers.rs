@@ -0,0 +1,233 @@
+//! PLM 分层配置
+//!
+//! 加载配置时按以下顺序合并，后面的层覆盖前面的层：
+//!
+//! 1. 内置默认值（全局设置、Git 钩子等）
+//! 2. 用户配置 `~/.plm/config.json`（可用环境变量 `PLM_USER_CONFIG` 指定其他路径）
+//! 3. 项目配置 `plm.json`
+//!
+//! 对象按字段递归合并，数组和其他值整体覆盖。合并时记录每个值的来源，
+//! 保存项目配置时不会写出从默认值或用户配置继承、且未被修改的值。
+
+use crate::config::{GitHooksConfig, GlobalSettings};
+use crate::traits::PluginError;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// 指定用户配置路径的环境变量
+pub const USER_CONFIG_ENV: &str = "PLM_USER_CONFIG";
+
+/// 配置值的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConfigOrigin {
+    Default,
+    User,
+    Project,
+}
+
+impl fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigOrigin::Default => "default",
+            ConfigOrigin::User => "user",
+            ConfigOrigin::Project => "project",
+        })
+    }
+}
+
+/// 合并后的配置
+#[derive(Debug, Clone, Default)]
+pub struct Layered {
+    pub value: Value,
+    /// JSON 指针 -> (来源, 该层的值)，只记录叶子值（非对象或空对象）
+    pub origins: BTreeMap<String, (ConfigOrigin, Value)>,
+}
+
+/// 用户配置路径
+pub fn user_config_path() -> Option<PathBuf> {
+    match std::env::var_os(USER_CONFIG_ENV) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => dirs::home_dir().map(|home| home.join(".plm").join("config.json")),
+    }
+}
+
+/// 读取用户配置，文件不存在时返回 `None`
+pub fn read_user_config() -> Result<Option<Value>, PluginError> {
+    let Some(path) = user_config_path() else {
+        return Ok(None);
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(PluginError::ConfigError(format!(
+                "Failed to read user config {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    serde_json::from_str(&content).map(Some).map_err(|e| {
+        PluginError::ConfigError(format!(
+            "Failed to parse user config {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// 内置默认值层
+pub fn defaults() -> Value {
+    let settings = serde_json::to_value(GlobalSettings::default()).unwrap_or_default();
+    let mut root = Map::new();
+    root.insert("global_settings".to_string(), settings.clone());
+    root.insert("settings".to_string(), settings);
+    root.insert(
+        "git_hooks".to_string(),
+        serde_json::to_value(GitHooksConfig::default()).unwrap_or_default(),
+    );
+    Value::Object(root)
+}
+
+/// 按顺序合并各层
+pub fn merge_layers(layers: Vec<(ConfigOrigin, Value)>) -> Layered {
+    let mut layered = Layered {
+        value: Value::Object(Map::new()),
+        origins: BTreeMap::new(),
+    };
+    for (origin, layer) in layers {
+        record_origins(&layer, "", origin, &mut layered.origins);
+        merge(&mut layered.value, layer);
+    }
+    layered
+}
+
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn record_origins(
+    value: &Value,
+    pointer: &str,
+    origin: ConfigOrigin,
+    origins: &mut BTreeMap<String, (ConfigOrigin, Value)>,
+) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            // 之前的层在该路径上可能是叶子值，现在由子字段各自记录来源
+            origins.remove(pointer);
+            for (key, item) in map {
+                let child = format!("{}/{}", pointer, escape(key));
+                record_origins(item, &child, origin, origins);
+            }
+        }
+        _ => {
+            let prefix = format!("{}/", pointer);
+            origins.retain(|existing, _| !existing.starts_with(&prefix));
+            origins.insert(pointer.to_string(), (origin, value.clone()));
+        }
+    }
+}
+
+/// 删除 JSON 指针指向的值
+pub fn remove_pointer(root: &mut Value, pointer: &str) {
+    let Some((parent, key)) = pointer.rsplit_once('/') else {
+        return;
+    };
+    let key = unescape(key);
+    match root.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.remove(&key);
+        }
+        Some(Value::Array(items)) => {
+            if let Ok(index) = key.parse::<usize>() {
+                if index < items.len() {
+                    items.remove(index);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 把 JSON 指针转换为点分路径，如 `/global_settings/cache_dir` -> `global_settings.cache_dir`
+pub fn display_path(pointer: &str) -> String {
+    pointer
+        .trim_start_matches('/')
+        .split('/')
+        .map(unescape)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(key: &str) -> String {
+    key.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_with_origins() {
+        let layered = merge_layers(vec![
+            (
+                ConfigOrigin::Default,
+                json!({ "global_settings": { "cache_dir": "~/.plm/cache", "parallel_downloads": 4 } }),
+            ),
+            (
+                ConfigOrigin::User,
+                json!({ "global_settings": { "cache_dir": "/data/plm" }, "sources": [{ "url": "a" }] }),
+            ),
+            (
+                ConfigOrigin::Project,
+                json!({ "global_settings": { "parallel_downloads": 8 }, "sources": [] }),
+            ),
+        ]);
+
+        assert_eq!(
+            layered.value,
+            json!({
+                "global_settings": { "cache_dir": "/data/plm", "parallel_downloads": 8 },
+                "sources": []
+            })
+        );
+        assert_eq!(
+            layered.origins["/global_settings/cache_dir"].0,
+            ConfigOrigin::User
+        );
+        assert_eq!(
+            layered.origins["/global_settings/parallel_downloads"].0,
+            ConfigOrigin::Project
+        );
+        assert_eq!(layered.origins["/sources"].0, ConfigOrigin::Project);
+        assert!(!layered.origins.contains_key("/sources/0/url"));
+    }
+
+    #[test]
+    fn test_remove_pointer_and_display_path() {
+        let mut value = json!({ "a": { "b/c": 1, "d": 2 } });
+        remove_pointer(&mut value, "/a/b~1c");
+        assert_eq!(value, json!({ "a": { "d": 2 } }));
+        assert_eq!(display_path("/a/b~1c"), "a.b/c");
+    }
+}
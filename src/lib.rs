@@ -3,13 +3,86 @@
 //! This library provides a complete plugin lifecycle management system that can be
 //! integrated into any Rust project through simple configuration.
 
+pub mod audit;
+pub mod auth;
+pub mod backup;
+pub mod bug_report;
+pub mod build;
+pub mod cache;
+pub mod cargo_subcommand;
+pub mod check;
+pub mod compat;
 pub mod config;
 pub mod core;
+pub mod crates;
+pub mod declarative;
+pub mod delegate;
+pub mod download;
+pub mod events;
+pub mod export;
+pub mod extract;
+pub mod git_hooks;
+pub mod git_registry;
+pub mod github;
+pub mod gitlab;
+pub mod hooks;
+pub mod id;
+pub mod inspect;
+pub mod launcher;
+pub mod layers;
+pub mod lint;
+pub mod lock;
+pub mod managed;
+pub mod metadata_cache;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod migrate;
+pub mod mirror;
+pub mod negative_cache;
+pub mod node;
+#[cfg(feature = "object-store")]
+pub mod object_store;
+pub mod oci;
+pub mod output;
+#[cfg(feature = "p2p")]
+pub mod p2p;
+pub mod permissions;
+pub mod prelude;
+pub mod project_files;
+pub mod providers;
+pub mod publish;
+pub mod registry;
+#[cfg(feature = "registry-server")]
+pub mod registry_server;
+pub mod releases;
+pub mod reload;
+pub mod rustup;
+pub mod sdk;
+pub mod secrets;
+pub mod self_update;
+pub mod shims;
+pub mod signature;
+pub mod state;
+pub mod static_registry;
+pub mod system;
+pub mod target;
+pub mod temp;
+pub mod terraform;
+pub mod timings;
 pub mod traits;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod upgrade;
+pub mod vendor;
+pub mod version;
 
 // Re-export main types for easy use
 pub use config::{PluginConfig, ProjectConfig};
-pub use core::PluginManager;
+pub use core::{PluginManager, PluginManagerBuilder};
+pub use id::PluginId;
+#[cfg(feature = "metrics")]
+pub use metrics::MetricsSnapshot;
+pub use timings::TimingsReport;
 pub use traits::{Plugin, PluginError, PluginMetadata};
 
 /// Initialize plugin manager from project configuration
@@ -0,0 +1,413 @@
+//! PLM 配置检查
+//!
+//! 在原始 JSON 层面检查 plm.json（保留未知字段，便于安全改写），发现：
+//!
+//! - 已废弃的字段（如插件的 `config`，已由 `settings` 取代）
+//! - 冗余的默认值（平台设置块中与基础设置相同的项）
+//! - 未使用的插件条目
+//! - 无法访问的插件源
+//! - 与主字段不一致的兼容性字段（`project_name`、`settings` 等）
+//!
+//! 可自动修复的问题会生成修复后的配置，并可渲染为逐行差异供预览。
+
+use crate::config::{ProjectConfig, PLATFORM_KEYS};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// 顶层已知字段
+const KNOWN_TOP_LEVEL_FIELDS: &[&str] = &[
+    "config_version",
+    "project",
+    "global_settings",
+    "plugins",
+    "sources",
+    "git_hooks",
+    "project_name",
+    "project_root",
+    "version",
+    "settings",
+];
+
+/// 兼容性字段与其对应的主字段路径
+const COMPAT_FIELDS: &[(&str, &[&str])] = &[
+    ("project_name", &["project", "name"]),
+    ("project_root", &["project", "root_path"]),
+    ("version", &["project", "version"]),
+    ("settings", &["global_settings"]),
+];
+
+/// 问题级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Warning,
+    Error,
+}
+
+/// 单个检查问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintIssue {
+    pub severity: LintSeverity,
+    /// 问题所在的字段路径，如 `plugins.node.config`
+    pub path: String,
+    pub message: String,
+    /// 是否可以自动修复
+    pub fixable: bool,
+}
+
+/// 检查报告
+#[derive(Debug, Clone)]
+pub struct LintReport {
+    pub issues: Vec<LintIssue>,
+    /// 应用所有安全修复后的配置，没有可修复问题时为 None
+    pub fixed: Option<Value>,
+}
+
+impl LintReport {
+    /// 是否没有任何问题
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// 可自动修复的问题数量
+    pub fn fixable_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.fixable).count()
+    }
+}
+
+/// 检查原始配置，`base_dir` 用于解析相对路径的本地插件源
+pub fn lint(raw: &Value, base_dir: &Path) -> LintReport {
+    let mut issues = Vec::new();
+    let mut fixed = raw.clone();
+
+    if let Some(root) = fixed.as_object_mut() {
+        check_unknown_fields(root, &mut issues);
+        check_compat_fields(root, &mut issues);
+
+        if let Some(plugins) = root.get_mut("plugins").and_then(Value::as_object_mut) {
+            for (name, plugin) in plugins.iter_mut() {
+                if let Some(plugin) = plugin.as_object_mut() {
+                    check_deprecated_plugin_fields(name, plugin, &mut issues);
+                    check_redundant_platform_settings(name, plugin, &mut issues);
+                    check_unused_plugin(name, plugin, &mut issues);
+                }
+            }
+        }
+
+        check_sources(root, base_dir, &mut issues);
+    } else {
+        issues.push(LintIssue {
+            severity: LintSeverity::Error,
+            path: String::new(),
+            message: "Configuration must be a JSON object".to_string(),
+            fixable: false,
+        });
+    }
+
+    // 只有修复结果仍能被正确解析时才提供修复
+    let fixed = (issues.iter().any(|i| i.fixable)
+        && serde_json::from_value::<ProjectConfig>(fixed.clone()).is_ok())
+    .then_some(fixed);
+
+    LintReport { issues, fixed }
+}
+
+fn check_unknown_fields(root: &Map<String, Value>, issues: &mut Vec<LintIssue>) {
+    for key in root.keys() {
+        if !KNOWN_TOP_LEVEL_FIELDS.contains(&key.as_str()) {
+            issues.push(LintIssue {
+                severity: LintSeverity::Warning,
+                path: key.clone(),
+                message: "Unknown field is ignored by PLM".to_string(),
+                fixable: false,
+            });
+        }
+    }
+}
+
+fn check_compat_fields(root: &mut Map<String, Value>, issues: &mut Vec<LintIssue>) {
+    for (compat, primary_path) in COMPAT_FIELDS {
+        let Some(primary) = lookup(root, primary_path).cloned() else {
+            continue;
+        };
+        let Some(current) = root.get(*compat) else {
+            continue;
+        };
+
+        if *current != primary {
+            issues.push(LintIssue {
+                severity: LintSeverity::Warning,
+                path: compat.to_string(),
+                message: format!(
+                    "Compatibility field differs from '{}'; it will be synced",
+                    primary_path.join(".")
+                ),
+                fixable: true,
+            });
+            root.insert(compat.to_string(), primary);
+        }
+    }
+}
+
+fn check_deprecated_plugin_fields(
+    name: &str,
+    plugin: &mut Map<String, Value>,
+    issues: &mut Vec<LintIssue>,
+) {
+    if !matches!(plugin.get("config"), Some(Value::Object(_))) {
+        return;
+    }
+    let Some(Value::Object(legacy)) = plugin.remove("config") else {
+        return;
+    };
+
+    issues.push(LintIssue {
+        severity: LintSeverity::Warning,
+        path: format!("plugins.{}.config", name),
+        message: "Field 'config' is deprecated; use 'settings' instead".to_string(),
+        fixable: true,
+    });
+
+    let settings = plugin
+        .entry("settings")
+        .or_insert_with(|| Value::Object(Map::new()));
+    if let Some(settings) = settings.as_object_mut() {
+        for (key, value) in legacy {
+            // 已存在的 settings 项优先
+            settings.entry(key).or_insert(value);
+        }
+    }
+}
+
+fn check_redundant_platform_settings(
+    name: &str,
+    plugin: &mut Map<String, Value>,
+    issues: &mut Vec<LintIssue>,
+) {
+    let Some(settings) = plugin.get_mut("settings").and_then(Value::as_object_mut) else {
+        return;
+    };
+
+    for platform in PLATFORM_KEYS {
+        let Some(Value::Object(block)) = settings.get(*platform) else {
+            continue;
+        };
+
+        let redundant: Vec<String> = block
+            .iter()
+            .filter(|(key, value)| settings.get(key.as_str()) == Some(*value))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &redundant {
+            issues.push(LintIssue {
+                severity: LintSeverity::Warning,
+                path: format!("plugins.{}.settings.{}.{}", name, platform, key),
+                message: "Platform override repeats the base setting".to_string(),
+                fixable: true,
+            });
+        }
+
+        if let Some(Value::Object(block)) = settings.get_mut(*platform) {
+            for key in &redundant {
+                block.remove(key);
+            }
+            if block.is_empty() && !redundant.is_empty() {
+                settings.remove(*platform);
+            }
+        }
+    }
+}
+
+fn check_unused_plugin(name: &str, plugin: &Map<String, Value>, issues: &mut Vec<LintIssue>) {
+    let enabled = plugin
+        .get("enabled")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let has_version = plugin.get("version").is_some_and(|v| !v.is_null());
+    let has_settings = plugin
+        .get("settings")
+        .and_then(Value::as_object)
+        .is_some_and(|s| !s.is_empty());
+
+    if !enabled && !has_version && !has_settings {
+        issues.push(LintIssue {
+            severity: LintSeverity::Warning,
+            path: format!("plugins.{}", name),
+            message: "Plugin is disabled and has no version or settings; consider removing it"
+                .to_string(),
+            fixable: false,
+        });
+    }
+}
+
+fn check_sources(root: &Map<String, Value>, base_dir: &Path, issues: &mut Vec<LintIssue>) {
+    let mut sources: Vec<(String, &Value)> = Vec::new();
+    if let Some(list) = root.get("sources").and_then(Value::as_array) {
+        for (i, source) in list.iter().enumerate() {
+            sources.push((format!("sources[{}]", i), source));
+        }
+    }
+    if let Some(plugins) = root.get("plugins").and_then(Value::as_object) {
+        for (name, plugin) in plugins {
+            if let Some(source) = plugin.get("source").filter(|s| !s.is_null()) {
+                sources.push((format!("plugins.{}.source", name), source));
+            }
+        }
+    }
+
+    for (path, source) in sources {
+        let source_type = source.get("type").and_then(Value::as_str).unwrap_or("");
+        let url = source.get("url").and_then(Value::as_str).unwrap_or("");
+
+        let problem = match source_type {
+            "local" => {
+                let local = Path::new(url);
+                let resolved = if local.is_absolute() {
+                    local.to_path_buf()
+                } else {
+                    base_dir.join(local)
+                };
+                (!resolved.exists()).then(|| format!("Local path '{}' does not exist", url))
+            }
+            // Git 仓库注册表（`git+<url>[#branch]`）
+            "registry" if url.starts_with("git+") => match url::Url::parse(&url["git+".len()..]) {
+                Ok(parsed)
+                    if matches!(parsed.scheme(), "http" | "https" | "ssh" | "git" | "file") =>
+                {
+                    None
+                }
+                Ok(parsed) => Some(format!("Unsupported git URL scheme '{}'", parsed.scheme())),
+                Err(e) => Some(format!("Invalid git URL '{}': {}", url, e)),
+            },
+            "builtin" => (!crate::core::BUILTIN_PLUGINS.contains(&url))
+                .then(|| format!("Unknown builtin plugin '{}'", url)),
+            "http" | "registry" | "static" => match url::Url::parse(url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https" | "file") => None,
+                Ok(parsed) => Some(format!("Unsupported URL scheme '{}'", parsed.scheme())),
+                Err(e) => Some(format!("Invalid URL '{}': {}", url, e)),
+            },
+            _ => None,
+        };
+
+        if let Some(message) = problem {
+            issues.push(LintIssue {
+                severity: LintSeverity::Error,
+                path,
+                message: format!("Unreachable source: {}", message),
+                fixable: false,
+            });
+        }
+    }
+}
+
+fn lookup<'a>(root: &'a Map<String, Value>, path: &[&str]) -> Option<&'a Value> {
+    let (first, rest) = path.split_first()?;
+    rest.iter()
+        .try_fold(root.get(*first)?, |value, key| value.get(*key))
+}
+
+/// 渲染两个文本之间的逐行差异（`-` 删除，`+` 新增，未变化的行以空格开头）
+pub fn render_diff(before: &str, after: &str) -> String {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+
+    // 最长公共子序列
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out.push_str(&format!("  {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            out.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        } else {
+            out.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_config() -> Value {
+        serde_json::to_value(ProjectConfig::default_for_project("lint-test", ".")).unwrap()
+    }
+
+    #[test]
+    fn test_clean_default_config() {
+        let report = lint(&sample_config(), Path::new("."));
+        assert!(report.is_clean(), "{:?}", report.issues);
+        assert!(report.fixed.is_none());
+    }
+
+    #[test]
+    fn test_fixes_compat_and_deprecated_fields() {
+        let mut raw = sample_config();
+        raw["project_name"] = Value::String("stale-name".to_string());
+        raw["plugins"]["node"] = serde_json::json!({
+            "name": "node",
+            "enabled": true,
+            "version": "18.17.0",
+            "source": null,
+            "auto_update": false,
+            "settings": {
+                "binary": "node",
+                "linux": { "binary": "node" }
+            },
+            "config": { "registry": "https://registry.npmjs.org" }
+        });
+
+        let report = lint(&raw, Path::new("."));
+        assert_eq!(report.fixable_count(), 3);
+
+        let fixed = report.fixed.unwrap();
+        assert_eq!(fixed["project_name"], "lint-test");
+        assert!(fixed["plugins"]["node"].get("config").is_none());
+        assert_eq!(
+            fixed["plugins"]["node"]["settings"]["registry"],
+            "https://registry.npmjs.org"
+        );
+        assert!(fixed["plugins"]["node"]["settings"].get("linux").is_none());
+    }
+
+    #[test]
+    fn test_unknown_builtin_source() {
+        let mut raw = sample_config();
+        raw["plugins"]["nodejs"] = serde_json::json!({
+            "name": "nodejs",
+            "enabled": true,
+            "source": { "type": "builtin", "url": "nodejs" }
+        });
+        let report = lint(&raw, Path::new("."));
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].path, "plugins.nodejs.source");
+        assert!(report.issues[0].message.contains("Unknown builtin plugin"));
+
+        raw["plugins"]["nodejs"]["source"]["url"] = Value::String("node".to_string());
+        assert!(lint(&raw, Path::new(".")).is_clean());
+    }
+
+    #[test]
+    fn test_render_diff() {
+        let diff = render_diff("a\nb\nc\n", "a\nc\nd\n");
+        assert_eq!(diff, "  a\n- b\n  c\n+ d\n");
+    }
+}
@@ -0,0 +1,314 @@
+//! PLM 进程锁
+//!
+//! 修改配置文件、下载缓存或插件目录的命令在运行期间持有 `cache_dir/plm.lock`，
+//! 避免两个 plm 进程同时写入同一份数据。锁文件以独占方式创建，内容为持有者的进程号和获取时间，
+//! 进程退出时删除。这是协作式的锁，只约束同样获取锁的 plm 进程。
+//!
+//! 持有者已经退出（进程不存在；无法判断时锁文件超过 `STALE_AFTER`）的锁视为过期锁，直接接管。
+//! 进程是否存在在 Unix 上用 `kill(pid, 0)`、Windows 上用 `OpenProcess` 判断，
+//! 因此 `process::exit` 或崩溃后没有删除的锁文件不会阻塞后续命令。
+
+use crate::temp::TempFileGuard;
+use crate::traits::PluginError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// 锁文件名，位于 `cache_dir` 下
+pub const LOCK_FILE: &str = "plm.lock";
+
+/// 无法判断持有进程是否存在时，锁文件超过该时长视为过期
+pub const STALE_AFTER: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// 等待锁时的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 刚创建、尚未写入内容的锁文件在该时长内不视为损坏
+const WRITE_GRACE: Duration = Duration::from_secs(5);
+
+/// 锁的持有者
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub acquired_at: DateTime<Utc>,
+}
+
+/// 已获取的进程锁，释放时删除锁文件
+#[derive(Debug)]
+pub struct ProcessLock {
+    path: PathBuf,
+}
+
+impl ProcessLock {
+    /// 获取锁
+    ///
+    /// `wait` 为 None 时锁被占用立即返回错误，否则最多等待该时长
+    pub async fn acquire(path: &Path, wait: Option<Duration>) -> Result<Self, PluginError> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                PluginError::IoError(format!("创建锁目录 {} 失败: {}", parent.display(), e))
+            })?;
+        }
+
+        let started = Instant::now();
+        let mut logged = false;
+        loop {
+            let owner = match try_create(path)? {
+                None => {
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                    })
+                }
+                Some(owner) => owner,
+            };
+
+            if is_stale(path, owner.as_ref()) {
+                log::warn!("接管过期的锁文件 {}", path.display());
+                take_over(path, owner.as_ref())?;
+                continue;
+            }
+
+            let busy = || {
+                PluginError::PluginError(match &owner {
+                    Some(owner) => format!(
+                        "Another plm process is running (pid {}, since {}); wait for it to finish or rerun with --wait",
+                        owner.pid,
+                        owner.acquired_at.format("%Y-%m-%d %H:%M:%S UTC")
+                    ),
+                    None => "Another plm process is running; wait for it to finish or rerun with --wait"
+                        .to_string(),
+                })
+            };
+            match wait {
+                Some(wait) if started.elapsed() < wait => {
+                    if !logged {
+                        log::info!("等待其他 plm 进程释放 {}", path.display());
+                        logged = true;
+                    }
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+                _ => return Err(busy()),
+            }
+        }
+    }
+
+    /// 锁文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ProcessLock {
+    fn drop(&mut self) {
+        // 只删除自己持有的锁，锁被当作过期锁接管时不删除别人的锁
+        if read_owner(&self.path).is_some_and(|owner| owner.pid == std::process::id()) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// 独占创建锁文件，已存在时返回 `Some(持有者)`（内容无法解析时为 `Some(None)`）
+fn try_create(path: &Path) -> Result<Option<Option<LockOwner>>, PluginError> {
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+    {
+        Ok(mut file) => {
+            let owner = LockOwner {
+                pid: std::process::id(),
+                acquired_at: Utc::now(),
+            };
+            let content = serde_json::to_vec(&owner)
+                .map_err(|e| PluginError::IoError(format!("序列化锁信息失败: {}", e)))?;
+            file.write_all(&content).map_err(|e| {
+                PluginError::IoError(format!("写入锁文件 {} 失败: {}", path.display(), e))
+            })?;
+            Ok(None)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(Some(read_owner(path))),
+        Err(e) => Err(PluginError::IoError(format!(
+            "创建锁文件 {} 失败: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+fn read_owner(path: &Path) -> Option<LockOwner> {
+    let content = std::fs::read(path).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+/// 持有者已经退出的锁
+fn is_stale(path: &Path, owner: Option<&LockOwner>) -> bool {
+    let age = std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .unwrap_or_default();
+    match owner {
+        Some(owner) => match process_exists(owner.pid) {
+            Some(exists) => !exists,
+            None => age > STALE_AFTER,
+        },
+        // 内容损坏，或者其他进程刚创建还没来得及写入
+        None => age > WRITE_GRACE,
+    }
+}
+
+/// 移走过期的锁文件，之后由 `try_create` 重新独占创建
+///
+/// 先把锁文件重命名为只属于本进程的名称，确认移走的仍是检查过的过期锁后再删除；
+/// 移走的是其他进程刚接管的新锁时原样放回，不会出现两个进程都认为自己持有锁的情况
+fn take_over(path: &Path, stale: Option<&LockOwner>) -> Result<(), PluginError> {
+    let moved = TempFileGuard::sibling(path, &format!("stale-{}", std::process::id()));
+    match std::fs::rename(path, moved.path()) {
+        Ok(()) => {}
+        // 已被其他进程接管并释放
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(PluginError::IoError(format!(
+                "移走锁文件 {} 失败: {}",
+                path.display(),
+                e
+            )))
+        }
+    }
+
+    let moved_owner = read_owner(moved.path());
+    let still_stale = match stale {
+        Some(_) => moved_owner.as_ref() == stale,
+        None => moved_owner.is_none() && is_stale(moved.path(), None),
+    };
+    if !still_stale {
+        // 硬链接不会覆盖已存在的文件，放回时不会顶掉其他进程此后创建的锁
+        if let Err(e) = std::fs::hard_link(moved.path(), path) {
+            log::warn!("放回锁文件 {} 失败: {}", path.display(), e);
+        }
+    }
+    Ok(())
+}
+
+/// 进程是否存在，无法判断时返回 None
+#[cfg(unix)]
+fn process_exists(pid: u32) -> Option<bool> {
+    // pid 0 和负数在 kill 中表示进程组，不是合法的持有者
+    let pid = match libc::pid_t::try_from(pid) {
+        Ok(pid) if pid > 0 => pid,
+        _ => return Some(false),
+    };
+    // 信号 0 只检查进程是否存在；EPERM 表示进程存在但属于其他用户
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return Some(true);
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::ESRCH) => Some(false),
+        Some(libc::EPERM) => Some(true),
+        _ => None,
+    }
+}
+
+/// 进程是否存在，无法判断时返回 None
+#[cfg(windows)]
+fn process_exists(pid: u32) -> Option<bool> {
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ACCESS_DENIED};
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    const STILL_ACTIVE: u32 = 259;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            // 没有权限打开说明进程存在，其他错误（如 ERROR_INVALID_PARAMETER）说明进程不存在
+            return Some(GetLastError() == ERROR_ACCESS_DENIED);
+        }
+        let mut code = 0;
+        let queried = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        queried.then_some(code == STILL_ACTIVE)
+    }
+}
+
+/// 进程是否存在，无法判断时返回 None
+#[cfg(not(any(unix, windows)))]
+fn process_exists(_pid: u32) -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_process_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache").join(LOCK_FILE);
+
+        let lock = ProcessLock::acquire(&path, None).await.unwrap();
+        assert_eq!(read_owner(&path).unwrap().pid, std::process::id());
+
+        // 持有者仍在运行，不等待时立即失败，等待超时后失败
+        let error = ProcessLock::acquire(&path, None).await.unwrap_err();
+        assert!(error.to_string().contains("Another plm process is running"));
+        let started = Instant::now();
+        assert!(
+            ProcessLock::acquire(&path, Some(Duration::from_millis(300)))
+                .await
+                .is_err()
+        );
+        assert!(started.elapsed() >= Duration::from_millis(300));
+
+        // 释放后其他等待者可以获取
+        let waiter = tokio::spawn({
+            let path = path.clone();
+            async move { ProcessLock::acquire(&path, Some(Duration::from_secs(10))).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(lock);
+        let lock = waiter.await.unwrap().unwrap();
+        drop(lock);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_take_over_keeps_fresh_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCK_FILE);
+        let stale = LockOwner {
+            pid: u32::MAX,
+            acquired_at: Utc::now(),
+        };
+        // 检查之后另一个进程已经接管并写入了自己的锁
+        let fresh = LockOwner {
+            pid: std::process::id(),
+            acquired_at: Utc::now(),
+        };
+        std::fs::write(&path, serde_json::to_vec(&fresh).unwrap()).unwrap();
+
+        take_over(&path, Some(&stale)).unwrap();
+        assert_eq!(read_owner(&path), Some(fresh));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        take_over(&path, read_owner(&path).as_ref()).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[cfg(any(unix, windows))]
+    #[tokio::test]
+    async fn test_stale_lock_is_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCK_FILE);
+        let owner = LockOwner {
+            pid: u32::MAX,
+            acquired_at: Utc::now(),
+        };
+        std::fs::write(&path, serde_json::to_vec(&owner).unwrap()).unwrap();
+
+        let lock = ProcessLock::acquire(&path, None).await.unwrap();
+        assert_eq!(read_owner(lock.path()).unwrap().pid, std::process::id());
+    }
+}
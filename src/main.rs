@@ -1,13 +1,19 @@
 //! PLM CLI - Plugin Lifecycle Manager
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use colored::Colorize;
+use futures_util::FutureExt;
+use plm::output::{
+    fit_line, DiscoverReport, OutdatedPlugin, OutdatedReport, OutputFormat, PluginListEntry,
+    PluginStatusEntry, PluginValidation, RenderSettings, StatusReport,
+};
 use plm::{init_from_config, quick_setup};
+use std::panic::AssertUnwindSafe;
 
 #[derive(Parser)]
 #[command(name = "plm")]
 #[command(about = "Plugin Lifecycle Manager")]
-#[command(version = "0.1.0")]
+#[command(version)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
@@ -19,6 +25,30 @@ struct Cli {
     /// Verbose output
     #[arg(short, long)]
     verbose: bool,
+
+    /// Print how long each phase (config load, plugin load, download, ...) took
+    #[arg(long)]
+    timings: bool,
+
+    /// Wait for another running plm process to finish (up to lock_timeout seconds) instead of failing
+    #[arg(long, global = true)]
+    wait: bool,
+
+    /// Load plugins that require a newer plm version (same as PLM_IGNORE_COMPAT=1)
+    #[arg(long, global = true)]
+    ignore_compat: bool,
+
+    /// Deterministic output at a fixed width, for snapshot tests (same as PLM_DETERMINISTIC=1)
+    #[arg(long, global = true, hide = true, value_name = "COLUMNS")]
+    render_width: Option<usize>,
+}
+
+/// Structured output for scripting
+#[derive(Args)]
+struct OutputArgs {
+    /// Output format: text, json or yaml
+    #[arg(long, value_name = "FORMAT", default_value = "text")]
+    output: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -42,6 +72,23 @@ enum Commands {
         /// Force installation
         #[arg(short, long)]
         force: bool,
+        /// Show what would be downloaded and installed without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Install offline, using only artifacts from a directory created by `plm vendor`
+        #[arg(long, value_name = "DIR")]
+        vendor_dir: Option<std::path::PathBuf>,
+        /// Install artifacts for another platform, e.g. linux-arm64 (requires --dest)
+        #[arg(
+            long,
+            value_name = "PLATFORM",
+            requires = "dest",
+            conflicts_with = "dry_run"
+        )]
+        platform: Option<plm::target::TargetPlatform>,
+        /// Directory for --platform installs; the plugin goes into <DIR>/<plugin>/<version>
+        #[arg(long, value_name = "DIR", requires = "platform")]
+        dest: Option<std::path::PathBuf>,
     },
     /// Uninstall a plugin
     Uninstall {
@@ -49,40 +96,88 @@ enum Commands {
         name: String,
         /// Plugin version
         version: String,
+        /// Show what would be removed without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Remove recorded installations of plugins no longer enabled in the config
+    Prune {
+        /// Show what would be removed without changing anything
+        #[arg(long)]
+        dry_run: bool,
     },
     /// List plugins
     List {
-        /// Show only installed plugins
+        /// Show only plugins with installed versions, and list those versions
         #[arg(short, long)]
         installed: bool,
+        /// Show only plugins enabled in the config
+        #[arg(long)]
+        enabled: bool,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// List available and installed versions of a plugin
+    Versions {
+        /// Plugin name
+        name: String,
+        /// Include pre-release and yanked versions
+        #[arg(short, long)]
+        all: bool,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     /// Show plugin information
     Info {
         /// Plugin name
         name: String,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Run a plugin-specific command, e.g. `plm exec node -- npm ping`
+    Exec {
+        /// Plugin name
+        name: String,
+        /// Command and arguments, after `--`
+        #[arg(last = true, required = true, value_name = "COMMAND")]
+        command: Vec<String>,
     },
     /// Discover available plugins
-    Discover,
+    Discover {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
     /// Validate plugins
     Validate {
         /// Plugin name (validate all if not specified)
         #[arg(short, long)]
         name: Option<String>,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     /// Configure plugin settings
+    #[command(args_conflicts_with_subcommands = true)]
     Config {
+        #[command(subcommand)]
+        action: Option<ConfigCommands>,
         /// Plugin name
-        name: String,
+        name: Option<String>,
         /// Setting key
         key: Option<String>,
         /// Setting value
         value: Option<String>,
+        /// Show every effective setting and the layer it comes from (default, user, project)
+        #[arg(long)]
+        show_origin: bool,
     },
     /// Export configuration
     Export {
         /// Output file path
         #[arg(short, long)]
         output: String,
+        /// Output format: json (plm.json), devcontainer, nix or dockerfile
+        #[arg(short, long, default_value = "json")]
+        format: plm::export::ExportFormat,
     },
     /// Import configuration
     Import {
@@ -90,15 +185,318 @@ enum Commands {
         #[arg(short, long)]
         input: String,
     },
+    /// Restore the configuration file from a rotated backup
+    RestoreConfig {
+        /// Backup to restore (1 is the most recent)
+        #[arg(long, value_name = "N", default_value_t = 1)]
+        from_backup: usize,
+        /// List available backups instead of restoring
+        #[arg(short, long)]
+        list: bool,
+    },
+    /// Check active tool versions against the project's pinned versions
+    Check,
+    /// Install all enabled plugins at their pinned versions
+    Sync {
+        /// Quiet mode (minimal output)
+        #[arg(short, long)]
+        quiet: bool,
+        /// List the versions that would be installed without installing them
+        #[arg(long)]
+        dry_run: bool,
+        /// Install offline, using only artifacts from a directory created by `plm vendor`
+        #[arg(long, value_name = "DIR")]
+        vendor_dir: Option<std::path::PathBuf>,
+        /// Instead of downloading, write shims that install each missing plugin on first run
+        #[arg(long, conflicts_with_all = ["dry_run", "vendor_dir"])]
+        lazy: bool,
+        /// Install artifacts for another platform, e.g. linux-arm64 (requires --dest)
+        #[arg(long, value_name = "PLATFORM", requires = "dest", conflicts_with_all = ["dry_run", "lazy"])]
+        platform: Option<plm::target::TargetPlatform>,
+        /// Directory for --platform installs; each plugin goes into <DIR>/<plugin>/<version>
+        #[arg(long, value_name = "DIR", requires = "platform")]
+        dest: Option<std::path::PathBuf>,
+    },
+    /// Run a tool through a shim written by `plm sync --lazy`, installing its plugin first if needed
+    #[command(hide = true, disable_help_flag = true)]
+    ShimExec {
+        /// Plugin name
+        plugin: String,
+        /// Tool name
+        tool: String,
+        /// Arguments passed to the tool
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Download the artifacts of pinned plugins into a portable directory for offline installs
+    Vendor {
+        /// Vendor directory (created if missing)
+        dir: std::path::PathBuf,
+    },
+    /// Maintain a private registry mirror
+    Mirror {
+        #[command(subcommand)]
+        action: MirrorCommands,
+    },
+    /// Manage a self-hosted static registry
+    Registry {
+        #[command(subcommand)]
+        action: RegistryCommands,
+    },
+    /// Package a plugin from its manifest and publish it to a registry or static source
+    Publish {
+        /// Plugin manifest; artifact paths are relative to its directory
+        #[arg(default_value = "plm-plugin.json")]
+        manifest: std::path::PathBuf,
+        /// URL of the configured source to publish to (defaults to the first registry or static source)
+        #[arg(long, value_name = "URL")]
+        source: Option<String>,
+        /// Validate and checksum the package without uploading it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Update one plugin or all enabled plugins, honoring version constraints in the config
+    Update {
+        /// Plugin name (updates all enabled plugins when omitted)
+        name: Option<String>,
+        /// Show the versions that would be installed without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Upgrade enabled plugins that opt into auto_update, within their version constraints
+    Upgrade {
+        /// Upgrade every enabled plugin; exact pins and plugins with auto_update disabled are skipped
+        #[arg(long)]
+        all: bool,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Interactive terminal UI for installing, updating and uninstalling plugins
+    #[cfg(feature = "tui")]
+    Ui,
+    /// Search all configured registry sources for plugins
+    Search {
+        /// Search query
+        query: String,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Show registered plugins, active versions and changes not yet applied
+    Status {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Show configured plugins whose pinned version is behind the registry
+    Outdated {
+        /// Ignore cached "not found" results and query the registry again
+        #[arg(long)]
+        refresh: bool,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Upgrade the config, plugin store and shims left by older plm versions
+    Migrate {
+        /// Show the migration steps without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Manage git hooks
+    Hooks {
+        #[command(subcommand)]
+        action: HooksCommands,
+    },
+    /// Manage the download cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommands,
+    },
+    /// Manage credentials stored in the OS keychain
+    Secret {
+        #[command(subcommand)]
+        action: SecretCommands,
+    },
+    /// Update plm itself to the latest release
+    SelfUpdate {
+        /// Only check whether a newer release is available
+        #[arg(long)]
+        check: bool,
+        /// Reinstall even if already on the latest release
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Collect sanitized diagnostics into a tarball to attach to bug reports
+    BugReport {
+        /// Archive path (default: plm-bug-report-<timestamp>.tar.gz)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+        /// Write the archive without reviewing its contents
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Lint the configuration file
+    Lint {
+        /// Apply safe fixes
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Lint the configuration file and apply safe fixes
+    Fix,
+    /// Validate a configuration file against the config schema
+    Validate {
+        /// File to validate (defaults to the --config file)
+        file: Option<String>,
+    },
+    /// Print the JSON Schema for plm.json
+    Schema {
+        /// Write the schema to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
 }
 
+#[derive(Subcommand)]
+enum HooksCommands {
+    /// Install git pre-commit/post-checkout hooks configured in plm.json
+    InstallGit {
+        /// Overwrite existing hooks not created by PLM
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheCommands {
+    /// Show cache location, size and contents
+    Info,
+    /// Evict cached files by size and age limits
+    Clean {
+        /// Remove all cached files
+        #[arg(long)]
+        all: bool,
+        /// Maximum cache size in MB (overrides cache_max_size_mb)
+        #[arg(long)]
+        max_size: Option<u64>,
+        /// Maximum age in days (overrides cache_max_age_days)
+        #[arg(long)]
+        max_age: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MirrorCommands {
+    /// Copy plugin versions from an upstream registry and regenerate the mirror index and checksums
+    Sync {
+        /// Mirror directory (created if missing); serve it with any static file server
+        dir: std::path::PathBuf,
+        /// URL the mirror directory is served from, used for artifact download URLs
+        #[arg(long, value_name = "URL")]
+        base_url: String,
+        /// Plugin to mirror as name or name@version (repeatable; defaults to the enabled plugins in the config)
+        #[arg(short, long = "plugin", value_name = "SPEC")]
+        plugins: Vec<String>,
+        /// Upstream registry (defaults to registry_url)
+        #[arg(long, value_name = "URL")]
+        from: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RegistryCommands {
+    /// Generate index.json for a static registry from the plugin manifests in <dir>/manifests
+    Build {
+        /// Registry directory containing manifests/ and the artifacts they reference
+        dir: std::path::PathBuf,
+    },
+    /// Serve a registry directory (laid out like `plm mirror sync` output) over HTTP
+    #[cfg(feature = "registry-server")]
+    Serve {
+        /// Registry directory; published plugins are written into it
+        dir: std::path::PathBuf,
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: std::net::SocketAddr,
+        /// Public URL written into download URLs (defaults to http://<addr>)
+        #[arg(long, value_name = "URL")]
+        base_url: Option<String>,
+        /// Bearer token that `plm publish` must present; publishing is disabled without it
+        #[arg(long, env = "PLM_REGISTRY_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+        /// Largest artifact `plm publish` may upload, in MiB
+        #[arg(long, value_name = "MIB", default_value_t = 1024)]
+        max_upload_mb: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum SecretCommands {
+    /// Store a secret read from stdin in the OS keychain
+    Set {
+        /// Secret name
+        name: String,
+        /// Reference the secret as the token of the source with this URL in plm.json
+        #[arg(long, value_name = "URL")]
+        source: Option<String>,
+    },
+    /// Remove a secret from the OS keychain
+    Delete {
+        /// Secret name
+        name: String,
+    },
+}
+
+/// Exit code when plm crashes, distinct from ordinary command failures (EX_SOFTWARE)
+const CRASH_EXIT_CODE: i32 = 70;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    install_crash_handler();
     let cli = Cli::parse();
 
-    // Initialize logging
-    let log_level = if cli.verbose { "debug" } else { "info" };
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
+    // Initialize logging; bug-report captures the log into the report instead
+    if matches!(cli.command, Commands::BugReport { .. }) {
+        plm::bug_report::LogCapture::install(log::LevelFilter::Debug)?;
+    } else {
+        let log_level = if cli.verbose { "debug" } else { "info" };
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level))
+            .init();
+    }
+
+    let mut render = RenderSettings::from_env();
+    if let Some(width) = cli.render_width {
+        render = render.with_width(width);
+    }
+    render.install();
+    if cli.ignore_compat {
+        std::env::set_var(plm::compat::IGNORE_COMPAT_ENV, "1");
+    }
+
+    // 耗时每次运行都不同，确定性输出时不显示
+    let show_timings = cli.timings && !render.deterministic;
+    // 命令崩溃时展开到这里，进程锁和临时文件已随之释放；崩溃信息已由 panic hook 输出
+    let result = match AssertUnwindSafe(run(cli)).catch_unwind().await {
+        Ok(result) => result,
+        Err(_) => std::process::exit(CRASH_EXIT_CODE),
+    };
+    if show_timings {
+        let report = plm::timings::report();
+        if !report.is_empty() {
+            eprintln!("\n⏱️  Timings:\n{}", report);
+        }
+    }
+    result
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    // 修改配置文件、缓存或插件目录的命令不能与其他 plm 进程同时运行
+    let _lock = if cli.command.modifies_state() {
+        Some(acquire_lock(&cli.config, cli.wait).await?)
+    } else {
+        None
+    };
 
     match cli.command {
         Commands::Init { name, root } => {
@@ -117,68 +515,275 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             name,
             version,
             force,
+            dry_run,
+            vendor_dir,
+            platform,
+            dest,
         } => {
-            let mut manager = init_from_config(&cli.config).await?;
+            let mut manager = init_with_vendor_dir(&cli.config, vendor_dir).await?;
             manager.initialize().await?;
 
-            let mut options = plm::traits::InstallOptions::new();
+            let mut options = cross_install_options(platform, dest)?;
             if force {
                 options = options.force();
             }
+            if dry_run {
+                let plan = manager
+                    .plan_install(&name, version.as_deref(), &options.dry_run())
+                    .await?;
+                print_plan(&plan);
+                return Ok(());
+            }
             if !cli.verbose {
                 options = options.quiet();
             }
 
-            let install_path = manager
+            let result = manager
                 .install_plugin(&name, version.as_deref(), &options)
                 .await?;
-            println!("✅ {} installed to {}", name.green(), install_path);
+            for warning in &result.warnings {
+                println!("⚠️  {}", warning.yellow());
+            }
+            if result.was_cached {
+                println!(
+                    "✅ {} {} already installed at {}",
+                    name.green(),
+                    result.version,
+                    result
+                );
+            } else {
+                match plm::output::format_duration(result.duration) {
+                    Some(duration) => println!(
+                        "✅ {} installed to {} in {}",
+                        name.green(),
+                        result,
+                        duration
+                    ),
+                    None => println!("✅ {} installed to {}", name.green(), result),
+                }
+            }
 
             // Save updated configuration
             manager.save_config(&cli.config).await?;
         }
 
-        Commands::Uninstall { name, version } => {
+        Commands::Uninstall {
+            name,
+            version,
+            dry_run,
+        } => {
             let mut manager = init_from_config(&cli.config).await?;
             manager.initialize().await?;
 
+            if dry_run {
+                print_plan(&manager.plan_uninstall(&name, &version).await?);
+                return Ok(());
+            }
+
             manager.uninstall_plugin(&name, &version).await?;
             println!("✅ {} {} uninstalled", name.green(), version);
         }
 
-        Commands::List { installed: _ } => {
+        Commands::Prune { dry_run } => {
+            let manager = init_from_config(&cli.config).await?;
+            let orphans = manager.prune_orphans(dry_run).await?;
+            if orphans.is_empty() {
+                println!("✅ No orphaned installations");
+                return Ok(());
+            }
+            for orphan in &orphans {
+                let location = orphan
+                    .path
+                    .as_ref()
+                    .map(|path| format!(" ({})", path.display()))
+                    .unwrap_or_default();
+                if dry_run {
+                    println!(
+                        "  - would remove {} {}{}",
+                        orphan.plugin, orphan.version, location
+                    );
+                } else {
+                    println!(
+                        "✅ {} {} removed{}",
+                        orphan.plugin.green(),
+                        orphan.version,
+                        location
+                    );
+                }
+            }
+        }
+
+        Commands::List {
+            installed,
+            enabled,
+            output,
+        } => {
             let manager = init_from_config(&cli.config).await?;
-            let plugins = manager.list_plugins().await;
+            // 插件实现不可用（如已从配置中移除）时使用运行状态中的安装记录
+            let state = manager.state().await?;
 
-            if plugins.is_empty() {
+            let mut entries = Vec::new();
+            let mut plugins = manager.list_plugin_metadata().await;
+            if installed && !enabled {
+                for (name, _) in state.plugins() {
+                    if !plugins.iter().any(|(listed, _)| listed == name) {
+                        plugins.push((name.to_string(), None));
+                    }
+                }
+                plugins.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+            for (name, metadata) in plugins {
+                let is_enabled = manager
+                    .get_plugin_config(&name)
+                    .is_some_and(|config| config.enabled);
+                if enabled && !is_enabled {
+                    continue;
+                }
+                let plugin = manager.get_plugin(&name).await.ok();
+                // 只有已注册实现的插件能查询已安装版本
+                let versions = match (&plugin, installed) {
+                    (Some(plugin), true) => match plugin.list_installed().await {
+                        Ok(versions) => Some(versions),
+                        Err(e) => {
+                            eprintln!("⚠️  Failed to list installed versions of {}: {}", name, e);
+                            None
+                        }
+                    },
+                    (None, true) => Some(state.installed_versions(&name)),
+                    _ => None,
+                };
+                if installed && versions.as_ref().is_none_or(|v| v.is_empty()) {
+                    continue;
+                }
+                entries.push(PluginListEntry {
+                    status: manager.plugin_state(&name).map(|state| state.state),
+                    name,
+                    description: metadata.map(|metadata| metadata.description),
+                    enabled: is_enabled,
+                    installed: versions,
+                });
+            }
+
+            if output.output.is_structured() {
+                println!("{}", output.output.render(&entries)?);
+                return Ok(());
+            }
+
+            if entries.is_empty() {
                 println!("No plugins found");
                 return Ok(());
             }
 
-            println!("Available plugins:");
-            for plugin_name in plugins {
-                let plugin = manager.get_plugin(&plugin_name).await?;
-                let metadata = plugin.metadata();
-                let status_icon = match plugin.status() {
-                    plm::traits::PluginStatus::Active => "✓".green(),
-                    plm::traits::PluginStatus::Inactive => "✗".red(),
-                    plm::traits::PluginStatus::Loading => "⏳".yellow(),
-                    plm::traits::PluginStatus::Error(_) => "⚠".red(),
+            println!(
+                "{}",
+                if installed {
+                    "Installed plugins:"
+                } else {
+                    "Available plugins:"
+                }
+            );
+            for entry in entries {
+                // 未加载的插件使用缓存的元数据，不显示运行状态
+                let status_icon = status_icon(entry.status.as_ref());
+                let description = entry
+                    .description
+                    .unwrap_or_else(|| "(metadata not cached)".to_string());
+
+                let line = match entry.installed {
+                    Some(versions) => format!(
+                        "  {} {} [{}] - {}",
+                        status_icon,
+                        entry.name.cyan(),
+                        versions.join(", "),
+                        description
+                    ),
+                    None => format!("  {} {} - {}", status_icon, entry.name.cyan(), description),
                 };
+                println!("{}", fit_line(&line));
+            }
+        }
 
-                println!(
-                    "  {} {} - {}",
-                    status_icon,
-                    plugin_name.cyan(),
-                    metadata.description
-                );
+        Commands::Versions { name, all, output } => {
+            let mut manager = init_from_config(&cli.config).await?;
+            manager.initialize().await?;
+
+            let mut entries = manager.version_matrix(&name).await?;
+            if output.output.is_structured() {
+                entries
+                    .retain(|entry| all || !(entry.prerelease || entry.yanked) || entry.installed);
+                println!("{}", output.output.render(&entries)?);
+                return Ok(());
+            }
+            println!("{}", format!("Versions of {}:", name).bold().blue());
+            for entry in &entries {
+                if !all && (entry.prerelease || entry.yanked) && !entry.installed {
+                    continue;
+                }
+
+                let marker = if entry.active {
+                    "*".green()
+                } else if entry.installed {
+                    "✓".green()
+                } else {
+                    " ".normal()
+                };
+                let mut flags = Vec::new();
+                if entry.prerelease {
+                    flags.push("prerelease");
+                }
+                if entry.yanked {
+                    flags.push("yanked");
+                }
+                if !entry.platform_compatible {
+                    flags.push("unsupported platform");
+                }
+
+                if flags.is_empty() {
+                    println!("  {} {}", marker, entry.version);
+                } else {
+                    println!(
+                        "  {} {} {}",
+                        marker,
+                        entry.version,
+                        format!("({})", flags.join(", ")).dimmed()
+                    );
+                }
             }
         }
 
-        Commands::Info { name } => {
-            let manager = init_from_config(&cli.config).await?;
+        Commands::Exec { name, command } => {
+            let mut manager = init_from_config(&cli.config).await?;
+            manager.initialize().await?;
+
             let plugin = manager.get_plugin(&name).await?;
-            let metadata = plugin.metadata();
+            if !plugin.supports(plm::traits::Capability::Exec) {
+                return Err(format!("plugin {} does not support exec", name).into());
+            }
+            let (program, args) = command
+                .split_first()
+                .ok_or("exec requires a command after --")?;
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+            let code = match plugin.exec_command(program, &args).await? {
+                plm::traits::ExecOutcome::Exited(code) => code,
+                plm::traits::ExecOutcome::Captured(output) => {
+                    print!("{}", output);
+                    0
+                }
+            };
+            if code != 0 {
+                std::io::Write::flush(&mut std::io::stdout())?;
+                std::process::exit(code);
+            }
+        }
+
+        Commands::Info { name, output } => {
+            let manager = init_from_config(&cli.config).await?;
+            let metadata = manager.plugin_metadata(&name).await?;
+            if output.output.is_structured() {
+                println!("{}", output.output.render(&metadata)?);
+                return Ok(());
+            }
 
             println!("{}", format!("Plugin Information: {}", name).bold().blue());
             println!("  Name: {}", metadata.name);
@@ -204,20 +809,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        Commands::Discover => {
+        Commands::Discover { output } => {
             let mut manager = init_from_config(&cli.config).await?;
             manager.initialize().await?;
 
             let count = manager.discover_plugins().await?;
+            manager.refresh_metadata_cache().await?;
             if count > 0 {
-                println!("✅ Discovered {} new plugins", count);
                 manager.save_config(&cli.config).await?;
+            }
+            if output.output.is_structured() {
+                let report = DiscoverReport { discovered: count };
+                println!("{}", output.output.render(&report)?);
+            } else if count > 0 {
+                println!("✅ Discovered {} new plugins", count);
             } else {
                 println!("ℹ️  No new plugins found");
             }
         }
 
-        Commands::Validate { name } => {
+        Commands::Validate { name, output } => {
             let manager = init_from_config(&cli.config).await?;
 
             if let Some(plugin_name) = name {
@@ -226,13 +837,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 let metadata = plugin.metadata();
                 let is_valid = !metadata.name.is_empty() && !metadata.version.is_empty();
 
-                if is_valid {
+                if output.output.is_structured() {
+                    let validation = PluginValidation {
+                        plugin: plugin_name,
+                        valid: is_valid,
+                    };
+                    println!("{}", output.output.render(&validation)?);
+                } else if is_valid {
                     println!("✅ {} - Valid", plugin_name.green());
                 } else {
                     println!("❌ {} - Invalid (incomplete metadata)", plugin_name.red());
                 }
             } else {
                 let summary = manager.validate_all_plugins().await?;
+                if output.output.is_structured() {
+                    println!("{}", output.output.render(&summary)?);
+                    return Ok(());
+                }
                 println!("📊 Validation Summary:");
                 println!(
                     "  Valid plugins: {}",
@@ -252,23 +873,81 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        Commands::Config { name, key, value } => {
+        Commands::Config {
+            action: Some(action),
+            ..
+        } => match action {
+            ConfigCommands::Lint { fix } => lint_config(&cli.config, fix).await?,
+            ConfigCommands::Fix => lint_config(&cli.config, true).await?,
+            ConfigCommands::Validate { file } => {
+                let file = file.unwrap_or_else(|| cli.config.clone());
+                let content = tokio::fs::read_to_string(&file).await?;
+                match plm::config::ProjectConfig::validate_json(&content) {
+                    Ok(_) => println!("✅ {} is valid", file),
+                    Err(e) => {
+                        eprintln!("❌ {}: {}", file, e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            ConfigCommands::Schema { output } => {
+                let schema =
+                    serde_json::to_string_pretty(&plm::config::ProjectConfig::json_schema())?;
+                match output {
+                    Some(output) => {
+                        tokio::fs::write(&output, schema).await?;
+                        println!("✅ Schema written to {}", output);
+                    }
+                    None => println!("{}", schema),
+                }
+            }
+        },
+
+        Commands::Config {
+            action: None,
+            show_origin: true,
+            ..
+        } => {
+            let config = plm::config::ProjectConfig::load_from_file(&cli.config).await?;
+            for (pointer, origin, raw) in config.origins() {
+                println!(
+                    "{:<8} {} = {}",
+                    origin.to_string().dimmed(),
+                    plm::layers::display_path(pointer).cyan(),
+                    raw
+                );
+            }
+        }
+
+        Commands::Config {
+            action: None,
+            name,
+            key,
+            value,
+            ..
+        } => {
+            let Some(name) = name else {
+                eprintln!("Plugin name is required");
+                std::process::exit(1);
+            };
             let mut manager = init_from_config(&cli.config).await?;
+            manager.enable_auto_save(&cli.config, std::time::Duration::from_millis(200));
 
             match (key, value) {
                 (Some(k), Some(v)) => {
                     // Set configuration value
                     let json_value = serde_json::Value::String(v.clone());
-                    // 获取可变配置并更新
-                    let mut config = manager.get_config().clone();
-                    if let Some(plugin_config) = config.get_plugin_mut(&name) {
-                        plugin_config.set_setting(&k, json_value);
-                        manager.update_config(config);
-                    } else {
+                    if manager.get_plugin_config(&name).is_none() {
                         println!("Plugin '{}' not found", name);
                         return Ok(());
                     }
-                    manager.save_config(&cli.config).await?;
+                    manager
+                        .with_config_mut(|config| {
+                            if let Some(plugin_config) = config.get_plugin_mut(&name) {
+                                plugin_config.set_setting(&k, json_value);
+                            }
+                        })
+                        .await?;
                     println!("✅ Set {} {} = {}", name.cyan(), k, v);
                 }
                 (Some(k), None) => {
@@ -287,7 +966,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     // Show all configuration
                     if let Some(plugin_config) = manager.get_config().get_plugin(&name) {
                         println!("Configuration for {}:", name.cyan());
-                        for (key, value) in &plugin_config.settings {
+                        let mut settings: Vec<_> =
+                            plugin_config.effective_settings().into_iter().collect();
+                        settings.sort_by(|a, b| a.0.cmp(&b.0));
+                        for (key, value) in settings {
                             println!("  {} = {}", key, value);
                         }
                     } else {
@@ -299,12 +981,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     std::process::exit(1);
                 }
             }
+
+            // 写入自动保存尚未持久化的修改
+            let report = manager.shutdown().await?;
+            for (name, error) in &report.failed {
+                eprintln!("⚠️  {} failed to shut down: {}", name.yellow(), error);
+            }
+            for name in &report.timed_out {
+                eprintln!("⚠️  {} did not shut down in time", name.yellow());
+            }
         }
 
-        Commands::Export { output } => {
+        Commands::Export { output, format } => {
             let manager = init_from_config(&cli.config).await?;
-            manager.save_config(&output).await?;
-            println!("✅ Configuration exported to {}", output);
+            if format == plm::export::ExportFormat::Json {
+                manager.save_config(&output).await?;
+            } else {
+                let content = plm::export::export(manager.get_config(), format)?;
+                tokio::fs::write(&output, content).await?;
+            }
+            println!("✅ Configuration exported to {} ({})", output, format);
         }
 
         Commands::Import { input } => {
@@ -315,7 +1011,1054 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             manager.save_config(&cli.config).await?;
             println!("✅ Configuration imported from {}", input);
         }
+
+        Commands::RestoreConfig { from_backup, list } => {
+            let config_path = std::path::Path::new(&cli.config);
+            if list {
+                let backups = plm::backup::list_backups(config_path).await;
+                if backups.is_empty() {
+                    println!("ℹ️  No backups found for {}", cli.config);
+                }
+                for (i, backup) in backups.iter().enumerate() {
+                    println!("  {} {}", i + 1, backup.display());
+                }
+                return Ok(());
+            }
+
+            let backup = plm::backup::backup_path(config_path, from_backup);
+            let backup_str = backup.to_string_lossy();
+            // 只恢复能被正确解析的备份；当前配置会成为新的 .bak.1
+            let config = plm::config::ProjectConfig::load_from_file(&backup_str).await?;
+            config.save_to_file(&cli.config).await?;
+            println!("✅ Restored {} from {}", cli.config, backup.display());
+        }
+
+        Commands::Check => {
+            let manager = init_from_config(&cli.config).await?;
+            let report = manager.check_toolchain().await;
+
+            for tool in &report.tools {
+                let actual = tool.actual.as_deref().unwrap_or("not found");
+                if tool.status == plm::check::CheckStatus::Ok {
+                    println!("✅ {} {}", tool.plugin.green(), actual);
+                } else {
+                    println!(
+                        "❌ {} {} (expected {})",
+                        tool.plugin.red(),
+                        actual,
+                        tool.pinned
+                    );
+                }
+            }
+
+            if !report.is_ok() {
+                println!();
+                println!("To align your toolchain with this project, run:");
+                for tool in report.failures() {
+                    println!("  plm install {} --version {}", tool.plugin, tool.pinned);
+                }
+                std::process::exit(1);
+            }
+
+            println!("✅ All tools match the pinned versions");
+        }
+
+        Commands::Sync {
+            quiet,
+            dry_run,
+            vendor_dir,
+            lazy,
+            platform,
+            dest,
+        } => {
+            let mut manager = init_with_vendor_dir(&cli.config, vendor_dir).await?;
+            manager.initialize().await?;
+
+            if lazy {
+                let program = std::env::current_exe()?;
+                let config_path = std::path::absolute(&cli.config)?;
+                let deferred = manager
+                    .sync_plugins_lazy(&program, &config_path, quiet)
+                    .await?;
+                if !quiet {
+                    if deferred.is_empty() {
+                        println!("✅ All plugins are in sync");
+                    }
+                    for plugin in &deferred {
+                        println!("💤 {} will be installed on first run", plugin.cyan());
+                    }
+                }
+                return Ok(());
+            }
+
+            let mut options = cross_install_options(platform, dest)?.yes();
+            if quiet {
+                options = options.quiet();
+            }
+            if dry_run {
+                options = options.dry_run();
+            }
+
+            let synced = manager.sync_plugins(&options).await?;
+            if dry_run {
+                for plugin in &synced {
+                    println!("🔍 Would install {}", plugin.cyan());
+                }
+                if synced.is_empty() {
+                    println!("✅ All plugins are in sync");
+                }
+            } else if !quiet {
+                if synced.is_empty() {
+                    println!("✅ All plugins are in sync");
+                } else {
+                    for plugin in &synced {
+                        println!("✅ {} installed", plugin.green());
+                    }
+                }
+            }
+        }
+
+        Commands::ShimExec { plugin, tool, args } => {
+            let mut manager = init_from_config(&cli.config).await?;
+            manager.initialize().await?;
+            let (target, wrapper) = {
+                // 只在安装期间持有进程锁，工具本身可能长时间运行
+                let _lock = acquire_lock(&cli.config, true).await?;
+                manager
+                    .prepare_lazy_shim(&plugin, &tool, confirm_lazy_install)
+                    .await?
+            };
+
+            let status = std::process::Command::new(&target)
+                .args(&wrapper.args)
+                .args(&args)
+                .envs(&wrapper.env)
+                .status()
+                .map_err(|e| format!("Failed to run {}: {}", target.display(), e))?;
+            std::process::exit(status.code().unwrap_or(1));
+        }
+
+        Commands::Vendor { dir } => {
+            let config = plm::config::ProjectConfig::load_from_file(&cli.config).await?;
+            let report = plm::vendor::vendor(&config, &dir).await?;
+            for (name, version) in &report.vendored {
+                println!("✅ {} {} vendored", name.green(), version);
+            }
+            for (name, reason) in &report.skipped {
+                println!("⏭️  {} skipped: {}", name.yellow(), reason);
+            }
+            for (name, error) in &report.failed {
+                println!("❌ {}: {}", name.red(), error);
+            }
+            if !report.is_success() {
+                return Err(
+                    format!("{} plugin(s) could not be vendored", report.failed.len()).into(),
+                );
+            }
+            println!(
+                "📦 Vendor directory ready at {}; install from it with --vendor-dir",
+                dir.display()
+            );
+        }
+
+        Commands::Mirror { action } => match action {
+            MirrorCommands::Sync {
+                dir,
+                base_url,
+                plugins,
+                from,
+            } => {
+                let config = plm::config::ProjectConfig::load_from_file(&cli.config).await?;
+                let specs = if plugins.is_empty() {
+                    plm::mirror::MirrorSpec::from_config(&config)
+                } else {
+                    plugins
+                        .iter()
+                        .map(|spec| plm::mirror::MirrorSpec::parse(spec))
+                        .collect::<Result<Vec<_>, _>>()?
+                };
+                if specs.is_empty() {
+                    return Err(
+                        "No plugins to mirror; pass --plugin or enable plugins in the config"
+                            .into(),
+                    );
+                }
+
+                let mirror = plm::mirror::Mirror::new(&dir, &base_url);
+                let report = plm::mirror::sync(&config, &specs, &mirror, from.as_deref()).await?;
+                for (name, version) in &report.mirrored {
+                    println!("✅ {} {} mirrored", name.green(), version);
+                }
+                for (name, version) in &report.up_to_date {
+                    println!("✓ {} {} already mirrored", name.green(), version);
+                }
+                for (name, error) in &report.failed {
+                    println!("❌ {}: {}", name.red(), error);
+                }
+                if !report.is_success() {
+                    return Err(
+                        format!("{} plugin(s) could not be mirrored", report.failed.len()).into(),
+                    );
+                }
+                println!(
+                    "🪞 Mirror at {} updated; serve it at {}",
+                    dir.display(),
+                    base_url
+                );
+            }
+        },
+
+        Commands::Registry { action } => match action {
+            RegistryCommands::Build { dir } => {
+                let index = plm::static_registry::build(&dir).await?;
+                for (name, plugin) in &index.plugins {
+                    println!(
+                        "✅ {} ({} artifact(s))",
+                        name.green(),
+                        plugin.versions.len()
+                    );
+                }
+                println!(
+                    "📦 Wrote {} with {} plugin(s); publish {} and add it as a \"static\" source",
+                    dir.join(plm::static_registry::INDEX_FILE).display(),
+                    index.plugins.len(),
+                    dir.display()
+                );
+            }
+            #[cfg(feature = "registry-server")]
+            RegistryCommands::Serve {
+                dir,
+                addr,
+                base_url,
+                token,
+                max_upload_mb,
+            } => {
+                let publishing = token.is_some();
+                let (addr, server) = plm::registry_server::RegistryServer::new(&dir)
+                    .with_base_url(base_url)
+                    .with_publish_token(token)
+                    .with_upload_limit(max_upload_mb.saturating_mul(1024 * 1024))
+                    .bind(addr)?;
+                println!(
+                    "🌐 Serving {} at http://{} (publishing {})",
+                    dir.display(),
+                    addr,
+                    if publishing { "enabled" } else { "disabled" }
+                );
+                server.await?;
+            }
+        },
+
+        Commands::Publish {
+            manifest,
+            source,
+            dry_run,
+        } => {
+            let package = plm::publish::package(&manifest).await?;
+            for (info, path) in package.artifacts() {
+                println!(
+                    "📦 {} {} [{}] {} ({} bytes, sha256 {})",
+                    package.name(),
+                    info.version,
+                    info.platform,
+                    path.display(),
+                    info.size.unwrap_or_default(),
+                    info.checksum.as_deref().unwrap_or_default()
+                );
+            }
+            let config = plm::config::ProjectConfig::load_from_file(&cli.config).await?;
+            let target = plm::publish::select_source(&config, source.as_deref())?;
+            if dry_run {
+                println!(
+                    "✅ Package {} is valid; would publish {} to {}",
+                    package.name().green(),
+                    package.versions().join(", "),
+                    target.url
+                );
+            } else {
+                let report =
+                    plm::publish::publish(&package, target, &config.global_settings).await?;
+                println!(
+                    "🚀 Published {} {} ({} artifact(s)) to {}",
+                    report.name.green(),
+                    report.versions.join(", "),
+                    report.artifacts,
+                    report.destination
+                );
+            }
+        }
+
+        Commands::Update { name, dry_run } => {
+            let mut manager = init_from_config(&cli.config).await?;
+            manager.initialize().await?;
+
+            if dry_run {
+                let plans = match name {
+                    Some(name) => {
+                        let plan = manager.plan_update(&name).await;
+                        vec![(name, plan)]
+                    }
+                    None => manager.plan_update_all().await,
+                };
+                if plans.is_empty() {
+                    println!("ℹ️  No plugins to update");
+                }
+                for (name, plan) in &plans {
+                    match plan {
+                        Ok(plan) => print_plan(plan),
+                        Err(e) => println!("❌ {}: {}", name.red(), e),
+                    }
+                }
+                return Ok(());
+            }
+
+            let results = match name {
+                Some(name) => {
+                    let result = manager.update_plugin(&name).await;
+                    vec![(name, result)]
+                }
+                None => manager.update_all_plugins().await,
+            };
+
+            let mut failed = 0;
+            for (name, result) in &results {
+                match result {
+                    Ok(update) if update.changed() => println!(
+                        "✅ {} {} → {}",
+                        name.green(),
+                        update.previous.as_deref().unwrap_or("-"),
+                        update.version.green()
+                    ),
+                    Ok(update) => println!("✅ {} {} is up to date", name.green(), update.version),
+                    Err(e) => {
+                        failed += 1;
+                        println!("❌ {}: {}", name.red(), e);
+                    }
+                }
+            }
+            if results.is_empty() {
+                println!("ℹ️  No plugins to update");
+            }
+
+            if manager.is_dirty() {
+                manager.save_config(&cli.config).await?;
+            }
+            if failed > 0 {
+                return Err(format!("{} plugin(s) failed to update", failed).into());
+            }
+        }
+
+        Commands::Upgrade { all, output } => {
+            if !all {
+                return Err(
+                    "plm upgrade requires --all (use `plm update <name>` for one plugin)".into(),
+                );
+            }
+            let mut manager = init_from_config(&cli.config).await?;
+            manager.initialize().await?;
+
+            let summary = manager.upgrade_all_plugins().await;
+            if manager.is_dirty() {
+                manager.save_config(&cli.config).await?;
+            }
+
+            if output.output.is_structured() {
+                println!("{}", output.output.render(&summary)?);
+            } else {
+                for update in &summary.upgraded {
+                    println!(
+                        "⬆️  {} {} → {}",
+                        update.plugin.green(),
+                        update.previous.as_deref().unwrap_or("-"),
+                        update.version.green()
+                    );
+                }
+                for update in &summary.unchanged {
+                    println!(
+                        "✅ {} {} is up to date",
+                        update.plugin.green(),
+                        update.version
+                    );
+                }
+                for (name, reason) in &summary.skipped {
+                    println!("⏭️  {} skipped: {}", name.yellow(), reason);
+                }
+                for (name, error) in &summary.failed {
+                    println!("❌ {}: {}", name.red(), error);
+                }
+                println!(
+                    "\n{} upgraded, {} up to date, {} skipped, {} failed",
+                    summary.upgraded.len(),
+                    summary.unchanged.len(),
+                    summary.skipped.len(),
+                    summary.failed.len()
+                );
+            }
+            if !summary.is_success() {
+                return Err(format!("{} plugin(s) failed to upgrade", summary.failed.len()).into());
+            }
+        }
+
+        Commands::Migrate { dry_run } => {
+            let plan = plm::upgrade::plan(std::path::Path::new(&cli.config)).await?;
+            if plan.actions.is_empty() {
+                println!(
+                    "✅ {} and the plugin store are already up to date",
+                    cli.config
+                );
+            } else if dry_run {
+                for action in &plan.actions {
+                    println!("  • {}", action);
+                }
+                println!("ℹ️  Dry run: nothing was modified");
+            } else {
+                for action in plm::upgrade::apply(&plan).await? {
+                    if let plm::upgrade::UpgradeAction::MigrateConfig { path, .. } = &action {
+                        println!(
+                            "✅ {} (previous file saved as {})",
+                            action,
+                            plm::upgrade::config_backup_path(path).display()
+                        );
+                    } else {
+                        println!("✅ {}", action);
+                    }
+                }
+            }
+            if !plan.manual.is_empty() {
+                println!("⚠️  Needs manual attention:");
+                for note in &plan.manual {
+                    println!("  • {}", note);
+                }
+            }
+        }
+
+        #[cfg(feature = "tui")]
+        Commands::Ui => {
+            let mut manager = init_from_config(&cli.config).await?;
+            manager.initialize().await?;
+
+            plm::tui::run(&mut manager).await?;
+            if manager.is_dirty() {
+                manager.save_config(&cli.config).await?;
+            }
+        }
+
+        Commands::Search { query, output } => {
+            let config = plm::config::ProjectConfig::load_from_file(&cli.config).await?;
+            let mut results = plm::registry::search_sources(&config, &query).await?;
+            if plm::output::is_deterministic() {
+                results
+                    .hits
+                    .sort_by(|a, b| (&a.name, &a.source).cmp(&(&b.name, &b.source)));
+            }
+
+            if output.output.is_structured() {
+                println!("{}", output.output.render(&results.hits)?);
+            } else {
+                if results.hits.is_empty() {
+                    println!("ℹ️  No plugins found for '{}'", query);
+                }
+                for hit in &results.hits {
+                    let line = format!(
+                        "  {} {} - {} {}",
+                        hit.name.cyan(),
+                        hit.latest_version.as_deref().unwrap_or("-").green(),
+                        hit.description,
+                        format!("({})", hit.source).dimmed()
+                    );
+                    println!("{}", fit_line(&line));
+                }
+            }
+            for (source, error) in &results.failed {
+                eprintln!("⚠️  {}: {}", source, error);
+            }
+        }
+
+        Commands::Status { output } => {
+            let mut manager = init_from_config(&cli.config).await?;
+            // 初始化失败的插件记为 error 状态，在下面逐个显示
+            if let Err(e) = manager.initialize().await {
+                log::debug!("{}", e);
+            }
+            let state = manager.state().await?;
+            let config = manager.get_config();
+
+            let mut names: std::collections::BTreeSet<String> =
+                config.plugins.keys().cloned().collect();
+            names.extend(state.plugins().map(|(name, _)| name.to_string()));
+
+            let mut report = StatusReport {
+                pending: state.pending_changes(config),
+                ..StatusReport::default()
+            };
+            for name in names {
+                let plugin_state = state.get(&name).cloned().unwrap_or_default();
+                let lifecycle = manager.plugin_state(&name);
+                report.plugins.push(PluginStatusEntry {
+                    enabled: config.get_plugin(&name).is_some_and(|p| p.enabled),
+                    status: lifecycle.as_ref().map(|lifecycle| lifecycle.state),
+                    last_error: lifecycle.and_then(|lifecycle| lifecycle.last_error),
+                    active_version: plugin_state.active_version,
+                    installed_at: plugin_state.installed_at,
+                    updated_at: plugin_state.updated_at,
+                    name,
+                });
+            }
+            if output.output.is_structured() {
+                println!("{}", output.output.render(&report)?);
+                return Ok(());
+            }
+
+            println!(
+                "📦 {} plugins, {} registered",
+                report.plugins.len(),
+                manager.plugin_ids().await.len()
+            );
+            for entry in &report.plugins {
+                let mut line = format!(
+                    "  {} {} {}",
+                    status_icon(entry.status.as_ref()),
+                    entry.name.cyan(),
+                    entry.active_version.as_deref().unwrap_or("-").green()
+                );
+                if !entry.enabled {
+                    line.push_str(&format!(" {}", "(disabled)".dimmed()));
+                }
+                let times: Vec<String> = [
+                    ("installed", entry.installed_at.as_ref()),
+                    ("updated", entry.updated_at.as_ref()),
+                ]
+                .into_iter()
+                .filter_map(|(label, time)| {
+                    plm::output::format_timestamp(time?).map(|time| format!("{} {}", label, time))
+                })
+                .collect();
+                if !times.is_empty() {
+                    line.push_str(&format!(" {}", format!("({})", times.join(", ")).dimmed()));
+                }
+                println!("{}", fit_line(&line));
+                if let (Some(plm::traits::LifecycleState::Error), Some(error)) =
+                    (entry.status, &entry.last_error)
+                {
+                    println!("{}", fit_line(&format!("      {}", error.red())));
+                }
+            }
+
+            if report.pending.is_empty() {
+                println!("✅ No pending changes");
+            } else {
+                println!("Pending changes (run plm sync to apply):");
+                for change in &report.pending {
+                    println!("{}", fit_line(&format!("  • {}", change)));
+                }
+            }
+        }
+
+        Commands::Outdated { refresh, output } => {
+            let config = plm::config::ProjectConfig::load_from_file(&cli.config).await?;
+            let mut names: Vec<String> = config
+                .plugins
+                .values()
+                .filter(|p| p.enabled)
+                .map(|p| p.name.clone())
+                .collect();
+            names.sort();
+
+            let registry = plm::registry::RegistryClient::from_settings(&config.global_settings)?
+                .with_refresh(refresh);
+            let result = registry.fetch_batch(&names).await?;
+
+            let mut report = OutdatedReport {
+                missing: result.missing.clone(),
+                failed: result.failed.clone(),
+                ..OutdatedReport::default()
+            };
+            for name in &names {
+                let Some(latest) = result.entries.get(name).and_then(|e| e.latest.as_ref()) else {
+                    continue;
+                };
+                let pinned = config.get_plugin(name).and_then(|p| p.get_version());
+                if pinned != Some(latest.version.as_str()) {
+                    report.outdated.push(OutdatedPlugin {
+                        name: name.clone(),
+                        pinned: pinned.map(str::to_string),
+                        latest: latest.version.clone(),
+                    });
+                }
+            }
+            if output.output.is_structured() {
+                println!("{}", output.output.render(&report)?);
+                return Ok(());
+            }
+
+            for plugin in &report.outdated {
+                println!(
+                    "  {} {} → {}",
+                    plugin.name.cyan(),
+                    plugin.pinned.as_deref().unwrap_or("-"),
+                    plugin.latest.green()
+                );
+            }
+            for name in &result.missing {
+                println!(
+                    "  {} {}",
+                    "?".yellow(),
+                    format!("{} not found in registry", name).dimmed()
+                );
+            }
+            for (name, error) in &result.failed {
+                println!("  {} {}: {}", "⚠".red(), name, error);
+            }
+
+            if report.outdated.is_empty() {
+                println!("✅ All plugins are up to date");
+            }
+        }
+
+        Commands::Hooks { action } => match action {
+            HooksCommands::InstallGit { force } => {
+                let manager = init_from_config(&cli.config).await?;
+                let config = manager.get_config();
+                let installed = plm::git_hooks::install_git_hooks(
+                    config.get_project_root(),
+                    &config.git_hooks,
+                    force,
+                )
+                .await?;
+
+                if installed.is_empty() {
+                    println!("ℹ️  No git hooks configured");
+                }
+                for path in installed {
+                    println!("✅ Installed git hook {}", path.display());
+                }
+            }
+        },
+
+        Commands::Cache { action } => {
+            let manager = init_from_config(&cli.config).await?;
+            let mut settings = manager.get_config().global_settings.clone();
+            let cache = plm::cache::Cache::from_settings(&settings);
+
+            match action {
+                CacheCommands::Info => {
+                    let info = cache.info().await?;
+                    println!("📦 Cache: {}", cache.root().display());
+                    println!(
+                        "   {} files, {}",
+                        info.entries.len(),
+                        plm::cache::format_size(info.total_size)
+                    );
+                    if cli.verbose {
+                        for entry in &info.entries {
+                            if plm::output::is_deterministic() {
+                                println!(
+                                    "   {} {}",
+                                    entry.path.display(),
+                                    plm::cache::format_size(entry.size)
+                                );
+                            } else {
+                                println!(
+                                    "   {} {} (last used {})",
+                                    entry.path.display(),
+                                    plm::cache::format_size(entry.size),
+                                    entry.last_access.format("%Y-%m-%d %H:%M")
+                                );
+                            }
+                        }
+                    }
+                }
+                CacheCommands::Clean {
+                    all,
+                    max_size,
+                    max_age,
+                } => {
+                    let report = if all {
+                        cache.clear().await?
+                    } else {
+                        settings.cache_max_size_mb = max_size.or(settings.cache_max_size_mb);
+                        settings.cache_max_age_days = max_age.or(settings.cache_max_age_days);
+                        let policy = plm::cache::CachePolicy::from_settings(&settings);
+                        if policy == plm::cache::CachePolicy::default() {
+                            println!("ℹ️  No cache limits configured; use --max-size, --max-age or --all");
+                            return Ok(());
+                        }
+                        cache.clean(&policy).await?
+                    };
+
+                    println!(
+                        "✅ Removed {} files, freed {}",
+                        report.removed.len(),
+                        plm::cache::format_size(report.freed_bytes)
+                    );
+                }
+            }
+        }
+
+        Commands::SelfUpdate { check, force } => {
+            // 配置文件不存在时（例如在项目之外运行）使用默认设置
+            let settings = plm::config::ProjectConfig::load_from_file(&cli.config)
+                .await
+                .map(|config| config.global_settings)
+                .unwrap_or_default();
+            let updater = plm::self_update::SelfUpdater::from_settings(&settings);
+            let release = updater.latest_release().await?;
+            let current = plm::self_update::current_version();
+
+            if !release.is_newer_than(current) && !force {
+                println!("✅ plm {} is up to date", current);
+                return Ok(());
+            }
+            if check {
+                println!(
+                    "⬆️  plm {} is available (current {}), run `plm self-update` to install it",
+                    release.version.green(),
+                    current
+                );
+                return Ok(());
+            }
+
+            let target = std::env::current_exe()?;
+            updater.install(&release, &target).await?;
+            println!(
+                "✅ Updated plm {} → {} at {}",
+                current,
+                release.version.green(),
+                target.display()
+            );
+        }
+
+        Commands::BugReport { output, yes } => {
+            let report = plm::bug_report::collect(&cli.config).await;
+            let path = output.unwrap_or_else(|| {
+                format!(
+                    "plm-bug-report-{}.tar.gz",
+                    chrono::Utc::now().format("%Y%m%d-%H%M%S")
+                )
+                .into()
+            });
+
+            println!("📋 The bug report contains:");
+            for file in &report.files {
+                println!(
+                    "  {} ({})",
+                    file.name.cyan(),
+                    plm::cache::format_size(file.contents.len() as u64)
+                );
+            }
+            println!("   Secrets in the config, URLs and logs are replaced with ***");
+
+            if !yes && std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+                loop {
+                    print!("\nFile to review (Enter writes the report, q cancels): ");
+                    std::io::Write::flush(&mut std::io::stdout())?;
+                    let mut answer = String::new();
+                    std::io::stdin().read_line(&mut answer)?;
+                    match answer.trim() {
+                        "" => break,
+                        "q" | "quit" => {
+                            println!("Cancelled, nothing was written");
+                            return Ok(());
+                        }
+                        name => match report.file(name) {
+                            Some(file) => {
+                                println!("{}", format!("── {} ──", file.name).dimmed());
+                                println!("{}", file.contents.trim_end());
+                            }
+                            None => println!("No file named '{}' in the report", name),
+                        },
+                    }
+                }
+            }
+
+            report.write_tarball(&path)?;
+            println!("✅ Wrote {}, attach it to your bug report", path.display());
+        }
+
+        Commands::Secret { action } => match action {
+            SecretCommands::Set { name, source } => {
+                let mut value = String::new();
+                std::io::stdin().read_line(&mut value)?;
+                let value = value.trim_end_matches(['\r', '\n']);
+                if value.is_empty() {
+                    return Err("No secret provided on stdin".into());
+                }
+                plm::secrets::store_secret(&name, value).await?;
+                println!("🔑 Stored secret '{}' in the OS keychain", name);
+
+                if let Some(url) = source {
+                    let mut config =
+                        plm::config::ProjectConfig::load_from_file(&cli.config).await?;
+                    let Some(entry) = config.sources.iter_mut().find(|s| s.url == url) else {
+                        return Err(
+                            format!("No source with URL '{}' in {}", url, cli.config).into()
+                        );
+                    };
+                    entry.token = Some(plm::secrets::keychain_reference(&name));
+                    config.save_to_file(&cli.config).await?;
+                    println!(
+                        "✅ Source {} now uses token {}",
+                        url,
+                        plm::secrets::keychain_reference(&name)
+                    );
+                }
+            }
+            SecretCommands::Delete { name } => {
+                if plm::secrets::delete_secret(&name).await? {
+                    println!("✅ Removed secret '{}'", name);
+                } else {
+                    println!("ℹ️  Secret '{}' not found", name);
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Lint the configuration file, optionally applying safe fixes
+async fn lint_config(config_path: &str, fix: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let content = tokio::fs::read_to_string(config_path).await?;
+    let raw: serde_json::Value = serde_json::from_str(&content)?;
+    let base_dir = std::path::Path::new(config_path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+
+    let report = plm::lint::lint(&raw, base_dir);
+    if report.is_clean() {
+        println!("✅ No issues found in {}", config_path);
+        return Ok(());
+    }
+
+    for issue in &report.issues {
+        let label = match issue.severity {
+            plm::lint::LintSeverity::Error => "error".red(),
+            plm::lint::LintSeverity::Warning => "warning".yellow(),
+        };
+        let fixable = if issue.fixable { " (fixable)" } else { "" };
+        println!(
+            "{}: {} - {}{}",
+            label,
+            issue.path.cyan(),
+            issue.message,
+            fixable
+        );
+    }
+
+    if let Some(fixed) = &report.fixed {
+        let before = serde_json::to_string_pretty(&raw)?;
+        let after = serde_json::to_string_pretty(fixed)?;
+
+        println!();
+        println!("Proposed changes:");
+        for line in plm::lint::render_diff(&before, &after).lines() {
+            if line.starts_with('+') {
+                println!("{}", line.green());
+            } else if line.starts_with('-') {
+                println!("{}", line.red());
+            }
+        }
+
+        if fix {
+            plm::backup::write_with_backups(
+                std::path::Path::new(config_path),
+                &after,
+                plm::backup::backup_count(&raw),
+            )
+            .await?;
+            println!(
+                "✅ Applied {} fixes to {}",
+                report.fixable_count(),
+                config_path
+            );
+        } else {
+            println!(
+                "ℹ️  Run `plm config lint --fix` to apply {} fixes",
+                report.fixable_count()
+            );
+        }
+    }
+
+    let has_errors = report
+        .issues
+        .iter()
+        .any(|i| i.severity == plm::lint::LintSeverity::Error);
+    if has_errors {
+        std::process::exit(1);
     }
 
     Ok(())
 }
+
+/// Record panics in the audit log and point the user at `plm bug-report`, then run
+/// the previous hook for the usual panic message and backtrace
+///
+/// The hook does not exit: panics inside spawned tasks are still caught and returned as
+/// `JoinError`, and `main` picks `CRASH_EXIT_CODE` once the command has unwound.
+/// Builds with `panic = "abort"` cannot unwind, so there the hook exits directly
+fn install_crash_handler() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let location = info.location().map(|location| location.to_string());
+
+        previous(info);
+        eprintln!("💥 plm crashed unexpectedly: {}", message);
+        let record = plm::audit::AuditRecord::crash(&message, location);
+        if let Some(path) = plm::audit::audit_log_path() {
+            if plm::audit::append(&path, &record).is_ok() {
+                eprintln!("   A crash record was written to {}", path.display());
+            }
+        }
+        eprintln!("   Please run `plm bug-report` and attach the archive to an issue.");
+        if cfg!(panic = "abort") {
+            std::process::exit(CRASH_EXIT_CODE);
+        }
+    }));
+}
+
+impl Commands {
+    /// Whether the command writes the config file, the download cache or the plugin directory
+    fn modifies_state(&self) -> bool {
+        match self {
+            Commands::Install { dry_run, .. }
+            | Commands::Uninstall { dry_run, .. }
+            | Commands::Prune { dry_run }
+            | Commands::Sync { dry_run, .. }
+            | Commands::Update { dry_run, .. }
+            | Commands::Migrate { dry_run } => !dry_run,
+            Commands::Config { action, value, .. } => match action {
+                Some(ConfigCommands::Lint { fix }) => *fix,
+                Some(ConfigCommands::Fix) => true,
+                Some(_) => false,
+                None => value.is_some(),
+            },
+            Commands::Cache { action } => matches!(action, CacheCommands::Clean { .. }),
+            Commands::Secret { action } => {
+                matches!(
+                    action,
+                    SecretCommands::Set {
+                        source: Some(_),
+                        ..
+                    }
+                )
+            }
+            #[cfg(feature = "tui")]
+            Commands::Ui => true,
+            Commands::Init { .. }
+            | Commands::Import { .. }
+            | Commands::Discover { .. }
+            | Commands::Vendor { .. }
+            | Commands::Upgrade { .. } => true,
+            Commands::RestoreConfig { list, .. } => !list,
+            // 按需安装脚本随后运行工具本身，持有锁会阻塞其他 plm 进程直到工具退出
+            Commands::ShimExec { .. } => false,
+            // 只读取状态，或只写入 plm 管理之外的位置（导出文件、镜像目录、git 钩子、plm 自身）
+            Commands::List { .. }
+            | Commands::Versions { .. }
+            | Commands::Info { .. }
+            | Commands::Exec { .. }
+            | Commands::Validate { .. }
+            | Commands::Export { .. }
+            | Commands::Check
+            | Commands::Mirror { .. }
+            | Commands::Registry { .. }
+            | Commands::Publish { .. }
+            | Commands::Search { .. }
+            | Commands::Status { .. }
+            | Commands::Outdated { .. }
+            | Commands::Hooks { .. }
+            | Commands::SelfUpdate { .. }
+            | Commands::BugReport { .. } => false,
+        }
+    }
+}
+
+/// Install options for `--platform`/`--dest`; without them, a normal install for the host
+fn cross_install_options(
+    platform: Option<plm::target::TargetPlatform>,
+    dest: Option<std::path::PathBuf>,
+) -> std::io::Result<plm::traits::InstallOptions> {
+    let mut options = plm::traits::InstallOptions::new();
+    if let (Some(platform), Some(dest)) = (platform, dest) {
+        let dest = std::path::absolute(dest)?;
+        options = options
+            .platform(platform)
+            .install_dir(&dest.to_string_lossy());
+    }
+    Ok(options)
+}
+
+/// Ask on the terminal whether to install a plugin for a lazy shim; never installs without a terminal
+fn confirm_lazy_install(spec: &str) -> bool {
+    if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        eprintln!(
+            "{} is not installed; run `plm sync` or set lazy_install to \"auto\"",
+            spec
+        );
+        return false;
+    }
+    eprint!("{} is not installed yet. Install it now? [Y/n] ", spec);
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "" | "y" | "yes")
+}
+
+/// Take the process lock in the cache directory; with `wait`, wait up to `lock_timeout` seconds
+async fn acquire_lock(
+    config_path: &str,
+    wait: bool,
+) -> Result<plm::lock::ProcessLock, plm::traits::PluginError> {
+    // 配置文件不存在时（如 plm init）使用默认设置
+    let settings = plm::config::ProjectConfig::load_from_file(config_path)
+        .await
+        .map(|config| config.global_settings)
+        .unwrap_or_default();
+    let timeout = match settings.lock_timeout {
+        0 => std::time::Duration::MAX,
+        seconds => std::time::Duration::from_secs(seconds),
+    };
+    plm::lock::ProcessLock::acquire(&settings.lock_path(), wait.then_some(timeout)).await
+}
+
+/// Build the manager; with a vendor directory, artifacts are resolved only from it
+async fn init_with_vendor_dir(
+    config_path: &str,
+    vendor_dir: Option<std::path::PathBuf>,
+) -> Result<plm::PluginManager, plm::traits::PluginError> {
+    let Some(vendor_dir) = vendor_dir else {
+        return init_from_config(config_path).await;
+    };
+    let mut config = plm::config::ProjectConfig::load_from_file(config_path).await?;
+    config.global_settings.vendor_dir = Some(vendor_dir);
+    plm::PluginManager::from_project_config(config).await
+}
+
+/// Print one change reported by a dry run
+fn print_plan(plan: &plm::traits::PlannedChange) {
+    if plan.is_noop() {
+        println!("✅ {}", plan);
+    } else {
+        println!("🔍 Would {}", plan);
+    }
+}
+
+/// Icon for a plugin's lifecycle state, `·` when its implementation is not loaded
+fn status_icon(status: Option<&plm::traits::LifecycleState>) -> colored::ColoredString {
+    use plm::traits::LifecycleState;
+    match status {
+        Some(LifecycleState::Active) => "✓".green(),
+        Some(LifecycleState::Registered) => "✗".red(),
+        Some(LifecycleState::Initializing | LifecycleState::ShuttingDown) => "⏳".yellow(),
+        Some(LifecycleState::Error) => "⚠".red(),
+        None => "·".dimmed(),
+    }
+}
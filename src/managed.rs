@@ -0,0 +1,306 @@
+//! 管理器持有的插件
+//!
+//! `Plugin::initialize` 和 `Plugin::shutdown` 需要可变引用，而 `PluginManager::get_plugin`
+//! 把插件以 `Arc<dyn Plugin>` 交给调用方。`ManagedPlugin` 独占插件（`Box<dyn Plugin>`）并放在读写锁中：
+//! 生命周期方法在写锁中执行，其他方法共享读锁，因此调用方持有插件引用时管理器仍能初始化和关闭插件；
+//! 正在执行的安装等操作完成后，关闭才会开始。
+//!
+//! 同步方法（`metadata`、`status` 等）不能等待锁，生命周期方法执行期间返回缓存的元数据、
+//! `PluginStatus::Loading`，`get_help` 返回插件描述，`capabilities` 返回空集合，
+//! `supports_feature` 返回 false。
+//!
+//! 插件配置声明了 `permissions` 时，每次调用插件都在其权限下执行（见 `crate::permissions`），
+//! 安装前还会检查安装目录和传入的环境变量。
+
+use crate::permissions::{self, PermissionGuard};
+use crate::traits::{
+    Capability, ExecOutcome, InstallOptions, LauncherEntry, Plugin, PluginError, PluginMetadata,
+    PluginStatus, VersionInfo, VersionPage,
+};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::RwLock as StdRwLock;
+use tokio::sync::RwLock;
+
+/// 管理器持有的插件，见模块文档
+pub struct ManagedPlugin {
+    inner: RwLock<Box<dyn Plugin>>,
+    /// 最近一次读到的元数据，写锁被持有时由 `metadata` 返回
+    metadata: StdRwLock<PluginMetadata>,
+    /// 配置中声明的权限，调用插件时在当前任务中生效
+    permissions: StdRwLock<Option<PermissionGuard>>,
+}
+
+impl ManagedPlugin {
+    /// 接管插件
+    pub fn new(plugin: Box<dyn Plugin>) -> Self {
+        let metadata = plugin.metadata();
+        Self {
+            inner: RwLock::new(plugin),
+            metadata: StdRwLock::new(metadata),
+            permissions: StdRwLock::new(None),
+        }
+    }
+
+    /// 设置插件的权限，None 表示不受限制
+    pub fn set_permissions(&self, guard: Option<PermissionGuard>) {
+        *self
+            .permissions
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = guard;
+    }
+
+    fn guard(&self) -> Option<PermissionGuard> {
+        self.permissions
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// 在写锁中初始化插件，等待正在执行的其他操作完成
+    pub async fn initialize_exclusive(&self) -> Result<(), PluginError> {
+        let mut plugin = self.inner.write().await;
+        let result = permissions::scope(self.guard(), plugin.initialize()).await;
+        self.refresh_metadata(plugin.metadata());
+        result
+    }
+
+    /// 在写锁中关闭插件，等待正在执行的其他操作完成
+    pub async fn shutdown_exclusive(&self) -> Result<(), PluginError> {
+        let mut plugin = self.inner.write().await;
+        permissions::scope(self.guard(), plugin.shutdown()).await
+    }
+
+    fn cached_metadata(&self) -> PluginMetadata {
+        self.metadata
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    fn refresh_metadata(&self, metadata: PluginMetadata) {
+        *self
+            .metadata
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = metadata;
+    }
+}
+
+#[async_trait]
+impl Plugin for ManagedPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        match self.inner.try_read() {
+            Ok(plugin) => plugin.metadata(),
+            Err(_) => self.cached_metadata(),
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        match self.inner.try_read() {
+            Ok(plugin) => plugin.status(),
+            Err(_) => PluginStatus::Loading,
+        }
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        self.initialize_exclusive().await
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.shutdown_exclusive().await
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        permissions::scope(self.guard(), async {
+            if let Some(guard) = permissions::current() {
+                if let Some(dir) = &options.install_dir {
+                    guard.check_path(Path::new(dir))?;
+                }
+                for name in options.env_vars.keys() {
+                    guard.check_env(name)?;
+                }
+            }
+            self.inner.read().await.install(version, options).await
+        })
+        .await
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.uninstall(version).await
+        })
+        .await
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.list_versions().await
+        })
+        .await
+    }
+
+    async fn list_versions_page(
+        &self,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<VersionPage, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner
+                .read()
+                .await
+                .list_versions_page(cursor, limit)
+                .await
+        })
+        .await
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.list_installed().await
+        })
+        .await
+    }
+
+    async fn project_constraint(&self, project_root: &Path) -> Result<Option<String>, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner
+                .read()
+                .await
+                .project_constraint(project_root)
+                .await
+        })
+        .await
+    }
+
+    async fn launcher_entries(
+        &self,
+        version: &str,
+        install_path: &Path,
+    ) -> Result<Vec<LauncherEntry>, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner
+                .read()
+                .await
+                .launcher_entries(version, install_path)
+                .await
+        })
+        .await
+    }
+
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.is_installed(version).await
+        })
+        .await
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.get_latest_version().await
+        })
+        .await
+    }
+
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.update(version).await
+        })
+        .await
+    }
+
+    async fn switch_version(&self, version: &str) -> Result<(), PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.switch_version(version).await
+        })
+        .await
+    }
+
+    async fn verify_installation(&self, version: &str) -> Result<bool, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.verify_installation(version).await
+        })
+        .await
+    }
+
+    async fn cleanup(&self) -> Result<(), PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.cleanup().await
+        })
+        .await
+    }
+
+    async fn configure(&self, settings: serde_json::Value) -> Result<(), PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.configure(settings).await
+        })
+        .await
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.get_config().await
+        })
+        .await
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.set_config(config).await
+        })
+        .await
+    }
+
+    async fn get_config_value(&self, key: &str) -> Result<Option<String>, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.get_config_value(key).await
+        })
+        .await
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.set_config_value(key, value).await
+        })
+        .await
+    }
+
+    async fn execute_command(&self, command: &str, args: &[&str]) -> Result<String, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.execute_command(command, args).await
+        })
+        .await
+    }
+
+    async fn exec_command(&self, command: &str, args: &[&str]) -> Result<ExecOutcome, PluginError> {
+        permissions::scope(self.guard(), async {
+            self.inner.read().await.exec_command(command, args).await
+        })
+        .await
+    }
+
+    fn get_help(&self) -> String {
+        match self.inner.try_read() {
+            Ok(plugin) => plugin.get_help(),
+            Err(_) => self.cached_metadata().description,
+        }
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        match self.inner.try_read() {
+            Ok(plugin) => plugin.capabilities(),
+            Err(_) => HashSet::new(),
+        }
+    }
+
+    fn supports_feature(&self, feature: &str) -> bool {
+        match self.inner.try_read() {
+            Ok(plugin) => plugin.supports_feature(feature),
+            Err(_) => false,
+        }
+    }
+}
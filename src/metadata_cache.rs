@@ -0,0 +1,133 @@
+//! PLM 插件元数据缓存
+//!
+//! 首次加载插件后把元数据写入缓存目录，之后 `plm list`、`plm info` 等命令
+//! 无需加载插件实现即可显示描述信息。每条记录带有插件配置（名称、版本、来源）
+//! 的哈希，配置变化后记录自动失效。
+
+use crate::config::PluginConfig;
+use crate::traits::{PluginError, PluginMetadata};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// 缓存文件名，位于 `cache_dir` 下
+pub const METADATA_CACHE_FILE: &str = "plugin-metadata.json";
+
+/// 单个插件的缓存记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedMetadata {
+    pub source_hash: String,
+    pub metadata: PluginMetadata,
+    pub cached_at: DateTime<Utc>,
+}
+
+/// 插件元数据缓存，键为插件标识
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetadataCache {
+    #[serde(default)]
+    entries: BTreeMap<String, CachedMetadata>,
+}
+
+impl MetadataCache {
+    /// 读取缓存，文件不存在或损坏时返回空缓存
+    pub async fn load(path: &Path) -> Self {
+        tokio::fs::read_to_string(path)
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 写入缓存
+    pub async fn save(&self, path: &Path) -> Result<(), PluginError> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| PluginError::IoError(format!("创建缓存目录失败: {}", e)))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| PluginError::IoError(format!("序列化元数据缓存失败: {}", e)))?;
+        tokio::fs::write(path, content)
+            .await
+            .map_err(|e| PluginError::IoError(format!("写入元数据缓存失败: {}", e)))
+    }
+
+    /// 获取插件元数据，来源哈希不一致时视为失效
+    pub fn get(&self, id: &str, source_hash: &str) -> Option<&PluginMetadata> {
+        self.entries
+            .get(id)
+            .filter(|entry| entry.source_hash == source_hash)
+            .map(|entry| &entry.metadata)
+    }
+
+    /// 记录插件元数据
+    pub fn insert(&mut self, id: &str, source_hash: String, metadata: PluginMetadata) {
+        self.entries.insert(
+            id.to_string(),
+            CachedMetadata {
+                source_hash,
+                metadata,
+                cached_at: Utc::now(),
+            },
+        );
+    }
+
+    /// 删除不在列表中的插件记录
+    pub fn retain(&mut self, ids: &[String]) {
+        self.entries.retain(|id, _| ids.contains(id));
+    }
+
+    /// 记录数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否没有任何记录
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// 计算插件配置的来源哈希，名称、版本或来源变化时哈希随之变化
+///
+/// 没有配置的插件（如测试中直接注册的插件）使用空配置计算
+pub fn source_hash(config: Option<&PluginConfig>) -> String {
+    let key = config.map(|config| (&config.name, &config.version, &config.source));
+    let bytes = serde_json::to_vec(&key).unwrap_or_default();
+    format!("{:x}", Sha256::digest(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_invalidated_by_source_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(METADATA_CACHE_FILE);
+
+        let mut config = PluginConfig::new("node");
+        config.version = Some("18.17.0".to_string());
+        let hash = source_hash(Some(&config));
+
+        let mut cache = MetadataCache::default();
+        let metadata = PluginMetadata {
+            name: "node".to_string(),
+            description: "Node.js runtime".to_string(),
+            ..PluginMetadata::default()
+        };
+        cache.insert("node", hash.clone(), metadata.clone());
+        cache.save(&path).await.unwrap();
+
+        let cache = MetadataCache::load(&path).await;
+        assert_eq!(cache.get("node", &hash), Some(&metadata));
+
+        config.version = Some("20.0.0".to_string());
+        assert_eq!(cache.get("node", &source_hash(Some(&config))), None);
+        assert!(MetadataCache::load(&dir.path().join("missing.json"))
+            .await
+            .is_empty());
+    }
+}
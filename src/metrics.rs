@@ -0,0 +1,385 @@
+//! PLM Prometheus 指标（需要以 `--features metrics` 构建）
+//!
+//! 进程级的计数器和直方图，记录安装次数、安装失败次数、下载字节数、安装和下载耗时以及注册表请求延迟。
+//! `PluginManager` 通过生命周期事件记录安装结果，下载器和注册表客户端在请求结束时记录，
+//! 同一进程中的多个管理器共享同一份指标。
+//!
+//! 嵌入 PLM 的宿主应用可以调用 `PluginManager::metrics_snapshot` 读取当前值，或用
+//! `MetricsServer` 在 `GET /metrics` 上提供 Prometheus 文本格式供抓取；
+//! `plm registry serve` 同时启用 `registry-server` 特性时也提供 `/metrics`。
+
+use crate::events::PlmEvent;
+use crate::traits::PluginError;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Prometheus 文本格式的 Content-Type
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 安装和下载耗时的直方图分桶（秒）
+const DURATION_BUCKETS: [f64; 12] = [
+    0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0,
+];
+
+/// 注册表请求延迟的直方图分桶（秒）
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+struct Histogram {
+    bounds: &'static [f64; 12],
+    counts: [u64; 12],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    const fn new(bounds: &'static [f64; 12]) -> Self {
+        Self {
+            bounds,
+            counts: [0; 12],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        if let Some(bucket) = self.bounds.iter().position(|bound| seconds <= *bound) {
+            self.counts[bucket] += 1;
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+
+    fn snapshot(&self) -> HistogramSnapshot {
+        let mut cumulative = 0;
+        let buckets = self
+            .bounds
+            .iter()
+            .zip(self.counts)
+            .map(|(bound, count)| {
+                cumulative += count;
+                (*bound, cumulative)
+            })
+            .collect();
+        HistogramSnapshot {
+            buckets,
+            sum: self.sum,
+            count: self.count,
+        }
+    }
+}
+
+struct Metrics {
+    installs: BTreeMap<String, u64>,
+    install_failures: BTreeMap<String, u64>,
+    download_bytes: u64,
+    install_duration: Histogram,
+    download_duration: Histogram,
+    registry_latency: Histogram,
+}
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics {
+    installs: BTreeMap::new(),
+    install_failures: BTreeMap::new(),
+    download_bytes: 0,
+    install_duration: Histogram::new(&DURATION_BUCKETS),
+    download_duration: Histogram::new(&DURATION_BUCKETS),
+    registry_latency: Histogram::new(&LATENCY_BUCKETS),
+});
+
+fn with_metrics(f: impl FnOnce(&mut Metrics)) {
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut metrics);
+}
+
+/// 直方图的当前值
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HistogramSnapshot {
+    /// 各分桶的上界（秒）和小于等于该上界的累计观测次数
+    pub buckets: Vec<(f64, u64)>,
+    /// 观测值之和（秒）
+    pub sum: f64,
+    /// 观测次数
+    pub count: u64,
+}
+
+/// 指标的当前值
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetricsSnapshot {
+    /// 各插件安装成功的次数
+    pub installs: BTreeMap<String, u64>,
+    /// 各插件安装失败的次数
+    pub install_failures: BTreeMap<String, u64>,
+    /// 下载的总字节数（不含缓存命中）
+    pub download_bytes: u64,
+    /// 安装耗时
+    pub install_duration: HistogramSnapshot,
+    /// 下载耗时
+    pub download_duration: HistogramSnapshot,
+    /// 注册表请求延迟
+    pub registry_latency: HistogramSnapshot,
+}
+
+impl MetricsSnapshot {
+    /// 所有插件安装成功的总次数
+    pub fn installs_total(&self) -> u64 {
+        self.installs.values().sum()
+    }
+
+    /// 所有插件安装失败的总次数
+    pub fn install_failures_total(&self) -> u64 {
+        self.install_failures.values().sum()
+    }
+
+    /// Prometheus 文本格式
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        write_counter(
+            &mut out,
+            "plm_installs_total",
+            "Successful plugin installs",
+            &self.installs,
+        );
+        write_counter(
+            &mut out,
+            "plm_install_failures_total",
+            "Failed plugin installs",
+            &self.install_failures,
+        );
+        let _ = writeln!(
+            out,
+            "# HELP plm_download_bytes_total Bytes downloaded, excluding cache hits\n\
+             # TYPE plm_download_bytes_total counter\n\
+             plm_download_bytes_total {}",
+            self.download_bytes
+        );
+        write_histogram(
+            &mut out,
+            "plm_install_duration_seconds",
+            "Plugin install duration",
+            &self.install_duration,
+        );
+        write_histogram(
+            &mut out,
+            "plm_download_duration_seconds",
+            "Artifact download duration",
+            &self.download_duration,
+        );
+        write_histogram(
+            &mut out,
+            "plm_registry_request_duration_seconds",
+            "Registry request latency",
+            &self.registry_latency,
+        );
+        out
+    }
+}
+
+fn write_counter(out: &mut String, name: &str, help: &str, values: &BTreeMap<String, u64>) {
+    let _ = writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help, name);
+    for (plugin, value) in values {
+        let _ = writeln!(
+            out,
+            "{}{{plugin=\"{}\"}} {}",
+            name,
+            escape_label(plugin),
+            value
+        );
+    }
+}
+
+fn write_histogram(out: &mut String, name: &str, help: &str, histogram: &HistogramSnapshot) {
+    let _ = writeln!(out, "# HELP {} {}\n# TYPE {} histogram", name, help, name);
+    for (bound, count) in &histogram.buckets {
+        let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, count);
+    }
+    let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, histogram.count);
+    let _ = writeln!(out, "{}_sum {}", name, histogram.sum);
+    let _ = writeln!(out, "{}_count {}", name, histogram.count);
+}
+
+/// 转义标签值中的反斜杠、引号和换行
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// 记录一次成功的安装
+pub fn record_install(plugin: &str, duration: Duration) {
+    with_metrics(|metrics| {
+        *metrics.installs.entry(plugin.to_string()).or_default() += 1;
+        metrics.install_duration.observe(duration);
+    });
+}
+
+/// 记录一次失败的安装
+pub fn record_install_failure(plugin: &str) {
+    with_metrics(|metrics| {
+        *metrics
+            .install_failures
+            .entry(plugin.to_string())
+            .or_default() += 1;
+    });
+}
+
+/// 记录一次下载的字节数和耗时
+pub fn record_download(bytes: u64, duration: Duration) {
+    with_metrics(|metrics| {
+        metrics.download_bytes += bytes;
+        metrics.download_duration.observe(duration);
+    });
+}
+
+/// 记录一次注册表请求的延迟
+pub fn record_registry_request(latency: Duration) {
+    with_metrics(|metrics| metrics.registry_latency.observe(latency));
+}
+
+/// 按生命周期事件记录安装结果，由 `PluginManager` 注册为事件监听器
+pub fn record_event(event: &PlmEvent) {
+    match event {
+        PlmEvent::InstallCompleted {
+            plugin, duration, ..
+        } => record_install(plugin, *duration),
+        PlmEvent::InstallFailed { plugin, .. } => record_install_failure(plugin),
+        _ => {}
+    }
+}
+
+/// 当前的指标
+pub fn snapshot() -> MetricsSnapshot {
+    let metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    MetricsSnapshot {
+        installs: metrics.installs.clone(),
+        install_failures: metrics.install_failures.clone(),
+        download_bytes: metrics.download_bytes,
+        install_duration: metrics.install_duration.snapshot(),
+        download_duration: metrics.download_duration.snapshot(),
+        registry_latency: metrics.registry_latency.snapshot(),
+    }
+}
+
+/// 清空已记录的指标
+pub fn reset() {
+    with_metrics(|metrics| {
+        metrics.installs.clear();
+        metrics.install_failures.clear();
+        metrics.download_bytes = 0;
+        metrics.install_duration = Histogram::new(&DURATION_BUCKETS);
+        metrics.download_duration = Histogram::new(&DURATION_BUCKETS);
+        metrics.registry_latency = Histogram::new(&LATENCY_BUCKETS);
+    });
+}
+
+/// `GET /metrics` 的处理函数，可以挂到宿主应用自己的 axum 路由上
+pub async fn handler() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, CONTENT_TYPE)],
+        snapshot().to_prometheus(),
+    )
+}
+
+/// 只提供 `GET /metrics` 的抓取服务，供长时间运行的宿主应用使用
+pub struct MetricsServer;
+
+impl MetricsServer {
+    /// 绑定 `addr`，返回实际监听的地址和运行服务的 future
+    pub fn bind(
+        addr: SocketAddr,
+    ) -> Result<(SocketAddr, impl Future<Output = Result<(), PluginError>>), PluginError> {
+        let listener = std::net::TcpListener::bind(addr)
+            .map_err(|e| PluginError::NetworkError(format!("监听 {} 失败: {}", addr, e)))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| PluginError::NetworkError(format!("监听 {} 失败: {}", addr, e)))?;
+        let router = Router::new().route("/metrics", get(handler));
+        let server = axum::Server::from_tcp(listener)
+            .map_err(|e| PluginError::NetworkError(format!("监听 {} 失败: {}", addr, e)))?
+            .serve(router.into_make_service());
+        Ok((local_addr, async move {
+            server
+                .await
+                .map_err(|e| PluginError::NetworkError(format!("指标服务出错: {}", e)))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let mut histogram = Histogram::new(&LATENCY_BUCKETS);
+        histogram.observe(Duration::from_millis(3));
+        histogram.observe(Duration::from_millis(40));
+        histogram.observe(Duration::from_secs(60));
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count, 3);
+        assert_eq!(snapshot.buckets[0], (0.005, 1));
+        assert_eq!(snapshot.buckets[3], (0.05, 2));
+        // 超过最大分桶的观测值只计入 +Inf
+        assert_eq!(snapshot.buckets.last().unwrap().1, 2);
+        assert!((snapshot.sum - 60.043).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_record_event_and_exposition() {
+        // 指标是进程级的，其他测试可能同时记录，只检查本测试使用的插件和下限
+        record_event(&PlmEvent::InstallCompleted {
+            plugin: "metrics-test".to_string(),
+            version: "1.0.0".to_string(),
+            path: String::new(),
+            duration: Duration::from_millis(200),
+        });
+        record_event(&PlmEvent::InstallFailed {
+            plugin: "metrics-\"test\"".to_string(),
+            version: "1.0.0".to_string(),
+            error: "boom".to_string(),
+        });
+        record_download(1024, Duration::from_millis(50));
+
+        let snapshot = snapshot();
+        assert_eq!(snapshot.installs["metrics-test"], 1);
+        assert!(snapshot.install_failures_total() >= 1);
+        assert!(snapshot.download_bytes >= 1024);
+        assert!(snapshot.install_duration.count >= 1);
+
+        let text = snapshot.to_prometheus();
+        assert!(text.contains("# TYPE plm_installs_total counter"));
+        assert!(text.contains("plm_installs_total{plugin=\"metrics-test\"} 1"));
+        assert!(text.contains("plm_install_failures_total{plugin=\"metrics-\\\"test\\\"\"} 1"));
+        assert!(text.contains("plm_install_duration_seconds_bucket{le=\"+Inf\"}"));
+        assert!(text.contains("# TYPE plm_registry_request_duration_seconds histogram"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_server() {
+        record_registry_request(Duration::from_millis(20));
+        let (addr, server) = MetricsServer::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        tokio::spawn(server);
+        let response = reqwest::get(format!("http://{}/metrics", addr))
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        assert!(response.headers()[reqwest::header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+        let body = response.text().await.unwrap();
+        assert!(body.contains("plm_registry_request_duration_seconds_count"));
+    }
+}
@@ -0,0 +1,133 @@
+//! PLM 配置格式迁移
+//!
+//! 配置文件通过 `config_version` 记录格式版本，没有该字段的文件视为版本 1。
+//! 加载时按顺序执行迁移步骤，把旧格式的 JSON 升级到 `CURRENT_CONFIG_VERSION`，
+//! 迁移在原始 JSON 层面进行，未知字段和环境变量占位符都会原样保留。
+
+use crate::traits::PluginError;
+use serde_json::{Map, Value};
+
+/// 当前配置格式版本
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+
+/// 迁移步骤：(目标版本, 说明, 迁移函数)
+type Migration = (u32, &'static str, fn(&mut Map<String, Value>));
+
+const MIGRATIONS: &[Migration] = &[(
+    2,
+    "convert plugin list to a map keyed by name and rename plugin 'config' to 'settings'",
+    migrate_v1_to_v2,
+)];
+
+/// 读取配置的格式版本，没有 `config_version` 字段时为 1
+pub fn config_version(raw: &Value) -> u32 {
+    raw.get("config_version")
+        .and_then(Value::as_u64)
+        .map_or(1, |v| v as u32)
+}
+
+/// 是否需要迁移
+pub fn needs_migration(raw: &Value) -> bool {
+    config_version(raw) < CURRENT_CONFIG_VERSION
+}
+
+/// 把配置迁移到当前版本，返回执行过的步骤说明
+///
+/// 配置版本比当前版本新时返回错误，提示升级 PLM
+pub fn migrate(raw: &mut Value) -> Result<Vec<String>, PluginError> {
+    let version = config_version(raw);
+    if version > CURRENT_CONFIG_VERSION {
+        return Err(PluginError::ConfigError(format!(
+            "Config version {} is newer than supported version {}; please upgrade plm",
+            version, CURRENT_CONFIG_VERSION
+        )));
+    }
+
+    let root = raw.as_object_mut().ok_or_else(|| {
+        PluginError::ConfigError("Configuration must be a JSON object".to_string())
+    })?;
+
+    let mut applied = Vec::new();
+    for (target, description, step) in MIGRATIONS {
+        if *target <= version {
+            continue;
+        }
+        step(root);
+        root.insert("config_version".to_string(), Value::from(*target));
+        applied.push(format!("v{} -> v{}: {}", target - 1, target, description));
+    }
+    Ok(applied)
+}
+
+fn migrate_v1_to_v2(root: &mut Map<String, Value>) {
+    // 早期格式中插件是数组
+    if let Some(Value::Array(list)) = root.get("plugins") {
+        let mut plugins = Map::new();
+        for plugin in list {
+            if let Some(name) = plugin.get("name").and_then(Value::as_str) {
+                plugins.insert(name.to_string(), plugin.clone());
+            }
+        }
+        root.insert("plugins".to_string(), Value::Object(plugins));
+    }
+
+    let Some(plugins) = root.get_mut("plugins").and_then(Value::as_object_mut) else {
+        return;
+    };
+    for plugin in plugins.values_mut() {
+        let Some(plugin) = plugin.as_object_mut() else {
+            continue;
+        };
+        let Some(Value::Object(legacy)) = plugin.remove("config") else {
+            continue;
+        };
+        let settings = plugin
+            .entry("settings")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Some(settings) = settings.as_object_mut() {
+            for (key, value) in legacy {
+                // 已存在的 settings 项优先
+                settings.entry(key).or_insert(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrate_v1() {
+        let mut raw = json!({
+            "plugins": [
+                { "name": "node", "config": { "registry": "npm", "mirror": "a" }, "settings": { "mirror": "b" } }
+            ],
+            "token": "${GITHUB_TOKEN}"
+        });
+        assert!(needs_migration(&raw));
+
+        let applied = migrate(&mut raw).unwrap();
+        assert_eq!(applied.len(), 1);
+        assert_eq!(config_version(&raw), CURRENT_CONFIG_VERSION);
+        assert_eq!(
+            raw["plugins"]["node"]["settings"],
+            json!({ "registry": "npm", "mirror": "b" })
+        );
+        assert!(raw["plugins"]["node"].get("config").is_none());
+        assert_eq!(raw["token"], "${GITHUB_TOKEN}");
+
+        // 已是当前版本时不做任何修改
+        assert!(migrate(&mut raw).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let mut raw = json!({ "config_version": CURRENT_CONFIG_VERSION + 1 });
+        assert!(matches!(
+            migrate(&mut raw),
+            Err(PluginError::ConfigError(_))
+        ));
+    }
+}
@@ -0,0 +1,535 @@
+//! PLM 注册表镜像
+//!
+//! `plm mirror sync <dir>` 从上游注册表复制选定插件的版本到静态文件布局：
+//!
+//! ```text
+//! <dir>/
+//!   v1/plugins/index.json                  镜像中的全部插件（搜索接口的响应）
+//!   v1/plugins/<name>/index.json           插件信息和最新稳定版本
+//!   v1/plugins/<name>/versions/index.json  全部版本（单页）
+//!   artifacts/<name>/<version>/<file>      制品
+//!   SHA256SUMS                             全部制品的校验和
+//! ```
+//!
+//! 用任意静态文件服务器发布该目录，并把目录请求映射到其中的 `index.json`
+//! （如 nginx 的 `try_files $uri $uri/index.json`），即可作为私有注册表使用：
+//! 把 `registry_url` 或 `Registry` 插件源指向发布地址。静态服务器不支持批量接口，
+//! 客户端会退回到逐个查询；搜索接口返回镜像中的全部插件。
+//!
+//! 镜像中的下载地址改写为 `<base_url>/artifacts/...`，上游地址保留为备用下载地址；
+//! 校验和按镜像中的文件重新计算。重复同步只下载镜像中还没有的版本。
+
+use crate::config::ProjectConfig;
+use crate::core::same_version;
+use crate::download::{file_name_from_url, sha256_file, Downloader};
+use crate::registry::{RegistryClient, RegistryEntry};
+use crate::temp::TempFileGuard;
+use crate::traits::{PluginError, PluginMetadata, VersionInfo, VersionPage};
+use crate::version::Version;
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// 插件接口在镜像目录中的位置
+pub const PLUGINS_DIR: &str = "v1/plugins";
+
+/// 静态文件服务器为目录请求返回的文件
+pub const INDEX_FILE: &str = "index.json";
+
+/// 制品目录名
+pub const ARTIFACTS_DIR: &str = "artifacts";
+
+/// 校验和文件名，格式与 `sha256sum` 输出相同
+pub const CHECKSUMS_FILE: &str = "SHA256SUMS";
+
+/// 从上游分页获取版本时每页的数量
+const VERSIONS_PAGE_SIZE: usize = 100;
+
+/// 要镜像的插件和版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MirrorSpec {
+    pub name: String,
+    /// 未指定时镜像最新的稳定版本
+    pub version: Option<String>,
+}
+
+impl MirrorSpec {
+    /// 解析 `name` 或 `name@version`
+    pub fn parse(spec: &str) -> Result<Self, PluginError> {
+        let (name, version) = match spec.trim().split_once('@') {
+            Some((name, version)) => (name, Some(version.to_string())),
+            None => (spec.trim(), None),
+        };
+        if name.is_empty() || version.as_deref() == Some("") {
+            return Err(PluginError::ValidationError(format!(
+                "无效的镜像插件 {}，应为 name 或 name@version",
+                spec
+            )));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            version,
+        })
+    }
+
+    /// 配置中启用的插件：固定版本的插件镜像该版本，范围约束或未指定版本的插件镜像最新的稳定版本
+    pub fn from_config(config: &ProjectConfig) -> Vec<Self> {
+        let mut specs: Vec<Self> = config
+            .plugins
+            .values()
+            .filter(|plugin| plugin.enabled)
+            .map(|plugin| Self {
+                name: plugin.name.clone(),
+                version: plugin
+                    .get_version()
+                    .filter(|version| Version::parse(version).is_ok())
+                    .map(str::to_string),
+            })
+            .collect();
+        specs.sort_by(|a, b| a.name.cmp(&b.name));
+        specs
+    }
+}
+
+/// `sync` 的结果
+#[derive(Debug, Clone, Default)]
+pub struct MirrorReport {
+    /// 本次复制的插件和版本
+    pub mirrored: Vec<(String, String)>,
+    /// 镜像中已有的插件和版本
+    pub up_to_date: Vec<(String, String)>,
+    /// 失败的插件和错误信息
+    pub failed: Vec<(String, String)>,
+}
+
+impl MirrorReport {
+    /// 是否全部成功
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// 镜像目录
+pub struct Mirror {
+    dir: PathBuf,
+    base_url: String,
+}
+
+impl Mirror {
+    /// `base_url` 为发布镜像目录的地址，写入版本信息的下载地址
+    pub fn new(dir: impl Into<PathBuf>, base_url: &str) -> Self {
+        Self {
+            dir: dir.into(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// 镜像目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 发布镜像目录的地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn plugin_dir(&self, name: &str) -> PathBuf {
+        self.dir.join(PLUGINS_DIR).join(name)
+    }
+
+    fn versions_path(&self, name: &str) -> PathBuf {
+        self.plugin_dir(name).join("versions").join(INDEX_FILE)
+    }
+
+    /// 镜像中插件的全部版本，插件尚未镜像时返回空列表
+    pub async fn load_versions(&self, name: &str) -> Result<Vec<VersionInfo>, PluginError> {
+        let path = self.versions_path(name);
+        if tokio::fs::metadata(&path).await.is_err() {
+            return Ok(Vec::new());
+        }
+        let page: VersionPage = read_json(&path).await?;
+        Ok(page.versions)
+    }
+
+    /// 镜像中的插件信息，插件尚未镜像时返回 None
+    pub async fn load_entry(&self, name: &str) -> Result<Option<RegistryEntry>, PluginError> {
+        let path = self.plugin_dir(name).join(INDEX_FILE);
+        if tokio::fs::metadata(&path).await.is_err() {
+            return Ok(None);
+        }
+        read_json(&path).await.map(Some)
+    }
+
+    /// 镜像中的全部插件（`write_index` 生成的插件列表），尚未生成时返回空列表
+    pub async fn load_entries(&self) -> Result<Vec<RegistryEntry>, PluginError> {
+        #[derive(serde::Deserialize)]
+        struct PluginList {
+            plugins: Vec<RegistryEntry>,
+        }
+
+        let path = self.dir.join(PLUGINS_DIR).join(INDEX_FILE);
+        if tokio::fs::metadata(&path).await.is_err() {
+            return Ok(Vec::new());
+        }
+        let list: PluginList = read_json(&path).await?;
+        Ok(list.plugins)
+    }
+
+    /// 镜像中是否已有该版本且制品文件存在
+    pub async fn contains(&self, versions: &[VersionInfo], info: &VersionInfo) -> bool {
+        match find_version(versions, info) {
+            Some(mirrored) => match self.artifact_path(&mirrored.download_url) {
+                Some(path) => tokio::fs::metadata(path).await.is_ok(),
+                None => false,
+            },
+            None => false,
+        }
+    }
+
+    /// 镜像下载地址对应的本地文件，不是本镜像的地址时返回 None
+    fn artifact_path(&self, url: &str) -> Option<PathBuf> {
+        let relative = url.strip_prefix(&self.base_url)?.trim_start_matches('/');
+        relative
+            .starts_with(ARTIFACTS_DIR)
+            .then(|| self.dir.join(relative))
+    }
+
+    /// 把已下载并校验通过的制品复制到镜像，返回改写了下载地址和校验和的版本信息
+    pub async fn add_artifact(
+        &self,
+        name: &str,
+        info: &VersionInfo,
+        path: &Path,
+    ) -> Result<VersionInfo, PluginError> {
+        let file = file_name_from_url(&info.download_url)?;
+        let relative = format!("{}/{}/{}/{}", ARTIFACTS_DIR, name, info.version, file);
+        let target = self.dir.join(&relative);
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                PluginError::IoError(format!("创建 {} 失败: {}", parent.display(), e))
+            })?;
+        }
+        let staging = TempFileGuard::sibling(&target, "part");
+        tokio::fs::copy(path, staging.path())
+            .await
+            .map_err(|e| PluginError::IoError(format!("复制 {} 失败: {}", path.display(), e)))?;
+        staging.persist(&target).await?;
+
+        let mut mirrored = info.clone();
+        mirrored.download_url = format!("{}/{}", self.base_url, relative);
+        mirrored.mirror_urls = info
+            .urls()
+            .filter(|url| *url != mirrored.download_url)
+            .map(str::to_string)
+            .collect();
+        mirrored.checksum = Some(sha256_file(&target).await?);
+        mirrored.size = Some(
+            tokio::fs::metadata(&target)
+                .await
+                .map_err(|e| {
+                    PluginError::IoError(format!("读取 {} 失败: {}", target.display(), e))
+                })?
+                .len(),
+        );
+        Ok(mirrored)
+    }
+
+    /// 写入插件信息和版本列表，版本按从新到旧排列
+    pub async fn write_plugin(
+        &self,
+        metadata: &PluginMetadata,
+        mut versions: Vec<VersionInfo>,
+    ) -> Result<(), PluginError> {
+        versions.sort_by(|a, b| {
+            parse_version(&b.version)
+                .cmp(&parse_version(&a.version))
+                .then_with(|| a.platform.cmp(&b.platform))
+        });
+        let latest = versions
+            .iter()
+            .find(|info| !info.prerelease && !info.yanked)
+            .cloned();
+
+        let dir = self.plugin_dir(&metadata.name);
+        write_json(
+            &dir.join(INDEX_FILE),
+            &RegistryEntry {
+                metadata: metadata.clone(),
+                latest,
+            },
+        )
+        .await?;
+        write_json(
+            &self.versions_path(&metadata.name),
+            &VersionPage {
+                versions,
+                next_cursor: None,
+            },
+        )
+        .await
+    }
+
+    /// 按各插件的信息和版本列表重新生成插件列表和 `SHA256SUMS`
+    pub async fn write_index(&self) -> Result<(), PluginError> {
+        let root = self.dir.join(PLUGINS_DIR);
+        let mut names = Vec::new();
+        if let Ok(mut entries) = tokio::fs::read_dir(&root).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                if entry.path().join(INDEX_FILE).is_file() {
+                    names.push(entry.file_name().to_string_lossy().into_owned());
+                }
+            }
+        }
+        names.sort();
+
+        let mut plugins: Vec<RegistryEntry> = Vec::new();
+        let mut checksums = Vec::new();
+        for name in &names {
+            plugins.push(read_json(&self.plugin_dir(name).join(INDEX_FILE)).await?);
+            for info in self.load_versions(name).await? {
+                let (Some(checksum), Some(path)) =
+                    (&info.checksum, self.artifact_path(&info.download_url))
+                else {
+                    continue;
+                };
+                let relative = path.strip_prefix(&self.dir).unwrap_or(&path);
+                checksums.push(format!("{}  {}", checksum, relative.display()));
+            }
+        }
+        checksums.sort();
+        checksums.dedup();
+
+        write_json(
+            &root.join(INDEX_FILE),
+            &serde_json::json!({ "plugins": plugins }),
+        )
+        .await?;
+        let mut content = checksums.join("\n");
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        tokio::fs::write(self.dir.join(CHECKSUMS_FILE), content)
+            .await
+            .map_err(|e| PluginError::IoError(format!("写入 {} 失败: {}", CHECKSUMS_FILE, e)))
+    }
+}
+
+/// 从上游注册表复制 `specs` 中的插件版本（所有平台的制品）到镜像目录
+///
+/// `upstream` 未指定时使用全局设置中的 `registry_url`。单个插件失败不会中断其他插件，
+/// 插件列表和校验和在最后统一重新生成
+pub async fn sync(
+    config: &ProjectConfig,
+    specs: &[MirrorSpec],
+    mirror: &Mirror,
+    upstream: Option<&str>,
+) -> Result<MirrorReport, PluginError> {
+    let settings = &config.global_settings;
+    let mut client = RegistryClient::from_settings(settings)?;
+    if let Some(upstream) = upstream {
+        client = client.with_base_url(upstream);
+    }
+    // 上游是配置中的插件源时使用其凭据
+    let mut downloader = Downloader::from_settings(settings)?;
+    if let Some(source) = config
+        .sources
+        .iter()
+        .find(|source| source.url.trim_end_matches('/') == client.base_url())
+    {
+        let credentials = source.resolve_auth().await?;
+        client = client.with_credentials(credentials.clone());
+        downloader = downloader.with_source_credentials(source, credentials);
+    }
+
+    let mut report = MirrorReport::default();
+    for spec in specs {
+        if let Err(e) = sync_plugin(&client, &downloader, mirror, spec, &mut report).await {
+            let version = spec.version.as_deref().unwrap_or("latest");
+            report
+                .failed
+                .push((format!("{}@{}", spec.name, version), e.to_string()));
+        }
+    }
+    mirror.write_index().await?;
+    Ok(report)
+}
+
+async fn sync_plugin(
+    client: &RegistryClient,
+    downloader: &Downloader,
+    mirror: &Mirror,
+    spec: &MirrorSpec,
+    report: &mut MirrorReport,
+) -> Result<(), PluginError> {
+    let entry = client.fetch_plugin(&spec.name).await?;
+    let version = match (&spec.version, &entry.latest) {
+        (Some(version), _) => version.clone(),
+        (None, Some(latest)) => latest.version.clone(),
+        (None, None) => return Err(PluginError::NotFound(format!("{} 没有稳定版本", spec.name))),
+    };
+
+    let mut artifacts = Vec::new();
+    let mut stream = Box::pin(client.versions_stream(&spec.name, VERSIONS_PAGE_SIZE));
+    while let Some(info) = stream.next().await {
+        let info = info?;
+        if same_version(&info.version, &version) {
+            artifacts.push(info);
+        }
+    }
+    if artifacts.is_empty() {
+        return Err(PluginError::NotFound(format!(
+            "上游注册表中没有 {} {}",
+            spec.name, version
+        )));
+    }
+
+    let mut versions = mirror.load_versions(&spec.name).await?;
+    let mut copied = false;
+    for info in &artifacts {
+        if mirror.contains(&versions, info).await {
+            continue;
+        }
+        let outcome = downloader.fetch(info).await?;
+        let mirrored = mirror.add_artifact(&spec.name, info, &outcome.path).await?;
+        versions.retain(|existing| find_version(std::slice::from_ref(existing), info).is_none());
+        versions.push(mirrored);
+        copied = true;
+    }
+    mirror.write_plugin(&entry.metadata, versions).await?;
+
+    let result = (spec.name.clone(), version);
+    if copied {
+        report.mirrored.push(result);
+    } else {
+        report.up_to_date.push(result);
+    }
+    Ok(())
+}
+
+/// 版本列表中与 `info` 版本和平台都相同的记录
+fn find_version<'a>(versions: &'a [VersionInfo], info: &VersionInfo) -> Option<&'a VersionInfo> {
+    versions.iter().find(|existing| {
+        existing.platform == info.platform && same_version(&existing.version, &info.version)
+    })
+}
+
+/// 无法解析的版本号排在最后
+fn parse_version(version: &str) -> Option<Version> {
+    Version::parse(version).ok()
+}
+
+async fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, PluginError> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e)))?;
+    serde_json::from_str(&content).map_err(|e| {
+        PluginError::ValidationError(format!("无效的镜像文件 {}: {}", path.display(), e))
+    })
+}
+
+async fn write_json(path: &Path, value: &impl Serialize) -> Result<(), PluginError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| PluginError::IoError(format!("创建 {} 失败: {}", parent.display(), e)))?;
+    }
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| PluginError::IoError(format!("序列化 {} 失败: {}", path.display(), e)))?;
+    tokio::fs::write(path, content)
+        .await
+        .map_err(|e| PluginError::IoError(format!("写入 {} 失败: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Deserialize)]
+    struct PluginList {
+        plugins: Vec<RegistryEntry>,
+    }
+
+    #[test]
+    fn test_parse_mirror_spec() {
+        assert_eq!(
+            MirrorSpec::parse("node@20.11.0").unwrap(),
+            MirrorSpec {
+                name: "node".to_string(),
+                version: Some("20.11.0".to_string()),
+            }
+        );
+        assert_eq!(MirrorSpec::parse("jq").unwrap().version, None);
+        assert!(MirrorSpec::parse("@1.0.0").is_err());
+        assert!(MirrorSpec::parse("jq@").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mirror_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let downloaded = dir.path().join("tool.tar.gz");
+        std::fs::write(&downloaded, "artifact").unwrap();
+        let mirror = Mirror::new(dir.path().join("mirror"), "https://mirror.internal/plm/");
+
+        let metadata = PluginMetadata::builder("tool")
+            .version("1.10.0")
+            .description("Mirrored tool")
+            .author("PLM")
+            .build()
+            .unwrap();
+        let mut versions = Vec::new();
+        for (version, prerelease) in [("1.9.0", false), ("1.10.0", false), ("2.0.0-rc.1", true)] {
+            let mut info = VersionInfo::new(
+                version,
+                "linux-x64",
+                &format!("https://example.com/{}/tool.tar.gz", version),
+            );
+            info.prerelease = prerelease;
+            assert!(!mirror.contains(&versions, &info).await);
+            versions.push(
+                mirror
+                    .add_artifact("tool", &info, &downloaded)
+                    .await
+                    .unwrap(),
+            );
+            assert!(mirror.contains(&versions, &info).await);
+        }
+
+        let mirrored = versions[0].clone();
+        assert_eq!(
+            mirrored.download_url,
+            "https://mirror.internal/plm/artifacts/tool/1.9.0/tool.tar.gz"
+        );
+        assert_eq!(
+            mirrored.mirror_urls,
+            ["https://example.com/1.9.0/tool.tar.gz"]
+        );
+        assert_eq!(mirrored.size, Some(8));
+
+        mirror.write_plugin(&metadata, versions).await.unwrap();
+        mirror.write_index().await.unwrap();
+
+        let plugin_dir = mirror.dir().join(PLUGINS_DIR).join("tool");
+        let entry: RegistryEntry = read_json(&plugin_dir.join(INDEX_FILE)).await.unwrap();
+        assert_eq!(entry.latest.unwrap().version, "1.10.0");
+        let order: Vec<String> = mirror
+            .load_versions("tool")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|info| info.version)
+            .collect();
+        assert_eq!(order, ["2.0.0-rc.1", "1.10.0", "1.9.0"]);
+
+        let list: PluginList = read_json(&mirror.dir().join(PLUGINS_DIR).join(INDEX_FILE))
+            .await
+            .unwrap();
+        assert_eq!(list.plugins.len(), 1);
+        let checksums = std::fs::read_to_string(mirror.dir().join(CHECKSUMS_FILE)).unwrap();
+        assert_eq!(checksums.lines().count(), 3);
+        assert!(checksums.contains(&format!(
+            "{}  artifacts/tool/1.9.0/tool.tar.gz",
+            mirrored.checksum.as_deref().unwrap()
+        )));
+    }
+}
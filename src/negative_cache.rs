@@ -0,0 +1,124 @@
+//! PLM 注册表负缓存
+//!
+//! 记录注册表中不存在的插件，在 `negative_cache_ttl` 秒内不再重复查询，
+//! 避免拼写错误或 CI 循环反复请求注册表。每条记录带有来源标识（注册表地址），
+//! 切换注册表后记录自动失效；`--refresh` 可跳过缓存强制重新查询。
+
+use crate::traits::PluginError;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// 缓存文件名，位于 `cache_dir` 下
+pub const NEGATIVE_CACHE_FILE: &str = "registry-missing.json";
+
+/// 单个插件的缓存记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingEntry {
+    pub source: String,
+    pub cached_at: DateTime<Utc>,
+}
+
+/// 注册表负缓存，键为插件名称
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NegativeCache {
+    #[serde(default)]
+    entries: BTreeMap<String, MissingEntry>,
+}
+
+impl NegativeCache {
+    /// 读取缓存，文件不存在或损坏时返回空缓存
+    pub async fn load(path: &Path) -> Self {
+        tokio::fs::read_to_string(path)
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 写入缓存
+    pub async fn save(&self, path: &Path) -> Result<(), PluginError> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| PluginError::IoError(format!("创建缓存目录失败: {}", e)))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| PluginError::IoError(format!("序列化负缓存失败: {}", e)))?;
+        tokio::fs::write(path, content)
+            .await
+            .map_err(|e| PluginError::IoError(format!("写入负缓存失败: {}", e)))
+    }
+
+    /// 插件是否在有效期内被记录为不存在，来源不一致或已过期时返回 `false`
+    pub fn is_missing(&self, name: &str, source: &str, ttl: u64) -> bool {
+        self.entries.get(name).is_some_and(|entry| {
+            entry.source == source && Utc::now() - entry.cached_at < ttl_duration(ttl)
+        })
+    }
+
+    /// 记录插件不存在
+    pub fn record_missing(&mut self, name: &str, source: &str) {
+        self.entries.insert(
+            name.to_string(),
+            MissingEntry {
+                source: source.to_string(),
+                cached_at: Utc::now(),
+            },
+        );
+    }
+
+    /// 删除插件记录（插件已能查到时调用），返回是否存在该记录
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+
+    /// 删除过期的记录
+    pub fn prune(&mut self, ttl: u64) {
+        let now = Utc::now();
+        self.entries
+            .retain(|_, entry| now - entry.cached_at < ttl_duration(ttl));
+    }
+
+    /// 记录数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否没有任何记录
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn ttl_duration(ttl: u64) -> Duration {
+    Duration::seconds(i64::try_from(ttl).unwrap_or(i64::MAX / 1000))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_expiry_and_source_invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(NEGATIVE_CACHE_FILE);
+
+        let mut cache = NegativeCache::default();
+        cache.record_missing("nodee", "https://registry.plm.dev");
+        cache.save(&path).await.unwrap();
+
+        let mut cache = NegativeCache::load(&path).await;
+        assert!(cache.is_missing("nodee", "https://registry.plm.dev", 300));
+        assert!(!cache.is_missing("nodee", "https://mirror.example.com", 300));
+        assert!(!cache.is_missing("nodee", "https://registry.plm.dev", 0));
+        assert!(!cache.is_missing("node", "https://registry.plm.dev", 300));
+
+        cache.prune(0);
+        assert!(cache.is_empty());
+        assert!(NegativeCache::load(&dir.path().join("missing.json"))
+            .await
+            .is_empty());
+    }
+}
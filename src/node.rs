@@ -0,0 +1,398 @@
+//! PLM 内置 Node.js 插件
+//!
+//! 通过 nvm 管理 Node.js 版本，并用 corepack 准备 `package.json` 中
+//! `packageManager` 字段固定的包管理器（pnpm、yarn 或 npm），
+//! 让 JS 项目只依赖 `plm sync` 即可复现完整的工具环境：
+//!
+//! ```json
+//! { "name": "node", "version": "20.11.1", "source": { "type": "builtin", "url": "node" } }
+//! ```
+//!
+//! 安装 Node.js 后执行 `corepack enable <name>` 和 `corepack prepare <name>@<version> --activate`，
+//! 带哈希的声明（`pnpm@8.15.4+sha256.…`）由 corepack 校验。
+//! 设置 `"corepack": false` 时只管理 Node.js 本身。
+
+use crate::check::extract_version;
+use crate::config::{current_platform, PluginConfig};
+use crate::delegate::{DelegatePlugin, VersionManager};
+use crate::project_files::find_upwards;
+use crate::system::run_command;
+use crate::traits::{
+    Capability, ExecOutcome, InstallOptions, Plugin, PluginError, PluginMetadata, PluginStatus,
+    VersionInfo,
+};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 内置插件源中 Node.js 插件的名称（`"source": { "type": "builtin", "url": "node" }`）
+pub const BUILTIN_NODE: &str = "node";
+
+/// corepack 支持的包管理器
+pub const PACKAGE_MANAGERS: [&str; 3] = ["npm", "pnpm", "yarn"];
+
+/// `package.json` 中 `packageManager` 字段的声明，如 `pnpm@8.15.4+sha256.abc…`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageManagerSpec {
+    pub name: String,
+    pub version: String,
+    /// 完整性哈希（`sha256.abc…`）
+    pub hash: Option<String>,
+}
+
+impl PackageManagerSpec {
+    /// 解析 `packageManager` 字段
+    pub fn parse(value: &str) -> Result<Self, PluginError> {
+        let invalid = || {
+            PluginError::ConfigError(format!(
+                "Invalid packageManager '{}' (expected <name>@<version>)",
+                value
+            ))
+        };
+        let (name, rest) = value.trim().split_once('@').ok_or_else(invalid)?;
+        if !PACKAGE_MANAGERS.contains(&name) {
+            return Err(PluginError::ConfigError(format!(
+                "Unsupported packageManager '{}' (expected npm, pnpm or yarn)",
+                name
+            )));
+        }
+        let (version, hash) = match rest.split_once('+') {
+            Some((version, hash)) => (version, Some(hash.to_string())),
+            None => (rest, None),
+        };
+        if version.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            hash,
+        })
+    }
+
+    /// 读取 `package.json` 内容中的 `packageManager`，未声明时返回 None
+    pub fn from_package_json(content: &str) -> Result<Option<Self>, PluginError> {
+        let manifest: serde_json::Value = serde_json::from_str(content)
+            .map_err(|e| PluginError::ConfigError(format!("解析 package.json 失败: {}", e)))?;
+        manifest["packageManager"]
+            .as_str()
+            .map(Self::parse)
+            .transpose()
+    }
+
+    /// 通过 node 运行 corepack 的命令：启用包管理器的 shim，并激活固定版本
+    pub fn corepack_commands(&self, node: &Path) -> [Vec<String>; 2] {
+        let node = node.to_string_lossy().into_owned();
+        let corepack = corepack_path(Path::new(&node))
+            .to_string_lossy()
+            .into_owned();
+        [
+            vec![
+                node.clone(),
+                corepack.clone(),
+                "enable".to_string(),
+                self.name.clone(),
+            ],
+            vec![
+                node,
+                corepack,
+                "prepare".to_string(),
+                self.to_string(),
+                "--activate".to_string(),
+            ],
+        ]
+    }
+}
+
+impl fmt::Display for PackageManagerSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.name, self.version)?;
+        if let Some(hash) = &self.hash {
+            write!(f, "+{}", hash)?;
+        }
+        Ok(())
+    }
+}
+
+/// 与 node 可执行文件同目录的 corepack 脚本
+fn corepack_path(node: &Path) -> PathBuf {
+    node.with_file_name("corepack")
+}
+
+/// 读取 `start` 所在目录或上级目录中 `package.json` 的 `packageManager`
+pub async fn read_package_manager(start: &Path) -> Result<Option<PackageManagerSpec>, PluginError> {
+    let Some(path) = find_upwards(start, &["package.json"]) else {
+        return Ok(None);
+    };
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e)))?;
+    PackageManagerSpec::from_package_json(&content)
+}
+
+/// 通过 nvm 管理 Node.js 并用 corepack 准备包管理器的内置插件
+pub struct NodePlugin {
+    name: String,
+    project_root: PathBuf,
+    /// 是否准备 `packageManager` 中的包管理器
+    corepack: bool,
+    status: PluginStatus,
+    inner: DelegatePlugin,
+}
+
+impl NodePlugin {
+    /// 从插件配置创建，`project_root` 用于查找 `package.json`
+    pub fn from_config(config: &PluginConfig, project_root: &Path) -> Self {
+        let settings = config.effective_settings_for(current_platform());
+        Self {
+            name: config.name.clone(),
+            project_root: project_root.to_path_buf(),
+            corepack: settings
+                .get("corepack")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+            status: PluginStatus::Inactive,
+            inner: DelegatePlugin::new(&config.name, VersionManager::Nvm),
+        }
+    }
+
+    /// 项目声明的包管理器，禁用 corepack 时返回 None
+    pub async fn package_manager(&self) -> Result<Option<PackageManagerSpec>, PluginError> {
+        if !self.corepack {
+            return Ok(None);
+        }
+        read_package_manager(&self.project_root).await
+    }
+
+    /// 指定版本的 node 可执行文件路径
+    async fn node_binary(&self, version: &str) -> Result<PathBuf, PluginError> {
+        let nvm = VersionManager::Nvm;
+        let output = run_command(&nvm.command(&nvm.prefix_args(version)), None).await?;
+        Ok(PathBuf::from(output.trim()))
+    }
+
+    /// 为指定的 node 启用并激活项目的包管理器
+    async fn provision(
+        &self,
+        node: &Path,
+        options: Option<&InstallOptions>,
+    ) -> Result<(), PluginError> {
+        let Some(spec) = self.package_manager().await? else {
+            return Ok(());
+        };
+        for command in spec.corepack_commands(node) {
+            run_command(&command, options).await?;
+        }
+        Ok(())
+    }
+
+    /// 包管理器的当前版本是否与项目声明一致
+    async fn package_manager_ready(&self, node: &Path) -> Result<bool, PluginError> {
+        let Some(spec) = self.package_manager().await? else {
+            return Ok(true);
+        };
+        let command = [
+            node.to_string_lossy().into_owned(),
+            node.with_file_name(&spec.name)
+                .to_string_lossy()
+                .into_owned(),
+            "--version".to_string(),
+        ];
+        Ok(match run_command(&command, None).await {
+            Ok(output) => extract_version(&output).is_some_and(|v| v == spec.version),
+            Err(_) => false,
+        })
+    }
+}
+
+#[async_trait]
+impl Plugin for NodePlugin {
+    fn metadata(&self) -> PluginMetadata {
+        PluginMetadata {
+            name: self.name.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            description: "Node.js managed by nvm, with the project's package manager via corepack"
+                .to_string(),
+            homepage: Some("https://nodejs.org".to_string()),
+            tags: vec!["builtin".to_string(), BUILTIN_NODE.to_string()],
+            ..PluginMetadata::default()
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        self.inner.initialize().await?;
+        self.status = PluginStatus::Active;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Inactive;
+        Ok(())
+    }
+
+    /// 安装 Node.js 后准备包管理器，返回 node 可执行文件路径
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        let node = self.inner.install(version, options).await?;
+        self.provision(Path::new(&node), Some(options)).await?;
+        Ok(node)
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        self.inner.uninstall(version).await
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        self.inner.list_versions().await
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        self.inner.list_installed().await
+    }
+
+    /// Node.js 已安装且包管理器版本与 `packageManager` 一致时才视为已安装，
+    /// 修改 `packageManager` 后 `plm sync` 会重新准备包管理器
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        if !self.inner.is_installed(version).await? {
+            return Ok(false);
+        }
+        let node = self.node_binary(version).await?;
+        self.package_manager_ready(&node).await
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        self.inner.get_latest_version().await
+    }
+
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        let version = self.inner.update(version).await?;
+        let node = self.node_binary(&version).await?;
+        self.provision(&node, None).await?;
+        Ok(version)
+    }
+
+    async fn switch_version(&self, version: &str) -> Result<(), PluginError> {
+        self.inner.switch_version(version).await
+    }
+
+    async fn verify_installation(&self, version: &str) -> Result<bool, PluginError> {
+        self.is_installed(version).await
+    }
+
+    async fn cleanup(&self) -> Result<(), PluginError> {
+        Ok(())
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        self.inner.get_config().await
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        self.inner.set_config(config).await
+    }
+
+    async fn get_config_value(&self, key: &str) -> Result<Option<String>, PluginError> {
+        self.inner.get_config_value(key).await
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        self.inner.set_config_value(key, value).await
+    }
+
+    async fn execute_command(&self, command: &str, args: &[&str]) -> Result<String, PluginError> {
+        self.inner.execute_command(command, args).await
+    }
+
+    async fn exec_command(&self, command: &str, args: &[&str]) -> Result<ExecOutcome, PluginError> {
+        self.inner.exec_command(command, args).await
+    }
+
+    fn get_help(&self) -> String {
+        "Manages Node.js with nvm and activates the package manager pinned in \
+         package.json's packageManager field through corepack."
+            .to_string()
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::SwitchVersion,
+            Capability::ListVersions,
+            Capability::Exec,
+            Capability::Config,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_package_manager() {
+        let spec = PackageManagerSpec::parse("pnpm@8.15.4+sha256.abc123").unwrap();
+        assert_eq!(spec.name, "pnpm");
+        assert_eq!(spec.version, "8.15.4");
+        assert_eq!(spec.hash.as_deref(), Some("sha256.abc123"));
+        assert_eq!(spec.to_string(), "pnpm@8.15.4+sha256.abc123");
+
+        assert_eq!(
+            PackageManagerSpec::parse("yarn@4.1.0").unwrap().to_string(),
+            "yarn@4.1.0"
+        );
+        assert!(PackageManagerSpec::parse("bun@1.0.0").is_err());
+        assert!(PackageManagerSpec::parse("pnpm").is_err());
+        assert!(PackageManagerSpec::parse("pnpm@").is_err());
+    }
+
+    #[test]
+    fn test_corepack_commands() {
+        let spec = PackageManagerSpec::parse("pnpm@8.15.4").unwrap();
+        let [enable, prepare] = spec.corepack_commands(Path::new("/nvm/v20.11.1/bin/node"));
+        assert_eq!(
+            enable,
+            [
+                "/nvm/v20.11.1/bin/node",
+                "/nvm/v20.11.1/bin/corepack",
+                "enable",
+                "pnpm"
+            ]
+        );
+        assert_eq!(&prepare[2..], ["prepare", "pnpm@8.15.4", "--activate"]);
+    }
+
+    #[tokio::test]
+    async fn test_package_manager_from_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("packages/web");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let mut config = PluginConfig::new("node");
+        let plugin = NodePlugin::from_config(&config, &nested);
+        assert_eq!(plugin.package_manager().await.unwrap(), None);
+
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{ "name": "app", "packageManager": "pnpm@8.15.4" }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            plugin.package_manager().await.unwrap(),
+            Some(PackageManagerSpec::parse("pnpm@8.15.4").unwrap())
+        );
+
+        config.set_setting("corepack", serde_json::json!(false));
+        let plugin = NodePlugin::from_config(&config, &nested);
+        assert_eq!(plugin.package_manager().await.unwrap(), None);
+    }
+}
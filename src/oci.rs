@@ -0,0 +1,1057 @@
+//! PLM OCI 注册表插件源
+//!
+//! 插件包可以作为 OCI 制品发布到 ghcr.io、ECR、Harbor 等容器注册表，复用已有的注册表和认证：
+//!
+//! ```json
+//! "plugins": {
+//!   "tool": { "source": { "type": "oci", "url": "oci://ghcr.io/example/tool" }, ... }
+//! }
+//! ```
+//!
+//! 仓库的标签即版本（`1.2.0` 或 `v1.2.0`），无法解析为版本号的标签（如 `latest`）被忽略。
+//! 标签指向的清单可以是：
+//!
+//! - 多平台索引：按 `platform.os`/`platform.architecture` 选择当前平台的清单
+//! - 单个清单：每一层是一个制品，层注解 `io.plm.platform`（如 `linux-x64`）指定平台，
+//!   没有该注解时使用第一层
+//!
+//! 例如用 oras 发布：
+//!
+//! ```text
+//! oras push ghcr.io/example/tool:1.2.0 \
+//!   --annotation-file annotations.json \
+//!   tool-linux-x64.tar.gz tool-darwin-arm64.tar.gz
+//! ```
+//!
+//! 层注解 `org.opencontainers.image.title` 是制品文件名，用于判断压缩格式；
+//! `io.plm.entry-points` 以逗号分隔列出制品中的可执行文件。制品的 SHA-256 即层摘要。
+//!
+//! 凭据按以下顺序确定：插件源的 `auth`/`token`（见 `crate::auth`），
+//! 然后是 `docker login` 保存的凭据（`$DOCKER_CONFIG/config.json`，默认 `~/.docker/config.json`，
+//! 包括 `credHelpers`/`credsStore` 凭据助手，如 ECR 的 `docker-credential-ecr-login`）。
+//! 注册表要求令牌认证时，用这些凭据向 `WWW-Authenticate` 指定的服务换取拉取令牌。
+
+use crate::auth::Credentials;
+use crate::config::{current_platform, GlobalSettings, PluginConfig, PluginSource};
+use crate::core::same_version;
+use crate::download::{build_client, Downloader};
+use crate::permissions;
+use crate::providers::Secret;
+use crate::target::{canonical_arch, TargetPlatform};
+use crate::traits::{
+    canonical_platform, ArchiveFormat, Capability, InstallOptions, Plugin, PluginError,
+    PluginMetadata, PluginStatus, VersionInfo,
+};
+use crate::version::Version;
+use async_trait::async_trait;
+use base64::Engine;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+/// 插件源地址前缀
+pub const OCI_SCHEME: &str = "oci://";
+
+/// 指定层平台的注解
+pub const PLATFORM_ANNOTATION: &str = "io.plm.platform";
+
+/// 列出层中可执行文件的注解
+pub const ENTRY_POINTS_ANNOTATION: &str = "io.plm.entry-points";
+
+/// 层的文件名注解
+const TITLE_ANNOTATION: &str = "org.opencontainers.image.title";
+
+/// 请求清单时接受的媒体类型
+const MANIFEST_ACCEPT: &str = "application/vnd.oci.image.index.v1+json, \
+     application/vnd.oci.image.manifest.v1+json, \
+     application/vnd.docker.distribution.manifest.list.v2+json, \
+     application/vnd.docker.distribution.manifest.v2+json";
+
+/// Docker Hub 的 API 地址和 `docker login` 使用的键
+const DOCKER_HUB: &str = "docker.io";
+const DOCKER_HUB_API: &str = "registry-1.docker.io";
+const DOCKER_HUB_AUTH_KEY: &str = "https://index.docker.io/v1/";
+
+/// `oci://<registry>/<repository>` 形式的仓库地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OciReference {
+    /// 注册表主机（可带端口），如 `ghcr.io`
+    pub registry: String,
+    /// 仓库路径，如 `example/tool`
+    pub repository: String,
+}
+
+impl OciReference {
+    /// 解析插件源地址，`oci://` 前缀可省略
+    pub fn parse(url: &str) -> Result<Self, PluginError> {
+        let rest = url.trim().strip_prefix(OCI_SCHEME).unwrap_or(url.trim());
+        let invalid = || {
+            PluginError::ConfigError(format!(
+                "无效的 OCI 仓库地址 '{}'，应为 oci://<registry>/<repository>",
+                url
+            ))
+        };
+        let (registry, repository) = rest
+            .trim_end_matches('/')
+            .split_once('/')
+            .ok_or_else(invalid)?;
+        // 标签和摘要由插件版本决定，不能写在地址中
+        if registry.is_empty() || repository.is_empty() || repository.contains([':', '@']) {
+            return Err(invalid());
+        }
+        let repository = if registry == DOCKER_HUB && !repository.contains('/') {
+            format!("library/{}", repository)
+        } else {
+            repository.to_string()
+        };
+        Ok(Self {
+            registry: registry.to_ascii_lowercase(),
+            repository,
+        })
+    }
+
+    /// 注册表 API 地址，本机注册表使用 HTTP
+    pub fn api_base(&self) -> String {
+        let host = self.registry.split(':').next().unwrap_or_default();
+        if self.registry == DOCKER_HUB {
+            format!("https://{}", DOCKER_HUB_API)
+        } else if matches!(host, "localhost" | "127.0.0.1" | "[::1]") {
+            format!("http://{}", self.registry)
+        } else {
+            format!("https://{}", self.registry)
+        }
+    }
+
+    /// 仓库下的 API 地址，如 `tags/list`、`blobs/<digest>`
+    pub fn api_url(&self, path: &str) -> String {
+        format!("{}/v2/{}/{}", self.api_base(), self.repository, path)
+    }
+}
+
+/// 清单或索引
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    #[serde(default)]
+    media_type: Option<String>,
+    /// 索引中的各平台清单
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+    /// 清单中的层
+    #[serde(default)]
+    layers: Vec<Descriptor>,
+}
+
+/// 内容描述符
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    #[serde(default)]
+    media_type: String,
+    digest: String,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
+    annotations: HashMap<String, String>,
+    #[serde(default)]
+    platform: Option<DescriptorPlatform>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct DescriptorPlatform {
+    os: String,
+    architecture: String,
+}
+
+impl Manifest {
+    fn is_index(&self) -> bool {
+        !self.manifests.is_empty()
+            || self
+                .media_type
+                .as_deref()
+                .is_some_and(|t| t.contains("index") || t.contains("manifest.list"))
+    }
+
+    /// 索引中目标平台的清单
+    fn select_manifest(&self, target: &TargetPlatform) -> Option<&Descriptor> {
+        self.manifests.iter().find(|descriptor| {
+            descriptor.platform.as_ref().is_some_and(|platform| {
+                canonical_platform(&platform.os) == Some(target.os.as_str())
+                    && canonical_arch(&platform.architecture) == Some(target.arch.as_str())
+            })
+        })
+    }
+
+    /// 目标平台的层：优先按平台注解选择，所有层都没有平台注解时使用第一层
+    fn select_layer(&self, target: &TargetPlatform) -> Option<&Descriptor> {
+        let annotated: Vec<&Descriptor> = self
+            .layers
+            .iter()
+            .filter(|layer| layer.annotations.contains_key(PLATFORM_ANNOTATION))
+            .collect();
+        if annotated.is_empty() {
+            return self.layers.first();
+        }
+        annotated.into_iter().find(|layer| {
+            VersionInfo::new("", &layer.annotations[PLATFORM_ANNOTATION], "")
+                .supports_target(target)
+        })
+    }
+}
+
+impl Descriptor {
+    /// 层的压缩格式，优先根据文件名注解判断
+    fn archive_format(&self) -> ArchiveFormat {
+        if let Some(title) = self.annotations.get(TITLE_ANNOTATION) {
+            return ArchiveFormat::from_file_name(title);
+        }
+        let media_type = self.media_type.to_ascii_lowercase();
+        if media_type.contains("tar+gzip") || media_type.ends_with(".tar.gz") {
+            ArchiveFormat::TarGz
+        } else if media_type.ends_with("zip") && !media_type.ends_with("gzip") {
+            ArchiveFormat::Zip
+        } else {
+            ArchiveFormat::Raw
+        }
+    }
+}
+
+/// OCI 分发 API 客户端，只支持拉取
+pub struct OciClient {
+    client: reqwest::Client,
+    reference: OciReference,
+    credentials: Option<Credentials>,
+    /// 令牌认证换取的拉取令牌
+    token: Mutex<Option<String>>,
+}
+
+impl OciClient {
+    /// 根据全局设置创建客户端
+    pub fn new(
+        reference: OciReference,
+        settings: &GlobalSettings,
+        credentials: Option<Credentials>,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            client: build_client(
+                Duration::from_secs(settings.download_timeout),
+                settings.proxy.as_deref(),
+                settings.no_proxy.as_deref(),
+            )?,
+            reference,
+            credentials,
+            token: Mutex::new(None),
+        })
+    }
+
+    /// 为插件源创建客户端，凭据按模块文档中的顺序确定
+    pub async fn for_source(
+        source: &PluginSource,
+        settings: &GlobalSettings,
+    ) -> Result<Self, PluginError> {
+        let reference = OciReference::parse(&source.url)?;
+        let credentials = match source.resolve_auth().await? {
+            Some(credentials) => Some(credentials),
+            None => docker_credentials(&reference.registry).await,
+        };
+        Self::new(reference, settings, credentials)
+    }
+
+    /// 仓库地址
+    pub fn reference(&self) -> &OciReference {
+        &self.reference
+    }
+
+    /// 访问仓库使用的凭据：换取过令牌时为该令牌
+    pub fn authorization(&self) -> Option<Credentials> {
+        match self.token.lock().ok().and_then(|token| token.clone()) {
+            Some(token) => Some(Credentials::Bearer(Secret::from(token))),
+            None => self.credentials.clone(),
+        }
+    }
+
+    /// 仓库的全部标签
+    pub async fn tags(&self) -> Result<Vec<String>, PluginError> {
+        #[derive(Deserialize)]
+        struct TagList {
+            #[serde(default)]
+            tags: Option<Vec<String>>,
+        }
+
+        let mut tags = Vec::new();
+        let mut next = Some(self.reference.api_url("tags/list"));
+        while let Some(url) = next.take() {
+            let response = self.get(&url, None).await?;
+            next = next_page(&self.reference, response.headers());
+            let page: TagList = json(response, &url).await?;
+            tags.extend(page.tags.unwrap_or_default());
+        }
+        Ok(tags)
+    }
+
+    /// 标签或摘要对应的清单
+    async fn manifest(&self, reference: &str) -> Result<Manifest, PluginError> {
+        let url = self.reference.api_url(&format!("manifests/{}", reference));
+        let response = self.get(&url, Some(MANIFEST_ACCEPT)).await?;
+        json(response, &url).await
+    }
+
+    /// 解析版本在目标平台上的制品
+    pub async fn artifact(
+        &self,
+        version: &str,
+        tag: &str,
+        target: &TargetPlatform,
+    ) -> Result<VersionInfo, PluginError> {
+        let not_found = || {
+            PluginError::NotFound(format!(
+                "{}:{} 中没有 {} 的制品",
+                self.reference.repository, tag, target
+            ))
+        };
+        let mut manifest = self.manifest(tag).await?;
+        let mut platform = None;
+        if manifest.is_index() {
+            let selected = manifest.select_manifest(target).ok_or_else(not_found)?;
+            platform = Some(target.to_string());
+            manifest = self.manifest(&selected.digest).await?;
+        }
+        let layer = manifest.select_layer(target).ok_or_else(not_found)?;
+
+        let platform = layer
+            .annotations
+            .get(PLATFORM_ANNOTATION)
+            .cloned()
+            .or(platform)
+            .unwrap_or_else(|| "any".to_string());
+        let mut info = VersionInfo::new(
+            version,
+            &platform,
+            &self.reference.api_url(&format!("blobs/{}", layer.digest)),
+        );
+        info.checksum = layer.digest.strip_prefix("sha256:").map(str::to_string);
+        info.size = layer.size;
+        info.archive_format = Some(layer.archive_format());
+        info.entry_points = layer
+            .annotations
+            .get(ENTRY_POINTS_ANNOTATION)
+            .map(|entries| {
+                entries
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|entry| !entry.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        info.prerelease = version.contains('-');
+        Ok(info)
+    }
+
+    /// 发送 GET 请求，注册表要求令牌认证时换取令牌后重试一次
+    async fn get(&self, url: &str, accept: Option<&str>) -> Result<reqwest::Response, PluginError> {
+        permissions::check(|guard| guard.check_network(url))?;
+        let mut response = self.send(url, accept).await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            let challenge = response
+                .headers()
+                .get(reqwest::header::WWW_AUTHENTICATE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_bearer_challenge);
+            if let Some(challenge) = challenge {
+                let token = self.fetch_token(&challenge).await?;
+                if let Ok(mut cached) = self.token.lock() {
+                    *cached = Some(token);
+                }
+                response = self.send(url, accept).await?;
+            }
+        }
+        match response.status() {
+            status if status.is_success() => Ok(response),
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                Err(PluginError::PermissionDenied(format!(
+                    "无权访问 {}，请检查插件源的 auth 或执行 docker login {}",
+                    url, self.reference.registry
+                )))
+            }
+            reqwest::StatusCode::NOT_FOUND => {
+                Err(PluginError::NotFound(format!("OCI 注册表中没有 {}", url)))
+            }
+            status => Err(PluginError::NetworkError(format!(
+                "请求 {} 失败: {}",
+                url, status
+            ))),
+        }
+    }
+
+    async fn send(
+        &self,
+        url: &str,
+        accept: Option<&str>,
+    ) -> Result<reqwest::Response, PluginError> {
+        let mut request = self.client.get(url);
+        if let Some(accept) = accept {
+            request = request.header(reqwest::header::ACCEPT, accept);
+        }
+        if let Some(credentials) = self.authorization() {
+            request = credentials.apply(request);
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))
+    }
+
+    /// 向令牌服务换取拉取令牌，Basic 凭据随请求发送
+    async fn fetch_token(&self, challenge: &BearerChallenge) -> Result<String, PluginError> {
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            #[serde(default)]
+            token: Option<String>,
+            #[serde(default)]
+            access_token: Option<String>,
+        }
+
+        permissions::check(|guard| guard.check_network(&challenge.realm))?;
+        let scope = challenge
+            .scope
+            .clone()
+            .unwrap_or_else(|| format!("repository:{}:pull", self.reference.repository));
+        let mut query = vec![("scope", scope)];
+        if let Some(service) = &challenge.service {
+            query.push(("service", service.clone()));
+        }
+        let mut request = self.client.get(&challenge.realm).query(&query);
+        if let Some(credentials @ Credentials::Basic { .. }) = &self.credentials {
+            request = credentials.apply(request);
+        }
+        let response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| {
+                PluginError::PermissionDenied(format!(
+                    "从 {} 获取 {} 的访问令牌失败: {}",
+                    challenge.realm, self.reference.registry, e
+                ))
+            })?;
+        let body: TokenResponse = json(response, &challenge.realm).await?;
+        body.token.or(body.access_token).ok_or_else(|| {
+            PluginError::PermissionDenied(format!("{} 没有返回访问令牌", challenge.realm))
+        })
+    }
+}
+
+async fn json<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    url: &str,
+) -> Result<T, PluginError> {
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| PluginError::NetworkError(format!("读取 {} 的响应失败: {}", url, e)))?;
+    serde_json::from_slice(&body)
+        .map_err(|e| PluginError::ValidationError(format!("无效的 OCI 响应 {}: {}", url, e)))
+}
+
+/// `Link: </v2/<repo>/tags/list?n=100&last=x>; rel="next"` 指向的下一页
+fn next_page(reference: &OciReference, headers: &reqwest::header::HeaderMap) -> Option<String> {
+    let link = headers.get(reqwest::header::LINK)?.to_str().ok()?;
+    let (target, params) = link.split_once(';')?;
+    if !params.contains("rel=\"next\"") {
+        return None;
+    }
+    let target = target.trim().trim_start_matches('<').trim_end_matches('>');
+    Some(if target.starts_with('/') {
+        format!("{}{}", reference.api_base(), target)
+    } else {
+        target.to_string()
+    })
+}
+
+/// `WWW-Authenticate: Bearer realm="...",service="...",scope="..."`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BearerChallenge {
+    realm: String,
+    service: Option<String>,
+    scope: Option<String>,
+}
+
+fn parse_bearer_challenge(header: &str) -> Option<BearerChallenge> {
+    let (scheme, params) = header.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return None;
+    }
+    let mut values = HashMap::new();
+    let mut rest = params.trim();
+    while let Some((key, after)) = rest.split_once('=') {
+        let key = key
+            .trim()
+            .trim_start_matches(',')
+            .trim()
+            .to_ascii_lowercase();
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"')?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => match after.find(',') {
+                Some(end) => (&after[..end], &after[end..]),
+                None => (after, ""),
+            },
+        };
+        values.insert(key, value.to_string());
+        rest = remaining.trim_start_matches(',').trim();
+    }
+    Some(BearerChallenge {
+        realm: values.remove("realm")?,
+        service: values.remove("service"),
+        scope: values.remove("scope"),
+    })
+}
+
+/// `docker login` 保存的凭据
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DockerConfig {
+    #[serde(default)]
+    auths: HashMap<String, DockerAuth>,
+    #[serde(default)]
+    cred_helpers: HashMap<String, String>,
+    #[serde(default)]
+    creds_store: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DockerAuth {
+    #[serde(default)]
+    auth: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+}
+
+/// Docker 配置文件路径
+fn docker_config_path() -> Option<PathBuf> {
+    match std::env::var("DOCKER_CONFIG") {
+        Ok(dir) if !dir.is_empty() => Some(Path::new(&dir).join("config.json")),
+        _ => Some(dirs::home_dir()?.join(".docker").join("config.json")),
+    }
+}
+
+/// 读取 Docker 配置中注册表的凭据，没有配置或读取失败时返回 None
+pub async fn docker_credentials(registry: &str) -> Option<Credentials> {
+    let content = tokio::fs::read_to_string(docker_config_path()?)
+        .await
+        .ok()?;
+    let config: DockerConfig = serde_json::from_str(&content)
+        .map_err(|e| log::warn!("无法解析 Docker 配置: {}", e))
+        .ok()?;
+    let key = docker_auth_key(registry);
+    if let Some(helper) = config.cred_helpers.get(key).or(config.creds_store.as_ref()) {
+        match credential_helper(helper, key).await {
+            Ok(Some(credentials)) => return Some(credentials),
+            Ok(None) => {}
+            Err(e) => log::warn!("凭据助手 {} 获取 {} 的凭据失败: {}", helper, key, e),
+        }
+    }
+    config_credentials(&config, key)
+}
+
+/// Docker 配置中注册表使用的键，Docker Hub 使用旧的索引地址
+fn docker_auth_key(registry: &str) -> &str {
+    if registry == DOCKER_HUB || registry == DOCKER_HUB_API {
+        DOCKER_HUB_AUTH_KEY
+    } else {
+        registry
+    }
+}
+
+/// `auths` 中注册表的凭据，键可以带 `https://` 前缀和路径
+fn config_credentials(config: &DockerConfig, key: &str) -> Option<Credentials> {
+    let host_of = |entry: &str| {
+        entry
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+    let auth = config.auths.get(key).or_else(|| {
+        config
+            .auths
+            .iter()
+            .find(|(entry, _)| host_of(entry) == host_of(key))
+            .map(|(_, auth)| auth)
+    })?;
+    let (username, password) = match (&auth.auth, &auth.username, &auth.password) {
+        (Some(encoded), _, _) if !encoded.is_empty() => {
+            let decoded = base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .ok()?;
+            let decoded = String::from_utf8(decoded).ok()?;
+            let (username, password) = decoded.split_once(':')?;
+            (username.to_string(), password.to_string())
+        }
+        (_, Some(username), Some(password)) => (username.clone(), password.clone()),
+        _ => return None,
+    };
+    Some(Credentials::Basic {
+        username,
+        password: Secret::from(password),
+    })
+}
+
+/// 执行 `docker-credential-<helper> get` 获取凭据
+async fn credential_helper(helper: &str, key: &str) -> Result<Option<Credentials>, PluginError> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct HelperOutput {
+        username: String,
+        secret: String,
+    }
+
+    use tokio::io::AsyncWriteExt;
+
+    let program = format!("docker-credential-{}", helper);
+    permissions::check(|guard| guard.check_subprocess(&program))?;
+    let mut child = permissions::command(&program)
+        .arg("get")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| PluginError::PluginError(format!("无法执行 {}: {}", program, e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(key.as_bytes())
+            .await
+            .map_err(|e| PluginError::IoError(format!("写入 {} 失败: {}", program, e)))?;
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| PluginError::PluginError(format!("{} 执行失败: {}", program, e)))?;
+    // 凭据助手没有该注册表的凭据时以非零状态退出
+    if !output.status.success() {
+        log::debug!(
+            "{} get {}: {}",
+            program,
+            key,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return Ok(None);
+    }
+    let parsed: HelperOutput = serde_json::from_slice(&output.stdout)
+        .map_err(|e| PluginError::ValidationError(format!("{} 的输出无效: {}", program, e)))?;
+    Ok(Some(Credentials::Basic {
+        username: parsed.username,
+        password: Secret::from(parsed.secret),
+    }))
+}
+
+/// 从 OCI 仓库安装的插件
+pub struct OciPlugin {
+    name: String,
+    source: PluginSource,
+    /// 各版本的安装目录 `<install_root>/<version>`
+    install_root: PathBuf,
+    settings: GlobalSettings,
+    status: PluginStatus,
+    config: RwLock<HashMap<String, String>>,
+    /// 首次使用时创建的客户端，缓存换取的令牌
+    client: tokio::sync::OnceCell<OciClient>,
+    /// 版本号 -> 标签
+    tags: RwLock<Option<Vec<(String, String)>>>,
+}
+
+impl OciPlugin {
+    /// 创建插件，安装到 `plugin_dir/<name>` 下
+    pub fn from_config(
+        config: &PluginConfig,
+        source: &PluginSource,
+        settings: &GlobalSettings,
+    ) -> Self {
+        Self {
+            name: config.name.clone(),
+            source: source.clone(),
+            install_root: settings.plugin_path().join(&config.name),
+            settings: settings.clone(),
+            status: PluginStatus::Inactive,
+            config: RwLock::new(HashMap::new()),
+            client: tokio::sync::OnceCell::new(),
+            tags: RwLock::new(None),
+        }
+    }
+
+    /// 安装版本的目录
+    pub fn version_dir(&self, version: &str) -> PathBuf {
+        self.install_root.join(version.trim_start_matches('v'))
+    }
+
+    async fn client(&self) -> Result<&OciClient, PluginError> {
+        self.client
+            .get_or_try_init(|| OciClient::for_source(&self.source, &self.settings))
+            .await
+    }
+
+    /// 可解析为版本号的标签，最新的在前
+    async fn versions(&self) -> Result<Vec<(String, String)>, PluginError> {
+        if let Some(tags) = self.tags.read().ok().and_then(|t| t.clone()) {
+            return Ok(tags);
+        }
+        let mut versions: Vec<(Version, String, String)> = self
+            .client()
+            .await?
+            .tags()
+            .await?
+            .into_iter()
+            .filter_map(|tag| {
+                let version = tag.trim_start_matches('v').to_string();
+                Version::parse(&version)
+                    .ok()
+                    .map(|parsed| (parsed, version, tag))
+            })
+            .collect();
+        versions.sort_by(|a, b| b.0.cmp(&a.0));
+        // `1.0.0` 和 `v1.0.0` 同时存在时使用先出现的标签
+        versions.dedup_by(|a, b| a.0 == b.0);
+        let versions: Vec<(String, String)> = versions
+            .into_iter()
+            .map(|(_, version, tag)| (version, tag))
+            .collect();
+        if let Ok(mut cached) = self.tags.write() {
+            *cached = Some(versions.clone());
+        }
+        Ok(versions)
+    }
+
+    /// 版本对应的标签，`latest` 为最新的稳定版本
+    async fn resolve_tag(&self, version: &str) -> Result<(String, String), PluginError> {
+        self.versions()
+            .await?
+            .into_iter()
+            .find(|(candidate, _)| match version {
+                "latest" => !candidate.contains('-'),
+                version => same_version(candidate, version),
+            })
+            .ok_or_else(|| {
+                PluginError::NotFound(format!("{} {}（{}）", self.name, version, self.source.url))
+            })
+    }
+}
+
+#[async_trait]
+impl Plugin for OciPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        let latest = self.tags.read().ok().and_then(|tags| {
+            tags.as_ref()?
+                .iter()
+                .find(|(version, _)| !version.contains('-'))
+                .map(|(version, _)| version.clone())
+        });
+        PluginMetadata {
+            name: self.name.clone(),
+            version: latest.unwrap_or_else(|| PluginMetadata::default().version),
+            description: format!("Installed from the OCI repository {}", self.source.url),
+            tags: vec!["oci".to_string()],
+            ..PluginMetadata::default()
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Active;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Inactive;
+        Ok(())
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        let (version, tag) = self.resolve_tag(version).await?;
+        let target = options
+            .cross_target()
+            .cloned()
+            .unwrap_or_else(TargetPlatform::host);
+        let dest = match &options.install_dir {
+            Some(dir) => Path::new(dir).join(&version),
+            None => self.version_dir(&version),
+        };
+        if !options.force && dest.is_dir() {
+            return Ok(dest.to_string_lossy().into_owned());
+        }
+
+        let client = self.client().await?;
+        let info = client.artifact(&version, &tag, &target).await?;
+        if options.force {
+            let _ = tokio::fs::remove_dir_all(&dest).await;
+        }
+        // 层内容只在仓库的 blobs 地址下使用凭据，重定向到存储服务时不携带
+        Downloader::from_settings(&self.settings)?
+            .with_scoped_credentials(
+                vec![client.reference().api_url("blobs")],
+                client.authorization(),
+            )
+            .fetch_and_extract(&info, Some(&self.source), &dest)
+            .await?;
+
+        if let Some(missing) = info
+            .entry_points
+            .iter()
+            .map(|entry| dest.join(entry))
+            .find(|p| !p.is_file())
+        {
+            return Err(PluginError::InstallationError(format!(
+                "{} {} 的制品中没有 {}",
+                self.name,
+                info.version,
+                missing.display()
+            )));
+        }
+        Ok(dest.to_string_lossy().into_owned())
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        let dest = self.version_dir(version);
+        if !dest.exists() {
+            return Err(PluginError::NotFound(format!("{} {}", self.name, version)));
+        }
+        tokio::fs::remove_dir_all(&dest)
+            .await
+            .map_err(|e| PluginError::IoError(format!("删除 {} 失败: {}", dest.display(), e)))
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        // 只列出标签，制品在安装时才解析，避免为每个版本请求清单
+        let reference = self.client().await?.reference().clone();
+        Ok(self
+            .versions()
+            .await?
+            .into_iter()
+            .map(|(version, tag)| {
+                let mut info = VersionInfo::new(
+                    &version,
+                    current_platform(),
+                    &reference.api_url(&format!("manifests/{}", tag)),
+                );
+                info.prerelease = version.contains('-');
+                info
+            })
+            .collect())
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        let mut entries = match tokio::fs::read_dir(&self.install_root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(PluginError::IoError(format!("读取安装目录失败: {}", e))),
+        };
+        let mut versions = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取安装目录失败: {}", e)))?
+        {
+            let version = entry.file_name().to_string_lossy().into_owned();
+            if self.is_installed(&version).await? {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+        Ok(versions)
+    }
+
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        Ok(self.version_dir(version).is_dir())
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        self.list_versions()
+            .await?
+            .into_iter()
+            .find(|info| !info.prerelease)
+            .ok_or_else(|| PluginError::NotFound(format!("{} releases", self.name)))
+    }
+
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        let version = match version {
+            Some(version) => version.trim_start_matches('v').to_string(),
+            None => self.get_latest_version().await?.version,
+        };
+        self.install(&version, &InstallOptions::new()).await?;
+        Ok(version)
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        Ok(self.config.read().map(|c| c.clone()).unwrap_or_default())
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+        Ok(())
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        if let Ok(mut config) = self.config.write() {
+            config.insert(key.to_string(), value.to_string());
+        }
+        Ok(())
+    }
+
+    fn get_help(&self) -> String {
+        format!(
+            "{}: installs artifacts from the OCI repository {}",
+            self.name, self.source.url
+        )
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::ListVersions,
+            Capability::Config,
+            Capability::Vendor,
+            Capability::CrossInstall,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reference() {
+        let reference = OciReference::parse("oci://ghcr.io/example/tool").unwrap();
+        assert_eq!(reference.registry, "ghcr.io");
+        assert_eq!(reference.repository, "example/tool");
+        assert_eq!(
+            reference.api_url("tags/list"),
+            "https://ghcr.io/v2/example/tool/tags/list"
+        );
+
+        let hub = OciReference::parse("oci://docker.io/tool").unwrap();
+        assert_eq!(
+            hub.api_url("tags/list"),
+            "https://registry-1.docker.io/v2/library/tool/tags/list"
+        );
+        assert_eq!(
+            OciReference::parse("localhost:5000/tool")
+                .unwrap()
+                .api_base(),
+            "http://localhost:5000"
+        );
+
+        assert!(OciReference::parse("oci://ghcr.io").is_err());
+        assert!(OciReference::parse("oci://ghcr.io/example/tool:1.0.0").is_err());
+    }
+
+    #[test]
+    fn test_parse_bearer_challenge() {
+        let challenge = parse_bearer_challenge(
+            r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:example/tool:pull""#,
+        )
+        .unwrap();
+        assert_eq!(challenge.realm, "https://ghcr.io/token");
+        assert_eq!(challenge.service.as_deref(), Some("ghcr.io"));
+        assert_eq!(
+            challenge.scope.as_deref(),
+            Some("repository:example/tool:pull")
+        );
+        assert!(parse_bearer_challenge(r#"Basic realm="registry""#).is_none());
+    }
+
+    #[test]
+    fn test_select_platform() {
+        let index: Manifest = serde_json::from_value(serde_json::json!({
+            "mediaType": "application/vnd.oci.image.index.v1+json",
+            "manifests": [
+                { "digest": "sha256:aaa", "platform": { "os": "linux", "architecture": "amd64" } },
+                { "digest": "sha256:bbb", "platform": { "os": "darwin", "architecture": "arm64" } }
+            ]
+        }))
+        .unwrap();
+        assert!(index.is_index());
+        let mac: TargetPlatform = "macos-arm64".parse().unwrap();
+        assert_eq!(index.select_manifest(&mac).unwrap().digest, "sha256:bbb");
+        let windows: TargetPlatform = "windows-x64".parse().unwrap();
+        assert!(index.select_manifest(&windows).is_none());
+
+        let manifest: Manifest = serde_json::from_value(serde_json::json!({
+            "layers": [
+                { "mediaType": "application/octet-stream", "digest": "sha256:111",
+                  "annotations": { "io.plm.platform": "linux-x64",
+                                   "org.opencontainers.image.title": "tool-linux-x64.tar.gz" } },
+                { "mediaType": "application/zip", "digest": "sha256:222",
+                  "annotations": { "io.plm.platform": "windows-x64" } }
+            ]
+        }))
+        .unwrap();
+        assert!(!manifest.is_index());
+        let linux: TargetPlatform = "linux-x64".parse().unwrap();
+        let layer = manifest.select_layer(&linux).unwrap();
+        assert_eq!(layer.digest, "sha256:111");
+        assert_eq!(layer.archive_format(), ArchiveFormat::TarGz);
+        let layer = manifest.select_layer(&windows).unwrap();
+        assert_eq!(layer.archive_format(), ArchiveFormat::Zip);
+        assert!(manifest.select_layer(&mac).is_none());
+
+        // 没有平台注解时使用第一层
+        let single: Manifest = serde_json::from_value(serde_json::json!({
+            "layers": [{ "digest": "sha256:333" }, { "digest": "sha256:444" }]
+        }))
+        .unwrap();
+        assert_eq!(single.select_layer(&mac).unwrap().digest, "sha256:333");
+    }
+
+    #[test]
+    fn test_docker_config_credentials() {
+        let config: DockerConfig = serde_json::from_value(serde_json::json!({
+            "auths": {
+                "https://ghcr.io": {
+                    "auth": base64::engine::general_purpose::STANDARD.encode("ci:ghp_token")
+                },
+                "https://index.docker.io/v1/": { "username": "hub", "password": "secret" }
+            },
+            "credHelpers": { "123.dkr.ecr.us-east-1.amazonaws.com": "ecr-login" }
+        }))
+        .unwrap();
+        assert_eq!(
+            config_credentials(&config, docker_auth_key("ghcr.io")),
+            Some(Credentials::Basic {
+                username: "ci".to_string(),
+                password: Secret::from("ghp_token".to_string()),
+            })
+        );
+        assert_eq!(
+            config_credentials(&config, docker_auth_key("docker.io")),
+            Some(Credentials::Basic {
+                username: "hub".to_string(),
+                password: Secret::from("secret".to_string()),
+            })
+        );
+        assert!(config_credentials(&config, "quay.io").is_none());
+        assert_eq!(
+            config.cred_helpers["123.dkr.ecr.us-east-1.amazonaws.com"],
+            "ecr-login"
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! PLM 命令输出格式
+//!
+//! 默认输出面向终端的彩色文本；`--output json|yaml` 输出结构化数据，便于脚本处理。
+//! 结构化输出的字段只会新增，不会改名或删除。
+//!
+//! 设置 `PLM_DETERMINISTIC=1`（或传入隐藏参数 `--render-width`）时进入确定性输出模式：
+//! 不输出颜色、耗时和时间戳，列表按名称排序，行宽固定，便于对 CLI 输出做快照测试。
+
+use crate::state::PendingChange;
+use crate::traits::{LifecycleState, PluginError};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// 开启确定性输出的环境变量
+pub const DETERMINISTIC_ENV: &str = "PLM_DETERMINISTIC";
+
+/// 确定性输出模式下未指定宽度时的行宽
+pub const DEFAULT_RENDER_WIDTH: usize = 100;
+
+static RENDER_SETTINGS: OnceLock<RenderSettings> = OnceLock::new();
+
+/// 文本输出设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderSettings {
+    /// 不输出颜色、耗时和时间戳，列表按名称排序
+    pub deterministic: bool,
+    /// 行宽，超出的部分截断；None 表示不限制
+    pub width: Option<usize>,
+}
+
+impl RenderSettings {
+    /// 从 `PLM_DETERMINISTIC` 环境变量读取
+    pub fn from_env() -> Self {
+        Self::from_env_value(std::env::var(DETERMINISTIC_ENV).ok().as_deref())
+    }
+
+    /// 按环境变量的值创建，`1`、`true`、`yes`、`on` 表示开启
+    pub fn from_env_value(value: Option<&str>) -> Self {
+        let deterministic = value.is_some_and(|value| {
+            matches!(
+                value.trim().to_ascii_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        });
+        Self {
+            deterministic,
+            width: deterministic.then_some(DEFAULT_RENDER_WIDTH),
+        }
+    }
+
+    /// 固定行宽，同时开启确定性输出
+    pub fn with_width(self, width: usize) -> Self {
+        Self {
+            deterministic: true,
+            width: Some(width),
+        }
+    }
+
+    /// 设为进程的输出设置，只有第一次调用生效；确定性模式下关闭颜色
+    pub fn install(self) {
+        if self.deterministic {
+            colored::control::set_override(false);
+        }
+        let _ = RENDER_SETTINGS.set(self);
+    }
+}
+
+/// 当前的输出设置，未调用 `RenderSettings::install` 时从环境变量读取
+pub fn render_settings() -> RenderSettings {
+    *RENDER_SETTINGS.get_or_init(|| {
+        let settings = RenderSettings::from_env();
+        if settings.deterministic {
+            colored::control::set_override(false);
+        }
+        settings
+    })
+}
+
+/// 是否处于确定性输出模式
+pub fn is_deterministic() -> bool {
+    render_settings().deterministic
+}
+
+/// 格式化耗时，确定性模式下返回 None
+pub fn format_duration(duration: Duration) -> Option<String> {
+    (!is_deterministic()).then(|| format!("{:.1}s", duration.as_secs_f64()))
+}
+
+/// 格式化时间戳，确定性模式下返回 None
+pub fn format_timestamp(time: &DateTime<Utc>) -> Option<String> {
+    (!is_deterministic()).then(|| time.format("%Y-%m-%d %H:%M").to_string())
+}
+
+/// 按设置的行宽截断一行文本，颜色控制字符不计入宽度
+pub fn fit_line(line: &str) -> String {
+    match render_settings().width {
+        Some(width) => fit_to_width(line, width),
+        None => line.to_string(),
+    }
+}
+
+/// 把一行文本截断到指定宽度，超出时以 `…` 结尾
+pub fn fit_to_width(line: &str, width: usize) -> String {
+    console::truncate_str(line, width, "…").into_owned()
+}
+
+/// 命令输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// 彩色文本
+    #[default]
+    Text,
+    Json,
+    Yaml,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
+            other => Err(format!(
+                "unknown output format '{}' (expected text, json or yaml)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Yaml => "yaml",
+        })
+    }
+}
+
+impl OutputFormat {
+    /// 是否输出结构化数据
+    pub fn is_structured(&self) -> bool {
+        !matches!(self, OutputFormat::Text)
+    }
+
+    /// 序列化为 JSON 或 YAML，文本格式由各命令自行输出
+    pub fn render<T: Serialize>(&self, value: &T) -> Result<String, PluginError> {
+        match self {
+            OutputFormat::Json => serde_json::to_string_pretty(value)
+                .map_err(|e| PluginError::ValidationError(format!("序列化 JSON 失败: {}", e))),
+            OutputFormat::Yaml => serde_yaml::to_string(value)
+                .map(|yaml| yaml.trim_end().to_string())
+                .map_err(|e| PluginError::ValidationError(format!("序列化 YAML 失败: {}", e))),
+            OutputFormat::Text => Err(PluginError::ValidationError(
+                "text output is rendered by each command".to_string(),
+            )),
+        }
+    }
+}
+
+/// `plm list` 中的一个插件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PluginListEntry {
+    pub name: String,
+    /// 管理器记录的生命周期状态，插件实现未加载时为 None
+    pub status: Option<LifecycleState>,
+    /// 缓存的描述，元数据未缓存时为 None
+    pub description: Option<String>,
+    /// 配置中是否启用，配置中没有该插件时为 false
+    pub enabled: bool,
+    /// 已安装的版本，只有 `--installed` 时查询
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed: Option<Vec<String>>,
+}
+
+/// `plm validate <name>` 的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PluginValidation {
+    pub plugin: String,
+    pub valid: bool,
+}
+
+/// `plm discover` 的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiscoverReport {
+    pub discovered: usize,
+}
+
+/// 固定版本落后于注册表的插件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutdatedPlugin {
+    pub name: String,
+    /// 配置中的版本，未设置时为 None
+    pub pinned: Option<String>,
+    pub latest: String,
+}
+
+/// `plm outdated` 的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OutdatedReport {
+    pub outdated: Vec<OutdatedPlugin>,
+    /// 注册表中不存在的插件
+    pub missing: Vec<String>,
+    /// 查询失败的插件及错误信息
+    pub failed: BTreeMap<String, String>,
+}
+
+/// `plm status` 中的一个插件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PluginStatusEntry {
+    pub name: String,
+    /// 配置中是否启用，只存在于运行状态中的插件为 false
+    pub enabled: bool,
+    /// 管理器记录的生命周期状态，插件实现未加载时为 None
+    pub status: Option<LifecycleState>,
+    /// 最近一次初始化错误
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// 最近一次安装或更新到的版本
+    pub active_version: Option<String>,
+    pub installed_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// `plm status` 的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StatusReport {
+    pub plugins: Vec<PluginStatusEntry>,
+    /// 配置与运行状态不一致的变更
+    pub pending: Vec<PendingChange>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_settings() {
+        assert_eq!(
+            RenderSettings::from_env_value(None),
+            RenderSettings::default()
+        );
+        assert_eq!(
+            RenderSettings::from_env_value(Some("0")),
+            RenderSettings::default()
+        );
+        let settings = RenderSettings::from_env_value(Some("1"));
+        assert!(settings.deterministic);
+        assert_eq!(settings.width, Some(DEFAULT_RENDER_WIDTH));
+        assert_eq!(
+            RenderSettings::default().with_width(40),
+            RenderSettings {
+                deterministic: true,
+                width: Some(40),
+            }
+        );
+
+        assert_eq!(
+            fit_to_width("node - JavaScript runtime", 12),
+            "node - Java…"
+        );
+        assert_eq!(fit_to_width("node", 12), "node");
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let report = DiscoverReport { discovered: 2 };
+        assert_eq!(
+            OutputFormat::Json.render(&report).unwrap(),
+            "{\n  \"discovered\": 2\n}"
+        );
+        assert_eq!(OutputFormat::Yaml.render(&report).unwrap(), "discovered: 2");
+        assert!(OutputFormat::Text.render(&report).is_err());
+        assert_eq!("YAML".parse::<OutputFormat>(), Ok(OutputFormat::Yaml));
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! PLM 内容寻址制品获取（实验性）
+//!
+//! 启用 `p2p` 特性后，版本信息中的 `content_uris` 在 HTTP 下载地址之前尝试，
+//! 大型制品由网络中的其他节点分发，分担内部下载服务的带宽：
+//!
+//! - `ipfs://<cid>[/<path>]`：通过 IPFS 网关下载（全局设置 `ipfs_gateway`，
+//!   默认为本机节点 `http://127.0.0.1:8080`），和 HTTP 下载一样支持断点续传
+//! - `magnet:?xt=urn:btih:...`：调用 `aria2c` 下载，下载完成后不做种
+//!
+//! 获取失败（网关不可用、未安装 aria2c 等）时按网络错误处理，继续尝试其余下载地址。
+//! 内容寻址只保证取回的内容与地址一致，制品本身仍按 `checksum` 和签名校验。
+
+use crate::permissions;
+use crate::traits::PluginError;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// 未配置 `ipfs_gateway` 时使用的本机 IPFS 网关
+pub const DEFAULT_IPFS_GATEWAY: &str = "http://127.0.0.1:8080";
+
+/// 是否为内容寻址地址
+pub fn is_content_uri(uri: &str) -> bool {
+    uri.starts_with("ipfs://") || is_magnet(uri)
+}
+
+/// 是否为 BitTorrent magnet 链接
+pub fn is_magnet(uri: &str) -> bool {
+    uri.starts_with("magnet:")
+}
+
+/// `ipfs://` 地址对应的网关地址，其他地址返回 None
+pub fn gateway_url(uri: &str, gateway: Option<&str>) -> Option<String> {
+    let path = uri.strip_prefix("ipfs://")?.trim_start_matches('/');
+    if path.is_empty() {
+        return None;
+    }
+    let gateway = gateway
+        .unwrap_or(DEFAULT_IPFS_GATEWAY)
+        .trim_end_matches('/');
+    Some(format!("{}/ipfs/{}", gateway, path))
+}
+
+/// 用 aria2c 下载 magnet 链接中的单个文件到 `dest`，返回文件大小
+pub async fn fetch_magnet(uri: &str, dest: &Path) -> Result<u64, PluginError> {
+    permissions::check(|guard| guard.check_network(uri))?;
+    permissions::check(|guard| guard.check_subprocess("aria2c"))?;
+    let aria2c = which::which("aria2c")
+        .map_err(|_| PluginError::NetworkError("下载 magnet 链接需要安装 aria2c".to_string()))?;
+    let parent = dest.parent().unwrap_or_else(|| Path::new("."));
+    // 临时目录与目标在同一文件系统中，完成后直接重命名
+    let staging = tempfile::Builder::new()
+        .prefix(".plm-p2p-")
+        .tempdir_in(parent)
+        .map_err(|e| PluginError::IoError(format!("创建临时目录失败: {}", e)))?;
+
+    log::debug!("执行: aria2c {}", uri);
+    let output = permissions::command(aria2c)
+        .args([
+            "--seed-time=0",
+            "--follow-torrent=mem",
+            "--bt-save-metadata=false",
+            "--summary-interval=0",
+            "--console-log-level=warn",
+            "--dir",
+        ])
+        .arg(staging.path())
+        .arg(uri)
+        .output()
+        .await
+        .map_err(|e| PluginError::NetworkError(format!("无法执行 aria2c: {}", e)))?;
+    if !output.status.success() {
+        return Err(PluginError::NetworkError(format!(
+            "aria2c 下载 {} 失败: {}",
+            uri,
+            String::from_utf8_lossy(&output.stdout).trim()
+        )));
+    }
+
+    let files = downloaded_files(staging.path()).await?;
+    let [file] = files.as_slice() else {
+        return Err(PluginError::NetworkError(format!(
+            "magnet 链接应只包含一个制品文件，实际为 {} 个: {}",
+            files.len(),
+            uri
+        )));
+    };
+    let size = tokio::fs::metadata(file)
+        .await
+        .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", file.display(), e)))?
+        .len();
+    tokio::fs::rename(file, dest)
+        .await
+        .map_err(|e| PluginError::IoError(format!("移动 {} 失败: {}", file.display(), e)))?;
+    Ok(size)
+}
+
+/// aria2c 下载目录中的文件，不包括 `.aria2` 控制文件
+async fn downloaded_files(dir: &Path) -> Result<Vec<PathBuf>, PluginError> {
+    let read_error = |dir: &Path, e: std::io::Error| {
+        PluginError::IoError(format!("读取 {} 失败: {}", dir.display(), e))
+    };
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&dir)
+            .await
+            .map_err(|e| read_error(&dir, e))?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| read_error(&dir, e))?
+        {
+            let path = entry.path();
+            let file_type = entry.file_type().await.map_err(|e| read_error(&path, e))?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if path.extension() != Some(OsStr::new("aria2")) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_uris() {
+        assert!(is_content_uri(
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"
+        ));
+        assert!(is_content_uri(
+            "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a"
+        ));
+        assert!(!is_content_uri("https://example.com/tool.tar.gz"));
+
+        assert_eq!(
+            gateway_url("ipfs://bafy123/tool.tar.gz", None).as_deref(),
+            Some("http://127.0.0.1:8080/ipfs/bafy123/tool.tar.gz")
+        );
+        assert_eq!(
+            gateway_url("ipfs://bafy123", Some("https://ipfs.internal.example.com/")).as_deref(),
+            Some("https://ipfs.internal.example.com/ipfs/bafy123")
+        );
+        assert_eq!(gateway_url("ipfs://", None), None);
+        assert_eq!(gateway_url("https://example.com/a", None), None);
+    }
+
+    #[tokio::test]
+    async fn test_downloaded_files_skips_control_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("toolchain")).unwrap();
+        std::fs::write(dir.path().join("toolchain/toolchain.tar.gz"), b"data").unwrap();
+        std::fs::write(dir.path().join("toolchain.tar.gz.aria2"), b"control").unwrap();
+
+        let files = downloaded_files(dir.path()).await.unwrap();
+        assert_eq!(files, [dir.path().join("toolchain/toolchain.tar.gz")]);
+    }
+}
@@ -0,0 +1,330 @@
+//! 插件权限
+//!
+//! 插件配置中的 `permissions` 限制插件可以做的事：
+//!
+//! ```json
+//! "permissions": {
+//!   "network": true,
+//!   "subprocess": false,
+//!   "filesystem": ["~/.local/share/tools"],
+//!   "env": ["GITHUB_TOKEN"]
+//! }
+//! ```
+//!
+//! 没有 `permissions` 的插件不受限制；声明后未列出的权限都被拒绝，插件目录和缓存目录总是可以写入。
+//! 管理器调用插件时在当前任务中设置插件的权限，PLM 启动子进程（`system::run_command` 等）、
+//! 下载制品和选择安装目录时据此检查，越权时返回 `PluginError::PermissionDenied`。
+//! 受限插件启动的子进程只继承 `PATH`、`HOME` 等基本环境变量和 `env` 中声明的变量。
+//!
+//! 这不是沙箱：插件绕过 PLM 自行启动的进程、发起的网络请求，以及在新任务中执行的操作都不受检查。
+
+use crate::config::{resolve_path, GlobalSettings, PluginConfig};
+use crate::traits::PluginError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::future::Future;
+use std::path::{Component, Path, PathBuf};
+
+/// 插件配置中声明的权限，未列出的权限都被拒绝
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PluginPermissions {
+    /// 访问网络（下载制品、查询版本）
+    #[serde(default)]
+    pub network: bool,
+    /// 启动子进程
+    #[serde(default)]
+    pub subprocess: bool,
+    /// 插件目录和缓存目录之外可以写入的目录，支持 `~` 和环境变量
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filesystem: Vec<String>,
+    /// 可以传给子进程的环境变量名
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+}
+
+/// 受限插件的子进程总是继承的环境变量
+const BASE_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "TMPDIR",
+    "TMP",
+    "TEMP",
+    "USERPROFILE",
+    "SYSTEMROOT",
+];
+
+/// 一个插件生效的权限
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionGuard {
+    plugin: String,
+    permissions: PluginPermissions,
+    /// 可以写入的目录，已展开并规范化
+    writable: Vec<PathBuf>,
+}
+
+impl PermissionGuard {
+    /// 插件配置声明了权限时创建，否则返回 None（不受限制）
+    pub fn for_plugin(config: &PluginConfig, settings: &GlobalSettings) -> Option<Self> {
+        let permissions = config.permissions.clone()?;
+        let base = settings.base_dir.as_deref();
+        let writable = [settings.plugin_path(), settings.cache_path()]
+            .into_iter()
+            .chain(
+                permissions
+                    .filesystem
+                    .iter()
+                    .map(|path| resolve_path(path, base)),
+            )
+            .map(|path| normalize(&path))
+            .collect();
+        Some(Self {
+            plugin: config.name.clone(),
+            permissions,
+            writable,
+        })
+    }
+
+    /// 插件名称
+    pub fn plugin(&self) -> &str {
+        &self.plugin
+    }
+
+    /// 声明的权限
+    pub fn permissions(&self) -> &PluginPermissions {
+        &self.permissions
+    }
+
+    /// 访问 `url`；本地 `file://` 地址不需要网络权限
+    pub fn check_network(&self, url: &str) -> Result<(), PluginError> {
+        if self.permissions.network || url.starts_with("file://") {
+            Ok(())
+        } else {
+            Err(self.denied(format!("访问网络 {}", url)))
+        }
+    }
+
+    /// 启动 `program`
+    pub fn check_subprocess(&self, program: &str) -> Result<(), PluginError> {
+        if self.permissions.subprocess {
+            Ok(())
+        } else {
+            Err(self.denied(format!("启动子进程 {}", program)))
+        }
+    }
+
+    /// 把环境变量 `name` 传给子进程
+    pub fn check_env(&self, name: &str) -> Result<(), PluginError> {
+        if self.permissions.env.iter().any(|allowed| allowed == name) {
+            Ok(())
+        } else {
+            Err(self.denied(format!("使用环境变量 {}", name)))
+        }
+    }
+
+    /// 清空 `command` 继承的环境变量，只保留基本变量和声明的变量
+    pub fn restrict_env(&self, command: &mut tokio::process::Command) {
+        command.env_clear();
+        let allowed = BASE_ENV
+            .iter()
+            .copied()
+            .chain(self.permissions.env.iter().map(String::as_str));
+        for name in allowed {
+            if let Some(value) = std::env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+    }
+
+    /// 写入 `path`，相对路径相对于当前目录
+    pub fn check_path(&self, path: &Path) -> Result<(), PluginError> {
+        let path = normalize(path);
+        if self.writable.iter().any(|root| path.starts_with(root)) {
+            Ok(())
+        } else {
+            Err(self.denied(format!("写入 {}", path.display())))
+        }
+    }
+
+    fn denied(&self, action: String) -> PluginError {
+        PluginError::PermissionDenied(format!(
+            "插件 {} 没有权限{}，请在 plm.json 的 permissions 中声明",
+            self.plugin, action
+        ))
+    }
+}
+
+tokio::task_local! {
+    static CURRENT: PermissionGuard;
+}
+
+/// 在 `guard` 的权限下执行 `future`，`guard` 为 None 时不受限制
+pub async fn scope<F: Future>(guard: Option<PermissionGuard>, future: F) -> F::Output {
+    match guard {
+        Some(guard) => CURRENT.scope(guard, future).await,
+        None => future.await,
+    }
+}
+
+/// 当前任务中生效的权限
+pub fn current() -> Option<PermissionGuard> {
+    CURRENT.try_with(PermissionGuard::clone).ok()
+}
+
+/// 用当前任务中生效的权限检查操作，没有设置权限时放行
+pub fn check(
+    f: impl FnOnce(&PermissionGuard) -> Result<(), PluginError>,
+) -> Result<(), PluginError> {
+    CURRENT.try_with(f).unwrap_or(Ok(()))
+}
+
+/// 创建启动 `program` 的命令，当前任务设置了权限时只传递允许的环境变量
+pub fn command(program: impl AsRef<OsStr>) -> tokio::process::Command {
+    let mut command = tokio::process::Command::new(program);
+    // 没有设置权限时 try_with 返回错误，命令继承全部环境变量
+    let _ = CURRENT.try_with(|guard| guard.restrict_env(&mut command));
+    command
+}
+
+/// 转为绝对路径并按字面去掉 `.` 和 `..`，不访问文件系统
+fn normalize(path: &Path) -> PathBuf {
+    let absolute = if path.is_relative() {
+        std::env::current_dir()
+            .map(|dir| dir.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    } else {
+        path.to_path_buf()
+    };
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard(permissions: PluginPermissions) -> PermissionGuard {
+        let mut config = PluginConfig::new("tool");
+        config.permissions = Some(permissions);
+        let settings = GlobalSettings {
+            plugin_dir: "/opt/plm/plugins".to_string(),
+            cache_dir: "/opt/plm/cache".to_string(),
+            ..GlobalSettings::default()
+        };
+        PermissionGuard::for_plugin(&config, &settings).unwrap()
+    }
+
+    #[test]
+    fn test_declared_permissions() {
+        let restricted = guard(PluginPermissions {
+            filesystem: vec!["/srv/tools".to_string()],
+            env: vec!["GITHUB_TOKEN".to_string()],
+            ..PluginPermissions::default()
+        });
+        assert!(matches!(
+            restricted.check_network("https://example.com/tool.tar.gz"),
+            Err(PluginError::PermissionDenied(_))
+        ));
+        assert!(restricted.check_network("file:///srv/tool.tar.gz").is_ok());
+        assert!(restricted.check_subprocess("brew").is_err());
+        assert!(restricted.check_env("GITHUB_TOKEN").is_ok());
+        assert!(restricted.check_env("AWS_SECRET_ACCESS_KEY").is_err());
+        assert!(restricted
+            .check_path(Path::new("/opt/plm/plugins/tool/1.0.0"))
+            .is_ok());
+        assert!(restricted.check_path(Path::new("/srv/tools/bin")).is_ok());
+        assert!(restricted
+            .check_path(Path::new("/srv/tools/../etc"))
+            .is_err());
+
+        let open = guard(PluginPermissions {
+            network: true,
+            subprocess: true,
+            ..PluginPermissions::default()
+        });
+        assert!(open.check_network("https://example.com").is_ok());
+        assert!(open.check_subprocess("brew").is_ok());
+
+        assert!(PermissionGuard::for_plugin(
+            &PluginConfig::new("tool"),
+            &GlobalSettings::default()
+        )
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_scope() {
+        assert!(check(|g| g.check_subprocess("brew")).is_ok());
+        let restricted = guard(PluginPermissions::default());
+        let result = scope(Some(restricted), async {
+            assert_eq!(current().unwrap().plugin(), "tool");
+            check(|g| g.check_subprocess("brew"))
+        })
+        .await;
+        assert!(result.is_err());
+        assert!(current().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subprocess_env_is_restricted() {
+        let restricted = guard(PluginPermissions {
+            subprocess: true,
+            env: vec!["GITHUB_TOKEN".to_string()],
+            ..PluginPermissions::default()
+        });
+        let restricted_command = scope(Some(restricted), async { command("env") }).await;
+        let names: Vec<_> = restricted_command
+            .as_std()
+            .get_envs()
+            .map(|(name, _)| name.to_string_lossy().into_owned())
+            .collect();
+        assert!(names
+            .iter()
+            .all(|name| name == "GITHUB_TOKEN" || BASE_ENV.contains(&name.as_str())));
+        if std::env::var_os("PATH").is_some() {
+            assert!(names.iter().any(|name| name == "PATH"));
+        }
+
+        // 未设置权限时继承全部环境变量
+        assert_eq!(command("env").as_std().get_envs().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_subprocesses_are_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifact = dir.path().join("artifact");
+        std::fs::write(&artifact, "data").unwrap();
+
+        let restricted = guard(PluginPermissions::default());
+        scope(Some(restricted), async {
+            let index = crate::git_registry::GitIndex::new(
+                "git+https://example.com/plm-index.git",
+                dir.path(),
+            );
+            assert!(matches!(
+                index.update().await,
+                Err(PluginError::PermissionDenied(_))
+            ));
+            let signature = "-----BEGIN PGP SIGNATURE-----\n-----END PGP SIGNATURE-----";
+            assert!(matches!(
+                crate::signature::verify_file(
+                    &artifact,
+                    signature,
+                    &[crate::signature::TrustedKey::gpg("ABCD")]
+                )
+                .await,
+                Err(PluginError::PermissionDenied(_))
+            ));
+        })
+        .await;
+    }
+}
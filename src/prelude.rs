@@ -0,0 +1,32 @@
+//! Everything a plugin implementation needs in one import
+//!
+//! ```ignore
+//! use plm::prelude::*;
+//!
+//! #[derive(Default)]
+//! pub struct MyPlugin;
+//!
+//! #[async_trait]
+//! impl Plugin for MyPlugin {
+//!     // ...
+//! }
+//!
+//! #[plm_plugin]
+//! fn create() -> MyPlugin {
+//!     MyPlugin::default()
+//! }
+//! ```
+
+pub use crate::config::{
+    settings_from_value, GlobalSettings, PluginConfig, PluginSource, PluginSourceType,
+};
+pub use crate::declarative::{ReleasePlugin, ReleaseSpec};
+pub use crate::declarative_plugin;
+pub use crate::hooks::HookContext;
+pub use crate::traits::{
+    ArchiveFormat, Capability, InstallOptions, LauncherEntry, Plugin, PluginError, PluginFactory,
+    PluginLoader, PluginMetadata, PluginStatus, VersionInfo,
+};
+pub use crate::version::{Version, VersionReq};
+pub use async_trait::async_trait;
+pub use plm_macros::plm_plugin;
@@ -0,0 +1,128 @@
+//! PLM 项目文件版本解析
+//!
+//! 许多工具在项目文件中声明需要的版本（Terraform 的 `required_version`、
+//! `rust-toolchain.toml` 等），插件通过 `Plugin::project_constraint` 返回这些约束，
+//! `plm sync` 为没有在配置中固定版本的插件选择满足约束的最新版本。
+//! 本模块提供插件实现这类解析时共用的辅助函数。
+
+use crate::traits::{PluginError, VersionInfo};
+use crate::version::{Version, VersionReq};
+use std::path::{Path, PathBuf};
+
+/// 从 `start` 开始向上逐级查找第一个存在的文件，`names` 按优先顺序排列
+pub fn find_upwards(start: &Path, names: &[&str]) -> Option<PathBuf> {
+    start.ancestors().find_map(|dir| {
+        names
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    })
+}
+
+/// 读取目录中指定扩展名的所有文件（不递归），按路径排序
+pub async fn read_files_with_extension(
+    dir: &Path,
+    extension: &str,
+) -> Result<Vec<(PathBuf, String)>, PluginError> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(PluginError::IoError(format!(
+                "读取目录 {} 失败: {}",
+                dir.display(),
+                e
+            )))
+        }
+    };
+
+    let mut paths = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| PluginError::IoError(format!("读取目录 {} 失败: {}", dir.display(), e)))?
+    {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == extension) && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e)))?;
+        files.push((path, content));
+    }
+    Ok(files)
+}
+
+/// 在可用版本中选择满足约束的最新稳定版本
+///
+/// 忽略预发布、已撤回和不支持 `platform` 的版本，没有满足条件的版本时返回 None
+pub fn select_version(
+    versions: Vec<VersionInfo>,
+    req: &VersionReq,
+    platform: &str,
+) -> Option<String> {
+    versions
+        .into_iter()
+        .filter(|info| !info.prerelease && !info.yanked && info.supports_platform(platform))
+        .filter_map(|info| Version::parse(&info.version).ok().map(|v| (v, info)))
+        .filter(|(v, _)| req.matches(v))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, info)| info.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_version() {
+        let platform = crate::config::current_platform();
+        let mut prerelease = VersionInfo::new("1.7.0-rc1", platform, "");
+        prerelease.prerelease = true;
+        let versions = vec![
+            VersionInfo::new("1.5.7", platform, ""),
+            VersionInfo::new("1.6.2", platform, ""),
+            VersionInfo::new("2.0.0", platform, ""),
+            prerelease,
+        ];
+
+        let req = VersionReq::parse(">=1.5, <2").unwrap();
+        assert_eq!(
+            select_version(versions.clone(), &req, platform).as_deref(),
+            Some("1.6.2")
+        );
+        let req = VersionReq::parse(">=3").unwrap();
+        assert_eq!(select_version(versions, &req, platform), None);
+    }
+
+    #[tokio::test]
+    async fn test_find_and_read_project_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("modules/network");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.path().join("versions.tf"), "terraform {}").unwrap();
+        std::fs::write(dir.path().join("main.tf"), "").unwrap();
+        std::fs::write(dir.path().join("README.md"), "").unwrap();
+
+        assert_eq!(
+            find_upwards(&nested, &["versions.tf"]),
+            Some(dir.path().join("versions.tf"))
+        );
+        let files = read_files_with_extension(dir.path(), "tf").await.unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["main.tf", "versions.tf"]);
+        assert!(read_files_with_extension(&dir.path().join("missing"), "tf")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
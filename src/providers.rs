@@ -0,0 +1,257 @@
+//! PLM 设置值提供者
+//!
+//! 插件设置中的字符串值可以引用运行时才解析的来源，而不是直接写入配置文件：
+//!
+//! - `env:GITHUB_TOKEN` - 读取环境变量
+//! - `file:~/.secrets/token` - 读取文件内容（去掉末尾换行）
+//! - `exec:op read op://vault/item/token` - 执行命令并读取标准输出
+//! - `keychain:github-token` - 读取系统钥匙串中的凭据（见 `secrets` 模块）
+//!
+//! 解析是惰性的，只在真正需要该值时执行，结果在 `SettingResolver` 中缓存。
+//! 解析得到的值包装为 `Secret`，在 Debug/Display 中自动脱敏，配置文件中只保留引用。
+
+use crate::permissions;
+use crate::traits::PluginError;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// 脱敏后的显示文本
+const REDACTED: &str = "***";
+
+/// 设置值提供者
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueProvider {
+    /// 环境变量
+    Env(String),
+    /// 文件内容
+    File(String),
+    /// 命令输出
+    Exec(String),
+    /// 系统钥匙串中的凭据
+    Keychain(String),
+}
+
+impl ValueProvider {
+    /// 解析提供者引用，不是提供者引用时返回 None
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Some(name) = value.strip_prefix("env:") {
+            Some(ValueProvider::Env(name.trim().to_string()))
+        } else if let Some(path) = value.strip_prefix("file:") {
+            Some(ValueProvider::File(path.trim().to_string()))
+        } else if let Some(name) = value.strip_prefix("keychain:") {
+            Some(ValueProvider::Keychain(name.trim().to_string()))
+        } else {
+            value
+                .strip_prefix("exec:")
+                .map(|command| ValueProvider::Exec(command.trim().to_string()))
+        }
+    }
+
+    /// 执行解析
+    async fn resolve(&self) -> Result<String, PluginError> {
+        match self {
+            ValueProvider::Env(name) => std::env::var(name).map_err(|_| {
+                PluginError::ConfigError(format!("Environment variable '{}' is not set", name))
+            }),
+            ValueProvider::File(path) => {
+                let path = match path.strip_prefix("~/") {
+                    Some(rest) => dirs::home_dir()
+                        .map(|home| home.join(rest))
+                        .unwrap_or_else(|| path.into()),
+                    None => path.into(),
+                };
+                let content = tokio::fs::read_to_string(&path).await.map_err(|e| {
+                    PluginError::ConfigError(format!(
+                        "Failed to read secret file '{}': {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                Ok(content.trim_end_matches(['\r', '\n']).to_string())
+            }
+            ValueProvider::Exec(command) => {
+                permissions::check(|guard| guard.check_subprocess(command))?;
+                let output = shell_command(command).output().await.map_err(|e| {
+                    PluginError::ConfigError(format!("Failed to run '{}': {}", command, e))
+                })?;
+                if !output.status.success() {
+                    return Err(PluginError::ConfigError(format!(
+                        "Command '{}' exited with {}",
+                        command, output.status
+                    )));
+                }
+                Ok(String::from_utf8_lossy(&output.stdout)
+                    .trim_end_matches(['\r', '\n'])
+                    .to_string())
+            }
+            ValueProvider::Keychain(name) => crate::secrets::read_secret(name).await,
+        }
+    }
+}
+
+#[cfg(windows)]
+pub(crate) fn shell_command(command: &str) -> tokio::process::Command {
+    let mut cmd = permissions::command("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+#[cfg(not(windows))]
+pub(crate) fn shell_command(command: &str) -> tokio::process::Command {
+    let mut cmd = permissions::command("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+/// 解析得到的敏感值，Debug/Display 输出均为脱敏文本
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    /// 获取原始值
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Secret(value)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret({})", REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// 解析后的设置值
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedValue {
+    /// 配置中直接写出的普通值
+    Plain(serde_json::Value),
+    /// 由提供者解析得到的值
+    Secret(Secret),
+}
+
+impl ResolvedValue {
+    /// 以字符串形式获取值（普通值必须是字符串）
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ResolvedValue::Plain(value) => value.as_str(),
+            ResolvedValue::Secret(secret) => Some(secret.expose()),
+        }
+    }
+}
+
+impl fmt::Display for ResolvedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolvedValue::Plain(serde_json::Value::String(s)) => f.write_str(s),
+            ResolvedValue::Plain(value) => write!(f, "{}", value),
+            ResolvedValue::Secret(secret) => write!(f, "{}", secret),
+        }
+    }
+}
+
+/// 带缓存的设置值解析器
+#[derive(Default)]
+pub struct SettingResolver {
+    /// 提供者引用原文 -> 解析结果
+    cache: Mutex<HashMap<String, String>>,
+}
+
+impl SettingResolver {
+    /// 创建解析器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 解析设置值
+    ///
+    /// 提供者引用返回 `Some(Secret)`；普通值返回 None，调用方直接使用原值即可
+    pub async fn resolve(&self, value: &serde_json::Value) -> Result<Option<Secret>, PluginError> {
+        let Some(raw) = value.as_str() else {
+            return Ok(None);
+        };
+        let Some(provider) = ValueProvider::parse(raw) else {
+            return Ok(None);
+        };
+
+        let cached = self.cache.lock().ok().and_then(|c| c.get(raw).cloned());
+        if let Some(cached) = cached {
+            return Ok(Some(Secret(cached)));
+        }
+
+        let resolved = provider.resolve().await?;
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(raw.to_string(), resolved.clone());
+        }
+        Ok(Some(Secret(resolved)))
+    }
+
+    /// 清空缓存，下次访问时重新解析
+    pub fn clear_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
+        }
+    }
+}
+
+/// 判断设置值是否为提供者引用
+pub fn is_provider_reference(value: &serde_json::Value) -> bool {
+    value.as_str().and_then(ValueProvider::parse).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_parse() {
+        assert_eq!(
+            ValueProvider::parse("env:TOKEN"),
+            Some(ValueProvider::Env("TOKEN".to_string()))
+        );
+        assert_eq!(
+            ValueProvider::parse("exec:op read op://vault/token"),
+            Some(ValueProvider::Exec("op read op://vault/token".to_string()))
+        );
+        assert_eq!(
+            ValueProvider::parse("keychain:github-token"),
+            Some(ValueProvider::Keychain("github-token".to_string()))
+        );
+        assert_eq!(ValueProvider::parse("plain value"), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_file_with_cache_and_redaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token");
+        tokio::fs::write(&path, "s3cr3t\n").await.unwrap();
+
+        let resolver = SettingResolver::new();
+        let reference = serde_json::Value::String(format!("file:{}", path.display()));
+
+        let secret = resolver.resolve(&reference).await.unwrap().unwrap();
+        assert_eq!(secret.expose(), "s3cr3t");
+        assert_eq!(secret.to_string(), "***");
+        assert!(!format!("{:?}", secret).contains("s3cr3t"));
+
+        // 缓存命中时不再读取文件
+        tokio::fs::remove_file(&path).await.unwrap();
+        let cached = resolver.resolve(&reference).await.unwrap().unwrap();
+        assert_eq!(cached.expose(), "s3cr3t");
+
+        let plain = serde_json::Value::String("plain".to_string());
+        assert!(resolver.resolve(&plain).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,505 @@
+//! 发布插件
+//!
+//! `plm publish <manifest>` 把插件清单（格式同 `plm registry build` 的清单，制品路径相对于清单所在目录）
+//! 打包为插件包：检查名称、版本号和制品，计算各制品的 SHA-256 和大小。`--dry-run` 只检查，不上传。
+//!
+//! 发布目标是配置中的插件源，默认为第一个 `registry` 或 `static` 插件源，可用 `--source <url>` 指定：
+//!
+//! - `registry`：逐个上传制品（`PUT /v1/plugins/<name>/versions/<version>/artifacts/<file>`，
+//!   请求头 `x-plm-checksum` 为制品的 SHA-256），再提交插件信息和版本列表
+//!   （`POST /v1/plugins/<name>/versions`，请求体见 `PublishRequest`）。请求带插件源的凭据
+//!   （`auth` 或 `token`，见 `crate::auth`），注册表中已有相同版本时应返回 409。
+//! - `static`（仅 `file://` 地址）：把制品复制到注册表目录的 `artifacts/<name>/<version>/`，
+//!   把清单合并到 `manifests/<name>.json` 并重新生成索引。
+
+use crate::config::{GlobalSettings, PluginSource, PluginSourceType, ProjectConfig};
+use crate::download::build_client;
+use crate::static_registry::{self, IndexedPlugin, ManifestRelease, PluginManifest, MANIFESTS_DIR};
+use crate::temp::TempFileGuard;
+use crate::traits::{PluginError, PluginMetadata, VersionInfo};
+use crate::version::Version;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 静态注册表中存放已发布制品的目录
+pub const ARTIFACTS_DIR: &str = "artifacts";
+
+/// 请求头：上传制品的 SHA-256
+pub const CHECKSUM_HEADER: &str = "x-plm-checksum";
+
+/// 打包好的插件
+#[derive(Debug, Clone)]
+pub struct Package {
+    /// 清单所在目录，制品路径相对于该目录
+    pub dir: PathBuf,
+    pub manifest: PluginManifest,
+    /// 插件信息和各制品的版本信息，下载地址为制品的相对路径
+    pub plugin: IndexedPlugin,
+}
+
+impl Package {
+    /// 插件名称
+    pub fn name(&self) -> &str {
+        &self.plugin.metadata.name
+    }
+
+    /// 包含的版本号，最新的在前
+    pub fn versions(&self) -> Vec<String> {
+        let mut versions: Vec<String> = Vec::new();
+        for info in &self.plugin.versions {
+            if !versions.contains(&info.version) {
+                versions.push(info.version.clone());
+            }
+        }
+        versions
+    }
+
+    /// 各制品的版本信息和本地路径
+    pub fn artifacts(&self) -> impl Iterator<Item = (&VersionInfo, PathBuf)> + '_ {
+        self.plugin
+            .versions
+            .iter()
+            .map(|info| (info, self.dir.join(&info.download_url)))
+    }
+}
+
+/// 提交到注册表的插件信息和版本列表，下载地址为上传后的制品地址
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishRequest {
+    pub metadata: PluginMetadata,
+    pub versions: Vec<VersionInfo>,
+}
+
+/// 发布结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishReport {
+    pub name: String,
+    /// 发布的版本号，最新的在前
+    pub versions: Vec<String>,
+    /// 上传的制品数量
+    pub artifacts: usize,
+    /// 插件源地址
+    pub destination: String,
+}
+
+/// 读取并检查插件清单，计算各制品的校验和与大小
+pub async fn package(manifest_path: &Path) -> Result<Package, PluginError> {
+    let manifest = static_registry::read_manifest(manifest_path).await?;
+    validate(&manifest)?;
+    let dir = manifest_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let plugin = static_registry::index_manifest(&dir, manifest.clone()).await?;
+    Ok(Package {
+        dir,
+        manifest,
+        plugin,
+    })
+}
+
+/// 检查清单中的版本号，同一版本的平台和制品文件名不能重复
+fn validate(manifest: &PluginManifest) -> Result<(), PluginError> {
+    if manifest.releases.is_empty() {
+        return Err(PluginError::ValidationError(format!(
+            "插件 {} 的清单中没有制品",
+            manifest.name
+        )));
+    }
+    let mut platforms = HashSet::new();
+    let mut files = HashSet::new();
+    for release in &manifest.releases {
+        let version = release.version.trim_start_matches('v');
+        Version::parse(version).map_err(|e| {
+            PluginError::ValidationError(format!(
+                "{} 的版本号 {} 无效: {}",
+                manifest.name, release.version, e
+            ))
+        })?;
+        if !platforms.insert((version, platform_of(release))) {
+            return Err(PluginError::ValidationError(format!(
+                "{} {} 的 {} 平台制品重复",
+                manifest.name,
+                version,
+                platform_of(release)
+            )));
+        }
+        if !files.insert((version, file_name(&release.file)?)) {
+            return Err(PluginError::ValidationError(format!(
+                "{} {} 中有多个名为 {} 的制品",
+                manifest.name,
+                version,
+                file_name(&release.file)?
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn platform_of(release: &ManifestRelease) -> &str {
+    if release.platform.is_empty() {
+        "any"
+    } else {
+        &release.platform
+    }
+}
+
+fn file_name(file: &str) -> Result<&str, PluginError> {
+    Path::new(file)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| PluginError::ValidationError(format!("无效的制品路径 {}", file)))
+}
+
+/// 选择发布目标：`url` 指定的插件源，未指定时为配置中第一个 `registry` 或 `static` 插件源
+pub fn select_source<'a>(
+    config: &'a ProjectConfig,
+    url: Option<&str>,
+) -> Result<&'a PluginSource, PluginError> {
+    let publishable = |source: &&PluginSource| {
+        matches!(
+            source.source_type,
+            PluginSourceType::Registry | PluginSourceType::Static
+        )
+    };
+    match url {
+        Some(url) => config
+            .sources
+            .iter()
+            .filter(publishable)
+            .find(|source| source.url.trim_end_matches('/') == url.trim_end_matches('/'))
+            .ok_or_else(|| {
+                PluginError::ConfigError(format!(
+                    "配置中没有地址为 {} 的 registry 或 static 插件源",
+                    url
+                ))
+            }),
+        None => config.sources.iter().find(publishable).ok_or_else(|| {
+            PluginError::ConfigError("配置中没有 registry 或 static 插件源，无法发布".to_string())
+        }),
+    }
+}
+
+/// 把插件包发布到插件源
+pub async fn publish(
+    package: &Package,
+    source: &PluginSource,
+    settings: &GlobalSettings,
+) -> Result<PublishReport, PluginError> {
+    match source.source_type {
+        PluginSourceType::Registry => publish_to_registry(package, source, settings).await?,
+        PluginSourceType::Static => {
+            publish_to_directory(package, &static_dir(&source.url)?).await?
+        }
+        _ => {
+            return Err(PluginError::Unsupported(format!(
+                "只能发布到 registry 或 static 插件源: {}",
+                source.url
+            )))
+        }
+    }
+    Ok(PublishReport {
+        name: package.name().to_string(),
+        versions: package.versions(),
+        artifacts: package.plugin.versions.len(),
+        destination: source.url.clone(),
+    })
+}
+
+/// 上传制品后提交版本列表
+async fn publish_to_registry(
+    package: &Package,
+    source: &PluginSource,
+    settings: &GlobalSettings,
+) -> Result<(), PluginError> {
+    let credentials = source
+        .resolve_auth()
+        .await?
+        .filter(|credentials| credentials.header().is_some())
+        .ok_or_else(|| {
+            PluginError::ConfigError(format!(
+                "发布到 {} 需要在插件源中配置 auth 或 token",
+                source.url
+            ))
+        })?;
+    let client = build_client(
+        Duration::from_secs(settings.download_timeout),
+        settings.proxy.as_deref(),
+        settings.no_proxy.as_deref(),
+    )?;
+    let base = format!(
+        "{}/v1/plugins/{}",
+        source.url.trim_end_matches('/'),
+        package.name()
+    );
+
+    let mut versions = Vec::new();
+    for (info, path) in package.artifacts() {
+        let url = format!(
+            "{}/versions/{}/artifacts/{}",
+            base,
+            info.version,
+            file_name(&info.download_url)?
+        );
+        let body = tokio::fs::read(&path)
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e)))?;
+        let response = credentials
+            .apply(client.put(&url))
+            .header(CHECKSUM_HEADER, info.checksum.clone().unwrap_or_default())
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("上传 {} 失败: {}", url, e)))?;
+        check_response(response, &url).await?;
+
+        let mut published = info.clone();
+        published.download_url = url;
+        versions.push(published);
+    }
+
+    let url = format!("{}/versions", base);
+    let response = credentials
+        .apply(client.post(&url))
+        .json(&PublishRequest {
+            metadata: package.plugin.metadata.clone(),
+            versions,
+        })
+        .send()
+        .await
+        .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?;
+    check_response(response, &url).await
+}
+
+/// 把注册表的拒绝转为对应的错误
+async fn check_response(response: reqwest::Response, url: &str) -> Result<(), PluginError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    let detail = if body.trim().is_empty() {
+        status.to_string()
+    } else {
+        format!("{}: {}", status, body.trim())
+    };
+    Err(match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => PluginError::PermissionDenied(format!(
+            "注册表拒绝了 {}（{}），请检查插件源的 auth 或 token",
+            url, detail
+        )),
+        StatusCode::CONFLICT => {
+            PluginError::ValidationError(format!("注册表中已有相同版本 {}（{}）", url, detail))
+        }
+        _ => PluginError::NetworkError(format!("请求 {} 失败: {}", url, detail)),
+    })
+}
+
+/// `file://` 静态注册表的本地目录
+fn static_dir(url: &str) -> Result<PathBuf, PluginError> {
+    url::Url::parse(url)
+        .ok()
+        .filter(|parsed| parsed.scheme() == "file")
+        .and_then(|parsed| parsed.to_file_path().ok())
+        .ok_or_else(|| {
+            PluginError::Unsupported(format!(
+                "只能直接发布到 file:// 静态注册表，{} 请用 `plm registry build` 生成后自行上传",
+                url
+            ))
+        })
+}
+
+/// 复制制品、合并清单并重新生成静态注册表索引
+async fn publish_to_directory(package: &Package, dir: &Path) -> Result<(), PluginError> {
+    let name = package.name();
+    let manifest_path = dir.join(MANIFESTS_DIR).join(format!("{}.json", name));
+    let mut releases = if tokio::fs::try_exists(&manifest_path).await.unwrap_or(false) {
+        static_registry::read_manifest(&manifest_path)
+            .await?
+            .releases
+    } else {
+        Vec::new()
+    };
+
+    let published = |release: &ManifestRelease| {
+        releases.iter().any(|existing| {
+            existing.version.trim_start_matches('v') == release.version.trim_start_matches('v')
+                && platform_of(existing) == platform_of(release)
+        })
+    };
+    if let Some(release) = package.manifest.releases.iter().find(|r| published(r)) {
+        return Err(PluginError::ValidationError(format!(
+            "{} {} 的 {} 平台制品已发布到 {}",
+            name,
+            release.version,
+            platform_of(release),
+            dir.display()
+        )));
+    }
+
+    for release in &package.manifest.releases {
+        let relative = format!(
+            "{}/{}/{}/{}",
+            ARTIFACTS_DIR,
+            name,
+            release.version.trim_start_matches('v'),
+            file_name(&release.file)?
+        );
+        let source = package.dir.join(&release.file);
+        let target = dir.join(&relative);
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                PluginError::IoError(format!("创建 {} 失败: {}", parent.display(), e))
+            })?;
+        }
+        let staging = TempFileGuard::sibling(&target, "part");
+        tokio::fs::copy(&source, staging.path())
+            .await
+            .map_err(|e| PluginError::IoError(format!("复制 {} 失败: {}", source.display(), e)))?;
+        staging.persist(&target).await?;
+
+        releases.push(ManifestRelease {
+            file: relative,
+            ..release.clone()
+        });
+    }
+
+    let manifest = PluginManifest {
+        releases,
+        ..package.manifest.clone()
+    };
+    if let Some(parent) = manifest_path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| PluginError::IoError(format!("创建 {} 失败: {}", parent.display(), e)))?;
+    }
+    let content = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| PluginError::IoError(format!("序列化插件清单失败: {}", e)))?;
+    tokio::fs::write(&manifest_path, content)
+        .await
+        .map_err(|e| {
+            PluginError::IoError(format!("写入 {} 失败: {}", manifest_path.display(), e))
+        })?;
+    static_registry::build(dir).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn write_manifest(dir: &Path, releases: serde_json::Value) -> PathBuf {
+        tokio::fs::create_dir_all(dir.join("dist")).await.unwrap();
+        tokio::fs::write(dir.join("dist/tool-linux.tar.gz"), b"linux")
+            .await
+            .unwrap();
+        tokio::fs::write(dir.join("dist/tool-macos.tar.gz"), b"macos")
+            .await
+            .unwrap();
+        let path = dir.join("plm-plugin.json");
+        let manifest = serde_json::json!({
+            "name": "tool",
+            "description": "Internal tool",
+            "releases": releases,
+        });
+        tokio::fs::write(&path, manifest.to_string()).await.unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_package_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_manifest(
+            dir.path(),
+            serde_json::json!([
+                { "version": "1.0.0", "platform": "linux-x64", "file": "dist/tool-linux.tar.gz" },
+                { "version": "1.0.0", "platform": "macos-arm64", "file": "dist/tool-macos.tar.gz" }
+            ]),
+        )
+        .await;
+        let packaged = package(&path).await.unwrap();
+        assert_eq!(packaged.name(), "tool");
+        assert_eq!(packaged.versions(), vec!["1.0.0"]);
+        let (info, artifact) = packaged.artifacts().next().unwrap();
+        assert_eq!(info.size, Some(5));
+        assert!(info.checksum.is_some());
+        assert!(artifact.exists());
+
+        for releases in [
+            serde_json::json!([]),
+            serde_json::json!([{ "version": "latest", "file": "dist/tool-linux.tar.gz" }]),
+            serde_json::json!([
+                { "version": "1.0.0", "platform": "linux-x64", "file": "dist/tool-linux.tar.gz" },
+                { "version": "1.0.0", "platform": "linux-x64", "file": "dist/tool-macos.tar.gz" }
+            ]),
+            serde_json::json!([{ "version": "1.0.0", "file": "dist/missing.tar.gz" }]),
+        ] {
+            let path = write_manifest(dir.path(), releases).await;
+            assert!(package(&path).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_to_static_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = dir.path().join("registry");
+        let mut config = ProjectConfig::default_for_project("test-project", ".");
+        let url = url::Url::from_directory_path(&registry)
+            .unwrap()
+            .to_string();
+        config.sources = vec![
+            PluginSource::git_simple("https://example.com/plugins.git"),
+            PluginSource::static_registry(&url),
+        ];
+        let source = select_source(&config, None).unwrap();
+        assert_eq!(source.url, url);
+        assert!(select_source(&config, Some("https://example.com/plugins.git")).is_err());
+
+        let path = write_manifest(
+            &dir.path().join("tool"),
+            serde_json::json!([
+                { "version": "1.0.0", "platform": "linux-x64", "file": "dist/tool-linux.tar.gz" }
+            ]),
+        )
+        .await;
+        let packaged = package(&path).await.unwrap();
+        let report = publish(&packaged, source, &config.global_settings)
+            .await
+            .unwrap();
+        assert_eq!(report.versions, vec!["1.0.0"]);
+        assert_eq!(report.artifacts, 1);
+
+        // 再次发布相同版本被拒绝，新版本追加到已有清单
+        assert!(matches!(
+            publish(&packaged, source, &config.global_settings).await,
+            Err(PluginError::ValidationError(_))
+        ));
+        let path = write_manifest(
+            &dir.path().join("tool"),
+            serde_json::json!([
+                { "version": "1.1.0", "platform": "linux-x64", "file": "dist/tool-linux.tar.gz" }
+            ]),
+        )
+        .await;
+        publish(
+            &package(&path).await.unwrap(),
+            source,
+            &config.global_settings,
+        )
+        .await
+        .unwrap();
+
+        let index = static_registry::load_index(&url, &config.global_settings)
+            .await
+            .unwrap();
+        let plugin = &index.plugins["tool"];
+        assert_eq!(plugin.metadata.version, "1.1.0");
+        assert_eq!(plugin.versions.len(), 2);
+        assert!(plugin.versions[0]
+            .download_url
+            .ends_with("artifacts/tool/1.1.0/tool-linux.tar.gz"));
+    }
+}
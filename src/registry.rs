@@ -0,0 +1,649 @@
+//! PLM 插件注册表客户端
+//!
+//! 批量获取插件元数据和最新版本信息。优先使用注册表的批量接口
+//! （`POST /v1/plugins/batch`，每次最多 `BATCH_SIZE` 个插件），
+//! 注册表不支持批量接口时退回到并发的单个查询（`GET /v1/plugins/<name>`）。
+//! 启用负缓存后，近期查询不到的插件直接计入 `missing`，不再请求注册表。
+//!
+//! `search_sources` 并发搜索配置中的所有 `Registry` 插件源（`GET /v1/plugins?q=<query>`）
+//! 和 `Static` 插件源（读取 `index.json` 后在本地匹配）并合并结果。
+//!
+//! 注册表地址以 `git+` 开头时，查询改为读取 Git 仓库的本地检出，见 `git_registry`。
+
+use crate::auth::Credentials;
+use crate::config::{GlobalSettings, PluginSource, PluginSourceType, ProjectConfig};
+use crate::download::build_client;
+use crate::git_registry::{self, GitIndex};
+use crate::negative_cache::{NegativeCache, NEGATIVE_CACHE_FILE};
+use crate::static_registry;
+use crate::traits::{stream_pages, PluginError, PluginMetadata, VersionInfo, VersionPage};
+use futures_util::stream::{self, Stream, StreamExt};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// 单次批量请求包含的插件数量上限
+const BATCH_SIZE: usize = 100;
+
+/// 注册表中的插件信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    pub metadata: PluginMetadata,
+    /// 最新的稳定版本
+    #[serde(default)]
+    pub latest: Option<VersionInfo>,
+}
+
+impl RegistryEntry {
+    /// 名称、描述或标签包含关键字（不区分大小写）
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        let metadata = &self.metadata;
+        metadata.name.to_lowercase().contains(&query)
+            || metadata.description.to_lowercase().contains(&query)
+            || metadata
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(&query))
+    }
+}
+
+/// 批量查询结果
+#[derive(Debug, Clone, Default)]
+pub struct BatchResult {
+    pub entries: BTreeMap<String, RegistryEntry>,
+    /// 注册表中不存在的插件
+    pub missing: Vec<String>,
+    /// 查询失败的插件及错误信息
+    pub failed: BTreeMap<String, String>,
+}
+
+/// 搜索结果中的一个插件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub name: String,
+    /// 最新的稳定版本
+    pub latest_version: Option<String>,
+    pub description: String,
+    /// 返回该结果的注册表地址
+    pub source: String,
+}
+
+/// 合并后的搜索结果
+#[derive(Debug, Clone, Default)]
+pub struct SearchResults {
+    /// 按名称排序；多个注册表返回同名插件时保留配置中靠前的注册表的结果
+    pub hits: Vec<SearchHit>,
+    /// 搜索失败的注册表及错误信息
+    pub failed: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    plugins: Vec<RegistryEntry>,
+}
+
+#[derive(Serialize)]
+struct BatchRequest<'a> {
+    names: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct BatchResponse {
+    #[serde(default)]
+    plugins: BTreeMap<String, RegistryEntry>,
+}
+
+/// 注册表客户端
+pub struct RegistryClient {
+    client: reqwest::Client,
+    base_url: String,
+    max_concurrent: usize,
+    /// 负缓存文件路径和有效期（秒）
+    negative_cache: Option<(PathBuf, u64)>,
+    /// 忽略负缓存中的记录，重新查询
+    refresh: bool,
+    /// Git 仓库注册表的检出位置
+    cache_dir: PathBuf,
+    /// 已更新的 Git 仓库检出，每个客户端只更新一次
+    git_index: OnceCell<GitIndex>,
+    /// 访问注册表的凭据
+    credentials: Option<Credentials>,
+}
+
+impl RegistryClient {
+    /// 创建注册表客户端
+    pub fn new(base_url: &str, timeout: Duration) -> Result<Self, PluginError> {
+        Ok(Self {
+            client: build_client(timeout, None, None)?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            max_concurrent: 4,
+            negative_cache: None,
+            refresh: false,
+            cache_dir: GlobalSettings::default().cache_path(),
+            git_index: OnceCell::new(),
+            credentials: None,
+        })
+    }
+
+    /// 根据全局设置创建注册表客户端，单个查询的并发数取自 `parallel_downloads`
+    ///
+    /// `negative_cache_ttl` 大于 0 时启用负缓存，缓存文件位于 `cache_dir` 下
+    pub fn from_settings(settings: &GlobalSettings) -> Result<Self, PluginError> {
+        let client = build_client(
+            Duration::from_secs(settings.download_timeout),
+            settings.proxy.as_deref(),
+            settings.no_proxy.as_deref(),
+        )?;
+        Ok(Self {
+            client,
+            base_url: settings.registry_url.trim_end_matches('/').to_string(),
+            max_concurrent: 4,
+            negative_cache: None,
+            refresh: false,
+            cache_dir: settings.cache_path(),
+            git_index: OnceCell::new(),
+            credentials: None,
+        }
+        .with_concurrency(settings.parallel_downloads as usize)
+        .with_negative_cache(
+            settings.cache_path().join(NEGATIVE_CACHE_FILE),
+            settings.negative_cache_ttl,
+        ))
+    }
+
+    /// 使用其他注册表地址（如配置中的注册表插件源）
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self.git_index = OnceCell::new();
+        self
+    }
+
+    /// 设置访问注册表的凭据（见 `PluginSource::resolve_auth`）
+    pub fn with_credentials(mut self, credentials: Option<Credentials>) -> Self {
+        self.credentials = credentials;
+        self.git_index = OnceCell::new();
+        self
+    }
+
+    /// 为请求加上凭据
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.credentials {
+            Some(credentials) => credentials.apply(request),
+            None => request,
+        }
+    }
+
+    /// 注册表地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// 设置退回单个查询时的最大并发数
+    pub fn with_concurrency(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent.max(1);
+        self
+    }
+
+    /// 启用负缓存，`ttl` 为 0 时不缓存
+    pub fn with_negative_cache(mut self, path: PathBuf, ttl: u64) -> Self {
+        self.negative_cache = (ttl > 0).then_some((path, ttl));
+        self
+    }
+
+    /// 跳过负缓存中的记录，重新查询所有插件（查询结果仍会更新缓存）
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// 查询单个插件，插件不存在时返回 `NotFound`
+    pub async fn fetch_plugin(&self, name: &str) -> Result<RegistryEntry, PluginError> {
+        if let Some(index) = self.git_index().await? {
+            return Ok(index.plugin(name).await?.entry());
+        }
+        let url = format!("{}/v1/plugins/{}", self.base_url, name);
+        let response = send(self.authorize(self.client.get(&url)), &url).await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(PluginError::NotFound(name.to_string()));
+        }
+        let response = response
+            .error_for_status()
+            .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?;
+        response
+            .json()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("解析 {} 的响应失败: {}", url, e)))
+    }
+
+    /// 按关键字搜索插件
+    pub async fn search(&self, query: &str) -> Result<Vec<RegistryEntry>, PluginError> {
+        if let Some(index) = self.git_index().await? {
+            return index.search(query).await;
+        }
+        let url = format!("{}/v1/plugins", self.base_url);
+        let request = self.authorize(self.client.get(&url)).query(&[("q", query)]);
+        let response = send(request, &url).await?;
+        let body: SearchResponse = response
+            .error_for_status()
+            .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?
+            .json()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("解析 {} 的响应失败: {}", url, e)))?;
+        Ok(body.plugins)
+    }
+
+    /// 分页查询插件的可用版本，`cursor` 为上一页返回的 `next_cursor`
+    pub async fn fetch_versions_page(
+        &self,
+        name: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<VersionPage, PluginError> {
+        if let Some(index) = self.git_index().await? {
+            let offset = cursor.and_then(|c| c.parse().ok()).unwrap_or(0);
+            let versions = index.plugin(name).await?.versions;
+            return Ok(VersionPage::from_offset(versions, offset, limit));
+        }
+        let url = format!("{}/v1/plugins/{}/versions", self.base_url, name);
+        let mut request = self
+            .authorize(self.client.get(&url))
+            .query(&[("limit", limit.to_string())]);
+        if let Some(cursor) = cursor {
+            request = request.query(&[("cursor", cursor)]);
+        }
+
+        let response = send(request, &url).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(PluginError::NotFound(name.to_string()));
+        }
+        response
+            .error_for_status()
+            .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?
+            .json()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("解析 {} 的响应失败: {}", url, e)))
+    }
+
+    /// 逐页获取插件的所有可用版本，只在消费完当前页后才请求下一页
+    pub fn versions_stream<'a>(
+        &'a self,
+        name: &'a str,
+        page_size: usize,
+    ) -> impl Stream<Item = Result<VersionInfo, PluginError>> + 'a {
+        stream_pages(move |cursor| async move {
+            self.fetch_versions_page(name, cursor.as_deref(), page_size)
+                .await
+        })
+    }
+
+    /// 批量查询插件
+    ///
+    /// 单个插件查询失败不会中断整个批次，失败信息记录在 `BatchResult::failed` 中
+    pub async fn fetch_batch(&self, names: &[String]) -> Result<BatchResult, PluginError> {
+        let mut result = BatchResult::default();
+
+        let negative = match &self.negative_cache {
+            Some((path, ttl)) => {
+                let mut cache = NegativeCache::load(path).await;
+                cache.prune(*ttl);
+                Some((cache, path, *ttl))
+            }
+            None => None,
+        };
+        let (cached, pending): (Vec<String>, Vec<String>) =
+            names.iter().cloned().partition(|name| match &negative {
+                Some((cache, _, ttl)) => {
+                    !self.refresh && cache.is_missing(name, &self.base_url, *ttl)
+                }
+                None => false,
+            });
+        if !cached.is_empty() {
+            log::debug!("负缓存中的插件跳过注册表查询: {}", cached.join(", "));
+        }
+        result.missing.extend(cached);
+
+        for chunk in pending.chunks(BATCH_SIZE) {
+            match self.post_batch(chunk).await? {
+                Some(mut entries) => {
+                    for name in chunk {
+                        match entries.remove(name) {
+                            Some(entry) => {
+                                result.entries.insert(name.clone(), entry);
+                            }
+                            None => result.missing.push(name.clone()),
+                        }
+                    }
+                }
+                None => {
+                    log::debug!("注册表 {} 不支持批量查询，改为逐个查询", self.base_url);
+                    self.fetch_each(chunk, &mut result).await;
+                }
+            }
+        }
+        result.missing.sort();
+
+        if let Some((mut cache, path, _)) = negative {
+            for name in result.entries.keys() {
+                cache.remove(name);
+            }
+            // 只记录本次实际查询过的插件，避免延长已缓存记录的有效期
+            for name in result.missing.iter().filter(|name| pending.contains(name)) {
+                cache.record_missing(name, &self.base_url);
+            }
+            // 负缓存只是优化，写入失败不影响查询结果
+            if let Err(e) = cache.save(path).await {
+                log::warn!("{}", e);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 调用批量接口，注册表不支持时返回 `None`
+    async fn post_batch(
+        &self,
+        names: &[String],
+    ) -> Result<Option<BTreeMap<String, RegistryEntry>>, PluginError> {
+        // Git 仓库注册表逐个读取本地文件即可
+        if git_registry::is_git_url(&self.base_url) {
+            return Ok(None);
+        }
+        let url = format!("{}/v1/plugins/batch", self.base_url);
+        let request = self
+            .authorize(self.client.post(&url))
+            .json(&BatchRequest { names });
+        let response = send(request, &url).await?;
+
+        if matches!(
+            response.status(),
+            StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+        ) {
+            return Ok(None);
+        }
+        let response = response
+            .error_for_status()
+            .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?;
+        let body: BatchResponse = response
+            .json()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("解析 {} 的响应失败: {}", url, e)))?;
+        Ok(Some(body.plugins))
+    }
+
+    /// 地址为 Git 仓库时克隆或更新检出后返回，其他注册表返回 `None`
+    async fn git_index(&self) -> Result<Option<&GitIndex>, PluginError> {
+        if !git_registry::is_git_url(&self.base_url) {
+            return Ok(None);
+        }
+        self.git_index
+            .get_or_try_init(|| async {
+                let index = GitIndex::new(&self.base_url, &self.cache_dir)
+                    .with_credentials(self.credentials.as_ref());
+                index.update().await?;
+                Ok(index)
+            })
+            .await
+            .map(Some)
+    }
+
+    async fn fetch_each(&self, names: &[String], result: &mut BatchResult) {
+        let responses: Vec<(String, Result<RegistryEntry, PluginError>)> = stream::iter(names)
+            .map(|name| async move { (name.clone(), self.fetch_plugin(name).await) })
+            .buffer_unordered(self.max_concurrent)
+            .collect()
+            .await;
+
+        for (name, response) in responses {
+            match response {
+                Ok(entry) => {
+                    result.entries.insert(name, entry);
+                }
+                Err(PluginError::NotFound(_)) => result.missing.push(name),
+                Err(e) => {
+                    result.failed.insert(name, e.to_string());
+                }
+            }
+        }
+    }
+}
+
+/// 发送注册表请求，启用 `metrics` 特性时记录请求延迟
+async fn send(request: RequestBuilder, url: &str) -> Result<Response, PluginError> {
+    #[cfg(feature = "metrics")]
+    let started = std::time::Instant::now();
+    let response = request
+        .send()
+        .await
+        .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)));
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_registry_request(started.elapsed());
+    response
+}
+
+/// 配置中的注册表地址：所有 `Registry` 类型的插件源，没有时使用 `registry_url`
+pub fn registry_urls(config: &ProjectConfig) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for source in &config.sources {
+        let url = source.url.trim_end_matches('/').to_string();
+        if matches!(source.source_type, PluginSourceType::Registry) && !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    if urls.is_empty() {
+        urls.push(
+            config
+                .global_settings
+                .registry_url
+                .trim_end_matches('/')
+                .to_string(),
+        );
+    }
+    urls
+}
+
+/// 配置中地址为 `url` 的插件源的凭据，没有对应的插件源时返回 None
+pub async fn source_credentials(
+    config: &ProjectConfig,
+    url: &str,
+) -> Result<Option<Credentials>, PluginError> {
+    let url = url.trim_end_matches('/');
+    match config
+        .sources
+        .iter()
+        .find(|source| source.url.trim_end_matches('/') == url)
+    {
+        Some(source) => source.resolve_auth().await,
+        None => Ok(None),
+    }
+}
+
+/// 并发搜索配置中的所有注册表（包括静态文件注册表）并合并结果
+pub async fn search_sources(
+    config: &ProjectConfig,
+    query: &str,
+) -> Result<SearchResults, PluginError> {
+    let base = RegistryClient::from_settings(&config.global_settings)?;
+    let urls = registry_urls(config);
+    let mut clients = Vec::new();
+    for url in &urls {
+        clients.push(RegistryClient {
+            client: base.client.clone(),
+            base_url: url.clone(),
+            max_concurrent: base.max_concurrent,
+            negative_cache: None,
+            refresh: false,
+            cache_dir: base.cache_dir.clone(),
+            git_index: OnceCell::new(),
+            credentials: source_credentials(config, url).await?,
+        });
+    }
+    let responses =
+        futures_util::future::join_all(clients.iter().map(|client| client.search(query))).await;
+
+    // 静态文件注册表没有搜索接口，读取索引后在本地匹配
+    let static_sources: Vec<&PluginSource> = config
+        .sources
+        .iter()
+        .filter(|source| matches!(source.source_type, PluginSourceType::Static))
+        .collect();
+    let static_responses =
+        futures_util::future::join_all(static_sources.iter().map(|source| async move {
+            static_registry::load_source_index(source, &config.global_settings)
+                .await
+                .map(|index| index.search(query))
+        }))
+        .await;
+    let static_urls = static_sources
+        .iter()
+        .map(|source| source.url.trim_end_matches('/').to_string());
+
+    Ok(merge_search_results(
+        urls.into_iter()
+            .zip(responses)
+            .chain(static_urls.zip(static_responses))
+            .collect(),
+    ))
+}
+
+/// 按注册表顺序合并搜索结果，同名插件保留先出现的结果
+fn merge_search_results(
+    responses: Vec<(String, Result<Vec<RegistryEntry>, PluginError>)>,
+) -> SearchResults {
+    let mut results = SearchResults::default();
+    let mut hits: BTreeMap<String, SearchHit> = BTreeMap::new();
+    for (source, response) in responses {
+        match response {
+            Ok(entries) => {
+                for entry in entries {
+                    hits.entry(entry.metadata.name.clone())
+                        .or_insert_with(|| SearchHit {
+                            name: entry.metadata.name,
+                            latest_version: entry.latest.map(|v| v.version),
+                            description: entry.metadata.description,
+                            source: source.clone(),
+                        });
+                }
+            }
+            Err(e) => {
+                results.failed.insert(source, e.to_string());
+            }
+        }
+    }
+    results.hits = hits.into_values().collect();
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_batch_response() {
+        let body = r#"{
+            "plugins": {
+                "node": {
+                    "metadata": {
+                        "name": "node",
+                        "version": "1.0.0",
+                        "description": "Node.js runtime",
+                        "author": "PLM",
+                        "homepage": null,
+                        "repository": null,
+                        "supported_platforms": ["linux"],
+                        "tags": [],
+                        "dependencies": [],
+                        "min_plm_version": null
+                    },
+                    "latest": {
+                        "version": "20.1.0",
+                        "platform": "linux-x64",
+                        "download_url": "https://example.com/node-20.1.0.tar.gz",
+                        "checksum": null,
+                        "release_date": null,
+                        "prerelease": false
+                    }
+                }
+            }
+        }"#;
+
+        let response: BatchResponse = serde_json::from_str(body).unwrap();
+        let node = &response.plugins["node"];
+        assert_eq!(node.metadata.description, "Node.js runtime");
+        assert_eq!(node.latest.as_ref().unwrap().version, "20.1.0");
+    }
+
+    #[test]
+    fn test_from_settings_trims_base_url() {
+        let settings = GlobalSettings {
+            registry_url: "https://registry.example.com/".to_string(),
+            parallel_downloads: 0,
+            ..GlobalSettings::default()
+        };
+        let client = RegistryClient::from_settings(&settings).unwrap();
+        assert_eq!(client.base_url, "https://registry.example.com");
+        assert_eq!(client.max_concurrent, 1);
+    }
+
+    #[test]
+    fn test_merge_search_results() {
+        let entry = |name: &str, description: &str| RegistryEntry {
+            metadata: PluginMetadata {
+                name: name.to_string(),
+                description: description.to_string(),
+                ..PluginMetadata::default()
+            },
+            latest: None,
+        };
+        let results = merge_search_results(vec![
+            (
+                "https://a.example.com".to_string(),
+                Ok(vec![entry("node", "from a"), entry("deno", "from a")]),
+            ),
+            (
+                "https://b.example.com".to_string(),
+                Ok(vec![entry("node", "from b"), entry("bun", "from b")]),
+            ),
+            (
+                "https://c.example.com".to_string(),
+                Err(PluginError::NetworkError("timeout".to_string())),
+            ),
+        ]);
+
+        let names: Vec<&str> = results.hits.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["bun", "deno", "node"]);
+        assert_eq!(results.hits[2].description, "from a");
+        assert_eq!(results.hits[2].source, "https://a.example.com");
+        assert!(results.failed.contains_key("https://c.example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_negative_cache_skips_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(NEGATIVE_CACHE_FILE);
+        // 不可达的注册表地址：只有命中负缓存时查询才能成功
+        let base_url = "http://127.0.0.1:9";
+
+        let mut cache = NegativeCache::default();
+        cache.record_missing("nodee", base_url);
+        cache.save(&path).await.unwrap();
+
+        let client = RegistryClient::new(base_url, Duration::from_secs(1))
+            .unwrap()
+            .with_negative_cache(path.clone(), 300);
+        let result = client.fetch_batch(&["nodee".to_string()]).await.unwrap();
+        assert_eq!(result.missing, vec!["nodee".to_string()]);
+
+        // --refresh 时重新查询注册表
+        assert!(client
+            .with_refresh(true)
+            .fetch_batch(&["nodee".to_string()])
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,547 @@
+//! 内置的注册表服务
+//!
+//! `plm registry serve <dir>`（需要以 `--features registry-server` 构建）以 `plm mirror sync`
+//! 的目录布局（见 `crate::mirror`）提供注册表客户端使用的 HTTP 接口，适合在内网中自托管：
+//!
+//! ```text
+//! GET  /v1/plugins?q=<query>                                  搜索
+//! POST /v1/plugins/batch                                      批量查询
+//! GET  /v1/plugins/<name>                                     插件信息和最新稳定版本
+//! GET  /v1/plugins/<name>/versions?limit=<n>&cursor=<c>       分页的版本列表
+//! GET  /artifacts/<name>/<version>/<file>                     制品
+//! PUT  /v1/plugins/<name>/versions/<version>/artifacts/<file> 上传制品（`plm publish`）
+//! POST /v1/plugins/<name>/versions                            发布版本（`plm publish`）
+//! GET  /metrics                                               Prometheus 指标（需要 `metrics` 特性）
+//! ```
+//!
+//! 设置了发布令牌（`--token` 或 `PLM_REGISTRY_TOKEN`）时才接受发布，请求需带
+//! `Authorization: Bearer <token>`；已发布的版本不能覆盖。发布后重新生成插件列表和 `SHA256SUMS`，
+//! 目录仍可以交给静态文件服务器发布。
+//!
+//! 上传的制品边接收边写入临时文件，超过上传大小限制（默认 1 GiB，见 `with_upload_limit`）时
+//! 以 413 拒绝；其他请求体使用 axum 的默认限制。
+
+use crate::download::verify_checksum;
+use crate::mirror::{Mirror, ARTIFACTS_DIR};
+use crate::publish::{PublishRequest, CHECKSUM_HEADER};
+use crate::registry::RegistryEntry;
+use crate::temp::TempFileGuard;
+use crate::traits::{PluginError, VersionPage};
+use crate::version::Version;
+use axum::extract::{BodyStream, Path as UrlPath, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// 分页查询未指定 `limit` 时每页的版本数
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// 默认的单个制品上传大小限制（1 GiB）
+pub const DEFAULT_UPLOAD_LIMIT: u64 = 1 << 30;
+
+/// 注册表服务
+pub struct RegistryServer {
+    dir: PathBuf,
+    base_url: Option<String>,
+    token: Option<String>,
+    upload_limit: u64,
+}
+
+struct ServerState {
+    mirror: Mirror,
+    token: Option<String>,
+    upload_limit: u64,
+    /// 发布请求逐个处理
+    publish_lock: Mutex<()>,
+}
+
+impl RegistryServer {
+    /// 提供 `dir` 中的注册表
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            base_url: None,
+            token: None,
+            upload_limit: DEFAULT_UPLOAD_LIMIT,
+        }
+    }
+
+    /// 写入版本信息的下载地址前缀，默认为 `http://<监听地址>`；在反向代理后运行时设置为对外地址
+    pub fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// 发布令牌，未设置时拒绝所有发布请求
+    pub fn with_publish_token(mut self, token: Option<String>) -> Self {
+        self.token = token.filter(|token| !token.is_empty());
+        self
+    }
+
+    /// 单个制品上传的最大字节数
+    pub fn with_upload_limit(mut self, limit: u64) -> Self {
+        self.upload_limit = limit;
+        self
+    }
+
+    /// 绑定 `addr`，返回实际监听的地址和运行服务的 future
+    pub fn bind(
+        self,
+        addr: SocketAddr,
+    ) -> Result<(SocketAddr, impl Future<Output = Result<(), PluginError>>), PluginError> {
+        let listener = std::net::TcpListener::bind(addr)
+            .map_err(|e| PluginError::NetworkError(format!("监听 {} 失败: {}", addr, e)))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| PluginError::NetworkError(format!("监听 {} 失败: {}", addr, e)))?;
+        let base_url = self
+            .base_url
+            .unwrap_or_else(|| format!("http://{}", local_addr));
+        let state = Arc::new(ServerState {
+            mirror: Mirror::new(self.dir, &base_url),
+            token: self.token,
+            upload_limit: self.upload_limit,
+            publish_lock: Mutex::new(()),
+        });
+        let server = axum::Server::from_tcp(listener)
+            .map_err(|e| PluginError::NetworkError(format!("监听 {} 失败: {}", addr, e)))?
+            .serve(router(state).into_make_service());
+        Ok((local_addr, async move {
+            server
+                .await
+                .map_err(|e| PluginError::NetworkError(format!("注册表服务出错: {}", e)))
+        }))
+    }
+
+    /// 在 `addr` 上运行服务，直到出错
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), PluginError> {
+        let (_, server) = self.bind(addr)?;
+        server.await
+    }
+}
+
+fn router(state: Arc<ServerState>) -> Router {
+    let router = Router::new()
+        .route("/v1/plugins", get(search))
+        .route("/v1/plugins/batch", post(batch))
+        .route("/v1/plugins/:name", get(plugin))
+        .route("/v1/plugins/:name/versions", get(versions).post(publish))
+        .route(
+            "/v1/plugins/:name/versions/:version/artifacts/:file",
+            put(upload),
+        )
+        .route("/artifacts/*path", get(artifact));
+    #[cfg(feature = "metrics")]
+    let router = router.route("/metrics", get(crate::metrics::handler));
+    router.with_state(state)
+}
+
+/// 错误响应，响应体为 `{"error": "..."}`
+struct ApiError(StatusCode, String);
+
+impl From<PluginError> for ApiError {
+    fn from(error: PluginError) -> Self {
+        let status = match &error {
+            PluginError::NotFound(_) => StatusCode::NOT_FOUND,
+            PluginError::ValidationError(_) | PluginError::ChecksumMismatch { .. } => {
+                StatusCode::BAD_REQUEST
+            }
+            PluginError::PermissionDenied(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    #[serde(default)]
+    q: String,
+}
+
+async fn search(
+    State(state): State<Arc<ServerState>>,
+    Query(query): Query<SearchQuery>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let plugins: Vec<RegistryEntry> = state
+        .mirror
+        .load_entries()
+        .await?
+        .into_iter()
+        .filter(|entry| entry.matches(&query.q))
+        .collect();
+    Ok(Json(serde_json::json!({ "plugins": plugins })))
+}
+
+#[derive(Deserialize)]
+struct BatchRequest {
+    names: Vec<String>,
+}
+
+async fn batch(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<BatchRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let mut plugins = BTreeMap::new();
+    for name in request.names {
+        if crate::id::PluginId::new(&name).is_err() {
+            continue;
+        }
+        if let Some(entry) = state.mirror.load_entry(&name).await? {
+            plugins.insert(name, entry);
+        }
+    }
+    Ok(Json(serde_json::json!({ "plugins": plugins })))
+}
+
+async fn plugin(
+    State(state): State<Arc<ServerState>>,
+    UrlPath(name): UrlPath<String>,
+) -> ApiResult<Json<RegistryEntry>> {
+    crate::id::PluginId::new(&name)?;
+    match state.mirror.load_entry(&name).await? {
+        Some(entry) => Ok(Json(entry)),
+        None => Err(PluginError::NotFound(name).into()),
+    }
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    limit: Option<usize>,
+    cursor: Option<String>,
+}
+
+async fn versions(
+    State(state): State<Arc<ServerState>>,
+    UrlPath(name): UrlPath<String>,
+    Query(query): Query<PageQuery>,
+) -> ApiResult<Json<VersionPage>> {
+    crate::id::PluginId::new(&name)?;
+    if state.mirror.load_entry(&name).await?.is_none() {
+        return Err(PluginError::NotFound(name).into());
+    }
+    let offset = query
+        .cursor
+        .as_deref()
+        .and_then(|cursor| cursor.parse().ok())
+        .unwrap_or(0);
+    Ok(Json(VersionPage::from_offset(
+        state.mirror.load_versions(&name).await?,
+        offset,
+        query.limit.unwrap_or(DEFAULT_PAGE_SIZE),
+    )))
+}
+
+async fn artifact(
+    State(state): State<Arc<ServerState>>,
+    UrlPath(path): UrlPath<String>,
+) -> ApiResult<Response> {
+    let relative = Path::new(&path);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(PluginError::NotFound(path).into());
+    }
+    let path = state.mirror.dir().join(ARTIFACTS_DIR).join(relative);
+    let content = tokio::fs::read(&path)
+        .await
+        .map_err(|_| PluginError::NotFound(path.display().to_string()))?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/octet-stream")],
+        content,
+    )
+        .into_response())
+}
+
+/// 请求带有发布令牌
+fn authorize(state: &ServerState, headers: &HeaderMap) -> ApiResult<()> {
+    let Some(token) = &state.token else {
+        return Err(ApiError(
+            StatusCode::FORBIDDEN,
+            "注册表未启用发布，请以 --token 或 PLM_REGISTRY_TOKEN 设置发布令牌".to_string(),
+        ));
+    };
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    // 逐字节比较耗时与令牌内容无关，避免通过响应时间猜测令牌
+    if provided.is_some_and(|provided| bool::from(provided.as_bytes().ct_eq(token.as_bytes()))) {
+        Ok(())
+    } else {
+        Err(ApiError(
+            StatusCode::UNAUTHORIZED,
+            "发布令牌无效".to_string(),
+        ))
+    }
+}
+
+/// 制品在注册表目录中的相对路径，检查名称、版本号和文件名
+fn artifact_relative(name: &str, version: &str, file: &str) -> ApiResult<String> {
+    crate::id::PluginId::new(name)?;
+    Version::parse(version)?;
+    let mut components = Path::new(file).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return Err(PluginError::ValidationError(format!("无效的制品文件名 {}", file)).into());
+    }
+    Ok(format!("{}/{}/{}/{}", ARTIFACTS_DIR, name, version, file))
+}
+
+/// 已发布的版本不能覆盖
+async fn ensure_unpublished(state: &ServerState, name: &str, version: &str) -> ApiResult<()> {
+    let published = state.mirror.load_versions(name).await?;
+    if published
+        .iter()
+        .any(|info| crate::core::same_version(&info.version, version))
+    {
+        return Err(ApiError(
+            StatusCode::CONFLICT,
+            format!("{} {} 已发布", name, version),
+        ));
+    }
+    Ok(())
+}
+
+async fn upload(
+    State(state): State<Arc<ServerState>>,
+    UrlPath((name, version, file)): UrlPath<(String, String, String)>,
+    headers: HeaderMap,
+    mut body: BodyStream,
+) -> ApiResult<StatusCode> {
+    authorize(&state, &headers)?;
+    let relative = artifact_relative(&name, &version, &file)?;
+    let too_large = || {
+        ApiError(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("制品 {} 超过上传大小限制 {} 字节", file, state.upload_limit),
+        )
+    };
+    let declared = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if declared.is_some_and(|length| length > state.upload_limit) {
+        return Err(too_large());
+    }
+    let _guard = state.publish_lock.lock().await;
+    ensure_unpublished(&state, &name, &version).await?;
+
+    let target = state.mirror.dir().join(&relative);
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| PluginError::IoError(format!("创建 {} 失败: {}", parent.display(), e)))?;
+    }
+    let staging = TempFileGuard::sibling(&target, "part");
+    let write_error =
+        |e: std::io::Error| PluginError::IoError(format!("写入 {} 失败: {}", target.display(), e));
+    let mut output = tokio::fs::File::create(staging.path())
+        .await
+        .map_err(write_error)?;
+    let mut received = 0u64;
+    while let Some(chunk) = body.next().await {
+        let chunk =
+            chunk.map_err(|e| PluginError::NetworkError(format!("接收 {} 失败: {}", file, e)))?;
+        received += chunk.len() as u64;
+        if received > state.upload_limit {
+            return Err(too_large());
+        }
+        output.write_all(&chunk).await.map_err(write_error)?;
+    }
+    output.flush().await.map_err(write_error)?;
+    drop(output);
+    if let Some(checksum) = headers
+        .get(CHECKSUM_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+    {
+        verify_checksum(staging.path(), checksum).await?;
+    }
+    staging.persist(&target).await?;
+    Ok(StatusCode::CREATED)
+}
+
+async fn publish(
+    State(state): State<Arc<ServerState>>,
+    UrlPath(name): UrlPath<String>,
+    headers: HeaderMap,
+    Json(request): Json<PublishRequest>,
+) -> ApiResult<StatusCode> {
+    authorize(&state, &headers)?;
+    if request.metadata.name != name {
+        return Err(PluginError::ValidationError(format!(
+            "请求中的插件名称 {} 与地址中的 {} 不一致",
+            request.metadata.name, name
+        ))
+        .into());
+    }
+    if request.versions.is_empty() {
+        return Err(PluginError::ValidationError(format!("{} 没有要发布的版本", name)).into());
+    }
+    let _guard = state.publish_lock.lock().await;
+
+    let mut versions = state.mirror.load_versions(&name).await?;
+    let mut published = Vec::new();
+    for mut info in request.versions {
+        ensure_unpublished(&state, &name, &info.version).await?;
+        let file = crate::download::file_name_from_url(&info.download_url)?;
+        let relative = artifact_relative(&name, &info.version, &file)?;
+        let path = state.mirror.dir().join(&relative);
+        if tokio::fs::metadata(&path).await.is_err() {
+            return Err(PluginError::ValidationError(format!(
+                "{} {} 的制品 {} 尚未上传",
+                name, info.version, file
+            ))
+            .into());
+        }
+        if let Some(checksum) = &info.checksum {
+            verify_checksum(&path, checksum).await?;
+        }
+        info.download_url = format!("{}/{}", state.mirror.base_url(), relative);
+        info.mirror_urls.clear();
+        published.push(info);
+    }
+    versions.extend(published);
+    state
+        .mirror
+        .write_plugin(&request.metadata, versions)
+        .await?;
+    state.mirror.write_index().await?;
+    Ok(StatusCode::CREATED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{GlobalSettings, PluginSource};
+    use crate::publish;
+    use crate::registry::RegistryClient;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_publish_and_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let (addr, server) = RegistryServer::new(dir.path().join("registry"))
+            .with_publish_token(Some("secret".to_string()))
+            .bind("127.0.0.1:0".parse().unwrap())
+            .unwrap();
+        tokio::spawn(server);
+        let url = format!("http://{}", addr);
+
+        let project = dir.path().join("tool");
+        tokio::fs::create_dir_all(project.join("dist"))
+            .await
+            .unwrap();
+        tokio::fs::write(project.join("dist/tool.tar.gz"), b"tool")
+            .await
+            .unwrap();
+        let manifest = serde_json::json!({
+            "name": "tool",
+            "description": "Internal tool",
+            "releases": [{ "version": "1.0.0", "file": "dist/tool.tar.gz" }]
+        });
+        tokio::fs::write(project.join("plm-plugin.json"), manifest.to_string())
+            .await
+            .unwrap();
+        let package = publish::package(&project.join("plm-plugin.json"))
+            .await
+            .unwrap();
+
+        let settings = GlobalSettings::default();
+        let mut source = PluginSource::registry(&url);
+        source.token = Some("wrong".to_string());
+        assert!(matches!(
+            publish::publish(&package, &source, &settings).await,
+            Err(PluginError::PermissionDenied(_))
+        ));
+        source.token = Some("secret".to_string());
+        publish::publish(&package, &source, &settings)
+            .await
+            .unwrap();
+        assert!(matches!(
+            publish::publish(&package, &source, &settings).await,
+            Err(PluginError::ValidationError(_))
+        ));
+
+        let client = RegistryClient::new(&url, Duration::from_secs(10)).unwrap();
+        let entry = client.fetch_plugin("tool").await.unwrap();
+        assert_eq!(entry.latest.unwrap().version, "1.0.0");
+        assert_eq!(client.search("internal").await.unwrap().len(), 1);
+        assert!(matches!(
+            client.fetch_plugin("missing").await,
+            Err(PluginError::NotFound(_))
+        ));
+        let page = client.fetch_versions_page("tool", None, 10).await.unwrap();
+        let artifact = reqwest::get(&page.versions[0].download_url)
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        assert_eq!(&artifact[..], b"tool");
+    }
+
+    #[tokio::test]
+    async fn test_upload_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = dir.path().join("registry");
+        let (addr, server) = RegistryServer::new(&registry)
+            .with_publish_token(Some("secret".to_string()))
+            .with_upload_limit(4)
+            .bind("127.0.0.1:0".parse().unwrap())
+            .unwrap();
+        tokio::spawn(server);
+        let url = format!(
+            "http://{}/v1/plugins/tool/versions/1.0.0/artifacts/tool.tar.gz",
+            addr
+        );
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+
+        // 分块发送（没有 Content-Length）时在接收过程中检查大小
+        let chunks =
+            futures_util::stream::iter(["tool", "-too-large"].map(Ok::<_, std::io::Error>));
+        let response = client
+            .put(&url)
+            .bearer_auth("secret")
+            .body(reqwest::Body::wrap_stream(chunks))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let artifact = registry.join(ARTIFACTS_DIR).join("tool/1.0.0/tool.tar.gz");
+        assert!(!artifact.exists());
+
+        let response = client
+            .put(&url)
+            .bearer_auth("secret")
+            .body("tool")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(std::fs::read(&artifact).unwrap(), b"tool");
+    }
+}
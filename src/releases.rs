@@ -0,0 +1,783 @@
+//! PLM 代码托管平台的发布（Releases）插件源
+//!
+//! 大多数开发工具把各平台的制品作为发布附件上传。`github` 和 `gitlab` 插件源
+//! （见 `crate::github`、`crate::gitlab`）列出仓库的发布，按插件设置中的文件名模式为目标平台选择附件：
+//!
+//! ```json
+//! "ripgrep": {
+//!   "source": { "type": "github", "url": "https://github.com/BurntSushi/ripgrep" },
+//!   "settings": {
+//!     "asset": "ripgrep-{version}-{arch}-{os}.{ext}",
+//!     "entry_points": ["ripgrep-{version}-{arch}-{os}/rg{exe}"],
+//!     "os": { "linux": "unknown-linux-musl" }
+//!   }
+//! }
+//! ```
+//!
+//! 模式中可以使用以下占位符，`*` 匹配任意字符，匹配不区分大小写：
+//!
+//! - `{version}`：版本号（不带 `v` 前缀），`{tag}`：发布的标签
+//! - `{os}`：目标操作系统的常见写法（`linux`、`darwin`/`macos`/`apple-darwin`、`windows`/`win64` 等），
+//!   可在 `os` 设置中指定唯一写法
+//! - `{arch}`：目标架构的常见写法（`x86_64`/`amd64`/`x64`、`aarch64`/`arm64` 等），可在 `arch` 设置中指定
+//! - `{ext}`：`tar.gz`、`tgz` 或 `zip`，`{exe}`：可选的 `.exe`
+//!
+//! 未设置 `asset` 时，选择文件名中包含目标操作系统、且架构相符（或不含架构）的压缩包或可执行文件，
+//! 跳过校验和、签名和安装包附件。`entry_points` 中的 `{os}`/`{arch}` 替换为附件名中实际匹配到的写法。
+//!
+//! 附件的 SHA-256 取自托管平台提供的摘要；设置 `checksums`（如 `SHA256SUMS` 或 `{asset}.sha256`）时
+//! 从该附件中读取。
+
+use crate::config::{current_platform, GlobalSettings, PluginConfig, PluginSource};
+use crate::core::same_version;
+use crate::declarative::parse_checksum;
+use crate::download::{file_name_from_url, Downloader};
+use crate::target::TargetPlatform;
+use crate::traits::{
+    ArchiveFormat, Capability, InstallOptions, Plugin, PluginError, PluginMetadata, PluginStatus,
+    VersionInfo,
+};
+use crate::version::Version;
+use async_trait::async_trait;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// 一个发布
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    /// 发布的标签，如 `v1.2.0`
+    pub tag: String,
+    /// 版本号（不带 `v` 前缀）
+    pub version: String,
+    pub prerelease: bool,
+    pub published_at: Option<String>,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// 发布的附件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub download_url: String,
+    pub size: Option<u64>,
+    /// SHA-256（小写十六进制），托管平台未提供时为 None
+    pub checksum: Option<String>,
+}
+
+/// 代码托管平台的发布接口
+#[async_trait]
+pub trait ReleaseHost: Send + Sync {
+    /// 平台名称，如 `GitHub`
+    fn kind(&self) -> &'static str;
+
+    /// 仓库的全部发布，不包括草稿
+    async fn releases(&self) -> Result<Vec<Release>, PluginError>;
+
+    /// 读取附件的文本内容，用于校验和文件
+    async fn asset_text(&self, asset: &ReleaseAsset) -> Result<String, PluginError>;
+
+    /// 为下载附件设置凭据等选项
+    async fn prepare_download(&self, downloader: Downloader) -> Result<Downloader, PluginError>;
+}
+
+/// 插件设置中的发布源选项
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ReleaseSettings {
+    /// 附件文件名模式，见模块文档
+    pub asset: Option<String>,
+    /// 校验和附件的文件名模式，`{asset}` 为所选附件的文件名
+    pub checksums: Option<String>,
+    /// 附件中的可执行文件，相对于解压目录
+    pub entry_points: Vec<String>,
+    /// 操作系统 -> 附件名中的写法，如 `"macos": "apple-darwin"`
+    pub os: HashMap<String, String>,
+    /// 架构 -> 附件名中的写法，如 `"x86_64": "amd64"`
+    pub arch: HashMap<String, String>,
+}
+
+/// 各操作系统在附件名中的常见写法
+const OS_ALIASES: &[(&str, &[&str])] = &[
+    (
+        "linux",
+        &[
+            "unknown-linux-musl",
+            "unknown-linux-gnu",
+            "linux-musl",
+            "linux",
+        ],
+    ),
+    ("macos", &["apple-darwin", "darwin", "macos", "osx", "mac"]),
+    (
+        "windows",
+        &[
+            "pc-windows-msvc",
+            "pc-windows-gnu",
+            "windows",
+            "win64",
+            "win32",
+            "win",
+        ],
+    ),
+];
+
+/// 各架构在附件名中的常见写法，较长的写法在前，避免 `x86` 匹配 `x86_64` 的前缀
+const ARCH_ALIASES: &[(&str, &[&str])] = &[
+    ("x86_64", &["x86_64", "x86-64", "amd64", "x64"]),
+    ("aarch64", &["aarch64", "arm64"]),
+    ("riscv64", &["riscv64"]),
+    ("x86", &["i686", "i386", "386", "x86"]),
+    ("arm", &["armv7l", "armv7", "armhf", "arm"]),
+];
+
+/// 不是制品的附件扩展名
+const SKIPPED_EXTENSIONS: &[&str] = &[
+    "sha256", "sha512", "sha1", "md5", "sig", "asc", "pem", "crt", "txt", "json", "sbom", "deb",
+    "rpm", "msi", "dmg", "pkg", "apk", "xz", "bz2", "zst", "intoto", "jsonl",
+];
+
+/// 附件与目标平台的匹配结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetMatch {
+    /// 附件名中的操作系统写法
+    pub os: String,
+    /// 附件名中的架构写法，附件名不含架构时为规范名称
+    pub arch: String,
+}
+
+impl ReleaseSettings {
+    fn os_aliases(&self, os: &str) -> Vec<String> {
+        aliases(&self.os, OS_ALIASES, os)
+    }
+
+    fn arch_aliases(&self, arch: &str) -> Vec<String> {
+        aliases(&self.arch, ARCH_ALIASES, arch)
+    }
+
+    /// 附件是否为发布在目标平台上的制品
+    pub fn match_asset(
+        &self,
+        name: &str,
+        release: &Release,
+        target: &TargetPlatform,
+    ) -> Option<AssetMatch> {
+        match &self.asset {
+            Some(pattern) => self.match_pattern(pattern, name, release, target),
+            None => self.detect(name, target),
+        }
+    }
+
+    fn match_pattern(
+        &self,
+        pattern: &str,
+        name: &str,
+        release: &Release,
+        target: &TargetPlatform,
+    ) -> Option<AssetMatch> {
+        let alternation = |values: &[String]| {
+            values
+                .iter()
+                .map(|value| regex::escape(value))
+                .collect::<Vec<_>>()
+                .join("|")
+        };
+        let os = alternation(&self.os_aliases(&target.os));
+        let arch = alternation(&self.arch_aliases(&target.arch));
+
+        let mut expression = String::from("(?i)^");
+        let mut rest = pattern;
+        let (mut os_captured, mut arch_captured) = (false, false);
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('*') {
+                expression.push_str(".*?");
+                rest = after;
+                continue;
+            }
+            let placeholder = ["{version}", "{tag}", "{os}", "{arch}", "{ext}", "{exe}"]
+                .into_iter()
+                .find(|placeholder| rest.starts_with(placeholder));
+            let Some(placeholder) = placeholder else {
+                let literal = rest.chars().next().unwrap_or_default();
+                expression.push_str(&regex::escape(&literal.to_string()));
+                rest = &rest[literal.len_utf8()..];
+                continue;
+            };
+            match placeholder {
+                "{version}" => expression.push_str(&regex::escape(&release.version)),
+                "{tag}" => expression.push_str(&regex::escape(&release.tag)),
+                // 第一次出现的位置捕获实际写法
+                "{os}" if !os_captured => {
+                    os_captured = true;
+                    expression.push_str(&format!("(?P<os>{})", os));
+                }
+                "{os}" => expression.push_str(&format!("(?:{})", os)),
+                "{arch}" if !arch_captured => {
+                    arch_captured = true;
+                    expression.push_str(&format!("(?P<arch>{})", arch));
+                }
+                "{arch}" => expression.push_str(&format!("(?:{})", arch)),
+                "{ext}" => expression.push_str(r"(?:tar\.gz|tgz|zip)"),
+                _ => expression.push_str(r"(?:\.exe)?"),
+            }
+            rest = &rest[placeholder.len()..];
+        }
+        expression.push('$');
+
+        let regex = Regex::new(&expression)
+            .map_err(|e| log::warn!("无效的附件模式 '{}': {}", pattern, e))
+            .ok()?;
+        let captures = regex.captures(name)?;
+        let captured = |group: &str, fallback: &str| {
+            captures
+                .name(group)
+                .map_or(fallback.to_string(), |m| m.as_str().to_string())
+        };
+        Some(AssetMatch {
+            os: captured("os", &target.os),
+            arch: captured("arch", &target.arch),
+        })
+    }
+
+    /// 未设置模式时按文件名中的平台写法识别
+    fn detect(&self, name: &str, target: &TargetPlatform) -> Option<AssetMatch> {
+        let lower = name.to_ascii_lowercase();
+        let extension = lower.rsplit_once('.').map(|(_, extension)| extension);
+        if extension.is_some_and(|extension| SKIPPED_EXTENSIONS.contains(&extension)) {
+            return None;
+        }
+
+        let os = self
+            .os_aliases(&target.os)
+            .into_iter()
+            .find(|alias| find_token(&lower, &alias.to_ascii_lowercase()).is_some())?;
+
+        // 依次识别各架构，识别后从文件名中去掉，避免 `x86` 匹配 `x86_64`
+        let mut remaining = lower.clone();
+        let mut found = None;
+        for (canonical, _) in ARCH_ALIASES {
+            for alias in self.arch_aliases(canonical) {
+                if let Some(range) = find_token(&remaining, &alias.to_ascii_lowercase()) {
+                    found.get_or_insert((*canonical, alias));
+                    remaining.replace_range(range, "-");
+                }
+            }
+        }
+        let arch = match found {
+            Some((canonical, alias)) if canonical == target.arch => alias,
+            Some(_) => return None,
+            None => target.arch.clone(),
+        };
+        Some(AssetMatch { os, arch })
+    }
+
+    /// 发布中目标平台的附件，未设置模式时优先选择压缩包和带架构的附件
+    pub fn select<'a>(
+        &self,
+        release: &'a Release,
+        target: &TargetPlatform,
+    ) -> Option<(&'a ReleaseAsset, AssetMatch)> {
+        let score = |asset: &ReleaseAsset, matched: &AssetMatch| {
+            let archive = ArchiveFormat::from_file_name(&asset.name) != ArchiveFormat::Raw;
+            let has_arch = matched.arch != target.arch
+                || find_token(&asset.name.to_ascii_lowercase(), &target.arch).is_some();
+            u8::from(archive) + 2 * u8::from(has_arch)
+        };
+        let mut candidates: Vec<(&ReleaseAsset, AssetMatch)> = release
+            .assets
+            .iter()
+            .filter_map(|asset| {
+                self.match_asset(&asset.name, release, target)
+                    .map(|matched| (asset, matched))
+            })
+            .collect();
+        // 分数相同时保持发布中的顺序
+        candidates.sort_by_key(|(asset, matched)| std::cmp::Reverse(score(asset, matched)));
+        candidates.into_iter().next()
+    }
+
+    /// 替换可执行文件路径中的占位符
+    pub fn entry_points_for(
+        &self,
+        release: &Release,
+        matched: &AssetMatch,
+        target: &TargetPlatform,
+    ) -> Vec<String> {
+        let windows = target.os == "windows";
+        self.entry_points
+            .iter()
+            .map(|entry| {
+                entry
+                    .replace("{version}", &release.version)
+                    .replace("{tag}", &release.tag)
+                    .replace("{os}", &matched.os)
+                    .replace("{arch}", &matched.arch)
+                    .replace("{exe}", if windows { ".exe" } else { "" })
+            })
+            .collect()
+    }
+}
+
+/// 设置中指定的写法，或内置的常见写法
+fn aliases(
+    overrides: &HashMap<String, String>,
+    table: &[(&str, &[&str])],
+    key: &str,
+) -> Vec<String> {
+    if let Some(value) = overrides.get(key) {
+        return vec![value.clone()];
+    }
+    table
+        .iter()
+        .find(|(canonical, _)| *canonical == key)
+        .map(|(_, aliases)| aliases.iter().map(|alias| alias.to_string()).collect())
+        .unwrap_or_else(|| vec![key.to_string()])
+}
+
+/// 以 `-`、`_`、`.` 或首尾为边界查找写法
+fn find_token(name: &str, token: &str) -> Option<std::ops::Range<usize>> {
+    let is_boundary = |c: Option<char>| c.is_none_or(|c| matches!(c, '-' | '_' | '.' | ' '));
+    name.match_indices(token).find_map(|(start, _)| {
+        let end = start + token.len();
+        (is_boundary(name[..start].chars().next_back()) && is_boundary(name[end..].chars().next()))
+            .then_some(start..end)
+    })
+}
+
+/// 单个可执行文件的附件：按附件名保存（下载地址不以附件名结尾时）并设置可执行权限
+async fn finish_raw_asset(dest: &Path, download_url: &str, name: &str) -> Result<(), PluginError> {
+    let downloaded = dest.join(file_name_from_url(download_url)?);
+    let path = dest.join(name);
+    if downloaded != path {
+        tokio::fs::rename(&downloaded, &path).await.map_err(|e| {
+            PluginError::IoError(format!("重命名 {} 失败: {}", downloaded.display(), e))
+        })?;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .await
+            .map_err(|e| {
+                PluginError::IoError(format!("设置 {} 的权限失败: {}", path.display(), e))
+            })?;
+    }
+    Ok(())
+}
+
+/// 标签中的版本号，无法解析时返回 None
+pub fn tag_version(tag: &str) -> Option<String> {
+    // 兼容 `tool-v1.2.0`、`release/1.2.0` 等带前缀的标签
+    let start = tag.find(|c: char| c.is_ascii_digit())?;
+    let prefix = &tag[..start];
+    if !(prefix.is_empty() || prefix.ends_with(['v', 'V', '-', '/', '_'])) {
+        return None;
+    }
+    let version = &tag[start..];
+    Version::parse(version).ok().map(|_| version.to_string())
+}
+
+/// 从代码托管平台的发布安装的插件
+pub struct ReleasesPlugin {
+    name: String,
+    source: PluginSource,
+    host: Box<dyn ReleaseHost>,
+    options: ReleaseSettings,
+    /// 各版本的安装目录 `<install_root>/<version>`
+    install_root: PathBuf,
+    settings: GlobalSettings,
+    status: PluginStatus,
+    config: RwLock<HashMap<String, String>>,
+    /// 首次查询后缓存的发布，最新的在前
+    releases: RwLock<Option<Vec<Release>>>,
+}
+
+impl ReleasesPlugin {
+    /// 创建插件，发布源选项取自插件设置，安装到 `plugin_dir/<name>` 下
+    pub fn new(
+        config: &PluginConfig,
+        source: &PluginSource,
+        host: Box<dyn ReleaseHost>,
+        settings: &GlobalSettings,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            name: config.name.clone(),
+            source: source.clone(),
+            host,
+            options: config.settings_as()?,
+            install_root: settings.plugin_path().join(&config.name),
+            settings: settings.clone(),
+            status: PluginStatus::Inactive,
+            config: RwLock::new(HashMap::new()),
+            releases: RwLock::new(None),
+        })
+    }
+
+    /// 安装版本的目录
+    pub fn version_dir(&self, version: &str) -> PathBuf {
+        self.install_root.join(version.trim_start_matches('v'))
+    }
+
+    async fn releases(&self) -> Result<Vec<Release>, PluginError> {
+        if let Some(releases) = self.releases.read().ok().and_then(|r| r.clone()) {
+            return Ok(releases);
+        }
+        let mut releases = self.host.releases().await?;
+        releases.sort_by(|a, b| {
+            let parse = |release: &Release| Version::parse(&release.version).ok();
+            parse(b)
+                .cmp(&parse(a))
+                .then(a.prerelease.cmp(&b.prerelease))
+        });
+        if let Ok(mut cached) = self.releases.write() {
+            *cached = Some(releases.clone());
+        }
+        Ok(releases)
+    }
+
+    /// 发布中目标平台的制品信息
+    async fn artifact(
+        &self,
+        release: &Release,
+        target: &TargetPlatform,
+    ) -> Result<(VersionInfo, String), PluginError> {
+        let (asset, matched) = self.options.select(release, target).ok_or_else(|| {
+            PluginError::NotFound(format!(
+                "{} {} 的发布中没有 {} 的附件",
+                self.name, release.tag, target
+            ))
+        })?;
+        let platform = if target.is_host() {
+            current_platform().to_string()
+        } else {
+            target.to_string()
+        };
+        let mut info = VersionInfo::new(&release.version, &platform, &asset.download_url);
+        info.size = asset.size;
+        info.prerelease = release.prerelease;
+        info.release_date = release.published_at.clone();
+        info.archive_format = Some(ArchiveFormat::from_file_name(&asset.name));
+        info.entry_points = self.options.entry_points_for(release, &matched, target);
+        info.checksum = match &self.options.checksums {
+            Some(pattern) => self.checksum_from_asset(release, asset, pattern).await,
+            None => asset.checksum.clone(),
+        };
+        Ok((info, asset.name.clone()))
+    }
+
+    /// 从校验和附件中读取附件的 SHA-256，读取失败时只记录日志
+    async fn checksum_from_asset(
+        &self,
+        release: &Release,
+        asset: &ReleaseAsset,
+        pattern: &str,
+    ) -> Option<String> {
+        let name = pattern
+            .replace("{asset}", &asset.name)
+            .replace("{version}", &release.version)
+            .replace("{tag}", &release.tag);
+        let Some(checksums) = release.assets.iter().find(|a| a.name == name) else {
+            log::debug!("{} 的发布中没有校验和附件 {}", release.tag, name);
+            return asset.checksum.clone();
+        };
+        match self.host.asset_text(checksums).await {
+            Ok(content) => parse_checksum(&content, &asset.name).or(asset.checksum.clone()),
+            Err(e) => {
+                log::debug!("读取 {} 失败: {}", checksums.name, e);
+                asset.checksum.clone()
+            }
+        }
+    }
+
+    /// 版本对应的发布，`latest` 为最新的正式版本
+    async fn release(&self, version: &str) -> Result<Release, PluginError> {
+        self.releases()
+            .await?
+            .into_iter()
+            .find(|release| match version {
+                "latest" => !release.prerelease,
+                version => same_version(&release.version, version),
+            })
+            .ok_or_else(|| {
+                PluginError::NotFound(format!("{} {}（{}）", self.name, version, self.source.url))
+            })
+    }
+}
+
+#[async_trait]
+impl Plugin for ReleasesPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        let latest = self.releases.read().ok().and_then(|releases| {
+            releases
+                .as_ref()?
+                .iter()
+                .find(|release| !release.prerelease)
+                .map(|release| release.version.clone())
+        });
+        PluginMetadata {
+            name: self.name.clone(),
+            version: latest.unwrap_or_else(|| PluginMetadata::default().version),
+            description: format!(
+                "Installed from {} releases of {}",
+                self.host.kind(),
+                self.source.url
+            ),
+            tags: vec![self.host.kind().to_ascii_lowercase()],
+            ..PluginMetadata::default()
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Active;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Inactive;
+        Ok(())
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        let release = self.release(version).await?;
+        let target = options
+            .cross_target()
+            .cloned()
+            .unwrap_or_else(TargetPlatform::host);
+        let dest = match &options.install_dir {
+            Some(dir) => Path::new(dir).join(&release.version),
+            None => self.version_dir(&release.version),
+        };
+        if !options.force && dest.is_dir() {
+            return Ok(dest.to_string_lossy().into_owned());
+        }
+
+        let (info, asset_name) = self.artifact(&release, &target).await?;
+        if options.force {
+            let _ = tokio::fs::remove_dir_all(&dest).await;
+        }
+        self.host
+            .prepare_download(Downloader::from_settings(&self.settings)?)
+            .await?
+            .fetch_and_extract(&info, Some(&self.source), &dest)
+            .await?;
+        if info.archive_format() == ArchiveFormat::Raw {
+            finish_raw_asset(&dest, &info.download_url, &asset_name).await?;
+        }
+
+        if let Some(missing) = info
+            .entry_points
+            .iter()
+            .map(|entry| dest.join(entry))
+            .find(|p| !p.is_file())
+        {
+            return Err(PluginError::InstallationError(format!(
+                "{} {} 的制品中没有 {}",
+                self.name,
+                info.version,
+                missing.display()
+            )));
+        }
+        Ok(dest.to_string_lossy().into_owned())
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        let dest = self.version_dir(version);
+        if !dest.exists() {
+            return Err(PluginError::NotFound(format!("{} {}", self.name, version)));
+        }
+        tokio::fs::remove_dir_all(&dest)
+            .await
+            .map_err(|e| PluginError::IoError(format!("删除 {} 失败: {}", dest.display(), e)))
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        let target = TargetPlatform::host();
+        let mut versions = Vec::new();
+        // 没有当前平台附件的发布不列出
+        for release in self.releases().await? {
+            let Some((asset, _)) = self.options.select(&release, &target) else {
+                continue;
+            };
+            let mut info =
+                VersionInfo::new(&release.version, current_platform(), &asset.download_url);
+            info.size = asset.size;
+            info.checksum = asset.checksum.clone();
+            info.prerelease = release.prerelease;
+            info.release_date = release.published_at.clone();
+            versions.push(info);
+        }
+        Ok(versions)
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        let mut entries = match tokio::fs::read_dir(&self.install_root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(PluginError::IoError(format!("读取安装目录失败: {}", e))),
+        };
+        let mut versions = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取安装目录失败: {}", e)))?
+        {
+            let version = entry.file_name().to_string_lossy().into_owned();
+            if self.is_installed(&version).await? {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+        Ok(versions)
+    }
+
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        Ok(self.version_dir(version).is_dir())
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        self.list_versions()
+            .await?
+            .into_iter()
+            .find(|info| !info.prerelease)
+            .ok_or_else(|| PluginError::NotFound(format!("{} releases", self.name)))
+    }
+
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        let version = match version {
+            Some(version) => version.trim_start_matches('v').to_string(),
+            None => self.get_latest_version().await?.version,
+        };
+        self.install(&version, &InstallOptions::new()).await?;
+        Ok(version)
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        Ok(self.config.read().map(|c| c.clone()).unwrap_or_default())
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+        Ok(())
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        if let Ok(mut config) = self.config.write() {
+            config.insert(key.to_string(), value.to_string());
+        }
+        Ok(())
+    }
+
+    fn get_help(&self) -> String {
+        format!(
+            "{}: installs {} release assets from {}",
+            self.name,
+            self.host.kind(),
+            self.source.url
+        )
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::ListVersions,
+            Capability::Config,
+            Capability::Vendor,
+            Capability::CrossInstall,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(assets: &[&str]) -> Release {
+        Release {
+            tag: "v1.2.0".to_string(),
+            version: "1.2.0".to_string(),
+            prerelease: false,
+            published_at: None,
+            assets: assets
+                .iter()
+                .map(|name| ReleaseAsset {
+                    name: name.to_string(),
+                    download_url: format!("https://example.com/{}", name),
+                    size: None,
+                    checksum: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_tag_version() {
+        assert_eq!(tag_version("v1.2.0").as_deref(), Some("1.2.0"));
+        assert_eq!(tag_version("tool-v1.2.0").as_deref(), Some("1.2.0"));
+        assert_eq!(
+            tag_version("release/2.0.0-rc.1").as_deref(),
+            Some("2.0.0-rc.1")
+        );
+        assert_eq!(tag_version("nightly"), None);
+        assert_eq!(tag_version("go1.22.0"), None);
+    }
+
+    #[test]
+    fn test_detect_platform_tokens() {
+        let release = release(&[
+            "tool-1.2.0-darwin-x86_64.tar.gz",
+            "tool-1.2.0-linux-x86.tar.gz",
+            "tool-1.2.0-linux-x86_64.tar.gz",
+            "tool-1.2.0-linux-x86_64.tar.gz.sig",
+            "tool_1.2.0_windows_amd64.exe",
+            "tool-1.2.0-linux",
+        ]);
+        let settings = ReleaseSettings::default();
+        let pick = |target: &str| {
+            settings
+                .select(&release, &target.parse().unwrap())
+                .map(|(asset, _)| asset.name.clone())
+        };
+        // `darwin` 中的 `win` 不算 Windows，`x86_64` 不算 `x86`
+        assert_eq!(pick("windows-x64").unwrap(), "tool_1.2.0_windows_amd64.exe");
+        assert_eq!(pick("linux-x64").unwrap(), "tool-1.2.0-linux-x86_64.tar.gz");
+        assert_eq!(pick("linux-x86").unwrap(), "tool-1.2.0-linux-x86.tar.gz");
+        // 不含架构的附件适用于所有架构
+        assert_eq!(pick("linux-arm64").unwrap(), "tool-1.2.0-linux");
+        assert!(pick("macos-arm64").is_none());
+    }
+
+    #[test]
+    fn test_pattern_with_overrides() {
+        let release = release(&[
+            "tool_1.2.0_Darwin_all.tar.gz",
+            "tool_1.2.0_Linux_arm64.tar.gz",
+        ]);
+        let settings: ReleaseSettings = serde_json::from_value(serde_json::json!({
+            "asset": "tool_{version}_{os}_{arch}.tar.gz",
+            "arch": { "x86_64": "all", "aarch64": "all" },
+            "os": { "macos": "Darwin" }
+        }))
+        .unwrap();
+        let mac: TargetPlatform = "macos-arm64".parse().unwrap();
+        let (asset, matched) = settings.select(&release, &mac).unwrap();
+        assert_eq!(asset.name, "tool_1.2.0_Darwin_all.tar.gz");
+        assert_eq!(matched.arch, "all");
+        // 覆盖了写法后不再匹配内置写法
+        let linux: TargetPlatform = "linux-arm64".parse().unwrap();
+        assert!(settings.select(&release, &linux).is_none());
+    }
+}
@@ -0,0 +1,204 @@
+//! PLM 配置热加载
+//!
+//! 宿主应用（服务、编辑器插件等）长时间持有 `PluginManager` 时，可以在 `plm.json`
+//! 变化后调用 `PluginManager::reload` 应用新配置，而不必重启：
+//!
+//! ```ignore
+//! let mut watcher = ConfigWatcher::spawn("plm.json", Duration::from_secs(2));
+//! while let Some(config) = watcher.next().await {
+//!     match config {
+//!         Ok(config) => println!("{:?}", manager.reload(config).await?),
+//!         Err(e) => eprintln!("plm.json 无效，保留当前配置: {}", e),
+//!     }
+//! }
+//! ```
+//!
+//! `ConfigWatcher` 按固定间隔比较文件内容（不依赖文件系统通知，网络文件系统和容器挂载中同样可用），
+//! 内容变化且能解析时产生新配置。`ConfigDiff` 比较新旧配置，决定哪些插件需要关闭、创建或重新加载。
+
+use crate::config::{PluginConfig, ProjectConfig};
+use crate::traits::PluginError;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// 新旧配置之间影响插件的差异，插件名称按字母顺序排列
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// 新启用的插件
+    pub added: Vec<String>,
+    /// 被移除或禁用的插件
+    pub removed: Vec<String>,
+    /// 仍然启用、版本以外的配置有变化的插件
+    pub changed: Vec<String>,
+    /// 仍然启用、配置没有变化的插件
+    pub unchanged: Vec<String>,
+    /// 全局设置是否变化
+    pub global_settings_changed: bool,
+}
+
+impl ConfigDiff {
+    /// 比较新旧配置
+    ///
+    /// 只有版本不同的插件不算变化：版本在安装时才使用，不影响已加载的插件
+    pub fn between(old: &ProjectConfig, new: &ProjectConfig) -> Self {
+        let enabled = |config: &ProjectConfig| -> BTreeSet<String> {
+            config
+                .plugins
+                .values()
+                .filter(|plugin| plugin.enabled)
+                .map(|plugin| plugin.name.clone())
+                .collect()
+        };
+        let (before, after) = (enabled(old), enabled(new));
+
+        let mut diff = ConfigDiff {
+            added: after.difference(&before).cloned().collect(),
+            removed: before.difference(&after).cloned().collect(),
+            global_settings_changed: to_value(&old.global_settings)
+                != to_value(&new.global_settings),
+            ..ConfigDiff::default()
+        };
+        for name in before.intersection(&after) {
+            if plugin_key(&old.plugins[name]) == plugin_key(&new.plugins[name]) {
+                diff.unchanged.push(name.clone());
+            } else {
+                diff.changed.push(name.clone());
+            }
+        }
+        diff
+    }
+
+    /// 是否没有影响插件的变化
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && !self.global_settings_changed
+    }
+}
+
+/// 比较插件配置时忽略版本
+fn plugin_key(config: &PluginConfig) -> serde_json::Value {
+    let mut config = config.clone();
+    config.version = None;
+    to_value(&config)
+}
+
+fn to_value(value: &impl serde::Serialize) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
+}
+
+/// 轮询配置文件，文件内容变化时产生新的配置
+///
+/// 被丢弃时停止轮询
+pub struct ConfigWatcher {
+    rx: mpsc::Receiver<Result<ProjectConfig, PluginError>>,
+    task: JoinHandle<()>,
+}
+
+impl ConfigWatcher {
+    /// 开始每隔 `interval` 检查一次 `path`，启动时的文件内容视为当前配置
+    pub fn spawn(path: impl Into<PathBuf>, interval: Duration) -> Self {
+        let path = path.into();
+        let (tx, rx) = mpsc::channel(1);
+        let task = tokio::spawn(async move {
+            let mut last = tokio::fs::read_to_string(&path).await.ok();
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // 文件暂时不存在（编辑器先删后写）时等待下一次检查
+                let Ok(content) = tokio::fs::read_to_string(&path).await else {
+                    continue;
+                };
+                if last.as_deref() == Some(content.as_str()) {
+                    continue;
+                }
+                // 无效的内容同样记为已处理，修正之前不重复报告
+                let config = ProjectConfig::from_json_str(&content);
+                last = Some(content);
+                if tx.send(config).await.is_err() {
+                    return;
+                }
+            }
+        });
+        Self { rx, task }
+    }
+
+    /// 等待下一次配置变化；无效的配置以错误返回
+    pub async fn next(&mut self) -> Option<Result<ProjectConfig, PluginError>> {
+        self.rx.recv().await
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with(plugins: &[(&str, bool, &str)]) -> ProjectConfig {
+        let mut config = ProjectConfig::default_for_project("demo", ".");
+        for (name, enabled, mirror) in plugins {
+            let mut plugin = PluginConfig::new(name);
+            plugin.enabled = *enabled;
+            plugin.set_version("1.0.0");
+            plugin
+                .settings
+                .insert("mirror".to_string(), serde_json::json!(mirror));
+            config.add_plugin(plugin);
+        }
+        config
+    }
+
+    #[test]
+    fn test_config_diff() {
+        let old = config_with(&[("node", true, "a"), ("go", true, "a"), ("jq", true, "a")]);
+        let mut new = config_with(&[("node", true, "b"), ("jq", false, "a"), ("deno", true, "a")]);
+        new.plugins.get_mut("node").unwrap().set_version("2.0.0");
+
+        let diff = ConfigDiff::between(&old, &new);
+        assert_eq!(diff.added, ["deno"]);
+        assert_eq!(diff.removed, ["go", "jq"]);
+        assert_eq!(diff.changed, ["node"]);
+        assert!(!diff.global_settings_changed);
+
+        // 只改版本不算变化
+        let mut bumped = old.clone();
+        bumped.plugins.get_mut("go").unwrap().set_version("1.1.0");
+        let diff = ConfigDiff::between(&old, &bumped);
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged, ["go", "jq", "node"]);
+
+        bumped.global_settings.download_timeout += 1;
+        assert!(ConfigDiff::between(&old, &bumped).global_settings_changed);
+    }
+
+    #[tokio::test]
+    async fn test_config_watcher() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plm.json");
+        let config = config_with(&[("node", true, "a")]);
+        std::fs::write(&path, config.to_json_string().unwrap()).unwrap();
+
+        let mut watcher = ConfigWatcher::spawn(&path, Duration::from_millis(10));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(watcher.next().await.unwrap().is_err());
+
+        let changed = config_with(&[("node", true, "b")]);
+        std::fs::write(&path, changed.to_json_string().unwrap()).unwrap();
+        let reloaded = watcher.next().await.unwrap().unwrap();
+        assert_eq!(
+            reloaded.plugins["node"].settings["mirror"],
+            serde_json::json!("b")
+        );
+    }
+}
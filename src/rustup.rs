@@ -0,0 +1,416 @@
+//! PLM 内置 rustup 插件
+//!
+//! 通过 rustup 管理 Rust 工具链，让 Rust 项目也能在 `plm.json` 中声明完整的工具环境：
+//!
+//! ```json
+//! {
+//!   "name": "rust",
+//!   "version": "1.76.0",
+//!   "source": { "type": "builtin", "url": "rustup" },
+//!   "settings": { "components": ["clippy", "rustfmt"], "targets": ["wasm32-unknown-unknown"] }
+//! }
+//! ```
+//!
+//! 未设置版本时读取项目中的 `rust-toolchain.toml`（或旧格式的 `rust-toolchain`），
+//! 文件中的 `components`、`targets` 与插件设置合并；都没有时使用 `stable`。
+//! 切换版本通过 `rustup override set --path <project_root>` 设置项目默认工具链，
+//! 不影响其他目录。
+
+use crate::check::extract_version;
+use crate::config::PluginConfig;
+use crate::delegate::{DelegatePlugin, VersionManager};
+use crate::project_files::find_upwards;
+use crate::system::run_command;
+use crate::traits::{
+    Capability, ExecOutcome, InstallOptions, Plugin, PluginError, PluginMetadata, PluginStatus,
+    VersionInfo,
+};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// 内置插件源中 rustup 插件的名称（`"source": { "type": "builtin", "url": "rustup" }`）
+pub const BUILTIN_RUSTUP: &str = "rustup";
+
+/// 工具链文件名，按优先顺序排列
+pub const TOOLCHAIN_FILES: [&str; 2] = ["rust-toolchain.toml", "rust-toolchain"];
+
+/// 工具链声明，对应 `rust-toolchain.toml` 中的 `[toolchain]` 表
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ToolchainSpec {
+    pub channel: String,
+    #[serde(default)]
+    pub components: Vec<String>,
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ToolchainFile {
+    toolchain: ToolchainSpec,
+}
+
+impl ToolchainSpec {
+    /// 只指定通道的工具链
+    pub fn channel(channel: &str) -> Self {
+        Self {
+            channel: channel.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// 解析工具链文件内容，支持 TOML 格式和只包含通道名的旧格式
+    pub fn parse(content: &str) -> Result<Self, PluginError> {
+        let trimmed = content.trim();
+        if !trimmed.is_empty() && !trimmed.contains(['\n', '=', '[']) {
+            return Ok(Self::channel(trimmed));
+        }
+        let file: ToolchainFile = toml::from_str(content)
+            .map_err(|e| PluginError::ConfigError(format!("解析工具链文件失败: {}", e)))?;
+        if file.toolchain.channel.is_empty() {
+            return Err(PluginError::ConfigError(
+                "rust-toolchain.toml has no toolchain.channel".to_string(),
+            ));
+        }
+        Ok(file.toolchain)
+    }
+
+    /// 合并额外的组件和目标平台，忽略重复项
+    pub fn merge(&mut self, components: &[String], targets: &[String]) {
+        for component in components {
+            if !self.components.contains(component) {
+                self.components.push(component.clone());
+            }
+        }
+        for target in targets {
+            if !self.targets.contains(target) {
+                self.targets.push(target.clone());
+            }
+        }
+    }
+
+    /// `rustup toolchain install` 的参数
+    pub fn install_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "toolchain".to_string(),
+            "install".to_string(),
+            self.channel.clone(),
+        ];
+        if let Some(profile) = &self.profile {
+            args.push("--profile".to_string());
+            args.push(profile.clone());
+        }
+        if !self.components.is_empty() {
+            args.push("--component".to_string());
+            args.push(self.components.join(","));
+        }
+        if !self.targets.is_empty() {
+            args.push("--target".to_string());
+            args.push(self.targets.join(","));
+        }
+        args
+    }
+}
+
+/// 从 `start` 向上查找工具链文件
+pub fn find_toolchain_file(start: &Path) -> Option<PathBuf> {
+    find_upwards(start, &TOOLCHAIN_FILES)
+}
+
+/// 读取 `start` 所在目录或上级目录中的工具链文件
+pub async fn read_toolchain_file(start: &Path) -> Result<Option<ToolchainSpec>, PluginError> {
+    let Some(path) = find_toolchain_file(start) else {
+        return Ok(None);
+    };
+    let content = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e)))?;
+    ToolchainSpec::parse(&content).map(Some)
+}
+
+/// 通过 rustup 管理 Rust 工具链的内置插件
+pub struct RustupPlugin {
+    name: String,
+    project_root: PathBuf,
+    components: Vec<String>,
+    targets: Vec<String>,
+    profile: Option<String>,
+    status: PluginStatus,
+    /// 列出、卸载等与委托后端相同的操作
+    inner: DelegatePlugin,
+}
+
+impl RustupPlugin {
+    /// 从插件配置创建，`project_root` 用于查找工具链文件和设置项目默认工具链
+    pub fn from_config(config: &PluginConfig, project_root: &Path) -> Self {
+        let settings = config.effective_settings_for(crate::config::current_platform());
+        let list = |key: &str| -> Vec<String> {
+            settings
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|item| item.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Self {
+            name: config.name.clone(),
+            project_root: project_root.to_path_buf(),
+            components: list("components"),
+            targets: list("targets"),
+            profile: settings
+                .get("profile")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            status: PluginStatus::Inactive,
+            inner: DelegatePlugin::new(&config.name, VersionManager::Rustup),
+        }
+    }
+
+    /// 解析要安装的工具链：`latest` 取项目工具链文件，没有时为 `stable`
+    pub async fn toolchain_spec(&self, version: &str) -> Result<ToolchainSpec, PluginError> {
+        let mut spec = match version {
+            "latest" => read_toolchain_file(&self.project_root)
+                .await?
+                .unwrap_or_else(|| ToolchainSpec::channel("stable")),
+            version => ToolchainSpec::channel(version),
+        };
+        spec.merge(&self.components, &self.targets);
+        if self.profile.is_some() {
+            spec.profile = self.profile.clone();
+        }
+        Ok(spec)
+    }
+
+    async fn rustup(
+        &self,
+        args: &[String],
+        options: Option<&InstallOptions>,
+    ) -> Result<String, PluginError> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        run_command(&VersionManager::Rustup.command(&args), options).await
+    }
+
+    /// 工具链中 rustc 的版本号，如 `stable` -> `1.76.0`
+    async fn rustc_version(&self, toolchain: &str) -> Result<String, PluginError> {
+        let args = ["run", toolchain, "rustc", "--version"].map(String::from);
+        let output = self.rustup(&args, None).await?;
+        extract_version(&output).ok_or_else(|| {
+            PluginError::ValidationError(format!("无法解析 rustc 版本: {}", output.trim()))
+        })
+    }
+}
+
+#[async_trait]
+impl Plugin for RustupPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        PluginMetadata {
+            name: self.name.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            description: "Rust toolchains managed by rustup".to_string(),
+            homepage: Some("https://rustup.rs".to_string()),
+            tags: vec!["builtin".to_string(), BUILTIN_RUSTUP.to_string()],
+            ..PluginMetadata::default()
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        self.inner.initialize().await?;
+        self.status = PluginStatus::Active;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Inactive;
+        Ok(())
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        let spec = self.toolchain_spec(version).await?;
+        // 已安装的工具链再次安装时 rustup 只补充缺少的组件和目标平台
+        self.rustup(&spec.install_args(), Some(options)).await?;
+        let sysroot = ["run", &spec.channel, "rustc", "--print", "sysroot"].map(String::from);
+        Ok(self.rustup(&sysroot, None).await?.trim().to_string())
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        self.inner.uninstall(version).await
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        self.inner.list_versions().await
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        self.inner.list_installed().await
+    }
+
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        self.inner.is_installed(version).await
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        self.inner.get_latest_version().await
+    }
+
+    /// 指定版本时安装该版本；否则更新 stable 通道并安装对应的具体版本，返回版本号
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        let options = InstallOptions::new();
+        if let Some(version) = version {
+            self.install(version, &options).await?;
+            return Ok(version.to_string());
+        }
+        self.install("stable", &options).await?;
+        let version = self.rustc_version("stable").await?;
+        self.install(&version, &options).await?;
+        Ok(version)
+    }
+
+    /// 设置项目目录的默认工具链
+    async fn switch_version(&self, version: &str) -> Result<(), PluginError> {
+        let args = [
+            "override".to_string(),
+            "set".to_string(),
+            version.to_string(),
+            "--path".to_string(),
+            self.project_root.to_string_lossy().into_owned(),
+        ];
+        self.rustup(&args, None).await.map(|_| ())
+    }
+
+    async fn verify_installation(&self, version: &str) -> Result<bool, PluginError> {
+        self.inner.verify_installation(version).await
+    }
+
+    async fn cleanup(&self) -> Result<(), PluginError> {
+        Ok(())
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        self.inner.get_config().await
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        self.inner.set_config(config).await
+    }
+
+    async fn get_config_value(&self, key: &str) -> Result<Option<String>, PluginError> {
+        self.inner.get_config_value(key).await
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        self.inner.set_config_value(key, value).await
+    }
+
+    async fn execute_command(&self, command: &str, args: &[&str]) -> Result<String, PluginError> {
+        self.inner.execute_command(command, args).await
+    }
+
+    async fn exec_command(&self, command: &str, args: &[&str]) -> Result<ExecOutcome, PluginError> {
+        self.inner.exec_command(command, args).await
+    }
+
+    fn get_help(&self) -> String {
+        "Manages Rust toolchains with rustup. Without a version, the project's \
+         rust-toolchain.toml is used; switching sets a rustup override for the project."
+            .to_string()
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::SwitchVersion,
+            Capability::ListVersions,
+            Capability::Exec,
+            Capability::Config,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toolchain_file() {
+        let spec = ToolchainSpec::parse(
+            "[toolchain]\nchannel = \"1.76.0\"\ncomponents = [\"clippy\"]\nprofile = \"minimal\"\n",
+        )
+        .unwrap();
+        assert_eq!(spec.channel, "1.76.0");
+        assert_eq!(spec.components, ["clippy"]);
+        assert_eq!(spec.profile.as_deref(), Some("minimal"));
+
+        assert_eq!(
+            ToolchainSpec::parse("nightly-2024-01-01\n").unwrap(),
+            ToolchainSpec::channel("nightly-2024-01-01")
+        );
+        assert!(ToolchainSpec::parse("[toolchain]\ncomponents = []\n").is_err());
+    }
+
+    #[test]
+    fn test_install_args() {
+        let mut spec = ToolchainSpec::channel("stable");
+        spec.merge(
+            &["clippy".to_string(), "rustfmt".to_string()],
+            &["wasm32-unknown-unknown".to_string()],
+        );
+        spec.merge(&["clippy".to_string()], &[]);
+        assert_eq!(
+            spec.install_args(),
+            [
+                "toolchain",
+                "install",
+                "stable",
+                "--component",
+                "clippy,rustfmt",
+                "--target",
+                "wasm32-unknown-unknown"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_toolchain_spec_from_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("crates/app");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let mut config = PluginConfig::new("rust");
+        config.set_setting("components", serde_json::json!(["rustfmt"]));
+        let plugin = RustupPlugin::from_config(&config, &nested);
+        assert_eq!(
+            plugin.toolchain_spec("latest").await.unwrap().channel,
+            "stable"
+        );
+
+        std::fs::write(
+            dir.path().join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"1.75.0\"\ncomponents = [\"clippy\"]\n",
+        )
+        .unwrap();
+        let spec = plugin.toolchain_spec("latest").await.unwrap();
+        assert_eq!(spec.channel, "1.75.0");
+        assert_eq!(spec.components, ["clippy", "rustfmt"]);
+        assert_eq!(
+            plugin.toolchain_spec("1.76.0").await.unwrap().channel,
+            "1.76.0"
+        );
+    }
+}
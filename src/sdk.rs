@@ -0,0 +1,10 @@
+//! PLM 插件 SDK
+//!
+//! `Plugin` trait 没有稳定的 ABI：trait 对象的布局随 PLM 版本和 Rust 编译器变化，
+//! 因此 PLM 不从动态库加载插件。插件以普通 Rust crate 分发，宿主把它作为依赖编译进来，
+//! 调用 `#[plm_plugin]` 生成的 `plm_plugin_create` 创建实例并注册到 `PluginManager`。
+
+use crate::traits::Plugin;
+
+/// `#[plm_plugin]` 生成的 `plm_plugin_create` 的函数类型
+pub type PluginCreateFn = fn() -> Box<dyn Plugin>;
@@ -0,0 +1,155 @@
+//! PLM 凭据存储
+//!
+//! 插件源的访问令牌等凭据保存在系统钥匙串中（macOS Keychain、Windows 凭据管理器、
+//! Linux Secret Service），配置文件中只记录引用 `keychain:<name>`。
+//!
+//! 钥匙串不可用（如 CI 环境或未启用 `keychain` 特性）或其中没有该凭据时，
+//! 读取环境变量 `PLM_SECRET_<NAME>` 作为后备，`<NAME>` 为大写的名称，
+//! 非字母数字字符替换为 `_`，如 `github-token` -> `PLM_SECRET_GITHUB_TOKEN`。
+
+use crate::traits::PluginError;
+
+/// 钥匙串中的服务名
+pub const KEYCHAIN_SERVICE: &str = "plm";
+
+/// 后备环境变量前缀
+pub const SECRET_ENV_PREFIX: &str = "PLM_SECRET_";
+
+/// 配置文件中引用钥匙串凭据的写法
+pub fn keychain_reference(name: &str) -> String {
+    format!("keychain:{}", name)
+}
+
+/// 凭据的后备环境变量名
+pub fn fallback_env_var(name: &str) -> String {
+    let suffix: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}{}", SECRET_ENV_PREFIX, suffix)
+}
+
+/// 把凭据保存到钥匙串
+pub async fn store_secret(name: &str, value: &str) -> Result<(), PluginError> {
+    let name = name.to_string();
+    let value = value.to_string();
+    tokio::task::spawn_blocking(move || backend::set(&name, &value))
+        .await
+        .map_err(|e| PluginError::IoError(format!("钥匙串任务失败: {}", e)))?
+}
+
+/// 从钥匙串删除凭据，返回凭据是否存在
+pub async fn delete_secret(name: &str) -> Result<bool, PluginError> {
+    let name = name.to_string();
+    tokio::task::spawn_blocking(move || backend::delete(&name))
+        .await
+        .map_err(|e| PluginError::IoError(format!("钥匙串任务失败: {}", e)))?
+}
+
+/// 读取凭据：先查钥匙串，再查后备环境变量
+pub async fn read_secret(name: &str) -> Result<String, PluginError> {
+    let key = name.to_string();
+    let stored = tokio::task::spawn_blocking(move || backend::get(&key))
+        .await
+        .map_err(|e| PluginError::IoError(format!("钥匙串任务失败: {}", e)))?;
+
+    let keychain_error = match stored {
+        Ok(Some(value)) => return Ok(value),
+        Ok(None) => None,
+        Err(e) => Some(e),
+    };
+
+    let env_var = fallback_env_var(name);
+    if let Ok(value) = std::env::var(&env_var) {
+        return Ok(value);
+    }
+    if let Some(e) = keychain_error {
+        log::debug!("读取钥匙串凭据 '{}' 失败: {}", name, e);
+    }
+    Err(PluginError::ConfigError(format!(
+        "Secret '{}' not found in the OS keychain and {} is not set",
+        name, env_var
+    )))
+}
+
+#[cfg(feature = "keychain")]
+mod backend {
+    use super::KEYCHAIN_SERVICE;
+    use crate::traits::PluginError;
+
+    fn entry(name: &str) -> Result<keyring::Entry, PluginError> {
+        keyring::Entry::new(KEYCHAIN_SERVICE, name)
+            .map_err(|e| PluginError::ConfigError(format!("无法访问钥匙串: {}", e)))
+    }
+
+    pub fn get(name: &str) -> Result<Option<String>, PluginError> {
+        match entry(name)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(PluginError::ConfigError(format!("读取钥匙串失败: {}", e))),
+        }
+    }
+
+    pub fn set(name: &str, value: &str) -> Result<(), PluginError> {
+        entry(name)?
+            .set_password(value)
+            .map_err(|e| PluginError::ConfigError(format!("写入钥匙串失败: {}", e)))
+    }
+
+    pub fn delete(name: &str) -> Result<bool, PluginError> {
+        match entry(name)?.delete_password() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(PluginError::ConfigError(format!(
+                "删除钥匙串凭据失败: {}",
+                e
+            ))),
+        }
+    }
+}
+
+#[cfg(not(feature = "keychain"))]
+mod backend {
+    use crate::traits::PluginError;
+
+    fn unsupported() -> PluginError {
+        PluginError::ConfigError("PLM was built without keychain support".to_string())
+    }
+
+    pub fn get(_name: &str) -> Result<Option<String>, PluginError> {
+        Err(unsupported())
+    }
+
+    pub fn set(_name: &str, _value: &str) -> Result<(), PluginError> {
+        Err(unsupported())
+    }
+
+    pub fn delete(_name: &str) -> Result<bool, PluginError> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_env_var() {
+        assert_eq!(fallback_env_var("github-token"), "PLM_SECRET_GITHUB_TOKEN");
+        assert_eq!(keychain_reference("github-token"), "keychain:github-token");
+    }
+
+    #[tokio::test]
+    async fn test_read_secret_env_fallback() {
+        // 测试环境中通常没有钥匙串服务，也不会存在该条目
+        std::env::set_var("PLM_SECRET_PLM_TEST_FALLBACK", "from-env");
+        assert_eq!(read_secret("plm-test-fallback").await.unwrap(), "from-env");
+        assert!(read_secret("plm-test-missing").await.is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//! PLM 自更新
+//!
+//! `plm self-update` 从发布端点读取发布清单，找到当前平台的制品，
+//! 下载并校验 SHA-256 后原子替换正在运行的可执行文件：
+//!
+//! ```json
+//! {
+//!   "version": "0.2.0",
+//!   "artifacts": {
+//!     "linux-x86_64": { "url": "https://…/plm-linux-x86_64.tar.gz", "sha256": "…" }
+//!   }
+//! }
+//! ```
+//!
+//! 平台键为 `<os>-<arch>`（Rust 的 `std::env::consts` 命名）。制品可以是单个可执行文件，
+//! 也可以是包含 `plm` 可执行文件的 `.tar.gz`/`.zip`。发布清单缺少校验和或校验失败时拒绝更新，
+//! 不受 `verify_checksums` 设置影响。默认端点可用 `PLM_RELEASE_URL` 覆盖。
+//!
+//! 发布清单必须带有 minisign 分离签名 `<清单地址>.sig`，并由发布者公钥校验通过；
+//! 没有公钥或签名无效时拒绝更新。发布者公钥在构建时通过 `PLM_RELEASE_KEY` 环境变量嵌入，
+//! 运行时设置同名环境变量可以覆盖（用于自建的发布端点）。不使用 `trusted_keys`：
+//! 项目中的 plm.json 可以修改该设置，不能用来授权替换 plm 本身。
+
+use crate::config::GlobalSettings;
+use crate::download::{build_client, cache_file_name, verify_checksum, Downloader};
+use crate::extract::extract_file;
+use crate::signature::{self, TrustedKey};
+use crate::temp::TempFileGuard;
+use crate::traits::{ArchiveFormat, PluginError};
+use crate::version::Version;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 默认的发布清单地址
+pub const DEFAULT_RELEASE_URL: &str = "https://releases.plm.dev/plm/latest.json";
+
+/// 覆盖发布清单地址的环境变量
+pub const RELEASE_URL_ENV: &str = "PLM_RELEASE_URL";
+
+/// 发布清单签名地址的后缀
+pub const SIGNATURE_SUFFIX: &str = ".sig";
+
+/// 覆盖发布者公钥（minisign 公钥）的环境变量，构建时设置则把公钥嵌入可执行文件
+pub const RELEASE_KEY_ENV: &str = "PLM_RELEASE_KEY";
+
+/// 构建时嵌入的发布者公钥
+const EMBEDDED_RELEASE_KEY: Option<&str> = option_env!("PLM_RELEASE_KEY");
+
+/// 校验发布清单的发布者公钥：运行时的 `PLM_RELEASE_KEY` 优先，其次是构建时嵌入的公钥
+pub fn release_keys() -> Vec<TrustedKey> {
+    std::env::var(RELEASE_KEY_ENV)
+        .ok()
+        .as_deref()
+        .or(EMBEDDED_RELEASE_KEY)
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(|key| vec![TrustedKey::minisign(key)])
+        .unwrap_or_default()
+}
+
+/// 发布清单中单个平台的制品
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ReleaseArtifact {
+    pub url: String,
+    pub sha256: String,
+}
+
+/// 发布清单
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    /// 平台键 -> 制品
+    #[serde(default)]
+    pub artifacts: BTreeMap<String, ReleaseArtifact>,
+}
+
+impl ReleaseManifest {
+    /// 解析发布清单
+    pub fn parse(content: &str) -> Result<Self, PluginError> {
+        serde_json::from_str(content)
+            .map_err(|e| PluginError::ValidationError(format!("无效的发布清单: {}", e)))
+    }
+
+    /// 指定平台的制品
+    pub fn artifact_for(&self, platform: &str) -> Option<&ReleaseArtifact> {
+        self.artifacts.get(platform)
+    }
+
+    /// 发布的版本是否比 `current` 新，版本号无法解析时返回 false
+    pub fn is_newer_than(&self, current: &str) -> bool {
+        match (
+            Version::parse(self.version.trim_start_matches('v')),
+            Version::parse(current),
+        ) {
+            (Ok(latest), Ok(current)) => latest > current,
+            _ => false,
+        }
+    }
+}
+
+/// 当前平台在发布清单中的键，如 `linux-x86_64`、`macos-aarch64`
+pub fn platform_key() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// PLM 自身的版本
+pub fn current_version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// 检查并安装新版本的 PLM
+pub struct SelfUpdater {
+    release_url: String,
+    release_keys: Vec<TrustedKey>,
+    settings: GlobalSettings,
+}
+
+impl SelfUpdater {
+    /// 根据全局设置（下载超时、代理、缓存目录）创建，发布清单地址取自 `PLM_RELEASE_URL`
+    /// 或默认地址，发布者公钥见 `release_keys`
+    pub fn from_settings(settings: &GlobalSettings) -> Self {
+        Self {
+            release_url: std::env::var(RELEASE_URL_ENV)
+                .unwrap_or_else(|_| DEFAULT_RELEASE_URL.to_string()),
+            release_keys: release_keys(),
+            settings: settings.clone(),
+        }
+    }
+
+    /// 使用指定的发布清单地址
+    pub fn with_release_url(mut self, url: impl Into<String>) -> Self {
+        self.release_url = url.into();
+        self
+    }
+
+    /// 使用指定的发布者公钥
+    pub fn with_release_keys(mut self, keys: Vec<TrustedKey>) -> Self {
+        self.release_keys = keys;
+        self
+    }
+
+    /// 读取最新的发布清单并校验其签名
+    pub async fn latest_release(&self) -> Result<ReleaseManifest, PluginError> {
+        let client = build_client(
+            Duration::from_secs(self.settings.download_timeout),
+            self.settings.proxy.as_deref(),
+            self.settings.no_proxy.as_deref(),
+        )?;
+        let content = fetch_text(&client, &self.release_url).await?;
+        let signature_url = format!("{}{}", self.release_url, SIGNATURE_SUFFIX);
+        let signature = fetch_text(&client, &signature_url).await?;
+        verify_manifest(&content, &signature, &self.release_keys).await?;
+        ReleaseManifest::parse(&content)
+    }
+
+    /// 下载当前平台的制品，校验后替换 `target` 处的可执行文件
+    pub async fn install(
+        &self,
+        release: &ReleaseManifest,
+        target: &Path,
+    ) -> Result<(), PluginError> {
+        let platform = platform_key();
+        let artifact = release.artifact_for(&platform).ok_or_else(|| {
+            PluginError::NotFound(format!(
+                "PLM {} 没有 {} 平台的发布制品",
+                release.version, platform
+            ))
+        })?;
+        if artifact.sha256.trim().is_empty() {
+            return Err(PluginError::ValidationError(format!(
+                "PLM {} 的 {} 制品缺少校验和",
+                release.version, platform
+            )));
+        }
+
+        let downloaded = Downloader::from_settings(&self.settings)?
+            .download_as(
+                &artifact.url,
+                &cache_file_name(&artifact.url)?,
+                Some(&artifact.sha256),
+            )
+            .await?;
+        if let Err(e) = verify_checksum(&downloaded.path, &artifact.sha256).await {
+            let _ = tokio::fs::remove_file(&downloaded.path).await;
+            return Err(e);
+        }
+
+        // 解压到可执行文件旁边，保证最后的重命名在同一文件系统内完成
+        let staging = TempFileGuard::sibling(target, "update");
+        staging.clear().await?;
+        extract_file(
+            &downloaded.path,
+            ArchiveFormat::from_file_name(&artifact.url),
+            staging.path(),
+        )
+        .await?;
+        let binary = find_binary(staging.path())?;
+        set_executable(&binary)?;
+        replace_executable(&binary, target)
+    }
+}
+
+/// 读取地址的文本内容
+async fn fetch_text(client: &reqwest::Client, url: &str) -> Result<String, PluginError> {
+    client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?
+        .text()
+        .await
+        .map_err(|e| PluginError::NetworkError(format!("读取 {} 的响应失败: {}", url, e)))
+}
+
+/// 用发布者公钥校验发布清单的签名
+///
+/// 制品只按清单中的 SHA-256 校验，清单本身必须由发布者签名
+pub async fn verify_manifest(
+    content: &str,
+    signature: &str,
+    release_keys: &[TrustedKey],
+) -> Result<(), PluginError> {
+    if release_keys.is_empty() {
+        return Err(PluginError::SignatureError(format!(
+            "此构建没有嵌入 PLM 发布者公钥，请通过 {} 指定后再自更新",
+            RELEASE_KEY_ENV
+        )));
+    }
+    let manifest_file = tempfile::NamedTempFile::new()
+        .map_err(|e| PluginError::IoError(format!("创建临时文件失败: {}", e)))?;
+    tokio::fs::write(manifest_file.path(), content)
+        .await
+        .map_err(|e| PluginError::IoError(format!("写入发布清单失败: {}", e)))?;
+    signature::verify_file(manifest_file.path(), signature, release_keys).await
+}
+
+/// 在解压目录中查找 `plm` 可执行文件，目录中只有一个文件时直接使用
+fn find_binary(dir: &Path) -> Result<PathBuf, PluginError> {
+    let expected = format!("plm{}", std::env::consts::EXE_SUFFIX);
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = std::fs::read_dir(&current)
+            .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", current.display(), e)))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path
+                .file_name()
+                .is_some_and(|name| name == expected.as_str())
+            {
+                return Ok(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    match <[PathBuf; 1]>::try_from(files) {
+        Ok([file]) => Ok(file),
+        Err(_) => Err(PluginError::ValidationError(format!(
+            "发布制品中没有找到 {}",
+            expected
+        ))),
+    }
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> Result<(), PluginError> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| PluginError::IoError(format!("设置 {} 的权限失败: {}", path.display(), e)))
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> Result<(), PluginError> {
+    Ok(())
+}
+
+/// 用新的可执行文件替换 `target`
+///
+/// Unix 上直接重命名覆盖，正在运行的进程不受影响；Windows 不允许覆盖正在运行的
+/// 可执行文件，先把它移到 `<target>.old`，替换失败时再移回来。
+fn replace_executable(new: &Path, target: &Path) -> Result<(), PluginError> {
+    let replace_err =
+        |e: std::io::Error| PluginError::IoError(format!("替换 {} 失败: {}", target.display(), e));
+    if cfg!(windows) {
+        let mut old = target.as_os_str().to_os_string();
+        old.push(".old");
+        let old = PathBuf::from(old);
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(target, &old).map_err(replace_err)?;
+        if let Err(e) = std::fs::rename(new, target) {
+            let _ = std::fs::rename(&old, target);
+            return Err(replace_err(e));
+        }
+        Ok(())
+    } else {
+        std::fs::rename(new, target).map_err(replace_err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_manifest() {
+        let manifest = ReleaseManifest::parse(
+            r#"{
+                "version": "v0.2.0",
+                "artifacts": {
+                    "linux-x86_64": { "url": "https://example.com/plm.tar.gz", "sha256": "abc" }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert!(manifest.is_newer_than("0.1.9"));
+        assert!(!manifest.is_newer_than("0.2.0"));
+        assert!(!manifest.is_newer_than("not-a-version"));
+        assert_eq!(
+            manifest.artifact_for("linux-x86_64").unwrap().url,
+            "https://example.com/plm.tar.gz"
+        );
+        assert!(manifest.artifact_for("windows-aarch64").is_none());
+        assert!(ReleaseManifest::parse("{}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_manifest_signature() {
+        // minisign-verify 的测试向量，签名内容为 "test"
+        let signature = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1556193335\tfile:test
+y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==";
+        let keys = [TrustedKey::minisign(
+            "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3",
+        )];
+        verify_manifest("test", signature, &keys).await.unwrap();
+        assert!(matches!(
+            verify_manifest(r#"{"version":"9.9.9"}"#, signature, &keys).await,
+            Err(PluginError::SignatureError(_))
+        ));
+        // 没有发布者公钥时拒绝更新
+        assert!(matches!(
+            verify_manifest("test", signature, &[]).await,
+            Err(PluginError::SignatureError(_))
+        ));
+
+        // 项目配置中的 trusted_keys 不能授权自更新
+        let settings = GlobalSettings {
+            trusted_keys: keys.to_vec(),
+            ..GlobalSettings::default()
+        };
+        let updater = SelfUpdater::from_settings(&settings);
+        assert!(!updater.release_keys.contains(&keys[0]));
+    }
+
+    #[test]
+    fn test_find_and_replace_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let unpacked = dir.path().join("unpacked");
+        std::fs::create_dir_all(unpacked.join("plm-0.2.0/bin")).unwrap();
+        std::fs::write(unpacked.join("plm-0.2.0/README.md"), "docs").unwrap();
+        let binary = unpacked
+            .join("plm-0.2.0/bin")
+            .join(format!("plm{}", std::env::consts::EXE_SUFFIX));
+        std::fs::write(&binary, "new").unwrap();
+        assert_eq!(find_binary(&unpacked).unwrap(), binary);
+
+        let target = dir.path().join("plm");
+        std::fs::write(&target, "old").unwrap();
+        set_executable(&binary).unwrap();
+        replace_executable(&binary, &target).unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+        assert!(!binary.exists());
+    }
+}
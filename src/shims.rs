@@ -0,0 +1,415 @@
+//! PLM 包装脚本
+//!
+//! 插件配置中的 `wrappers` 为工具生成包装脚本，统一项目中调用工具的方式，例如：
+//!
+//! ```json
+//! "wrappers": { "kubectl": { "args": ["--context", "dev"], "env": { "KUBECONFIG": "/etc/kube/dev" } } }
+//! ```
+//!
+//! 安装插件后在 `shims_dir`（默认为项目的 `.plm/shims`）中生成同名脚本：设置环境变量后
+//! 执行已安装版本中的可执行文件，默认参数放在用户参数之前。Unix 上是 `sh` 脚本，
+//! Windows 上是 `.cmd` 文件。生成的脚本记录在运行状态中，卸载对应版本时删除；
+//! 没有 PLM 标记的同名文件不会被覆盖或删除。
+//!
+//! `plm sync --lazy` 不下载尚未安装的插件，而是为它们生成按需安装的脚本：首次运行时通过
+//! `plm shim-exec` 按 `lazy_install` 策略（`prompt` 询问确认，`auto` 直接安装）安装插件，
+//! 然后执行工具。安装时生成的包装脚本会替换同名的按需安装脚本。
+
+use crate::config::{PluginConfig, WrapperConfig};
+use crate::traits::PluginError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// PLM 生成的包装脚本中的标记
+const SHIM_MARKER: &str = "Generated by plm";
+
+/// 按需安装脚本首次运行时安装插件的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LazyInstall {
+    /// 询问确认，非交互环境中不安装
+    #[default]
+    Prompt,
+    /// 直接安装
+    Auto,
+}
+
+/// 插件在包装脚本目录中的命令：配置的包装脚本名，没有配置时为插件名
+pub fn tool_names(config: &PluginConfig) -> Vec<String> {
+    if config.wrappers.is_empty() {
+        vec![config.name.clone()]
+    } else {
+        config.wrappers.keys().cloned().collect()
+    }
+}
+
+/// 在安装位置中查找被包装的可执行文件
+///
+/// 配置了 `target` 时使用该路径（相对于安装位置），否则依次查找 `bin/<name>` 和 `<name>`，
+/// Windows 上同时尝试 `.exe`/`.cmd` 后缀。安装位置本身就是可执行文件时直接使用
+pub fn find_target(install_path: &Path, name: &str, wrapper: &WrapperConfig) -> Option<PathBuf> {
+    if let Some(target) = &wrapper.target {
+        let target = install_path.join(target);
+        return target.is_file().then_some(target);
+    }
+    if install_path.is_file() {
+        return Some(install_path.to_path_buf());
+    }
+
+    let file_names: Vec<String> = if cfg!(windows) {
+        vec![
+            format!("{}.exe", name),
+            format!("{}.cmd", name),
+            name.to_string(),
+        ]
+    } else {
+        vec![name.to_string()]
+    };
+    [install_path.join("bin"), install_path.to_path_buf()]
+        .iter()
+        .flat_map(|dir| file_names.iter().map(move |file| dir.join(file)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// 在 `dir` 下生成包装脚本，返回脚本路径
+pub async fn write_wrapper(
+    dir: &Path,
+    plugin: &str,
+    name: &str,
+    target: &Path,
+    wrapper: &WrapperConfig,
+) -> Result<PathBuf, PluginError> {
+    if let Some(key) = wrapper.env.keys().find(|key| !is_env_name(key)) {
+        return Err(PluginError::ValidationError(format!(
+            "包装脚本 {} 的环境变量名 '{}' 无效",
+            name, key
+        )));
+    }
+    let content = if cfg!(windows) {
+        render_cmd(plugin, target, wrapper)
+    } else {
+        render_sh(plugin, target, wrapper)
+    };
+    write_shim(dir, plugin, name, content).await
+}
+
+/// 在 `dir` 下生成按需安装脚本，返回脚本路径
+///
+/// 脚本以 `program`（plm 可执行文件）运行 `shim-exec`，使用 `config_path` 指定的配置
+pub async fn write_lazy_shim(
+    dir: &Path,
+    plugin: &str,
+    name: &str,
+    program: &Path,
+    config_path: &Path,
+) -> Result<PathBuf, PluginError> {
+    let content = if cfg!(windows) {
+        render_lazy_cmd(plugin, name, program, config_path)
+    } else {
+        render_lazy_sh(plugin, name, program, config_path)
+    };
+    write_shim(dir, plugin, name, content).await
+}
+
+/// 写入脚本文件，不覆盖没有 PLM 标记的同名文件
+async fn write_shim(
+    dir: &Path,
+    plugin: &str,
+    name: &str,
+    content: String,
+) -> Result<PathBuf, PluginError> {
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        return Err(PluginError::ValidationError(format!(
+            "插件 {} 的包装脚本名 '{}' 无效",
+            plugin, name
+        )));
+    }
+    tokio::fs::create_dir_all(dir).await.map_err(|e| {
+        PluginError::IoError(format!("创建包装脚本目录 {} 失败: {}", dir.display(), e))
+    })?;
+
+    let path = if cfg!(windows) {
+        dir.join(format!("{}.cmd", name))
+    } else {
+        dir.join(name)
+    };
+    if let Ok(existing) = tokio::fs::read_to_string(&path).await {
+        if !existing.contains(SHIM_MARKER) {
+            return Err(PluginError::ValidationError(format!(
+                "{} 已存在且不是由 PLM 生成",
+                path.display()
+            )));
+        }
+    }
+
+    tokio::fs::write(&path, content).await.map_err(|e| {
+        PluginError::IoError(format!("写入包装脚本 {} 失败: {}", path.display(), e))
+    })?;
+    make_executable(&path).await?;
+    Ok(path)
+}
+
+/// 删除之前生成的包装脚本，已不存在或不是由 PLM 生成的文件跳过
+pub async fn remove_shims(paths: &[PathBuf]) -> Result<(), PluginError> {
+    for path in paths {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) if content.contains(SHIM_MARKER) => {}
+            Ok(_) => {
+                log::warn!("{} 不是由 PLM 生成，不删除", path.display());
+                continue;
+            }
+            Err(_) => continue,
+        }
+        match tokio::fs::remove_file(path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(PluginError::IoError(format!(
+                    "删除包装脚本 {} 失败: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 生成 `sh` 包装脚本
+fn render_sh(plugin: &str, target: &Path, wrapper: &WrapperConfig) -> String {
+    let mut script = format!("#!/bin/sh\n# {} (wrapper for {})\n", SHIM_MARKER, plugin);
+    for (key, value) in &wrapper.env {
+        script.push_str(&format!("export {}={}\n", key, sh_quote(value)));
+    }
+    let command = std::iter::once(target.to_string_lossy().into_owned())
+        .chain(wrapper.args.iter().cloned())
+        .map(|arg| sh_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    script.push_str(&format!("exec {} \"$@\"\n", command));
+    script
+}
+
+/// 生成 Windows `.cmd` 包装脚本
+fn render_cmd(plugin: &str, target: &Path, wrapper: &WrapperConfig) -> String {
+    let mut lines = vec![
+        "@echo off".to_string(),
+        format!("rem {} (wrapper for {})", SHIM_MARKER, plugin),
+        "setlocal".to_string(),
+    ];
+    for (key, value) in &wrapper.env {
+        lines.push(format!("set \"{}={}\"", key, value.replace('%', "%%")));
+    }
+    let command = std::iter::once(target.to_string_lossy().into_owned())
+        .chain(wrapper.args.iter().cloned())
+        .map(|arg| cmd_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    lines.push(format!("{} %*", command));
+    lines.join("\r\n") + "\r\n"
+}
+
+/// 生成 `sh` 按需安装脚本
+fn render_lazy_sh(plugin: &str, name: &str, program: &Path, config_path: &Path) -> String {
+    let command = [
+        program.to_string_lossy().into_owned(),
+        "--config".to_string(),
+        config_path.to_string_lossy().into_owned(),
+        "shim-exec".to_string(),
+        plugin.to_string(),
+        name.to_string(),
+    ]
+    .iter()
+    .map(|arg| sh_quote(arg))
+    .collect::<Vec<_>>()
+    .join(" ");
+    format!(
+        "#!/bin/sh\n# {} (installs {} on first run)\nexec {} \"$@\"\n",
+        SHIM_MARKER, plugin, command
+    )
+}
+
+/// 生成 Windows `.cmd` 按需安装脚本
+fn render_lazy_cmd(plugin: &str, name: &str, program: &Path, config_path: &Path) -> String {
+    let command = [
+        program.to_string_lossy().into_owned(),
+        "--config".to_string(),
+        config_path.to_string_lossy().into_owned(),
+        "shim-exec".to_string(),
+        plugin.to_string(),
+        name.to_string(),
+    ]
+    .iter()
+    .map(|arg| cmd_quote(arg))
+    .collect::<Vec<_>>()
+    .join(" ");
+    [
+        "@echo off".to_string(),
+        format!("rem {} (installs {} on first run)", SHIM_MARKER, plugin),
+        format!("{} %*", command),
+    ]
+    .join("\r\n")
+        + "\r\n"
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 单引号包裹，内部的单引号写成 `'\''`
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn cmd_quote(value: &str) -> String {
+    let value = value.replace('%', "%%");
+    if value.is_empty() || value.contains([' ', '\t', '&', '|', '<', '>', '^', '(', ')']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(unix)]
+async fn make_executable(path: &Path) -> Result<(), PluginError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .await
+        .map_err(|e| PluginError::PermissionDenied(format!("设置包装脚本可执行权限失败: {}", e)))
+}
+
+#[cfg(not(unix))]
+async fn make_executable(_path: &Path) -> Result<(), PluginError> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_wrapper() -> WrapperConfig {
+        WrapperConfig {
+            target: None,
+            args: vec!["--context".to_string(), "it's dev".to_string()],
+            env: [("KUBECONFIG".to_string(), "/etc/kube/100%".to_string())].into(),
+        }
+    }
+
+    #[test]
+    fn test_render_wrappers() {
+        let script = render_sh(
+            "kubectl",
+            Path::new("/opt/kubectl/bin/kubectl"),
+            &sample_wrapper(),
+        );
+        assert!(script.starts_with("#!/bin/sh\n# Generated by plm (wrapper for kubectl)\n"));
+        assert!(script.contains("export KUBECONFIG='/etc/kube/100%'\n"));
+        assert!(
+            script.ends_with("exec '/opt/kubectl/bin/kubectl' '--context' 'it'\\''s dev' \"$@\"\n")
+        );
+
+        let script = render_cmd(
+            "kubectl",
+            Path::new("C:\\Tools\\kubectl.exe"),
+            &sample_wrapper(),
+        );
+        assert!(script.contains("set \"KUBECONFIG=/etc/kube/100%%\"\r\n"));
+        assert!(script.ends_with("C:\\Tools\\kubectl.exe --context \"it's dev\" %*\r\n"));
+    }
+
+    #[test]
+    fn test_render_lazy_shims() {
+        let script = render_lazy_sh(
+            "kubectl",
+            "kubectl",
+            Path::new("/usr/local/bin/plm"),
+            Path::new("/work/my app/plm.json"),
+        );
+        assert!(
+            script.starts_with("#!/bin/sh\n# Generated by plm (installs kubectl on first run)\n")
+        );
+        assert!(script.ends_with(
+            "exec '/usr/local/bin/plm' '--config' '/work/my app/plm.json' 'shim-exec' 'kubectl' 'kubectl' \"$@\"\n"
+        ));
+
+        let script = render_lazy_cmd(
+            "kubectl",
+            "kubectl",
+            Path::new("C:\\plm\\plm.exe"),
+            Path::new("C:\\work\\my app\\plm.json"),
+        );
+        assert!(script.ends_with(
+            "C:\\plm\\plm.exe --config \"C:\\work\\my app\\plm.json\" shim-exec kubectl kubectl %*\r\n"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_write_and_remove_wrapper() {
+        let dir = tempfile::tempdir().unwrap();
+        let install = dir.path().join("kubectl/1.30.0");
+        std::fs::create_dir_all(install.join("bin")).unwrap();
+        std::fs::write(install.join("bin/kubectl"), "").unwrap();
+        std::fs::write(install.join("tool"), "").unwrap();
+
+        let wrapper = sample_wrapper();
+        let target = find_target(&install, "kubectl", &wrapper).unwrap();
+        assert_eq!(target, install.join("bin/kubectl"));
+        assert!(find_target(&install, "missing", &wrapper).is_none());
+        let custom = WrapperConfig {
+            target: Some("tool".to_string()),
+            ..WrapperConfig::default()
+        };
+        assert_eq!(
+            find_target(&install, "kubectl", &custom).unwrap(),
+            install.join("tool")
+        );
+
+        let shims = dir.path().join("shims");
+        let path = write_wrapper(&shims, "kubectl", "kubectl", &target, &wrapper)
+            .await
+            .unwrap();
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains(SHIM_MARKER));
+        // 重新生成时覆盖自己生成的脚本
+        write_wrapper(&shims, "kubectl", "kubectl", &target, &wrapper)
+            .await
+            .unwrap();
+        assert!(
+            write_wrapper(&shims, "kubectl", "../kubectl", &target, &wrapper)
+                .await
+                .is_err()
+        );
+
+        let mut invalid = wrapper.clone();
+        invalid.env.insert("BAD-NAME".to_string(), String::new());
+        assert!(
+            write_wrapper(&shims, "kubectl", "kubectl", &target, &invalid)
+                .await
+                .is_err()
+        );
+
+        // 不是由 PLM 生成的同名文件既不覆盖也不删除
+        let foreign = path.with_file_name(
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .replace("kubectl", "helm"),
+        );
+        std::fs::write(&foreign, "#!/bin/sh\n").unwrap();
+        assert!(write_wrapper(&shims, "helm", "helm", &target, &wrapper)
+            .await
+            .is_err());
+        remove_shims(&[path.clone(), foreign.clone()])
+            .await
+            .unwrap();
+        assert!(!path.exists());
+        assert!(foreign.exists());
+    }
+}
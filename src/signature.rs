@@ -0,0 +1,334 @@
+//! PLM 制品签名校验
+//!
+//! 支持三种签名格式：
+//!
+//! - minisign：使用 `minisign-verify` 在进程内校验
+//! - GPG（ASCII armor 分离签名）：调用系统 `gpg`，并要求签名者指纹在受信任列表中
+//! - Sigstore 无密钥签名（cosign bundle）：调用系统 `cosign verify-blob`，
+//!   校验证书身份、OIDC 签发者并查询 Rekor 透明日志
+
+use crate::permissions;
+use crate::traits::PluginError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// cosign 默认查询的 Rekor 透明日志
+const DEFAULT_REKOR_URL: &str = "https://rekor.sigstore.dev";
+
+/// 签名类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureKind {
+    Minisign,
+    Gpg,
+    Sigstore,
+}
+
+/// 受信任的公钥
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TrustedKey {
+    pub kind: SignatureKind,
+    /// minisign 为 base64 公钥；GPG 为签名者指纹
+    pub key: String,
+    /// 备注，如公钥所属的发布者
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+impl TrustedKey {
+    /// 创建 minisign 公钥
+    pub fn minisign(public_key: &str) -> Self {
+        Self {
+            kind: SignatureKind::Minisign,
+            key: public_key.to_string(),
+            comment: None,
+        }
+    }
+
+    /// 创建 GPG 指纹
+    pub fn gpg(fingerprint: &str) -> Self {
+        Self {
+            kind: SignatureKind::Gpg,
+            key: fingerprint.to_string(),
+            comment: None,
+        }
+    }
+}
+
+/// Sigstore 无密钥签名校验策略，配置在插件源上
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct KeylessPolicy {
+    /// 签名证书中的身份，如 CI 工作流地址或邮箱
+    pub identity: String,
+    /// 签发证书的 OIDC 提供方，如 `https://token.actions.githubusercontent.com`
+    pub oidc_issuer: String,
+    /// 自定义 Rekor 透明日志地址
+    #[serde(default)]
+    pub rekor_url: Option<String>,
+}
+
+impl KeylessPolicy {
+    /// 创建无密钥签名校验策略
+    pub fn new(identity: &str, oidc_issuer: &str) -> Self {
+        Self {
+            identity: identity.to_string(),
+            oidc_issuer: oidc_issuer.to_string(),
+            rekor_url: None,
+        }
+    }
+}
+
+/// 根据签名内容判断签名类型
+pub fn detect_kind(signature: &str) -> Option<SignatureKind> {
+    let trimmed = signature.trim_start();
+    if trimmed.starts_with("-----BEGIN PGP SIGNATURE-----") {
+        Some(SignatureKind::Gpg)
+    } else if trimmed.starts_with("untrusted comment:") {
+        Some(SignatureKind::Minisign)
+    } else if trimmed.starts_with('{') {
+        Some(SignatureKind::Sigstore)
+    } else {
+        None
+    }
+}
+
+/// 使用受信任公钥校验文件签名，任一公钥校验通过即成功
+pub async fn verify_file(
+    path: &Path,
+    signature: &str,
+    trusted_keys: &[TrustedKey],
+) -> Result<(), PluginError> {
+    let kind = detect_kind(signature)
+        .ok_or_else(|| PluginError::SignatureError("无法识别的签名格式".to_string()))?;
+    if kind == SignatureKind::Sigstore {
+        return Err(PluginError::SignatureError(
+            "Sigstore 签名需要在插件源上配置 keyless 校验策略".to_string(),
+        ));
+    }
+
+    let keys: Vec<&TrustedKey> = trusted_keys.iter().filter(|k| k.kind == kind).collect();
+    if keys.is_empty() {
+        return Err(PluginError::SignatureError(format!(
+            "没有配置 {:?} 类型的受信任公钥",
+            kind
+        )));
+    }
+
+    match kind {
+        SignatureKind::Minisign => verify_minisign(path, signature, &keys).await,
+        SignatureKind::Gpg => verify_gpg(path, signature, &keys).await,
+        SignatureKind::Sigstore => unreachable!("handled above"),
+    }
+}
+
+/// 使用 cosign 校验 Sigstore bundle（包含签名、证书和 Rekor 记录）
+pub async fn verify_keyless(
+    path: &Path,
+    bundle: &str,
+    policy: &KeylessPolicy,
+) -> Result<(), PluginError> {
+    let rekor_url = policy.rekor_url.as_deref().unwrap_or(DEFAULT_REKOR_URL);
+    permissions::check(|guard| guard.check_network(rekor_url))?;
+    permissions::check(|guard| guard.check_subprocess("cosign"))?;
+    let cosign = which::which("cosign").map_err(|_| {
+        PluginError::SignatureError("未找到 cosign，无法校验 Sigstore 签名".to_string())
+    })?;
+
+    let bundle_file = tempfile::NamedTempFile::new()
+        .map_err(|e| PluginError::IoError(format!("创建临时文件失败: {}", e)))?;
+    tokio::fs::write(bundle_file.path(), bundle)
+        .await
+        .map_err(|e| PluginError::IoError(format!("写入签名文件失败: {}", e)))?;
+
+    let mut command = permissions::command(cosign);
+    command
+        .arg("verify-blob")
+        .arg("--bundle")
+        .arg(bundle_file.path())
+        .args(["--certificate-identity", &policy.identity])
+        .args(["--certificate-oidc-issuer", &policy.oidc_issuer]);
+    if let Some(rekor_url) = &policy.rekor_url {
+        command.args(["--rekor-url", rekor_url]);
+    }
+
+    let output = command
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| PluginError::SignatureError(format!("运行 cosign 失败: {}", e)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(PluginError::SignatureError(format!(
+            "{} 的 Sigstore 签名校验失败: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+async fn verify_minisign(
+    path: &Path,
+    signature: &str,
+    keys: &[&TrustedKey],
+) -> Result<(), PluginError> {
+    let signature = minisign_verify::Signature::decode(signature)
+        .map_err(|e| PluginError::SignatureError(format!("minisign 签名无效: {}", e)))?;
+    let content = tokio::fs::read(path)
+        .await
+        .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e)))?;
+
+    for key in keys {
+        let Ok(public_key) = minisign_verify::PublicKey::from_base64(key.key.trim()) else {
+            continue;
+        };
+        if public_key.verify(&content, &signature, false).is_ok() {
+            return Ok(());
+        }
+    }
+
+    Err(PluginError::SignatureError(format!(
+        "{} 的 minisign 签名与受信任公钥不匹配",
+        path.display()
+    )))
+}
+
+async fn verify_gpg(path: &Path, signature: &str, keys: &[&TrustedKey]) -> Result<(), PluginError> {
+    permissions::check(|guard| guard.check_subprocess("gpg"))?;
+    let gpg = which::which("gpg")
+        .map_err(|_| PluginError::SignatureError("未找到 gpg，无法校验 GPG 签名".to_string()))?;
+
+    let sig_file = tempfile::NamedTempFile::new()
+        .map_err(|e| PluginError::IoError(format!("创建临时文件失败: {}", e)))?;
+    tokio::fs::write(sig_file.path(), signature)
+        .await
+        .map_err(|e| PluginError::IoError(format!("写入签名文件失败: {}", e)))?;
+
+    let output = permissions::command(gpg)
+        .args(["--batch", "--status-fd", "1", "--verify"])
+        .arg(sig_file.path())
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| PluginError::SignatureError(format!("运行 gpg 失败: {}", e)))?;
+
+    let status = String::from_utf8_lossy(&output.stdout);
+    match validsig_fingerprints(&status) {
+        Some(fingerprints)
+            if output.status.success()
+                && keys.iter().any(|k| {
+                    fingerprints
+                        .iter()
+                        .any(|f| normalize_fingerprint(&k.key) == normalize_fingerprint(f))
+                }) =>
+        {
+            Ok(())
+        }
+        Some(fingerprints) if output.status.success() => Err(PluginError::SignatureError(format!(
+            "签名者 {} 不在受信任列表中",
+            fingerprints.join(" / ")
+        ))),
+        _ => Err(PluginError::SignatureError(format!(
+            "{} 的 GPG 签名校验失败",
+            path.display()
+        ))),
+    }
+}
+
+/// GPG 状态输出中签名密钥（可能是子密钥）和主密钥的指纹
+///
+/// 状态行格式: `[GNUPG:] VALIDSIG <指纹> <日期> <时间戳> <过期时间> <版本> <保留> <公钥算法>
+/// <哈希算法> <签名类别> [<主密钥指纹>]`
+fn validsig_fingerprints(status: &str) -> Option<Vec<&str>> {
+    let fields: Vec<&str> = status
+        .lines()
+        .find_map(|line| line.strip_prefix("[GNUPG:] VALIDSIG "))?
+        .split_whitespace()
+        .collect();
+    let mut fingerprints = vec![*fields.first()?];
+    if let Some(primary) = fields.get(9).filter(|primary| **primary != fields[0]) {
+        fingerprints.push(primary);
+    }
+    Some(fingerprints)
+}
+
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_kind() {
+        assert_eq!(
+            detect_kind("untrusted comment: signature from minisign secret key\nRWQ..."),
+            Some(SignatureKind::Minisign)
+        );
+        assert_eq!(
+            detect_kind("-----BEGIN PGP SIGNATURE-----\n\niQ..."),
+            Some(SignatureKind::Gpg)
+        );
+        assert_eq!(
+            detect_kind("{\"mediaType\": \"application/vnd.dev.sigstore.bundle+json\"}"),
+            Some(SignatureKind::Sigstore)
+        );
+        assert_eq!(detect_kind("garbage"), None);
+    }
+
+    #[test]
+    fn test_validsig_fingerprints() {
+        let status = "[GNUPG:] NEWSIG\n\
+            [GNUPG:] VALIDSIG 1111AAAA 2024-01-01 1704067200 0 4 0 1 10 00 2222BBBB\n\
+            [GNUPG:] TRUST_UNDEFINED 0 pgp\n";
+        assert_eq!(
+            validsig_fingerprints(status),
+            Some(vec!["1111AAAA", "2222BBBB"])
+        );
+        // 直接用主密钥签名时两个指纹相同
+        assert_eq!(
+            validsig_fingerprints("[GNUPG:] VALIDSIG AAAA 2024-01-01 0 0 4 0 1 10 00 AAAA"),
+            Some(vec!["AAAA"])
+        );
+        assert_eq!(validsig_fingerprints("[GNUPG:] BADSIG AAAA"), None);
+    }
+
+    #[tokio::test]
+    async fn test_verifies_minisign_signature() {
+        // minisign-verify 的测试向量
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("artifact");
+        tokio::fs::write(&path, "test").await.unwrap();
+        let signature = "untrusted comment: signature from minisign secret key
+RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=
+trusted comment: timestamp:1556193335\tfile:test
+y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==";
+        let keys = [TrustedKey::minisign(
+            "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3",
+        )];
+        verify_file(&path, signature, &keys).await.unwrap();
+
+        tokio::fs::write(&path, "tampered").await.unwrap();
+        assert!(matches!(
+            verify_file(&path, signature, &keys).await,
+            Err(PluginError::SignatureError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_without_trusted_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("artifact");
+        tokio::fs::write(&path, "data").await.unwrap();
+
+        let result = verify_file(&path, "untrusted comment: x\nRWQ", &[]).await;
+        assert!(matches!(result, Err(PluginError::SignatureError(_))));
+    }
+}
@@ -0,0 +1,485 @@
+//! PLM 运行状态存储
+//!
+//! 记录每个插件当前使用的版本、PLM 安装过的各个版本及其安装位置和时间，
+//! 保存在 `plugin_dir/state.json`。
+//! 插件元数据只描述插件本身，状态存储记录的是在本机实际执行过的操作，
+//! `plm status` 据此显示运行状态，并与配置比较得出尚未同步的变更。
+//! 插件从配置中移除后无法再通过插件实现查询，`plm list --installed`、`plm uninstall`
+//! 和 `plm prune` 依靠这里的记录找到之前安装的版本。
+
+use crate::config::ProjectConfig;
+use crate::core::same_version;
+use crate::target::TargetPlatform;
+use crate::temp::TempFileGuard;
+use crate::traits::PluginError;
+use crate::version::{Version, VersionReq};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+/// 状态文件名，位于 `plugin_dir` 下
+pub const STATE_FILE: &str = "state.json";
+
+/// 单个插件的运行状态
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginState {
+    /// 最近一次安装或更新到的版本，卸载该版本后清空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// PLM 安装过且尚未卸载的版本
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub installed: BTreeMap<String, InstalledVersion>,
+    /// 为其他平台安装的版本，键为目标平台（见 `target` 模块），不影响当前使用的版本
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub targets: BTreeMap<String, BTreeMap<String, InstalledVersion>>,
+}
+
+/// 一个已安装的版本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledVersion {
+    /// 安装位置，插件没有报告时为空（如系统包管理器安装的版本）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    pub installed_at: DateTime<Utc>,
+    /// 为该版本生成的启动器文件，卸载时删除
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub launchers: Vec<PathBuf>,
+    /// 为该版本生成的包装脚本，卸载时删除
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shims: Vec<PathBuf>,
+}
+
+/// 插件已从配置中移除或被禁用，但仍有记录的已安装版本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Orphan {
+    pub plugin: String,
+    pub version: String,
+    pub path: Option<PathBuf>,
+}
+
+/// 配置与运行状态不一致、需要 `plm sync` 或 `plm uninstall` 处理的变更
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PendingChange {
+    /// 启用的插件尚未安装过
+    NotInstalled { plugin: String, version: String },
+    /// 配置中的版本与当前使用的版本不一致
+    VersionChanged {
+        plugin: String,
+        active: String,
+        configured: String,
+    },
+    /// 插件已从配置中移除或被禁用，但仍有使用中的版本
+    Removed { plugin: String, active: String },
+}
+
+impl fmt::Display for PendingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PendingChange::NotInstalled { plugin, version } => {
+                write!(f, "{} {} is configured but not installed", plugin, version)
+            }
+            PendingChange::VersionChanged {
+                plugin,
+                active,
+                configured,
+            } => write!(
+                f,
+                "{} is at {} but the config asks for {}",
+                plugin, active, configured
+            ),
+            PendingChange::Removed { plugin, active } => write!(
+                f,
+                "{} {} is still installed but no longer enabled in the config",
+                plugin, active
+            ),
+        }
+    }
+}
+
+/// 插件运行状态存储，键为插件名称
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StateStore {
+    #[serde(default)]
+    plugins: BTreeMap<String, PluginState>,
+}
+
+impl StateStore {
+    /// 读取状态，文件不存在时返回空状态
+    ///
+    /// 文件损坏时返回错误而不是空状态，避免下次保存覆盖掉所有安装记录
+    pub async fn load(path: &Path) -> Result<Self, PluginError> {
+        let content = match tokio::fs::read_to_string(path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(PluginError::IoError(format!(
+                    "读取运行状态 {} 失败: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        serde_json::from_str(&content).map_err(|e| {
+            PluginError::ConfigError(format!(
+                "运行状态 {} 已损坏，请修复或删除后重试: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// 写入状态：先写入同目录下的临时文件并同步到磁盘，再重命名替换，写入中断不会损坏原文件
+    pub async fn save(&self, path: &Path) -> Result<(), PluginError> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| PluginError::IoError(format!("创建插件目录失败: {}", e)))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| PluginError::IoError(format!("序列化运行状态失败: {}", e)))?;
+        let staging = TempFileGuard::sibling(path, "tmp");
+        let write = async {
+            let mut file = tokio::fs::File::create(staging.path()).await?;
+            file.write_all(content.as_bytes()).await?;
+            file.sync_all().await
+        };
+        write
+            .await
+            .map_err(|e| PluginError::IoError(format!("写入运行状态失败: {}", e)))?;
+        staging.persist(path).await
+    }
+
+    /// 获取插件的运行状态
+    pub fn get(&self, name: &str) -> Option<&PluginState> {
+        self.plugins.get(name)
+    }
+
+    /// 按名称排序的所有插件状态
+    pub fn plugins(&self) -> impl Iterator<Item = (&str, &PluginState)> {
+        self.plugins
+            .iter()
+            .map(|(name, state)| (name.as_str(), state))
+    }
+
+    /// 记录安装完成，`path` 为插件报告的安装位置
+    pub fn record_install(&mut self, name: &str, version: &str, path: Option<&Path>) {
+        let now = Utc::now();
+        let state = self.plugins.entry(name.to_string()).or_default();
+        state.active_version = Some(version.to_string());
+        state.installed_at = Some(now);
+        state.installed.insert(
+            version.to_string(),
+            InstalledVersion {
+                path: path.map(Path::to_path_buf),
+                installed_at: now,
+                launchers: Vec::new(),
+                shims: Vec::new(),
+            },
+        );
+    }
+
+    /// 记录为其他平台安装的版本
+    pub fn record_target_install(
+        &mut self,
+        name: &str,
+        target: &TargetPlatform,
+        version: &str,
+        path: Option<&Path>,
+    ) {
+        self.plugins
+            .entry(name.to_string())
+            .or_default()
+            .targets
+            .entry(target.to_string())
+            .or_default()
+            .insert(
+                version.to_string(),
+                InstalledVersion {
+                    path: path.map(Path::to_path_buf),
+                    installed_at: Utc::now(),
+                    launchers: Vec::new(),
+                    shims: Vec::new(),
+                },
+            );
+    }
+
+    /// 为其他平台安装的版本记录
+    pub fn target_version(
+        &self,
+        name: &str,
+        target: &TargetPlatform,
+        version: &str,
+    ) -> Option<&InstalledVersion> {
+        self.plugins
+            .get(name)?
+            .targets
+            .get(&target.to_string())?
+            .iter()
+            .find(|(installed, _)| same_version(installed, version))
+            .map(|(_, installed)| installed)
+    }
+
+    /// 记录更新完成，更新不报告安装位置，已有记录时保留原来的位置
+    pub fn record_update(&mut self, name: &str, version: &str) {
+        let now = Utc::now();
+        let state = self.plugins.entry(name.to_string()).or_default();
+        state.active_version = Some(version.to_string());
+        state.updated_at = Some(now);
+        state
+            .installed
+            .entry(version.to_string())
+            .or_insert(InstalledVersion {
+                path: None,
+                installed_at: now,
+                launchers: Vec::new(),
+                shims: Vec::new(),
+            });
+    }
+
+    /// 记录为已安装版本生成的启动器
+    pub fn record_launchers(&mut self, name: &str, version: &str, launchers: Vec<PathBuf>) {
+        if let Some(installed) = self
+            .plugins
+            .get_mut(name)
+            .and_then(|state| state.installed.get_mut(version))
+        {
+            installed.launchers = launchers;
+        }
+    }
+
+    /// 记录为已安装版本生成的包装脚本
+    ///
+    /// 包装脚本按命令名生成，新版本的脚本会覆盖其他版本的同名脚本，这些路径从其他版本的记录中移除
+    pub fn record_shims(&mut self, name: &str, version: &str, shims: Vec<PathBuf>) {
+        for state in self.plugins.values_mut() {
+            for installed in state.installed.values_mut() {
+                installed.shims.retain(|path| !shims.contains(path));
+            }
+        }
+        if let Some(installed) = self
+            .plugins
+            .get_mut(name)
+            .and_then(|state| state.installed.get_mut(version))
+        {
+            installed.shims = shims;
+        }
+    }
+
+    /// 记录卸载，卸载的是当前使用的版本时清空 `active_version`
+    pub fn record_uninstall(&mut self, name: &str, version: &str) {
+        if let Some(state) = self.plugins.get_mut(name) {
+            if state
+                .active_version
+                .as_deref()
+                .is_some_and(|active| same_version(active, version))
+            {
+                state.active_version = None;
+            }
+            state
+                .installed
+                .retain(|installed, _| !same_version(installed, version));
+        }
+    }
+
+    /// 插件记录的已安装版本
+    pub fn installed_version(&self, name: &str, version: &str) -> Option<&InstalledVersion> {
+        self.plugins
+            .get(name)?
+            .installed
+            .iter()
+            .find(|(installed, _)| same_version(installed, version))
+            .map(|(_, installed)| installed)
+    }
+
+    /// 插件记录的所有已安装版本，按版本排序
+    pub fn installed_versions(&self, name: &str) -> Vec<String> {
+        self.plugins
+            .get(name)
+            .map(|state| state.installed.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 配置中不存在或已禁用的插件仍记录的已安装版本，按插件名称和版本排序
+    pub fn orphans(&self, config: &ProjectConfig) -> Vec<Orphan> {
+        self.plugins
+            .iter()
+            .filter(|(name, _)| {
+                !config
+                    .plugins
+                    .get(name.as_str())
+                    .is_some_and(|plugin| plugin.enabled)
+            })
+            .flat_map(|(name, state)| {
+                state.installed.iter().map(|(version, installed)| Orphan {
+                    plugin: name.clone(),
+                    version: version.clone(),
+                    path: installed.path.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// 比较配置与运行状态，返回按插件名称排序的待处理变更
+    ///
+    /// 只比较配置了版本的插件：固定版本要求当前版本与之相同，范围约束要求当前版本满足约束。
+    pub fn pending_changes(&self, config: &ProjectConfig) -> Vec<PendingChange> {
+        let mut changes = Vec::new();
+        let mut names: Vec<&String> = config.plugins.keys().collect();
+        names.sort();
+
+        for name in names {
+            let plugin_config = &config.plugins[name];
+            let active = self
+                .plugins
+                .get(name.as_str())
+                .and_then(|state| state.active_version.clone());
+            if !plugin_config.enabled {
+                if let Some(active) = active {
+                    changes.push(PendingChange::Removed {
+                        plugin: name.clone(),
+                        active,
+                    });
+                }
+                continue;
+            }
+            let Some(configured) = plugin_config.get_version() else {
+                continue;
+            };
+            match active {
+                None => changes.push(PendingChange::NotInstalled {
+                    plugin: name.clone(),
+                    version: configured.to_string(),
+                }),
+                Some(active) if !satisfies(&active, configured) => {
+                    changes.push(PendingChange::VersionChanged {
+                        plugin: name.clone(),
+                        active,
+                        configured: configured.to_string(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+
+        for (name, state) in &self.plugins {
+            if config.plugins.contains_key(name) {
+                continue;
+            }
+            if let Some(active) = &state.active_version {
+                changes.push(PendingChange::Removed {
+                    plugin: name.clone(),
+                    active: active.clone(),
+                });
+            }
+        }
+        changes
+    }
+}
+
+/// 当前版本是否满足配置中的固定版本或范围约束
+fn satisfies(active: &str, configured: &str) -> bool {
+    if configured == "latest" {
+        return true;
+    }
+    if Version::parse(configured).is_ok() {
+        return same_version(active, configured);
+    }
+    match (VersionReq::parse(configured), Version::parse(active)) {
+        (Ok(req), Ok(version)) => req.matches(&version),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PluginConfig;
+
+    #[tokio::test]
+    async fn test_record_and_pending_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STATE_FILE);
+
+        let mut state = StateStore::default();
+        state.record_install("node", "18.19.0", None);
+        state.record_install("python", "3.11.7", None);
+        state.record_update("python", "3.12.1");
+        state.record_install("jq", "1.7.1", Some(Path::new("/opt/plm/jq/1.7.1")));
+        state.save(&path).await.unwrap();
+
+        let mut state = StateStore::load(&path).await.unwrap();
+        let python = state.get("python").unwrap();
+        assert_eq!(python.active_version.as_deref(), Some("3.12.1"));
+        assert!(python.installed_at.is_some() && python.updated_at.is_some());
+        assert_eq!(state.installed_versions("python"), ["3.11.7", "3.12.1"]);
+        assert_eq!(
+            state
+                .installed_version("jq", "v1.7.1")
+                .unwrap()
+                .path
+                .as_deref(),
+            Some(Path::new("/opt/plm/jq/1.7.1"))
+        );
+
+        let mut config = ProjectConfig::default_for_project("demo", ".");
+        for (name, version) in [("node", "20.11.1"), ("python", "^3.12"), ("go", "1.22.0")] {
+            let mut plugin = PluginConfig::new(name);
+            plugin.enabled = true;
+            plugin.set_version(version);
+            config.add_plugin(plugin);
+        }
+        assert_eq!(
+            state.pending_changes(&config),
+            [
+                PendingChange::NotInstalled {
+                    plugin: "go".to_string(),
+                    version: "1.22.0".to_string()
+                },
+                PendingChange::VersionChanged {
+                    plugin: "node".to_string(),
+                    active: "18.19.0".to_string(),
+                    configured: "20.11.1".to_string()
+                },
+                PendingChange::Removed {
+                    plugin: "jq".to_string(),
+                    active: "1.7.1".to_string()
+                },
+            ]
+        );
+
+        assert_eq!(
+            state.orphans(&config),
+            [Orphan {
+                plugin: "jq".to_string(),
+                version: "1.7.1".to_string(),
+                path: Some(PathBuf::from("/opt/plm/jq/1.7.1")),
+            }]
+        );
+
+        state.record_uninstall("jq", "1.7.1");
+        assert!(state.get("jq").unwrap().active_version.is_none());
+        assert!(state.installed_versions("jq").is_empty());
+        assert!(state.orphans(&config).is_empty());
+        assert!(StateStore::load(&dir.path().join("missing.json"))
+            .await
+            .unwrap()
+            .plugins()
+            .next()
+            .is_none());
+
+        // 损坏的状态文件不会被当作空状态覆盖
+        std::fs::write(&path, "{\"plugins\": {").unwrap();
+        assert!(matches!(
+            StateStore::load(&path).await,
+            Err(PluginError::ConfigError(_))
+        ));
+    }
+}
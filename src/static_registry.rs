@@ -0,0 +1,756 @@
+//! PLM 静态文件注册表
+//!
+//! 静态注册表只是一个目录：`index.json` 描述全部插件及其各平台的制品，制品文件和索引放在一起。
+//! 目录可以用任意 HTTPS 静态文件服务发布，也可以直接以 `file://` 地址使用（如共享盘），
+//! 不需要运行注册表服务：
+//!
+//! ```json
+//! "sources": [{ "type": "static", "url": "https://plugins.example.com/plm" }],
+//! "plugins": {
+//!   "tool": { "source": { "type": "static", "url": "https://plugins.example.com/plm" }, ... }
+//! }
+//! ```
+//!
+//! `static` 插件源会参与 `plm search`；插件的源为 `static` 时，插件从该注册表的索引中查询版本并安装。
+//!
+//! `plm registry build <dir>` 从 `<dir>/manifests/*.json` 中的插件清单生成索引：
+//!
+//! ```json
+//! {
+//!   "name": "tool",
+//!   "description": "Internal tool",
+//!   "releases": [
+//!     { "version": "1.2.0", "platform": "linux-x64",
+//!       "file": "artifacts/tool-1.2.0-linux-x64.tar.gz", "entry_points": ["bin/tool"] }
+//!   ]
+//! }
+//! ```
+//!
+//! 生成时计算每个制品的 SHA-256 和大小。索引中的下载地址相对于注册表地址，
+//! 目录换一个位置发布时无需重新生成。
+
+use crate::auth::Credentials;
+use crate::config::{current_platform, GlobalSettings, PluginConfig, PluginSource};
+use crate::core::same_version;
+use crate::download::{build_client, sha256_file, Downloader};
+use crate::permissions;
+use crate::registry::RegistryEntry;
+use crate::target::TargetPlatform;
+use crate::traits::{
+    Capability, InstallOptions, Plugin, PluginError, PluginMetadata, PluginStatus, VersionInfo,
+};
+use crate::version::Version;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// 索引文件名
+pub const INDEX_FILE: &str = "index.json";
+
+/// 插件清单目录名
+pub const MANIFESTS_DIR: &str = "manifests";
+
+/// 静态注册表索引
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaticIndex {
+    /// 插件名称 -> 插件
+    #[serde(default)]
+    pub plugins: BTreeMap<String, IndexedPlugin>,
+}
+
+/// 索引中的一个插件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedPlugin {
+    pub metadata: PluginMetadata,
+    /// 各版本各平台的制品，最新的在前
+    #[serde(default)]
+    pub versions: Vec<VersionInfo>,
+}
+
+impl IndexedPlugin {
+    /// 注册表格式的插件信息，`latest` 为最新的未撤回稳定版本
+    pub fn entry(&self) -> RegistryEntry {
+        RegistryEntry {
+            metadata: self.metadata.clone(),
+            latest: self
+                .versions
+                .iter()
+                .find(|info| !info.prerelease && !info.yanked)
+                .cloned(),
+        }
+    }
+}
+
+impl StaticIndex {
+    /// 名称、描述或标签包含关键字（不区分大小写）的插件
+    pub fn search(&self, query: &str) -> Vec<RegistryEntry> {
+        self.plugins
+            .values()
+            .map(IndexedPlugin::entry)
+            .filter(|entry| entry.matches(query))
+            .collect()
+    }
+
+    /// 把相对下载地址和签名地址解析为相对于注册表地址的绝对地址
+    fn resolve_urls(&mut self, base: &url::Url) {
+        let resolve = |value: &mut String| {
+            if url::Url::parse(value).is_err() {
+                if let Ok(resolved) = base.join(value) {
+                    *value = resolved.to_string();
+                }
+            }
+        };
+        for info in self
+            .plugins
+            .values_mut()
+            .flat_map(|plugin| plugin.versions.iter_mut())
+        {
+            resolve(&mut info.download_url);
+            info.mirror_urls.iter_mut().for_each(resolve);
+            // 内联签名以注释行开头，不会被当作相对地址
+            if let Some(signature) = info.signature.as_mut().filter(|s| !s.contains('\n')) {
+                resolve(signature);
+            }
+        }
+    }
+}
+
+/// 注册表地址，末尾带 `/` 以便解析相对地址
+fn base_url(url: &str) -> Result<url::Url, PluginError> {
+    url::Url::parse(&format!("{}/", url.trim_end_matches('/')))
+        .map_err(|e| PluginError::ConfigError(format!("无效的静态注册表地址 {}: {}", url, e)))
+}
+
+/// 读取静态注册表的索引，支持 `http(s)://` 和 `file://` 地址
+pub async fn load_index(url: &str, settings: &GlobalSettings) -> Result<StaticIndex, PluginError> {
+    fetch_index(url, settings, None).await
+}
+
+/// 使用插件源的凭据读取静态注册表的索引
+pub async fn load_source_index(
+    source: &PluginSource,
+    settings: &GlobalSettings,
+) -> Result<StaticIndex, PluginError> {
+    let credentials = source.resolve_auth().await?;
+    fetch_index(&source.url, settings, credentials.as_ref()).await
+}
+
+async fn fetch_index(
+    url: &str,
+    settings: &GlobalSettings,
+    credentials: Option<&Credentials>,
+) -> Result<StaticIndex, PluginError> {
+    let base = base_url(url)?;
+    let index_url = base
+        .join(INDEX_FILE)
+        .map_err(|e| PluginError::ConfigError(format!("无效的静态注册表地址 {}: {}", url, e)))?;
+
+    let content = if index_url.scheme() == "file" {
+        let path = index_url
+            .to_file_path()
+            .map_err(|_| PluginError::ConfigError(format!("无效的静态注册表地址 {}", index_url)))?;
+        tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e)))?
+    } else {
+        permissions::check(|guard| guard.check_network(index_url.as_str()))?;
+        let request = build_client(
+            Duration::from_secs(settings.download_timeout),
+            settings.proxy.as_deref(),
+            settings.no_proxy.as_deref(),
+        )?
+        .get(index_url.clone());
+        match credentials {
+            Some(credentials) => credentials.apply(request),
+            None => request,
+        }
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", index_url, e)))?
+        .text()
+        .await
+        .map_err(|e| PluginError::NetworkError(format!("读取 {} 的响应失败: {}", index_url, e)))?
+    };
+
+    let mut index: StaticIndex = serde_json::from_str(&content).map_err(|e| {
+        PluginError::ValidationError(format!("无效的静态注册表索引 {}: {}", index_url, e))
+    })?;
+    index.resolve_urls(&base);
+    Ok(index)
+}
+
+/// 在静态注册表中搜索插件
+pub async fn search(
+    url: &str,
+    settings: &GlobalSettings,
+    query: &str,
+) -> Result<Vec<RegistryEntry>, PluginError> {
+    Ok(load_index(url, settings).await?.search(query))
+}
+
+/// 插件清单，`plm registry build` 和 `plm publish` 的输入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    #[serde(default)]
+    pub repository: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub releases: Vec<ManifestRelease>,
+}
+
+/// 清单中的一个制品
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestRelease {
+    pub version: String,
+    /// 为空或 `any` 时适用于所有平台
+    #[serde(default)]
+    pub platform: String,
+    /// 制品文件，相对于注册表目录
+    pub file: String,
+    #[serde(default)]
+    pub entry_points: Vec<String>,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub yanked: bool,
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+/// 从 `dir/manifests/*.json` 生成 `dir/index.json`，返回生成的索引
+pub async fn build(dir: &Path) -> Result<StaticIndex, PluginError> {
+    let manifests_dir = dir.join(MANIFESTS_DIR);
+    let mut entries = tokio::fs::read_dir(&manifests_dir).await.map_err(|e| {
+        PluginError::NotFound(format!(
+            "无法读取清单目录 {}: {}",
+            manifests_dir.display(),
+            e
+        ))
+    })?;
+    let mut paths = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(|e| {
+        PluginError::IoError(format!("读取 {} 失败: {}", manifests_dir.display(), e))
+    })? {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut index = StaticIndex::default();
+    for path in paths {
+        let manifest = read_manifest(&path).await?;
+        if index.plugins.contains_key(&manifest.name) {
+            return Err(PluginError::ValidationError(format!(
+                "插件 {} 在多个清单中重复定义",
+                manifest.name
+            )));
+        }
+        let plugin = index_manifest(dir, manifest).await?;
+        index.plugins.insert(plugin.metadata.name.clone(), plugin);
+    }
+
+    let content = serde_json::to_string_pretty(&index)
+        .map_err(|e| PluginError::IoError(format!("序列化静态注册表索引失败: {}", e)))?;
+    tokio::fs::write(dir.join(INDEX_FILE), content)
+        .await
+        .map_err(|e| PluginError::IoError(format!("写入 {} 失败: {}", INDEX_FILE, e)))?;
+    Ok(index)
+}
+
+/// 读取插件清单并检查插件名称
+pub async fn read_manifest(path: &Path) -> Result<PluginManifest, PluginError> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e)))?;
+    let manifest: PluginManifest = serde_json::from_str(&content).map_err(|e| {
+        PluginError::ValidationError(format!("无效的插件清单 {}: {}", path.display(), e))
+    })?;
+    crate::id::PluginId::new(&manifest.name)?;
+    Ok(manifest)
+}
+
+/// 计算清单中各制品的校验和与大小，制品路径相对于 `dir`
+pub(crate) async fn index_manifest(
+    dir: &Path,
+    manifest: PluginManifest,
+) -> Result<IndexedPlugin, PluginError> {
+    let mut versions = Vec::new();
+    for release in manifest.releases {
+        let relative = Path::new(&release.file);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(PluginError::ValidationError(format!(
+                "{} {} 的制品 {} 必须是注册表目录内的相对路径",
+                manifest.name, release.version, release.file
+            )));
+        }
+        let path = dir.join(relative);
+        let size = tokio::fs::metadata(&path)
+            .await
+            .map_err(|e| {
+                PluginError::NotFound(format!(
+                    "{} {} 的制品 {}: {}",
+                    manifest.name,
+                    release.version,
+                    path.display(),
+                    e
+                ))
+            })?
+            .len();
+
+        let mut info = VersionInfo::new(
+            release.version.trim_start_matches('v'),
+            if release.platform.is_empty() {
+                "any"
+            } else {
+                &release.platform
+            },
+            &release.file.replace('\\', "/"),
+        )
+        .with_size(size);
+        info.checksum = Some(sha256_file(&path).await?);
+        info.entry_points = release.entry_points;
+        info.prerelease = release.prerelease || release.version.contains('-');
+        info.yanked = release.yanked;
+        info.signature = release.signature;
+        versions.push(info);
+    }
+    sort_newest_first(&mut versions);
+
+    let latest = versions
+        .iter()
+        .find(|info| !info.prerelease && !info.yanked)
+        .or(versions.first())
+        .map(|info| info.version.clone());
+    let metadata = PluginMetadata {
+        name: manifest.name,
+        version: latest.unwrap_or_else(|| PluginMetadata::default().version),
+        description: manifest.description,
+        author: manifest.author,
+        homepage: manifest.homepage,
+        repository: manifest.repository,
+        tags: manifest.tags,
+        dependencies: manifest.dependencies,
+        ..PluginMetadata::default()
+    };
+    Ok(IndexedPlugin { metadata, versions })
+}
+
+/// 最新的在前，同一版本号的正式版排在预发布版之前
+fn sort_newest_first(versions: &mut [VersionInfo]) {
+    versions.sort_by(|a, b| {
+        let parse = |info: &VersionInfo| Version::parse(&info.version).ok();
+        parse(b)
+            .cmp(&parse(a))
+            .then(a.prerelease.cmp(&b.prerelease))
+            .then_with(|| a.platform.cmp(&b.platform))
+    });
+}
+
+/// 从静态注册表安装的插件（插件源类型为 `static`）
+pub struct StaticPlugin {
+    name: String,
+    source: PluginSource,
+    /// 各版本的安装目录 `<install_root>/<version>`
+    install_root: PathBuf,
+    settings: GlobalSettings,
+    status: PluginStatus,
+    config: RwLock<HashMap<String, String>>,
+    /// 首次查询后缓存的索引条目
+    indexed: RwLock<Option<IndexedPlugin>>,
+}
+
+impl StaticPlugin {
+    /// 创建插件，安装到 `plugin_dir/<name>` 下
+    pub fn from_config(
+        config: &PluginConfig,
+        source: &PluginSource,
+        settings: &GlobalSettings,
+    ) -> Self {
+        Self {
+            name: config.name.clone(),
+            source: source.clone(),
+            install_root: settings.plugin_path().join(&config.name),
+            settings: settings.clone(),
+            status: PluginStatus::Inactive,
+            config: RwLock::new(HashMap::new()),
+            indexed: RwLock::new(None),
+        }
+    }
+
+    /// 安装版本的目录
+    pub fn version_dir(&self, version: &str) -> PathBuf {
+        self.install_root.join(version.trim_start_matches('v'))
+    }
+
+    async fn indexed(&self) -> Result<IndexedPlugin, PluginError> {
+        if let Some(indexed) = self.indexed.read().ok().and_then(|i| i.clone()) {
+            return Ok(indexed);
+        }
+        let mut index = load_source_index(&self.source, &self.settings).await?;
+        let indexed = index.plugins.remove(&self.name).ok_or_else(|| {
+            PluginError::NotFound(format!(
+                "静态注册表 {} 中没有 {}",
+                self.source.url, self.name
+            ))
+        })?;
+        if let Ok(mut cached) = self.indexed.write() {
+            *cached = Some(indexed.clone());
+        }
+        Ok(indexed)
+    }
+
+    /// 其他平台的制品信息，`version` 为 `latest` 时选择最新的稳定版本
+    async fn target_artifact(
+        &self,
+        version: &str,
+        target: &TargetPlatform,
+    ) -> Result<VersionInfo, PluginError> {
+        let mut versions: Vec<VersionInfo> = self
+            .indexed()
+            .await?
+            .versions
+            .into_iter()
+            .filter(|info| info.supports_target(target))
+            .collect();
+        sort_newest_first(&mut versions);
+        versions
+            .into_iter()
+            .find(|info| match version {
+                "latest" => !info.prerelease && !info.yanked,
+                version => same_version(&info.version, version),
+            })
+            .ok_or_else(|| {
+                PluginError::NotFound(format!("{} {}（{}）", self.name, version, target))
+            })
+    }
+
+    /// 当前平台的制品信息
+    async fn artifact(&self, version: &str) -> Result<VersionInfo, PluginError> {
+        self.list_versions()
+            .await?
+            .into_iter()
+            .find(|info| same_version(&info.version, version))
+            .ok_or_else(|| {
+                PluginError::NotFound(format!(
+                    "{} {}（{}）",
+                    self.name,
+                    version,
+                    current_platform()
+                ))
+            })
+    }
+}
+
+#[async_trait]
+impl Plugin for StaticPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        match self.indexed.read().ok().and_then(|i| i.clone()) {
+            Some(indexed) => indexed.metadata,
+            None => PluginMetadata {
+                name: self.name.clone(),
+                description: format!("Installed from the static registry {}", self.source.url),
+                tags: vec!["static".to_string()],
+                ..PluginMetadata::default()
+            },
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Active;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Inactive;
+        Ok(())
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        let info = match (version, options.cross_target()) {
+            (version, Some(target)) => self.target_artifact(version, target).await?,
+            ("latest", None) => self.get_latest_version().await?,
+            (version, None) => self.artifact(version).await?,
+        };
+        let dest = match &options.install_dir {
+            Some(dir) => Path::new(dir).join(info.version.trim_start_matches('v')),
+            None => self.version_dir(&info.version),
+        };
+        if !options.force && dest.is_dir() {
+            return Ok(dest.to_string_lossy().into_owned());
+        }
+
+        if options.force {
+            let _ = tokio::fs::remove_dir_all(&dest).await;
+        }
+        let credentials = self.source.resolve_auth().await?;
+        Downloader::from_settings(&self.settings)?
+            .with_source_credentials(&self.source, credentials)
+            .fetch_and_extract(&info, Some(&self.source), &dest)
+            .await?;
+
+        if let Some(missing) = info
+            .entry_points
+            .iter()
+            .map(|entry| dest.join(entry))
+            .find(|p| !p.is_file())
+        {
+            return Err(PluginError::InstallationError(format!(
+                "{} {} 的制品中没有 {}",
+                self.name,
+                info.version,
+                missing.display()
+            )));
+        }
+        Ok(dest.to_string_lossy().into_owned())
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        let dest = self.version_dir(version);
+        if !dest.exists() {
+            return Err(PluginError::NotFound(format!("{} {}", self.name, version)));
+        }
+        tokio::fs::remove_dir_all(&dest)
+            .await
+            .map_err(|e| PluginError::IoError(format!("删除 {} 失败: {}", dest.display(), e)))
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        let mut versions: Vec<VersionInfo> = self
+            .indexed()
+            .await?
+            .versions
+            .into_iter()
+            .filter(|info| info.supports_platform(current_platform()))
+            .collect();
+        sort_newest_first(&mut versions);
+        // 同一版本有多个匹配的制品时使用第一个
+        versions.dedup_by(|a, b| same_version(&a.version, &b.version));
+        Ok(versions)
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        let mut entries = match tokio::fs::read_dir(&self.install_root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(PluginError::IoError(format!("读取安装目录失败: {}", e))),
+        };
+        let mut versions = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取安装目录失败: {}", e)))?
+        {
+            let version = entry.file_name().to_string_lossy().into_owned();
+            if self.is_installed(&version).await? {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+        Ok(versions)
+    }
+
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        Ok(self.version_dir(version).is_dir())
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        self.list_versions()
+            .await?
+            .into_iter()
+            .find(|info| !info.prerelease && !info.yanked)
+            .ok_or_else(|| PluginError::NotFound(format!("{} releases", self.name)))
+    }
+
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        let version = match version {
+            Some(version) => version.trim_start_matches('v').to_string(),
+            None => self.get_latest_version().await?.version,
+        };
+        self.install(&version, &InstallOptions::new()).await?;
+        Ok(version)
+    }
+
+    async fn switch_version(&self, version: &str) -> Result<(), PluginError> {
+        if self.is_installed(version).await? {
+            Ok(())
+        } else {
+            Err(PluginError::NotFound(format!("{} {}", self.name, version)))
+        }
+    }
+
+    async fn verify_installation(&self, version: &str) -> Result<bool, PluginError> {
+        self.is_installed(version).await
+    }
+
+    async fn cleanup(&self) -> Result<(), PluginError> {
+        Ok(())
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        Ok(self.config.read().map(|c| c.clone()).unwrap_or_default())
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+        Ok(())
+    }
+
+    async fn get_config_value(&self, key: &str) -> Result<Option<String>, PluginError> {
+        Ok(self.config.read().ok().and_then(|c| c.get(key).cloned()))
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        if let Ok(mut config) = self.config.write() {
+            config.insert(key.to_string(), value.to_string());
+        }
+        Ok(())
+    }
+
+    async fn execute_command(&self, command: &str, args: &[&str]) -> Result<String, PluginError> {
+        Err(PluginError::PluginError(format!(
+            "{} does not support command '{}' ({} arguments)",
+            self.name,
+            command,
+            args.len()
+        )))
+    }
+
+    fn get_help(&self) -> String {
+        format!(
+            "{}: installs artifacts from the static registry {}",
+            self.name, self.source.url
+        )
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::ListVersions,
+            Capability::Config,
+            Capability::Vendor,
+            Capability::CrossInstall,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_build_and_install_from_static_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = dir.path().join("registry");
+        std::fs::create_dir_all(registry.join(MANIFESTS_DIR)).unwrap();
+        std::fs::create_dir_all(registry.join("artifacts")).unwrap();
+
+        // 制品为只包含 bin/tool 的 tar.gz
+        let archive = registry.join("artifacts/tool-1.1.0.tar.gz");
+        {
+            let file = std::fs::File::create(&archive).unwrap();
+            let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            let mut builder = tar::Builder::new(encoder);
+            let content = b"#!/bin/sh\n";
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, "bin/tool", &content[..])
+                .unwrap();
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+        std::fs::write(registry.join("artifacts/tool-2.0.0-rc.1.tar.gz"), "rc").unwrap();
+        std::fs::write(
+            registry.join(MANIFESTS_DIR).join("tool.json"),
+            serde_json::json!({
+                "name": "tool",
+                "description": "Internal tool",
+                "tags": ["internal"],
+                "releases": [
+                    { "version": "1.1.0", "file": "artifacts/tool-1.1.0.tar.gz", "entry_points": ["bin/tool"] },
+                    { "version": "2.0.0-rc.1", "file": "artifacts/tool-2.0.0-rc.1.tar.gz" }
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let built = build(&registry).await.unwrap();
+        let tool = &built.plugins["tool"];
+        assert_eq!(tool.metadata.version, "1.1.0");
+        assert_eq!(tool.versions[0].version, "2.0.0-rc.1");
+        assert!(tool.versions[0].prerelease);
+        assert_eq!(tool.versions[1].download_url, "artifacts/tool-1.1.0.tar.gz");
+        assert!(tool.versions[1].checksum.is_some());
+
+        let url = url::Url::from_directory_path(&registry)
+            .unwrap()
+            .to_string();
+        let settings = GlobalSettings {
+            plugin_dir: dir.path().join("plugins").to_string_lossy().into_owned(),
+            cache_dir: dir.path().join("cache").to_string_lossy().into_owned(),
+            ..GlobalSettings::default()
+        };
+        let index = load_index(&url, &settings).await.unwrap();
+        assert!(index.plugins["tool"].versions[1]
+            .download_url
+            .starts_with("file://"));
+        assert_eq!(index.search("INTERNAL").len(), 1);
+        assert!(index.search("missing").is_empty());
+
+        let mut config = PluginConfig::new("tool");
+        config.set_source(PluginSource::static_registry(&url));
+        let plugin = StaticPlugin::from_config(&config, config.source.as_ref().unwrap(), &settings);
+        assert_eq!(plugin.get_latest_version().await.unwrap().version, "1.1.0");
+        let path = plugin
+            .install("latest", &InstallOptions::new().quiet())
+            .await
+            .unwrap();
+        assert!(Path::new(&path).join("bin/tool").is_file());
+        assert_eq!(plugin.list_installed().await.unwrap(), ["1.1.0"]);
+        assert_eq!(plugin.metadata().description, "Internal tool");
+
+        // 清单中的制品路径不能指向注册表目录之外
+        std::fs::write(
+            registry.join(MANIFESTS_DIR).join("escape.json"),
+            r#"{ "name": "escape", "releases": [{ "version": "1.0.0", "file": "../secret" }] }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            build(&registry).await,
+            Err(PluginError::ValidationError(_))
+        ));
+    }
+}
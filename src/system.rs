@@ -0,0 +1,734 @@
+//! PLM 系统包管理器后端
+//!
+//! 部分工具必须由系统包管理器管理（如依赖系统库的编译器、需要服务注册的数据库），
+//! 在插件设置中指定 `"backend": "system"` 后，PLM 不再下载制品，而是调用
+//! Homebrew、apt 或 winget 安装、升级和卸载，并记录包管理器实际安装的版本：
+//!
+//! ```json
+//! {
+//!   "name": "postgresql",
+//!   "settings": {
+//!     "backend": "system",
+//!     "package": "postgresql@16",
+//!     "linux": { "package": "postgresql-16", "sudo": true }
+//!   }
+//! }
+//! ```
+//!
+//! 可用的设置（支持按平台覆盖）：
+//!
+//! - `package`：包名，默认为插件名；winget 使用包标识，如 `Git.Git`
+//! - `manager`：`brew`、`apt` 或 `winget`，未设置时按平台检测 PATH 中可用的包管理器
+//! - `binary`：安装后查找的可执行文件名，默认为插件名
+//! - `sudo`：apt 的安装、升级、卸载是否通过 `sudo` 执行，默认 false
+//!
+//! 系统包管理器同一时间只保留一个版本，切换版本等同于重新安装。
+
+use crate::config::{current_platform, PluginConfig};
+use crate::permissions;
+use crate::traits::{
+    Capability, ExecOutcome, InstallOptions, Plugin, PluginError, PluginFactory, PluginMetadata,
+    PluginStatus, VersionInfo,
+};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// 插件设置中选择系统包管理器后端的值
+pub const SYSTEM_BACKEND: &str = "system";
+
+/// 插件配置是否使用系统包管理器后端
+pub fn is_system_backend(config: &PluginConfig) -> bool {
+    config
+        .get_setting_for("backend", current_platform())
+        .and_then(|v| v.as_str())
+        == Some(SYSTEM_BACKEND)
+}
+
+/// 支持的系统包管理器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Brew,
+    Apt,
+    Winget,
+}
+
+impl FromStr for PackageManager {
+    type Err = PluginError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "brew" | "homebrew" => Ok(PackageManager::Brew),
+            "apt" | "apt-get" => Ok(PackageManager::Apt),
+            "winget" => Ok(PackageManager::Winget),
+            other => Err(PluginError::ConfigError(format!(
+                "Unknown package manager '{}' (expected brew, apt or winget)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for PackageManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PackageManager::Brew => "brew",
+            PackageManager::Apt => "apt",
+            PackageManager::Winget => "winget",
+        })
+    }
+}
+
+impl PackageManager {
+    /// 按当前平台的优先顺序检测 PATH 中可用的包管理器
+    pub fn detect() -> Option<Self> {
+        let candidates: &[PackageManager] = match current_platform() {
+            "macos" => &[PackageManager::Brew],
+            "windows" => &[PackageManager::Winget],
+            _ => &[PackageManager::Apt, PackageManager::Brew],
+        };
+        candidates
+            .iter()
+            .copied()
+            .find(|manager| which::which(manager.program()).is_ok())
+    }
+
+    /// 安装、升级和卸载使用的命令
+    pub fn program(&self) -> &'static str {
+        match self {
+            PackageManager::Brew => "brew",
+            PackageManager::Apt => "apt-get",
+            PackageManager::Winget => "winget",
+        }
+    }
+
+    /// 安装命令，`version` 为 None 时安装仓库中的最新版本
+    ///
+    /// Homebrew 只能安装版本化的 formula，指定版本时安装 `<package>@<version>`
+    pub fn install_command(&self, package: &str, version: Option<&str>) -> Vec<String> {
+        let mut cmd = vec![self.program().to_string()];
+        match self {
+            PackageManager::Brew => {
+                cmd.push("install".to_string());
+                cmd.push(match version {
+                    Some(version) => format!("{}@{}", package, version),
+                    None => package.to_string(),
+                });
+            }
+            PackageManager::Apt => {
+                cmd.extend(["install", "-y"].map(String::from));
+                cmd.push(match version {
+                    Some(version) => format!("{}={}", package, version),
+                    None => package.to_string(),
+                });
+            }
+            PackageManager::Winget => {
+                cmd.extend(winget_args("install", package));
+                if let Some(version) = version {
+                    cmd.push("--version".to_string());
+                    cmd.push(version.to_string());
+                }
+            }
+        }
+        cmd
+    }
+
+    /// 升级到仓库中最新版本的命令
+    pub fn upgrade_command(&self, package: &str) -> Vec<String> {
+        let mut cmd = vec![self.program().to_string()];
+        match self {
+            PackageManager::Brew => cmd.extend(["upgrade", package].map(String::from)),
+            PackageManager::Apt => {
+                cmd.extend(["install", "-y", "--only-upgrade", package].map(String::from))
+            }
+            PackageManager::Winget => cmd.extend(winget_args("upgrade", package)),
+        }
+        cmd
+    }
+
+    /// 卸载命令
+    pub fn uninstall_command(&self, package: &str) -> Vec<String> {
+        let mut cmd = vec![self.program().to_string()];
+        match self {
+            PackageManager::Brew => cmd.extend(["uninstall", package].map(String::from)),
+            PackageManager::Apt => cmd.extend(["remove", "-y", package].map(String::from)),
+            PackageManager::Winget => {
+                cmd.extend(["uninstall", "--id", package, "--exact", "--silent"].map(String::from))
+            }
+        }
+        cmd
+    }
+
+    /// 查询已安装版本的命令，输出由 `parse_installed` 解析
+    pub fn installed_command(&self, package: &str) -> Vec<String> {
+        let cmd: &[&str] = match self {
+            PackageManager::Brew => &["brew", "list", "--versions", package],
+            PackageManager::Apt => &["dpkg-query", "-W", "-f=${Version}", package],
+            PackageManager::Winget => &["winget", "list", "--id", package, "--exact"],
+        };
+        cmd.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    /// 查询仓库中可用版本的命令，输出由 `parse_available` 解析
+    pub fn available_command(&self, package: &str) -> Vec<String> {
+        let cmd: &[&str] = match self {
+            PackageManager::Brew => &["brew", "info", "--json=v2", package],
+            PackageManager::Apt => &["apt-cache", "madison", package],
+            PackageManager::Winget => &["winget", "show", "--id", package, "--exact", "--versions"],
+        };
+        cmd.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    /// 从查询输出中解析已安装的版本
+    pub fn parse_installed(&self, package: &str, output: &str) -> Option<String> {
+        match self {
+            // `node 20.9.0 21.1.0`，多个版本时最后一个是最新安装的
+            PackageManager::Brew => output
+                .lines()
+                .find(|line| line.split_whitespace().next() == Some(package))
+                .and_then(|line| line.split_whitespace().last())
+                .filter(|version| *version != package)
+                .map(str::to_string),
+            PackageManager::Apt => Some(output.trim())
+                .filter(|version| !version.is_empty())
+                .map(str::to_string),
+            // 表格输出，版本位于包标识之后的一列
+            PackageManager::Winget => output.lines().find_map(|line| {
+                let mut columns = line.split_whitespace();
+                columns.position(|column| column.eq_ignore_ascii_case(package))?;
+                columns.next().map(str::to_string)
+            }),
+        }
+    }
+
+    /// 从查询输出中解析可用版本，最新的在前
+    pub fn parse_available(&self, output: &str) -> Vec<String> {
+        match self {
+            PackageManager::Brew => serde_json::from_str::<serde_json::Value>(output)
+                .ok()
+                .and_then(|info| {
+                    info["formulae"][0]["versions"]["stable"]
+                        .as_str()
+                        .map(str::to_string)
+                })
+                .into_iter()
+                .collect(),
+            // `nodejs | 18.19.1+dfsg-6ubuntu5 | http://archive.ubuntu.com/ubuntu noble/universe amd64 Packages`
+            PackageManager::Apt => {
+                let mut versions: Vec<String> = Vec::new();
+                for line in output.lines() {
+                    if let Some(version) = line.split('|').nth(1).map(str::trim) {
+                        if !version.is_empty() && !versions.iter().any(|v| v == version) {
+                            versions.push(version.to_string());
+                        }
+                    }
+                }
+                versions
+            }
+            // 版本列表位于 `-------` 分隔行之后
+            PackageManager::Winget => output
+                .lines()
+                .skip_while(|line| !line.trim_start().starts_with("---"))
+                .skip(1)
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+fn winget_args(command: &str, package: &str) -> Vec<String> {
+    [
+        command,
+        "--id",
+        package,
+        "--exact",
+        "--silent",
+        "--accept-package-agreements",
+        "--accept-source-agreements",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+/// 通过系统包管理器安装工具的插件
+pub struct SystemPackagePlugin {
+    name: String,
+    package: String,
+    binary: String,
+    sudo: bool,
+    /// 配置指定的包管理器，未指定时在初始化时检测
+    manager: Option<PackageManager>,
+    status: PluginStatus,
+    /// 最近一次查询到的已安装版本
+    installed: RwLock<Option<String>>,
+    config: RwLock<HashMap<String, String>>,
+}
+
+impl SystemPackagePlugin {
+    /// 从插件配置创建，读取当前平台生效的设置
+    pub fn from_config(config: &PluginConfig) -> Result<Self, PluginError> {
+        let settings = config.effective_settings_for(current_platform());
+        let string_setting = |key: &str| settings.get(key).and_then(|v| v.as_str());
+
+        let manager = string_setting("manager")
+            .map(PackageManager::from_str)
+            .transpose()?;
+        let values = settings
+            .iter()
+            .filter_map(|(key, value)| value.as_str().map(|v| (key.clone(), v.to_string())))
+            .collect();
+
+        Ok(Self {
+            package: string_setting("package")
+                .unwrap_or(&config.name)
+                .to_string(),
+            binary: string_setting("binary").unwrap_or(&config.name).to_string(),
+            sudo: settings
+                .get("sudo")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            name: config.name.clone(),
+            manager,
+            status: PluginStatus::Inactive,
+            installed: RwLock::new(None),
+            config: RwLock::new(values),
+        })
+    }
+
+    /// 使用指定的包管理器
+    pub fn with_manager(mut self, manager: PackageManager) -> Self {
+        self.manager = Some(manager);
+        self
+    }
+
+    /// 包管理器中的包名
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// 最近一次记录的已安装版本
+    pub fn recorded_version(&self) -> Option<String> {
+        self.installed.read().ok().and_then(|v| v.clone())
+    }
+
+    fn record_version(&self, version: Option<String>) {
+        if let Ok(mut installed) = self.installed.write() {
+            *installed = version;
+        }
+    }
+
+    fn manager(&self) -> Result<PackageManager, PluginError> {
+        self.manager.ok_or_else(|| {
+            PluginError::NotFound(format!(
+                "system package manager for {} (install brew, apt or winget, or set `manager`)",
+                self.name
+            ))
+        })
+    }
+
+    /// 执行修改系统的命令（安装、升级、卸载），apt 按设置通过 sudo 执行
+    async fn run_mutating(
+        &self,
+        mut cmd: Vec<String>,
+        options: Option<&InstallOptions>,
+    ) -> Result<String, PluginError> {
+        if self.sudo && self.manager()? == PackageManager::Apt {
+            cmd.insert(0, "sudo".to_string());
+        }
+        run_command(&cmd, options).await
+    }
+
+    /// 查询包管理器中的已安装版本并记录
+    async fn query_installed(&self) -> Result<Option<String>, PluginError> {
+        let manager = self.manager()?;
+        // 未安装时查询命令以非零状态退出，视为没有安装
+        let version = match run_command(&manager.installed_command(&self.package), None).await {
+            Ok(output) => manager.parse_installed(&self.package, &output),
+            Err(PluginError::InstallationError(_)) => None,
+            Err(e) => return Err(e),
+        };
+        self.record_version(version.clone());
+        Ok(version)
+    }
+
+    fn binary_path(&self) -> String {
+        which::which(&self.binary)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| self.binary.clone())
+    }
+}
+
+/// 执行包管理器（或委托的版本管理器）命令，返回标准输出
+pub(crate) async fn run_command(
+    cmd: &[String],
+    options: Option<&InstallOptions>,
+) -> Result<String, PluginError> {
+    let (program, args) = cmd
+        .split_first()
+        .ok_or_else(|| PluginError::ValidationError("empty command".to_string()))?;
+    permissions::check(|guard| guard.check_subprocess(program))?;
+    let mut command = permissions::command(program);
+    command.args(args);
+    if let Some(options) = options {
+        command.envs(&options.env_vars);
+    }
+    // 包管理器不应等待交互输入
+    command.env("DEBIAN_FRONTEND", "noninteractive");
+    command.env("HOMEBREW_NO_AUTO_UPDATE", "1");
+
+    log::debug!("执行: {}", cmd.join(" "));
+    let output = command
+        .output()
+        .await
+        .map_err(|e| PluginError::InstallationError(format!("无法执行 {}: {}", program, e)))?;
+    if !output.status.success() {
+        return Err(PluginError::InstallationError(format!(
+            "{} 失败 ({}): {}",
+            cmd.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 继承标准输入输出执行命令，返回退出码（被信号终止时为 `128 + 信号`）
+pub(crate) async fn stream_command(
+    cmd: &[String],
+    options: Option<&InstallOptions>,
+) -> Result<i32, PluginError> {
+    let (program, args) = cmd
+        .split_first()
+        .ok_or_else(|| PluginError::ValidationError("empty command".to_string()))?;
+    permissions::check(|guard| guard.check_subprocess(program))?;
+    let mut command = permissions::command(program);
+    command.args(args);
+    if let Some(options) = options {
+        command.envs(&options.env_vars);
+    }
+
+    log::debug!("执行: {}", cmd.join(" "));
+    let status = command
+        .status()
+        .await
+        .map_err(|e| PluginError::PluginError(format!("无法执行 {}: {}", program, e)))?;
+    Ok(exit_code(status))
+}
+
+#[cfg(unix)]
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1)
+}
+
+#[cfg(not(unix))]
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    status.code().unwrap_or(1)
+}
+
+#[async_trait]
+impl Plugin for SystemPackagePlugin {
+    fn metadata(&self) -> PluginMetadata {
+        PluginMetadata {
+            name: self.name.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            description: format!("{} managed by the system package manager", self.package),
+            tags: vec![SYSTEM_BACKEND.to_string()],
+            ..PluginMetadata::default()
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        if self.manager.is_none() {
+            self.manager = PackageManager::detect();
+        }
+        self.manager()?;
+        self.query_installed().await?;
+        self.status = PluginStatus::Active;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Inactive;
+        Ok(())
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        let manager = self.manager()?;
+        let requested = Some(version).filter(|v| *v != "latest");
+        let installed = self.query_installed().await?;
+        let satisfied = match (requested, &installed) {
+            (Some(requested), Some(installed)) => requested == installed,
+            (None, Some(_)) => true,
+            (_, None) => false,
+        };
+        if !satisfied || options.force {
+            self.run_mutating(
+                manager.install_command(&self.package, requested),
+                Some(options),
+            )
+            .await?;
+            if self.query_installed().await?.is_none() {
+                return Err(PluginError::InstallationError(format!(
+                    "{} reported success but {} is not installed",
+                    manager, self.package
+                )));
+            }
+        }
+        Ok(self.binary_path())
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        let manager = self.manager()?;
+        match self.query_installed().await? {
+            None => Err(PluginError::NotFound(format!(
+                "{} is not installed by {}",
+                self.package, manager
+            ))),
+            Some(installed) if version != "latest" && installed != version => {
+                Err(PluginError::ValidationError(format!(
+                    "{} {} is installed, not {}",
+                    self.package, installed, version
+                )))
+            }
+            Some(_) => {
+                self.run_mutating(manager.uninstall_command(&self.package), None)
+                    .await?;
+                self.record_version(None);
+                Ok(())
+            }
+        }
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        let manager = self.manager()?;
+        let output = run_command(&manager.available_command(&self.package), None).await?;
+        Ok(manager
+            .parse_available(&output)
+            .iter()
+            .map(|version| VersionInfo::new(version, current_platform(), ""))
+            .collect())
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        Ok(self.query_installed().await?.into_iter().collect())
+    }
+
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        Ok(match self.query_installed().await? {
+            Some(installed) => version == "latest" || installed == version,
+            None => false,
+        })
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        self.list_versions()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| PluginError::NotFound(format!("versions of {}", self.package)))
+    }
+
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        let manager = self.manager()?;
+        let cmd = match version {
+            Some(version) => manager.install_command(&self.package, Some(version)),
+            None => manager.upgrade_command(&self.package),
+        };
+        self.run_mutating(cmd, None).await?;
+        self.query_installed().await?.ok_or_else(|| {
+            PluginError::InstallationError(format!("{} is not installed", self.package))
+        })
+    }
+
+    async fn switch_version(&self, version: &str) -> Result<(), PluginError> {
+        if self.is_installed(version).await? {
+            Ok(())
+        } else {
+            Err(PluginError::ValidationError(format!(
+                "{} keeps a single version of {}; install {} instead of switching",
+                self.manager()?,
+                self.package,
+                version
+            )))
+        }
+    }
+
+    async fn verify_installation(&self, version: &str) -> Result<bool, PluginError> {
+        Ok(self.is_installed(version).await? && which::which(&self.binary).is_ok())
+    }
+
+    async fn cleanup(&self) -> Result<(), PluginError> {
+        // 包管理器自行管理缓存
+        Ok(())
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        Ok(self.config.read().map(|c| c.clone()).unwrap_or_default())
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+        Ok(())
+    }
+
+    async fn get_config_value(&self, key: &str) -> Result<Option<String>, PluginError> {
+        Ok(self.config.read().ok().and_then(|c| c.get(key).cloned()))
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        if let Ok(mut config) = self.config.write() {
+            config.insert(key.to_string(), value.to_string());
+        }
+        Ok(())
+    }
+
+    async fn execute_command(&self, command: &str, args: &[&str]) -> Result<String, PluginError> {
+        let mut cmd = vec![self.manager()?.program().to_string(), command.to_string()];
+        cmd.extend(args.iter().map(|arg| arg.to_string()));
+        run_command(&cmd, None).await
+    }
+
+    async fn exec_command(&self, command: &str, args: &[&str]) -> Result<ExecOutcome, PluginError> {
+        let mut cmd = vec![self.manager()?.program().to_string(), command.to_string()];
+        cmd.extend(args.iter().map(|arg| arg.to_string()));
+        stream_command(&cmd, None).await.map(ExecOutcome::Exited)
+    }
+
+    fn get_help(&self) -> String {
+        format!(
+            "{} is installed with the system package manager ({}), package '{}'",
+            self.name,
+            self.manager
+                .map_or("not detected".to_string(), |m| m.to_string()),
+            self.package
+        )
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::ListVersions,
+            Capability::Exec,
+            Capability::Config,
+        ])
+    }
+}
+
+/// 创建系统包管理器插件的工厂
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemPackageFactory;
+
+#[async_trait]
+impl PluginFactory for SystemPackageFactory {
+    async fn create_plugin(&self, config: &PluginConfig) -> Result<Box<dyn Plugin>, PluginError> {
+        self.validate_config(config)?;
+        Ok(Box::new(SystemPackagePlugin::from_config(config)?))
+    }
+
+    fn supported_types(&self) -> Vec<String> {
+        vec![SYSTEM_BACKEND.to_string()]
+    }
+
+    fn validate_config(&self, config: &PluginConfig) -> Result<(), PluginError> {
+        if !is_system_backend(config) {
+            return Err(PluginError::ConfigError(format!(
+                "Plugin '{}' does not use the system backend",
+                config.name
+            )));
+        }
+        SystemPackagePlugin::from_config(config).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_manager_commands() {
+        assert_eq!(
+            PackageManager::Apt.install_command("nodejs", Some("18.19.1")),
+            ["apt-get", "install", "-y", "nodejs=18.19.1"]
+        );
+        assert_eq!(
+            PackageManager::Brew.install_command("python", Some("3.12")),
+            ["brew", "install", "python@3.12"]
+        );
+        assert_eq!(
+            PackageManager::Winget.upgrade_command("Git.Git")[..4],
+            ["winget", "upgrade", "--id", "Git.Git"]
+        );
+        assert_eq!(
+            "homebrew".parse::<PackageManager>().unwrap(),
+            PackageManager::Brew
+        );
+        assert!("pacman".parse::<PackageManager>().is_err());
+    }
+
+    #[test]
+    fn test_parse_versions() {
+        assert_eq!(
+            PackageManager::Brew.parse_installed("node", "node 20.9.0 21.1.0\n"),
+            Some("21.1.0".to_string())
+        );
+        assert_eq!(PackageManager::Brew.parse_installed("node", ""), None);
+        assert_eq!(
+            PackageManager::Apt.parse_installed("nodejs", "18.19.1+dfsg-6ubuntu5"),
+            Some("18.19.1+dfsg-6ubuntu5".to_string())
+        );
+        let winget = "Name  Id       Version Source\n-----------------------------\nGit   Git.Git  2.43.0  winget\n";
+        assert_eq!(
+            PackageManager::Winget.parse_installed("Git.Git", winget),
+            Some("2.43.0".to_string())
+        );
+
+        let madison = " nodejs | 20.11.1-1nodesource1 | https://deb.nodesource.com/node_20.x nodistro/main amd64 Packages\n nodejs | 18.19.1+dfsg-6ubuntu5 | http://archive.ubuntu.com/ubuntu noble/universe amd64 Packages\n";
+        assert_eq!(
+            PackageManager::Apt.parse_available(madison),
+            ["20.11.1-1nodesource1", "18.19.1+dfsg-6ubuntu5"]
+        );
+        assert_eq!(
+            PackageManager::Brew
+                .parse_available(r#"{"formulae":[{"versions":{"stable":"21.6.1"}}]}"#),
+            ["21.6.1"]
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut config = PluginConfig::new("postgresql");
+        assert!(!is_system_backend(&config));
+        config.set_setting("backend", serde_json::json!("system"));
+        config.set_setting("manager", serde_json::json!("apt"));
+        config.set_setting("package", serde_json::json!("postgresql-16"));
+        assert!(is_system_backend(&config));
+
+        let plugin = SystemPackagePlugin::from_config(&config).unwrap();
+        assert_eq!(plugin.package(), "postgresql-16");
+        assert_eq!(plugin.manager, Some(PackageManager::Apt));
+        assert!(SystemPackageFactory.validate_config(&config).is_ok());
+
+        config.set_setting("manager", serde_json::json!("pacman"));
+        assert!(SystemPackageFactory.validate_config(&config).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! PLM 跨平台安装
+//!
+//! 交叉编译环境需要目标平台的工具链，例如在 x86_64 主机上为 arm64 准备 sysroot：
+//!
+//! ```text
+//! plm install zig --platform linux-arm64 --dest ./sysroot
+//! plm sync --platform linux-arm64 --dest ./sysroot
+//! ```
+//!
+//! 目标平台写作 `<os>-<arch>`，操作系统和架构都接受常见别名（`darwin`、`amd64`、`arm64` 等）。
+//! 插件通过 `Capability::CrossInstall` 声明支持跨平台安装，安装到 `<dest>/<plugin>/<version>`；
+//! 这些安装单独记录在运行状态中，不改变当前使用的版本，也不生成启动器和包装脚本。
+
+use crate::traits::{canonical_platform, PluginError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 插件通过 `supports_feature` 声明可以为其他平台安装制品，等同于 `Capability::CrossInstall`
+pub const CROSS_INSTALL_FEATURE: &str = "cross_install";
+
+/// 安装制品的目标平台
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TargetPlatform {
+    /// 操作系统，与 `config::PLATFORM_KEYS` 中的键一致
+    pub os: String,
+    /// 架构，与 `std::env::consts::ARCH` 的取值一致，如 `x86_64`、`aarch64`
+    pub arch: String,
+}
+
+impl TargetPlatform {
+    /// 当前运行的平台
+    pub fn host() -> Self {
+        Self {
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+
+    /// 是否为当前运行的平台
+    pub fn is_host(&self) -> bool {
+        *self == Self::host()
+    }
+}
+
+impl FromStr for TargetPlatform {
+    type Err = PluginError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            PluginError::ValidationError(format!(
+                "无效的目标平台 '{}'，应为 <os>-<arch>，如 linux-arm64",
+                value
+            ))
+        };
+        let (os, arch) = value.trim().split_once(['-', '_']).ok_or_else(invalid)?;
+        Ok(Self {
+            os: canonical_platform(os).ok_or_else(invalid)?.to_string(),
+            arch: canonical_arch(arch).ok_or_else(invalid)?.to_string(),
+        })
+    }
+}
+
+impl TryFrom<String> for TargetPlatform {
+    type Error = PluginError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TargetPlatform> for String {
+    fn from(target: TargetPlatform) -> Self {
+        target.to_string()
+    }
+}
+
+impl fmt::Display for TargetPlatform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.os, self.arch)
+    }
+}
+
+/// 把架构名或常见别名映射为规范名称
+pub fn canonical_arch(arch: &str) -> Option<&'static str> {
+    match arch.trim().to_ascii_lowercase().as_str() {
+        "x86_64" | "x64" | "amd64" => Some("x86_64"),
+        "aarch64" | "arm64" => Some("aarch64"),
+        "x86" | "386" | "i386" | "i686" => Some("x86"),
+        "arm" | "armv7" | "armhf" => Some("arm"),
+        "riscv64" => Some("riscv64"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target_platform() {
+        let target: TargetPlatform = "linux-arm64".parse().unwrap();
+        assert_eq!(target.os, "linux");
+        assert_eq!(target.arch, "aarch64");
+        assert_eq!(target.to_string(), "linux-aarch64");
+        assert_eq!(
+            "darwin_amd64"
+                .parse::<TargetPlatform>()
+                .unwrap()
+                .to_string(),
+            "macos-x86_64"
+        );
+
+        assert!("linux".parse::<TargetPlatform>().is_err());
+        assert!("beos-x64".parse::<TargetPlatform>().is_err());
+        assert!("linux-sparc".parse::<TargetPlatform>().is_err());
+
+        let json = serde_json::to_string(&target).unwrap();
+        assert_eq!(json, "\"linux-aarch64\"");
+        assert_eq!(
+            serde_json::from_str::<TargetPlatform>(&json).unwrap(),
+            target
+        );
+    }
+
+    #[test]
+    fn test_artifact_supports_target() {
+        use crate::traits::VersionInfo;
+
+        let target: TargetPlatform = "linux-arm64".parse().unwrap();
+        let artifact = |platform: &str| VersionInfo::new("1.0.0", platform, "");
+        assert!(artifact("linux-arm64").supports_target(&target));
+        assert!(artifact("linux_aarch64").supports_target(&target));
+        assert!(!artifact("linux-x64").supports_target(&target));
+        assert!(!artifact("darwin-arm64").supports_target(&target));
+        // 没有架构后缀的制品适用于所有架构
+        assert!(artifact("linux").supports_target(&target));
+        assert!(artifact("any").supports_target(&target));
+    }
+}
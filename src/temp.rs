@@ -0,0 +1,139 @@
+//! PLM 临时文件管理
+//!
+//! 下载和解压过程中的临时文件（部分下载、解压暂存目录、写配置时的临时文件）
+//! 由 `TempFileGuard` 管理：guard 被丢弃时（出错返回、任务被取消、panic 展开）
+//! 自动删除临时文件，只有显式 `persist` 或 `keep` 后才会保留。
+//!
+//! 临时路径总是与目标位于同一目录，保证最终的重命名在同一文件系统内原子完成。
+
+use crate::traits::PluginError;
+use std::path::{Path, PathBuf};
+
+/// 临时文件或目录的清理守卫
+#[derive(Debug)]
+pub struct TempFileGuard {
+    path: PathBuf,
+    armed: bool,
+}
+
+impl TempFileGuard {
+    /// 管理指定的临时路径，路径应与最终目标位于同一目录
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            armed: true,
+        }
+    }
+
+    /// 在目标旁边创建临时路径 `<dest>.<suffix>`
+    pub fn sibling(dest: &Path, suffix: &str) -> Self {
+        let mut name = dest.as_os_str().to_os_string();
+        name.push(".");
+        name.push(suffix);
+        Self::new(name)
+    }
+
+    /// 临时路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 删除临时路径上遗留的文件或目录（例如上次进程崩溃留下的）
+    pub async fn clear(&self) -> Result<(), PluginError> {
+        remove_path(&self.path)
+            .await
+            .map_err(|e| PluginError::IoError(format!("删除 {} 失败: {}", self.path.display(), e)))
+    }
+
+    /// 把临时文件重命名为 `dest`，成功后不再清理
+    ///
+    /// `dest` 是已存在的目录时重命名会失败，调用方需要先删除旧目录
+    pub async fn persist(mut self, dest: &Path) -> Result<(), PluginError> {
+        tokio::fs::rename(&self.path, dest)
+            .await
+            .map_err(|e| PluginError::IoError(format!("移动到 {} 失败: {}", dest.display(), e)))?;
+        self.armed = false;
+        Ok(())
+    }
+
+    /// 保留临时文件（如可以续传的部分下载），返回其路径
+    pub fn keep(mut self) -> PathBuf {
+        self.armed = false;
+        std::mem::take(&mut self.path)
+    }
+}
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let result = match std::fs::symlink_metadata(&self.path) {
+            Ok(meta) if meta.is_dir() => std::fs::remove_dir_all(&self.path),
+            Ok(_) => std::fs::remove_file(&self.path),
+            Err(_) => Ok(()),
+        };
+        if let Err(e) = result {
+            log::warn!("清理临时文件 {} 失败: {}", self.path.display(), e);
+        }
+    }
+}
+
+async fn remove_path(path: &Path) -> std::io::Result<()> {
+    let result = match tokio::fs::symlink_metadata(path).await {
+        Ok(meta) if meta.is_dir() => tokio::fs::remove_dir_all(path).await,
+        Ok(_) => tokio::fs::remove_file(path).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_guard_cleanup_and_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("tool");
+
+        let guard = TempFileGuard::sibling(&dest, "partial");
+        assert_eq!(guard.path(), dir.path().join("tool.partial"));
+        std::fs::create_dir_all(guard.path().join("bin")).unwrap();
+        drop(guard);
+        assert!(!dir.path().join("tool.partial").exists());
+
+        let guard = TempFileGuard::sibling(&dest, "tmp");
+        std::fs::write(guard.path(), "content").unwrap();
+        guard.persist(&dest).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "content");
+
+        let guard = TempFileGuard::sibling(&dest, "part");
+        std::fs::write(guard.path(), "partial").unwrap();
+        let kept = guard.keep();
+        assert!(kept.exists());
+    }
+
+    #[tokio::test]
+    async fn test_guard_cleanup_on_cancel() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("artifact.part");
+
+        let task_path = path.clone();
+        let task = tokio::spawn(async move {
+            let guard = TempFileGuard::new(task_path);
+            tokio::fs::write(guard.path(), "data").await.unwrap();
+            std::future::pending::<()>().await;
+            drop(guard);
+        });
+        while !path.exists() {
+            tokio::task::yield_now().await;
+        }
+        task.abort();
+        let _ = task.await;
+        assert!(!path.exists());
+    }
+}
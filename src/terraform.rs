@@ -0,0 +1,582 @@
+//! PLM 内置 Terraform / OpenTofu 插件
+//!
+//! 从官方发布地址下载 Terraform（releases.hashicorp.com）或 OpenTofu（GitHub Releases），
+//! 解压到 `plugin_dir/<name>/<version>`，并用发布的 `SHA256SUMS` 校验制品：
+//!
+//! ```json
+//! { "name": "terraform", "source": { "type": "builtin", "url": "terraform" } }
+//! ```
+//!
+//! 配置中没有固定版本时，插件读取项目根目录下 `*.tf` 文件中 `terraform` 块的
+//! `required_version`，`plm sync` 安装满足所有约束的最新稳定版本。Terraform 的
+//! `~>` 运算符按其语义转换（`~> 1.5` 即 `>= 1.5, < 2`）；`!=` 条件无法表示为版本区间，会被忽略。
+
+use crate::config::{current_platform, GlobalSettings, PluginConfig};
+use crate::download::{build_client, Downloader};
+use crate::permissions;
+use crate::project_files::read_files_with_extension;
+use crate::traits::{
+    Capability, InstallOptions, Plugin, PluginError, PluginMetadata, PluginStatus, VersionInfo,
+};
+use crate::version::Version;
+use async_trait::async_trait;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// 内置插件源中的名称
+pub const BUILTIN_TERRAFORM: &str = "terraform";
+pub const BUILTIN_OPENTOFU: &str = "opentofu";
+
+/// Terraform 发行版
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Distribution {
+    Terraform,
+    OpenTofu,
+}
+
+impl FromStr for Distribution {
+    type Err = PluginError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            BUILTIN_TERRAFORM => Ok(Distribution::Terraform),
+            BUILTIN_OPENTOFU | "tofu" => Ok(Distribution::OpenTofu),
+            other => Err(PluginError::ConfigError(format!(
+                "Unknown Terraform distribution '{}' (expected terraform or opentofu)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Distribution::Terraform => BUILTIN_TERRAFORM,
+            Distribution::OpenTofu => BUILTIN_OPENTOFU,
+        })
+    }
+}
+
+impl Distribution {
+    /// 可执行文件名
+    pub fn binary(&self) -> &'static str {
+        match self {
+            Distribution::Terraform => "terraform",
+            Distribution::OpenTofu => "tofu",
+        }
+    }
+
+    /// 版本列表地址
+    pub fn index_url(&self) -> &'static str {
+        match self {
+            Distribution::Terraform => "https://releases.hashicorp.com/terraform/index.json",
+            Distribution::OpenTofu => "https://get.opentofu.org/tofu/api.json",
+        }
+    }
+
+    fn release_base(&self, version: &str) -> String {
+        match self {
+            Distribution::Terraform => {
+                format!("https://releases.hashicorp.com/terraform/{}", version)
+            }
+            Distribution::OpenTofu => format!(
+                "https://github.com/opentofu/opentofu/releases/download/v{}",
+                version
+            ),
+        }
+    }
+
+    /// 制品文件名，如 `terraform_1.6.2_linux_amd64.zip`
+    pub fn artifact_name(&self, version: &str, os: &str, arch: &str) -> String {
+        format!("{}_{}_{}_{}.zip", self.binary(), version, os, arch)
+    }
+
+    /// 当前平台的制品下载地址
+    pub fn download_url(&self, version: &str) -> String {
+        let (os, arch) = release_platform();
+        format!(
+            "{}/{}",
+            self.release_base(version),
+            self.artifact_name(version, os, arch)
+        )
+    }
+
+    /// 校验和文件地址
+    pub fn checksums_url(&self, version: &str) -> String {
+        format!(
+            "{}/{}_{}_SHA256SUMS",
+            self.release_base(version),
+            self.binary(),
+            version
+        )
+    }
+
+    /// 从版本列表响应中提取版本号
+    ///
+    /// Terraform 的 `versions` 是以版本号为键的对象，OpenTofu 的是带 `id` 字段的数组
+    pub fn parse_index(&self, index: &serde_json::Value) -> Vec<String> {
+        match &index["versions"] {
+            serde_json::Value::Object(versions) => versions.keys().cloned().collect(),
+            serde_json::Value::Array(versions) => versions
+                .iter()
+                .filter_map(|v| v["id"].as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// 发布制品使用的操作系统和架构名
+fn release_platform() -> (&'static str, &'static str) {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        other => other,
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" => "386",
+        other => other,
+    };
+    (os, arch)
+}
+
+/// 提取 Terraform 配置中所有 `required_version` 约束
+pub fn parse_required_versions(content: &str) -> Vec<String> {
+    let Ok(re) = Regex::new(r#"required_version\s*=\s*"([^"]*)""#) else {
+        return Vec::new();
+    };
+    re.captures_iter(content)
+        .map(|caps| caps[1].trim().to_string())
+        .filter(|constraint| !constraint.is_empty())
+        .collect()
+}
+
+/// 把 Terraform 版本约束转换为 PLM 的版本约束语法
+pub fn to_version_req(constraint: &str) -> Result<String, PluginError> {
+    let mut parts = Vec::new();
+    for condition in constraint.split(',').map(str::trim) {
+        if condition.is_empty() {
+            continue;
+        }
+        if let Some(rest) = condition.strip_prefix("!=") {
+            log::warn!("忽略无法表示为版本区间的约束: != {}", rest.trim());
+            continue;
+        }
+        let Some(rest) = condition.strip_prefix("~>") else {
+            parts.push(condition.to_string());
+            continue;
+        };
+
+        // `~>` 只允许最右侧写出的版本字段增加
+        let rest = rest.trim();
+        let version = Version::parse(rest)?;
+        match rest.split('.').count() {
+            1 => parts.push(format!(">={}", version)),
+            2 => parts.push(format!(">={}, <{}", version, version.major + 1)),
+            _ => parts.push(format!(
+                ">={}, <{}.{}",
+                version,
+                version.major,
+                version.minor + 1
+            )),
+        }
+    }
+    Ok(if parts.is_empty() {
+        "*".to_string()
+    } else {
+        parts.join(", ")
+    })
+}
+
+/// 读取项目根目录中 `*.tf` 文件的 `required_version`，合并为一个约束
+pub async fn required_version(project_root: &Path) -> Result<Option<String>, PluginError> {
+    let mut parts = Vec::new();
+    for (_, content) in read_files_with_extension(project_root, "tf").await? {
+        for constraint in parse_required_versions(&content) {
+            parts.push(to_version_req(&constraint)?);
+        }
+    }
+    parts.retain(|part| part != "*");
+    Ok((!parts.is_empty()).then(|| parts.join(", ")))
+}
+
+/// Terraform / OpenTofu 插件
+pub struct TerraformPlugin {
+    name: String,
+    distribution: Distribution,
+    /// 各版本的安装目录 `<install_root>/<version>`
+    install_root: PathBuf,
+    settings: GlobalSettings,
+    status: PluginStatus,
+    config: RwLock<HashMap<String, String>>,
+}
+
+impl TerraformPlugin {
+    /// 创建插件，安装到 `plugin_dir/<name>` 下
+    pub fn new(name: &str, distribution: Distribution, settings: &GlobalSettings) -> Self {
+        Self {
+            name: name.to_string(),
+            distribution,
+            install_root: settings.plugin_path().join(name),
+            settings: settings.clone(),
+            status: PluginStatus::Inactive,
+            config: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 从插件配置和内置插件源名称创建
+    pub fn from_config(
+        config: &PluginConfig,
+        builtin: &str,
+        settings: &GlobalSettings,
+    ) -> Result<Self, PluginError> {
+        Ok(Self::new(&config.name, builtin.parse()?, settings))
+    }
+
+    /// 安装版本的目录
+    pub fn version_dir(&self, version: &str) -> PathBuf {
+        self.install_root.join(version)
+    }
+
+    fn binary_path(&self, version: &str) -> PathBuf {
+        self.version_dir(version).join(format!(
+            "{}{}",
+            self.distribution.binary(),
+            std::env::consts::EXE_SUFFIX
+        ))
+    }
+
+    fn client(&self) -> Result<reqwest::Client, PluginError> {
+        build_client(
+            Duration::from_secs(self.settings.download_timeout),
+            self.settings.proxy.as_deref(),
+            self.settings.no_proxy.as_deref(),
+        )
+    }
+
+    async fn get_text(&self, url: &str) -> Result<String, PluginError> {
+        permissions::check(|guard| guard.check_network(url))?;
+        self.client()?
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| PluginError::NetworkError(format!("请求 {} 失败: {}", url, e)))?
+            .text()
+            .await
+            .map_err(|e| PluginError::NetworkError(format!("读取 {} 的响应失败: {}", url, e)))
+    }
+
+    /// 从发布的 SHA256SUMS 中查找制品的校验和
+    async fn checksum(&self, version: &str, artifact: &str) -> Result<Option<String>, PluginError> {
+        let sums = self
+            .get_text(&self.distribution.checksums_url(version))
+            .await?;
+        Ok(sums.lines().find_map(|line| {
+            let (hash, file) = line.split_once(char::is_whitespace)?;
+            (file.trim() == artifact).then(|| hash.to_string())
+        }))
+    }
+}
+
+#[async_trait]
+impl Plugin for TerraformPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        PluginMetadata {
+            name: self.name.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            description: match self.distribution {
+                Distribution::Terraform => "HashiCorp Terraform CLI".to_string(),
+                Distribution::OpenTofu => "OpenTofu CLI".to_string(),
+            },
+            homepage: Some(
+                match self.distribution {
+                    Distribution::Terraform => "https://www.terraform.io",
+                    Distribution::OpenTofu => "https://opentofu.org",
+                }
+                .to_string(),
+            ),
+            tags: vec!["builtin".to_string(), self.distribution.to_string()],
+            ..PluginMetadata::default()
+        }
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.status.clone()
+    }
+
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Active;
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        self.status = PluginStatus::Inactive;
+        Ok(())
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        let version = match version {
+            "latest" => self.get_latest_version().await?.version,
+            version => version.trim_start_matches('v').to_string(),
+        };
+        let dest = self.version_dir(&version);
+        if !options.force && self.binary_path(&version).is_file() {
+            return Ok(dest.to_string_lossy().into_owned());
+        }
+
+        let mut info = VersionInfo::new(
+            &version,
+            current_platform(),
+            &self.distribution.download_url(&version),
+        );
+        let (os, arch) = release_platform();
+        let artifact = self.distribution.artifact_name(&version, os, arch);
+        // 从 vendor 目录安装时不访问网络，校验和取自 vendor 索引
+        if self.settings.vendor_dir.is_none() {
+            match self.checksum(&version, &artifact).await {
+                Ok(checksum) => info.checksum = checksum,
+                // 未启用 verify_checksums 时缺少校验和不影响安装
+                Err(e) => log::debug!("获取 {} 的校验和失败: {}", artifact, e),
+            }
+        }
+
+        if options.force {
+            let _ = tokio::fs::remove_dir_all(&dest).await;
+        }
+        Downloader::from_settings(&self.settings)?
+            .fetch_and_extract(&info, None, &dest)
+            .await?;
+        Ok(dest.to_string_lossy().into_owned())
+    }
+
+    async fn uninstall(&self, version: &str) -> Result<(), PluginError> {
+        let dest = self.version_dir(version);
+        if !dest.exists() {
+            return Err(PluginError::NotFound(format!("{} {}", self.name, version)));
+        }
+        tokio::fs::remove_dir_all(&dest)
+            .await
+            .map_err(|e| PluginError::IoError(format!("删除 {} 失败: {}", dest.display(), e)))
+    }
+
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        let url = self.distribution.index_url();
+        let index: serde_json::Value = serde_json::from_str(&self.get_text(url).await?)
+            .map_err(|e| PluginError::NetworkError(format!("解析 {} 的响应失败: {}", url, e)))?;
+
+        let mut versions: Vec<(Version, VersionInfo)> = self
+            .distribution
+            .parse_index(&index)
+            .into_iter()
+            .filter_map(|version| {
+                let parsed = Version::parse(&version).ok()?;
+                let mut info = VersionInfo::new(
+                    &version,
+                    current_platform(),
+                    &self.distribution.download_url(&version),
+                );
+                info.prerelease = version.contains('-');
+                Some((parsed, info))
+            })
+            .collect();
+        // 最新的在前，同一版本号的正式版排在预发布版之前
+        versions.sort_by(|(a, a_info), (b, b_info)| {
+            b.cmp(a).then(a_info.prerelease.cmp(&b_info.prerelease))
+        });
+        Ok(versions.into_iter().map(|(_, info)| info).collect())
+    }
+
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        let mut entries = match tokio::fs::read_dir(&self.install_root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(PluginError::IoError(format!("读取安装目录失败: {}", e))),
+        };
+        let mut versions = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取安装目录失败: {}", e)))?
+        {
+            let version = entry.file_name().to_string_lossy().into_owned();
+            if self.binary_path(&version).is_file() {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+        Ok(versions)
+    }
+
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        Ok(self.binary_path(version.trim_start_matches('v')).is_file())
+    }
+
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        self.list_versions()
+            .await?
+            .into_iter()
+            .find(|info| !info.prerelease)
+            .ok_or_else(|| PluginError::NotFound(format!("{} releases", self.distribution)))
+    }
+
+    async fn update(&self, version: Option<&str>) -> Result<String, PluginError> {
+        let version = match version {
+            Some(version) => version.to_string(),
+            None => self.get_latest_version().await?.version,
+        };
+        self.install(&version, &InstallOptions::new()).await?;
+        Ok(version)
+    }
+
+    async fn switch_version(&self, version: &str) -> Result<(), PluginError> {
+        if self.is_installed(version).await? {
+            Ok(())
+        } else {
+            Err(PluginError::NotFound(format!("{} {}", self.name, version)))
+        }
+    }
+
+    async fn verify_installation(&self, version: &str) -> Result<bool, PluginError> {
+        self.is_installed(version).await
+    }
+
+    async fn project_constraint(&self, project_root: &Path) -> Result<Option<String>, PluginError> {
+        required_version(project_root).await
+    }
+
+    async fn cleanup(&self) -> Result<(), PluginError> {
+        Ok(())
+    }
+
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        Ok(self.config.read().map(|c| c.clone()).unwrap_or_default())
+    }
+
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+        Ok(())
+    }
+
+    async fn get_config_value(&self, key: &str) -> Result<Option<String>, PluginError> {
+        Ok(self.config.read().ok().and_then(|c| c.get(key).cloned()))
+    }
+
+    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError> {
+        if let Ok(mut config) = self.config.write() {
+            config.insert(key.to_string(), value.to_string());
+        }
+        Ok(())
+    }
+
+    async fn execute_command(&self, command: &str, args: &[&str]) -> Result<String, PluginError> {
+        Err(PluginError::PluginError(format!(
+            "{} does not support command '{}' ({} arguments)",
+            self.name,
+            command,
+            args.len()
+        )))
+    }
+
+    fn get_help(&self) -> String {
+        format!(
+            "Installs {} releases; without a pinned version, `plm sync` honors required_version \
+             in the project's *.tf files",
+            self.distribution
+        )
+    }
+
+    fn capabilities(&self) -> HashSet<Capability> {
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::ListVersions,
+            Capability::Config,
+            Capability::ProjectConstraint,
+            Capability::Vendor,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::VersionReq;
+
+    #[test]
+    fn test_parse_required_versions() {
+        let content = r#"
+terraform {
+  required_version = ">= 1.3.0, < 2.0.0"
+  required_providers {
+    aws = { source = "hashicorp/aws", version = "~> 5.0" }
+  }
+}
+"#;
+        assert_eq!(parse_required_versions(content), [">= 1.3.0, < 2.0.0"]);
+        assert!(parse_required_versions("provider \"aws\" {}").is_empty());
+    }
+
+    #[test]
+    fn test_pessimistic_constraint() {
+        assert_eq!(to_version_req("~> 1.5").unwrap(), ">=1.5.0, <2");
+        assert_eq!(to_version_req("~> 1.5.2").unwrap(), ">=1.5.2, <1.6");
+        assert_eq!(to_version_req(">= 1.3, != 1.4.0").unwrap(), ">= 1.3");
+
+        let req = VersionReq::parse(&to_version_req("~> 1.5.2").unwrap()).unwrap();
+        assert!(req.matches(&Version::new(1, 5, 7)));
+        assert!(!req.matches(&Version::new(1, 6, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_required_version_from_project() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(required_version(dir.path()).await.unwrap(), None);
+
+        std::fs::write(
+            dir.path().join("versions.tf"),
+            "terraform {\n  required_version = \"~> 1.6\"\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("main.tf"),
+            "terraform {\n  required_version = \">= 1.6.3\"\n}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            required_version(dir.path()).await.unwrap().as_deref(),
+            Some(">= 1.6.3, >=1.6.0, <2")
+        );
+    }
+
+    #[test]
+    fn test_parse_index() {
+        let terraform = serde_json::json!({ "versions": { "1.6.2": {}, "1.7.0-beta1": {} } });
+        let mut versions = Distribution::Terraform.parse_index(&terraform);
+        versions.sort();
+        assert_eq!(versions, ["1.6.2", "1.7.0-beta1"]);
+
+        let tofu = serde_json::json!({ "versions": [{ "id": "1.6.1" }, { "id": "1.6.0" }] });
+        assert_eq!(
+            Distribution::OpenTofu.parse_index(&tofu),
+            ["1.6.1", "1.6.0"]
+        );
+        assert!(Distribution::OpenTofu
+            .download_url("1.6.1")
+            .contains("/v1.6.1/tofu_1.6.1_"));
+    }
+}
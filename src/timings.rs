@@ -0,0 +1,179 @@
+//! PLM 阶段耗时统计
+//!
+//! 加载配置、加载插件、解析版本、下载、解压和执行钩子等阶段结束时，把耗时记录到
+//! 进程级的统计中，`plm --timings` 在命令结束后输出各阶段的汇总。
+//! 并发执行的阶段（如多个插件同时下载）耗时会累加，总和可能大于实际经过的时间。
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 统计的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// 读取、迁移和合并配置
+    ConfigLoad,
+    /// 初始化插件
+    PluginLoad,
+    /// 检查冲突、合并版本列表
+    Resolve,
+    /// 下载制品（包括流式解压的 tar.gz）
+    Download,
+    /// 从磁盘解压制品
+    Extract,
+    /// 执行生命周期钩子
+    Hooks,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Phase::ConfigLoad => "config load",
+            Phase::PluginLoad => "plugin load",
+            Phase::Resolve => "resolve",
+            Phase::Download => "download",
+            Phase::Extract => "extract",
+            Phase::Hooks => "hooks",
+        })
+    }
+}
+
+/// 单个阶段的耗时
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PhaseTiming {
+    pub phase: Phase,
+    /// 累计耗时
+    pub total: Duration,
+    /// 执行次数
+    pub count: u32,
+}
+
+/// 各阶段耗时汇总，按阶段顺序排列，只包含执行过的阶段
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TimingsReport {
+    pub phases: Vec<PhaseTiming>,
+}
+
+impl TimingsReport {
+    /// 获取阶段耗时
+    pub fn get(&self, phase: Phase) -> Option<&PhaseTiming> {
+        self.phases.iter().find(|timing| timing.phase == phase)
+    }
+
+    /// 所有阶段的累计耗时
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|timing| timing.total).sum()
+    }
+
+    /// 是否没有记录任何阶段
+    pub fn is_empty(&self) -> bool {
+        self.phases.is_empty()
+    }
+}
+
+impl fmt::Display for TimingsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for timing in &self.phases {
+            writeln!(
+                f,
+                "  {:<12} {:>10.1?}  ({}x)",
+                timing.phase.to_string(),
+                timing.total,
+                timing.count
+            )?;
+        }
+        write!(f, "  {:<12} {:>10.1?}", "total", self.total())
+    }
+}
+
+static TIMINGS: Mutex<BTreeMap<Phase, (Duration, u32)>> = Mutex::new(BTreeMap::new());
+
+/// 记录一次阶段耗时
+pub fn record(phase: Phase, elapsed: Duration) {
+    if let Ok(mut timings) = TIMINGS.lock() {
+        let entry = timings.entry(phase).or_default();
+        entry.0 += elapsed;
+        entry.1 += 1;
+    }
+}
+
+/// 执行 future 并记录其耗时
+pub async fn measure<F: Future>(phase: Phase, future: F) -> F::Output {
+    let started = Instant::now();
+    let output = future.await;
+    record(phase, started.elapsed());
+    output
+}
+
+/// 当前的耗时汇总
+pub fn report() -> TimingsReport {
+    let phases = TIMINGS
+        .lock()
+        .map(|timings| {
+            timings
+                .iter()
+                .map(|(phase, (total, count))| PhaseTiming {
+                    phase: *phase,
+                    total: *total,
+                    count: *count,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    TimingsReport { phases }
+}
+
+/// 清空已记录的耗时
+pub fn reset() {
+    if let Ok(mut timings) = TIMINGS.lock() {
+        timings.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_measure_records_phase() {
+        let value = measure(Phase::Hooks, async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            42
+        })
+        .await;
+        assert_eq!(value, 42);
+
+        // 其他测试可能同时记录耗时，只检查下限
+        let report = report();
+        let hooks = report.get(Phase::Hooks).unwrap();
+        assert!(hooks.total >= Duration::from_millis(5));
+        assert!(hooks.count >= 1);
+    }
+
+    #[test]
+    fn test_report_display() {
+        let report = TimingsReport {
+            phases: vec![
+                PhaseTiming {
+                    phase: Phase::ConfigLoad,
+                    total: Duration::from_millis(12),
+                    count: 1,
+                },
+                PhaseTiming {
+                    phase: Phase::Download,
+                    total: Duration::from_millis(300),
+                    count: 2,
+                },
+            ],
+        };
+        assert_eq!(report.total(), Duration::from_millis(312));
+        let text = report.to_string();
+        assert!(text.contains("config load"));
+        assert!(text.contains("(2x)"));
+        assert!(text.lines().last().unwrap().contains("total"));
+    }
+}
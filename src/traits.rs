@@ -1,8 +1,15 @@
 //! Core traits for the plugin system
 
+use crate::target::{canonical_arch, TargetPlatform};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use thiserror::Error;
 
 /// Plugin error types
@@ -31,10 +38,32 @@ pub enum PluginError {
 
     #[error("Plugin error: {0}")]
     PluginError(String),
+
+    #[error("Signature verification failed: {0}")]
+    SignatureError(String),
+
+    #[error("Unsupported operation: {0}")]
+    Unsupported(String),
+
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        /// Checksum published for the artifact
+        expected: String,
+        /// Checksum of the downloaded file
+        actual: String,
+    },
+
+    #[error("Version conflict on '{dependency}': {}", .constraints.join(" vs "))]
+    ConflictError {
+        /// Shared dependency name
+        dependency: String,
+        /// Conflicting constraints, each naming the plugin that declared it
+        constraints: Vec<String>,
+    },
 }
 
 /// Plugin metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PluginMetadata {
     /// Plugin name
     pub name: String,
@@ -59,11 +88,13 @@ pub struct PluginMetadata {
 }
 
 /// Plugin status
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PluginStatus {
     /// Plugin is active and ready
     Active,
     /// Plugin is inactive
+    #[default]
     Inactive,
     /// Plugin is loading
     Loading,
@@ -71,8 +102,172 @@ pub enum PluginStatus {
     Error(String),
 }
 
+/// An optional operation a plugin can support, reported by [`Plugin::capabilities`]
+///
+/// The manager and CLI check capabilities before calling the matching trait method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// `install`
+    Install,
+    /// `uninstall`
+    Uninstall,
+    /// `update`
+    Update,
+    /// `switch_version`
+    SwitchVersion,
+    /// `list_versions` and `list_versions_page`
+    ListVersions,
+    /// `execute_command` and `exec_command`
+    Exec,
+    /// `set_config` and `set_config_value`
+    Config,
+    /// `project_constraint` reads a constraint from the project's own files
+    ProjectConstraint,
+    /// Artifacts are fetched with the PLM downloader and can be vendored
+    Vendor,
+    /// `install` accepts [`InstallOptions::platform`] for other platforms
+    CrossInstall,
+}
+
+impl Capability {
+    /// Every capability, in declaration order
+    pub const ALL: [Capability; 10] = [
+        Capability::Install,
+        Capability::Uninstall,
+        Capability::Update,
+        Capability::SwitchVersion,
+        Capability::ListVersions,
+        Capability::Exec,
+        Capability::Config,
+        Capability::ProjectConstraint,
+        Capability::Vendor,
+        Capability::CrossInstall,
+    ];
+
+    /// Name used by `supports_feature` and in serialized output
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Capability::Install => "install",
+            Capability::Uninstall => "uninstall",
+            Capability::Update => "update",
+            Capability::SwitchVersion => "switch_version",
+            Capability::ListVersions => "list_versions",
+            Capability::Exec => "exec",
+            Capability::Config => "config",
+            Capability::ProjectConstraint => "project_constraint",
+            Capability::Vendor => "vendor",
+            Capability::CrossInstall => "cross_install",
+        }
+    }
+
+    /// Look a capability up by name; `execute` is accepted for `exec`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "execute" => Some(Capability::Exec),
+            _ => Capability::ALL.into_iter().find(|c| c.as_str() == name),
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Lifecycle state of a plugin as tracked by `PluginManager`
+///
+/// Unlike [`PluginStatus`], which each plugin reports about itself, this is recorded
+/// by the manager as it registers, initializes and shuts plugins down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleState {
+    /// Registered but not initialized yet
+    Registered,
+    /// `initialize` is running
+    Initializing,
+    /// Initialized successfully
+    Active,
+    /// Initialization failed, see [`PluginLifecycle::last_error`]
+    Error,
+    /// `shutdown` is running
+    ShuttingDown,
+}
+
+impl LifecycleState {
+    /// Whether the manager may move a plugin from this state to `next`
+    pub fn can_transition_to(self, next: LifecycleState) -> bool {
+        use LifecycleState::*;
+        matches!(
+            (self, next),
+            (Registered | Active | Error, Initializing)
+                | (Initializing, Active | Error)
+                | (Registered | Active | Error, ShuttingDown)
+        )
+    }
+}
+
+impl fmt::Display for LifecycleState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LifecycleState::Registered => "registered",
+            LifecycleState::Initializing => "initializing",
+            LifecycleState::Active => "active",
+            LifecycleState::Error => "error",
+            LifecycleState::ShuttingDown => "shutting down",
+        })
+    }
+}
+
+/// Manager-side lifecycle record of a plugin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginLifecycle {
+    /// Current state
+    pub state: LifecycleState,
+    /// When the plugin was registered
+    pub registered_at: DateTime<Utc>,
+    /// When the plugin entered the current state
+    pub changed_at: DateTime<Utc>,
+    /// Most recent initialization error, kept after a later successful initialization
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl PluginLifecycle {
+    /// Record for a freshly registered plugin
+    pub fn registered() -> Self {
+        let now = Utc::now();
+        Self {
+            state: LifecycleState::Registered,
+            registered_at: now,
+            changed_at: now,
+            last_error: None,
+        }
+    }
+
+    /// Move to `next`, returning false and leaving the record unchanged for an invalid transition
+    pub fn transition(&mut self, next: LifecycleState) -> bool {
+        if !self.state.can_transition_to(next) {
+            return false;
+        }
+        self.state = next;
+        self.changed_at = Utc::now();
+        true
+    }
+
+    /// Move to [`LifecycleState::Error`] and remember the error
+    pub fn fail(&mut self, error: impl Into<String>) -> bool {
+        let moved = self.transition(LifecycleState::Error);
+        if moved {
+            self.last_error = Some(error.into());
+        }
+        moved
+    }
+}
+
 /// Version information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VersionInfo {
     /// Version string
     pub version: String,
@@ -86,10 +281,237 @@ pub struct VersionInfo {
     pub release_date: Option<String>,
     /// Pre-release flag
     pub prerelease: bool,
+    /// Detached signature (minisign or armored GPG), inline or as an http(s) URL
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Artifact size in bytes
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// Alternative download URLs for the same artifact, tried after `download_url`
+    #[serde(default)]
+    pub mirror_urls: Vec<String>,
+    /// Additional signature references; any one that verifies is accepted
+    #[serde(default)]
+    pub signatures: Vec<String>,
+    /// Archive format; detected from the download URL when not set
+    #[serde(default)]
+    pub archive_format: Option<ArchiveFormat>,
+    /// Executables inside the extracted artifact, relative to its root (e.g. `bin/node`)
+    #[serde(default)]
+    pub entry_points: Vec<String>,
+    /// Withdrawn by the publisher; still installable when pinned explicitly
+    #[serde(default)]
+    pub yanked: bool,
+    /// Content-addressed locations (`ipfs://<cid>`, `magnet:?xt=...`), tried before the
+    /// download URLs when built with the experimental `p2p` feature
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub content_uris: Vec<String>,
+}
+
+/// One page of available versions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionPage {
+    pub versions: Vec<VersionInfo>,
+    /// Cursor for the next page, `None` on the last page
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
+impl VersionPage {
+    /// Take the page starting at `offset` from a full version list; the cursor is the next offset
+    pub fn from_offset(versions: Vec<VersionInfo>, offset: usize, limit: usize) -> Self {
+        let end = offset.saturating_add(limit.max(1)).min(versions.len());
+        let next_cursor = (end < versions.len()).then(|| end.to_string());
+        let versions = versions
+            .into_iter()
+            .skip(offset)
+            .take(end.saturating_sub(offset))
+            .collect();
+        Self {
+            versions,
+            next_cursor,
+        }
+    }
+}
+
+/// Stream versions page by page, fetching the next page only when the current one is consumed
+///
+/// `fetch` receives the cursor of the page to load (`None` for the first page).
+/// The stream ends after the last page or after the first error.
+pub fn stream_pages<'a, F, Fut>(
+    fetch: F,
+) -> impl Stream<Item = Result<VersionInfo, PluginError>> + 'a
+where
+    F: FnMut(Option<String>) -> Fut + 'a,
+    Fut: Future<Output = Result<VersionPage, PluginError>> + 'a,
+{
+    struct State<F> {
+        fetch: F,
+        cursor: Option<String>,
+        buffer: std::vec::IntoIter<VersionInfo>,
+        finished: bool,
+    }
+
+    let state = State {
+        fetch,
+        cursor: None,
+        buffer: Vec::new().into_iter(),
+        finished: false,
+    };
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(version) = state.buffer.next() {
+                return Some((Ok(version), state));
+            }
+            if state.finished {
+                return None;
+            }
+            match (state.fetch)(state.cursor.take()).await {
+                Ok(page) => {
+                    state.finished = page.next_cursor.is_none();
+                    state.cursor = page.next_cursor;
+                    state.buffer = page.versions.into_iter();
+                }
+                Err(e) => {
+                    state.finished = true;
+                    return Some((Err(e), state));
+                }
+            }
+        }
+    })
+}
+
+/// Stream a plugin's available versions using `list_versions_page`
+pub fn version_stream(
+    plugin: &dyn Plugin,
+    page_size: usize,
+) -> impl Stream<Item = Result<VersionInfo, PluginError>> + '_ {
+    stream_pages(move |cursor| async move {
+        plugin
+            .list_versions_page(cursor.as_deref(), page_size)
+            .await
+    })
+}
+
+/// One row of the merged available/installed version view
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionEntry {
+    /// Version string
+    pub version: String,
+    /// Installed locally
+    pub installed: bool,
+    /// Currently active in the environment
+    pub active: bool,
+    /// Pre-release flag
+    pub prerelease: bool,
+    /// Withdrawn by the publisher
+    pub yanked: bool,
+    /// An artifact exists for the current platform (always true for installed-only versions)
+    pub platform_compatible: bool,
+    /// Remote version information, None when the version is only installed locally
+    pub info: Option<VersionInfo>,
+}
+
+/// Artifact archive format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArchiveFormat {
+    TarGz,
+    Zip,
+    /// A single file that needs no extraction
+    Raw,
+}
+
+impl ArchiveFormat {
+    /// Detect the archive format from a file name or URL
+    pub fn from_file_name(name: &str) -> Self {
+        let name = name.split(['?', '#']).next().unwrap_or_default();
+        let lower = name.to_ascii_lowercase();
+        if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+            ArchiveFormat::TarGz
+        } else if lower.ends_with(".zip") {
+            ArchiveFormat::Zip
+        } else {
+            ArchiveFormat::Raw
+        }
+    }
+}
+
+/// Result of [`Plugin::exec_command`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecOutcome {
+    /// The command ran with the terminal attached and exited with this code
+    Exited(i32),
+    /// The command's captured output, for the caller to print
+    Captured(String),
+}
+
+/// A launcher entry (a `.desktop` file or Start Menu shortcut) for a GUI tool
+///
+/// Plugins return these from [`Plugin::launcher_entries`]; the manager creates them after
+/// installing and removes them when the version is uninstalled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LauncherEntry {
+    /// Identifier, unique within the plugin, used in the launcher file name
+    pub id: String,
+    /// Name shown in the application menu
+    pub name: String,
+    /// Executable, relative to the install path unless absolute
+    pub exec: PathBuf,
+    /// Arguments passed to the executable
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Icon file, relative to the install path unless absolute
+    #[serde(default)]
+    pub icon: Option<PathBuf>,
+    /// Short description shown as a tooltip
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Freedesktop menu categories, e.g. `Development`
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Run the executable in a terminal
+    #[serde(default)]
+    pub terminal: bool,
+}
+
+impl LauncherEntry {
+    /// Create an entry for an executable inside the installation
+    pub fn new(id: &str, name: &str, exec: impl Into<PathBuf>) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            exec: exec.into(),
+            args: Vec::new(),
+            icon: None,
+            comment: None,
+            categories: Vec::new(),
+            terminal: false,
+        }
+    }
+
+    /// Set the icon file
+    pub fn with_icon(mut self, icon: impl Into<PathBuf>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Set the description
+    pub fn with_comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Add a menu category
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.categories.push(category.to_string());
+        self
+    }
 }
 
 /// Installation options
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct InstallOptions {
     /// Force installation
     pub force: bool,
@@ -99,78 +521,524 @@ pub struct InstallOptions {
     pub yes: bool,
     /// Quiet mode (minimal output)
     pub quiet: bool,
-    /// Custom installation directory
+    /// Custom installation root; the version is installed into `<install_dir>/<version>`
     pub install_dir: Option<String>,
+    /// Install artifacts for another platform than the host (see `crate::target`)
+    pub platform: Option<TargetPlatform>,
     /// Additional environment variables
     pub env_vars: HashMap<String, String>,
+    /// Resolve versions and report what would change without touching anything
+    pub dry_run: bool,
+}
+
+/// Result of installing a plugin version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallResult {
+    /// Plugin identifier
+    pub plugin: String,
+    /// Installed version
+    pub version: String,
+    /// Installation path reported by the plugin
+    pub path: PathBuf,
+    /// Wall-clock time spent installing
+    pub duration: Duration,
+    /// Bytes transferred over the network (0 when unknown or nothing was downloaded)
+    pub bytes_downloaded: u64,
+    /// The version was already present and no new artifact was fetched
+    pub was_cached: bool,
+    /// Non-fatal issues noticed during installation
+    pub warnings: Vec<String>,
+}
+
+impl fmt::Display for InstallResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())
+    }
+}
+
+/// Result of updating a plugin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateResult {
+    /// Plugin identifier
+    pub plugin: String,
+    /// Version (or constraint) configured before the update
+    pub previous: Option<String>,
+    /// Version reported by the plugin after the update
+    pub version: String,
+    /// Version constraint from the config that limited the update, if any
+    pub constraint: Option<String>,
+}
+
+impl UpdateResult {
+    /// Whether the update changed the installed version
+    pub fn changed(&self) -> bool {
+        match &self.previous {
+            Some(previous) => {
+                previous.trim_start_matches('v') != self.version.trim_start_matches('v')
+            }
+            None => true,
+        }
+    }
+}
+
+/// Why a plugin was left out of a bulk upgrade
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "reason", content = "detail")]
+pub enum SkipReason {
+    /// `auto_update` is disabled for the plugin
+    AutoUpdateDisabled,
+    /// The config pins an exact version
+    Pinned(String),
+    /// No plugin implementation is registered under the name
+    NotRegistered,
+    /// The plugin does not report [`Capability::Update`]
+    Unsupported,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::AutoUpdateDisabled => write!(f, "auto_update is disabled"),
+            SkipReason::Pinned(version) => write!(f, "pinned to {}", version),
+            SkipReason::NotRegistered => write!(f, "no plugin implementation registered"),
+            SkipReason::Unsupported => write!(f, "plugin does not support update"),
+        }
+    }
+}
+
+/// Summary of a bulk upgrade
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeSummary {
+    /// Plugins whose version changed
+    pub upgraded: Vec<UpdateResult>,
+    /// Plugins that were already at the newest allowed version
+    pub unchanged: Vec<UpdateResult>,
+    /// Plugins that were not upgraded, with the reason
+    pub skipped: Vec<(String, SkipReason)>,
+    /// Plugins whose upgrade failed, with the error message
+    pub failed: Vec<(String, String)>,
+}
+
+impl UpgradeSummary {
+    /// Whether every attempted upgrade succeeded
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Summary of installing several plugins at once
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallReport {
+    /// Plugins that were installed or already present, in request order
+    pub installed: Vec<InstallResult>,
+    /// Plugins whose installation failed, with the error message, in request order
+    pub failed: Vec<(String, String)>,
+}
+
+impl InstallReport {
+    /// Whether every requested plugin was installed
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Summary of initializing the plugins of a manager
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InitReport {
+    /// Plugins that initialized successfully
+    pub initialized: Vec<String>,
+    /// Plugins whose creation (by a factory) or initialization returned an error, with the error message
+    pub failed: Vec<(String, String)>,
+    /// Plugins left uninitialized because a plugin they depend on failed, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+impl InitReport {
+    /// Whether every plugin initialized
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty() && self.skipped.is_empty()
+    }
+
+    /// All failures as one message, sorted by plugin
+    pub fn error_message(&self) -> String {
+        let mut errors: Vec<String> = self
+            .failed
+            .iter()
+            .map(|(plugin, error)| format!("插件 {} 初始化失败: {}", plugin, error))
+            .chain(
+                self.skipped
+                    .iter()
+                    .map(|(plugin, reason)| format!("插件 {} 未初始化: {}", plugin, reason)),
+            )
+            .collect();
+        errors.sort();
+        errors.join("; ")
+    }
+}
+
+/// Summary of shutting down the plugins of a manager
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    /// Plugins that shut down cleanly
+    pub stopped: Vec<String>,
+    /// Plugins whose shutdown returned an error, with the error message
+    pub failed: Vec<(String, String)>,
+    /// Plugins that did not finish shutting down within the timeout
+    pub timed_out: Vec<String>,
+}
+
+impl ShutdownReport {
+    /// Whether every plugin shut down cleanly
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty() && self.timed_out.is_empty()
+    }
+}
+
+/// Summary of applying a new project configuration to a running manager
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReloadReport {
+    /// Plugins that were newly registered and initialized
+    pub added: Vec<String>,
+    /// Plugins that were shut down because they were removed or disabled
+    pub removed: Vec<String>,
+    /// Plugins that picked up changed settings
+    pub reloaded: Vec<String>,
+    /// Plugins that could not be loaded or reconfigured, with the error message
+    pub failed: Vec<(String, String)>,
+}
+
+impl ReloadReport {
+    /// Whether every plugin change was applied
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Whether the new configuration did not affect any plugin
+    pub fn is_noop(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.reloaded.is_empty()
+            && self.failed.is_empty()
+    }
+}
+
+/// What a dry run found an operation would do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// A version would be downloaded and installed
+    Install,
+    /// An installed version would be removed
+    Uninstall,
+    /// The plugin would move to a different version
+    Update,
+    /// The requested version is already installed
+    AlreadyInstalled,
+    /// The plugin is already at the newest allowed version
+    UpToDate,
+}
+
+/// A change reported by a dry run instead of being made
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedChange {
+    /// Plugin identifier
+    pub plugin: String,
+    pub kind: ChangeKind,
+    /// Resolved target version (the removed version for uninstalls)
+    pub version: String,
+    /// Version configured before an update
+    pub previous: Option<String>,
+    /// Artifact that would be downloaded, when the plugin publishes one
+    pub download_url: Option<String>,
+    /// Artifact size in bytes, when known
+    pub size: Option<u64>,
+}
+
+impl PlannedChange {
+    /// Whether applying the change would do nothing
+    pub fn is_noop(&self) -> bool {
+        matches!(
+            self.kind,
+            ChangeKind::AlreadyInstalled | ChangeKind::UpToDate
+        )
+    }
+}
+
+impl fmt::Display for PlannedChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ChangeKind::Install => write!(f, "install {} {}", self.plugin, self.version)?,
+            ChangeKind::Uninstall => write!(f, "uninstall {} {}", self.plugin, self.version)?,
+            ChangeKind::Update => write!(
+                f,
+                "update {} {} → {}",
+                self.plugin,
+                self.previous.as_deref().unwrap_or("-"),
+                self.version
+            )?,
+            ChangeKind::AlreadyInstalled => {
+                return write!(f, "{} {} is already installed", self.plugin, self.version)
+            }
+            ChangeKind::UpToDate => {
+                return write!(f, "{} {} is up to date", self.plugin, self.version)
+            }
+        }
+        match (&self.download_url, self.size) {
+            (Some(url), Some(size)) => {
+                write!(
+                    f,
+                    " (download {} from {})",
+                    crate::cache::format_size(size),
+                    url
+                )
+            }
+            (Some(url), None) => write!(f, " (download from {})", url),
+            (None, _) => Ok(()),
+        }
+    }
 }
 
 /// Main plugin trait
+///
+/// Only `metadata` and `install` are required. Every other method has a default so
+/// trivial plugins stay small: lifecycle hooks and `cleanup` do nothing, installed
+/// versions and configuration are empty, and operations tied to a [`Capability`]
+/// (`uninstall`, `list_versions`, `update`, `switch_version`, `set_config`,
+/// `execute_command`) return [`PluginError::Unsupported`]. A plugin that overrides one of
+/// those should also report the capability from `capabilities`, or by name from
+/// `supports_feature`; by default only [`Capability::Install`] is reported.
 #[async_trait]
 pub trait Plugin: Send + Sync {
     /// Get plugin metadata
     fn metadata(&self) -> PluginMetadata;
 
     /// Get plugin status
-    fn status(&self) -> PluginStatus;
+    ///
+    /// The default implementation reports the plugin as active.
+    fn status(&self) -> PluginStatus {
+        PluginStatus::Active
+    }
 
     /// Initialize plugin
-    async fn initialize(&mut self) -> Result<(), PluginError>;
+    async fn initialize(&mut self) -> Result<(), PluginError> {
+        Ok(())
+    }
 
     /// Shutdown plugin
-    async fn shutdown(&mut self) -> Result<(), PluginError>;
+    async fn shutdown(&mut self) -> Result<(), PluginError> {
+        Ok(())
+    }
 
     /// Install a version of the tool
     async fn install(&self, version: &str, options: &InstallOptions)
         -> Result<String, PluginError>;
 
     /// Uninstall a version of the tool
-    async fn uninstall(&self, version: &str) -> Result<(), PluginError>;
+    async fn uninstall(&self, _version: &str) -> Result<(), PluginError> {
+        Err(unsupported(self, Capability::Uninstall))
+    }
 
     /// List available versions
-    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError>;
+    async fn list_versions(&self) -> Result<Vec<VersionInfo>, PluginError> {
+        Err(unsupported(self, Capability::ListVersions))
+    }
+
+    /// List one page of available versions, in the same order as `list_versions`
+    ///
+    /// `cursor` is the `next_cursor` of the previous page. The default implementation
+    /// pages over `list_versions`; plugins backed by a paginated API should override it
+    /// so that only the requested page is fetched.
+    async fn list_versions_page(
+        &self,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<VersionPage, PluginError> {
+        let offset = match cursor {
+            Some(cursor) => cursor.parse::<usize>().map_err(|_| {
+                PluginError::ValidationError(format!("Invalid version cursor '{}'", cursor))
+            })?,
+            None => 0,
+        };
+        let versions = self.list_versions().await?;
+        Ok(VersionPage::from_offset(versions, offset, limit))
+    }
 
     /// List installed versions
-    async fn list_installed(&self) -> Result<Vec<String>, PluginError>;
+    async fn list_installed(&self) -> Result<Vec<String>, PluginError> {
+        Ok(Vec::new())
+    }
+
+    /// Version constraint declared by the project's own files, such as `required_version`
+    /// in Terraform configurations
+    ///
+    /// `sync` uses it for plugins without a version in the config. The default
+    /// implementation declares no constraint.
+    async fn project_constraint(
+        &self,
+        _project_root: &Path,
+    ) -> Result<Option<String>, PluginError> {
+        Ok(None)
+    }
+
+    /// Launcher entries to create for an installed version, such as menu entries for GUI tools
+    ///
+    /// Called after a successful install with the path the plugin reported. The default
+    /// implementation declares none, so CLI-only plugins need not override it.
+    async fn launcher_entries(
+        &self,
+        _version: &str,
+        _install_path: &Path,
+    ) -> Result<Vec<LauncherEntry>, PluginError> {
+        Ok(Vec::new())
+    }
 
     /// Check if a version is installed
-    async fn is_installed(&self, version: &str) -> Result<bool, PluginError>;
+    ///
+    /// The default implementation looks the version up in `list_installed`.
+    async fn is_installed(&self, version: &str) -> Result<bool, PluginError> {
+        Ok(self
+            .list_installed()
+            .await?
+            .iter()
+            .any(|installed| crate::core::same_version(installed, version)))
+    }
 
     /// Get the latest version
-    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError>;
+    ///
+    /// The default implementation picks the newest stable version from `list_versions`
+    /// that supports the current platform.
+    async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
+        let platform = crate::config::current_platform();
+        self.list_versions()
+            .await?
+            .into_iter()
+            .filter(|info| !info.prerelease && !info.yanked && info.supports_platform(platform))
+            .filter_map(|info| {
+                crate::version::Version::parse(&info.version)
+                    .ok()
+                    .map(|v| (v, info))
+            })
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, info)| info)
+            .ok_or_else(|| {
+                PluginError::NotFound(format!("Stable version of {}", self.metadata().name))
+            })
+    }
 
     /// Update to latest or specific version
-    async fn update(&self, version: Option<&str>) -> Result<String, PluginError>;
+    async fn update(&self, _version: Option<&str>) -> Result<String, PluginError> {
+        Err(unsupported(self, Capability::Update))
+    }
 
     /// Switch to a specific version
-    async fn switch_version(&self, version: &str) -> Result<(), PluginError>;
+    async fn switch_version(&self, _version: &str) -> Result<(), PluginError> {
+        Err(unsupported(self, Capability::SwitchVersion))
+    }
 
     /// Verify installation
-    async fn verify_installation(&self, version: &str) -> Result<bool, PluginError>;
+    ///
+    /// The default implementation only checks that the version is installed.
+    async fn verify_installation(&self, version: &str) -> Result<bool, PluginError> {
+        self.is_installed(version).await
+    }
 
     /// Clean up plugin cache
-    async fn cleanup(&self) -> Result<(), PluginError>;
+    async fn cleanup(&self) -> Result<(), PluginError> {
+        Ok(())
+    }
+
+    /// Receive the plugin's settings from `plm.json`
+    ///
+    /// Called when the plugin is registered with a matching config entry and again when
+    /// the entry changes on reload, before `set_config`. `settings` is a JSON object with
+    /// the platform block for the current platform merged in; deserialize it into a typed
+    /// struct with [`crate::config::settings_from_value`]. The default implementation
+    /// ignores it.
+    async fn configure(&self, _settings: serde_json::Value) -> Result<(), PluginError> {
+        Ok(())
+    }
 
     /// Get plugin configuration
-    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError>;
+    async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
+        Ok(HashMap::new())
+    }
 
     /// Set plugin configuration
-    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError>;
+    async fn set_config(&self, _config: HashMap<String, String>) -> Result<(), PluginError> {
+        Err(unsupported(self, Capability::Config))
+    }
 
     /// Get specific configuration value
-    async fn get_config_value(&self, key: &str) -> Result<Option<String>, PluginError>;
+    ///
+    /// The default implementation looks the key up in `get_config`.
+    async fn get_config_value(&self, key: &str) -> Result<Option<String>, PluginError> {
+        Ok(self.get_config().await?.remove(key))
+    }
 
     /// Set specific configuration value
-    async fn set_config_value(&self, key: &str, value: &str) -> Result<(), PluginError>;
+    async fn set_config_value(&self, _key: &str, _value: &str) -> Result<(), PluginError> {
+        Err(unsupported(self, Capability::Config))
+    }
 
     /// Execute plugin-specific command
-    async fn execute_command(&self, command: &str, args: &[&str]) -> Result<String, PluginError>;
+    async fn execute_command(&self, _command: &str, _args: &[&str]) -> Result<String, PluginError> {
+        Err(unsupported(self, Capability::Exec))
+    }
+
+    /// Run a plugin-specific command with the terminal attached
+    ///
+    /// `plm exec` uses it so output streams as it is produced; implementations that
+    /// stream return [`ExecOutcome::Exited`]. The default implementation returns the
+    /// captured output of `execute_command` for the caller to print.
+    async fn exec_command(&self, command: &str, args: &[&str]) -> Result<ExecOutcome, PluginError> {
+        Ok(ExecOutcome::Captured(self.execute_command(command, args).await?))
+    }
 
     /// Get plugin help information
-    fn get_help(&self) -> String;
+    ///
+    /// The default implementation shows the name, version and description from `metadata`.
+    fn get_help(&self) -> String {
+        let metadata = self.metadata();
+        format!(
+            "{} {}\n{}",
+            metadata.name, metadata.version, metadata.description
+        )
+    }
 
-    /// Check if plugin supports a specific feature
-    fn supports_feature(&self, feature: &str) -> bool;
+    /// Optional operations the plugin supports
+    ///
+    /// The default is derived from `supports_feature`, so plugins that declare their
+    /// features by name keep working.
+    fn capabilities(&self) -> HashSet<Capability> {
+        Capability::ALL
+            .into_iter()
+            .filter(|capability| self.supports_feature(capability.as_str()))
+            .chain(self.supports_feature("execute").then_some(Capability::Exec))
+            .collect()
+    }
+
+    /// Check if plugin supports a capability
+    fn supports(&self, capability: Capability) -> bool {
+        self.capabilities().contains(&capability)
+    }
+
+    /// Check if plugin supports a feature given by its capability name
+    ///
+    /// The name-based way to declare capabilities; by default only `install` is supported.
+    /// Plugins that override `capabilities` don't need to override this, and callers should
+    /// query [`Plugin::supports`], which covers both.
+    fn supports_feature(&self, feature: &str) -> bool {
+        Capability::from_name(feature) == Some(Capability::Install)
+    }
+}
+
+/// Error for an operation the plugin does not support
+pub fn unsupported<P: Plugin + ?Sized>(plugin: &P, capability: Capability) -> PluginError {
+    PluginError::Unsupported(format!(
+        "{} does not support {}",
+        plugin.metadata().name,
+        capability
+    ))
 }
 
 /// Plugin factory trait for creating plugins
@@ -229,6 +1097,175 @@ impl Default for PluginMetadata {
     }
 }
 
+impl PluginMetadata {
+    /// Start building validated metadata for the named plugin
+    pub fn builder(name: &str) -> PluginMetadataBuilder {
+        PluginMetadataBuilder::new(name)
+    }
+}
+
+/// Builder for [`PluginMetadata`] that validates fields when `build` is called
+#[derive(Debug, Clone, Default)]
+pub struct PluginMetadataBuilder {
+    name: String,
+    version: Option<String>,
+    description: Option<String>,
+    author: Option<String>,
+    homepage: Option<String>,
+    repository: Option<String>,
+    supported_platforms: Vec<String>,
+    tags: Vec<String>,
+    dependencies: Vec<String>,
+    min_plm_version: Option<String>,
+}
+
+impl PluginMetadataBuilder {
+    /// Create a builder for the named plugin
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Set plugin version (required, semver)
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+
+    /// Set description (required)
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Set author (required)
+    pub fn author(mut self, author: &str) -> Self {
+        self.author = Some(author.to_string());
+        self
+    }
+
+    /// Set homepage URL
+    pub fn homepage(mut self, url: &str) -> Self {
+        self.homepage = Some(url.to_string());
+        self
+    }
+
+    /// Set repository URL
+    pub fn repository(mut self, url: &str) -> Self {
+        self.repository = Some(url.to_string());
+        self
+    }
+
+    /// Add a supported platform; aliases such as `darwin` or `win32` are canonicalized
+    pub fn platform(mut self, platform: &str) -> Self {
+        self.supported_platforms.push(platform.to_string());
+        self
+    }
+
+    /// Add a tag
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Add a dependency, e.g. `openssl >=3.0`
+    pub fn dependency(mut self, dependency: &str) -> Self {
+        self.dependencies.push(dependency.to_string());
+        self
+    }
+
+    /// Set minimum PLM version (semver)
+    pub fn min_plm_version(mut self, version: &str) -> Self {
+        self.min_plm_version = Some(version.to_string());
+        self
+    }
+
+    /// Validate all fields and build the metadata
+    ///
+    /// When no platform is given, all platforms are assumed to be supported
+    pub fn build(self) -> Result<PluginMetadata, PluginError> {
+        crate::id::PluginId::new(&self.name)?;
+        let version = required("version", self.version)?;
+        crate::version::Version::parse(&version)?;
+        let description = required("description", self.description)?;
+        let author = required("author", self.author)?;
+
+        for (field, url) in [
+            ("homepage", &self.homepage),
+            ("repository", &self.repository),
+        ] {
+            if let Some(url) = url {
+                validate_url(field, url)?;
+            }
+        }
+        if let Some(min_version) = &self.min_plm_version {
+            crate::version::Version::parse(min_version)?;
+        }
+        for dependency in &self.dependencies {
+            crate::version::DependencySpec::parse(dependency)?;
+        }
+
+        let mut supported_platforms = Vec::new();
+        for platform in &self.supported_platforms {
+            let canonical = canonical_platform(platform).ok_or_else(|| {
+                PluginError::ValidationError(format!("Unknown platform '{}'", platform))
+            })?;
+            if !supported_platforms.iter().any(|p| p == canonical) {
+                supported_platforms.push(canonical.to_string());
+            }
+        }
+        if supported_platforms.is_empty() {
+            supported_platforms = PluginMetadata::default().supported_platforms;
+        }
+
+        Ok(PluginMetadata {
+            name: self.name,
+            version,
+            description,
+            author,
+            homepage: self.homepage,
+            repository: self.repository,
+            supported_platforms,
+            tags: self.tags,
+            dependencies: self.dependencies,
+            min_plm_version: self.min_plm_version,
+        })
+    }
+}
+
+fn required(field: &str, value: Option<String>) -> Result<String, PluginError> {
+    value
+        .filter(|v| !v.trim().is_empty())
+        .ok_or_else(|| PluginError::ValidationError(format!("Missing required field '{}'", field)))
+}
+
+fn validate_url(field: &str, value: &str) -> Result<(), PluginError> {
+    match url::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+        Ok(url) => Err(PluginError::ValidationError(format!(
+            "Field '{}' must be an http(s) URL, got scheme '{}'",
+            field,
+            url.scheme()
+        ))),
+        Err(e) => Err(PluginError::ValidationError(format!(
+            "Field '{}' is not a valid URL: {}",
+            field, e
+        ))),
+    }
+}
+
+/// Map a platform name or common alias to its canonical name
+pub fn canonical_platform(platform: &str) -> Option<&'static str> {
+    match platform.trim().to_ascii_lowercase().as_str() {
+        "linux" => Some("linux"),
+        "macos" | "darwin" | "osx" | "mac" => Some("macos"),
+        "windows" | "win" | "win32" | "win64" => Some("windows"),
+        _ => None,
+    }
+}
+
 impl VersionInfo {
     /// Create new version info
     pub fn new(version: &str, platform: &str, download_url: &str) -> Self {
@@ -239,6 +1276,14 @@ impl VersionInfo {
             checksum: None,
             release_date: None,
             prerelease: false,
+            signature: None,
+            size: None,
+            mirror_urls: Vec::new(),
+            signatures: Vec::new(),
+            archive_format: None,
+            entry_points: Vec::new(),
+            yanked: false,
+            content_uris: Vec::new(),
         }
     }
 
@@ -254,15 +1299,106 @@ impl VersionInfo {
         self
     }
 
+    /// Set detached signature or signature URL
+    pub fn with_signature(mut self, signature: &str) -> Self {
+        self.signature = Some(signature.to_string());
+        self
+    }
+
     /// Mark as prerelease
     pub fn as_prerelease(mut self) -> Self {
         self.prerelease = true;
         self
     }
+
+    /// Mark as yanked
+    pub fn as_yanked(mut self) -> Self {
+        self.yanked = true;
+        self
+    }
+
+    /// Whether the artifact targets the given platform (`any` matches every platform)
+    ///
+    /// Architecture suffixes such as `linux-x64` or `darwin_arm64` are ignored
+    pub fn supports_platform(&self, platform: &str) -> bool {
+        let target = self.platform.trim();
+        let os = target.split(['-', '_']).next().unwrap_or_default();
+        target.is_empty()
+            || target.eq_ignore_ascii_case("any")
+            || canonical_platform(os).is_some_and(|p| Some(p) == canonical_platform(platform))
+    }
+
+    /// Whether the artifact targets the given OS and architecture
+    ///
+    /// Artifacts without a recognizable architecture suffix match every architecture
+    pub fn supports_target(&self, target: &TargetPlatform) -> bool {
+        let arch = self
+            .platform
+            .trim()
+            .split_once(['-', '_'])
+            .and_then(|(_, arch)| canonical_arch(arch));
+        self.supports_platform(&target.os) && arch.is_none_or(|arch| arch == target.arch)
+    }
+
+    /// Set artifact size in bytes
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Add an alternative download URL
+    pub fn with_mirror_url(mut self, url: &str) -> Self {
+        self.mirror_urls.push(url.to_string());
+        self
+    }
+
+    /// Add a content-addressed location (`ipfs://` or `magnet:`)
+    pub fn with_content_uri(mut self, uri: &str) -> Self {
+        self.content_uris.push(uri.to_string());
+        self
+    }
+
+    /// Add an additional signature reference
+    pub fn with_additional_signature(mut self, signature: &str) -> Self {
+        self.signatures.push(signature.to_string());
+        self
+    }
+
+    /// Set archive format
+    pub fn with_archive_format(mut self, format: ArchiveFormat) -> Self {
+        self.archive_format = Some(format);
+        self
+    }
+
+    /// Add an executable entry point
+    pub fn with_entry_point(mut self, path: &str) -> Self {
+        self.entry_points.push(path.to_string());
+        self
+    }
+
+    /// All download URLs in the order they should be tried
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.download_url.as_str())
+            .chain(self.mirror_urls.iter().map(String::as_str))
+    }
+
+    /// All signature references, `signature` first
+    pub fn all_signatures(&self) -> impl Iterator<Item = &str> {
+        self.signature
+            .iter()
+            .chain(self.signatures.iter())
+            .map(String::as_str)
+    }
+
+    /// Archive format, falling back to detection from the download URL
+    pub fn archive_format(&self) -> ArchiveFormat {
+        self.archive_format
+            .unwrap_or_else(|| ArchiveFormat::from_file_name(&self.download_url))
+    }
 }
 
 /// Validation summary
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ValidationSummary {
     pub valid_plugins: usize,
     pub invalid_plugins: usize,
@@ -322,4 +1458,21 @@ impl InstallOptions {
         self.env_vars.insert(key.to_string(), value.to_string());
         self
     }
+
+    /// Only report what would be installed
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Install artifacts for the given target platform
+    pub fn platform(mut self, target: TargetPlatform) -> Self {
+        self.platform = Some(target);
+        self
+    }
+
+    /// Target platform when it differs from the host
+    pub fn cross_target(&self) -> Option<&TargetPlatform> {
+        self.platform.as_ref().filter(|target| !target.is_host())
+    }
 }
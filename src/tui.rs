@@ -0,0 +1,598 @@
+//! PLM 交互式终端界面（`plm ui`）
+//!
+//! 上方列出已注册的插件及其状态、配置版本、已安装版本和可用更新，
+//! 下方的日志面板实时显示事件总线上的生命周期事件。
+//!
+//! 按键：`↑`/`↓`（或 `k`/`j`）选择插件，`i` 安装，`u` 更新，`d` 卸载最新的已安装版本
+//! （需要按 `y` 确认），`r` 刷新，`q`/`Esc` 退出。
+//! 操作执行期间界面继续刷新日志，按键会被忽略。
+
+use crate::core::PluginManager;
+use crate::events::PlmEvent;
+use crate::traits::{InstallOptions, PluginError, PluginStatus};
+use crate::version::Version;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Row, Table, TableState};
+use ratatui::{Frame, Terminal};
+use std::collections::VecDeque;
+use std::io::Stdout;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+/// 日志面板保留的最大行数
+const MAX_LOG_LINES: usize = 500;
+
+/// 读取按键时的轮询间隔，界面退出后输入线程在该时间内结束
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 插件列表中的一行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginRow {
+    pub name: String,
+    pub status: PluginStatus,
+    /// 配置中的版本或版本约束
+    pub pinned: Option<String>,
+    pub installed: Vec<String>,
+    /// 最新稳定版本，查询失败时为 None
+    pub latest: Option<String>,
+}
+
+impl PluginRow {
+    /// 最新的已安装版本
+    pub fn newest_installed(&self) -> Option<&str> {
+        self.installed
+            .iter()
+            .max_by(|a, b| match (Version::parse(a), Version::parse(b)) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.cmp(b),
+            })
+            .map(String::as_str)
+    }
+
+    /// 最新版本是否比所有已安装版本都新
+    pub fn update_available(&self) -> bool {
+        let Some(latest) = self.latest.as_deref().and_then(|v| Version::parse(v).ok()) else {
+            return false;
+        };
+        match self.newest_installed().map(Version::parse) {
+            Some(Ok(installed)) => latest > installed,
+            _ => false,
+        }
+    }
+}
+
+/// 按键触发的操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// 安装配置中的固定版本，未固定时安装最新版本
+    Install {
+        plugin: String,
+        version: Option<String>,
+    },
+    Update {
+        plugin: String,
+    },
+    Uninstall {
+        plugin: String,
+        version: String,
+    },
+    Refresh,
+    Quit,
+}
+
+/// 界面状态
+#[derive(Debug, Default)]
+pub struct App {
+    pub rows: Vec<PluginRow>,
+    pub selected: usize,
+    pub logs: VecDeque<String>,
+    /// 等待确认的操作
+    pub confirm: Option<Action>,
+    /// 状态栏提示
+    pub message: String,
+}
+
+impl App {
+    /// 创建界面状态
+    pub fn new(rows: Vec<PluginRow>) -> Self {
+        Self {
+            rows,
+            ..Self::default()
+        }
+    }
+
+    /// 当前选中的插件
+    pub fn selected_row(&self) -> Option<&PluginRow> {
+        self.rows.get(self.selected)
+    }
+
+    /// 追加一行日志
+    pub fn log(&mut self, line: impl Into<String>) {
+        let timestamp = chrono::Local::now().format("%H:%M:%S");
+        self.logs
+            .push_back(format!("{} {}", timestamp, line.into()));
+        while self.logs.len() > MAX_LOG_LINES {
+            self.logs.pop_front();
+        }
+    }
+
+    /// 替换某个插件的行，插件不存在时追加
+    pub fn set_row(&mut self, row: PluginRow) {
+        match self.rows.iter_mut().find(|r| r.name == row.name) {
+            Some(existing) => *existing = row,
+            None => self.rows.push(row),
+        }
+    }
+
+    /// 处理按键，返回需要执行的操作
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Action::Quit);
+        }
+
+        if let Some(action) = self.confirm.take() {
+            if key.code == KeyCode::Char('y') {
+                return Some(action);
+            }
+            self.message = "Cancelled".to_string();
+            return None;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+            KeyCode::Char('r') => Some(Action::Refresh),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < self.rows.len() {
+                    self.selected += 1;
+                }
+                None
+            }
+            KeyCode::Char('i') => {
+                let row = self.selected_row()?;
+                // 版本约束交给插件管理器按 latest 处理
+                let version = row
+                    .pinned
+                    .clone()
+                    .filter(|pinned| Version::parse(pinned).is_ok());
+                Some(Action::Install {
+                    plugin: row.name.clone(),
+                    version,
+                })
+            }
+            KeyCode::Char('u') => Some(Action::Update {
+                plugin: self.selected_row()?.name.clone(),
+            }),
+            KeyCode::Char('d') => {
+                let row = self.selected_row()?;
+                let plugin = row.name.clone();
+                let Some(version) = row.newest_installed().map(str::to_string) else {
+                    self.message = format!("{} has no installed versions", plugin);
+                    return None;
+                };
+                self.message = format!("Uninstall {} {}? (y/n)", plugin, version);
+                self.confirm = Some(Action::Uninstall { plugin, version });
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 日志面板中事件的描述
+pub fn describe_event(event: &PlmEvent) -> String {
+    match event {
+        PlmEvent::PluginRegistered { plugin } => format!("registered {}", plugin),
+        PlmEvent::InstallStarted { plugin, version } => {
+            format!("installing {} {}...", plugin, version)
+        }
+        PlmEvent::InstallCompleted {
+            plugin,
+            version,
+            path,
+            duration,
+        } => format!(
+            "installed {} {} to {} in {:.1}s",
+            plugin,
+            version,
+            path,
+            duration.as_secs_f64()
+        ),
+        PlmEvent::InstallFailed {
+            plugin,
+            version,
+            error,
+        } => format!("failed to install {} {}: {}", plugin, version, error),
+        PlmEvent::PluginShutdown { plugin } => format!("shut down {}", plugin),
+        PlmEvent::DiscoveryFinished { count } => format!("discovered {} plugin(s)", count),
+        PlmEvent::ConfigReloaded {
+            added,
+            removed,
+            reloaded,
+        } => format!(
+            "reloaded config: {} added, {} removed, {} reloaded",
+            added, removed, reloaded
+        ),
+    }
+}
+
+/// 读取插件的当前状态
+///
+/// 查询已安装版本或最新版本失败时记录为空，不影响其他插件显示
+pub async fn load_row(manager: &PluginManager, name: &str) -> Result<PluginRow, PluginError> {
+    let plugin = manager.get_plugin(name).await?;
+    let pinned = manager
+        .get_config()
+        .get_plugin(name)
+        .and_then(|config| config.get_version())
+        .map(str::to_string);
+    Ok(PluginRow {
+        name: name.to_string(),
+        status: plugin.status(),
+        pinned,
+        installed: plugin.list_installed().await.unwrap_or_default(),
+        latest: plugin
+            .get_latest_version()
+            .await
+            .ok()
+            .map(|info| info.version),
+    })
+}
+
+/// 读取所有已注册插件的状态，按名称排序
+pub async fn load_rows(manager: &PluginManager) -> Vec<PluginRow> {
+    let mut names = manager.list_plugins().await;
+    names.sort();
+    let mut rows = Vec::with_capacity(names.len());
+    for name in names {
+        if let Ok(row) = load_row(manager, &name).await {
+            rows.push(row);
+        }
+    }
+    rows
+}
+
+/// 执行操作，返回日志面板中的结果描述
+async fn perform(manager: &mut PluginManager, action: &Action) -> Result<String, PluginError> {
+    // 安静模式避免进度条输出破坏界面
+    let options = InstallOptions::new().yes().quiet();
+    match action {
+        Action::Install { plugin, version } => {
+            let result = manager
+                .install_plugin(plugin.as_str(), version.as_deref(), &options)
+                .await?;
+            Ok(if result.was_cached {
+                format!("{} {} already installed", plugin, result.version)
+            } else {
+                format!("{} {} installed", plugin, result.version)
+            })
+        }
+        Action::Update { plugin } => {
+            let update = manager.update_plugin(plugin.as_str()).await?;
+            Ok(if update.changed() {
+                format!(
+                    "{} {} → {}",
+                    plugin,
+                    update.previous.as_deref().unwrap_or("-"),
+                    update.version
+                )
+            } else {
+                format!("{} {} is up to date", plugin, update.version)
+            })
+        }
+        Action::Uninstall { plugin, version } => {
+            manager.uninstall_plugin(plugin.as_str(), version).await?;
+            Ok(format!("{} {} uninstalled", plugin, version))
+        }
+        Action::Refresh | Action::Quit => Ok(String::new()),
+    }
+}
+
+type Term = Terminal<CrosstermBackend<Stdout>>;
+
+/// 恢复终端状态，界面出错退出时同样生效
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+    }
+}
+
+fn terminal_error(e: std::io::Error) -> PluginError {
+    PluginError::IoError(format!("终端操作失败: {}", e))
+}
+
+/// 在独立线程中读取按键，接收端关闭后线程退出
+fn spawn_input_reader() -> mpsc::UnboundedReceiver<KeyEvent> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::task::spawn_blocking(move || {
+        while !tx.is_closed() {
+            match event::poll(INPUT_POLL_INTERVAL) {
+                Ok(true) => {
+                    if let Ok(Event::Key(key)) = event::read() {
+                        if tx.send(key).is_err() {
+                            break;
+                        }
+                    }
+                }
+                Ok(false) => {}
+                Err(_) => break,
+            }
+        }
+    });
+    rx
+}
+
+/// 运行交互式界面，直到用户退出
+///
+/// 更新操作可能修改配置，调用方应在返回后按 `is_dirty` 保存配置
+pub async fn run(manager: &mut PluginManager) -> Result<(), PluginError> {
+    let mut events = manager.subscribe();
+    let mut app = App::new(load_rows(manager).await);
+    app.message = "Loaded plugins".to_string();
+
+    enable_raw_mode().map_err(terminal_error)?;
+    let _guard = TerminalGuard;
+    let mut stdout = std::io::stdout();
+    execute!(stdout, EnterAlternateScreen).map_err(terminal_error)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout)).map_err(terminal_error)?;
+    let mut keys = spawn_input_reader();
+
+    loop {
+        draw(&mut terminal, &app)?;
+        tokio::select! {
+            key = keys.recv() => {
+                let Some(key) = key else { break };
+                match app.handle_key(key) {
+                    Some(Action::Quit) => break,
+                    Some(Action::Refresh) => {
+                        app.message = "Refreshing...".to_string();
+                        draw(&mut terminal, &app)?;
+                        app.rows = load_rows(manager).await;
+                        app.selected = app.selected.min(app.rows.len().saturating_sub(1));
+                        app.message = "Refreshed".to_string();
+                    }
+                    Some(action) => run_action(manager, &mut terminal, &mut app, &mut events, action).await?,
+                    None => {}
+                }
+            }
+            event = events.recv() => receive_event(&mut app, event),
+        }
+    }
+    Ok(())
+}
+
+/// 执行操作，期间继续显示事件日志
+async fn run_action(
+    manager: &mut PluginManager,
+    terminal: &mut Term,
+    app: &mut App,
+    events: &mut broadcast::Receiver<PlmEvent>,
+    action: Action,
+) -> Result<(), PluginError> {
+    app.message = "Working...".to_string();
+    let outcome = {
+        let task = perform(manager, &action);
+        tokio::pin!(task);
+        loop {
+            draw(terminal, app)?;
+            tokio::select! {
+                outcome = &mut task => break outcome,
+                event = events.recv() => receive_event(app, event),
+            }
+        }
+    };
+
+    match outcome {
+        Ok(summary) => {
+            app.log(summary.clone());
+            app.message = summary;
+        }
+        Err(e) => {
+            app.log(format!("error: {}", e));
+            app.message = format!("Error: {}", e);
+        }
+    }
+
+    let plugin = match &action {
+        Action::Install { plugin, .. }
+        | Action::Update { plugin }
+        | Action::Uninstall { plugin, .. } => plugin,
+        Action::Refresh | Action::Quit => return Ok(()),
+    };
+    if let Ok(row) = load_row(manager, plugin).await {
+        app.set_row(row);
+    }
+    Ok(())
+}
+
+fn receive_event(app: &mut App, event: Result<PlmEvent, broadcast::error::RecvError>) {
+    match event {
+        Ok(event) => app.log(describe_event(&event)),
+        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+            app.log(format!("... {} event(s) dropped", skipped))
+        }
+        Err(broadcast::error::RecvError::Closed) => {}
+    }
+}
+
+fn draw(terminal: &mut Term, app: &App) -> Result<(), PluginError> {
+    terminal
+        .draw(|frame| render(frame, app))
+        .map(|_| ())
+        .map_err(terminal_error)
+}
+
+fn render(frame: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),
+            Constraint::Length(10),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+
+    let header = Row::new(vec!["Plugin", "Status", "Pinned", "Installed", "Latest"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = app.rows.iter().map(|row| {
+        let (status, color) = match &row.status {
+            PluginStatus::Active => ("active".to_string(), Color::Green),
+            PluginStatus::Inactive => ("inactive".to_string(), Color::DarkGray),
+            PluginStatus::Loading => ("loading".to_string(), Color::Yellow),
+            PluginStatus::Error(e) => (format!("error: {}", e), Color::Red),
+        };
+        let latest = match &row.latest {
+            Some(latest) if row.update_available() => format!("{} ⬆", latest),
+            Some(latest) => latest.clone(),
+            None => "?".to_string(),
+        };
+        Row::new(vec![
+            row.name.clone(),
+            status,
+            row.pinned.clone().unwrap_or_else(|| "-".to_string()),
+            if row.installed.is_empty() {
+                "-".to_string()
+            } else {
+                row.installed.join(", ")
+            },
+            latest,
+        ])
+        .style(Style::default().fg(color))
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(20),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(35),
+            Constraint::Percentage(15),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(" Plugins "))
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state =
+        TableState::default().with_selected((!app.rows.is_empty()).then_some(app.selected));
+    frame.render_stateful_widget(table, chunks[0], &mut state);
+
+    // 只显示能放进面板的最新日志
+    let visible = chunks[1].height.saturating_sub(2) as usize;
+    let logs: Vec<ListItem> = app
+        .logs
+        .iter()
+        .skip(app.logs.len().saturating_sub(visible))
+        .map(|line| ListItem::new(line.as_str()))
+        .collect();
+    frame.render_widget(
+        List::new(logs).block(Block::default().borders(Borders::ALL).title(" Log ")),
+        chunks[1],
+    );
+
+    let help = if app.message.is_empty() {
+        String::new()
+    } else {
+        format!("{}  │  ", app.message)
+    };
+    frame.render_widget(
+        Paragraph::new(format!(
+            "{}↑/↓ select  i install  u update  d uninstall  r refresh  q quit",
+            help
+        ))
+        .style(Style::default().fg(Color::Cyan)),
+        chunks[2],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn row(name: &str, installed: &[&str], latest: Option<&str>) -> PluginRow {
+        PluginRow {
+            name: name.to_string(),
+            status: PluginStatus::Active,
+            pinned: Some("1.0.0".to_string()),
+            installed: installed.iter().map(|v| v.to_string()).collect(),
+            latest: latest.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_update_available() {
+        assert!(row("node", &["1.0.0", "1.10.0"], Some("1.11.0")).update_available());
+        assert!(!row("node", &["1.0.0", "1.10.0"], Some("1.9.0")).update_available());
+        assert!(!row("node", &[], Some("1.0.0")).update_available());
+        assert!(!row("node", &["1.0.0"], None).update_available());
+    }
+
+    #[test]
+    fn test_handle_key_actions() {
+        let mut app = App::new(vec![
+            row("go", &[], None),
+            row("node", &["1.0.0", "1.2.0"], Some("1.2.0")),
+        ]);
+
+        assert_eq!(app.handle_key(key('k')), None);
+        assert_eq!(app.selected, 0);
+        assert_eq!(app.handle_key(key('d')), None);
+        assert!(app.confirm.is_none());
+
+        app.handle_key(key('j'));
+        app.handle_key(key('j'));
+        assert_eq!(app.selected, 1);
+        assert_eq!(
+            app.handle_key(key('i')),
+            Some(Action::Install {
+                plugin: "node".to_string(),
+                version: Some("1.0.0".to_string()),
+            })
+        );
+
+        // 卸载需要确认，其他按键取消
+        assert_eq!(app.handle_key(key('d')), None);
+        assert_eq!(app.handle_key(key('n')), None);
+        assert_eq!(app.handle_key(key('d')), None);
+        assert_eq!(
+            app.handle_key(key('y')),
+            Some(Action::Uninstall {
+                plugin: "node".to_string(),
+                version: "1.2.0".to_string(),
+            })
+        );
+        assert_eq!(
+            app.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+    }
+
+    #[test]
+    fn test_log_is_bounded() {
+        let mut app = App::default();
+        for i in 0..MAX_LOG_LINES + 10 {
+            app.log(describe_event(&PlmEvent::DiscoveryFinished { count: i }));
+        }
+        assert_eq!(app.logs.len(), MAX_LOG_LINES);
+        assert!(app.logs[0].ends_with("discovered 10 plugin(s)"));
+    }
+}
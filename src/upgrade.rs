@@ -0,0 +1,408 @@
+//! PLM 版本升级助手
+//!
+//! `plm migrate` 检测旧版本 PLM 留下的配置格式和目录布局，并就地迁移：
+//!
+//! - 配置：按 `migrate` 模块升级到当前格式，原文件轮换为 `plm.json.bak.N`
+//! - 插件存储：旧版把每个版本装在 `plugin_dir/<name>-<version>`，迁移为 `plugin_dir/<name>/<version>`
+//! - 下载缓存：旧版把制品直接放在 `cache_dir` 下，迁移到 `cache_dir/downloads`
+//! - shim：`plugin_dir` 旁边 `shims` 目录中指向旧存储路径的脚本改写为新路径，原文件保存为 `<shim>.bak`
+//!
+//! 迁移只移动和改写，不删除任何文件；目标已存在等无法自动处理的情况列为需要手动处理的事项。
+
+use crate::backup::{backup_path, write_with_backups};
+use crate::config::ProjectConfig;
+use crate::migrate;
+use crate::traits::{ArchiveFormat, PluginError};
+use crate::version::Version;
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 旧版 shim 目录名，位于 `plugin_dir` 的上级目录
+pub const LEGACY_SHIMS_DIR: &str = "shims";
+
+/// 一个迁移动作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UpgradeAction {
+    /// 升级配置格式
+    MigrateConfig {
+        path: PathBuf,
+        from: u32,
+        to: u32,
+        steps: Vec<String>,
+    },
+    /// 把旧布局的插件版本目录移到 `<name>/<version>`
+    MoveStore { from: PathBuf, to: PathBuf },
+    /// 把缓存根目录中的制品移到 `downloads`
+    MoveDownload { from: PathBuf, to: PathBuf },
+    /// 改写 shim 中的旧存储路径
+    RewriteShim {
+        path: PathBuf,
+        replacements: Vec<(String, String)>,
+    },
+}
+
+impl fmt::Display for UpgradeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpgradeAction::MigrateConfig {
+                path,
+                from,
+                to,
+                steps,
+            } => write!(
+                f,
+                "migrate {} from config version {} to {} ({})",
+                path.display(),
+                from,
+                to,
+                steps.join("; ")
+            ),
+            UpgradeAction::MoveStore { from, to } | UpgradeAction::MoveDownload { from, to } => {
+                write!(f, "move {} to {}", from.display(), to.display())
+            }
+            UpgradeAction::RewriteShim { path, replacements } => write!(
+                f,
+                "rewrite {} store path(s) in shim {}",
+                replacements.len(),
+                path.display()
+            ),
+        }
+    }
+}
+
+/// 检测到的迁移计划
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UpgradePlan {
+    pub actions: Vec<UpgradeAction>,
+    /// 需要手动处理的事项
+    pub manual: Vec<String>,
+}
+
+impl UpgradePlan {
+    /// 是否没有任何需要迁移或处理的内容
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty() && self.manual.is_empty()
+    }
+}
+
+/// 检测配置文件和它引用的目录中需要迁移的内容，不做任何修改
+pub async fn plan(config_path: &Path) -> Result<UpgradePlan, PluginError> {
+    let content = tokio::fs::read_to_string(config_path).await.map_err(|e| {
+        PluginError::ConfigError(format!("无法读取 {}: {}", config_path.display(), e))
+    })?;
+    let mut raw: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| PluginError::ConfigError(format!("配置文件不是有效的 JSON: {}", e)))?;
+
+    let mut plan = UpgradePlan::default();
+    let from = migrate::config_version(&raw);
+    let steps = migrate::migrate(&mut raw)?;
+    if !steps.is_empty() {
+        plan.actions.push(UpgradeAction::MigrateConfig {
+            path: config_path.to_path_buf(),
+            from,
+            to: migrate::CURRENT_CONFIG_VERSION,
+            steps,
+        });
+    }
+
+    let config = ProjectConfig::from_json_str(&raw.to_string())?;
+    let plugin_dir = config.global_settings.plugin_path();
+    let moves = plan_store_moves(&plugin_dir, &mut plan).await?;
+    plan_download_moves(&config.global_settings.cache_path(), &mut plan).await?;
+    if let Some(home) = plugin_dir.parent() {
+        plan_shim_rewrites(&home.join(LEGACY_SHIMS_DIR), &moves, &mut plan).await?;
+    }
+    Ok(plan)
+}
+
+/// 执行迁移计划，返回已完成的动作；单个动作失败时停止并返回错误
+pub async fn apply(plan: &UpgradePlan) -> Result<Vec<UpgradeAction>, PluginError> {
+    let mut applied = Vec::new();
+    for action in &plan.actions {
+        match action {
+            UpgradeAction::MigrateConfig { path, .. } => migrate_config(path).await?,
+            UpgradeAction::MoveStore { from, to } | UpgradeAction::MoveDownload { from, to } => {
+                move_path(from, to).await?
+            }
+            UpgradeAction::RewriteShim { path, replacements } => {
+                rewrite_shim(path, replacements).await?
+            }
+        }
+        applied.push(action.clone());
+    }
+    Ok(applied)
+}
+
+/// 旧布局的版本目录名 `<name>-<version>`，版本必须是带 `.` 的版本号
+pub fn parse_legacy_store_name(name: &str) -> Option<(&str, &str)> {
+    let (plugin, version) = name.rsplit_once('-')?;
+    let version_number = version.trim_start_matches('v');
+    let is_version = !plugin.is_empty()
+        && version_number.contains('.')
+        && version_number.starts_with(|c: char| c.is_ascii_digit())
+        && Version::parse(version_number).is_ok();
+    is_version.then_some((plugin, version_number))
+}
+
+async fn plan_store_moves(
+    plugin_dir: &Path,
+    plan: &mut UpgradePlan,
+) -> Result<Vec<(PathBuf, PathBuf)>, PluginError> {
+    let mut moves = Vec::new();
+    for (name, path) in read_dir_sorted(plugin_dir).await? {
+        if !path.is_dir() {
+            continue;
+        }
+        let Some((plugin, version)) = parse_legacy_store_name(&name) else {
+            continue;
+        };
+        let target = plugin_dir.join(plugin).join(version);
+        if target.exists() {
+            plan.manual.push(format!(
+                "{} and {} both exist; keep one of them and remove the other",
+                path.display(),
+                target.display()
+            ));
+            continue;
+        }
+        plan.actions.push(UpgradeAction::MoveStore {
+            from: path.clone(),
+            to: target.clone(),
+        });
+        moves.push((path, target));
+    }
+    Ok(moves)
+}
+
+async fn plan_download_moves(cache_dir: &Path, plan: &mut UpgradePlan) -> Result<(), PluginError> {
+    let downloads = cache_dir.join("downloads");
+    for (name, path) in read_dir_sorted(cache_dir).await? {
+        if !path.is_file() || ArchiveFormat::from_file_name(&name) == ArchiveFormat::Raw {
+            continue;
+        }
+        let target = downloads.join(&name);
+        if target.exists() {
+            plan.manual.push(format!(
+                "{} is already cached as {}; remove the old copy",
+                path.display(),
+                target.display()
+            ));
+            continue;
+        }
+        plan.actions.push(UpgradeAction::MoveDownload {
+            from: path,
+            to: target,
+        });
+    }
+    Ok(())
+}
+
+async fn plan_shim_rewrites(
+    shims_dir: &Path,
+    moves: &[(PathBuf, PathBuf)],
+    plan: &mut UpgradePlan,
+) -> Result<(), PluginError> {
+    for (_, path) in read_dir_sorted(shims_dir).await? {
+        if !path.is_file() || path.extension().is_some_and(|ext| ext == "bak") {
+            continue;
+        }
+        let Ok(content) = tokio::fs::read_to_string(&path).await else {
+            plan.manual.push(format!(
+                "{} is not a text file; check that it does not point into the old plugin store",
+                path.display()
+            ));
+            continue;
+        };
+        let replacements: Vec<(String, String)> = moves
+            .iter()
+            .map(|(from, to)| {
+                (
+                    from.to_string_lossy().into_owned(),
+                    to.to_string_lossy().into_owned(),
+                )
+            })
+            .filter(|(from, _)| content.contains(from.as_str()))
+            .collect();
+        if !replacements.is_empty() {
+            plan.actions
+                .push(UpgradeAction::RewriteShim { path, replacements });
+        }
+    }
+    Ok(())
+}
+
+/// 目录中的条目，按名称排序，目录不存在时为空
+async fn read_dir_sorted(dir: &Path) -> Result<Vec<(String, PathBuf)>, PluginError> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(PluginError::IoError(format!(
+                "读取 {} 失败: {}",
+                dir.display(),
+                e
+            )))
+        }
+    };
+    let mut result = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", dir.display(), e)))?
+    {
+        result.push((
+            entry.file_name().to_string_lossy().into_owned(),
+            entry.path(),
+        ));
+    }
+    result.sort();
+    Ok(result)
+}
+
+async fn migrate_config(path: &Path) -> Result<(), PluginError> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| PluginError::ConfigError(format!("无法读取 {}: {}", path.display(), e)))?;
+    let mut raw: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| PluginError::ConfigError(format!("配置文件不是有效的 JSON: {}", e)))?;
+    migrate::migrate(&mut raw)?;
+    // 迁移结果能被正确解析后才写回文件
+    let config = ProjectConfig::from_json_str(&raw.to_string())?;
+    let migrated = serde_json::to_string_pretty(&raw)
+        .map_err(|e| PluginError::ConfigError(format!("无法序列化配置: {}", e)))?;
+    write_with_backups(
+        path,
+        &migrated,
+        config.global_settings.config_backups.max(1),
+    )
+    .await
+}
+
+async fn move_path(from: &Path, to: &Path) -> Result<(), PluginError> {
+    if let Some(parent) = to.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| PluginError::IoError(format!("创建 {} 失败: {}", parent.display(), e)))?;
+    }
+    tokio::fs::rename(from, to).await.map_err(|e| {
+        PluginError::IoError(format!(
+            "移动 {} 到 {} 失败: {}",
+            from.display(),
+            to.display(),
+            e
+        ))
+    })
+}
+
+async fn rewrite_shim(path: &Path, replacements: &[(String, String)]) -> Result<(), PluginError> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", path.display(), e)))?;
+    let mut backup = path.as_os_str().to_os_string();
+    backup.push(".bak");
+    // copy 会保留可执行权限
+    tokio::fs::copy(path, &backup)
+        .await
+        .map_err(|e| PluginError::IoError(format!("备份 {} 失败: {}", path.display(), e)))?;
+    let rewritten = replacements
+        .iter()
+        .fold(content, |content, (from, to)| content.replace(from, to));
+    tokio::fs::write(path, rewritten)
+        .await
+        .map_err(|e| PluginError::IoError(format!("写入 {} 失败: {}", path.display(), e)))
+}
+
+/// 配置迁移前的文件保存位置
+pub fn config_backup_path(config_path: &Path) -> PathBuf {
+    backup_path(config_path, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_legacy_store_name() {
+        assert_eq!(
+            parse_legacy_store_name("node-18.19.0"),
+            Some(("node", "18.19.0"))
+        );
+        assert_eq!(
+            parse_legacy_store_name("my-tool-v1.2.3"),
+            Some(("my-tool", "1.2.3"))
+        );
+        assert_eq!(parse_legacy_store_name("my-tool"), None);
+        assert_eq!(parse_legacy_store_name("tool-2"), None);
+        assert_eq!(parse_legacy_store_name("-1.0.0"), None);
+    }
+
+    #[tokio::test]
+    async fn test_plan_and_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let plugins = root.join("plm/plugins");
+        let cache = root.join("plm/cache");
+        let shims = root.join("plm/shims");
+        for path in [
+            plugins.join("node-18.19.0/bin"),
+            plugins.join("jq-1.7.1"),
+            plugins.join("jq/1.7.1"),
+            plugins.join("terraform/1.7.0"),
+            cache.join("downloads"),
+            shims.clone(),
+        ] {
+            std::fs::create_dir_all(path).unwrap();
+        }
+        std::fs::write(cache.join("node-v18.19.0-linux-x64.tar.gz"), "archive").unwrap();
+        std::fs::write(cache.join("registry-missing.json"), "{}").unwrap();
+        let old_node = plugins.join("node-18.19.0/bin/node");
+        std::fs::write(
+            shims.join("node"),
+            format!("#!/bin/sh\nexec {} \"$@\"\n", old_node.display()),
+        )
+        .unwrap();
+
+        let config_path = root.join("plm.json");
+        // 版本 1 的配置：没有 config_version，插件是数组且使用 config 字段
+        let mut config = serde_json::to_value(ProjectConfig::default_for_project(
+            "legacy",
+            &root.to_string_lossy(),
+        ))
+        .unwrap();
+        config.as_object_mut().unwrap().remove("config_version");
+        config["global_settings"]["plugin_dir"] = json!(plugins.to_string_lossy());
+        config["global_settings"]["cache_dir"] = json!(cache.to_string_lossy());
+        config["plugins"] =
+            json!([{ "name": "node", "version": "18.19.0", "config": { "mirror": "a" } }]);
+        std::fs::write(&config_path, config.to_string()).unwrap();
+
+        let plan = plan(&config_path).await.unwrap();
+        assert_eq!(plan.actions.len(), 4, "{:#?}", plan.actions);
+        assert!(matches!(
+            plan.actions[0],
+            UpgradeAction::MigrateConfig { from: 1, .. }
+        ));
+        assert_eq!(plan.manual.len(), 1);
+        assert!(plan.manual[0].contains("jq-1.7.1"));
+
+        apply(&plan).await.unwrap();
+        assert!(plugins.join("node/18.19.0/bin").is_dir());
+        assert!(!plugins.join("node-18.19.0").exists());
+        assert!(cache
+            .join("downloads/node-v18.19.0-linux-x64.tar.gz")
+            .is_file());
+        assert!(cache.join("registry-missing.json").is_file());
+        let shim = std::fs::read_to_string(shims.join("node")).unwrap();
+        assert!(shim.contains(&plugins.join("node/18.19.0/bin/node").display().to_string()));
+        assert!(shims.join("node.bak").is_file());
+        assert!(config_backup_path(&config_path).is_file());
+
+        // 迁移完成后再次检测只剩需要手动处理的事项
+        let again = super::plan(&config_path).await.unwrap();
+        assert!(again.actions.is_empty());
+        assert_eq!(again.manual.len(), 1);
+    }
+}
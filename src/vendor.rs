@@ -0,0 +1,292 @@
+//! PLM 离线 vendor 目录
+//!
+//! `plm vendor <dir>` 把配置中固定版本的插件所需的制品下载到一个可移植目录：
+//!
+//! ```text
+//! <dir>/
+//!   index.json     插件版本，以及下载地址 -> 制品文件、SHA-256 的索引
+//!   artifacts/     制品文件
+//! ```
+//!
+//! 把目录复制到无法访问网络的机器后，`plm install/sync --vendor-dir <dir>`
+//! 只从该目录解析制品：按下载地址在索引中查找文件，用索引中的 SHA-256 校验，不访问网络。
+//! 只有通过 PLM 下载器获取制品的插件（声明 `Capability::Vendor`）可以 vendor，
+//! 委托给外部工具（rustup、nvm、系统包管理器等）的插件会被跳过。
+
+use crate::config::ProjectConfig;
+use crate::core::PluginManager;
+use crate::download::{file_name_from_url, sha256_file, verify_checksum};
+use crate::temp::TempFileGuard;
+use crate::traits::{Capability, InstallOptions, PluginError, VersionInfo};
+use crate::version::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// 索引文件名
+pub const INDEX_FILE: &str = "index.json";
+
+/// 制品目录名
+pub const ARTIFACTS_DIR: &str = "artifacts";
+
+/// 下载器使用 vendor 目录的方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VendorMode {
+    /// 只从 vendor 目录解析制品，不访问网络
+    Resolve(PathBuf),
+    /// 正常下载，校验通过的制品同时保存到 vendor 目录
+    Record(PathBuf),
+}
+
+/// vendor 目录中的一个制品
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VendoredArtifact {
+    /// 相对 `artifacts` 目录的文件名
+    pub file: String,
+    pub sha256: String,
+    pub size: u64,
+    pub version: String,
+}
+
+/// vendor 目录索引
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VendorIndex {
+    /// 插件名称 -> vendor 的版本
+    #[serde(default)]
+    pub plugins: BTreeMap<String, String>,
+    /// 下载地址 -> 制品
+    #[serde(default)]
+    pub artifacts: BTreeMap<String, VendoredArtifact>,
+}
+
+impl VendorIndex {
+    /// 读取索引，目录中没有索引文件时返回错误
+    pub async fn load(dir: &Path) -> Result<Self, PluginError> {
+        let path = dir.join(INDEX_FILE);
+        let content = tokio::fs::read_to_string(&path).await.map_err(|e| {
+            PluginError::NotFound(format!(
+                "{} 不是 vendor 目录（无法读取 {}: {}）",
+                dir.display(),
+                INDEX_FILE,
+                e
+            ))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            PluginError::ValidationError(format!("无效的 vendor 索引 {}: {}", path.display(), e))
+        })
+    }
+
+    /// 读取索引，没有索引文件时返回空索引
+    pub async fn load_or_default(dir: &Path) -> Result<Self, PluginError> {
+        if tokio::fs::metadata(dir.join(INDEX_FILE)).await.is_err() {
+            return Ok(Self::default());
+        }
+        Self::load(dir).await
+    }
+
+    /// 写入索引
+    pub async fn save(&self, dir: &Path) -> Result<(), PluginError> {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| PluginError::IoError(format!("创建 {} 失败: {}", dir.display(), e)))?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| PluginError::IoError(format!("序列化 vendor 索引失败: {}", e)))?;
+        tokio::fs::write(dir.join(INDEX_FILE), content)
+            .await
+            .map_err(|e| PluginError::IoError(format!("写入 vendor 索引失败: {}", e)))
+    }
+
+    /// 按版本的下载地址和镜像地址查找制品
+    pub fn find(&self, info: &VersionInfo) -> Option<&VendoredArtifact> {
+        info.urls().find_map(|url| self.artifacts.get(url))
+    }
+}
+
+/// 把已下载并校验通过的制品复制到 vendor 目录并记入索引
+pub async fn record_artifact(
+    dir: &Path,
+    info: &VersionInfo,
+    path: &Path,
+) -> Result<VendoredArtifact, PluginError> {
+    let artifacts = dir.join(ARTIFACTS_DIR);
+    tokio::fs::create_dir_all(&artifacts)
+        .await
+        .map_err(|e| PluginError::IoError(format!("创建 {} 失败: {}", artifacts.display(), e)))?;
+
+    // 不同插件的制品可能同名，文件名带上版本
+    let file = format!(
+        "{}-{}",
+        info.version,
+        file_name_from_url(&info.download_url)?
+    );
+    let target = artifacts.join(&file);
+    let staging = TempFileGuard::sibling(&target, "part");
+    tokio::fs::copy(path, staging.path())
+        .await
+        .map_err(|e| PluginError::IoError(format!("复制 {} 失败: {}", path.display(), e)))?;
+    staging.persist(&target).await?;
+
+    let artifact = VendoredArtifact {
+        file,
+        sha256: sha256_file(&target).await?,
+        size: tokio::fs::metadata(&target)
+            .await
+            .map_err(|e| PluginError::IoError(format!("读取 {} 失败: {}", target.display(), e)))?
+            .len(),
+        version: info.version.clone(),
+    };
+    let mut index = VendorIndex::load_or_default(dir).await?;
+    index
+        .artifacts
+        .insert(info.download_url.clone(), artifact.clone());
+    index.save(dir).await?;
+    Ok(artifact)
+}
+
+/// 在 vendor 目录中查找版本的制品，校验 SHA-256 后返回文件路径和索引记录
+pub async fn resolve_artifact(
+    dir: &Path,
+    info: &VersionInfo,
+) -> Result<(PathBuf, VendoredArtifact), PluginError> {
+    let index = VendorIndex::load(dir).await?;
+    let artifact = index.find(info).cloned().ok_or_else(|| {
+        PluginError::NotFound(format!(
+            "vendor 目录 {} 中没有 {} 的制品（{}）",
+            dir.display(),
+            info.version,
+            info.download_url
+        ))
+    })?;
+    let path = dir.join(ARTIFACTS_DIR).join(&artifact.file);
+    verify_checksum(&path, &artifact.sha256).await?;
+    Ok((path, artifact))
+}
+
+/// `vendor` 的结果
+#[derive(Debug, Clone, Default)]
+pub struct VendorReport {
+    /// 已 vendor 的插件和版本
+    pub vendored: Vec<(String, String)>,
+    /// 跳过的插件和原因
+    pub skipped: Vec<(String, String)>,
+    /// 失败的插件和错误信息
+    pub failed: Vec<(String, String)>,
+}
+
+impl VendorReport {
+    /// 是否全部成功（跳过的插件不算失败）
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// 下载配置中启用且固定了版本的插件的制品到 `dir`
+///
+/// 插件被安装到 `dir` 下的临时插件目录，不影响本机已安装的版本；安装完成后临时目录被删除。
+/// 范围约束或未指定版本的插件会被跳过：离线环境需要的是确定的版本。
+pub async fn vendor(config: &ProjectConfig, dir: &Path) -> Result<VendorReport, PluginError> {
+    let staging = TempFileGuard::new(dir.join(".plugins"));
+    staging.clear().await?;
+
+    let mut config = config.clone();
+    config.global_settings.plugin_dir = staging.path().to_string_lossy().into_owned();
+    config.global_settings.vendor_dir = None;
+    config.global_settings.vendor_record_dir = Some(dir.to_path_buf());
+    let mut manager = PluginManager::from_project_config(config.clone()).await?;
+    manager.initialize().await?;
+
+    let mut names: Vec<&String> = config.plugins.keys().collect();
+    names.sort();
+    let options = InstallOptions::new().yes().quiet();
+    let mut report = VendorReport::default();
+    for name in names {
+        let plugin_config = &config.plugins[name];
+        if !plugin_config.enabled {
+            continue;
+        }
+        let Some(version) = plugin_config
+            .get_version()
+            .filter(|version| Version::parse(version.trim_start_matches('v')).is_ok())
+        else {
+            report.skipped.push((
+                name.clone(),
+                "version is not pinned to an exact release".to_string(),
+            ));
+            continue;
+        };
+        let plugin = match manager.get_plugin(name.as_str()).await {
+            Ok(plugin) => plugin,
+            Err(_) => {
+                report
+                    .skipped
+                    .push((name.clone(), "no registered implementation".to_string()));
+                continue;
+            }
+        };
+        if !plugin.supports(Capability::Vendor) {
+            report.skipped.push((
+                name.clone(),
+                "installs through an external tool and cannot be vendored".to_string(),
+            ));
+            continue;
+        }
+        match plugin.install(version, &options).await {
+            Ok(_) => report.vendored.push((name.clone(), version.to_string())),
+            Err(e) => report.failed.push((name.clone(), e.to_string())),
+        }
+    }
+
+    let _ = manager.shutdown().await;
+    let mut index = VendorIndex::load_or_default(dir).await?;
+    for (name, version) in &report.vendored {
+        index.plugins.insert(name.clone(), version.clone());
+    }
+    index.save(dir).await?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_and_resolve_artifact() {
+        let dir = tempfile::tempdir().unwrap();
+        let vendor_dir = dir.path().join("vendor");
+        let downloaded = dir.path().join("tool.tar.gz");
+        std::fs::write(&downloaded, "artifact").unwrap();
+
+        let info = VersionInfo::new("1.2.0", "linux-x64", "https://example.com/tool.tar.gz");
+        let recorded = record_artifact(&vendor_dir, &info, &downloaded)
+            .await
+            .unwrap();
+        assert_eq!(recorded.file, "1.2.0-tool.tar.gz");
+        assert_eq!(recorded.size, 8);
+
+        // 镜像地址也能命中索引
+        let mirrored = VersionInfo::new("1.2.0", "linux-x64", "https://mirror.example.com/a.tgz")
+            .with_mirror_url("https://example.com/tool.tar.gz");
+        let (path, artifact) = resolve_artifact(&vendor_dir, &mirrored).await.unwrap();
+        assert_eq!(
+            path,
+            vendor_dir.join(ARTIFACTS_DIR).join("1.2.0-tool.tar.gz")
+        );
+        assert_eq!(artifact, recorded);
+
+        let missing = VersionInfo::new("1.3.0", "linux-x64", "https://example.com/other.tar.gz");
+        assert!(matches!(
+            resolve_artifact(&vendor_dir, &missing).await,
+            Err(PluginError::NotFound(_))
+        ));
+
+        std::fs::write(&path, "tampered").unwrap();
+        assert!(matches!(
+            resolve_artifact(&vendor_dir, &info).await,
+            Err(PluginError::ChecksumMismatch { .. })
+        ));
+        assert!(matches!(
+            VendorIndex::load(dir.path()).await,
+            Err(PluginError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,363 @@
+//! PLM 版本与版本约束
+//!
+//! 提供轻量的语义化版本解析、版本约束（`>=1.2, <2`、`^1.2`、`~1.2.3` 等）匹配，
+//! 以及判断两个约束是否存在交集，用于插件依赖冲突检测。
+
+use crate::traits::PluginError;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// 语义化版本
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// 创建版本
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// 解析版本字符串，允许前缀 `v` 和省略次版本/补丁版本
+    pub fn parse(input: &str) -> Result<Self, PluginError> {
+        let trimmed = input.trim().trim_start_matches('v');
+        // 忽略预发布和构建元数据后缀
+        let core = trimmed.split(['-', '+']).next().unwrap_or_default();
+
+        let mut parts = [0u64; 3];
+        for (i, part) in core.split('.').enumerate() {
+            if i >= 3 {
+                return Err(invalid_version(input));
+            }
+            parts[i] = part.parse().map_err(|_| invalid_version(input))?;
+        }
+
+        Ok(Self::new(parts[0], parts[1], parts[2]))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for Version {
+    type Err = PluginError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn invalid_version(input: &str) -> PluginError {
+    PluginError::ValidationError(format!("Invalid version: '{}'", input))
+}
+
+/// 约束运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Caret,
+    Tilde,
+}
+
+/// 单个比较条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    version: Version,
+    /// 原始字符串中写出的版本字段数，用于 `~1` 这类部分版本
+    precision: usize,
+}
+
+/// 区间端点：版本以及是否包含该版本
+type Bound = Option<(Version, bool)>;
+
+impl Comparator {
+    fn parse(input: &str) -> Result<Self, PluginError> {
+        let input = input.trim();
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("^", Op::Caret),
+            ("~", Op::Tilde),
+        ]
+        .iter()
+        .find_map(|(prefix, op)| input.strip_prefix(prefix).map(|rest| (*op, rest)))
+        .unwrap_or((Op::Exact, input));
+
+        let rest = rest.trim();
+        let precision = rest
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .count();
+
+        Ok(Self {
+            op,
+            version: Version::parse(rest)?,
+            precision,
+        })
+    }
+
+    /// 转换为区间 (下界, 上界)
+    fn bounds(&self) -> (Bound, Bound) {
+        let v = self.version.clone();
+        match self.op {
+            Op::Exact => (Some((v.clone(), true)), Some((v, true))),
+            Op::Greater => (Some((v, false)), None),
+            Op::GreaterEq => (Some((v, true)), None),
+            Op::Less => (None, Some((v, false))),
+            Op::LessEq => (None, Some((v, true))),
+            Op::Caret => {
+                let upper = if v.major > 0 {
+                    Version::new(v.major + 1, 0, 0)
+                } else if v.minor > 0 {
+                    Version::new(0, v.minor + 1, 0)
+                } else {
+                    Version::new(0, 0, v.patch + 1)
+                };
+                (Some((v, true)), Some((upper, false)))
+            }
+            Op::Tilde => {
+                let upper = if self.precision >= 2 {
+                    Version::new(v.major, v.minor + 1, 0)
+                } else {
+                    Version::new(v.major + 1, 0, 0)
+                };
+                (Some((v, true)), Some((upper, false)))
+            }
+        }
+    }
+}
+
+/// 版本约束，由逗号分隔的多个比较条件组成（全部满足才匹配）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    raw: String,
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    /// 匹配任意版本的约束
+    pub fn any() -> Self {
+        Self {
+            raw: "*".to_string(),
+            comparators: Vec::new(),
+        }
+    }
+
+    /// 解析版本约束
+    pub fn parse(input: &str) -> Result<Self, PluginError> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() || trimmed == "*" {
+            return Ok(Self::any());
+        }
+
+        let comparators = trimmed
+            .split(',')
+            .map(Comparator::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            raw: trimmed.to_string(),
+            comparators,
+        })
+    }
+
+    /// 判断版本是否满足约束
+    pub fn matches(&self, version: &Version) -> bool {
+        let (lower, upper) = self.bounds();
+        satisfies_lower(&lower, version) && satisfies_upper(&upper, version)
+    }
+
+    /// 判断两个约束是否存在同时满足的版本
+    pub fn intersects(&self, other: &VersionReq) -> bool {
+        let (l1, u1) = self.bounds();
+        let (l2, u2) = other.bounds();
+        let lower = tighter_lower(l1, l2);
+        let upper = tighter_upper(u1, u2);
+
+        match (lower, upper) {
+            (Some((lv, linc)), Some((uv, uinc))) => match lv.cmp(&uv) {
+                Ordering::Less => true,
+                Ordering::Equal => linc && uinc,
+                Ordering::Greater => false,
+            },
+            _ => true,
+        }
+    }
+
+    fn bounds(&self) -> (Bound, Bound) {
+        self.comparators
+            .iter()
+            .map(Comparator::bounds)
+            .fold((None, None), |(lower, upper), (l, u)| {
+                (tighter_lower(lower, l), tighter_upper(upper, u))
+            })
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = PluginError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn tighter_lower(a: Bound, b: Bound) -> Bound {
+    match (a, b) {
+        (None, other) | (other, None) => other,
+        (Some((av, ainc)), Some((bv, binc))) => match av.cmp(&bv) {
+            Ordering::Greater => Some((av, ainc)),
+            Ordering::Less => Some((bv, binc)),
+            Ordering::Equal => Some((av, ainc && binc)),
+        },
+    }
+}
+
+fn tighter_upper(a: Bound, b: Bound) -> Bound {
+    match (a, b) {
+        (None, other) | (other, None) => other,
+        (Some((av, ainc)), Some((bv, binc))) => match av.cmp(&bv) {
+            Ordering::Less => Some((av, ainc)),
+            Ordering::Greater => Some((bv, binc)),
+            Ordering::Equal => Some((av, ainc && binc)),
+        },
+    }
+}
+
+fn satisfies_lower(bound: &Bound, version: &Version) -> bool {
+    match bound {
+        None => true,
+        Some((v, true)) => version >= v,
+        Some((v, false)) => version > v,
+    }
+}
+
+fn satisfies_upper(bound: &Bound, version: &Version) -> bool {
+    match bound {
+        None => true,
+        Some((v, true)) => version <= v,
+        Some((v, false)) => version < v,
+    }
+}
+
+/// 插件依赖声明，如 `openssl`、`openssl >=3.0`、`python@^3.10`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencySpec {
+    pub name: String,
+    pub req: VersionReq,
+}
+
+impl DependencySpec {
+    /// 解析依赖声明
+    pub fn parse(input: &str) -> Result<Self, PluginError> {
+        let input = input.trim();
+        let split = input
+            .find(|c: char| c.is_whitespace() || "@=<>^~".contains(c))
+            .unwrap_or(input.len());
+        let (name, rest) = input.split_at(split);
+
+        if name.is_empty() {
+            return Err(PluginError::ValidationError(format!(
+                "Invalid dependency: '{}'",
+                input
+            )));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            req: VersionReq::parse(rest.trim().trim_start_matches('@'))?,
+        })
+    }
+}
+
+impl fmt::Display for DependencySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_parse() {
+        assert_eq!(Version::parse("v1.2.3").unwrap(), Version::new(1, 2, 3));
+        assert_eq!(Version::parse("18").unwrap(), Version::new(18, 0, 0));
+        assert_eq!(
+            Version::parse("2.0.0-beta.1").unwrap(),
+            Version::new(2, 0, 0)
+        );
+        assert!(Version::parse("latest").is_err());
+    }
+
+    #[test]
+    fn test_req_matches() {
+        let req = VersionReq::parse(">=1.2, <2").unwrap();
+        assert!(req.matches(&Version::new(1, 5, 0)));
+        assert!(!req.matches(&Version::new(2, 0, 0)));
+
+        let caret = VersionReq::parse("^0.3.1").unwrap();
+        assert!(caret.matches(&Version::new(0, 3, 9)));
+        assert!(!caret.matches(&Version::new(0, 4, 0)));
+
+        let tilde = VersionReq::parse("~1.2").unwrap();
+        assert!(tilde.matches(&Version::new(1, 2, 7)));
+        assert!(!tilde.matches(&Version::new(1, 3, 0)));
+    }
+
+    #[test]
+    fn test_req_intersects() {
+        let a = VersionReq::parse(">=3.0").unwrap();
+        let b = VersionReq::parse("<3.0").unwrap();
+        let c = VersionReq::parse("^3.1").unwrap();
+        assert!(!a.intersects(&b));
+        assert!(a.intersects(&c));
+        assert!(VersionReq::any().intersects(&b));
+        assert!(!VersionReq::parse("=1.0.0")
+            .unwrap()
+            .intersects(&VersionReq::parse(">1.0.0").unwrap()));
+    }
+
+    #[test]
+    fn test_dependency_parse() {
+        let dep = DependencySpec::parse("openssl >=3.0").unwrap();
+        assert_eq!(dep.name, "openssl");
+        assert!(dep.req.matches(&Version::new(3, 1, 0)));
+
+        let dep = DependencySpec::parse("python@^3.10").unwrap();
+        assert_eq!(dep.name, "python");
+        assert!(!dep.req.matches(&Version::new(4, 0, 0)));
+
+        let dep = DependencySpec::parse("git").unwrap();
+        assert_eq!(dep.req, VersionReq::any());
+    }
+}
@@ -0,0 +1,82 @@
+//! CLI 输出快照测试
+//!
+//! 以 `PLM_DETERMINISTIC=1` 运行 `plm` 并与 `tests/snapshots/` 下的文件比较，
+//! 设置 `PLM_UPDATE_SNAPSHOTS=1` 时改为重写快照文件。
+
+use plm::config::{PluginSource, PluginSourceType};
+use plm::{PluginConfig, ProjectConfig};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn snapshot_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.txt", name))
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = snapshot_path(name);
+    if std::env::var_os("PLM_UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("missing snapshot {}: {}", path.display(), e));
+    assert_eq!(
+        expected, actual,
+        "snapshot {} differs, rerun with PLM_UPDATE_SNAPSHOTS=1 to update it",
+        name
+    );
+}
+
+/// 在临时项目中运行 plm，返回标准输出
+async fn run_plm(project: &Path, args: &[&str]) -> String {
+    let root = project.to_string_lossy();
+    let mut config = ProjectConfig::default_for_project("snapshot", &root);
+
+    let mut terraform = PluginConfig::new("terraform");
+    terraform.enabled = true;
+    terraform.set_source(PluginSource {
+        source_type: PluginSourceType::Builtin,
+        url: "terraform".to_string(),
+        branch: None,
+        tag: None,
+        token: None,
+        auth: None,
+        keyless: None,
+        mirrors: Vec::new(),
+    });
+    config.add_plugin(terraform);
+    config.add_plugin(PluginConfig::new("jq"));
+
+    let config_path = project.join("plm.json");
+    config
+        .save_to_file(&config_path.to_string_lossy())
+        .await
+        .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_plm"))
+        .arg("--config")
+        .arg(&config_path)
+        .args(args)
+        .current_dir(project)
+        .env("PLM_DETERMINISTIC", "1")
+        .env_remove("HTTP_PROXY")
+        .env_remove("HTTPS_PROXY")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "plm {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[tokio::test]
+async fn test_list_snapshot() {
+    let project = tempfile::tempdir().unwrap();
+    let stdout = run_plm(project.path(), &["list"]).await;
+    assert_snapshot("list", &stdout);
+}
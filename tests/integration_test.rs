@@ -1,10 +1,13 @@
 //! PLM 集成测试
 
 use async_trait::async_trait;
-use plm::config::PluginSource;
-use plm::traits::{InstallOptions, Plugin, PluginError, PluginMetadata, PluginStatus, VersionInfo};
+use plm::config::{PluginSource, PluginSourceType};
+use plm::traits::{
+    ArchiveFormat, Capability, ExecOutcome, InstallOptions, LauncherEntry, LifecycleState, Plugin,
+    PluginError, PluginFactory, PluginLoader, PluginMetadata, PluginStatus, VersionInfo,
+};
 use plm::{PluginConfig, PluginManager, ProjectConfig};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 /// 测试用的模拟插件
@@ -12,6 +15,14 @@ pub struct MockPlugin {
     metadata: PluginMetadata,
     status: PluginStatus,
     installed_versions: Vec<String>,
+    shutdown_delay: Option<std::time::Duration>,
+    /// 初始化或关闭时返回错误
+    fail_initialize: bool,
+    fail_shutdown: bool,
+    launchers: Vec<LauncherEntry>,
+    install_root: Option<std::path::PathBuf>,
+    cross_install: bool,
+    settings: std::sync::Mutex<HashMap<String, String>>,
 }
 
 impl MockPlugin {
@@ -26,13 +37,20 @@ impl MockPlugin {
             supported_platforms: vec!["linux".to_string(), "macos".to_string()],
             tags: vec!["test".to_string()],
             dependencies: vec![],
-            min_plm_version: Some("0.1.0".to_string()),
+            min_plm_version: Some("0.0.1".to_string()),
         };
 
         Self {
             metadata,
             status: PluginStatus::Inactive,
             installed_versions: vec!["1.0.0".to_string()],
+            shutdown_delay: None,
+            fail_initialize: false,
+            fail_shutdown: false,
+            launchers: Vec::new(),
+            install_root: None,
+            cross_install: false,
+            settings: std::sync::Mutex::new(HashMap::new()),
         }
     }
 }
@@ -48,11 +66,26 @@ impl Plugin for MockPlugin {
     }
 
     async fn initialize(&mut self) -> Result<(), PluginError> {
+        if self.fail_initialize {
+            return Err(PluginError::PluginError(format!(
+                "{} 初始化失败",
+                self.metadata.name
+            )));
+        }
         self.status = PluginStatus::Active;
         Ok(())
     }
 
     async fn shutdown(&mut self) -> Result<(), PluginError> {
+        if let Some(delay) = self.shutdown_delay {
+            tokio::time::sleep(delay).await;
+        }
+        if self.fail_shutdown {
+            return Err(PluginError::PluginError(format!(
+                "{} 关闭失败",
+                self.metadata.name
+            )));
+        }
         self.status = PluginStatus::Inactive;
         Ok(())
     }
@@ -60,9 +93,18 @@ impl Plugin for MockPlugin {
     async fn install(
         &self,
         version: &str,
-        _options: &InstallOptions,
+        options: &InstallOptions,
     ) -> Result<String, PluginError> {
-        Ok(format!("/tmp/test-{}-{}", self.metadata.name, version))
+        if let Some(dir) = &options.install_dir {
+            return Ok(std::path::Path::new(dir)
+                .join(version)
+                .to_string_lossy()
+                .into_owned());
+        }
+        match &self.install_root {
+            Some(root) => Ok(root.join(version).to_string_lossy().into_owned()),
+            None => Ok(format!("/tmp/test-{}-{}", self.metadata.name, version)),
+        }
     }
 
     async fn uninstall(&self, _version: &str) -> Result<(), PluginError> {
@@ -84,6 +126,14 @@ impl Plugin for MockPlugin {
         Ok(self.installed_versions.contains(&version.to_string()))
     }
 
+    async fn launcher_entries(
+        &self,
+        _version: &str,
+        _install_path: &std::path::Path,
+    ) -> Result<Vec<LauncherEntry>, PluginError> {
+        Ok(self.launchers.clone())
+    }
+
     async fn get_latest_version(&self) -> Result<VersionInfo, PluginError> {
         Ok(VersionInfo::new(
             "1.1.0",
@@ -110,10 +160,11 @@ impl Plugin for MockPlugin {
     }
 
     async fn get_config(&self) -> Result<HashMap<String, String>, PluginError> {
-        Ok(HashMap::new())
+        Ok(self.settings.lock().unwrap().clone())
     }
 
-    async fn set_config(&self, _config: HashMap<String, String>) -> Result<(), PluginError> {
+    async fn set_config(&self, config: HashMap<String, String>) -> Result<(), PluginError> {
+        *self.settings.lock().unwrap() = config;
         Ok(())
     }
 
@@ -135,6 +186,7 @@ impl Plugin for MockPlugin {
 
     fn supports_feature(&self, feature: &str) -> bool {
         matches!(feature, "install" | "uninstall" | "update" | "config")
+            || (self.cross_install && feature == plm::target::CROSS_INSTALL_FEATURE)
     }
 }
 
@@ -151,9 +203,9 @@ async fn test_plugin_registration_and_initialization() {
     let mut manager = PluginManager::from_project_config(config).await.unwrap();
 
     // 注册测试插件
-    let mock_plugin = Arc::new(MockPlugin::new("test-node"));
+    let mock_plugin = Box::new(MockPlugin::new("test-node"));
     manager
-        .register_plugin_for_test("test-node".to_string(), mock_plugin)
+        .register_plugin("test-node".to_string(), mock_plugin)
         .await
         .unwrap();
 
@@ -166,15 +218,25 @@ async fn test_plugin_registration_and_initialization() {
     assert!(plugins.contains(&"test-node".to_string()));
 }
 
+#[tokio::test]
+async fn test_default_exec_command_returns_captured_output() {
+    // 默认实现不直接打印，由调用方输出 execute_command 的结果
+    let plugin = MockPlugin::new("test-node");
+    assert_eq!(
+        plugin.exec_command("status", &["-v"]).await.unwrap(),
+        ExecOutcome::Captured("执行命令: status [\"-v\"]".to_string())
+    );
+}
+
 #[tokio::test]
 async fn test_plugin_installation() {
     let config = ProjectConfig::default_for_project("test-project", ".");
     let mut manager = PluginManager::from_project_config(config).await.unwrap();
 
     // 注册和初始化
-    let mock_plugin = Arc::new(MockPlugin::new("test-python"));
+    let mock_plugin = Box::new(MockPlugin::new("test-python"));
     manager
-        .register_plugin_for_test("test-python".to_string(), mock_plugin)
+        .register_plugin("test-python".to_string(), mock_plugin)
         .await
         .unwrap();
     manager.initialize().await.unwrap();
@@ -186,9 +248,15 @@ async fn test_plugin_installation() {
         .await;
     assert!(result.is_ok());
 
-    let install_path = result.unwrap();
+    let install_result = result.unwrap();
+    let install_path = install_result.to_string();
     assert!(install_path.contains("test-python"));
     assert!(install_path.contains("1.0.0"));
+    assert_eq!(install_result.plugin, "test-python");
+    assert_eq!(install_result.version, "1.0.0");
+    // MockPlugin 预装了 1.0.0
+    assert!(install_result.was_cached);
+    assert!(install_result.warnings.is_empty());
 }
 
 #[tokio::test]
@@ -199,9 +267,9 @@ async fn test_plugin_validation() {
     // 注册多个测试插件
     let plugins = vec!["test-go", "test-rust", "test-java"];
     for plugin_name in &plugins {
-        let mock_plugin = Arc::new(MockPlugin::new(plugin_name));
+        let mock_plugin = Box::new(MockPlugin::new(plugin_name));
         manager
-            .register_plugin_for_test(plugin_name.to_string(), mock_plugin)
+            .register_plugin(plugin_name.to_string(), mock_plugin)
             .await
             .unwrap();
     }
@@ -252,9 +320,9 @@ async fn test_plugin_discovery() {
     // 注册一些插件
     let plugins = vec!["discoverable-1", "discoverable-2"];
     for plugin_name in &plugins {
-        let mock_plugin = Arc::new(MockPlugin::new(plugin_name));
+        let mock_plugin = Box::new(MockPlugin::new(plugin_name));
         manager
-            .register_plugin_for_test(plugin_name.to_string(), mock_plugin)
+            .register_plugin(plugin_name.to_string(), mock_plugin)
             .await
             .unwrap();
     }
@@ -305,9 +373,9 @@ async fn test_plugin_lifecycle() {
     let mut manager = PluginManager::from_project_config(config).await.unwrap();
 
     // 注册插件
-    let mock_plugin = Arc::new(MockPlugin::new("lifecycle-test"));
+    let mock_plugin = Box::new(MockPlugin::new("lifecycle-test"));
     manager
-        .register_plugin_for_test("lifecycle-test".to_string(), mock_plugin)
+        .register_plugin("lifecycle-test".to_string(), mock_plugin)
         .await
         .unwrap();
 
@@ -353,3 +421,1819 @@ async fn test_error_handling() {
     let result = manager.get_plugin("non-existent-plugin").await;
     assert!(result.is_err());
 }
+
+#[tokio::test]
+async fn test_dependency_conflict_detection() {
+    let config = ProjectConfig::default_for_project("test-conflicts", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+
+    let mut legacy = MockPlugin::new("legacy-tool");
+    legacy.metadata.dependencies = vec!["openssl <3.0".to_string()];
+    let mut modern = MockPlugin::new("modern-tool");
+    modern.metadata.dependencies = vec!["openssl >=3.0".to_string()];
+
+    manager
+        .register_plugin("legacy-tool".to_string(), Box::new(legacy))
+        .await
+        .unwrap();
+    manager
+        .register_plugin("modern-tool".to_string(), Box::new(modern))
+        .await
+        .unwrap();
+
+    // 验证时报告冲突
+    match manager.validate_all_plugins().await {
+        Err(PluginError::ConflictError {
+            dependency,
+            constraints,
+        }) => {
+            assert_eq!(dependency, "openssl");
+            assert_eq!(constraints.len(), 2);
+            assert!(constraints[0].contains("legacy-tool"));
+            assert!(constraints[1].contains("modern-tool"));
+        }
+        other => panic!("expected conflict error, got {:?}", other),
+    }
+
+    // 安装冲突插件时拒绝
+    let options = InstallOptions::new();
+    let result = manager
+        .install_plugin("modern-tool", Some("1.0.0"), &options)
+        .await;
+    assert!(matches!(result, Err(PluginError::ConflictError { .. })));
+
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_watch_config_changes() {
+    let config = ProjectConfig::default_for_project("test-watch", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+
+    let mut rx = manager.watch_config();
+    assert!(!rx.has_changed().unwrap());
+
+    let mut plugin_config = PluginConfig::new("watched");
+    plugin_config.set_version("1.2.3");
+    manager.add_plugin_config(plugin_config);
+
+    rx.changed().await.unwrap();
+    assert_eq!(
+        rx.borrow_and_update()
+            .get_plugin("watched")
+            .and_then(|p| p.get_version()),
+        Some("1.2.3")
+    );
+
+    manager.remove_plugin_config("watched");
+    rx.changed().await.unwrap();
+    assert!(rx.borrow().get_plugin("watched").is_none());
+}
+
+#[test]
+fn test_public_types_serde_roundtrip() {
+    let status = PluginStatus::Error("boom".to_string());
+    let json = serde_json::to_string(&status).unwrap();
+    assert_eq!(serde_json::from_str::<PluginStatus>(&json).unwrap(), status);
+    assert_eq!(PluginStatus::default(), PluginStatus::Inactive);
+
+    let options = InstallOptions::new().force().quiet();
+    let json = serde_json::to_value(&options).unwrap();
+    assert_eq!(
+        serde_json::from_value::<InstallOptions>(json).unwrap(),
+        options
+    );
+
+    // 缺省字段使用默认值
+    let partial: InstallOptions = serde_json::from_str(r#"{"yes": true}"#).unwrap();
+    assert_eq!(partial, InstallOptions::new().yes());
+}
+
+#[tokio::test]
+async fn test_plugin_id_validation() {
+    let config = ProjectConfig::default_for_project("test-ids", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+
+    // 非法标识在注册时即被拒绝
+    let result = manager
+        .register_plugin("Bad Name", Box::new(MockPlugin::new("bad")))
+        .await;
+    assert!(matches!(result, Err(PluginError::ValidationError(_))));
+
+    // 作用域和变体不影响名称匹配，名称不一致时验证失败
+    manager
+        .register_plugin("tools/node:lts", Box::new(MockPlugin::new("node")))
+        .await
+        .unwrap();
+    manager
+        .register_plugin("python", Box::new(MockPlugin::new("python3")))
+        .await
+        .unwrap();
+
+    let summary = manager.validate_all_plugins().await.unwrap();
+    assert_eq!(summary.valid_plugins, 1);
+    assert_eq!(summary.invalid_plugins, 1);
+    assert!(manager.get_plugin("tools/node:lts").await.is_ok());
+
+    manager.shutdown().await.unwrap();
+}
+
+#[test]
+fn test_plugin_metadata_builder() {
+    let metadata = PluginMetadata::builder("node")
+        .version("18.17.0")
+        .description("Node.js runtime")
+        .author("PLM Team")
+        .homepage("https://nodejs.org")
+        .platform("Darwin")
+        .platform("osx")
+        .platform("win32")
+        .dependency("openssl >=3.0")
+        .build()
+        .unwrap();
+    assert_eq!(metadata.supported_platforms, vec!["macos", "windows"]);
+
+    // 缺少必填字段
+    let missing = PluginMetadata::builder("node").version("1.0.0").build();
+    assert!(matches!(missing, Err(PluginError::ValidationError(_))));
+
+    // 非法版本、URL 和平台
+    let base = PluginMetadata::builder("node")
+        .description("Node.js runtime")
+        .author("PLM Team");
+    assert!(base.clone().version("latest").build().is_err());
+    assert!(base
+        .clone()
+        .version("1.0.0")
+        .repository("not a url")
+        .build()
+        .is_err());
+    assert!(base.version("1.0.0").platform("beos").build().is_err());
+}
+
+#[test]
+fn test_version_info_extended_fields() {
+    // 旧格式的版本信息仍可解析
+    let legacy: VersionInfo = serde_json::from_value(serde_json::json!({
+        "version": "1.21.0",
+        "platform": "linux",
+        "download_url": "https://go.dev/dl/go1.21.0.linux-amd64.tar.gz",
+        "checksum": null,
+        "release_date": null,
+        "prerelease": false
+    }))
+    .unwrap();
+    assert_eq!(legacy.size, None);
+    assert!(legacy.mirror_urls.is_empty());
+    assert_eq!(legacy.archive_format(), ArchiveFormat::TarGz);
+
+    let info = VersionInfo::new(
+        "1.21.0",
+        "windows",
+        "https://go.dev/dl/go1.21.0.windows-amd64.zip",
+    )
+    .with_size(1024)
+    .with_mirror_url("https://mirrors.example.com/go/go1.21.0.windows-amd64.zip")
+    .with_signature("https://go.dev/dl/go1.21.0.windows-amd64.zip.sig")
+    .with_additional_signature("https://go.dev/dl/go1.21.0.windows-amd64.zip.minisig")
+    .with_entry_point("go/bin/go.exe");
+    assert_eq!(info.archive_format(), ArchiveFormat::Zip);
+    assert_eq!(info.urls().count(), 2);
+    assert_eq!(info.all_signatures().count(), 2);
+
+    let roundtrip: VersionInfo =
+        serde_json::from_value(serde_json::to_value(&info).unwrap()).unwrap();
+    assert_eq!(roundtrip, info);
+}
+
+#[tokio::test]
+async fn test_version_matrix() {
+    let config = ProjectConfig::default_for_project("test-versions", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    manager
+        .register_plugin("test-versions", Box::new(MockPlugin::new("test-versions")))
+        .await
+        .unwrap();
+
+    let matrix = manager.version_matrix("test-versions").await.unwrap();
+    let versions: Vec<&str> = matrix.iter().map(|e| e.version.as_str()).collect();
+    assert_eq!(versions, vec!["1.1.0", "1.0.0"]);
+    assert!(!matrix[0].installed);
+    assert!(matrix[1].installed);
+    assert!(matrix.iter().all(|e| !e.yanked && !e.prerelease));
+
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_with_config_mut() {
+    let config = ProjectConfig::default_for_project("test-config-mut", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    assert!(!manager.is_dirty());
+
+    let mut plugin_config = PluginConfig::new("node");
+    plugin_config.set_version("18.17.0");
+    let count = manager
+        .with_config_mut(|config| {
+            config.add_plugin(plugin_config);
+            config.plugins.len()
+        })
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert!(manager.is_dirty());
+    assert!(manager.get_plugin_config("node").is_some());
+
+    // 校验失败时修改被丢弃
+    let result = manager
+        .with_config_mut(|config| {
+            config.project_name.clear();
+            config.remove_plugin("node");
+        })
+        .await;
+    assert!(matches!(result, Err(PluginError::ConfigError(_))));
+    assert_eq!(manager.get_config().project_name, "test-config-mut");
+    assert!(manager.get_plugin_config("node").is_some());
+}
+
+#[tokio::test]
+async fn test_auto_save() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("plm.json");
+
+    let config = ProjectConfig::default_for_project("test-auto-save", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    manager.enable_auto_save(&path, std::time::Duration::from_millis(20));
+
+    let mut plugin_config = PluginConfig::new("debounced");
+    plugin_config.set_version("1.0.0");
+    manager.add_plugin_config(plugin_config);
+    assert!(manager.is_dirty());
+
+    // 防抖结束后写入文件
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let saved = ProjectConfig::load_from_file(path.to_str().unwrap())
+        .await
+        .unwrap();
+    assert!(saved.get_plugin("debounced").is_some());
+    assert!(!manager.is_dirty());
+
+    // shutdown 时写入尚未保存的修改
+    manager.enable_auto_save(&path, std::time::Duration::from_secs(60));
+    manager.remove_plugin_config("debounced");
+    manager.shutdown().await.unwrap();
+    let saved = ProjectConfig::load_from_file(path.to_str().unwrap())
+        .await
+        .unwrap();
+    assert!(saved.get_plugin("debounced").is_none());
+}
+
+#[tokio::test]
+async fn test_lifecycle_events() {
+    use plm::events::PlmEvent;
+    use std::sync::Mutex;
+
+    let config = ProjectConfig::default_for_project("test-events", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&received);
+    manager.on_event(move |event| sink.lock().unwrap().push(event.clone()));
+
+    manager
+        .register_plugin("evented", Box::new(MockPlugin::new("evented")))
+        .await
+        .unwrap();
+    manager
+        .install_plugin("evented", Some("1.0.0"), &InstallOptions::new())
+        .await
+        .unwrap();
+    manager.discover_plugins().await.unwrap();
+    manager.shutdown().await.unwrap();
+
+    let events = received.lock().unwrap();
+    assert_eq!(
+        events[0],
+        PlmEvent::PluginRegistered {
+            plugin: "evented".to_string()
+        }
+    );
+    assert!(matches!(events[1], PlmEvent::InstallStarted { .. }));
+    assert!(matches!(events[2], PlmEvent::InstallCompleted { .. }));
+    assert_eq!(events[3], PlmEvent::DiscoveryFinished { count: 1 });
+    assert_eq!(
+        events[4],
+        PlmEvent::PluginShutdown {
+            plugin: "evented".to_string()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_initialize_respects_dependencies() {
+    let config = ProjectConfig::default_for_project("test-init-order", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+
+    let mut app = MockPlugin::new("app");
+    app.metadata.dependencies = vec!["runtime >=1.0".to_string(), "openssl >=3.0".to_string()];
+    for (name, plugin) in [
+        ("app", app),
+        ("runtime", MockPlugin::new("runtime")),
+        ("linter", MockPlugin::new("linter")),
+    ] {
+        manager
+            .register_plugin(name, Box::new(plugin))
+            .await
+            .unwrap();
+    }
+
+    manager.initialize().await.unwrap();
+    for name in ["app", "runtime", "linter"] {
+        let plugin = manager.get_plugin(name).await.unwrap();
+        assert_eq!(plugin.status(), PluginStatus::Active);
+    }
+
+    // 循环依赖时拒绝初始化
+    let config = ProjectConfig::default_for_project("test-init-cycle", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    let mut first = MockPlugin::new("first");
+    first.metadata.dependencies = vec!["second".to_string()];
+    let mut second = MockPlugin::new("second");
+    second.metadata.dependencies = vec!["first".to_string()];
+    manager
+        .register_plugin("first", Box::new(first))
+        .await
+        .unwrap();
+    manager
+        .register_plugin("second", Box::new(second))
+        .await
+        .unwrap();
+
+    match manager.initialize().await {
+        Err(PluginError::ValidationError(message)) => {
+            assert!(message.contains("first, second"));
+        }
+        other => panic!("expected cycle error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_metadata_warm_start_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = ProjectConfig::default_for_project("test-metadata-cache", ".");
+    config.global_settings.cache_dir = dir.path().to_string_lossy().into_owned();
+    let mut plugin_config = PluginConfig::new("cached-tool");
+    plugin_config.version = Some("1.0.0".to_string());
+    config.add_plugin(plugin_config);
+
+    let mut manager = PluginManager::from_project_config(config.clone())
+        .await
+        .unwrap();
+    manager
+        .register_plugin("cached-tool", Box::new(MockPlugin::new("cached-tool")))
+        .await
+        .unwrap();
+    assert_eq!(manager.refresh_metadata_cache().await.unwrap(), 1);
+
+    // 新的管理器未注册插件实现，直接使用缓存的元数据
+    let manager = PluginManager::from_project_config(config.clone())
+        .await
+        .unwrap();
+    let metadata = manager.plugin_metadata("cached-tool").await.unwrap();
+    assert_eq!(metadata.description, "测试插件 cached-tool");
+    let listed = manager.list_plugin_metadata().await;
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].1.is_some());
+
+    // 配置的版本变化后缓存失效
+    config.get_plugin_mut("cached-tool").unwrap().version = Some("2.0.0".to_string());
+    let manager = PluginManager::from_project_config(config).await.unwrap();
+    assert!(matches!(
+        manager.plugin_metadata("cached-tool").await,
+        Err(PluginError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_config_migration_on_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("plm.json");
+
+    // 版本 1：没有 config_version，插件为数组，插件设置写在 config 中
+    let config = ProjectConfig::default_for_project("legacy", ".");
+    let mut raw = serde_json::to_value(&config).unwrap();
+    raw.as_object_mut().unwrap().remove("config_version");
+    raw["plugins"] = serde_json::json!([{
+        "name": "node",
+        "enabled": true,
+        "version": "18.17.0",
+        "source": null,
+        "auto_update": false,
+        "config": { "registry": "https://registry.npmjs.org" }
+    }]);
+    let original = serde_json::to_string_pretty(&raw).unwrap();
+    std::fs::write(&path, &original).unwrap();
+
+    let loaded = ProjectConfig::load_from_file(path.to_str().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(loaded.config_version, plm::migrate::CURRENT_CONFIG_VERSION);
+    assert_eq!(
+        loaded.get_plugin("node").unwrap().settings["registry"],
+        "https://registry.npmjs.org"
+    );
+
+    // 迁移结果已写回，原文件保留为备份
+    let backup = plm::backup::backup_path(&path, 1);
+    assert_eq!(std::fs::read_to_string(backup).unwrap(), original);
+    let migrated: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert!(!plm::migrate::needs_migration(&migrated));
+}
+
+#[tokio::test]
+async fn test_paged_version_listing() {
+    use futures_util::StreamExt;
+
+    let plugin = MockPlugin::new("paged");
+
+    let first = plugin.list_versions_page(None, 1).await.unwrap();
+    assert_eq!(first.versions.len(), 1);
+    assert_eq!(first.versions[0].version, "1.0.0");
+    let second = plugin
+        .list_versions_page(first.next_cursor.as_deref(), 1)
+        .await
+        .unwrap();
+    assert_eq!(second.versions[0].version, "1.1.0");
+    assert_eq!(second.next_cursor, None);
+    assert!(plugin.list_versions_page(Some("bogus"), 1).await.is_err());
+
+    let versions: Vec<String> = plm::traits::version_stream(&plugin, 1)
+        .map(|v| v.unwrap().version)
+        .collect()
+        .await;
+    assert_eq!(versions, vec!["1.0.0", "1.1.0"]);
+}
+
+#[tokio::test]
+async fn test_update_plugin_honors_constraints() {
+    let mut config = ProjectConfig::default_for_project("test-update", ".");
+    let mut pinned = PluginConfig::new("pinned");
+    pinned.enabled = true;
+    pinned.set_version("1.0.0");
+    config.add_plugin(pinned);
+    let mut ranged = PluginConfig::new("ranged");
+    ranged.enabled = true;
+    ranged.set_version("~1.0");
+    config.add_plugin(ranged);
+
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    for name in ["pinned", "ranged"] {
+        manager
+            .register_plugin(name, Box::new(MockPlugin::new(name)))
+            .await
+            .unwrap();
+    }
+
+    // 固定版本更新到最新版本并写回配置
+    let result = manager.update_plugin("pinned").await.unwrap();
+    assert_eq!(result.previous.as_deref(), Some("1.0.0"));
+    assert_eq!(result.version, "1.1.0");
+    assert!(result.changed());
+    assert_eq!(
+        manager.get_plugin_config("pinned").unwrap().get_version(),
+        Some("1.1.0")
+    );
+    assert!(manager.is_dirty());
+
+    // 范围约束只更新到满足约束的版本，配置保持不变（测试插件只提供 linux 制品）
+    if cfg!(target_os = "linux") {
+        let result = manager.update_plugin("ranged").await.unwrap();
+        assert_eq!(result.constraint.as_deref(), Some("~1.0"));
+        assert_eq!(result.version, "1.0.0");
+        assert_eq!(
+            manager.get_plugin_config("ranged").unwrap().get_version(),
+            Some("~1.0")
+        );
+    }
+
+    let results = manager.update_all_plugins().await;
+    assert_eq!(results.len(), 2);
+}
+
+#[tokio::test]
+async fn test_upgrade_all_plugins_summary() {
+    let mut config = ProjectConfig::default_for_project("test-upgrade", ".");
+    for (name, version, auto_update) in [
+        ("floating", None, true),
+        ("manual", None, false),
+        ("pinned", Some("1.0.0"), true),
+        ("ranged", Some("~1.0"), true),
+        ("unregistered", None, true),
+    ] {
+        let mut plugin = PluginConfig::new(name);
+        plugin.enabled = true;
+        plugin.auto_update = auto_update;
+        if let Some(version) = version {
+            plugin.set_version(version);
+        }
+        config.add_plugin(plugin);
+    }
+
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    for name in ["floating", "manual", "pinned", "ranged"] {
+        manager
+            .register_plugin(name, Box::new(MockPlugin::new(name)))
+            .await
+            .unwrap();
+    }
+
+    let summary = manager.upgrade_all_plugins().await;
+    let skipped: Vec<(&str, String)> = summary
+        .skipped
+        .iter()
+        .map(|(name, reason)| (name.as_str(), reason.to_string()))
+        .collect();
+    assert_eq!(
+        skipped,
+        [
+            ("manual", "auto_update is disabled".to_string()),
+            ("pinned", "pinned to 1.0.0".to_string()),
+            (
+                "unregistered",
+                "no plugin implementation registered".to_string()
+            ),
+        ]
+    );
+
+    // 未设置版本的插件升级到最新版本并写回配置
+    assert_eq!(summary.upgraded.len(), 1);
+    assert_eq!(summary.upgraded[0].plugin, "floating");
+    assert_eq!(summary.upgraded[0].version, "1.1.0");
+    assert_eq!(
+        manager.get_plugin_config("floating").unwrap().get_version(),
+        Some("1.1.0")
+    );
+
+    // 满足约束的最新版本已经安装（测试插件只提供 linux 制品）
+    if cfg!(target_os = "linux") {
+        assert!(summary.is_success());
+        assert_eq!(summary.unchanged.len(), 1);
+        assert_eq!(summary.unchanged[0].plugin, "ranged");
+    }
+}
+
+#[plm::prelude::plm_plugin]
+fn create_exported_plugin() -> MockPlugin {
+    MockPlugin::new("exported")
+}
+
+#[tokio::test]
+async fn test_plm_plugin_entry_point() {
+    let create: plm::sdk::PluginCreateFn = plm_plugin_create;
+    let plugin = create();
+    assert_eq!(plugin.metadata().name, "exported");
+    assert!(plugin.is_installed("1.0.0").await.unwrap());
+
+    let config = ProjectConfig::default_for_project("test-project", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    manager.register_plugin("exported", plugin).await.unwrap();
+    assert!(manager.list_plugins().await.contains(&"exported".to_string()));
+}
+
+#[tokio::test]
+async fn test_dry_run_plans_without_changes() {
+    use plm::traits::ChangeKind;
+
+    let mut config = ProjectConfig::default_for_project("test-dry-run", ".");
+    let mut pinned = PluginConfig::new("pinned");
+    pinned.enabled = true;
+    pinned.set_version("1.0.0");
+    config.add_plugin(pinned);
+
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    manager
+        .register_plugin("pinned", Box::new(MockPlugin::new("pinned")))
+        .await
+        .unwrap();
+    let mut events = manager.subscribe();
+
+    let options = InstallOptions::new().dry_run();
+    let plan = manager
+        .plan_install("pinned", None, &options)
+        .await
+        .unwrap();
+    assert_eq!(plan.kind, ChangeKind::Install);
+    assert_eq!(plan.version, "1.1.0");
+    assert_eq!(
+        plan.download_url.as_deref(),
+        Some("https://test.com/v1.1.0")
+    );
+    assert_eq!(
+        plan.to_string(),
+        "install pinned 1.1.0 (download from https://test.com/v1.1.0)"
+    );
+
+    let plan = manager
+        .plan_install("pinned", Some("1.0.0"), &options)
+        .await
+        .unwrap();
+    assert!(plan.is_noop());
+    assert_eq!(plan.download_url, None);
+
+    // 通过 InstallOptions 预演安装时不调用插件，也不发出安装事件
+    let result = manager
+        .install_plugin("pinned", None, &options)
+        .await
+        .unwrap();
+    assert_eq!(result.version, "1.1.0");
+    assert!(!result.was_cached);
+    assert!(events.try_recv().is_err());
+
+    let plan = manager.plan_uninstall("pinned", "1.0.0").await.unwrap();
+    assert_eq!(plan.to_string(), "uninstall pinned 1.0.0");
+    assert!(manager.plan_uninstall("pinned", "9.9.9").await.is_err());
+
+    let plans = manager.plan_update_all().await;
+    assert_eq!(plans.len(), 1);
+    let plan = plans[0].1.as_ref().unwrap();
+    assert_eq!(plan.kind, ChangeKind::Update);
+    assert_eq!(plan.previous.as_deref(), Some("1.0.0"));
+    assert_eq!(plan.version, "1.1.0");
+    assert_eq!(
+        manager.get_plugin_config("pinned").unwrap().get_version(),
+        Some("1.0.0")
+    );
+    assert!(!manager.is_dirty());
+}
+
+#[tokio::test]
+async fn test_state_store_tracks_lifecycle() {
+    use plm::state::PendingChange;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = ProjectConfig::default_for_project("test-state", ".");
+    config.global_settings.plugin_dir = dir.path().to_string_lossy().into_owned();
+    let mut tool = PluginConfig::new("tool");
+    tool.enabled = true;
+    tool.set_version("1.1.0");
+    config.add_plugin(tool);
+
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    manager
+        .register_plugin("tool", Box::new(MockPlugin::new("tool")))
+        .await
+        .unwrap();
+    assert_eq!(
+        manager
+            .state()
+            .await
+            .unwrap()
+            .pending_changes(manager.get_config()),
+        [PendingChange::NotInstalled {
+            plugin: "tool".to_string(),
+            version: "1.1.0".to_string()
+        }]
+    );
+
+    manager
+        .install_plugin("tool", Some("1.0.0"), &InstallOptions::new())
+        .await
+        .unwrap();
+    let state = manager.state().await.unwrap();
+    let tool = state.get("tool").unwrap();
+    assert_eq!(tool.active_version.as_deref(), Some("1.0.0"));
+    assert!(tool.installed_at.is_some() && tool.updated_at.is_none());
+    assert_eq!(
+        state.pending_changes(manager.get_config()),
+        [PendingChange::VersionChanged {
+            plugin: "tool".to_string(),
+            active: "1.0.0".to_string(),
+            configured: "1.1.0".to_string()
+        }]
+    );
+
+    manager.update_plugin("tool").await.unwrap();
+    let state = manager.state().await.unwrap();
+    assert_eq!(
+        state.get("tool").unwrap().active_version.as_deref(),
+        Some("1.1.0")
+    );
+    assert!(state.pending_changes(manager.get_config()).is_empty());
+
+    manager.uninstall_plugin("tool", "1.1.0").await.unwrap();
+    assert!(manager
+        .state()
+        .await
+        .unwrap()
+        .get("tool")
+        .unwrap()
+        .active_version
+        .is_none());
+
+    // 未指定版本时记录解析出的最新版本，而不是 "latest"
+    let installed = manager
+        .install_plugin("tool", None, &InstallOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(installed.version, "1.1.0");
+    assert_eq!(
+        manager
+            .state()
+            .await
+            .unwrap()
+            .get("tool")
+            .unwrap()
+            .active_version
+            .as_deref(),
+        Some("1.1.0")
+    );
+}
+
+#[tokio::test]
+async fn test_install_many_aggregates_results() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = ProjectConfig::default_for_project("test-install-many", ".");
+    config.global_settings.plugin_dir = dir.path().to_string_lossy().into_owned();
+    config.global_settings.parallel_downloads = 2;
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+
+    let mut app = MockPlugin::new("app");
+    app.metadata.dependencies = vec!["runtime >=1.0".to_string()];
+    for (name, plugin) in [
+        ("app", app),
+        ("runtime", MockPlugin::new("runtime")),
+        ("linter", MockPlugin::new("linter")),
+    ] {
+        manager
+            .register_plugin(name, Box::new(plugin))
+            .await
+            .unwrap();
+    }
+
+    let report = manager
+        .install_many(
+            &[
+                ("app", None),
+                ("missing", Some("1.0.0")),
+                ("runtime", Some("1.0.0")),
+                ("linter", Some("1.1.0")),
+            ],
+            &InstallOptions::new(),
+        )
+        .await;
+
+    assert!(!report.is_success());
+    let installed: Vec<(&str, &str)> = report
+        .installed
+        .iter()
+        .map(|r| (r.plugin.as_str(), r.version.as_str()))
+        .collect();
+    assert_eq!(
+        installed,
+        [("app", "1.1.0"), ("runtime", "1.0.0"), ("linter", "1.1.0")]
+    );
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "missing");
+
+    // 并发安装的记录都保存在运行状态中
+    let state = manager.state().await.unwrap();
+    for (name, version) in installed {
+        assert!(
+            state.installed_version(name, version).is_some(),
+            "{} {}",
+            name,
+            version
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_unknown_builtin_plugin_is_marked_failed() {
+    let mut config = ProjectConfig::default_for_project("test-unknown-builtin", ".");
+    let mut plugin = PluginConfig::new("nodejs");
+    plugin.enabled = true;
+    plugin.set_source(PluginSource::builtin("nodejs"));
+    config.add_plugin(plugin);
+
+    // 配置中拼错的内置插件不影响管理器创建，插件记为错误状态
+    let manager = PluginManager::from_project_config(config).await.unwrap();
+    let lifecycle = manager.plugin_state("nodejs").unwrap();
+    assert_eq!(lifecycle.state, LifecycleState::Error);
+    assert!(lifecycle.last_error.unwrap().contains("nodejs"));
+    assert!(manager.get_plugin("nodejs").await.is_err());
+}
+
+#[tokio::test]
+async fn test_manager_tracks_plugin_lifecycle() {
+    let config = ProjectConfig::default_for_project("test-plugin-state", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+
+    let mut busy = MockPlugin::new("busy");
+    busy.fail_initialize = true;
+    let mut dependent = MockPlugin::new("dependent");
+    dependent.metadata.dependencies = vec!["busy".to_string()];
+    manager
+        .register_plugin("busy", Box::new(busy))
+        .await
+        .unwrap();
+    manager
+        .register_plugin("dependent", Box::new(dependent))
+        .await
+        .unwrap();
+    manager
+        .register_plugin("healthy", Box::new(MockPlugin::new("healthy")))
+        .await
+        .unwrap();
+
+    let registered = manager.plugin_state("healthy").unwrap();
+    assert_eq!(registered.state, LifecycleState::Registered);
+    assert!(manager.plugin_state("unknown").is_none());
+
+    assert!(manager.initialize().await.is_err());
+    let healthy = manager.plugin_state("healthy").unwrap();
+    assert_eq!(healthy.state, LifecycleState::Active);
+    assert!(healthy.changed_at >= registered.changed_at);
+    assert!(healthy.last_error.is_none());
+
+    let failed = manager.plugin_state("busy").unwrap();
+    assert_eq!(failed.state, LifecycleState::Error);
+    assert!(failed.last_error.unwrap().contains("busy"));
+    let skipped = manager.plugin_state("dependent").unwrap();
+    assert_eq!(skipped.state, LifecycleState::Error);
+    assert!(skipped.last_error.unwrap().contains("busy"));
+
+    assert!(!LifecycleState::Registered.can_transition_to(LifecycleState::Active));
+    manager.shutdown().await.unwrap();
+    assert!(manager.plugin_state("healthy").is_none());
+}
+
+#[tokio::test]
+async fn test_initialize_continues_on_error() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut config = ProjectConfig::default_for_project("test-init-report", ".");
+    config.global_settings.plugin_dir = temp_dir.path().to_string_lossy().into_owned();
+    config.global_settings.continue_on_init_error = true;
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+
+    let mut busy = MockPlugin::new("busy");
+    busy.fail_initialize = true;
+    let mut dependent = MockPlugin::new("dependent");
+    dependent.metadata.dependencies = vec!["busy".to_string()];
+    for (name, plugin) in [
+        ("busy", busy),
+        ("dependent", dependent),
+        ("healthy", MockPlugin::new("healthy")),
+    ] {
+        manager.register_plugin(name, Box::new(plugin)).await.unwrap();
+    }
+
+    let report = manager.initialize_all().await.unwrap();
+    assert!(!report.is_success());
+    assert_eq!(report.initialized, ["healthy"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "busy");
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].0, "dependent");
+    assert!(report.error_message().contains("busy"));
+
+    // 开启 continue_on_init_error 时 initialize 不返回错误，其他插件照常使用
+    manager.initialize().await.unwrap();
+    assert_eq!(
+        manager.plugin_state("busy").unwrap().state,
+        LifecycleState::Error
+    );
+    manager
+        .install_plugin("healthy", Some("1.1.0"), &InstallOptions::new())
+        .await
+        .unwrap();
+
+    let mut config = manager.get_config().clone();
+    config.global_settings.continue_on_init_error = false;
+    manager.update_config(config);
+    assert!(manager.initialize().await.is_err());
+}
+
+#[tokio::test]
+async fn test_manager_reload_applies_config_changes() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut config = ProjectConfig::default_for_project("test-reload", ".");
+    config.global_settings.plugin_dir = temp_dir.path().to_string_lossy().into_owned();
+    for name in ["removed", "host"] {
+        let mut plugin_config = PluginConfig::new(name);
+        plugin_config.enabled = true;
+        config.add_plugin(plugin_config);
+    }
+
+    let mut manager = PluginManager::from_project_config(config.clone())
+        .await
+        .unwrap();
+    for name in ["removed", "host"] {
+        manager
+            .register_plugin(name, Box::new(MockPlugin::new(name)))
+            .await
+            .unwrap();
+    }
+    manager.initialize().await.unwrap();
+    let mut config_rx = manager.watch_config();
+
+    let mut new_config = config.clone();
+    new_config.remove_plugin("removed");
+    new_config
+        .get_plugin_mut("host")
+        .unwrap()
+        .set_setting("debug", serde_json::Value::Bool(true));
+    let mut terraform = PluginConfig::new("terraform");
+    terraform.enabled = true;
+    terraform.set_source(PluginSource::builtin("terraform"));
+    new_config.add_plugin(terraform);
+
+    let report = manager.reload(new_config.clone()).await.unwrap();
+    assert_eq!(report.added, ["terraform"]);
+    assert_eq!(report.removed, ["removed"]);
+    assert_eq!(report.reloaded, ["host"]);
+    assert!(report.is_success());
+
+    assert!(manager.plugin_state("removed").is_none());
+    assert!(manager.get_plugin("removed").await.is_err());
+    assert_eq!(
+        manager.plugin_state("terraform").unwrap().state,
+        LifecycleState::Active
+    );
+    assert!(config_rx.has_changed().unwrap());
+    assert!(config_rx
+        .borrow_and_update()
+        .get_plugin("terraform")
+        .is_some());
+    assert!(!manager.is_dirty());
+
+    // 再次应用相同配置没有变化，无效配置不做任何修改
+    assert!(manager.reload(new_config.clone()).await.unwrap().is_noop());
+    let mut invalid = new_config;
+    invalid.project_name.clear();
+    assert!(manager.reload(invalid).await.is_err());
+    assert_eq!(manager.get_config().project_name, "test-reload");
+
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_manager_shutdown_reports_failures_and_timeouts() {
+    let mut config = ProjectConfig::default_for_project("test-shutdown", ".");
+    config.global_settings.shutdown_timeout = 1;
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+
+    let mut slow = MockPlugin::new("slow");
+    slow.shutdown_delay = Some(std::time::Duration::from_secs(60));
+    let mut busy = MockPlugin::new("busy");
+    busy.fail_shutdown = true;
+    manager
+        .register_plugin("slow", Box::new(slow))
+        .await
+        .unwrap();
+    manager
+        .register_plugin("busy", Box::new(busy))
+        .await
+        .unwrap();
+    for name in ["a", "b"] {
+        manager
+            .register_plugin(name, Box::new(MockPlugin::new(name)))
+            .await
+            .unwrap();
+    }
+
+    let started = std::time::Instant::now();
+    let report = manager.shutdown().await.unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(30));
+    assert_eq!(report.stopped, ["a", "b"]);
+    assert_eq!(report.timed_out, ["slow"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "busy");
+    assert!(!report.is_success());
+    assert!(manager.plugin_state("slow").is_none());
+}
+
+#[tokio::test]
+async fn test_lifecycle_with_outstanding_plugin_handles() {
+    let config = ProjectConfig::default_for_project("test-plugin-handles", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    manager
+        .register_plugin("tool", Box::new(MockPlugin::new("tool")))
+        .await
+        .unwrap();
+
+    // 通过 get_plugin 取得的引用不影响初始化和关闭
+    let handle = manager.get_plugin("tool").await.unwrap();
+    assert!(matches!(handle.status(), PluginStatus::Inactive));
+    let report = manager.initialize_all().await.unwrap();
+    assert!(report.is_success());
+    assert!(matches!(handle.status(), PluginStatus::Active));
+    assert!(handle.is_installed("1.0.0").await.is_ok());
+
+    let report = manager.shutdown().await.unwrap();
+    assert!(report.is_success());
+    assert_eq!(report.stopped, ["tool"]);
+    assert!(matches!(handle.status(), PluginStatus::Inactive));
+}
+
+/// 按 `backend: "mock"` 创建模拟插件的工厂
+struct MockFactory;
+
+#[async_trait]
+impl PluginFactory for MockFactory {
+    async fn create_plugin(&self, config: &PluginConfig) -> Result<Box<dyn Plugin>, PluginError> {
+        let mut plugin = MockPlugin::new(&config.name);
+        plugin.metadata.description = "created by factory".to_string();
+        Ok(Box::new(plugin))
+    }
+
+    fn supported_types(&self) -> Vec<String> {
+        vec!["mock".to_string()]
+    }
+
+    fn validate_config(&self, config: &PluginConfig) -> Result<(), PluginError> {
+        match config.get_setting("invalid") {
+            Some(_) => Err(PluginError::ValidationError(format!(
+                "{} 的配置无效",
+                config.name
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 从 Git 插件源加载模拟插件的加载器
+struct MockLoader;
+
+#[async_trait]
+impl PluginLoader for MockLoader {
+    async fn load_plugin(&self, source: &PluginSource) -> Result<Box<dyn Plugin>, PluginError> {
+        let mut plugin = MockPlugin::new("loaded");
+        plugin.metadata.description = format!("loaded from {}", source.url);
+        Ok(Box::new(plugin))
+    }
+
+    fn supports_source(&self, source_type: &PluginSourceType) -> bool {
+        matches!(source_type, PluginSourceType::Git)
+    }
+
+    async fn validate_source(&self, _source: &PluginSource) -> Result<(), PluginError> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_manager_builder() {
+    let mut config = ProjectConfig::default_for_project("test-builder", ".");
+    let mut factory_config = PluginConfig::new("from-factory");
+    factory_config.enabled = true;
+    factory_config.set_setting("backend", serde_json::json!("mock"));
+    config.add_plugin(factory_config);
+    let mut loader_config = PluginConfig::new("from-loader");
+    loader_config.enabled = true;
+    loader_config.set_source(PluginSource::git_simple("https://github.com/acme/tool.git"));
+    config.add_plugin(loader_config);
+    let mut disabled = PluginConfig::new("disabled");
+    disabled.set_setting("backend", serde_json::json!("mock"));
+    config.add_plugin(disabled);
+
+    let mut manager = PluginManager::builder()
+        .with_config(config)
+        .with_factory(MockFactory)
+        .with_loader(MockLoader)
+        .register(MockPlugin::new("explicit"))
+        .build()
+        .await
+        .unwrap();
+
+    let mut ids = manager.list_plugins().await;
+    ids.sort();
+    assert_eq!(ids, ["explicit", "from-factory", "from-loader"]);
+    assert_eq!(
+        manager
+            .get_plugin("from-factory")
+            .await
+            .unwrap()
+            .metadata()
+            .description,
+        "created by factory"
+    );
+    assert_eq!(
+        manager
+            .get_plugin("from-loader")
+            .await
+            .unwrap()
+            .metadata()
+            .description,
+        "loaded from https://github.com/acme/tool.git"
+    );
+    assert_eq!(
+        manager.plugin_state("explicit").unwrap().state,
+        LifecycleState::Registered
+    );
+
+    manager.initialize().await.unwrap();
+    assert_eq!(
+        manager.plugin_state("from-loader").unwrap().state,
+        LifecycleState::Active
+    );
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_manager_builder_validates_plugins() {
+    // 重复注册同名插件
+    let duplicate = PluginManager::builder()
+        .register(MockPlugin::new("explicit"))
+        .register(MockPlugin::new("explicit"))
+        .build()
+        .await;
+    assert!(matches!(duplicate, Err(PluginError::ValidationError(_))));
+
+    // 工厂创建插件前校验配置
+    let mut config = ProjectConfig::default_for_project("test-builder", ".");
+    let mut invalid = PluginConfig::new("from-factory");
+    invalid.enabled = true;
+    invalid.set_setting("backend", serde_json::json!("mock"));
+    invalid.set_setting("invalid", serde_json::json!(true));
+    config.add_plugin(invalid);
+    let result = PluginManager::builder()
+        .with_config(config)
+        .with_factory(MockFactory)
+        .build()
+        .await;
+    assert!(matches!(result, Err(PluginError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_register_plugin_and_factory() {
+    let mut config = ProjectConfig::default_for_project("test-register", ".");
+    let mut linked = PluginConfig::new("linked");
+    linked.enabled = true;
+    linked.set_setting("channel", serde_json::json!("beta"));
+    linked.set_setting("retries", serde_json::json!(3));
+    config.add_plugin(linked);
+    let mut from_factory = PluginConfig::new("from-factory");
+    from_factory.enabled = true;
+    from_factory.set_setting("backend", serde_json::json!("mock"));
+    config.add_plugin(from_factory);
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+
+    // 注册时把配置中的插件设置传给插件
+    manager
+        .register_plugin("linked", Box::new(MockPlugin::new("linked")))
+        .await
+        .unwrap();
+    let settings = manager
+        .get_plugin("linked")
+        .await
+        .unwrap()
+        .get_config()
+        .await
+        .unwrap();
+    assert_eq!(settings["channel"], "beta");
+    assert_eq!(settings["retries"], "3");
+
+    // 同一标识不能重复注册
+    let error = manager
+        .register_plugin("linked", Box::new(MockPlugin::new("linked")))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, PluginError::ValidationError(_)));
+
+    // 工厂为配置中匹配的插件创建实例，并在热加载时用于新配置的插件
+    assert_eq!(
+        manager.register_factory(MockFactory).await.unwrap(),
+        ["from-factory"]
+    );
+    assert_eq!(
+        manager.plugin_state("from-factory").unwrap().state,
+        LifecycleState::Registered
+    );
+    let mut config = manager.get_config().clone();
+    let mut added = PluginConfig::new("added");
+    added.enabled = true;
+    added.set_setting("backend", serde_json::json!("mock"));
+    config.add_plugin(added);
+    let report = manager.reload(config).await.unwrap();
+    assert_eq!(report.added, ["added"]);
+    assert_eq!(
+        manager
+            .get_plugin("added")
+            .await
+            .unwrap()
+            .metadata()
+            .description,
+        "created by factory"
+    );
+}
+
+#[tokio::test]
+async fn test_initialize_creates_plugins_from_factories() {
+    let config = ProjectConfig::default_for_project("test-factory-init", ".");
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    assert!(manager
+        .register_factory(MockFactory)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // 工厂添加之后才出现在配置中的插件在初始化时创建
+    for name in ["created", "invalid", "disabled", "other"] {
+        let mut plugin_config = PluginConfig::new(name);
+        plugin_config.enabled = name != "disabled";
+        let backend = if name == "other" { "unknown" } else { "mock" };
+        plugin_config.set_setting("backend", serde_json::json!(backend));
+        if name == "invalid" {
+            plugin_config.set_setting("invalid", serde_json::json!(true));
+        }
+        manager.add_plugin_config(plugin_config);
+    }
+
+    let report = manager.initialize_all().await.unwrap();
+    assert_eq!(report.initialized, ["created"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "invalid");
+    assert_eq!(manager.list_plugins().await, ["created"]);
+    assert_eq!(
+        manager.plugin_state("created").unwrap().state,
+        LifecycleState::Active
+    );
+    assert_eq!(
+        manager
+            .get_plugin("created")
+            .await
+            .unwrap()
+            .metadata()
+            .description,
+        "created by factory"
+    );
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_manager_removes_recorded_installations() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let plugin_dir = temp_dir.path().join("plugins");
+    let mut config = ProjectConfig::default_for_project("test-state", ".");
+    config.global_settings.plugin_dir = plugin_dir.to_string_lossy().into_owned();
+    let mut kept = PluginConfig::new("kept");
+    kept.enabled = true;
+    config.add_plugin(kept);
+
+    // 之前运行时安装、现在已不在配置中的插件
+    let mut state = plm::state::StateStore::default();
+    for (name, version) in [
+        ("old", "1.0.0"),
+        ("old", "2.0.0"),
+        ("gone", "0.3.0"),
+        ("kept", "1.0.0"),
+    ] {
+        let path = plugin_dir.join(name).join(version);
+        std::fs::create_dir_all(&path).unwrap();
+        state.record_install(name, version, Some(&path));
+    }
+    state
+        .save(&plugin_dir.join(plm::state::STATE_FILE))
+        .await
+        .unwrap();
+
+    let manager = PluginManager::from_project_config(config).await.unwrap();
+    manager.uninstall_plugin("old", "1.0.0").await.unwrap();
+    assert!(!plugin_dir.join("old/1.0.0").exists());
+    assert!(matches!(
+        manager.uninstall_plugin("old", "1.0.0").await,
+        Err(PluginError::NotFound(_))
+    ));
+
+    let planned = manager.prune_orphans(true).await.unwrap();
+    let planned: Vec<(&str, &str)> = planned
+        .iter()
+        .map(|orphan| (orphan.plugin.as_str(), orphan.version.as_str()))
+        .collect();
+    assert_eq!(planned, [("gone", "0.3.0"), ("old", "2.0.0")]);
+    assert!(plugin_dir.join("old/2.0.0").exists());
+
+    assert_eq!(manager.prune_orphans(false).await.unwrap().len(), 2);
+    assert!(!plugin_dir.join("old/2.0.0").exists());
+    assert!(!plugin_dir.join("gone/0.3.0").exists());
+    assert!(plugin_dir.join("kept/1.0.0").exists());
+    let state = manager.state().await.unwrap();
+    assert!(state.installed_versions("old").is_empty());
+    assert_eq!(state.installed_versions("kept"), ["1.0.0"]);
+}
+
+#[tokio::test]
+async fn test_manager_creates_and_removes_launchers() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let launcher_dir = temp_dir.path().join("applications");
+    let mut config = ProjectConfig::default_for_project("test-launchers", ".");
+    config.global_settings.plugin_dir = temp_dir.path().join("plugins").to_string_lossy().into();
+    config.global_settings.launcher_dir = Some(launcher_dir.to_string_lossy().into_owned());
+
+    let mut plugin = MockPlugin::new("studio");
+    plugin.launchers = vec![LauncherEntry::new("studio", "Studio", "bin/studio")];
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    manager
+        .register_plugin("studio", Box::new(plugin))
+        .await
+        .unwrap();
+
+    let installed = manager
+        .install_plugin("studio", Some("2.0.0"), &InstallOptions::new())
+        .await
+        .unwrap();
+    assert!(installed.warnings.is_empty(), "{:?}", installed.warnings);
+    let state = manager.state().await.unwrap();
+    let launchers = &state.installed_version("studio", "2.0.0").unwrap().launchers;
+    assert_eq!(launchers.len(), 1);
+    assert!(launchers[0].starts_with(&launcher_dir));
+    let content = std::fs::read_to_string(&launchers[0]).unwrap();
+    assert!(content.contains("Name=Studio") || content.contains("URL=file:"));
+
+    manager.uninstall_plugin("studio", "2.0.0").await.unwrap();
+    assert!(!launchers[0].exists());
+
+    // 关闭集成后不再生成启动器
+    let mut config = manager.get_config().clone();
+    config.global_settings.launcher_integration = false;
+    manager.update_config(config);
+    manager
+        .install_plugin("studio", Some("2.0.0"), &InstallOptions::new())
+        .await
+        .unwrap();
+    assert!(manager
+        .state()
+        .await
+        .unwrap()
+        .installed_version("studio", "2.0.0")
+        .unwrap()
+        .launchers
+        .is_empty());
+    assert_eq!(std::fs::read_dir(&launcher_dir).unwrap().count(), 0);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_manager_writes_wrapper_shims() {
+    use plm::config::WrapperConfig;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let install_root = temp_dir.path().join("kubectl");
+    for version in ["1.29.0", "1.30.0"] {
+        std::fs::create_dir_all(install_root.join(version).join("bin")).unwrap();
+        std::fs::write(install_root.join(version).join("bin/kubectl"), "").unwrap();
+    }
+    let mut config = ProjectConfig::default_for_project("test-wrappers", ".");
+    config.global_settings.plugin_dir = temp_dir.path().join("plugins").to_string_lossy().into();
+    config.global_settings.shims_dir = temp_dir.path().join("shims").to_string_lossy().into();
+    let mut kubectl = PluginConfig::new("kubectl");
+    kubectl.enabled = true;
+    kubectl.wrappers.insert(
+        "kubectl".to_string(),
+        WrapperConfig {
+            args: vec!["--context".to_string(), "dev".to_string()],
+            ..WrapperConfig::default()
+        },
+    );
+    kubectl
+        .wrappers
+        .insert("missing".to_string(), WrapperConfig::default());
+    config.add_plugin(kubectl);
+
+    let mut plugin = MockPlugin::new("kubectl");
+    plugin.install_root = Some(install_root.clone());
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    manager
+        .register_plugin("kubectl", Box::new(plugin))
+        .await
+        .unwrap();
+
+    let installed = manager
+        .install_plugin("kubectl", Some("1.29.0"), &InstallOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(installed.warnings.len(), 1);
+    assert!(installed.warnings[0].contains("missing"));
+    let shim = temp_dir.path().join("shims/kubectl");
+    let script = std::fs::read_to_string(&shim).unwrap();
+    assert!(script.contains("1.29.0/bin/kubectl' '--context' 'dev' \"$@\""));
+
+    // 新版本覆盖同名脚本，卸载旧版本不删除
+    manager
+        .install_plugin("kubectl", Some("1.30.0"), &InstallOptions::new())
+        .await
+        .unwrap();
+    let state = manager.state().await.unwrap();
+    assert!(state
+        .installed_version("kubectl", "1.29.0")
+        .unwrap()
+        .shims
+        .is_empty());
+    assert_eq!(
+        state.installed_version("kubectl", "1.30.0").unwrap().shims,
+        std::slice::from_ref(&shim)
+    );
+    manager.uninstall_plugin("kubectl", "1.29.0").await.unwrap();
+    assert!(std::fs::read_to_string(&shim).unwrap().contains("1.30.0"));
+    manager.uninstall_plugin("kubectl", "1.30.0").await.unwrap();
+    assert!(!shim.exists());
+}
+
+#[tokio::test]
+async fn test_install_for_other_platform() {
+    use plm::target::TargetPlatform;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let sysroot = temp_dir.path().join("sysroot");
+    let mut config = ProjectConfig::default_for_project("test-cross-install", ".");
+    config.global_settings.plugin_dir = temp_dir.path().join("plugins").to_string_lossy().into();
+    let mut zig = PluginConfig::new("zig");
+    zig.enabled = true;
+    zig.set_version("0.11.0");
+    config.add_plugin(zig);
+
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    let mut plugin = MockPlugin::new("zig");
+    plugin.cross_install = true;
+    manager
+        .register_plugin("zig", Box::new(plugin))
+        .await
+        .unwrap();
+    manager
+        .register_plugin("legacy", Box::new(MockPlugin::new("legacy")))
+        .await
+        .unwrap();
+
+    let target: TargetPlatform = if TargetPlatform::host().os == "windows" {
+        "linux-arm64"
+    } else {
+        "windows-arm64"
+    }
+    .parse()
+    .unwrap();
+    let options = InstallOptions::new().platform(target.clone());
+    // 不支持跨平台安装的插件和未指定安装目录时报错
+    let error = manager
+        .install_plugin("legacy", Some("1.0.0"), &options)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("legacy"));
+    assert!(manager
+        .install_plugin("zig", Some("0.11.0"), &options)
+        .await
+        .is_err());
+
+    let options = options.install_dir(&sysroot.to_string_lossy());
+    let installed = manager
+        .install_plugin("zig", Some("0.11.0"), &options)
+        .await
+        .unwrap();
+    assert_eq!(installed.path, sysroot.join("zig/0.11.0"));
+    let state = manager.state().await.unwrap();
+    assert_eq!(
+        state
+            .target_version("zig", &target, "0.11.0")
+            .unwrap()
+            .path
+            .as_deref(),
+        Some(sysroot.join("zig/0.11.0").as_path())
+    );
+    // 为其他平台安装不改变当前使用的版本
+    assert!(state.get("zig").unwrap().active_version.is_none());
+    assert!(state.installed_versions("zig").is_empty());
+
+    // 同步按为该平台安装的记录判断，安装目录存在时跳过
+    let sync_options = InstallOptions::new()
+        .platform(target)
+        .install_dir(&sysroot.to_string_lossy())
+        .quiet();
+    assert_eq!(
+        manager.sync_plugins(&sync_options).await.unwrap(),
+        ["zig@0.11.0"]
+    );
+    std::fs::create_dir_all(sysroot.join("zig/0.11.0")).unwrap();
+    assert!(manager.sync_plugins(&sync_options).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_lazy_shims_install_on_first_run() {
+    use plm::config::WrapperConfig;
+    use plm::shims::LazyInstall;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let install_root = temp_dir.path().join("kubectl");
+    std::fs::create_dir_all(install_root.join("1.30.0/bin")).unwrap();
+    std::fs::write(install_root.join("1.30.0/bin/kubectl"), "").unwrap();
+    let mut config = ProjectConfig::default_for_project("test-lazy-shims", ".");
+    config.global_settings.plugin_dir = temp_dir.path().join("plugins").to_string_lossy().into();
+    config.global_settings.shims_dir = temp_dir.path().join("shims").to_string_lossy().into();
+    let mut kubectl = PluginConfig::new("kubectl");
+    kubectl.enabled = true;
+    kubectl.set_version("1.30.0");
+    kubectl.wrappers.insert(
+        "kubectl".to_string(),
+        WrapperConfig {
+            args: vec!["--context".to_string(), "dev".to_string()],
+            ..WrapperConfig::default()
+        },
+    );
+    config.add_plugin(kubectl);
+
+    let mut plugin = MockPlugin::new("kubectl");
+    plugin.install_root = Some(install_root.clone());
+    let mut manager = PluginManager::from_project_config(config.clone())
+        .await
+        .unwrap();
+    manager
+        .register_plugin("kubectl", Box::new(plugin))
+        .await
+        .unwrap();
+
+    let deferred = manager
+        .sync_plugins_lazy(
+            std::path::Path::new("/usr/bin/plm"),
+            std::path::Path::new("/work/plm.json"),
+            true,
+        )
+        .await
+        .unwrap();
+    assert_eq!(deferred, ["kubectl@1.30.0"]);
+    let shim = temp_dir.path().join("shims/kubectl");
+    let script = std::fs::read_to_string(&shim).unwrap();
+    assert!(script.contains("'shim-exec' 'kubectl' 'kubectl' \"$@\""));
+    assert!(manager.state().await.unwrap().installed_versions("kubectl").is_empty());
+
+    // prompt 策略下拒绝安装时返回错误，不安装
+    let mut asked = None;
+    let error = manager
+        .prepare_lazy_shim("kubectl", "kubectl", |spec| {
+            asked = Some(spec.to_string());
+            false
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(asked.as_deref(), Some("kubectl@1.30.0"));
+    assert!(error.to_string().contains("plm sync"));
+    assert!(manager.state().await.unwrap().installed_versions("kubectl").is_empty());
+
+    // auto 策略直接安装，安装生成的包装脚本替换按需安装脚本
+    let mut auto = config;
+    auto.global_settings.lazy_install = LazyInstall::Auto;
+    manager.update_config(auto);
+    let (target, wrapper) = manager
+        .prepare_lazy_shim("kubectl", "kubectl", |_| panic!("auto 策略不询问"))
+        .await
+        .unwrap();
+    assert_eq!(target, install_root.join("1.30.0/bin/kubectl"));
+    assert_eq!(wrapper.args, ["--context", "dev"]);
+    assert_eq!(
+        manager.state().await.unwrap().installed_versions("kubectl"),
+        ["1.30.0"]
+    );
+    let script = std::fs::read_to_string(&shim).unwrap();
+    assert!(script.contains("1.30.0/bin/kubectl' '--context' 'dev' \"$@\""));
+}
+
+/// 只实现必需方法的插件
+struct MinimalPlugin;
+
+#[async_trait]
+impl Plugin for MinimalPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        let mut metadata = MockPlugin::new("minimal").metadata;
+        metadata.description = "最小插件".to_string();
+        metadata
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        _options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        Ok(format!("/tmp/minimal-{}", version))
+    }
+}
+
+#[tokio::test]
+async fn test_plugin_default_methods() {
+    let mut config = ProjectConfig::default_for_project("test-project", ".");
+    let mut minimal = PluginConfig::new("minimal");
+    minimal.enabled = true;
+    minimal.set_setting("registry", serde_json::json!("https://example.com"));
+    config.add_plugin(minimal);
+
+    // 插件不支持配置时，初始化忽略插件设置
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    manager
+        .register_plugin("minimal", Box::new(MinimalPlugin))
+        .await
+        .unwrap();
+    manager.initialize().await.unwrap();
+
+    let plugin = manager.get_plugin("minimal").await.unwrap();
+    assert_eq!(plugin.status(), PluginStatus::Active);
+    assert_eq!(plugin.capabilities(), HashSet::from([Capability::Install]));
+    assert!(plugin.supports_feature("install"));
+    assert!(!plugin.supports_feature("uninstall"));
+
+    // 只实现 supports_feature 的插件，能力由声明的功能名推导
+    let mut mock = MockPlugin::new("mock");
+    mock.cross_install = true;
+    assert_eq!(
+        mock.capabilities(),
+        HashSet::from([
+            Capability::Install,
+            Capability::Uninstall,
+            Capability::Update,
+            Capability::Config,
+            Capability::CrossInstall,
+        ])
+    );
+    assert!(mock.supports(Capability::Update));
+
+    // 不支持的操作在调用插件之前被拒绝
+    assert!(matches!(
+        manager.uninstall_plugin("minimal", "1.0.0").await,
+        Err(PluginError::Unsupported(_))
+    ));
+    assert!(plugin.list_installed().await.unwrap().is_empty());
+    assert!(!plugin.is_installed("1.0.0").await.unwrap());
+    assert!(plugin.get_config().await.unwrap().is_empty());
+    assert_eq!(plugin.get_config_value("registry").await.unwrap(), None);
+    assert!(plugin.cleanup().await.is_ok());
+    assert!(plugin.get_help().contains("最小插件"));
+    assert!(matches!(
+        plugin.execute_command("status", &[]).await,
+        Err(PluginError::Unsupported(_))
+    ));
+    assert!(matches!(
+        plugin.uninstall("1.0.0").await,
+        Err(PluginError::Unsupported(_))
+    ));
+    assert!(matches!(
+        plugin.get_latest_version().await,
+        Err(PluginError::Unsupported(_))
+    ));
+}
+
+/// 接收强类型设置的插件
+#[derive(Default)]
+struct TypedPlugin {
+    settings: Arc<std::sync::Mutex<Option<TypedSettings>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+struct TypedSettings {
+    channel: String,
+    #[serde(default)]
+    retries: u32,
+}
+
+#[async_trait]
+impl Plugin for TypedPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        MockPlugin::new("typed").metadata
+    }
+
+    async fn install(
+        &self,
+        version: &str,
+        _options: &InstallOptions,
+    ) -> Result<String, PluginError> {
+        Ok(format!("/tmp/typed-{}", version))
+    }
+
+    async fn configure(&self, settings: serde_json::Value) -> Result<(), PluginError> {
+        let settings = plm::config::settings_from_value(settings)?;
+        *self.settings.lock().unwrap() = Some(settings);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_plugin_configure_typed_settings() {
+    let mut config = ProjectConfig::default_for_project("test-project", ".");
+    let mut typed = PluginConfig::new("typed");
+    typed.enabled = true;
+    typed.set_setting("channel", serde_json::json!("beta"));
+    typed.set_setting("retries", serde_json::json!(3));
+    config.add_plugin(typed.clone());
+
+    let mut manager = PluginManager::from_project_config(config.clone())
+        .await
+        .unwrap();
+    let plugin = TypedPlugin::default();
+    let settings = plugin.settings.clone();
+    manager
+        .register_plugin("typed", Box::new(plugin))
+        .await
+        .unwrap();
+    assert_eq!(
+        settings.lock().unwrap().clone(),
+        Some(TypedSettings {
+            channel: "beta".to_string(),
+            retries: 3,
+        })
+    );
+
+    // 设置类型不匹配时注册失败，错误带有字段路径
+    typed.set_setting("retries", serde_json::json!("three"));
+    config.add_plugin(typed);
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    match manager
+        .register_plugin("typed", Box::new(TypedPlugin::default()))
+        .await
+    {
+        Err(PluginError::ConfigError(message)) => assert!(message.starts_with("retries: ")),
+        other => panic!("expected config error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_plugin_permissions() {
+    let dir = tempfile::tempdir().unwrap();
+    let allowed = dir.path().join("tools");
+    let mut config = ProjectConfig::default_for_project("test-project", ".");
+    let mut sandboxed = PluginConfig::new("sandboxed");
+    sandboxed.enabled = true;
+    sandboxed.permissions = Some(plm::permissions::PluginPermissions {
+        filesystem: vec![allowed.to_string_lossy().into_owned()],
+        env: vec!["TOOL_TOKEN".to_string()],
+        ..Default::default()
+    });
+    config.add_plugin(sandboxed);
+
+    let mut manager = PluginManager::from_project_config(config.clone())
+        .await
+        .unwrap();
+    manager
+        .register_plugin("sandboxed", Box::new(MockPlugin::new("sandboxed")))
+        .await
+        .unwrap();
+    manager.initialize().await.unwrap();
+
+    // 安装目录和环境变量不在声明的权限中时拒绝
+    let mut options = InstallOptions::new();
+    options.install_dir = Some(dir.path().join("other").to_string_lossy().into_owned());
+    let error = manager
+        .install_plugin("sandboxed", Some("1.1.0"), &options)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, PluginError::PermissionDenied(_)));
+
+    options.install_dir = Some(allowed.to_string_lossy().into_owned());
+    options
+        .env_vars
+        .insert("AWS_SECRET_ACCESS_KEY".to_string(), "secret".to_string());
+    assert!(matches!(
+        manager
+            .install_plugin("sandboxed", Some("1.1.0"), &options)
+            .await,
+        Err(PluginError::PermissionDenied(_))
+    ));
+
+    options.env_vars.clear();
+    options
+        .env_vars
+        .insert("TOOL_TOKEN".to_string(), "token".to_string());
+    let installed = manager
+        .install_plugin("sandboxed", Some("1.1.0"), &options)
+        .await
+        .unwrap();
+    assert_eq!(installed.path, allowed.join("1.1.0"));
+
+    // 移除权限声明后不再受限制
+    config.get_plugin_mut("sandboxed").unwrap().permissions = None;
+    manager.update_config(config);
+    options.install_dir = Some(dir.path().join("other").to_string_lossy().into_owned());
+    assert!(manager
+        .install_plugin("sandboxed", Some("1.1.0"), &options)
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn test_min_plm_version_compat() {
+    let future = || {
+        let mut plugin = MockPlugin::new("future");
+        plugin.metadata.min_plm_version = Some("99.0.0".to_string());
+        Box::new(plugin)
+    };
+
+    let mut manager = PluginManager::new().await.unwrap();
+    match manager.register_plugin("future", future()).await {
+        Err(PluginError::ValidationError(message)) => {
+            assert!(message.contains("PLM 99.0.0"));
+            assert!(message.contains("--ignore-compat"));
+        }
+        other => panic!("expected validation error, got {:?}", other.err()),
+    }
+    assert!(manager.get_plugin("future").await.is_err());
+
+    let mut config = ProjectConfig::default_for_project("test-project", ".");
+    config.global_settings.ignore_compat = true;
+    let mut manager = PluginManager::from_project_config(config).await.unwrap();
+    manager.register_plugin("future", future()).await.unwrap();
+    assert!(manager.get_plugin("future").await.is_ok());
+}